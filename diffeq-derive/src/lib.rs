@@ -0,0 +1,107 @@
+//! Derive macros for the [`diffeq`](https://docs.rs/diffeq) crate.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index, Member};
+
+/// Derives `OdeType` for a struct whose fields all have the same scalar type, e.g.
+///
+/// ```ignore
+/// #[derive(Debug, Clone, OdeType)]
+/// struct State {
+///     x: f64,
+///     v: f64,
+/// }
+/// ```
+///
+/// The fields are the components of the state in their declaration order, so the RHS
+/// keeps its named field access. Tuple structs are supported the same way.
+#[proc_macro_derive(OdeType)]
+pub fn derive_ode_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "OdeType can only be derived for structs",
+            ))
+        }
+    };
+    let fields: Vec<_> = match fields {
+        Fields::Named(named) => named.named.iter().collect(),
+        Fields::Unnamed(unnamed) => unnamed.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let item = match fields.first() {
+        Some(field) => &field.ty,
+        None => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "OdeType can't be derived for a struct without fields",
+            ))
+        }
+    };
+    let item_str = quote!(#item).to_string();
+    if let Some(field) = fields.iter().find(|field| {
+        let ty = &field.ty;
+        quote!(#ty).to_string() != item_str
+    }) {
+        return Err(Error::new_spanned(
+            &field.ty,
+            format!("all fields of an OdeType must be of type `{}`", item_str),
+        ));
+    }
+
+    let members: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        })
+        .collect();
+    let indices = 0..members.len();
+    let indices_mut = indices.clone();
+    let dof = members.len();
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::diffeq::ode::types::OdeType for #name #ty_generics #where_clause {
+            type Item = #item;
+
+            #[inline]
+            fn dof(&self) -> usize {
+                #dof
+            }
+
+            fn get(&self, index: usize) -> Self::Item {
+                match index {
+                    #(#indices => self.#members,)*
+                    _ => panic!("index out of bounds: the len is {} but the index is {}", #dof, index),
+                }
+            }
+
+            fn get_mut(&mut self, index: usize) -> &mut Self::Item {
+                match index {
+                    #(#indices_mut => &mut self.#members,)*
+                    _ => panic!("index out of bounds: the len is {} but the index is {}", #dof, index),
+                }
+            }
+
+            fn insert(&mut self, index: usize, item: Self::Item) {
+                *::diffeq::ode::types::OdeType::get_mut(self, index) = item;
+            }
+        }
+    })
+}
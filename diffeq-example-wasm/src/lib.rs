@@ -1,6 +1,5 @@
 
 use diffeq::ode::problem::OdeProblem;
-use diffeq::ode::solution::SolutionPoint;
 use diffeq::ode::Ode;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
@@ -21,6 +20,7 @@ pub struct Config {
     pub num_times: usize,
 }
 
+#[allow(clippy::ptr_arg)]
 fn lorenz_attractor(_: f64, v: &Vec<f64>) -> Vec<f64> {
     let (x, y, z) = (v[0], v[1], v[2]);
 
@@ -39,6 +39,7 @@ fn lorenz_attractor(_: f64, v: &Vec<f64>) -> Vec<f64> {
 }
 
 #[wasm_bindgen]
+#[allow(deprecated)]
 pub fn solve_lorenz_attractor(config: &JsValue) -> Result<JsValue, JsValue> {
     let config = config
         .into_serde::<Config>()
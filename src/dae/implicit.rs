@@ -0,0 +1,274 @@
+use crate::dae::problem::DaeProblem;
+use crate::error::OdeError;
+use crate::ode::newton::NewtonOptions;
+use crate::ode::problem::OdeProblem;
+use na::{DMatrix, DVector};
+use std::cell::RefCell;
+
+/// The implicit ODE `r(t, y, y') = 0` whose jacobian `∂r/∂y'` is invertible.
+///
+/// R: the residual, a function of t, y(t) and y'(t).
+#[derive(Debug, Clone)]
+pub struct ImplicitOdeProblem<R>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// The residual `r(t, y, y')`.
+    residual: R,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Guess of the initial derivative.
+    yp0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImplicitOdeBuilder<R>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    residual: Option<R>,
+    y0: Option<DVector<f64>>,
+    yp0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<R> Default for ImplicitOdeBuilder<R>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            residual: None,
+            y0: None,
+            yp0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<R> ImplicitOdeBuilder<R>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// set the residual of the problem
+    pub fn residual(mut self, residual: R) -> Self {
+        self.residual = Some(residual);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// set a guess of the initial derivative, defaults to zero
+    pub fn derivative_guess<T: Into<DVector<f64>>>(mut self, yp0: T) -> Self {
+        self.yp0 = Some(yp0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`ImplicitOdeProblem`].
+    ///
+    /// Returns an error if a field is None or the derivative guess does not match the
+    /// initial value in length.
+    pub fn build(self) -> Result<ImplicitOdeProblem<R>, OdeError> {
+        let residual = self
+            .residual
+            .ok_or_else(|| OdeError::uninitialized("Residual must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        let yp0 = self.yp0.unwrap_or_else(|| DVector::zeros(y0.len()));
+        if yp0.len() != y0.len() {
+            return Err(OdeError::DimensionMismatch {
+                expected: y0.shape(),
+                found: yp0.shape(),
+            });
+        }
+
+        Ok(ImplicitOdeProblem {
+            residual,
+            y0,
+            yp0,
+            tspan,
+        })
+    }
+}
+
+impl<R> ImplicitOdeProblem<R>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `ImplicitOdeBuilder::default()`
+    pub fn builder() -> ImplicitOdeBuilder<R> {
+        ImplicitOdeBuilder::default()
+    }
+
+    /// The derivative `y'` at `(t, y)`, solved from the residual with a newton iteration
+    /// starting at `guess`.
+    ///
+    /// Fails with [`OdeError::InvalidMatrix`] where `∂r/∂y'` is singular.
+    pub fn derivative(
+        &self,
+        t: f64,
+        y: &DVector<f64>,
+        guess: &DVector<f64>,
+        opts: &NewtonOptions,
+    ) -> Result<DVector<f64>, OdeError> {
+        solve_derivative(&self.residual, t, y, guess, opts)
+    }
+
+    /// Converts the problem into the explicit problem `y' = f(t, y)`, where every
+    /// evaluation of `f` solves the residual for `y'` with
+    /// [`ImplicitOdeProblem::derivative`], starting from the previous derivative.
+    ///
+    /// Fails if the derivative can not be solved for at the initial value, an
+    /// evaluation that fails later on returns `NaN`s, which lets adaptive solvers
+    /// reject the step.
+    #[allow(clippy::type_complexity)]
+    pub fn into_explicit(
+        self,
+    ) -> Result<OdeProblem<impl Fn(f64, &DVector<f64>) -> DVector<f64>, DVector<f64>>, OdeError>
+    {
+        let t0 = self.tspan.first().cloned().unwrap_or_default();
+        let opts = NewtonOptions::default();
+        let yp0 = self.derivative(t0, &self.y0, &self.yp0, &opts)?;
+        let residual = self.residual;
+        let last = RefCell::new(yp0);
+
+        OdeProblem::builder()
+            .fun(move |t, y: &DVector<f64>| {
+                let guess = last.borrow().clone();
+                match solve_derivative(&residual, t, y, &guess, &opts) {
+                    Ok(yp) => {
+                        last.replace(yp.clone());
+                        yp
+                    }
+                    Err(_) => DVector::from_element(y.len(), f64::NAN),
+                }
+            })
+            .init(self.y0)
+            .tspan(self.tspan)
+            .build()
+    }
+
+    /// Converts the problem into a [`DaeProblem`] without algebraic components, which
+    /// solves the residual directly with [`DaeProblem::bdf`].
+    pub fn into_dae(self) -> Result<DaeProblem<R>, OdeError> {
+        let n = self.y0.len();
+        DaeProblem::builder()
+            .residual(self.residual)
+            .init(self.y0, self.yp0)
+            .differential(vec![true; n])
+            .tspan(self.tspan)
+            .build()
+    }
+}
+
+/// Solves `r(t, y, y') = 0` for `y'` with a newton iteration starting at `guess`, the
+/// jacobian `∂r/∂y'` is approximated by forward differences in every iteration.
+fn solve_derivative<R>(
+    residual: &R,
+    t: f64,
+    y: &DVector<f64>,
+    guess: &DVector<f64>,
+    opts: &NewtonOptions,
+) -> Result<DVector<f64>, OdeError>
+where
+    R: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    let n = y.len();
+    let mut yp = guess.clone();
+    for _ in 0..opts.maxiter {
+        let r0 = residual(t, y, &yp);
+        let mut jac = DMatrix::zeros(r0.len(), n);
+        for i in 0..n {
+            let delta = f64::EPSILON.sqrt() * yp[i].abs().max(1.);
+            let mut ypd = yp.clone();
+            ypd[i] += delta;
+            jac.set_column(i, &((residual(t, y, &ypd) - &r0) / delta));
+        }
+        let delta = jac.lu().solve(&(-r0)).ok_or(OdeError::InvalidMatrix)?;
+        yp += &delta;
+        if delta.amax() <= opts.tol * yp.amax().max(1.) {
+            return Ok(yp);
+        }
+    }
+
+    Err(OdeError::NewtonConvergence {
+        at: t,
+        iterations: opts.maxiter,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Dop853Options, MultistepOptions, Points, Reltol};
+
+    /// `exp(y') = exp(-y)`, the implicit form of `y' = -y`
+    fn exponential(_t: f64, y: &DVector<f64>, yp: &DVector<f64>) -> DVector<f64> {
+        yp.map(f64::exp) - y.map(|yi| (-yi).exp())
+    }
+
+    #[test]
+    fn explicit_form() {
+        let problem = ImplicitOdeProblem::builder()
+            .residual(exponential)
+            .init(DVector::from_row_slice(&[1., 2.]))
+            .tspan_linspace(0., 2., 11)
+            .build()
+            .unwrap();
+        let explicit = problem.clone().into_explicit().unwrap();
+        let dae = problem.into_dae().unwrap();
+        for solution in [
+            explicit
+                .dop853(Dop853Options {
+                    reltol: Reltol::from(1e-8),
+                    points: Points::Specified,
+                    ..Default::default()
+                })
+                .unwrap(),
+            dae.bdf(MultistepOptions {
+                reltol: Reltol::from(1e-8),
+                points: Points::Specified,
+                ..Default::default()
+            })
+            .unwrap(),
+        ] {
+            assert_eq!(11, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y[0] - (-t).exp()).abs() < 1e-5);
+                assert!((y[1] - 2. * (-t).exp()).abs() < 1e-5);
+            }
+        }
+
+        // the algebraic `r = y - 1` does not determine `y'`
+        let singular = ImplicitOdeProblem::builder()
+            .residual(|_t, y: &DVector<f64>, _yp: &DVector<f64>| y.add_scalar(-1.))
+            .init(DVector::from_row_slice(&[1.]))
+            .tspan_linspace(0., 1., 2)
+            .build()
+            .unwrap();
+        assert!(singular.into_explicit().is_err());
+    }
+}
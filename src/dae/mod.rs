@@ -0,0 +1,2 @@
+pub mod implicit;
+pub mod problem;
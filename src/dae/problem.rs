@@ -0,0 +1,583 @@
+use crate::error::OdeError;
+use crate::ode::bdf::{self, BdfCoeffs};
+use crate::ode::multistep::{rms_norm, DifferenceHistory};
+use crate::ode::newton::NewtonOptions;
+use crate::ode::options::{AdaptiveOptions, MultistepOptions, Points};
+use crate::ode::solution::{OdeSolution, ReturnCode};
+use na::linalg::LU;
+use na::{DMatrix, DVector, Dynamic};
+
+/// The fully implicit differential algebraic equation `F(t, y, y') = 0` of index 1.
+///
+/// F: the residual, a function of t, y(t) and y'(t).
+///
+/// Components of `y` whose derivative does not enter the residual are algebraic, they
+/// are determined by the constraints of the residual alone.
+#[derive(Debug, Clone)]
+pub struct DaeProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// The residual `F(t, y, y')`.
+    residual: F,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Initial derivative, only a guess for the algebraic components.
+    yp0: DVector<f64>,
+    /// Whether a component is differential rather than algebraic.
+    differential: Vec<bool>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DaeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    residual: Option<F>,
+    y0: Option<DVector<f64>>,
+    yp0: Option<DVector<f64>>,
+    differential: Option<Vec<bool>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F> Default for DaeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            residual: None,
+            y0: None,
+            yp0: None,
+            differential: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F> DaeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// set the residual of the problem
+    pub fn residual(mut self, residual: F) -> Self {
+        self.residual = Some(residual);
+        self
+    }
+
+    /// set the initial value and a guess of the initial derivative
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T, yp0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self.yp0 = Some(yp0.into());
+        self
+    }
+
+    /// mark the differential components with `true` and the algebraic components with
+    /// `false`, defaults to all components being differential
+    pub fn differential(mut self, differential: Vec<bool>) -> Self {
+        self.differential = Some(differential);
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`DaeProblem`].
+    ///
+    /// Returns an error if a field is None or the initial values and the differential
+    /// components differ in length.
+    pub fn build(self) -> Result<DaeProblem<F>, OdeError> {
+        let residual = self
+            .residual
+            .ok_or_else(|| OdeError::uninitialized("Residual must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let yp0 = self
+            .yp0
+            .ok_or_else(|| OdeError::uninitialized("Initial derivative must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        let differential = self.differential.unwrap_or_else(|| vec![true; y0.len()]);
+        if yp0.len() != y0.len() || differential.len() != y0.len() {
+            return Err(OdeError::DimensionMismatch {
+                expected: y0.shape(),
+                found: (yp0.len().min(differential.len()), 1),
+            });
+        }
+
+        Ok(DaeProblem {
+            residual,
+            y0,
+            yp0,
+            differential,
+            tspan,
+        })
+    }
+}
+
+impl<F> DaeProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `DaeBuilder::default()`
+    pub fn builder() -> DaeBuilder<F> {
+        DaeBuilder::default()
+    }
+
+    /// The jacobians `(∂F/∂y, ∂F/∂y')` of the residual by forward differences.
+    pub fn jacobians(
+        &self,
+        t: f64,
+        y: &DVector<f64>,
+        yp: &DVector<f64>,
+    ) -> (DMatrix<f64>, DMatrix<f64>) {
+        let r0 = (self.residual)(t, y, yp);
+        let n = y.len();
+        let mut jy = DMatrix::zeros(r0.len(), n);
+        let mut jyp = DMatrix::zeros(r0.len(), n);
+        for i in 0..n {
+            let delta = f64::EPSILON.sqrt() * y[i].abs().max(1.);
+            let mut yd = y.clone();
+            yd[i] += delta;
+            jy.set_column(i, &(((self.residual)(t, &yd, yp) - &r0) / delta));
+
+            let delta = f64::EPSILON.sqrt() * yp[i].abs().max(1.);
+            let mut ypd = yp.clone();
+            ypd[i] += delta;
+            jyp.set_column(i, &(((self.residual)(t, y, &ypd) - &r0) / delta));
+        }
+        (jy, jyp)
+    }
+
+    /// Computes consistent initial conditions at the start of `tspan`.
+    ///
+    /// Keeps the differential components of the initial value fixed and solves the
+    /// residual for the algebraic components of `y` and the derivatives of the
+    /// differential components with a newton iteration, starting from the given
+    /// initial values. The derivatives of the algebraic components are kept.
+    pub fn consistent_init(
+        &self,
+        opts: &NewtonOptions,
+    ) -> Result<(DVector<f64>, DVector<f64>), OdeError> {
+        let t0 = self.tspan.first().cloned().unwrap_or_default();
+        let mut y = self.y0.clone();
+        let mut yp = self.yp0.clone();
+
+        for _ in 0..opts.maxiter {
+            let r = (self.residual)(t0, &y, &yp);
+            let (jy, jyp) = self.jacobians(t0, &y, &yp);
+            // the unknown of a differential component is its derivative
+            let mut jac = jy;
+            for (i, differential) in self.differential.iter().enumerate() {
+                if *differential {
+                    jac.set_column(i, &jyp.column(i));
+                }
+            }
+            let delta = jac.lu().solve(&(-r)).ok_or(OdeError::InvalidMatrix)?;
+            for (i, differential) in self.differential.iter().enumerate() {
+                if *differential {
+                    yp[i] += delta[i];
+                } else {
+                    y[i] += delta[i];
+                }
+            }
+            if delta.amax() <= opts.tol * y.amax().max(yp.amax()).max(1.) {
+                return Ok((y, yp));
+            }
+        }
+
+        Err(OdeError::NewtonConvergence {
+            at: t0,
+            iterations: opts.maxiter,
+        })
+    }
+
+    /// Solve the problem with the variable order backward differentiation formulas of
+    /// orders 1 to 5, the highest order can be limited with
+    /// [`MaxOrder`](crate::ode::options::MaxOrder).
+    ///
+    /// Starts from the [`DaeProblem::consistent_init`] of the initial values. Each step
+    /// replaces `y'` by the derivative of the formula and solves the residual with a
+    /// simplified newton iteration on `∂F/∂y + α/h ∂F/∂y'`, which is only reevaluated if
+    /// the iteration fails to converge. The error test only includes the differential
+    /// components. Step size and order are adapted as by
+    /// [`OdeProblem::bdf`](crate::ode::problem::OdeProblem::bdf).
+    pub fn bdf(&self, opts: MultistepOptions) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let (y0, yp0) = self.consistent_init(&NewtonOptions::default())?;
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(y0.len())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| (tend - t).abs() / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| (tend - t).abs() / 2.5, |step| step.0);
+        let max_order = opts
+            .max_order
+            .map_or(bdf::MAX_ORDER, |order| order.0.clamp(1, bdf::MAX_ORDER));
+        let scale = |y: &DVector<f64>| -> Vec<f64> {
+            y.iter()
+                .enumerate()
+                .map(|(d, yi)| abstol.at(d) + reltol.at(d) * yi.abs())
+                .collect()
+        };
+
+        let mut h = if opts.initstep.0 != 0. {
+            opts.initstep.0.abs()
+        } else {
+            // keep the first step within the tolerance of the initial derivative
+            let yp_norm = rms_norm(&yp0, &scale(&y0));
+            let h = 1e-3 * (tend - t).abs();
+            if yp_norm * h > 0.5 {
+                0.5 / yp_norm
+            } else {
+                h
+            }
+        }
+        .min(maxstep);
+
+        let coeffs = BdfCoeffs::default();
+        let newton_tol = (10. * f64::EPSILON / reltol.min()).max(0.03f64.min(reltol.min().sqrt()));
+        let mut history = DifferenceHistory::new(y0.clone(), &yp0 * h, bdf::MAX_ORDER);
+        let mut order = 1;
+        let mut n_equal_steps = 0;
+        let mut jacobians = self.jacobians(t, &y0, &yp0);
+        let mut lu = None;
+
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(y0);
+        }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        'integration: while tend - t > 0. {
+            if h > maxstep {
+                history.rescale(order, maxstep / h);
+                h = maxstep;
+                n_equal_steps = 0;
+                lu = None;
+            }
+
+            let mut fresh_jac = false;
+            let (t_new, d, safety, error_norm) = loop {
+                if iters >= *opts.maxiters {
+                    retcode = ReturnCode::MaxIters;
+                    break 'integration;
+                }
+                iters += 1;
+                if h < minstep {
+                    // minimum step size reached
+                    retcode = ReturnCode::DtLessThanMin;
+                    break 'integration;
+                }
+                let mut t_new = t + h;
+                if t_new > tend {
+                    // hit the end point exactly
+                    t_new = tend;
+                    history.rescale(order, (t_new - t) / h);
+                    h = t_new - t;
+                    n_equal_steps = 0;
+                    lu = None;
+                }
+
+                let ypred = history.predict(order);
+                let psi = coeffs.psi(&history, order);
+                let c = (t_new - t) / coeffs.alpha[order];
+                let newton = loop {
+                    let iteration = lu.get_or_insert_with(|| {
+                        let (jy, jyp) = &jacobians;
+                        (jy + jyp / c).lu()
+                    });
+                    match self.solve_bdf_residual(
+                        t_new,
+                        &ypred,
+                        c,
+                        &psi,
+                        iteration,
+                        &scale(&ypred),
+                        newton_tol,
+                    )? {
+                        None if !fresh_jac => {
+                            // retry with up to date jacobians
+                            jacobians = self.jacobians(t_new, &ypred, &(&psi / c));
+                            lu = None;
+                            fresh_jac = true;
+                        }
+                        newton => break newton,
+                    }
+                };
+                let (iterations, ynew, d) = match newton {
+                    Some(newton) => newton,
+                    None => {
+                        h *= 0.5;
+                        history.rescale(order, 0.5);
+                        n_equal_steps = 0;
+                        lu = None;
+                        continue;
+                    }
+                };
+
+                let safety = 0.9 * (2 * bdf::NEWTON_MAXITER + 1) as f64
+                    / (2 * bdf::NEWTON_MAXITER + iterations) as f64;
+                let error_norm = self.error_norm(&(&d * coeffs.error_const[order]), &scale(&ynew));
+                if error_norm > 1. {
+                    let factor =
+                        bdf::MIN_FACTOR.max(safety * error_norm.powf(-1. / (order + 1) as f64));
+                    h *= factor;
+                    history.rescale(order, factor);
+                    n_equal_steps = 0;
+                    lu = None;
+                    continue;
+                }
+                break (t_new, d, safety, error_norm);
+            };
+
+            let t_old = t;
+            t = t_new;
+            n_equal_steps += 1;
+            history.update(order, &d);
+            let y = history.d[0].clone();
+
+            if n_equal_steps > order {
+                // estimate the error at the neighbouring orders and pick the order
+                // that allows the largest step
+                let error_at = |k: usize, dk: &DVector<f64>| {
+                    self.error_norm(&(dk * coeffs.error_const[k]), &scale(&y))
+                };
+                let error_m = if order > 1 {
+                    error_at(order - 1, &history.d[order])
+                } else {
+                    f64::INFINITY
+                };
+                let error_p = if order < max_order {
+                    error_at(order + 1, &history.d[order + 2])
+                } else {
+                    f64::INFINITY
+                };
+                let factors = [
+                    error_m.powf(-1. / order as f64),
+                    error_norm.powf(-1. / (order + 1) as f64),
+                    error_p.powf(-1. / (order + 2) as f64),
+                ];
+                let (idx, max) =
+                    factors
+                        .iter()
+                        .enumerate()
+                        .fold((0, f64::NEG_INFINITY), |(i, max), (j, f)| {
+                            if *f > max {
+                                (j, *f)
+                            } else {
+                                (i, max)
+                            }
+                        });
+                order = order + idx - 1;
+
+                let factor = bdf::MAX_FACTOR.min(safety * max);
+                h *= factor;
+                history.rescale(order, factor);
+                n_equal_steps = 0;
+                lu = None;
+            }
+
+            let interp = |tquery: f64| history.interpolate(order, t, h, tquery);
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && self.tspan[iter_fixed] <= t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && t_old < self.tspan[iter_fixed]
+                    && self.tspan[iter_fixed] < t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(y);
+                tout.push(t);
+            }
+        }
+
+        Ok(OdeSolution::new(tout, yout).with_retcode(retcode))
+    }
+
+    /// Solves the residual `F(t, y, (y - ypred + ψ) / c) = 0` of a BDF step from the
+    /// prediction `ypred` with a simplified newton iteration, `lu` is the factorized
+    /// iteration matrix `∂F/∂y + 1/c ∂F/∂y'`.
+    ///
+    /// Returns the number of iterations, the solution and its correction from the
+    /// prediction, or `None` if the iteration did not converge.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn solve_bdf_residual(
+        &self,
+        t: f64,
+        ypred: &DVector<f64>,
+        c: f64,
+        psi: &DVector<f64>,
+        lu: &LU<f64, Dynamic, Dynamic>,
+        scale: &[f64],
+        tol: f64,
+    ) -> Result<Option<(usize, DVector<f64>, DVector<f64>)>, OdeError> {
+        let mut y = ypred.clone();
+        let mut d = DVector::zeros(y.len());
+        let mut dy_norm_old: Option<f64> = None;
+
+        for k in 0..bdf::NEWTON_MAXITER {
+            let r = (self.residual)(t, &y, &((&d + psi) / c));
+            if r.iter().any(|ri| !ri.is_finite()) {
+                break;
+            }
+            let dy = lu.solve(&(-r)).ok_or(OdeError::InvalidMatrix)?;
+            let dy_norm = rms_norm(&dy, scale);
+
+            let rate = dy_norm_old.map(|old| dy_norm / old);
+            if let Some(rate) = rate {
+                // diverging or too slow to converge within the remaining iterations
+                if rate >= 1.
+                    || rate.powi((bdf::NEWTON_MAXITER - k) as i32) / (1. - rate) * dy_norm > tol
+                {
+                    break;
+                }
+            }
+
+            y += &dy;
+            d += &dy;
+
+            if dy_norm == 0. || rate.is_some_and(|rate| rate / (1. - rate) * dy_norm < tol) {
+                return Ok(Some((k + 1, y, d)));
+            }
+            dy_norm_old = Some(dy_norm);
+        }
+        Ok(None)
+    }
+
+    /// Root mean square norm of the differential components of `v` weighted by `scale`.
+    fn error_norm(&self, v: &DVector<f64>, scale: &[f64]) -> f64 {
+        let (sum, n) = v
+            .iter()
+            .zip(scale)
+            .zip(&self.differential)
+            .filter(|(_, differential)| **differential)
+            .fold((0., 0), |(sum, n), ((vi, si), _)| {
+                (sum + (vi / si).powi(2), n + 1)
+            });
+        if n == 0 {
+            0.
+        } else {
+            (sum / n as f64).sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Reltol};
+
+    #[test]
+    fn consistent_initial_conditions() {
+        // `y_1' = -y_1 + y_2, 0 = y_2 - sin(t)` with an inconsistent guess
+        let problem = DaeProblem::builder()
+            .residual(|t, y: &DVector<f64>, yp: &DVector<f64>| {
+                DVector::from_row_slice(&[yp[0] + y[0] - y[1], y[1] - t.sin()])
+            })
+            .init(
+                DVector::from_row_slice(&[1., 3.]),
+                DVector::from_row_slice(&[0., 0.]),
+            )
+            .differential(vec![true, false])
+            .tspan_linspace(1., 5., 5)
+            .build()
+            .unwrap();
+        let (y, yp) = problem.consistent_init(&NewtonOptions::default()).unwrap();
+        assert_eq!(1., y[0]);
+        assert!((y[1] - 1f64.sin()).abs() < 1e-10);
+        assert!((yp[0] - (1f64.sin() - 1.)).abs() < 1e-8);
+
+        // `y_1(t) = c e^{-t} + (sin(t) - cos(t)) / 2`
+        let c = (1. - (1f64.sin() - 1f64.cos()) / 2.) * 1f64.exp();
+        let exact = |t: f64| c * (-t).exp() + (t.sin() - t.cos()) / 2.;
+        let opts = MultistepOptions {
+            reltol: Reltol::from(1e-8),
+            abstol: Abstol::from(1e-10),
+            ..Default::default()
+        };
+        let solution = problem.bdf(opts).unwrap();
+        for (t, y) in solution.zipped() {
+            assert!((y[0] - exact(t)).abs() < 1e-6);
+            assert!((y[1] - t.sin()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn robertson_dae() {
+        // the robertson problem with the conservation law as algebraic equation
+        let problem = DaeProblem::builder()
+            .residual(|_t, y: &DVector<f64>, yp: &DVector<f64>| {
+                DVector::from_row_slice(&[
+                    yp[0] + 0.04 * y[0] - 1e4 * y[1] * y[2],
+                    yp[1] - 0.04 * y[0] + 1e4 * y[1] * y[2] + 3e7 * y[1] * y[1],
+                    y[0] + y[1] + y[2] - 1.,
+                ])
+            })
+            .init(
+                DVector::from_row_slice(&[1., 0., 0.]),
+                DVector::from_row_slice(&[0., 0., 0.]),
+            )
+            .differential(vec![true, true, false])
+            .tspan(vec![0., 40.])
+            .build()
+            .unwrap();
+        let opts = MultistepOptions {
+            reltol: Reltol::from(1e-6),
+            abstol: Abstol::from(1e-10),
+            ..Default::default()
+        };
+        let solution = problem.bdf(opts).unwrap();
+        let y = solution.yout.last().unwrap();
+        assert_eq!(40., *solution.tout.last().unwrap());
+        assert!((y[0] - 0.715_827_1).abs() < 1e-4);
+        assert!((y[1] - 9.185_535e-6).abs() < 1e-8);
+        assert!((y[2] - 0.284_163_7).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod neutral;
+pub mod problem;
@@ -0,0 +1,530 @@
+use crate::dde::problem::DenseStep;
+use crate::error::OdeError;
+use crate::ode::dense::DenseCoeffs;
+use crate::ode::options::{AdaptiveOptions, BasicOptions, Points};
+use crate::ode::problem::hinit;
+use crate::ode::runge_kutta::ButcherTableau;
+use crate::ode::solution::{OdeSolution, ReturnCode};
+use na::allocator::Allocator;
+use na::{DVector, DefaultAllocator, Dim, Dynamic, U1, U2};
+use std::cell::Cell;
+
+/// Maximum number of iterations of a step that depends on its own continuous extension.
+const MAX_ITER: usize = 10;
+
+/// The delay differential equation
+/// `dy/dt = f(t, y(t), y(t - τ_1), ..., y(t - τ_k), y'(t - τ_1), ..., y'(t - τ_k))` of
+/// neutral type with lags `τ_j(t, y(t)) ≥ 0` that may depend on the state, and the
+/// history `y(t) = φ(t)` for `t < t_0`.
+///
+/// F: the RHS, a function of t, y(t), the delayed states `y(t - τ_j)` and the delayed
+/// derivatives `y'(t - τ_j)` in the order of the lags.
+/// H: the history `φ`, a function of t.
+/// L: the lags `τ_j`, a function of t and y(t).
+#[derive(Debug, Clone)]
+pub struct NeutralDdeProblem<F, H, L>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+    L: Fn(f64, &DVector<f64>) -> Vec<f64>,
+{
+    /// The RHS `f(t, y, [y(t - τ_j)], [y'(t - τ_j)])`.
+    f: F,
+    /// The history `φ(t)` for `t < t_0`.
+    history: H,
+    /// The lags `τ_j(t, y)`.
+    lags: L,
+    /// Initial value, may differ from `φ(t_0)`.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NeutralDdeBuilder<F, H, L>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+    L: Fn(f64, &DVector<f64>) -> Vec<f64>,
+{
+    f: Option<F>,
+    history: Option<H>,
+    lags: Option<L>,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F, H, L> Default for NeutralDdeBuilder<F, H, L>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+    L: Fn(f64, &DVector<f64>) -> Vec<f64>,
+{
+    fn default() -> Self {
+        Self {
+            f: None,
+            history: None,
+            lags: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F, H, L> NeutralDdeBuilder<F, H, L>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+    L: Fn(f64, &DVector<f64>) -> Vec<f64>,
+{
+    /// set the problem function
+    pub fn fun(mut self, f: F) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// set the history of the problem
+    pub fn history(mut self, history: H) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// set the lags as a function of t and y(t), the delayed states and derivatives are
+    /// passed to the problem function in the same order
+    pub fn lags(mut self, lags: L) -> Self {
+        self.lags = Some(lags);
+        self
+    }
+
+    /// set the initial starting point, defaults to the history at the start of `tspan`
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`NeutralDdeProblem`].
+    ///
+    /// Returns an error if a field is None.
+    pub fn build(self) -> Result<NeutralDdeProblem<F, H, L>, OdeError> {
+        let f = self
+            .f
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let history = self
+            .history
+            .ok_or_else(|| OdeError::uninitialized("History must be initialized"))?;
+        let lags = self
+            .lags
+            .ok_or_else(|| OdeError::uninitialized("Lags must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        let y0 = self
+            .y0
+            .unwrap_or_else(|| history(tspan.first().cloned().unwrap_or_default()));
+
+        Ok(NeutralDdeProblem {
+            f,
+            history,
+            lags,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl<F, H, L> NeutralDdeProblem<F, H, L>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+    L: Fn(f64, &DVector<f64>) -> Vec<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `NeutralDdeBuilder::default()`
+    pub fn builder() -> NeutralDdeBuilder<F, H, L> {
+        NeutralDdeBuilder::default()
+    }
+
+    /// Solve the problem with the Dormand–Prince method of order 5(4) and its continuous
+    /// extension of order 4.
+    ///
+    /// Same as [`NeutralDdeProblem::method_of_steps`] with [`ButcherTableau::dopri5`].
+    pub fn dopri5(&self, opts: BasicOptions) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        self.method_of_steps(&ButcherTableau::dopri5(), &DenseCoeffs::dopri5(), opts)
+    }
+
+    /// Solve the problem by the method of steps with an embedded explicit Runge–Kutta
+    /// method.
+    ///
+    /// The delayed states and derivatives are evaluated with the continuous extension
+    /// `dense` of the accepted steps, the derivative of the history by central
+    /// differences. A step is not limited by the lags, if a delayed time falls into the
+    /// step itself, the step is repeated with its own continuous extension until the
+    /// solution settles, starting from the extrapolation of the previous step. Steps
+    /// whose iteration does not settle are retried with half the size. Discontinuities
+    /// are not tracked but left to the error control, the steps hit every value of
+    /// `tspan`, which is assumed to increase.
+    pub fn method_of_steps<S: Dim, O: Into<AdaptiveOptions>>(
+        &self,
+        btab: &ButcherTableau<S>,
+        dense: &DenseCoeffs,
+        opts: O,
+    ) -> Result<OdeSolution<f64, DVector<f64>>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>
+            + Allocator<f64, Dynamic>,
+    {
+        let pair = btab.embedded_pair()?;
+        if btab.is_implicit() {
+            return Err(OdeError::not_explicit());
+        }
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let opts = opts.into();
+        let (reltol, abstol) = opts.tolerances(self.y0.len())?;
+        let (t0, tend) = (self.tspan[0], self.tspan[self.tspan.len() - 1]);
+        let minstep = opts
+            .minstep
+            .map_or_else(|| (tend - t0).abs() / 1e18, |step| step.0);
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| (tend - t0).abs() / 2.5, |step| step.0);
+        let mut dt = if opts.initstep.0 != 0. {
+            opts.initstep.0.abs()
+        } else {
+            // the trial step extrapolates the initial state for lags below its size
+            let failure = Cell::new(None);
+            let f = |t: f64, y: &DVector<f64>| {
+                self.rhs(t, y, t0, dense, &[], None, &Cell::new(false))
+                    .unwrap_or_else(|err| {
+                        failure.set(Some(err));
+                        DVector::zeros(y.len())
+                    })
+            };
+            let order = btab.symbol.order().max();
+            let init = hinit(f, &self.y0, t0, tend, order, &reltol, &abstol)?;
+            if let Some(err) = failure.take() {
+                return Err(err);
+            }
+            init.h.abs()
+        }
+        .min(maxstep);
+
+        let order = btab.symbol.order().min() as f64;
+        let mut t = t0;
+        let mut y = self.y0.clone();
+        let mut steps: Vec<DenseStep> = Vec::new();
+        let mut solution = OdeSolution::new(vec![t], vec![y.clone()]);
+
+        let mut iters = 0;
+        for &stop in &self.tspan[1..] {
+            while t < stop {
+                if iters >= *opts.maxiters {
+                    return Ok(solution.with_retcode(ReturnCode::MaxIters));
+                }
+                iters += 1;
+                let h = dt.min(stop - t);
+                let scale = |d: usize, ynew: &DVector<f64>| {
+                    abstol.at(d) + reltol.at(d) * y[d].abs().max(ynew[d].abs())
+                };
+
+                // iterate the step while it depends on itself
+                let mut current: Option<DenseStep> = None;
+                let mut settled = None;
+                for _ in 0..MAX_ITER {
+                    let implicit = Cell::new(false);
+                    let mut ks =
+                        self.stages(btab, dense, t, h, &y, &steps, current.as_ref(), &implicit)?;
+                    let (ytrial, yerr) = pair.step(h, &y, &ks);
+                    let change = current.as_ref().map(|current| {
+                        let yold = dense.interpolate(1., h, &y, &current.ks);
+                        (0..y.len())
+                            .map(|d| (ytrial[d] - yold[d]).abs() / scale(d, &ytrial))
+                            .fold(0., f64::max)
+                    });
+                    self.dense_stages(dense, t, h, &y, &mut ks, &steps, current.as_ref())?;
+                    let step = DenseStep {
+                        t,
+                        dt: h,
+                        y: y.clone(),
+                        ks,
+                    };
+                    if !implicit.get() || change.is_some_and(|change| change <= 1e-2) {
+                        settled = Some((step, ytrial, yerr));
+                        break;
+                    }
+                    current = Some(step);
+                }
+                let (step, ytrial, yerr) = match settled {
+                    Some(settled) => settled,
+                    None if h / 2. < minstep => {
+                        // minimum step size reached
+                        return Ok(solution.with_retcode(ReturnCode::DtLessThanMin));
+                    }
+                    None => {
+                        dt = h / 2.;
+                        continue;
+                    }
+                };
+
+                let err = (0..y.len())
+                    .map(|d| yerr[d].abs() / scale(d, &ytrial))
+                    .fold(0., f64::max);
+                let fac = (0.9 * err.powf(-1. / (order + 1.))).clamp(0.2, 5.);
+
+                if err <= 1. {
+                    steps.push(step);
+                    t = if stop - (t + h) <= h * 1e-12 {
+                        stop
+                    } else {
+                        t + h
+                    };
+                    y = ytrial;
+                    if opts.points == Points::All || t == stop {
+                        solution.tout.push(t);
+                        solution.yout.push(y.clone());
+                    }
+                    dt = maxstep.min(h * fac);
+                } else if h * fac < minstep {
+                    // minimum step size reached
+                    return Ok(solution.with_retcode(ReturnCode::DtLessThanMin));
+                } else {
+                    // redo step with smaller dt
+                    dt = h * fac.min(1.);
+                }
+            }
+        }
+
+        Ok(solution)
+    }
+
+    /// The stages of a step of size `dt` from `(t, y)`, `current` is the guess of the
+    /// continuous extension of the step itself. `implicit` is set if a delayed time
+    /// falls into the step.
+    #[allow(clippy::too_many_arguments)]
+    fn stages<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+        dense: &DenseCoeffs,
+        t: f64,
+        dt: f64,
+        y: &DVector<f64>,
+        steps: &[DenseStep],
+        current: Option<&DenseStep>,
+        implicit: &Cell<bool>,
+    ) -> Result<Vec<DVector<f64>>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        let mut ks: Vec<DVector<f64>> = Vec::with_capacity(btab.nstages());
+        for i in 0..btab.nstages() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate() {
+                yi += k * (btab.a[(i, j)] * dt);
+            }
+            let ti = t + btab.c[i] * dt;
+            ks.push(self.rhs(ti, &yi, t, dense, steps, current, implicit)?);
+        }
+        Ok(ks)
+    }
+
+    /// Appends the additional stages of the continuous extension `dense` to `ks`.
+    #[allow(clippy::too_many_arguments)]
+    fn dense_stages(
+        &self,
+        dense: &DenseCoeffs,
+        t: f64,
+        dt: f64,
+        y: &DVector<f64>,
+        ks: &mut Vec<DVector<f64>>,
+        steps: &[DenseStep],
+        current: Option<&DenseStep>,
+    ) -> Result<(), OdeError> {
+        let implicit = Cell::new(false);
+        for s in 0..dense.nstages_extra() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate().take(dense.a.ncols()) {
+                yi += k * (dense.a[(s, j)] * dt);
+            }
+            let ti = t + dense.c[s] * dt;
+            let k = self.rhs(ti, &yi, t, dense, steps, current, &implicit)?;
+            ks.push(k);
+        }
+        Ok(())
+    }
+
+    /// The RHS at `(ti, yi)` inside the step that starts at `t`.
+    #[allow(clippy::too_many_arguments)]
+    fn rhs(
+        &self,
+        ti: f64,
+        yi: &DVector<f64>,
+        t: f64,
+        dense: &DenseCoeffs,
+        steps: &[DenseStep],
+        current: Option<&DenseStep>,
+        implicit: &Cell<bool>,
+    ) -> Result<DVector<f64>, OdeError> {
+        let lags = (self.lags)(ti, yi);
+        let mut states = Vec::with_capacity(lags.len());
+        let mut derivatives = Vec::with_capacity(lags.len());
+        for lag in lags {
+            if lag.is_nan() || lag < 0. {
+                return Err(OdeError::InvalidLag { lag });
+            }
+            let td = ti - lag;
+            let (state, derivative) = if td > t {
+                implicit.set(true);
+                match current.or_else(|| steps.last()) {
+                    // the guess of the current step or the extrapolation of the previous
+                    Some(step) => {
+                        let theta = (td - step.t) / step.dt;
+                        (
+                            dense.interpolate(theta, step.dt, &step.y, &step.ks),
+                            dense.derivative(theta, &step.ks),
+                        )
+                    }
+                    None => self.past(self.tspan[0], dense, steps),
+                }
+            } else {
+                self.past(td, dense, steps)
+            };
+            states.push(state);
+            derivatives.push(derivative);
+        }
+        Ok((self.f)(ti, yi, &states, &derivatives))
+    }
+
+    /// The solution and its derivative at `t`, which precedes the current step.
+    fn past(
+        &self,
+        t: f64,
+        dense: &DenseCoeffs,
+        steps: &[DenseStep],
+    ) -> (DVector<f64>, DVector<f64>) {
+        let t0 = self.tspan[0];
+        if t < t0 {
+            let delta = f64::EPSILON.cbrt() * t.abs().max(1.);
+            let derivative = ((self.history)(t + delta) - (self.history)(t - delta)) / (2. * delta);
+            return ((self.history)(t), derivative);
+        }
+        match steps.partition_point(|step| step.t <= t) {
+            // the start of the first step, whose derivative is taken from the history
+            0 => {
+                let (_, derivative) =
+                    self.past(t0 - f64::EPSILON.cbrt() * t0.abs().max(1.), dense, steps);
+                (self.y0.clone(), derivative)
+            }
+            i => {
+                let step = &steps[i - 1];
+                let theta = (t - step.t) / step.dt;
+                (
+                    dense.interpolate(theta, step.dt, &step.y, &step.ks),
+                    dense.derivative(theta, &step.ks),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dde::problem::DdeProblem;
+    use crate::ode::options::{Abstol, Initstep, Reltol};
+
+    type Rhs = fn(f64, &DVector<f64>, &[DVector<f64>], &[DVector<f64>]) -> DVector<f64>;
+    type History = fn(f64) -> DVector<f64>;
+    type Lags = fn(f64, &DVector<f64>) -> Vec<f64>;
+    type Neutral = NeutralDdeProblem<Rhs, History, Lags>;
+
+    fn tight() -> BasicOptions {
+        BasicOptions {
+            reltol: Reltol::from(1e-9),
+            abstol: Abstol::from(1e-9),
+            points: Points::Specified,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn state_dependent() {
+        // `y'(t) = -y(t - y(t))` with `y(t) = 1` for `t ≤ 0` has the solution `1 - t` up
+        // to `t = 1/2` and `t - 2 + 2 e^{1/2 - t}` thereafter
+        let problem = Neutral::builder()
+            .fun(|_t, _y, delayed, _derivatives| -&delayed[0])
+            .history(|_t| DVector::from_element(1, 1.))
+            .lags(|_t, y| vec![y[0]])
+            .tspan(vec![0., 0.5, 0.75])
+            .build()
+            .unwrap();
+        let solution = problem.dopri5(tight()).unwrap();
+        let exact = [1., 0.5, -1.25 + 2. * (-0.25f64).exp()];
+        for (y, exact) in solution.yout.iter().zip(exact.iter()) {
+            assert!((y[0] - exact).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn neutral() {
+        // `y'(t) = y'(t - 1) / 2` with `y(t) = t` for `t ≤ 0` is piecewise linear
+        let problem = Neutral::builder()
+            .fun(|_t, _y, _delayed, derivatives| &derivatives[0] * 0.5)
+            .history(|t| DVector::from_element(1, t))
+            .lags(|_t, _y| vec![1.])
+            .tspan(vec![0., 1., 2.])
+            .build()
+            .unwrap();
+        let solution = problem.dopri5(tight()).unwrap();
+        assert!((solution.yout[1][0] - 0.5).abs() < 1e-7);
+        assert!((solution.yout[2][0] - 0.75).abs() < 1e-7);
+    }
+
+    #[test]
+    fn iterated_steps() {
+        // steps longer than the lag agree with the method of steps of `DdeProblem`
+        let problem = Neutral::builder()
+            .fun(|_t, _y, delayed, _derivatives| -&delayed[0])
+            .history(|_t| DVector::from_element(1, 1.))
+            .lags(|_t, _y| vec![0.05])
+            .tspan(vec![0., 2.])
+            .build()
+            .unwrap();
+        let opts = BasicOptions {
+            initstep: Initstep(0.2),
+            ..tight()
+        };
+        let y = problem.dopri5(opts).unwrap().yout[1][0];
+
+        let reference = DdeProblem::builder()
+            .fun(|_t, _y: &DVector<f64>, delayed: &[DVector<f64>]| -&delayed[0])
+            .history(|_t| DVector::from_element(1, 1.))
+            .lags(vec![0.05])
+            .tspan(vec![0., 2.])
+            .build()
+            .unwrap()
+            .dopri5(tight())
+            .unwrap();
+        assert!((y - reference.yout[1][0]).abs() < 1e-6);
+    }
+}
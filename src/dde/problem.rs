@@ -0,0 +1,458 @@
+use crate::error::OdeError;
+use crate::ode::dense::DenseCoeffs;
+use crate::ode::options::{AdaptiveOptions, BasicOptions, Points};
+use crate::ode::problem::hinit;
+use crate::ode::runge_kutta::ButcherTableau;
+use crate::ode::solution::{OdeSolution, ReturnCode};
+use na::allocator::Allocator;
+use na::{DVector, DefaultAllocator, Dim, Dynamic, U1, U2};
+
+/// The delay differential equation `dy/dt = f(t, y(t), y(t - τ_1), ..., y(t - τ_k))` with
+/// constant lags `τ_j > 0` and the history `y(t) = φ(t)` for `t < t_0`.
+///
+/// F: the RHS, a function of t, y(t) and the delayed states `y(t - τ_j)` in the order of
+/// the lags.
+/// H: the history `φ`, a function of t.
+#[derive(Debug, Clone)]
+pub struct DdeProblem<F, H>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+{
+    /// The RHS `f(t, y, [y(t - τ_j)])`.
+    f: F,
+    /// The history `φ(t)` for `t < t_0`.
+    history: H,
+    /// The constant lags `τ_j`.
+    lags: Vec<f64>,
+    /// Initial value, may differ from `φ(t_0)`.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DdeBuilder<F, H>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+{
+    f: Option<F>,
+    history: Option<H>,
+    lags: Vec<f64>,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F, H> Default for DdeBuilder<F, H>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            f: None,
+            history: None,
+            lags: Vec::new(),
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F, H> DdeBuilder<F, H>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+{
+    /// set the problem function
+    pub fn fun(mut self, f: F) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// set the history of the problem
+    pub fn history(mut self, history: H) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// set the constant lags, the delayed states are passed to the problem function in
+    /// the same order
+    pub fn lags(mut self, lags: Vec<f64>) -> Self {
+        self.lags = lags;
+        self
+    }
+
+    /// set the initial starting point, defaults to the history at the start of `tspan`
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`DdeProblem`].
+    ///
+    /// Returns an error if a field is None, no lag was set or a lag is not positive.
+    pub fn build(self) -> Result<DdeProblem<F, H>, OdeError> {
+        let f = self
+            .f
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let history = self
+            .history
+            .ok_or_else(|| OdeError::uninitialized("History must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        if self.lags.is_empty() {
+            return Err(OdeError::uninitialized("At least one lag must be set"));
+        }
+        if let Some(&lag) = self.lags.iter().find(|lag| lag.is_nan() || **lag <= 0.) {
+            return Err(OdeError::InvalidLag { lag });
+        }
+        let y0 = self
+            .y0
+            .unwrap_or_else(|| history(tspan.first().cloned().unwrap_or_default()));
+
+        Ok(DdeProblem {
+            f,
+            history,
+            lags: self.lags,
+            y0,
+            tspan,
+        })
+    }
+}
+
+/// An accepted step together with the stages of its continuous extension.
+#[derive(Debug, Clone)]
+pub(crate) struct DenseStep {
+    pub(crate) t: f64,
+    pub(crate) dt: f64,
+    pub(crate) y: DVector<f64>,
+    pub(crate) ks: Vec<DVector<f64>>,
+}
+
+impl<F, H> DdeProblem<F, H>
+where
+    F: Fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+    H: Fn(f64) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `DdeBuilder::default()`
+    pub fn builder() -> DdeBuilder<F, H> {
+        DdeBuilder::default()
+    }
+
+    /// The constant lags.
+    #[inline]
+    pub fn lags(&self) -> &[f64] {
+        &self.lags
+    }
+
+    /// The breaking points `t_0 + Σ_j n_j τ_j` within `tspan` together with the order of
+    /// the derivative of `y` that jumps there, up to discontinuities of `order`.
+    ///
+    /// The initial point has order 0 if the initial value differs from the history and
+    /// order 1 otherwise, each lag raises the order by one.
+    pub fn discontinuities(&self, order: usize) -> Vec<(f64, usize)> {
+        if self.tspan.is_empty() {
+            return Vec::new();
+        }
+        let (t0, tend) = (self.tspan[0], self.tspan[self.tspan.len() - 1]);
+        let first = if (self.history)(t0) == self.y0 { 1 } else { 0 };
+        if first > order {
+            return Vec::new();
+        }
+
+        let mut points = vec![(t0, first)];
+        let mut level = vec![(t0, first)];
+        while !level.is_empty() {
+            let mut next: Vec<(f64, usize)> = Vec::new();
+            for (t, k) in level.iter().filter(|(_, k)| *k < order) {
+                for lag in &self.lags {
+                    let t = t + lag;
+                    if t > tend * (1. + 1e-12) {
+                        continue;
+                    }
+                    // the same point may be reached through different lags
+                    if !points
+                        .iter()
+                        .chain(next.iter())
+                        .any(|(p, _)| (p - t).abs() <= 1e-12 * t.abs().max(1.))
+                    {
+                        next.push((t, k + 1));
+                    }
+                }
+            }
+            points.extend(next.iter().cloned());
+            level = next;
+        }
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        points
+    }
+
+    /// Solve the problem with the Dormand–Prince 5(4) method and its continuous extension
+    /// of order 4.
+    pub fn dopri5(&self, opts: BasicOptions) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        self.method_of_steps(&ButcherTableau::dopri5(), &DenseCoeffs::dopri5(), opts)
+    }
+
+    /// Solve the problem by the method of steps with an embedded explicit Runge–Kutta
+    /// method.
+    ///
+    /// The delayed states are evaluated with the continuous extension `dense` of the
+    /// accepted steps, which is why the step size is limited by the smallest lag. The
+    /// steps hit every breaking point of [`DdeProblem::discontinuities`] up to the order
+    /// of the method, as well as every value of `tspan`, which is assumed to increase.
+    pub fn method_of_steps<S: Dim, O: Into<AdaptiveOptions>>(
+        &self,
+        btab: &ButcherTableau<S>,
+        dense: &DenseCoeffs,
+        opts: O,
+    ) -> Result<OdeSolution<f64, DVector<f64>>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>
+            + Allocator<f64, Dynamic>,
+    {
+        let pair = btab.embedded_pair()?;
+        if btab.is_implicit() {
+            return Err(OdeError::not_explicit());
+        }
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let opts = opts.into();
+        let (reltol, abstol) = opts.tolerances(self.y0.len())?;
+        let (t0, tend) = (self.tspan[0], self.tspan[self.tspan.len() - 1]);
+        let minstep = opts
+            .minstep
+            .map_or_else(|| (tend - t0).abs() / 1e18, |step| step.0);
+        let min_lag = self.lags.iter().cloned().fold(f64::INFINITY, f64::min);
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| (tend - t0).abs() / 2.5, |step| step.0)
+            .min(min_lag);
+        let mut dt = if opts.initstep.0 != 0. {
+            opts.initstep.0.abs()
+        } else {
+            // the delayed states of the first step are known
+            let f = |t: f64, y: &DVector<f64>| (self.f)(t, y, &self.delayed(t, dense, &[]));
+            let order = btab.symbol.order().max();
+            hinit(f, &self.y0, t0, tend, order, &reltol, &abstol)?
+                .h
+                .abs()
+        }
+        .min(maxstep);
+
+        // the points to hit, flagged if they are output points
+        let mut stops: Vec<(f64, bool)> = self.tspan[1..].iter().map(|t| (*t, true)).collect();
+        for (t, _) in self.discontinuities(btab.symbol.order().max()) {
+            if t > t0
+                && !stops
+                    .iter()
+                    .any(|(s, _)| (s - t).abs() <= 1e-12 * t.abs().max(1.))
+            {
+                stops.push((t, false));
+            }
+        }
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let order = btab.symbol.order().min() as f64;
+        let mut t = t0;
+        let mut y = self.y0.clone();
+        let mut steps: Vec<DenseStep> = Vec::new();
+        let mut solution = OdeSolution::new(vec![t], vec![y.clone()]);
+
+        let mut iters = 0;
+        for (stop, output) in stops {
+            while t < stop {
+                if iters >= *opts.maxiters {
+                    return Ok(solution.with_retcode(ReturnCode::MaxIters));
+                }
+                iters += 1;
+                let h = dt.min(stop - t);
+                let ks = self.stages(btab, dense, t, h, &y, &steps);
+
+                let (ytrial, yerr) = pair.step(h, &y, &ks);
+                let err = (0..y.len())
+                    .map(|d| {
+                        let scale = abstol.at(d) + reltol.at(d) * y[d].abs().max(ytrial[d].abs());
+                        yerr[d].abs() / scale
+                    })
+                    .fold(0., f64::max);
+                let fac = (0.9 * err.powf(-1. / (order + 1.))).clamp(0.2, 5.);
+
+                if err <= 1. {
+                    let mut ks = ks;
+                    self.dense_stages(dense, t, h, &y, &mut ks, &steps);
+                    steps.push(DenseStep {
+                        t,
+                        dt: h,
+                        y: y.clone(),
+                        ks,
+                    });
+                    t = if stop - (t + h) <= h * 1e-12 {
+                        stop
+                    } else {
+                        t + h
+                    };
+                    y = ytrial;
+                    if opts.points == Points::All || (output && t == stop) {
+                        solution.tout.push(t);
+                        solution.yout.push(y.clone());
+                    }
+                    dt = maxstep.min(h * fac);
+                } else if h * fac < minstep {
+                    // minimum step size reached
+                    return Ok(solution.with_retcode(ReturnCode::DtLessThanMin));
+                } else {
+                    // redo step with smaller dt
+                    dt = h * fac.min(1.);
+                }
+            }
+        }
+
+        Ok(solution)
+    }
+
+    /// The stages of a step of size `dt` from `(t, y)`.
+    #[allow(clippy::too_many_arguments)]
+    fn stages<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+        dense: &DenseCoeffs,
+        t: f64,
+        dt: f64,
+        y: &DVector<f64>,
+        steps: &[DenseStep],
+    ) -> Vec<DVector<f64>>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        let mut ks: Vec<DVector<f64>> = Vec::with_capacity(btab.nstages());
+        for i in 0..btab.nstages() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate() {
+                yi += k * (btab.a[(i, j)] * dt);
+            }
+            let ti = t + btab.c[i] * dt;
+            ks.push((self.f)(ti, &yi, &self.delayed(ti, dense, steps)));
+        }
+        ks
+    }
+
+    /// Appends the additional stages of the continuous extension `dense` to `ks`.
+    fn dense_stages(
+        &self,
+        dense: &DenseCoeffs,
+        t: f64,
+        dt: f64,
+        y: &DVector<f64>,
+        ks: &mut Vec<DVector<f64>>,
+        steps: &[DenseStep],
+    ) {
+        for s in 0..dense.nstages_extra() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate().take(dense.a.ncols()) {
+                yi += k * (dense.a[(s, j)] * dt);
+            }
+            let ti = t + dense.c[s] * dt;
+            ks.push((self.f)(ti, &yi, &self.delayed(ti, dense, steps)));
+        }
+    }
+
+    /// The delayed states `y(t - τ_j)`.
+    fn delayed(&self, t: f64, dense: &DenseCoeffs, steps: &[DenseStep]) -> Vec<DVector<f64>> {
+        self.lags
+            .iter()
+            .map(|lag| self.past(t - lag, dense, steps))
+            .collect()
+    }
+
+    /// The solution at `t`, which precedes the current step.
+    fn past(&self, t: f64, dense: &DenseCoeffs, steps: &[DenseStep]) -> DVector<f64> {
+        let t0 = self.tspan[0];
+        if t < t0 {
+            return (self.history)(t);
+        }
+        match steps.partition_point(|step| step.t <= t) {
+            // only reached by a step of the size of the smallest lag
+            0 => self.y0.clone(),
+            i => {
+                let step = &steps[i - 1];
+                dense.interpolate((t - step.t) / step.dt, step.dt, &step.y, &step.ks)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Reltol};
+
+    type Dde = DdeProblem<
+        fn(f64, &DVector<f64>, &[DVector<f64>]) -> DVector<f64>,
+        fn(f64) -> DVector<f64>,
+    >;
+
+    /// `y'(t) = -y(t - 1)` with `y(t) = 1` for `t ≤ 0`
+    fn decay(lags: Vec<f64>) -> Dde {
+        Dde::builder()
+            .fun(|_t, _y, delayed| -&delayed[0])
+            .history(|_t| DVector::from_element(1, 1.))
+            .lags(lags)
+            .tspan_linspace(0., 3., 4)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn discontinuity_orders() {
+        assert_eq!(
+            vec![(0., 1), (1., 2), (1.5, 2), (2., 3), (2.5, 3), (3., 3)],
+            decay(vec![1., 1.5]).discontinuities(3)
+        );
+        assert!(Dde::builder()
+            .fun(|_t, y, _delayed| y.clone())
+            .history(|_t| DVector::from_element(1, 1.))
+            .lags(vec![0.])
+            .tspan_linspace(0., 3., 4)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn method_of_steps() {
+        // the solution is a polynomial of degree `n` on `[n - 1, n]`, `y(3) = -1/6`
+        let opts = BasicOptions {
+            reltol: Reltol::from(1e-8),
+            abstol: Abstol::from(1e-8),
+            ..Default::default()
+        };
+        let solution = decay(vec![1.]).dopri5(opts).unwrap();
+        assert!(solution.tout.contains(&1.) && solution.tout.contains(&2.));
+        let y = solution.yout.last().unwrap()[0];
+        assert!((y + 1. / 6.).abs() < 1e-7);
+    }
+}
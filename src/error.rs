@@ -1,4 +1,6 @@
 use crate::ode::runge_kutta::WeightType;
+use crate::ode::Ode;
+use crate::sde::noise::NoiseType;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -20,12 +22,96 @@ pub enum OdeError {
     InvalidInitstep,
     #[error("Unable to compute matrix operation")]
     InvalidMatrix,
+    #[error("Newton iteration did not converge at {at} after {iterations} iterations")]
+    NewtonConvergence { at: f64, iterations: usize },
+    #[error("Expected a matrix of shape {expected:?}, found {found:?}")]
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    #[error("Invalid Butcher tableau: {reason}")]
+    InvalidButcherTableau { reason: String },
+    #[error("Lags must be positive, found {lag}")]
+    InvalidLag { lag: f64 },
+    #[error("The {method} method does not support {noise:?} noise")]
+    UnsupportedNoise {
+        method: &'static str,
+        noise: NoiseType,
+    },
+    #[error("The {method:?} solver does not support {feature}")]
+    UnsupportedSolver { method: Ode, feature: &'static str },
+    #[error("The {method} method does not support {feature}")]
+    UnsupportedMethod {
+        method: &'static str,
+        feature: &'static str,
+    },
+    #[error("Expected a tolerance for each of the {expected} components, found {found}")]
+    ToleranceLength { expected: usize, found: usize },
+    #[error("Rate bounds must be positive and finite, found {bound}")]
+    InvalidRateBound { bound: f64 },
+    #[error("Jump rate {rate} exceeds its bound {bound} at {at}")]
+    RateBoundExceeded { at: f64, rate: f64, bound: f64 },
+    #[error("Invalid option `{input}`: {reason}")]
+    InvalidOption { input: String, reason: String },
+    #[error("Invalid configuration: {msg}")]
+    InvalidConfig { msg: String },
+    #[error("The option {name}={value} must be {expected}")]
+    OptionOutOfRange {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    #[error("The option {name} holds a {found} option")]
+    OptionMismatch {
+        name: &'static str,
+        found: &'static str,
+    },
+    #[error("The option {name} is not honored by the solvers of {options}")]
+    UnhonoredOption {
+        name: &'static str,
+        options: &'static str,
+    },
+    #[error("Jacobian entry {entry:?} is {analytic} at {at}, finite differences give {approx}")]
+    JacobianMismatch {
+        at: f64,
+        entry: (usize, usize),
+        analytic: f64,
+        approx: f64,
+    },
 }
 
 impl OdeError {
     pub(crate) fn uninitialized<T: ToString>(s: T) -> Self {
         OdeError::Uninitialized { msg: s.to_string() }
     }
+
+    /// a tableau without embedded weights where an error estimate is required
+    pub(crate) fn not_embedded() -> Self {
+        OdeError::InvalidButcherTableauWeightType {
+            expected: WeightType::Adaptive,
+            found: WeightType::Explicit,
+        }
+    }
+
+    /// an implicit tableau for a method that computes the stages explicitly
+    pub(crate) fn not_explicit() -> Self {
+        OdeError::InvalidButcherTableau {
+            reason: "the stages of an implicit tableau can't be computed explicitly".to_string(),
+        }
+    }
+
+    pub(crate) fn invalid_config<T: ToString>(msg: T) -> Self {
+        OdeError::InvalidConfig {
+            msg: msg.to_string(),
+        }
+    }
+
+    pub(crate) fn invalid_option<T: ToString>(input: &str, reason: T) -> Self {
+        OdeError::InvalidOption {
+            input: input.trim().to_string(),
+            reason: reason.to_string(),
+        }
+    }
 }
 
 /// Enumeration of the errors that may arise during integration.
@@ -0,0 +1,396 @@
+use crate::error::OdeError;
+use crate::jump::solution::JumpSolution;
+use crate::ode::options::{Dop853Options, Points};
+use crate::ode::problem::OdeProblem;
+use crate::sde::noise::wiener_increment;
+use na::{DMatrix, DVector};
+use rand::Rng;
+use rand_distr::Exp1;
+use std::fmt;
+
+/// The type of the rate `λ(t, y)` of a jump.
+pub type JumpRate = Box<dyn Fn(f64, &DVector<f64>) -> f64>;
+
+/// The type of the change of the state by a jump.
+pub type JumpAffect = Box<dyn Fn(&mut DVector<f64>)>;
+
+/// A jump whose rate only changes with the jumps, so that the waiting times are
+/// exponentially distributed.
+pub struct ConstantRateJump {
+    rate: JumpRate,
+    affect: JumpAffect,
+}
+
+impl ConstantRateJump {
+    pub fn new<L, A>(rate: L, affect: A) -> Self
+    where
+        L: Fn(f64, &DVector<f64>) -> f64 + 'static,
+        A: Fn(&mut DVector<f64>) + 'static,
+    {
+        Self {
+            rate: Box::new(rate),
+            affect: Box::new(affect),
+        }
+    }
+}
+
+impl fmt::Debug for ConstantRateJump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConstantRateJump").finish_non_exhaustive()
+    }
+}
+
+/// A jump whose rate changes with time or the continuous dynamics, sampled by thinning
+/// the jumps of the constant rate `bound`, which must not be exceeded by the rate.
+pub struct VariableRateJump {
+    rate: JumpRate,
+    affect: JumpAffect,
+    bound: f64,
+}
+
+impl VariableRateJump {
+    pub fn new<L, A>(rate: L, affect: A, bound: f64) -> Self
+    where
+        L: Fn(f64, &DVector<f64>) -> f64 + 'static,
+        A: Fn(&mut DVector<f64>) + 'static,
+    {
+        Self {
+            rate: Box::new(rate),
+            affect: Box::new(affect),
+            bound,
+        }
+    }
+}
+
+impl fmt::Debug for VariableRateJump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VariableRateJump")
+            .field("bound", &self.bound)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A process that jumps at random times, optionally on top of continuous dynamics
+/// between the jumps.
+#[derive(Debug)]
+pub struct JumpProblem {
+    constant: Vec<ConstantRateJump>,
+    variable: Vec<VariableRateJump>,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct JumpBuilder {
+    constant: Vec<ConstantRateJump>,
+    variable: Vec<VariableRateJump>,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl JumpBuilder {
+    /// add a jump that changes the state by `affect` with the rate `rate`, which must not
+    /// change between jumps
+    pub fn constant_jump<L, A>(mut self, rate: L, affect: A) -> Self
+    where
+        L: Fn(f64, &DVector<f64>) -> f64 + 'static,
+        A: Fn(&mut DVector<f64>) + 'static,
+    {
+        self.constant.push(ConstantRateJump::new(rate, affect));
+        self
+    }
+
+    /// add a jump that changes the state by `affect` with the rate `rate`, which may
+    /// change with time and the continuous dynamics but must not exceed `bound`
+    pub fn variable_jump<L, A>(mut self, rate: L, affect: A, bound: f64) -> Self
+    where
+        L: Fn(f64, &DVector<f64>) -> f64 + 'static,
+        A: Fn(&mut DVector<f64>) + 'static,
+    {
+        self.variable
+            .push(VariableRateJump::new(rate, affect, bound));
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`JumpProblem`].
+    ///
+    /// Returns an error if a field is None or a rate bound is not positive and finite.
+    pub fn build(self) -> Result<JumpProblem, OdeError> {
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        if let Some(jump) = self
+            .variable
+            .iter()
+            .find(|jump| !jump.bound.is_finite() || jump.bound <= 0.)
+        {
+            return Err(OdeError::InvalidRateBound { bound: jump.bound });
+        }
+
+        Ok(JumpProblem {
+            constant: self.constant,
+            variable: self.variable,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl JumpProblem {
+    /// convenience method to create a new builder
+    /// same as `JumpBuilder::default()`
+    pub fn builder() -> JumpBuilder {
+        JumpBuilder::default()
+    }
+
+    /// Simulate the pure jump process with the direct method of Gillespie's stochastic
+    /// simulation algorithm, the state is constant between the jumps.
+    pub fn ssa<R: Rng>(&self, rng: &mut R) -> Result<JumpSolution, OdeError> {
+        self.simulate(rng, |_rng, _t0, _t1, y| Ok(y))
+    }
+
+    /// Simulate the jumps on top of the ODE `dy/dt = f(t, y)`, which is solved between
+    /// the jumps with [`OdeProblem::dop853`].
+    pub fn ode<R, F>(
+        &self,
+        rng: &mut R,
+        f: F,
+        opts: Dop853Options,
+    ) -> Result<JumpSolution, OdeError>
+    where
+        R: Rng,
+        F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    {
+        let opts = Dop853Options {
+            points: Points::Specified,
+            ..opts
+        };
+        self.simulate(rng, |_rng, t0, t1, y| {
+            if t1 <= t0 {
+                return Ok(y);
+            }
+            let solution = OdeProblem::builder()
+                .fun(|t, y: &DVector<f64>| f(t, y))
+                .init(y)
+                .tspan(vec![t0, t1])
+                .build()?
+                .dop853(opts.clone())?;
+            Ok(solution.yout[solution.yout.len() - 1].clone())
+        })
+    }
+
+    /// Simulate the jumps on top of the Itô SDE `dy = f(t, y) dt + g(t, y) dW`, which is
+    /// solved between the jumps with the Euler–Maruyama method with steps of at most
+    /// `dt`. The Wiener increments are drawn from the same `rng` as the jumps.
+    pub fn sde<R, F, G>(
+        &self,
+        rng: &mut R,
+        drift: F,
+        diffusion: G,
+        dt: f64,
+    ) -> Result<JumpSolution, OdeError>
+    where
+        R: Rng,
+        F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+        G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+    {
+        self.simulate(rng, |rng, t0, t1, mut y| {
+            let steps = ((t1 - t0) / dt).ceil().max(1.) as usize;
+            let h = (t1 - t0) / steps as f64;
+            for i in 0..steps {
+                let t = t0 + i as f64 * h;
+                let g = diffusion(t, &y);
+                let dw = wiener_increment(rng, g.ncols(), h);
+                y += drift(t, &y) * h + g * dw;
+            }
+            Ok(y)
+        })
+    }
+
+    /// Simulate the jumps, `advance` evolves the state from `t0` to `t1` by the
+    /// continuous dynamics between the jumps.
+    ///
+    /// The candidate jumps arrive with the total rate of the constant rate jumps and the
+    /// bounds of the variable rate jumps. A candidate of a variable rate jump is only
+    /// accepted with the probability `λ(t, y) / bound`, the rates of the constant rate
+    /// jumps are only reevaluated after a jump. The solution holds the state at the
+    /// values of `tspan`, which is assumed to increase.
+    pub fn simulate<R, A>(&self, rng: &mut R, mut advance: A) -> Result<JumpSolution, OdeError>
+    where
+        R: Rng,
+        A: FnMut(&mut R, f64, f64, DVector<f64>) -> Result<DVector<f64>, OdeError>,
+    {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(JumpSolution::default());
+        }
+
+        let mut t = self.tspan[0];
+        let mut y = self.y0.clone();
+        let mut solution = JumpSolution {
+            tout: vec![t],
+            yout: vec![y.clone()],
+            jumps: Vec::new(),
+        };
+        let bound: f64 = self.variable.iter().map(|jump| jump.bound).sum();
+        let constant_rates = |t: f64, y: &DVector<f64>| -> Vec<f64> {
+            self.constant.iter().map(|jump| (jump.rate)(t, y)).collect()
+        };
+        let mut rates = constant_rates(t, &y);
+        let mut next_out = 1;
+
+        while next_out < self.tspan.len() {
+            let total = rates.iter().sum::<f64>() + bound;
+            let t_next = if total > 0. {
+                t + rng.sample::<f64, _>(Exp1) / total
+            } else {
+                f64::INFINITY
+            };
+
+            // output points before the next candidate
+            while next_out < self.tspan.len() && self.tspan[next_out] < t_next {
+                let tout = self.tspan[next_out];
+                y = advance(rng, t, tout, y)?;
+                t = tout;
+                solution.tout.push(t);
+                solution.yout.push(y.clone());
+                next_out += 1;
+            }
+            if next_out == self.tspan.len() {
+                break;
+            }
+            y = advance(rng, t, t_next, y)?;
+            t = t_next;
+
+            // select the candidate proportional to its rate
+            let mut u = rng.gen::<f64>() * total;
+            let mut fired = None;
+            if let Some(i) = select(&rates, &mut u) {
+                fired = Some(i);
+                (self.constant[i].affect)(&mut y);
+            } else {
+                let bounds: Vec<f64> = self.variable.iter().map(|jump| jump.bound).collect();
+                if let Some(j) = select(&bounds, &mut u) {
+                    let jump = &self.variable[j];
+                    let rate = (jump.rate)(t, &y);
+                    if rate > jump.bound {
+                        return Err(OdeError::RateBoundExceeded {
+                            at: t,
+                            rate,
+                            bound: jump.bound,
+                        });
+                    }
+                    // thinning
+                    if rng.gen::<f64>() * jump.bound < rate {
+                        fired = Some(self.constant.len() + j);
+                        (jump.affect)(&mut y);
+                    }
+                }
+            }
+            if let Some(index) = fired {
+                solution.jumps.push((t, index));
+                rates = constant_rates(t, &y);
+            }
+        }
+
+        Ok(solution)
+    }
+}
+
+/// The index of the weight that `u ∈ [0, Σ weights)` falls on, otherwise the sum of the
+/// weights is subtracted from `u`.
+fn select(weights: &[f64], u: &mut f64) -> Option<usize> {
+    for (i, w) in weights.iter().enumerate() {
+        if *u < *w {
+            return Some(i);
+        }
+        *u -= w;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn birth_death() {
+        // the stationary distribution of births at rate 10 and deaths at rate `y` is
+        // poisson with mean 10
+        let problem = JumpProblem::builder()
+            .constant_jump(|_t, _y| 10., |y| y[0] += 1.)
+            .constant_jump(|_t, y| y[0], |y| y[0] -= 1.)
+            .init(DVector::from_row_slice(&[0.]))
+            .tspan(vec![0., 20.])
+            .build()
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let paths = 500;
+        let mean = (0..paths)
+            .map(|_| problem.ssa(&mut rng).unwrap().yout[1][0])
+            .sum::<f64>()
+            / paths as f64;
+        assert!((mean - 10.).abs() < 0.5);
+    }
+
+    #[test]
+    fn thinning() {
+        // `x' = 1` drives the rate of the counter `n`, which is poisson with mean
+        // `∫_0^2 t dt = 2`
+        let problem = JumpProblem::builder()
+            .variable_jump(|_t, y| y[0], |y| y[1] += 1., 2.)
+            .init(DVector::from_row_slice(&[0., 0.]))
+            .tspan(vec![0., 2.])
+            .build()
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(2);
+        let paths = 1000;
+        let mut mean = 0.;
+        for _ in 0..paths {
+            let solution = problem
+                .ode(
+                    &mut rng,
+                    |_t, _y| DVector::from_row_slice(&[1., 0.]),
+                    Dop853Options::default(),
+                )
+                .unwrap();
+            assert!((solution.yout[1][0] - 2.).abs() < 1e-10);
+            assert_eq!(solution.jumps.len() as f64, solution.yout[1][1]);
+            mean += solution.yout[1][1] / paths as f64;
+        }
+        assert!((mean - 2.).abs() < 0.15);
+
+        let exceeded = JumpProblem::builder()
+            .variable_jump(|t, _y| t, |_y| {}, 1.)
+            .init(DVector::from_row_slice(&[0.]))
+            .tspan(vec![0., 100.])
+            .build()
+            .unwrap();
+        assert!(exceeded.ssa(&mut rng).is_err());
+    }
+}
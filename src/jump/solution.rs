@@ -0,0 +1,21 @@
+use na::DVector;
+
+/// The solution of a jump process together with the jumps that occurred.
+#[derive(Debug, Clone, Default)]
+pub struct JumpSolution {
+    /// Vector of points at which solutions were obtained
+    pub tout: Vec<f64>,
+    /// solutions at times `tout`, stored as a vector `yout`
+    pub yout: Vec<DVector<f64>>,
+    /// the times of the jumps and the index of the jump that fired, constant rate jumps
+    /// are counted before variable rate jumps
+    pub jumps: Vec<(f64, usize)>,
+}
+
+impl JumpSolution {
+    /// pair each timestep with the corresponding output
+    #[inline]
+    pub fn zipped(self) -> Vec<(f64, DVector<f64>)> {
+        self.tout.into_iter().zip(self.yout).collect()
+    }
+}
@@ -3,7 +3,14 @@
 extern crate derive_builder;
 
 extern crate nalgebra as na;
+// lets the derived `OdeType` impls name this crate from within it
+extern crate self as diffeq;
 
 /// Every equation should hav a Problem type, a solution type, and the same solution handling setup.
+pub mod dae;
+pub mod dde;
 pub mod error;
+pub mod jump;
 pub mod ode;
+pub mod rode;
+pub mod sde;
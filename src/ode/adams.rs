@@ -0,0 +1,86 @@
+use crate::ode::multistep::DifferenceHistory;
+use alga::general::RealField;
+use na::DVector;
+use std::ops::{Add, Mul};
+
+/// Highest order of the Adams–Bashforth predictor. The variable step predictor–corrector
+/// pairs of higher order are not reliably stable.
+pub const MAX_ORDER: usize = 9;
+
+/// Smallest factor a rejected step is reduced by.
+pub const MIN_FACTOR: f64 = 0.2;
+
+/// Largest factor the step size is increased by.
+pub const MAX_FACTOR: f64 = 5.;
+
+/// Coefficients of the Adams methods in backward difference form, c.f. Hairer,
+/// Nørsett & Wanner, "Solving Ordinary Differential Equations I", 1993, III.1.
+///
+/// The explicit Adams–Bashforth method of order `k` predicts
+///
+/// ```latex
+/// p_{n+1} = y_n + h \sum_{j=0}^{k-1} γ_j ∇^j f_n
+/// ```
+///
+/// and the implicit Adams–Moulton method of order `k + 1` corrects the prediction with
+///
+/// ```latex
+/// y_{n+1} = p_{n+1} + h γ_k ∇^k f_{n+1}
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdamsCoeffs {
+    /// `γ_j = 1 - Σ_{i<j} γ_i / (j + 1 - i)`
+    pub gamma: Vec<f64>,
+}
+
+impl Default for AdamsCoeffs {
+    fn default() -> Self {
+        let mut gamma: Vec<f64> = Vec::with_capacity(MAX_ORDER + 1);
+        for j in 0..=MAX_ORDER {
+            let sum = gamma
+                .iter()
+                .enumerate()
+                .fold(0., |acc, (i, g)| acc + g / (j + 1 - i) as f64);
+            gamma.push(1. - sum);
+        }
+        Self { gamma }
+    }
+}
+
+impl AdamsCoeffs {
+    /// The increment `Σ_{j<k} γ_j ∇^j f_n` of the Adams–Bashforth method of order `order`
+    /// from the history of the derivatives.
+    pub fn predictor<T>(&self, history: &DifferenceHistory<T>, order: usize) -> DVector<T>
+    where
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    {
+        history.d[..order]
+            .iter()
+            .enumerate()
+            .fold(DVector::zeros(history.d[0].len()), |acc, (j, dj)| {
+                acc + dj * (T::one() * self.gamma[j])
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adams_coeffs() {
+        let coeffs = AdamsCoeffs::default();
+        let expected = [
+            1.,
+            1. / 2.,
+            5. / 12.,
+            3. / 8.,
+            251. / 720.,
+            95. / 288.,
+            19087. / 60480.,
+        ];
+        for (g, e) in coeffs.gamma.iter().zip(expected.iter()) {
+            assert!((g - e).abs() < 1e-15);
+        }
+    }
+}
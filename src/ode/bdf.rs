@@ -0,0 +1,107 @@
+use crate::error::OdeError;
+use crate::ode::linsolve::LinearSolver;
+use crate::ode::multistep::{rms_norm, DifferenceHistory};
+use crate::ode::newton::{NewtonOptions, NewtonResult, NewtonSolver};
+use crate::ode::solution::Diagnostics;
+use alga::general::RealField;
+use na::{DMatrix, DVector};
+use std::cell::Cell;
+use std::ops::{Add, Mul};
+
+/// Highest order of the backward differentiation formulas.
+pub const MAX_ORDER: usize = 5;
+
+/// Maximum number of newton iterations per step.
+pub const NEWTON_MAXITER: usize = 4;
+
+/// Smallest factor a rejected step is reduced by.
+pub const MIN_FACTOR: f64 = 0.2;
+
+/// Largest factor the step size is increased by.
+pub const MAX_FACTOR: f64 = 10.;
+
+/// Coefficients of the backward differentiation formulas of order `1..=MAX_ORDER`
+/// in the form of Shampine & Reichelt, "The MATLAB ODE Suite", 1997.
+#[derive(Clone, Debug)]
+pub struct BdfCoeffs {
+    /// `γ_k = Σ_{j=1}^k 1/j`
+    pub gamma: Vec<f64>,
+    /// leading coefficients `α_k`, equal to `γ_k` for the plain formulas
+    pub alpha: Vec<f64>,
+    /// error constants `1/(k+1)`
+    pub error_const: Vec<f64>,
+}
+
+impl BdfCoeffs {
+    /// The part `ψ = Σ_{j=1}^k γ_j D_j / α_k` of the formula of order `order` that only
+    /// depends on the history.
+    pub fn psi<T>(&self, history: &DifferenceHistory<T>, order: usize) -> DVector<T>
+    where
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    {
+        history.d[1..=order]
+            .iter()
+            .enumerate()
+            .fold(DVector::zeros(history.d[0].len()), |acc, (j, dj)| {
+                acc + dj * (T::one() * (self.gamma[j + 1] / self.alpha[order]))
+            })
+    }
+}
+
+impl Default for BdfCoeffs {
+    fn default() -> Self {
+        let mut gamma = vec![0.];
+        for k in 1..=MAX_ORDER + 1 {
+            gamma.push(gamma[k - 1] + 1. / k as f64);
+        }
+        let error_const = (0..=MAX_ORDER + 1).map(|k| 1. / (k + 1) as f64).collect();
+
+        Self {
+            alpha: gamma.clone(),
+            gamma,
+            error_const,
+        }
+    }
+}
+
+/// Solves the system `M (y - ypred + ψ) - c f(t, y) = 0` of a BDF step from the
+/// prediction `ypred` with a simplified newton iteration, `lu` solves with the
+/// iteration matrix `M - c J` and `mass` defaults to the identity. The iteration
+/// converges in the norm `scale` of the integration tolerances.
+///
+/// Returns the correction `y - ypred` of the solution from the prediction, or `None` if
+/// the iteration did not converge. The solved linear systems are counted in `stats`.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_bdf_system<F, T, L>(
+    f: &F,
+    t: f64,
+    ypred: &DVector<T>,
+    c: f64,
+    psi: &DVector<T>,
+    mass: Option<&DMatrix<T>>,
+    lu: &L,
+    scale: &[f64],
+    opts: &NewtonOptions,
+    stats: &Cell<Diagnostics>,
+) -> Result<Option<NewtonResult<T>>, OdeError>
+where
+    F: Fn(f64, &DVector<T>) -> DVector<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    L: LinearSolver<T>,
+{
+    let residual = |d: &DVector<T>| {
+        let fy = f(t, &(ypred + d));
+        if fy.iter().any(|fi| !(*fi).into().is_finite()) {
+            return None;
+        }
+        let lhs = match mass {
+            Some(mass) => mass * (psi + d),
+            None => psi + d,
+        };
+        Some(fy * (T::one() * c) - lhs)
+    };
+    let norm = |dy: &DVector<T>| rms_norm(dy, scale);
+
+    let newton = NewtonSolver::new(opts, lu);
+    newton.solve(DVector::zeros(ypred.len()), opts.tol, residual, norm, stats)
+}
@@ -0,0 +1,590 @@
+use std::fmt;
+use std::rc::Rc;
+
+/// A function of the time and state whose roots or sign define events.
+pub type EventFunction<Y> = Rc<dyn Fn(f64, &Y) -> f64>;
+
+/// A function of the time and state that decides whether a discrete event occurs.
+pub type Condition<Y> = Rc<dyn Fn(f64, &Y) -> bool>;
+
+/// The modification of the state at an event.
+pub type Affect<Y> = Rc<dyn Fn(f64, &mut Y)>;
+
+/// The crossing direction of the event function of a [`ContinuousCallback`] that
+/// triggers the callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// The event function changes from negative to positive.
+    Up,
+    /// The event function changes from positive to negative.
+    Down,
+    /// Any sign change of the event function.
+    #[default]
+    Both,
+}
+
+impl Direction {
+    /// Whether the change of the event function from `g0` to `g1` is a crossing in this
+    /// direction, a crossing ends at zero but does not start there.
+    pub fn is_crossing(self, g0: f64, g1: f64) -> bool {
+        let up = g0 < 0. && g1 >= 0.;
+        let down = g0 > 0. && g1 <= 0.;
+        match self {
+            Direction::Up => up,
+            Direction::Down => down,
+            Direction::Both => up || down,
+        }
+    }
+}
+
+/// An event at which the integration is interrupted, defined by the root of an event
+/// function `g(t, y)`.
+///
+/// When `g` changes its sign across an accepted step, the time of the root is located
+/// on the continuous output of the step and the step is truncated there. The `affect`
+/// then modifies the state and the integration either continues from the modified
+/// state or terminates.
+#[derive(Clone)]
+pub struct ContinuousCallback<Y> {
+    condition: EventFunction<Y>,
+    affect: Option<Affect<Y>>,
+    direction: Direction,
+    terminate: bool,
+}
+
+impl<Y> ContinuousCallback<Y> {
+    /// A callback that locates the roots of the event function `condition`, without
+    /// affecting the integration other than stopping at them.
+    pub fn new<C: Fn(f64, &Y) -> f64 + 'static>(condition: C) -> Self {
+        Self {
+            condition: Rc::new(condition),
+            affect: None,
+            direction: Direction::Both,
+            terminate: false,
+        }
+    }
+
+    /// set the modification of the state at an event
+    pub fn affect<A: Fn(f64, &mut Y) + 'static>(mut self, affect: A) -> Self {
+        self.affect = Some(Rc::new(affect));
+        self
+    }
+
+    /// only trigger the callback on crossings in `direction`
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// whether the integration ends at an event
+    pub fn terminate(mut self, terminate: bool) -> Self {
+        self.terminate = terminate;
+        self
+    }
+
+    /// The value of the event function.
+    #[inline]
+    pub fn condition(&self, t: f64, y: &Y) -> f64 {
+        (self.condition)(t, y)
+    }
+
+    /// Applies the modification of the state at an event at `t`, if any.
+    pub fn apply(&self, t: f64, y: &mut Y) {
+        if let Some(affect) = &self.affect {
+            affect(t, y);
+        }
+    }
+
+    /// The crossing direction that triggers the callback.
+    #[inline]
+    pub fn crossing_direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Whether the integration ends at an event.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.terminate
+    }
+}
+
+impl<Y> fmt::Debug for ContinuousCallback<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContinuousCallback")
+            .field("affect", &self.affect.is_some())
+            .field("direction", &self.direction)
+            .field("terminate", &self.terminate)
+            .finish()
+    }
+}
+
+/// An event that is checked after every accepted step.
+///
+/// If the `condition` holds for the state at the end of a step, the `affect` modifies
+/// the state and the integration either continues from the modified state or
+/// terminates. Parameters of the problem can be changed by an `affect` if they are
+/// shared with the problem function, e.g. in a [`Cell`](std::cell::Cell).
+#[derive(Clone)]
+pub struct DiscreteCallback<Y> {
+    condition: Condition<Y>,
+    affect: Option<Affect<Y>>,
+    terminate: bool,
+}
+
+impl<Y> DiscreteCallback<Y> {
+    /// A callback that triggers after the steps at which `condition` holds, without
+    /// affecting the integration.
+    pub fn new<C: Fn(f64, &Y) -> bool + 'static>(condition: C) -> Self {
+        Self {
+            condition: Rc::new(condition),
+            affect: None,
+            terminate: false,
+        }
+    }
+
+    /// set the modification of the state at an event
+    pub fn affect<A: Fn(f64, &mut Y) + 'static>(mut self, affect: A) -> Self {
+        self.affect = Some(Rc::new(affect));
+        self
+    }
+
+    /// whether the integration ends at an event
+    pub fn terminate(mut self, terminate: bool) -> Self {
+        self.terminate = terminate;
+        self
+    }
+
+    /// Whether the callback triggers at `(t, y)`.
+    #[inline]
+    pub fn condition(&self, t: f64, y: &Y) -> bool {
+        (self.condition)(t, y)
+    }
+
+    /// Applies the modification of the state at an event at `t`, if any.
+    pub fn apply(&self, t: f64, y: &mut Y) {
+        if let Some(affect) = &self.affect {
+            affect(t, y);
+        }
+    }
+
+    /// Whether the integration ends at an event.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.terminate
+    }
+}
+
+impl<Y> fmt::Debug for DiscreteCallback<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DiscreteCallback")
+            .field("affect", &self.affect.is_some())
+            .field("terminate", &self.terminate)
+            .finish()
+    }
+}
+
+/// The callbacks of a problem.
+///
+/// At the end of every accepted step the continuous callbacks are handled before the
+/// discrete ones. Continuous callbacks whose roots coincide trigger a single event, at
+/// which all of them are applied in the order they were added, each one to the state
+/// modified by the previous ones. The discrete callbacks follow in the same way.
+#[derive(Clone, Debug)]
+pub struct CallbackSet<Y> {
+    continuous: Vec<ContinuousCallback<Y>>,
+    discrete: Vec<DiscreteCallback<Y>>,
+}
+
+impl<Y> Default for CallbackSet<Y> {
+    fn default() -> Self {
+        Self {
+            continuous: Vec::new(),
+            discrete: Vec::new(),
+        }
+    }
+}
+
+impl<Y> CallbackSet<Y> {
+    /// An empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a continuous callback
+    pub fn continuous(mut self, callback: ContinuousCallback<Y>) -> Self {
+        self.continuous.push(callback);
+        self
+    }
+
+    /// add a discrete callback
+    pub fn discrete(mut self, callback: DiscreteCallback<Y>) -> Self {
+        self.discrete.push(callback);
+        self
+    }
+
+    /// Appends the callbacks of `other`, which are applied after the ones of this set.
+    pub fn merge(mut self, other: CallbackSet<Y>) -> Self {
+        self.continuous.extend(other.continuous);
+        self.discrete.extend(other.discrete);
+        self
+    }
+
+    /// The continuous callbacks in the order they are applied.
+    #[inline]
+    pub fn continuous_callbacks(&self) -> &[ContinuousCallback<Y>] {
+        &self.continuous
+    }
+
+    /// The discrete callbacks in the order they are applied.
+    #[inline]
+    pub fn discrete_callbacks(&self) -> &[DiscreteCallback<Y>] {
+        &self.discrete
+    }
+
+    /// The number of callbacks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.continuous.len() + self.discrete.len()
+    }
+
+    /// Whether the set contains no callbacks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<Y> From<ContinuousCallback<Y>> for CallbackSet<Y> {
+    fn from(callback: ContinuousCallback<Y>) -> Self {
+        CallbackSet::new().continuous(callback)
+    }
+}
+
+impl<Y> From<DiscreteCallback<Y>> for CallbackSet<Y> {
+    fn from(callback: DiscreteCallback<Y>) -> Self {
+        CallbackSet::new().discrete(callback)
+    }
+}
+
+/// Identifies a callback of a [`CallbackSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallbackIndex {
+    /// index of a [`ContinuousCallback`]
+    Continuous(usize),
+    /// index of a [`DiscreteCallback`]
+    Discrete(usize),
+}
+
+/// An event that occurred during an integration.
+#[derive(Debug, Clone)]
+pub struct Event<Y> {
+    /// time of the event
+    pub t: f64,
+    /// the triggered callbacks in the order they were applied
+    pub callbacks: Vec<CallbackIndex>,
+    /// state at the event before it was modified by the callbacks
+    pub y: Y,
+}
+
+/// Locates the root of `g` inside the bracket `[a, b]` with the ITP method of Oliveira
+/// and Takahashi, `g(a)` must be nonzero and `g(b)` of the opposite sign or zero.
+///
+/// The bracket is shrunk to a width of `tol` and the end at which `g` has the sign of
+/// `g(b)` is returned, i.e. the first point past the crossing.
+pub fn itp<G: FnMut(f64) -> f64>(mut g: G, a: f64, b: f64, ga: f64, gb: f64, tol: f64) -> f64 {
+    let (mut a, mut b, mut ga, mut gb) = (a, b, ga, gb);
+    let width = (b - a).abs();
+    if width <= tol || gb == 0. {
+        return b;
+    }
+    // the iterations work on an increasing bracket
+    let dir = (b - a).signum();
+    let (k1, k2) = (0.2 / width, 2.);
+    let nmax = (width / tol).log2().ceil() + 1.;
+    let mut j = 0.;
+    while (b - a).abs() > tol && j <= nmax {
+        let (xa, xb) = (dir * a, dir * b);
+        let half = (xa + xb) / 2.;
+        let r = tol / 2. * 2f64.powf(nmax - j) - (xb - xa) / 2.;
+        let delta = k1 * (xb - xa).powf(k2);
+        // interpolation
+        let falsi = (gb * xa - ga * xb) / (gb - ga);
+        let sigma = (half - falsi).signum();
+        // truncation
+        let x = if delta <= (half - falsi).abs() {
+            falsi + sigma * delta
+        } else {
+            half
+        };
+        // projection
+        let x = if (x - half).abs() <= r {
+            x
+        } else {
+            half - sigma * r
+        };
+        let x = dir * x;
+        let gx = g(x);
+        if gx * ga > 0. {
+            a = x;
+            ga = gx;
+        } else if gx == 0. {
+            return x;
+        } else {
+            b = x;
+            gb = gx;
+        }
+        j += 1.;
+    }
+    b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OdeError;
+    use crate::ode::options::{Abstol, Dense, Dop853Options, ExplicitRkOptions, Points, Reltol};
+    use crate::ode::problem::OdeProblem;
+    use crate::ode::solution::ReturnCode;
+    use crate::ode::Ode;
+    use std::cell::Cell;
+
+    fn opts() -> ExplicitRkOptions {
+        ExplicitRkOptions {
+            reltol: Reltol::from(1e-10),
+            abstol: Abstol::from(1e-10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn bouncing_ball() {
+        const G: f64 = 9.81;
+        // height and velocity of a ball dropped from a height of 1
+        let problem = OdeProblem::builder()
+            .fun(|_t, y: &(f64, f64)| (y.1, -G))
+            .init((1., 0.))
+            .interval(0., 3.)
+            .callback(ContinuousCallback::new(|_t, y: &(f64, f64)| y.0).affect(|_t, y| y.1 *= -0.9))
+            .build()
+            .unwrap();
+        let ops = ExplicitRkOptions {
+            dense: Dense(true),
+            ..opts()
+        };
+        let solution = problem.tsit5(ops).unwrap();
+
+        // the impacts, the ball bounces back with 90% of the impact velocity
+        let mut impacts = vec![(2. / G).sqrt()];
+        let mut v = (2. * G).sqrt();
+        while impacts.len() < 4 {
+            v *= 0.9;
+            impacts.push(impacts.last().unwrap() + 2. * v / G);
+        }
+        for &t in &impacts {
+            // the state before and after each impact is stored
+            let i = solution
+                .tout
+                .iter()
+                .position(|tout| (tout - t).abs() < 1e-9)
+                .unwrap();
+            assert_eq!(solution.tout[i], solution.tout[i + 1]);
+            assert!(solution.yout[i].1 < 0. && solution.yout[i + 1].1 > 0.);
+            assert!(solution.yout[i].0.abs() < 1e-9);
+        }
+        let (dt, v) = (3. - impacts[3], 0.9 * v);
+        let h = solution.last().unwrap().y.0;
+        assert!((h - (v * dt - G / 2. * dt * dt)).abs() < 1e-8);
+        // the continuous output follows the bounces
+        let t = (impacts[0] + impacts[1]) / 2.;
+        assert!((solution.at(t).unwrap().1).abs() < 1e-8);
+    }
+
+    #[test]
+    fn dosing() {
+        // `y' = -k y`, the rate doubles with the first dose
+        let k = Rc::new(Cell::new(1.));
+        let rate = Rc::clone(&k);
+        let reset = Rc::clone(&k);
+        let problem = OdeProblem::builder()
+            .fun(move |_t, y: &f64| -rate.get() * y)
+            .init(1.)
+            .interval(0., 10.)
+            .discrete_callback(
+                DiscreteCallback::new(|t, y: &f64| *y < 0.5 && t < 1.).affect(move |_t, y| {
+                    *y += 1.;
+                    k.set(2.);
+                }),
+            )
+            .callback(
+                ContinuousCallback::new(|_t, y: &f64| y - 0.3)
+                    .direction(Direction::Down)
+                    .terminate(true),
+            )
+            .build()
+            .unwrap();
+        let solution = problem.tsit5(opts()).unwrap();
+        assert_eq!(ReturnCode::Terminated, solution.retcode);
+
+        // the dose is given once, after the first step below 0.5
+        let doses: Vec<_> = (1..solution.len())
+            .filter(|&i| solution.yout[i] > solution.yout[i - 1])
+            .map(|i| i - 1)
+            .collect();
+        assert_eq!(1, doses.len());
+        let (td, y) = (solution.tout[doses[0]], solution.yout[doses[0]]);
+        assert!((y - (-td).exp()).abs() < 1e-9 && y < 0.5);
+        assert_eq!(td, solution.tout[doses[0] + 1]);
+        let dosed = solution.yout[doses[0] + 1];
+        assert_eq!(y + 1., dosed);
+        // and the integration stops at `y = 0.3` with the doubled rate
+        let tend = td + (dosed / 0.3).ln() / 2.;
+        assert!((solution.last().unwrap().t - tend).abs() < 1e-9);
+
+        // the same callbacks end the integration with DOP853
+        reset.set(1.);
+        let solution = problem
+            .dop853(Dop853Options {
+                reltol: Reltol::from(1e-10),
+                abstol: Abstol::from(1e-10),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ReturnCode::Terminated, solution.retcode);
+        assert_eq!(2., reset.get());
+        assert!((solution.yout.last().unwrap() - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn simultaneous_events() {
+        // `y' = -y`, both event functions vanish at `y = 0.5`
+        let callbacks = CallbackSet::new()
+            .discrete(DiscreteCallback::new(|_t, y: &f64| *y > 2.5).affect(|_t, y| *y -= 1.))
+            .continuous(ContinuousCallback::new(|_t, y: &f64| y - 0.5).affect(|_t, y| *y += 1.))
+            .merge(
+                ContinuousCallback::new(|_t, y: &f64| 2. * y - 1.)
+                    .affect(|_t, y| *y *= 2.)
+                    .into(),
+            );
+        assert_eq!(3, callbacks.len());
+        let problem = OdeProblem::builder()
+            .fun(|_t, y: &f64| -y)
+            .init(1.)
+            .interval(0., 1.)
+            .callbacks(callbacks)
+            .build()
+            .unwrap();
+        let mut integrator = problem.tsit5_integrator(opts()).unwrap();
+        while integrator.event().is_none() {
+            assert!(integrator.step().unwrap());
+        }
+        // a single event at which the continuous callbacks are applied before the
+        // discrete one, each to the state modified by the previous ones
+        let event = integrator.event().unwrap();
+        assert_eq!(
+            vec![
+                CallbackIndex::Continuous(0),
+                CallbackIndex::Continuous(1),
+                CallbackIndex::Discrete(0)
+            ],
+            event.callbacks
+        );
+        assert!((event.t - 2f64.ln()).abs() < 1e-9);
+        assert!((event.y - 0.5).abs() < 1e-12);
+        assert!((integrator.y() - 2.).abs() < 1e-12);
+        while integrator.step().unwrap() {
+            assert!(integrator.event().is_none());
+        }
+        assert!((integrator.y() - 2. * (2f64.ln() - 1.).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn terminate() {
+        // `y' = -y` until `y = 0.5`
+        let problem = OdeProblem::builder()
+            .fun(|_t, y: &f64| -y)
+            .init(1.)
+            .tspan_linspace(0., 2., 11)
+            .callback(ContinuousCallback::new(|_t, y: &f64| y - 0.5).terminate(true))
+            .build()
+            .unwrap();
+        let ops = ExplicitRkOptions {
+            points: Points::Specified,
+            ..opts()
+        };
+        let solution = problem.tsit5(ops).unwrap();
+        assert_eq!(ReturnCode::Terminated, solution.retcode);
+        assert!(solution.is_success());
+        let ln2 = 2f64.ln();
+        assert_eq!(problem.tspan()[..4], solution.tout[..4]);
+        assert_eq!(5, solution.len());
+        assert!((solution.tout[4] - ln2).abs() < 1e-9);
+        assert!((solution.yout[4] - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn unsupported_solvers() {
+        let problem = OdeProblem::builder()
+            .fun(|_t, y: &f64| -y)
+            .init(1.)
+            .interval(0., 2.)
+            .callback(ContinuousCallback::new(|_t, y: &f64| y - 0.5).terminate(true))
+            .build()
+            .unwrap();
+        // the solvers that are not driven by an integrator would integrate past the event
+        for method in [
+            Ode::Feuler,
+            Ode::Ssprk104,
+            Ode::Lsrk33,
+            Ode::Beuler,
+            Ode::Rosenbrock23,
+            Ode::Rodas5,
+            Ode::Ode4ss,
+            Ode::Sdirk2,
+            Ode::Kvaerno4,
+            Ode::TrapezoidAdapt,
+            Ode::Radau5,
+            Ode::AutoTsit5,
+            Ode::Bdf,
+            Ode::Adams,
+            Ode::Abm,
+            Ode::Gbs,
+        ] {
+            let err = problem
+                .clone()
+                .solve(method, Default::default())
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                OdeError::UnsupportedSolver {
+                    feature: "callbacks",
+                    ..
+                }
+            ));
+        }
+        let solution = problem
+            .clone()
+            .solve(Ode::Tsit5, Default::default())
+            .unwrap();
+        assert_eq!(ReturnCode::Terminated, solution.retcode);
+    }
+
+    #[test]
+    fn itp_root() {
+        let g = |t: f64| t.cos() - t;
+        let root = 0.739_085_133_215_160_6;
+        let tol = 1e-14;
+        let t = itp(g, 0., 1., g(0.), g(1.), tol);
+        assert!(g(t) <= 0. && (t - root).abs() <= tol);
+        // the returned end is past the crossing in the reversed bracket as well
+        let t = itp(g, 1., 0., g(1.), g(0.), tol);
+        assert!(g(t) >= 0. && (t - root).abs() <= tol);
+    }
+
+    #[test]
+    fn directions() {
+        assert!(Direction::Up.is_crossing(-1., 0.));
+        assert!(!Direction::Up.is_crossing(1., -1.));
+        assert!(Direction::Down.is_crossing(1., -1.));
+        assert!(!Direction::Both.is_crossing(0., 1.));
+    }
+}
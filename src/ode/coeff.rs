@@ -1,6 +1,6 @@
 use crate::ode::types::OdeType;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct CoefficientMap<Y: OdeType> {
     inner: Vec<CoefficientPoint<Y>>,
 }
@@ -14,20 +14,26 @@ impl<Y: OdeType> CoefficientMap<Y> {
     }
 
     #[inline]
-    pub fn ks(&self) -> Ks<Y> {
+    pub fn ks(&self) -> Ks<'_, Y> {
         Ks {
             inner: self.inner.iter(),
         }
     }
 
     #[inline]
-    pub fn ys(&self) -> Ks<Y> {
+    pub fn ys(&self) -> Ks<'_, Y> {
         Ks {
             inner: self.inner.iter(),
         }
     }
 }
 
+impl<Y: OdeType> Default for CoefficientMap<Y> {
+    fn default() -> Self {
+        Self { inner: Vec::new() }
+    }
+}
+
 impl<Y: OdeType> std::ops::Deref for CoefficientMap<Y> {
     type Target = Vec<CoefficientPoint<Y>>;
 
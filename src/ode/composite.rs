@@ -0,0 +1,460 @@
+use crate::error::OdeError;
+use crate::ode::coeff::CoefficientPoint;
+use crate::ode::implicit::{ImplicitTrial, Interpolant, RosenbrockDense};
+use crate::ode::jacobian::JacobianCache;
+use crate::ode::linsolve::{Factorization, LinearSolver};
+use crate::ode::problem::OdeProblem;
+use crate::ode::rosenbrock::RodasCoeffs;
+use crate::ode::runge_kutta::{ButcherTableau, EmbeddedPair};
+use crate::ode::stiffness::{StiffnessDetector, NONSTIFF_STEPS};
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::allocator::Allocator;
+use na::{DMatrix, DVector, DefaultAllocator, Dim, U1, U2};
+use std::ops::{Add, Mul};
+
+/// A one step method that computes trial steps with a local error estimate, the step
+/// size control and the output are left to
+/// [`OdeProblem::oderk_adapt_method`].
+///
+/// Besides the trial steps, a method may report whether the problem appears unsuited to
+/// it, which a [`Composite`] uses to switch between a non-stiff and a stiff method.
+pub trait StepMethod<Y: OdeType> {
+    /// The order of the error estimate.
+    fn order(&self) -> usize;
+
+    /// Computes a trial step of size `dt` from `(t, y)`, `f0` is the RHS at `(t, y)`.
+    fn trial(&mut self, t: f64, dt: f64, y: &Y, f0: &Y) -> Result<ImplicitTrial<Y>, OdeError>;
+
+    /// Informs the method that its last trial, a step of size `dt`, was accepted and
+    /// ends at `(t, y1)` with the RHS `f1`.
+    fn accept(&mut self, _t: f64, _dt: f64, _y1: &Y, _f1: &Y) {}
+
+    /// Forgets the previous steps, e.g. after another method took over.
+    fn reset(&mut self) {}
+
+    /// Whether the accepted steps are limited by stability rather than accuracy.
+    fn is_stiff(&self) -> bool {
+        false
+    }
+
+    /// The estimate of `|h λ|` of a step of size `h` up to which the method is stable,
+    /// `None` if the method is not limited by stability.
+    fn stability_boundary(&self) -> Option<f64> {
+        None
+    }
+
+    /// An upper bound of the magnitude of the eigenvalues of the jacobian at the last
+    /// trial, if the method computed the jacobian.
+    fn spectral_radius(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// A method given by a closure that computes the trial steps.
+pub(crate) struct FnMethod<St> {
+    pub(crate) order: usize,
+    pub(crate) step: St,
+}
+
+impl<Y, St> StepMethod<Y> for FnMethod<St>
+where
+    Y: OdeType,
+    St: FnMut(f64, f64, &Y) -> Result<ImplicitTrial<Y>, OdeError>,
+{
+    fn order(&self) -> usize {
+        self.order
+    }
+
+    fn trial(&mut self, t: f64, dt: f64, y: &Y, _f0: &Y) -> Result<ImplicitTrial<Y>, OdeError> {
+        (self.step)(t, dt, y)
+    }
+}
+
+/// An embedded explicit Runge–Kutta method that detects stiffness with a
+/// [`StiffnessDetector`], if the tableau allows for it.
+pub struct ExplicitMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    problem: &'a OdeProblem<F, Y, G, M>,
+    btab: ButcherTableau<S>,
+    /// the weights of the solution and of its error estimate, trials fail without them
+    pair: Option<EmbeddedPair>,
+    /// whether the last stage of a step is the RHS at its end
+    fsal: bool,
+    detector: Option<StiffnessDetector>,
+    /// the stage of the last trial that is compared by the detector
+    stage: Option<CoefficientPoint<Y>>,
+    stiff: bool,
+}
+
+impl<'a, F, Y, G, M, S> ExplicitMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    /// The method of the embedded tableau `btab` for `problem`.
+    pub fn new(problem: &'a OdeProblem<F, Y, G, M>, btab: ButcherTableau<S>) -> Self {
+        Self {
+            problem,
+            detector: StiffnessDetector::from_tableau(&btab),
+            fsal: btab.is_first_same_as_last(),
+            pair: btab.embedded_pair().ok(),
+            btab,
+            stage: None,
+            stiff: false,
+        }
+    }
+}
+
+impl<'a, F, Y, G, M, S, T> StepMethod<Y> for ExplicitMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    fn order(&self) -> usize {
+        self.btab.order().min()
+    }
+
+    fn trial(&mut self, t: f64, dt: f64, y: &Y, f0: &Y) -> Result<ImplicitTrial<Y>, OdeError> {
+        let init = CoefficientPoint::new(f0.clone(), y.clone());
+        let coeffs = self.problem.calc_coefficients(&self.btab, t, init, dt);
+        let pair = self.pair.as_ref().ok_or_else(OdeError::not_embedded)?;
+        let (ytrial, err) = pair.step(dt, y, coeffs.ks());
+        self.stage = self
+            .detector
+            .as_ref()
+            .map(|detector| coeffs[detector.stage()].clone());
+        Ok(ImplicitTrial {
+            y: ytrial,
+            err,
+            interpolant: None,
+            f1: if self.fsal {
+                coeffs.last().map(|stage| stage.k.clone())
+            } else {
+                None
+            },
+        })
+    }
+
+    fn accept(&mut self, _t: f64, dt: f64, y1: &Y, f1: &Y) {
+        if let (Some(detector), Some(stage)) = (self.detector.as_mut(), &self.stage) {
+            self.stiff = detector.update(dt, y1, f1, stage);
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Some(detector) = self.detector.as_mut() {
+            detector.reset();
+        }
+        self.stiff = false;
+    }
+
+    fn is_stiff(&self) -> bool {
+        self.stiff
+    }
+
+    fn stability_boundary(&self) -> Option<f64> {
+        self.detector.as_ref().map(StiffnessDetector::boundary)
+    }
+}
+
+/// A stiffly accurate Rosenbrock method of the Rodas family, c.f.
+/// [`OdeProblem::oderodas`].
+pub struct RosenbrockMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+    S: Dim,
+    DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
+{
+    problem: &'a OdeProblem<F, Y, G, M>,
+    coeffs: RodasCoeffs<S>,
+    order: usize,
+    /// the jacobian of the last trial, reused by the retry of a rejected step
+    jac: JacobianCache<Y>,
+    /// the infinity norm of the last jacobian
+    radius: Option<f64>,
+}
+
+impl<'a, F, Y, G, M, S> RosenbrockMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+    S: Dim,
+    DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
+{
+    /// The method `coeffs` for `problem`, `order` is the order of its error estimate.
+    pub fn new(problem: &'a OdeProblem<F, Y, G, M>, coeffs: RodasCoeffs<S>, order: usize) -> Self {
+        Self {
+            problem,
+            coeffs,
+            order,
+            jac: JacobianCache::default(),
+            radius: None,
+        }
+    }
+}
+
+impl<'a, F, Y, G, M, S, T> StepMethod<Y> for RosenbrockMethod<'a, F, Y, G, M, S>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+    S: Dim,
+    DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
+{
+    fn order(&self) -> usize {
+        self.order
+    }
+
+    fn trial(&mut self, t: f64, dt: f64, y: &Y, _f0: &Y) -> Result<ImplicitTrial<Y>, OdeError> {
+        let (problem, coeffs) = (self.problem, &self.coeffs);
+        let s = coeffs.nstages();
+        let n = y.dof();
+        let mass = problem.mass_matrix(t, y);
+        let jac = self.jac.at(t, y, |t, y| problem.jacobian(t, y))?;
+        self.radius = jac
+            .row_iter()
+            .map(|row| row.iter().map(|x| x.abs().into()).sum::<f64>())
+            .fold(None, |radius, sum| Some(sum.max(radius.unwrap_or(0.))));
+        // W = lu( M/(dt*gamma) - J )
+        let w = Factorization::dense(
+            &mass * (T::one() * (1. / (dt * coeffs.gamma))) - jac,
+            problem.linear_solver(),
+        )?;
+        problem.count(|stats| stats.num_decomp += 1);
+        let dfdt = problem.tgrad(t, y);
+
+        let to_y = |v: &DVector<T>| {
+            let mut x = y.clone();
+            for d in 0..n {
+                x.insert(d, v[d]);
+            }
+            x
+        };
+
+        let mut us: Vec<DVector<T>> = Vec::with_capacity(s);
+        let mut ystage = y.clone();
+        for i in 0..s {
+            ystage = y.clone();
+            for (j, u) in us.iter().enumerate() {
+                for d in 0..n {
+                    *ystage.get_mut(d) += u[d] * coeffs.a[(i, j)];
+                }
+            }
+            let fi = problem.rhs(t + coeffs.alpha[i] * dt, &ystage);
+            let mut rhs = DVector::from_iterator(
+                n,
+                (0..n).map(|d| fi.get(d) + dfdt.get(d) * (coeffs.gammas[i] * dt)),
+            );
+            let coupling = us
+                .iter()
+                .enumerate()
+                .fold(DVector::zeros(n), |acc, (j, u)| {
+                    acc + u * (T::one() * (coeffs.c[(i, j)] / dt))
+                });
+            rhs += &mass * coupling;
+            us.push(w.solve(&rhs).ok_or(OdeError::InvalidMatrix)?);
+            problem.count(|stats| stats.num_linsolve += 1);
+        }
+
+        // the argument of the last stage is the embedded solution
+        let err = to_y(&us[s - 1]);
+        let ytrial = ystage.sum(&err);
+
+        let dense = |col: usize| {
+            to_y(
+                &us.iter()
+                    .enumerate()
+                    .fold(DVector::zeros(n), |acc, (j, u)| {
+                        acc + u * (T::one() * coeffs.dense[(j, col)])
+                    }),
+            )
+        };
+
+        Ok(ImplicitTrial {
+            interpolant: Some(Interpolant::Rosenbrock(RosenbrockDense {
+                y1: ytrial.clone(),
+                k1: dense(0),
+                k2: dense(1),
+            })),
+            y: ytrial,
+            err,
+            f1: None,
+        })
+    }
+
+    fn spectral_radius(&self) -> Option<f64> {
+        self.radius
+    }
+}
+
+/// Switches between a non-stiff method `A` and a stiff method `B`, starting with the
+/// non-stiff one, c.f. Petzold, "Automatic selection of methods for solving stiff and
+/// nonstiff systems of ordinary differential equations", 1983.
+///
+/// The stiff method takes over once the non-stiff one reports stiffness. The non-stiff
+/// method takes over again after [`NONSTIFF_STEPS`] consecutive steps whose size times
+/// the spectral radius of the jacobian is within its stability boundary. The state and
+/// the step size carry over to the new method.
+#[derive(Debug, Clone)]
+pub struct Composite<A, B> {
+    nonstiff: A,
+    stiff: B,
+    /// whether the stiff method is in use
+    use_stiff: bool,
+    /// the number of consecutive steps of the stiff method that are stable for the
+    /// non-stiff method
+    stable: usize,
+    switches: Vec<f64>,
+}
+
+impl<A, B> Composite<A, B> {
+    pub fn new(nonstiff: A, stiff: B) -> Self {
+        Self {
+            nonstiff,
+            stiff,
+            use_stiff: false,
+            stable: 0,
+            switches: Vec::new(),
+        }
+    }
+
+    /// Whether the stiff method is in use.
+    #[inline]
+    pub fn uses_stiff(&self) -> bool {
+        self.use_stiff
+    }
+
+    /// The times at which the methods were switched, alternately to the stiff and back
+    /// to the non-stiff method.
+    #[inline]
+    pub fn switches(&self) -> &[f64] {
+        &self.switches
+    }
+}
+
+impl<Y, A, B> StepMethod<Y> for Composite<A, B>
+where
+    Y: OdeType,
+    A: StepMethod<Y>,
+    B: StepMethod<Y>,
+{
+    fn order(&self) -> usize {
+        if self.use_stiff {
+            self.stiff.order()
+        } else {
+            self.nonstiff.order()
+        }
+    }
+
+    fn trial(&mut self, t: f64, dt: f64, y: &Y, f0: &Y) -> Result<ImplicitTrial<Y>, OdeError> {
+        if self.use_stiff {
+            self.stiff.trial(t, dt, y, f0)
+        } else {
+            self.nonstiff.trial(t, dt, y, f0)
+        }
+    }
+
+    fn accept(&mut self, t: f64, dt: f64, y1: &Y, f1: &Y) {
+        if self.use_stiff {
+            self.stiff.accept(t, dt, y1, f1);
+            let stable = match (
+                self.stiff.spectral_radius(),
+                self.nonstiff.stability_boundary(),
+            ) {
+                (Some(radius), Some(boundary)) => dt.abs() * radius <= boundary,
+                _ => false,
+            };
+            self.stable = if stable { self.stable + 1 } else { 0 };
+            if self.stable >= NONSTIFF_STEPS {
+                self.use_stiff = false;
+                self.nonstiff.reset();
+                self.switches.push(t);
+            }
+        } else {
+            self.nonstiff.accept(t, dt, y1, f1);
+            if self.nonstiff.is_stiff() {
+                self.use_stiff = true;
+                self.stable = 0;
+                self.stiff.reset();
+                self.switches.push(t);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.nonstiff.reset();
+        self.stiff.reset();
+        self.use_stiff = false;
+        self.stable = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::ImplicitOptions;
+    use crate::ode::options::{Abstol, Reltol};
+
+    #[test]
+    fn switching() {
+        // `y' = -λ(t) (y - cos t) - sin t` with the solution `cos t`, stiff while
+        // `λ(t) = 1e4 exp(-2t)` is large
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 2)
+            .fun(|t, y: &f64| -1e4 * (-2. * t).exp() * (y - t.cos()) - t.sin())
+            .init(1.)
+            .build()
+            .unwrap();
+        let opts = ImplicitOptions {
+            reltol: Reltol::from(1e-6),
+            abstol: Abstol::from(1e-6),
+            ..Default::default()
+        };
+        let mut method = Composite::new(
+            ExplicitMethod::new(&problem, ButcherTableau::tsit5()),
+            RosenbrockMethod::new(&problem, RodasCoeffs::rodas5(), 4),
+        );
+        let solution = problem
+            .oderk_adapt_method(opts.clone(), &mut method)
+            .unwrap();
+        let switches = method.switches();
+        assert_eq!(2, switches.len());
+        assert!(switches[0] < 0.1);
+        assert!(switches[1] > 1. && switches[1] < 10.);
+        assert!(!method.uses_stiff());
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(10., t);
+        assert!((y - 10f64.cos()).abs() < 1e-4);
+
+        let auto = problem.auto_tsit5(opts).unwrap();
+        assert_eq!(Some(switches[0]), auto.stiff_at);
+        assert!(auto.stats.num_jac > 0);
+    }
+}
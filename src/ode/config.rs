@@ -0,0 +1,169 @@
+//! Solver configurations read from TOML or YAML files, so that the setup of a simulation
+//! can be versioned outside of the code, e.g.
+//!
+//! ```toml
+//! solver = "radau5"
+//! tspan = [0.0, 10.0]
+//!
+//! [options]
+//! Reltol = 1e-6
+//! Abstol = [1e-8, 1e-6]
+//! Points = "Specified"
+//! Tstops = [2.5, 5.0]
+//! ```
+//!
+//! The options are named and valued as in their string form, c.f. the
+//! [`FromStr`](std::str::FromStr) impl of [`OdeOptionMap`].
+
+use crate::error::OdeError;
+use crate::ode::options::{OdeOption, OdeOptionMap};
+use crate::ode::Ode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// The solver, time span and options of a simulation.
+#[derive(Clone)]
+pub struct SolverConfig {
+    pub solver: Ode,
+    /// The time span, set on the problem with
+    /// [`OdeBuilder::tspan`](crate::ode::problem::OdeBuilder::tspan).
+    pub tspan: Vec<f64>,
+    pub options: OdeOptionMap,
+}
+
+impl SolverConfig {
+    /// Parses a configuration in TOML.
+    pub fn from_toml(s: &str) -> Result<Self, OdeError> {
+        toml::from_str::<RawConfig>(s)
+            .map_err(OdeError::invalid_config)?
+            .parse()
+    }
+
+    /// Parses a configuration in YAML.
+    pub fn from_yaml(s: &str) -> Result<Self, OdeError> {
+        serde_yaml::from_str::<RawConfig>(s)
+            .map_err(OdeError::invalid_config)?
+            .parse()
+    }
+
+    /// Reads the configuration from a `.toml`, `.yaml` or `.yml` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, OdeError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(OdeError::invalid_config)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("yaml") | Some("yml") => Self::from_yaml(&content),
+            _ => Err(OdeError::invalid_config(format!(
+                "unknown configuration format of {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for SolverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SolverConfig")
+            .field("solver", &self.solver)
+            .field("tspan", &self.tspan)
+            .field("options", &self.options.to_string())
+            .finish()
+    }
+}
+
+/// The configuration as written in the file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    solver: String,
+    tspan: Vec<f64>,
+    #[serde(default)]
+    options: BTreeMap<String, RawValue>,
+}
+
+/// The value of an option, converted to its string form.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<f64>),
+}
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RawValue::Bool(value) => write!(f, "{}", value),
+            RawValue::Number(value) => write!(f, "{}", value),
+            RawValue::Text(value) => f.write_str(value),
+            RawValue::List(values) => {
+                let values: Vec<_> = values.iter().map(f64::to_string).collect();
+                f.write_str(&values.join(", "))
+            }
+        }
+    }
+}
+
+impl RawConfig {
+    fn parse(self) -> Result<SolverConfig, OdeError> {
+        let solver = self.solver.parse().map_err(OdeError::invalid_config)?;
+        let mut options = OdeOptionMap::default();
+        for (name, value) in self.options {
+            options.set(format!("{}={}", name, value).parse::<OdeOption>()?);
+        }
+        Ok(SolverConfig {
+            solver,
+            tspan: self.tspan,
+            options,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{AdaptiveOptions, Points, Reltol, Tolerance};
+
+    #[test]
+    fn toml_and_yaml() {
+        let toml = r#"
+            solver = "radau5"
+            tspan = [0.0, 10.0]
+
+            [options]
+            Reltol = 1e-6
+            Abstol = [1e-8, 1e-6]
+            Points = "Specified"
+            MaxIters = 1000
+            Dense = true
+        "#;
+        let yaml = "
+            solver: radau5
+            tspan: [0.0, 10.0]
+            options:
+              Reltol: 1.0e-6
+              Abstol: [1.0e-8, 1.0e-6]
+              Points: Specified
+              MaxIters: 1000
+              Dense: true
+        ";
+        for config in [SolverConfig::from_toml(toml), SolverConfig::from_yaml(yaml)] {
+            let config = config.unwrap();
+            assert!(matches!(config.solver, Ode::Radau5));
+            assert_eq!(vec![0., 10.], config.tspan);
+            let opts = AdaptiveOptions::from_map(&config.options).unwrap();
+            assert_eq!(Reltol::from(1e-6), opts.reltol);
+            assert_eq!(Tolerance::Vector(vec![1e-8, 1e-6]), opts.abstol.0);
+            assert_eq!(Points::Specified, opts.points);
+            assert_eq!(1000, *opts.maxiters);
+            assert!(*opts.dense);
+        }
+
+        let unknown = "solver = \"radau5\"\ntspan = [0.0, 1.0]\n[options]\nTol = 1.0";
+        assert!(SolverConfig::from_toml(unknown).is_err());
+        assert!(SolverConfig::from_toml("solver = \"none\"\ntspan = [0.0, 1.0]").is_err());
+    }
+}
@@ -0,0 +1,360 @@
+use crate::error::OdeError;
+use crate::ode::options::AdaptiveOptions;
+#[cfg(feature = "serde0")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Chooses the size of the next step from the scaled error `err` of the current step,
+/// the step is accepted if `err <= 1`.
+///
+/// Step sizes are signed by the direction of integration.
+pub trait StepController: fmt::Debug {
+    /// The size of the step after an accepted step of size `dt` with error `err`.
+    fn accept(&mut self, dt: f64, err: f64) -> f64;
+
+    /// The size of the step that replaces a rejected step of size `dt` with error `err`.
+    fn reject(&mut self, dt: f64, err: f64) -> f64;
+
+    /// Forgets the history of previous steps, e.g. after the state was changed.
+    fn reset(&mut self);
+}
+
+/// The step size controllers selectable with the
+/// [`Controller`](crate::ode::options::Controller) option.
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControllerType {
+    /// [`PiController`]
+    #[default]
+    Pi,
+    /// [`PidController`]
+    Pid,
+    /// [`H211bController`]
+    H211b,
+    /// [`PredictiveController`]
+    Predictive,
+}
+
+impl ControllerType {
+    /// Creates the controller for an error estimate of order `order`.
+    pub fn controller(self, order: usize, opts: &AdaptiveOptions) -> Box<dyn StepController> {
+        match self {
+            ControllerType::Pi => Box::new(PiController::new(order, opts)),
+            ControllerType::Pid => Box::new(PidController::new(order, opts)),
+            ControllerType::H211b => Box::new(H211bController::new(order, opts)),
+            ControllerType::Predictive => Box::new(PredictiveController::new(order, opts)),
+        }
+    }
+}
+
+impl fmt::Display for ControllerType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControllerType::Pi => write!(f, "Pi"),
+            ControllerType::Pid => write!(f, "Pid"),
+            ControllerType::H211b => write!(f, "H211b"),
+            ControllerType::Predictive => write!(f, "Predictive"),
+        }
+    }
+}
+
+impl FromStr for ControllerType {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "Pi" => Ok(ControllerType::Pi),
+            "Pid" => Ok(ControllerType::Pid),
+            "H211b" => Ok(ControllerType::H211b),
+            "Predictive" => Ok(ControllerType::Predictive),
+            _ => Err(OdeError::invalid_option(s, "unknown controller")),
+        }
+    }
+}
+
+/// The factor `safety * err^-exponent` of an integral controller, which only shrinks
+/// the step.
+#[inline]
+fn reject_factor(safety: f64, err: f64, exponent: f64, qmin: f64) -> f64 {
+    (safety * err.powf(-exponent)).clamp(qmin, 1.)
+}
+
+/// The PI step size controller of Gustafsson, c.f. Hairer & Wanner, Solving Ordinary
+/// Differential Equations II, Section IV.2.
+///
+/// After an accepted step with the scaled error `err` the step size is multiplied by
+/// `safety * err^-beta1 * errold^beta2` limited to `[qmin, qmax]`, where `errold` is the
+/// error of the previous accepted step. The memory of the previous error damps the
+/// oscillations of the step size that cause repeated rejections on mildly stiff
+/// problems. Rejected steps are shrunk with the integral part only and never grow.
+#[derive(Debug, Clone)]
+pub struct PiController {
+    /// exponent of the current error
+    pub beta1: f64,
+    /// exponent of the error of the previous accepted step
+    pub beta2: f64,
+    /// safety factor of the new step size
+    pub safety: f64,
+    /// smallest factor of the step size
+    pub qmin: f64,
+    /// largest factor of the step size
+    pub qmax: f64,
+    /// exponent of the error after a rejected step, `1 / (order + 1)`
+    reject_exponent: f64,
+    /// error of the last accepted step
+    errold: f64,
+}
+
+impl PiController {
+    /// The controller for an error estimate of order `order`, the gains default to
+    /// `beta1 = 0.7 / (order + 1)` and `beta2 = 0.4 / (order + 1)`.
+    pub fn new(order: usize, opts: &AdaptiveOptions) -> Self {
+        let k = (order + 1) as f64;
+        Self {
+            beta1: opts.beta1.as_ref().map_or(0.7 / k, |beta| beta.0),
+            beta2: opts.beta2.as_ref().map_or(0.4 / k, |beta| beta.0),
+            safety: opts.safety.0,
+            qmin: opts.qmin.0,
+            qmax: opts.qmax.0,
+            reject_exponent: 1. / k,
+            errold: 1e-4,
+        }
+    }
+}
+
+impl StepController for PiController {
+    fn accept(&mut self, dt: f64, err: f64) -> f64 {
+        let q = self.safety * err.powf(-self.beta1) * self.errold.powf(self.beta2);
+        self.errold = err.max(1e-4);
+        dt * q.clamp(self.qmin, self.qmax)
+    }
+
+    fn reject(&mut self, dt: f64, err: f64) -> f64 {
+        dt * reject_factor(self.safety, err, self.reject_exponent, self.qmin)
+    }
+
+    fn reset(&mut self) {
+        self.errold = 1e-4;
+    }
+}
+
+/// The PID step size controller of Söderlind, a digital filter of the errors of the
+/// last three steps.
+///
+/// With the inverse errors `e_n = 1 / err_n` the step size is multiplied by
+/// `e_n^(beta1/k) e_(n-1)^(beta2/k) e_(n-2)^(beta3/k)` with `k = order + 1`, smoothed
+/// with the limiter `1 + atan(q - 1)` and limited to `[qmin, qmax]`. The gains default
+/// to the `H312PID` filter `(1/18, 1/9, 1/18)` scaled by `k`, which yields smooth step
+/// size sequences.
+#[derive(Debug, Clone)]
+pub struct PidController {
+    /// gains of the inverse errors of the current and the two previous steps
+    pub beta: [f64; 3],
+    /// safety factor of the new step size
+    pub safety: f64,
+    /// smallest factor of the step size
+    pub qmin: f64,
+    /// largest factor of the step size
+    pub qmax: f64,
+    k: f64,
+    /// inverse errors of the two previous accepted steps
+    errs: [f64; 2],
+}
+
+impl PidController {
+    /// The controller for an error estimate of order `order`.
+    pub fn new(order: usize, opts: &AdaptiveOptions) -> Self {
+        let k = (order + 1) as f64;
+        Self {
+            beta: [k / 18., k / 9., k / 18.],
+            safety: opts.safety.0,
+            qmin: opts.qmin.0,
+            qmax: opts.qmax.0,
+            k,
+            errs: [1., 1.],
+        }
+    }
+}
+
+impl StepController for PidController {
+    fn accept(&mut self, dt: f64, err: f64) -> f64 {
+        let e = 1. / err.max(1e-10);
+        let q = e.powf(self.beta[0] / self.k)
+            * self.errs[0].powf(self.beta[1] / self.k)
+            * self.errs[1].powf(self.beta[2] / self.k);
+        let q = self.safety * (1. + (q - 1.).atan());
+        self.errs = [e, self.errs[0]];
+        dt * q.clamp(self.qmin, self.qmax)
+    }
+
+    fn reject(&mut self, dt: f64, err: f64) -> f64 {
+        dt * reject_factor(self.safety, err, 1. / self.k, self.qmin)
+    }
+
+    fn reset(&mut self) {
+        self.errs = [1., 1.];
+    }
+}
+
+/// The `H211b` step size controller of Söderlind, a low pass filter of the errors and
+/// step sizes with `b = 4`.
+///
+/// The step size is multiplied by `(e_n e_(n-1))^(1/(b k)) (dt_n / dt_(n-1))^(-1/b)`
+/// with the inverse errors `e_n = 1 / err_n` of the last two steps and `k = order + 1`,
+/// which suppresses the step size oscillations of problems at the stability boundary.
+#[derive(Debug, Clone)]
+pub struct H211bController {
+    /// the filter parameter
+    pub b: f64,
+    /// safety factor of the new step size
+    pub safety: f64,
+    /// smallest factor of the step size
+    pub qmin: f64,
+    /// largest factor of the step size
+    pub qmax: f64,
+    k: f64,
+    /// inverse error and size of the previous accepted step
+    previous: Option<(f64, f64)>,
+}
+
+impl H211bController {
+    /// The controller for an error estimate of order `order`.
+    pub fn new(order: usize, opts: &AdaptiveOptions) -> Self {
+        Self {
+            b: 4.,
+            safety: opts.safety.0,
+            qmin: opts.qmin.0,
+            qmax: opts.qmax.0,
+            k: (order + 1) as f64,
+            previous: None,
+        }
+    }
+}
+
+impl StepController for H211bController {
+    fn accept(&mut self, dt: f64, err: f64) -> f64 {
+        let e = 1. / err.max(1e-10);
+        let q = match self.previous {
+            Some((eold, dtold)) => {
+                (e * eold).powf(1. / (self.b * self.k)) * (dt / dtold).powf(-1. / self.b)
+            }
+            // integral control for the first step
+            None => e.powf(1. / self.k),
+        };
+        self.previous = Some((e, dt));
+        dt * (self.safety * q).clamp(self.qmin, self.qmax)
+    }
+
+    fn reject(&mut self, dt: f64, err: f64) -> f64 {
+        dt * reject_factor(self.safety, err, 1. / self.k, self.qmin)
+    }
+
+    fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+/// The predictive step size controller of Gustafsson, as used by Hairer's `RADAU5`.
+///
+/// Besides the step size of integral control, the change of the error between the last
+/// two accepted steps predicts a step size, and the smaller one is taken. This avoids
+/// the rejections that integral control causes on stiff problems when the error grows
+/// with the step size and is the recommended controller of implicit methods.
+#[derive(Debug, Clone)]
+pub struct PredictiveController {
+    /// safety factor of the new step size
+    pub safety: f64,
+    /// smallest factor of the step size
+    pub qmin: f64,
+    /// largest factor of the step size
+    pub qmax: f64,
+    k: f64,
+    /// error and size of the previous accepted step
+    previous: Option<(f64, f64)>,
+}
+
+impl PredictiveController {
+    /// The controller for an error estimate of order `order`.
+    pub fn new(order: usize, opts: &AdaptiveOptions) -> Self {
+        Self {
+            safety: opts.safety.0,
+            qmin: opts.qmin.0,
+            qmax: opts.qmax.0,
+            k: (order + 1) as f64,
+            previous: None,
+        }
+    }
+}
+
+impl StepController for PredictiveController {
+    fn accept(&mut self, dt: f64, err: f64) -> f64 {
+        let err = err.max(1e-10);
+        let mut q = (self.safety * err.powf(-1. / self.k)).clamp(self.qmin, self.qmax);
+        if let Some((errold, dtold)) = self.previous {
+            let predicted = self.safety * dt / dtold * (errold / (err * err)).powf(1. / self.k);
+            q = q.min(predicted.clamp(self.qmin, self.qmax));
+        }
+        self.previous = Some((err.max(1e-2), dt));
+        dt * q
+    }
+
+    fn reject(&mut self, dt: f64, err: f64) -> f64 {
+        dt * reject_factor(self.safety, err, 1. / self.k, self.qmin)
+    }
+
+    fn reset(&mut self) {
+        self.previous = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Controller, ExplicitRkOptions, Reltol};
+    use crate::ode::problem::OdeProblem;
+
+    #[test]
+    fn controllers() {
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        for controller in [
+            ControllerType::Pi,
+            ControllerType::Pid,
+            ControllerType::H211b,
+            ControllerType::Predictive,
+        ] {
+            let ops = ExplicitRkOptions {
+                reltol: Reltol::from(1e-8),
+                abstol: Abstol::from(1e-8),
+                controller: Controller(controller),
+                ..Default::default()
+            };
+            let solution = problem.tsit5(ops).unwrap();
+            let y = solution.last().unwrap().y;
+            assert!((y.0 - 10f64.cos()).abs() < 1e-6, "{}", controller);
+        }
+    }
+
+    #[test]
+    fn pi_factors() {
+        let mut controller = PiController::new(4, &AdaptiveOptions::default());
+        assert!((controller.beta1 - 0.14).abs() < 1e-15);
+        // the step grows at most by qmax and is reduced after a rejection
+        assert_eq!(10., controller.accept(1., 0.));
+        assert!(controller.reject(1., 2.) < 0.9);
+        assert_eq!(0.2, controller.reject(1., 1e10));
+        // a growing error reduces the step more than a steady one
+        let mut growing = PiController::new(4, &AdaptiveOptions::default());
+        growing.accept(1., 0.01);
+        let mut steady = PiController::new(4, &AdaptiveOptions::default());
+        steady.accept(1., 0.5);
+        assert!(growing.accept(1., 0.5) < steady.accept(1., 0.5));
+        assert!(steady.accept(-1., 0.5) < 0.);
+    }
+}
@@ -0,0 +1,754 @@
+use crate::ode::implicit::Interpolant;
+use crate::ode::types::OdeType;
+use na::{DMatrix, DVector};
+
+/// Coefficients of a continuous extension (dense output) of an explicit Runge–Kutta method.
+///
+/// The solution inside a step is approximated by
+///
+/// ```latex
+/// y(t_n + θh) = y_n + h \sum_i b_i(θ) k_i
+/// ```
+///
+/// where the weights `b_i(θ) = \sum_j bi_{ij} θ^{j+1}` are polynomials in `θ ∈ [0, 1]`.
+/// Interpolants of higher order may require additional stages, these are only evaluated
+/// if output inside an accepted step is requested.
+#[derive(Clone, Debug)]
+pub struct DenseCoeffs {
+    /// order of the interpolant
+    pub order: usize,
+    /// rk matrix of the additional stages, one row for each additional stage
+    /// and one column for each stage of the tableau and the preceding additional stages
+    pub a: DMatrix<f64>,
+    /// nodes of the additional stages
+    pub c: DVector<f64>,
+    /// polynomial coefficients of the weights,
+    /// one row per stage and one column per power of `θ`
+    pub bi: DMatrix<f64>,
+}
+
+impl DenseCoeffs {
+    /// the number of additional stages
+    #[inline]
+    pub fn nstages_extra(&self) -> usize {
+        self.c.nrows()
+    }
+
+    /// Evaluates the weights `b_i(θ)` of all stages.
+    pub fn weights(&self, theta: f64) -> DVector<f64> {
+        DVector::from_iterator(
+            self.bi.nrows(),
+            self.bi.row_iter().map(|row| {
+                // horner scheme, no constant term
+                (0..row.ncols())
+                    .rev()
+                    .fold(0., |acc, j| (acc + row[j]) * theta)
+            }),
+        )
+    }
+
+    /// Evaluates the derivatives `b_i'(θ)` of the weights of all stages.
+    pub fn derivative_weights(&self, theta: f64) -> DVector<f64> {
+        DVector::from_iterator(
+            self.bi.nrows(),
+            self.bi.row_iter().map(|row| {
+                (0..row.ncols())
+                    .rev()
+                    .fold(0., |acc, j| acc * theta + row[j] * (j + 1) as f64)
+            }),
+        )
+    }
+
+    /// Evaluates the continuous extension of a step of size `dt` starting at `y0` at `θ`.
+    ///
+    /// `ks` are the stages of the step followed by the additional stages.
+    pub fn interpolate<Y: OdeType>(&self, theta: f64, dt: f64, y0: &Y, ks: &[Y]) -> Y {
+        let mut y = y0.clone();
+        for (k, b) in ks.iter().zip(self.weights(theta).iter()) {
+            y.axpy(b * dt, k);
+        }
+        y
+    }
+
+    /// Evaluates the derivative `Σ_i b_i'(θ) k_i` of the continuous extension of a step
+    /// at `θ`.
+    pub fn derivative<Y: OdeType>(&self, theta: f64, ks: &[Y]) -> Y {
+        let weights = self.derivative_weights(theta);
+        let terms: Vec<_> = weights.iter().copied().zip(ks).collect();
+        let mut dy = ks[0].clone();
+        dy.linear_combination(&terms);
+        dy
+    }
+}
+
+impl DenseCoeffs {
+    /// Continuous extension of order 5 for [`ButcherTableau::vern6`](crate::ode::runge_kutta::ButcherTableau::vern6), requires one
+    /// additional stage at `c = 1/2`.
+    ///
+    /// The weights were obtained by solving the order conditions of the dense output
+    /// up to order 5, using the 4th order continuous extension of the method to
+    /// compute the additional stage.
+    pub fn vern6() -> Self {
+        let a = DMatrix::from_row_slice(
+            1,
+            9,
+            &[
+                -0.00132396817205544,
+                0.,
+                0.,
+                0.3412897798694734,
+                0.16971855897934568,
+                -0.0426084353861265,
+                0.,
+                0.01646203235873866,
+                0.016462032350624187,
+            ],
+        );
+        let bi = DMatrix::from_row_slice(
+            10,
+            5,
+            &[
+                0.9970628963345083,
+                -5.471134065305497,
+                10.951955198874781,
+                -9.26275537793572,
+                2.8192609267143083,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.0066191678744392385,
+                6.639313118078301,
+                -17.762101775955863,
+                16.77181237401127,
+                -5.397380428446051,
+                -0.00969000201350851,
+                6.338844363018864,
+                -22.09450048163065,
+                27.461263456609817,
+                -11.274980217018184,
+                0.7185607788209926,
+                4.324712374913999,
+                -22.745672285978863,
+                40.8915446504504,
+                -18.78374904853496,
+                -35.2376458235427,
+                -36.49705911317987,
+                -26.81717857828657,
+                -49.77526324287694,
+                -28.15597226640998,
+                34.53644620919881,
+                33.17622943690961,
+                43.61506657278044,
+                18.869831423763188,
+                42.16655975876563,
+                -0.011353225779192979,
+                -0.5109060801840752,
+                2.852431148619129,
+                -4.956432921302443,
+                2.6262610786495615,
+                -8.921555749426635e-10,
+                -8.000000034250137,
+                32.00000020157878,
+                -40.000000362718374,
+                16.00000019628087,
+            ],
+        );
+
+        Self {
+            order: 5,
+            a,
+            c: DVector::from_element(1, 0.5),
+            bi,
+        }
+    }
+}
+
+impl DenseCoeffs {
+    /// Continuous extension of order 7 for
+    /// [`ButcherTableau::vern9`](crate::ode::runge_kutta::ButcherTableau::vern9), requires
+    /// two additional stages, the RHS at the solution and at `c = 7/10`.
+    ///
+    /// The weights were obtained by solving the order conditions of the dense output up
+    /// to order 7, together with matching the solution and its derivative at the end of
+    /// the step. The second additional stage is computed with the 6th order continuous
+    /// extension of the method with the first additional stage.
+    pub fn vern9() -> Self {
+        let a = DMatrix::from_row_slice(
+            2,
+            17,
+            &[
+                0.014611976858423152,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.3915211862331339,
+                0.23109325002895065,
+                0.12747667699928525,
+                0.2246434176204158,
+                0.5684352689748513,
+                0.058258715572158275,
+                0.13643174034822156,
+                0.03057013983082797,
+                0.,
+                0.,
+                0.016826158963955437,
+                5.892131654139025e-13,
+                -6.953907984552852e-12,
+                -4.6202100835390836e-11,
+                -6.255163540469855e-14,
+                1.0017739487815403e-10,
+                -1.9079509153696778e-13,
+                -0.2705450398843335,
+                0.22845728141417812,
+                0.12302818300957219,
+                0.22897573566622037,
+                0.39592028220588776,
+                -0.03861984347025951,
+                0.017961854272321286,
+                0.046644646445721506,
+                0.025185533175310124,
+                -0.07383479184511321,
+            ],
+        );
+        let bi = DMatrix::from_row_slice(
+            18,
+            8,
+            &[
+                0.996681921864631,
+                -12.020175143106615,
+                56.83361908384631,
+                -132.80812865486453,
+                164.11960382924494,
+                -102.9257383737059,
+                25.818257680358265,
+                0.0004916332213273515,
+                8.233787511990519e-12,
+                3.533246155001575e-09,
+                -6.305581375209625e-08,
+                2.8950057523150365e-07,
+                -5.448317491598511e-07,
+                4.556984606045062e-07,
+                -1.4088195846704524e-07,
+                2.9005600469224747e-11,
+                2.7829859558840432e-11,
+                9.664744223518646e-09,
+                -1.7019713859387152e-07,
+                7.765845258390054e-07,
+                -1.45609245966095e-06,
+                1.214731734779695e-06,
+                -3.7472171735966024e-07,
+                2.4809127038316308e-12,
+                -7.192936256054488e-11,
+                -1.9357364843976005e-09,
+                1.6210542989680543e-08,
+                -4.194404986672975e-08,
+                4.062488003373339e-08,
+                -1.0095561411446124e-08,
+                -2.842052461639353e-09,
+                5.390656335944212e-11,
+                6.435472384893251e-13,
+                1.504961016599708e-10,
+                -2.5609308878642393e-09,
+                1.1519251004305532e-08,
+                -2.140079967495577e-08,
+                1.7730856859565047e-08,
+                -5.439111516772984e-09,
+                -4.054331760465188e-13,
+                -1.9288540298279677e-11,
+                -1.0996875688517781e-08,
+                1.9670354890246494e-07,
+                -9.025610083147413e-07,
+                1.6983486590969568e-06,
+                -1.420691649568464e-06,
+                4.3917993696009276e-07,
+                3.667715250702008e-11,
+                -4.2044735507754315e-13,
+                9.989580996847313e-11,
+                -1.9493561875905488e-09,
+                9.213012861459551e-09,
+                -1.765238897592471e-08,
+                1.4966323188093943e-08,
+                -4.677143869708843e-09,
+                7.762105721169301e-14,
+                -0.25740971374530486,
+                1.6672219732251279,
+                -25.45277393549001,
+                103.08921047950116,
+                -178.15667675872726,
+                139.4456012543596,
+                -40.84777780609056,
+                0.12108332073411608,
+                0.03271748893237424,
+                1.0360102476198427,
+                -20.547792712507494,
+                99.80778959856546,
+                -186.71685047854726,
+                152.43451949505666,
+                -45.80702029420991,
+                -0.008280094880722233,
+                0.0075805404655521795,
+                14.714346998948189,
+                -92.68664516340908,
+                241.42042711614673,
+                -315.20958293702705,
+                204.17841534034665,
+                -52.29589687810763,
+                -0.0011683403640687369,
+                -0.011981652797832296,
+                -3.782136744737188,
+                53.43878224444824,
+                -184.83447478646121,
+                279.8649501602175,
+                -198.42439520929568,
+                53.97176724051379,
+                0.002132165732798449,
+                0.2318491550783782,
+                1.5163200167228772,
+                -20.46332690565812,
+                87.4508373748747,
+                -153.72851872962733,
+                121.84370268638713,
+                -36.161946189187695,
+                -0.12048213961508565,
+                0.011813916309893365,
+                -1.6459277756966741,
+                7.3568384734504875,
+                -6.53055372053205,
+                -13.749736590058044,
+                26.240139498495285,
+                -11.634107747352637,
+                0.009792660955897082,
+                -0.014563307702016427,
+                2.0822737809001906,
+                -20.468342240910573,
+                77.1957320279612,
+                -136.3925164825584,
+                113.6982756649015,
+                -35.960464398121026,
+                -0.003963304122663907,
+                -0.005696413505973599,
+                -0.8653335840480814,
+                9.32309038936575,
+                -30.646060089265266,
+                43.207287815628625,
+                -27.194597434201256,
+                6.212557349225804,
+                -0.0006778933687748201,
+                0.009008065093125763,
+                -1.6800303019641167,
+                14.670683154901639,
+                -46.73381036942235,
+                68.59397100536498,
+                -47.322068411190834,
+                12.46117486560073,
+                0.0010719916168273613,
+                -1.253121807411589e-11,
+                2.0355660022641353,
+                -18.116645870821074,
+                52.81072516986849,
+                -64.78011781997857,
+                32.41681360239639,
+                -4.3663410837228644,
+                6.0316196481835505e-12,
+                7.462832855652252e-11,
+                -3.0581354706434616,
+                56.112513507633096,
+                -260.2216942886846,
+                492.94818728707173,
+                -414.39066838588974,
+                128.60979735047576,
+                -3.737454790098127e-11,
+            ],
+        );
+
+        Self {
+            order: 7,
+            a,
+            c: DVector::from_row_slice(&[1., 0.7]),
+            bi,
+        }
+    }
+}
+
+impl DenseCoeffs {
+    /// Continuous extension of order 4 for
+    /// [`ButcherTableau::dopri5`](crate::ode::runge_kutta::ButcherTableau::dopri5) without
+    /// additional stages, c.f. Hairer, Nørsett & Wanner, "Solving Ordinary Differential
+    /// Equations I", 1993, p. 192.
+    ///
+    /// ```latex
+    /// y(t_n + θh) = y_n + θ (Δ + (1 - θ) (h k_1 - Δ + θ (2Δ - h k_1 - h k_7
+    ///     + (1 - θ) h \sum_i d_i k_i)))
+    /// ```
+    ///
+    /// with `Δ = y_{n+1} - y_n`.
+    pub fn dopri5() -> Self {
+        let b = [
+            35. / 384.,
+            0.,
+            500. / 1113.,
+            125. / 192.,
+            -2187. / 6784.,
+            11. / 84.,
+            0.,
+        ];
+        let d = [
+            -12_715_105_075. / 11_282_082_432.,
+            0.,
+            87_487_479_700. / 32_700_410_799.,
+            -10_690_763_975. / 1_880_347_072.,
+            701_980_252_875. / 199_316_789_632.,
+            -1_453_857_185. / 822_651_844.,
+            69_997_945. / 29_380_423.,
+        ];
+        let mut bi = DMatrix::zeros(7, 4);
+        for i in 0..7 {
+            let first = if i == 0 { 1. } else { 0. };
+            let last = if i == 6 { 1. } else { 0. };
+            // expand the nested form in powers of θ
+            let c3 = first - b[i];
+            let c4 = 2. * b[i] - first - last;
+            bi[(i, 0)] = b[i] + c3;
+            bi[(i, 1)] = c4 - c3 + d[i];
+            bi[(i, 2)] = -c4 - 2. * d[i];
+            bi[(i, 3)] = d[i];
+        }
+
+        Self {
+            order: 4,
+            a: DMatrix::zeros(0, 7),
+            c: DVector::zeros(0),
+            bi,
+        }
+    }
+}
+
+impl DenseCoeffs {
+    /// Continuous extension of order 4 for
+    /// [`ButcherTableau::tsit5`](crate::ode::runge_kutta::ButcherTableau::tsit5) without
+    /// additional stages, c.f. Ch. Tsitouras, "Runge–Kutta pairs of order 5(4) satisfying
+    /// only the first column simplifying assumption", 2011.
+    pub fn tsit5() -> Self {
+        let bi = DMatrix::from_row_slice(
+            7,
+            4,
+            &[
+                1.,
+                -2.763706197274826,
+                2.9132554618219126,
+                -1.0530884977290216,
+                0.,
+                0.13169999999999998,
+                -0.2234,
+                0.1017,
+                0.,
+                3.930296236894751,
+                -5.941033872131505,
+                2.490627285651253,
+                0.,
+                -12.411077166933676,
+                30.33818863028232,
+                -16.548102889244902,
+                0.,
+                37.50931341651104,
+                -88.1789048947664,
+                47.37952196281928,
+                0.,
+                -27.896526289197286,
+                65.09189467479368,
+                -34.87065786149661,
+                0.,
+                1.5,
+                -4.,
+                2.5,
+            ],
+        );
+
+        Self {
+            order: 4,
+            a: DMatrix::zeros(0, 7),
+            c: DVector::zeros(0),
+            bi,
+        }
+    }
+}
+
+/// The continuous output of a single accepted step of size `dt` from `y_0`.
+#[derive(Clone, Debug)]
+pub enum StepInterpolant<Y> {
+    /// The polynomial `y(t + θ dt) = y_0 + \sum_j c_j θ^{j+1}` with the coefficients `c_j`.
+    Polynomial(Vec<Y>),
+    /// The continuous output of an implicit method.
+    Implicit(Interpolant<Y>),
+}
+
+impl<Y: OdeType> StepInterpolant<Y> {
+    /// The continuous extension `dense` of an explicit Runge–Kutta step, `ks` are the
+    /// stages of the step followed by the additional stages.
+    pub fn from_dense(dense: &DenseCoeffs, dt: f64, ks: &[Y]) -> Self {
+        let coeffs = (0..dense.bi.ncols())
+            .map(|j| {
+                let mut c = ks[0].clone();
+                c.set_zero();
+                for (i, k) in ks.iter().enumerate().take(dense.bi.nrows()) {
+                    let b = dense.bi[(i, j)] * dt;
+                    for d in 0..c.dof() {
+                        *c.get_mut(d) += k.get(d) * b;
+                    }
+                }
+                c
+            })
+            .collect();
+        StepInterpolant::Polynomial(coeffs)
+    }
+
+    /// The cubic hermite interpolation between `(y0, f0)` and `(y1, f1)`.
+    pub fn hermite(dt: f64, y0: &Y, y1: &Y, f0: &Y, f1: &Y) -> Self {
+        let mut coeffs = vec![y0.clone(), y0.clone(), y0.clone()];
+        for d in 0..y0.dof() {
+            let delta = y1.get(d) - y0.get(d);
+            let (f0, f1) = (f0.get(d) * dt, f1.get(d) * dt);
+            coeffs[0].insert(d, f0);
+            coeffs[1].insert(d, delta * 3. - f0 * 2. - f1);
+            coeffs[2].insert(d, f0 + f1 - delta * 2.);
+        }
+        StepInterpolant::Polynomial(coeffs)
+    }
+
+    /// The linear interpolation between `y0` and `y1`.
+    pub fn linear(y0: &Y, y1: &Y) -> Self {
+        let mut delta = y1.clone();
+        for d in 0..delta.dof() {
+            *delta.get_mut(d) -= y0.get(d);
+        }
+        StepInterpolant::Polynomial(vec![delta])
+    }
+
+    /// The degree of the interpolating polynomial.
+    pub fn degree(&self) -> usize {
+        match self {
+            StepInterpolant::Polynomial(coeffs) => coeffs.len(),
+            StepInterpolant::Implicit(Interpolant::Collocation(collocation)) => collocation.z.len(),
+            StepInterpolant::Implicit(Interpolant::Rosenbrock(_)) => 3,
+        }
+    }
+
+    /// Replaces an interpolant of a degree higher than `degree` by the cubic hermite
+    /// interpolation of the step from `(y0, f0)` to `(y1, f1)`, or by the linear
+    /// interpolation if `degree < 3`.
+    pub fn with_max_degree(self, degree: usize, dt: f64, y0: &Y, y1: &Y, f0: &Y, f1: &Y) -> Self {
+        if self.degree() <= degree {
+            self
+        } else if degree >= 3 {
+            StepInterpolant::hermite(dt, y0, y1, f0, f1)
+        } else {
+            StepInterpolant::linear(y0, y1)
+        }
+    }
+
+    /// The continuous extension of order 7 of
+    /// [`OdeProblem::dop853`](crate::ode::problem::OdeProblem::dop853) with the
+    /// coefficients `rcont` of [`dense_output`](crate::ode::dop853::dense_output).
+    pub fn dop853(rcont: &[Y]) -> Self {
+        // `θ, θ (1 - θ), θ^2 (1 - θ), ...` in powers of `θ`, without the constant term
+        let mut basis = vec![1.];
+        let mut coeffs: Vec<Y> = Vec::with_capacity(rcont.len());
+        for (i, r) in rcont.iter().enumerate() {
+            if i % 2 == 1 {
+                // multiply by `1 - θ`
+                let mut next = basis.clone();
+                next.push(0.);
+                for (j, b) in basis.iter().enumerate() {
+                    next[j + 1] -= b;
+                }
+                basis = next;
+            } else if i > 0 {
+                // multiply by `θ`
+                basis.insert(0, 0.);
+            }
+            for (j, b) in basis.iter().enumerate() {
+                if coeffs.len() <= j {
+                    let mut c = r.clone();
+                    c.set_zero();
+                    coeffs.push(c);
+                }
+                for d in 0..r.dof() {
+                    *coeffs[j].get_mut(d) += r.get(d) * *b;
+                }
+            }
+        }
+        StepInterpolant::Polynomial(coeffs)
+    }
+
+    /// Evaluates the interpolant of a step from `y0` at `θ ∈ [0, 1]`.
+    pub fn interpolate(&self, theta: f64, y0: &Y) -> Y {
+        match self {
+            StepInterpolant::Polynomial(coeffs) => {
+                let mut y = y0.clone();
+                for d in 0..y.dof() {
+                    // horner scheme, no constant term
+                    let acc = coeffs
+                        .iter()
+                        .rev()
+                        .fold(y0.get(d) * 0., |acc, c| (acc + c.get(d)) * theta);
+                    *y.get_mut(d) += acc;
+                }
+                y
+            }
+            StepInterpolant::Implicit(interpolant) => interpolant.interpolate(theta, y0),
+        }
+    }
+}
+
+/// An accepted step of a [`DenseOutput`].
+#[derive(Clone, Debug)]
+struct DenseSegment<Y> {
+    t: f64,
+    dt: f64,
+    /// the fraction of the step that is covered, less than one if the step was truncated
+    theta_end: f64,
+    y0: Y,
+    interpolant: StepInterpolant<Y>,
+}
+
+/// The piecewise polynomial continuous output of an integration, one
+/// [`StepInterpolant`] per accepted step.
+#[derive(Clone, Debug)]
+pub struct DenseOutput<Y> {
+    segments: Vec<DenseSegment<Y>>,
+}
+
+impl<Y> Default for DenseOutput<Y> {
+    fn default() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+}
+
+impl<Y: OdeType> DenseOutput<Y> {
+    /// Appends the step of size `dt` from `(t, y0)`, steps are expected in the direction
+    /// of integration.
+    pub fn push(&mut self, t: f64, dt: f64, y0: Y, interpolant: StepInterpolant<Y>) {
+        self.segments.push(DenseSegment {
+            t,
+            dt,
+            theta_end: 1.,
+            y0,
+            interpolant,
+        });
+    }
+
+    /// Ends the covered time span at `t` inside the last step, which is how a step
+    /// interrupted by an event is stored.
+    pub fn truncate(&mut self, t: f64) {
+        if let Some(last) = self.segments.last_mut() {
+            last.theta_end = ((t - last.t) / last.dt).clamp(0., 1.);
+        }
+    }
+
+    /// The number of stored steps.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Whether no steps are stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The start and end of the covered time span.
+    pub fn interval(&self) -> Option<(f64, f64)> {
+        let (first, last) = (self.segments.first()?, self.segments.last()?);
+        Some((first.t, last.t + last.dt * last.theta_end))
+    }
+
+    /// The interpolated state at `t`, `None` outside of the covered time span.
+    pub fn at(&self, t: f64) -> Option<Y> {
+        let tdir = self.segments.first()?.dt.signum();
+        let i = self
+            .segments
+            .partition_point(|segment| tdir * segment.t <= tdir * t);
+        let segment = &self.segments[i.checked_sub(1)?];
+        let theta = (t - segment.t) / segment.dt;
+        if theta > segment.theta_end + 1e-12 {
+            return None;
+        }
+        Some(
+            segment
+                .interpolant
+                .interpolate(theta.min(segment.theta_end), &segment.y0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::dop853;
+
+    #[test]
+    fn max_degree() {
+        let (y0, y1, f0, f1) = (1., 2., 0.5, 1.5);
+        let hermite = StepInterpolant::hermite(1., &y0, &y1, &f0, &f1);
+        assert_eq!(3, hermite.degree());
+        let same = hermite.clone().with_max_degree(3, 1., &y0, &y1, &f0, &f1);
+        assert_eq!(3, same.degree());
+        let linear = hermite.with_max_degree(2, 1., &y0, &y1, &f0, &f1);
+        assert_eq!(1, linear.degree());
+        assert_eq!(1.5, linear.interpolate(0.5, &y0));
+    }
+
+    #[test]
+    fn tsit5_weights() {
+        // the weights reproduce the quadrature conditions up to order 4
+        let dense = DenseCoeffs::tsit5();
+        let c: [f64; 7] = [0., 0.161, 0.327, 0.9, 0.980_025_540_904_509_7, 1., 1.];
+        for &theta in &[0.3, 0.7, 1.] {
+            let b = dense.weights(theta);
+            for q in 0..4 {
+                let sum: f64 = (0..7).map(|i| b[i] * c[i].powi(q)).sum();
+                assert!((sum - theta.powi(q + 1) / (q + 1) as f64).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn piecewise_output() {
+        // `y = t^3` on the steps `[0, 1]` and `[1, 3]`
+        let mut output = DenseOutput::default();
+        output.push(0., 1., 0., StepInterpolant::hermite(1., &0., &1., &0., &3.));
+        output.push(
+            1.,
+            2.,
+            1.,
+            StepInterpolant::hermite(2., &1., &27., &3., &27.),
+        );
+        assert_eq!(Some((0., 3.)), output.interval());
+        for &t in &[0., 0.5, 1., 2.5, 3.] {
+            assert!((output.at(t).unwrap() - t * t * t).abs() < 1e-12);
+        }
+        assert!(output.at(-0.1).is_none());
+        assert!(output.at(3.1).is_none());
+
+        // the expanded continuous extension of dop853 agrees with its nested form
+        let rcont = [0.3, -1.2, 0.7, 2.1, -0.4, 0.9, 1.3];
+        let interpolant = StepInterpolant::dop853(&rcont);
+        for &theta in &[0.2, 0.6, 1.] {
+            let y = dop853::dense_output(theta, &1., &rcont);
+            assert!((interpolant.interpolate(theta, &1.) - y).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::ode::types::OdeType;
+use na::*;
+
+/// Additional coefficients of the Dormand–Prince 8(5,3) method that are not
+/// part of its [`ButcherTableau`](crate::ode::runge_kutta::ButcherTableau).
+///
+/// The local error is estimated with both the embedded 5th and 3rd order
+/// solutions, the continuous extension of order 7 requires three additional
+/// stages per step.
+///
+/// Coefficients are taken from Hairer's `DOP853` implementation.
+#[derive(Clone, Debug)]
+pub struct Dop853Coeffs {
+    /// weights of the 5th order error estimator `b - b̂₅`
+    pub e5: VectorN<f64, U12>,
+    /// weights of the 3rd order error estimator `b - b̂₃`
+    pub e3: VectorN<f64, U12>,
+    /// rk matrix of the three additional stages required for dense output
+    pub a_dense: MatrixMN<f64, U3, U16>,
+    /// nodes of the additional stages
+    pub c_dense: Vector3<f64>,
+    /// coefficients of the 7th order continuous extension
+    pub d: MatrixMN<f64, U4, U16>,
+}
+
+impl Dop853Coeffs {
+    pub fn new() -> Self {
+        let e5 = VectorN::from_row_slice_generic(
+            U12,
+            U1,
+            &[
+                0.01312004499419488,
+                0.,
+                0.,
+                0.,
+                0.,
+                -1.2251564463762044,
+                -0.4957589496572502,
+                1.6643771824549864,
+                -0.35032884874997366,
+                0.3341791187130175,
+                0.08192320648511571,
+                -0.022355307863886294,
+            ],
+        );
+        let e3 = VectorN::from_row_slice_generic(
+            U12,
+            U1,
+            &[
+                -0.18980075407240762,
+                0.,
+                0.,
+                0.,
+                0.,
+                4.450312892752409,
+                1.8915178993145003,
+                -5.801203960010585,
+                -0.42268232132379197,
+                -0.1521609496625161,
+                0.20136540080403034,
+                0.022651792198360825,
+            ],
+        );
+        let a_dense = MatrixMN::from_row_slice_generic(
+            U3,
+            U16,
+            &[
+                0.056167502283047954,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.25350021021662483,
+                -0.2462390374708025,
+                -0.12419142326381637,
+                0.15329179827876568,
+                0.00820105229563469,
+                0.007567897660545699,
+                -0.008298,
+                0.,
+                0.,
+                0.,
+                0.03183464816350214,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.028300909672366776,
+                0.053541988307438566,
+                -0.05492374857139099,
+                0.,
+                0.,
+                -0.00010834732869724932,
+                0.0003825710908356584,
+                -0.00034046500868740456,
+                0.1413124436746325,
+                0.,
+                0.,
+                -0.42889630158379194,
+                0.,
+                0.,
+                0.,
+                0.,
+                -4.697621415361164,
+                7.683421196062599,
+                4.06898981839711,
+                0.3567271874552811,
+                0.,
+                0.,
+                0.,
+                -0.0013990241651590145,
+                2.9475147891527724,
+                -9.15095847217987,
+                0.,
+            ],
+        );
+        let c_dense = Vector3::new(0.1, 0.2, 7. / 9.);
+        let d = MatrixMN::from_row_slice_generic(
+            U4,
+            U16,
+            &[
+                -8.428938276109013,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.5667149535193777,
+                -3.0689499459498917,
+                2.38466765651207,
+                2.117034582445028,
+                -0.871391583777973,
+                2.2404374302607883,
+                0.6315787787694688,
+                -0.08899033645133331,
+                18.148505520854727,
+                -9.194632392478356,
+                -4.436036387594894,
+                10.427508642579134,
+                0.,
+                0.,
+                0.,
+                0.,
+                242.28349177525817,
+                165.20045171727028,
+                -374.5467547226902,
+                -22.113666853125306,
+                7.733432668472264,
+                -30.674084731089398,
+                -9.332130526430229,
+                15.697238121770845,
+                -31.139403219565178,
+                -9.35292435884448,
+                35.81684148639408,
+                19.985053242002433,
+                0.,
+                0.,
+                0.,
+                0.,
+                -387.0373087493518,
+                -189.17813819516758,
+                527.8081592054236,
+                -11.57390253995963,
+                6.8812326946963,
+                -1.0006050966910838,
+                0.7777137798053443,
+                -2.778205752353508,
+                -60.19669523126412,
+                84.32040550667716,
+                11.99229113618279,
+                -25.69393346270375,
+                0.,
+                0.,
+                0.,
+                0.,
+                -154.18974869023643,
+                -231.5293791760455,
+                357.6391179106141,
+                93.40532418362432,
+                -37.45832313645163,
+                104.0996495089623,
+                29.8402934266605,
+                -43.53345659001114,
+                96.32455395918828,
+                -39.17726167561544,
+                -149.72683625798564,
+            ],
+        );
+
+        Self {
+            e5,
+            e3,
+            a_dense,
+            c_dense,
+            d,
+        }
+    }
+}
+
+impl Default for Dop853Coeffs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Evaluates the continuous extension of a step at `theta ∈ [0, 1]`.
+///
+/// `y0` is the solution at the beginning of the step and `rcont` are the
+/// seven coefficient vectors of the 7th order dense output of that step.
+pub fn dense_output<Y: OdeType>(theta: f64, y0: &Y, rcont: &[Y]) -> Y {
+    assert_eq!(7, rcont.len());
+    let theta1 = 1. - theta;
+    let mut y = y0.clone();
+    for d in 0..y.dof() {
+        let mut acc = rcont[6].get(d);
+        // alternate between `theta` and `1 - theta`
+        for (i, r) in rcont[..6].iter().enumerate().rev() {
+            let fac = if i % 2 == 0 { theta1 } else { theta };
+            acc = r.get(d) + acc * fac;
+        }
+        *y.get_mut(d) += acc * theta;
+    }
+    y
+}
@@ -0,0 +1,985 @@
+use crate::ode::types::OdeType;
+use alga::general::{
+    AbstractField, AbstractGroup, AbstractGroupAbelian, AbstractLoop, AbstractMagma,
+    AbstractMonoid, AbstractQuasigroup, AbstractRing, AbstractRingCommutative, AbstractSemigroup,
+    Additive, ComplexField, Identity, JoinSemilattice, Lattice, MeetSemilattice, Multiplicative,
+    RealField, SubsetOf, TwoSidedInverse,
+};
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+use na::allocator::Allocator;
+use na::{DMatrix, DefaultAllocator, Dim, MatrixMN};
+use num_traits::{Bounded, FromPrimitive, Num, One, Signed, Zero};
+use std::cmp::Ordering;
+use std::f64::consts;
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+/// A dual number `a + b ε` with `ε² = 0`, c.f. Griewank & Walther, "Evaluating
+/// Derivatives", 2008, Sec. 3.1.
+///
+/// Evaluating a function at `x + ε` yields `f(x) + f'(x) ε`, so the derivative of any
+/// RHS that is generic over its [`RealField`] scalar is exact up to rounding, see
+/// [`jacobian_ad`] and [`tgrad_ad`]. Dual numbers are compared and ordered by their
+/// value only.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Dual {
+    re: f64,
+    eps: f64,
+}
+
+impl Dual {
+    /// The dual number `re + eps ε`.
+    #[inline]
+    pub fn new(re: f64, eps: f64) -> Self {
+        Self { re, eps }
+    }
+
+    /// The constant `value`, with a vanishing derivative.
+    #[inline]
+    pub fn constant(value: f64) -> Self {
+        Self::new(value, 0.)
+    }
+
+    /// The independent variable at `value`, with the derivative one.
+    #[inline]
+    pub fn variable(value: f64) -> Self {
+        Self::new(value, 1.)
+    }
+
+    /// The value `a` of `a + b ε`.
+    #[inline]
+    pub fn value(&self) -> f64 {
+        self.re
+    }
+
+    /// The derivative `b` of `a + b ε`.
+    #[inline]
+    pub fn derivative(&self) -> f64 {
+        self.eps
+    }
+
+    /// `f(a) + f'(a) b ε` for `f(a) = value` and `f'(a) = deriv`
+    #[inline]
+    fn chain(self, value: f64, deriv: f64) -> Self {
+        Self::new(value, self.eps * deriv)
+    }
+}
+
+impl From<f64> for Dual {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Dual::constant(value)
+    }
+}
+
+impl From<Dual> for f64 {
+    /// The value of the dual number, its derivative is dropped.
+    #[inline]
+    fn from(dual: Dual) -> Self {
+        dual.re
+    }
+}
+
+impl PartialEq for Dual {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.re == other.re
+    }
+}
+
+impl PartialOrd for Dual {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.re.partial_cmp(&other.re)
+    }
+}
+
+impl fmt::Display for Dual {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.eps.is_sign_negative() {
+            write!(f, "{} - {}ε", self.re, -self.eps)
+        } else {
+            write!(f, "{} + {}ε", self.re, self.eps)
+        }
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    #[inline]
+    fn neg(self) -> Dual {
+        Dual::new(-self.re, -self.eps)
+    }
+}
+
+/// implements a binary operation and its assignment for dual numbers and all
+/// combinations with scalars in terms of the operation on dual numbers
+macro_rules! dual_op {
+    ($op:ident, $fun:ident, $op_assign:ident, $fun_assign:ident, |$a:ident, $b:ident| $dual:expr) => {
+        impl $op<Dual> for Dual {
+            type Output = Dual;
+
+            #[inline]
+            fn $fun(self, other: Dual) -> Dual {
+                let ($a, $b) = (self, other);
+                $dual
+            }
+        }
+
+        impl $op<f64> for Dual {
+            type Output = Dual;
+
+            #[inline]
+            fn $fun(self, other: f64) -> Dual {
+                $op::$fun(self, Dual::constant(other))
+            }
+        }
+
+        impl $op<Dual> for f64 {
+            type Output = Dual;
+
+            #[inline]
+            fn $fun(self, other: Dual) -> Dual {
+                $op::$fun(Dual::constant(self), other)
+            }
+        }
+
+        impl $op_assign<Dual> for Dual {
+            #[inline]
+            fn $fun_assign(&mut self, other: Dual) {
+                *self = $op::$fun(*self, other);
+            }
+        }
+
+        impl $op_assign<f64> for Dual {
+            #[inline]
+            fn $fun_assign(&mut self, other: f64) {
+                *self = $op::$fun(*self, other);
+            }
+        }
+    };
+}
+
+dual_op!(Add, add, AddAssign, add_assign, |a, b| Dual::new(
+    a.re + b.re,
+    a.eps + b.eps
+));
+dual_op!(Sub, sub, SubAssign, sub_assign, |a, b| Dual::new(
+    a.re - b.re,
+    a.eps - b.eps
+));
+dual_op!(Mul, mul, MulAssign, mul_assign, |a, b| Dual::new(
+    a.re * b.re,
+    a.eps * b.re + a.re * b.eps
+));
+dual_op!(Div, div, DivAssign, div_assign, |a, b| Dual::new(
+    a.re / b.re,
+    (a.eps * b.re - a.re * b.eps) / (b.re * b.re)
+));
+dual_op!(Rem, rem, RemAssign, rem_assign, |a, b| Dual::new(
+    a.re % b.re,
+    a.eps - b.eps * (a.re / b.re).trunc()
+));
+
+impl Zero for Dual {
+    #[inline]
+    fn zero() -> Self {
+        Dual::constant(0.)
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.re == 0.
+    }
+}
+
+impl One for Dual {
+    #[inline]
+    fn one() -> Self {
+        Dual::constant(1.)
+    }
+}
+
+impl Num for Dual {
+    type FromStrRadixErr = <f64 as Num>::FromStrRadixErr;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        f64::from_str_radix(s, radix).map(Dual::constant)
+    }
+}
+
+impl FromPrimitive for Dual {
+    #[inline]
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(Dual::constant(n as f64))
+    }
+
+    #[inline]
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(Dual::constant(n as f64))
+    }
+
+    #[inline]
+    fn from_f64(n: f64) -> Option<Self> {
+        Some(Dual::constant(n))
+    }
+}
+
+impl Signed for Dual {
+    #[inline]
+    fn abs(&self) -> Self {
+        self.chain(self.re.abs(), self.re.signum())
+    }
+
+    #[inline]
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self <= other {
+            Dual::zero()
+        } else {
+            *self - *other
+        }
+    }
+
+    #[inline]
+    fn signum(&self) -> Self {
+        Dual::constant(self.re.signum())
+    }
+
+    #[inline]
+    fn is_positive(&self) -> bool {
+        self.re > 0.
+    }
+
+    #[inline]
+    fn is_negative(&self) -> bool {
+        self.re < 0.
+    }
+}
+
+impl Bounded for Dual {
+    #[inline]
+    fn min_value() -> Self {
+        Dual::constant(f64::MIN)
+    }
+
+    #[inline]
+    fn max_value() -> Self {
+        Dual::constant(f64::MAX)
+    }
+}
+
+impl AbsDiffEq for Dual {
+    type Epsilon = Dual;
+
+    #[inline]
+    fn default_epsilon() -> Self::Epsilon {
+        Dual::constant(f64::default_epsilon())
+    }
+
+    #[inline]
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.re.abs_diff_eq(&other.re, epsilon.re)
+    }
+}
+
+impl RelativeEq for Dual {
+    #[inline]
+    fn default_max_relative() -> Self::Epsilon {
+        Dual::constant(f64::default_max_relative())
+    }
+
+    #[inline]
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool {
+        self.re.relative_eq(&other.re, epsilon.re, max_relative.re)
+    }
+}
+
+impl UlpsEq for Dual {
+    #[inline]
+    fn default_max_ulps() -> u32 {
+        f64::default_max_ulps()
+    }
+
+    #[inline]
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool {
+        self.re.ulps_eq(&other.re, epsilon.re, max_ulps)
+    }
+}
+
+impl SubsetOf<Dual> for Dual {
+    #[inline]
+    fn to_superset(&self) -> Dual {
+        *self
+    }
+
+    #[inline]
+    unsafe fn from_superset_unchecked(element: &Dual) -> Self {
+        *element
+    }
+
+    #[inline]
+    fn is_in_subset(_: &Dual) -> bool {
+        true
+    }
+}
+
+impl SubsetOf<Dual> for f64 {
+    #[inline]
+    fn to_superset(&self) -> Dual {
+        Dual::constant(*self)
+    }
+
+    #[inline]
+    unsafe fn from_superset_unchecked(element: &Dual) -> Self {
+        element.re
+    }
+
+    #[inline]
+    fn is_in_subset(element: &Dual) -> bool {
+        element.eps == 0.
+    }
+}
+
+impl MeetSemilattice for Dual {
+    #[inline]
+    fn meet(&self, other: &Self) -> Self {
+        RealField::min(*self, *other)
+    }
+}
+
+impl JoinSemilattice for Dual {
+    #[inline]
+    fn join(&self, other: &Self) -> Self {
+        RealField::max(*self, *other)
+    }
+}
+
+impl Lattice for Dual {}
+
+impl AbstractMagma<Additive> for Dual {
+    #[inline]
+    fn operate(&self, right: &Self) -> Self {
+        *self + *right
+    }
+}
+
+impl AbstractMagma<Multiplicative> for Dual {
+    #[inline]
+    fn operate(&self, right: &Self) -> Self {
+        *self * *right
+    }
+}
+
+impl TwoSidedInverse<Additive> for Dual {
+    #[inline]
+    fn two_sided_inverse(&self) -> Self {
+        -*self
+    }
+}
+
+impl TwoSidedInverse<Multiplicative> for Dual {
+    #[inline]
+    fn two_sided_inverse(&self) -> Self {
+        ComplexField::recip(*self)
+    }
+}
+
+impl Identity<Additive> for Dual {
+    #[inline]
+    fn identity() -> Self {
+        Dual::zero()
+    }
+}
+
+impl Identity<Multiplicative> for Dual {
+    #[inline]
+    fn identity() -> Self {
+        Dual::one()
+    }
+}
+
+impl AbstractSemigroup<Additive> for Dual {}
+impl AbstractQuasigroup<Additive> for Dual {}
+impl AbstractMonoid<Additive> for Dual {}
+impl AbstractLoop<Additive> for Dual {}
+impl AbstractGroup<Additive> for Dual {}
+impl AbstractGroupAbelian<Additive> for Dual {}
+impl AbstractSemigroup<Multiplicative> for Dual {}
+impl AbstractQuasigroup<Multiplicative> for Dual {}
+impl AbstractMonoid<Multiplicative> for Dual {}
+impl AbstractLoop<Multiplicative> for Dual {}
+impl AbstractGroup<Multiplicative> for Dual {}
+impl AbstractGroupAbelian<Multiplicative> for Dual {}
+impl AbstractRing for Dual {}
+impl AbstractRingCommutative for Dual {}
+impl AbstractField for Dual {}
+
+impl ComplexField for Dual {
+    type RealField = Dual;
+
+    #[inline]
+    fn from_real(re: Self::RealField) -> Self {
+        re
+    }
+
+    #[inline]
+    fn real(self) -> Self::RealField {
+        self
+    }
+
+    #[inline]
+    fn imaginary(self) -> Self::RealField {
+        Dual::zero()
+    }
+
+    #[inline]
+    fn modulus(self) -> Self::RealField {
+        Signed::abs(&self)
+    }
+
+    #[inline]
+    fn modulus_squared(self) -> Self::RealField {
+        self * self
+    }
+
+    #[inline]
+    fn argument(self) -> Self::RealField {
+        if self.re >= 0. {
+            Dual::zero()
+        } else {
+            Dual::constant(consts::PI)
+        }
+    }
+
+    #[inline]
+    fn norm1(self) -> Self::RealField {
+        Signed::abs(&self)
+    }
+
+    #[inline]
+    fn scale(self, factor: Self::RealField) -> Self {
+        self * factor
+    }
+
+    #[inline]
+    fn unscale(self, factor: Self::RealField) -> Self {
+        self / factor
+    }
+
+    #[inline]
+    fn floor(self) -> Self {
+        Dual::constant(self.re.floor())
+    }
+
+    #[inline]
+    fn ceil(self) -> Self {
+        Dual::constant(self.re.ceil())
+    }
+
+    #[inline]
+    fn round(self) -> Self {
+        Dual::constant(self.re.round())
+    }
+
+    #[inline]
+    fn trunc(self) -> Self {
+        Dual::constant(self.re.trunc())
+    }
+
+    #[inline]
+    fn fract(self) -> Self {
+        self.chain(self.re.fract(), 1.)
+    }
+
+    #[inline]
+    fn mul_add(self, a: Self, b: Self) -> Self {
+        self * a + b
+    }
+
+    #[inline]
+    fn abs(self) -> Self::RealField {
+        Signed::abs(&self)
+    }
+
+    #[inline]
+    fn hypot(self, other: Self) -> Self::RealField {
+        ComplexField::sqrt(self * self + other * other)
+    }
+
+    #[inline]
+    fn recip(self) -> Self {
+        self.chain(self.re.recip(), -1. / (self.re * self.re))
+    }
+
+    #[inline]
+    fn conjugate(self) -> Self {
+        self
+    }
+
+    #[inline]
+    fn sin(self) -> Self {
+        self.chain(self.re.sin(), self.re.cos())
+    }
+
+    #[inline]
+    fn cos(self) -> Self {
+        self.chain(self.re.cos(), -self.re.sin())
+    }
+
+    #[inline]
+    fn sin_cos(self) -> (Self, Self) {
+        (ComplexField::sin(self), ComplexField::cos(self))
+    }
+
+    #[inline]
+    fn tan(self) -> Self {
+        let tan = self.re.tan();
+        self.chain(tan, 1. + tan * tan)
+    }
+
+    #[inline]
+    fn asin(self) -> Self {
+        self.chain(self.re.asin(), 1. / (1. - self.re * self.re).sqrt())
+    }
+
+    #[inline]
+    fn acos(self) -> Self {
+        self.chain(self.re.acos(), -1. / (1. - self.re * self.re).sqrt())
+    }
+
+    #[inline]
+    fn atan(self) -> Self {
+        self.chain(self.re.atan(), 1. / (1. + self.re * self.re))
+    }
+
+    #[inline]
+    fn sinh(self) -> Self {
+        self.chain(self.re.sinh(), self.re.cosh())
+    }
+
+    #[inline]
+    fn cosh(self) -> Self {
+        self.chain(self.re.cosh(), self.re.sinh())
+    }
+
+    #[inline]
+    fn tanh(self) -> Self {
+        let tanh = self.re.tanh();
+        self.chain(tanh, 1. - tanh * tanh)
+    }
+
+    #[inline]
+    fn asinh(self) -> Self {
+        self.chain(self.re.asinh(), 1. / (self.re * self.re + 1.).sqrt())
+    }
+
+    #[inline]
+    fn acosh(self) -> Self {
+        self.chain(self.re.acosh(), 1. / (self.re * self.re - 1.).sqrt())
+    }
+
+    #[inline]
+    fn atanh(self) -> Self {
+        self.chain(self.re.atanh(), 1. / (1. - self.re * self.re))
+    }
+
+    #[inline]
+    fn is_finite(&self) -> bool {
+        self.re.is_finite() && self.eps.is_finite()
+    }
+
+    #[inline]
+    fn log(self, base: Self::RealField) -> Self {
+        ComplexField::ln(self) / ComplexField::ln(base)
+    }
+
+    #[inline]
+    fn log2(self) -> Self {
+        self.chain(self.re.log2(), 1. / (self.re * consts::LN_2))
+    }
+
+    #[inline]
+    fn log10(self) -> Self {
+        self.chain(self.re.log10(), 1. / (self.re * consts::LN_10))
+    }
+
+    #[inline]
+    fn ln(self) -> Self {
+        self.chain(self.re.ln(), 1. / self.re)
+    }
+
+    #[inline]
+    fn ln_1p(self) -> Self {
+        self.chain(self.re.ln_1p(), 1. / (1. + self.re))
+    }
+
+    #[inline]
+    fn sqrt(self) -> Self {
+        let sqrt = self.re.sqrt();
+        self.chain(sqrt, 0.5 / sqrt)
+    }
+
+    #[inline]
+    fn try_sqrt(self) -> Option<Self> {
+        if self.re >= 0. {
+            Some(ComplexField::sqrt(self))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn exp(self) -> Self {
+        let exp = self.re.exp();
+        self.chain(exp, exp)
+    }
+
+    #[inline]
+    fn exp2(self) -> Self {
+        let exp2 = self.re.exp2();
+        self.chain(exp2, exp2 * consts::LN_2)
+    }
+
+    #[inline]
+    fn exp_m1(self) -> Self {
+        self.chain(self.re.exp_m1(), self.re.exp())
+    }
+
+    #[inline]
+    fn powi(self, n: i32) -> Self {
+        if n == 0 {
+            return Dual::one();
+        }
+        self.chain(self.re.powi(n), f64::from(n) * self.re.powi(n - 1))
+    }
+
+    /// `a^n` by `d(a^n) = n a^(n-1) da + a^n ln(a) dn`, the second term is only
+    /// evaluated if the exponent has a derivative.
+    #[inline]
+    fn powf(self, n: Self::RealField) -> Self {
+        if n.re == 0. && n.eps == 0. {
+            return Dual::one();
+        }
+        let pow = self.re.powf(n.re);
+        let mut eps = self.eps * n.re * self.re.powf(n.re - 1.);
+        if n.eps != 0. {
+            eps += n.eps * pow * self.re.ln();
+        }
+        Dual::new(pow, eps)
+    }
+
+    #[inline]
+    fn powc(self, n: Self) -> Self {
+        ComplexField::powf(self, n)
+    }
+
+    #[inline]
+    fn cbrt(self) -> Self {
+        let cbrt = self.re.cbrt();
+        self.chain(cbrt, 1. / (3. * cbrt * cbrt))
+    }
+}
+
+impl RealField for Dual {
+    #[inline]
+    fn is_sign_positive(self) -> bool {
+        self.re.is_sign_positive()
+    }
+
+    #[inline]
+    fn is_sign_negative(self) -> bool {
+        self.re.is_sign_negative()
+    }
+
+    #[inline]
+    fn max(self, other: Self) -> Self {
+        if other.re > self.re || self.re.is_nan() {
+            other
+        } else {
+            self
+        }
+    }
+
+    #[inline]
+    fn min(self, other: Self) -> Self {
+        if other.re < self.re || self.re.is_nan() {
+            other
+        } else {
+            self
+        }
+    }
+
+    #[inline]
+    fn atan2(self, other: Self) -> Self {
+        let norm = self.re * self.re + other.re * other.re;
+        Dual::new(
+            self.re.atan2(other.re),
+            (other.re * self.eps - self.re * other.eps) / norm,
+        )
+    }
+
+    #[inline]
+    fn pi() -> Self {
+        Dual::constant(consts::PI)
+    }
+
+    #[inline]
+    fn two_pi() -> Self {
+        Dual::constant(2. * consts::PI)
+    }
+
+    #[inline]
+    fn frac_pi_2() -> Self {
+        Dual::constant(consts::FRAC_PI_2)
+    }
+
+    #[inline]
+    fn frac_pi_3() -> Self {
+        Dual::constant(consts::FRAC_PI_3)
+    }
+
+    #[inline]
+    fn frac_pi_4() -> Self {
+        Dual::constant(consts::FRAC_PI_4)
+    }
+
+    #[inline]
+    fn frac_pi_6() -> Self {
+        Dual::constant(consts::FRAC_PI_6)
+    }
+
+    #[inline]
+    fn frac_pi_8() -> Self {
+        Dual::constant(consts::FRAC_PI_8)
+    }
+
+    #[inline]
+    fn frac_1_pi() -> Self {
+        Dual::constant(consts::FRAC_1_PI)
+    }
+
+    #[inline]
+    fn frac_2_pi() -> Self {
+        Dual::constant(consts::FRAC_2_PI)
+    }
+
+    #[inline]
+    fn frac_2_sqrt_pi() -> Self {
+        Dual::constant(consts::FRAC_2_SQRT_PI)
+    }
+
+    #[inline]
+    fn e() -> Self {
+        Dual::constant(consts::E)
+    }
+
+    #[inline]
+    fn log2_e() -> Self {
+        Dual::constant(consts::LOG2_E)
+    }
+
+    #[inline]
+    fn log10_e() -> Self {
+        Dual::constant(consts::LOG10_E)
+    }
+
+    #[inline]
+    fn ln_2() -> Self {
+        Dual::constant(consts::LN_2)
+    }
+
+    #[inline]
+    fn ln_10() -> Self {
+        Dual::constant(consts::LN_10)
+    }
+}
+
+/// An [`OdeType`] of `f64` components with a counterpart of the same shape in dual
+/// numbers, the state a RHS generic over its scalar is differentiated with.
+pub trait DualType: OdeType<Item = f64> {
+    type Dual: OdeType<Item = Dual>;
+
+    /// The state in dual numbers with vanishing derivatives.
+    fn to_dual(&self) -> Self::Dual;
+}
+
+impl DualType for f64 {
+    type Dual = Dual;
+
+    #[inline]
+    fn to_dual(&self) -> Dual {
+        Dual::constant(*self)
+    }
+}
+
+impl DualType for Vec<f64> {
+    type Dual = Vec<Dual>;
+
+    fn to_dual(&self) -> Vec<Dual> {
+        self.iter().copied().map(Dual::constant).collect()
+    }
+}
+
+impl<const N: usize> DualType for [f64; N] {
+    type Dual = [Dual; N];
+
+    fn to_dual(&self) -> [Dual; N] {
+        self.map(Dual::constant)
+    }
+}
+
+impl<R: Dim, C: Dim> DualType for MatrixMN<f64, R, C>
+where
+    DefaultAllocator: Allocator<f64, R, C> + Allocator<Dual, R, C>,
+{
+    type Dual = MatrixMN<Dual, R, C>;
+
+    fn to_dual(&self) -> MatrixMN<Dual, R, C> {
+        self.map(Dual::constant)
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<D: ndarray::Dimension> DualType for ndarray::Array<f64, D> {
+    type Dual = ndarray::Array<Dual, D>;
+
+    fn to_dual(&self) -> ndarray::Array<Dual, D> {
+        self.mapv(Dual::constant)
+    }
+}
+
+/// The jacobian `∂F/∂y` of the RHS `f` at `(t, y)` by forward mode automatic
+/// differentiation.
+///
+/// `f` is the RHS evaluated in dual numbers, which is usually the same function as the
+/// RHS of the problem if that is generic over its scalar. Every column takes one
+/// evaluation of `f`.
+pub fn jacobian_ad<Y, F>(f: F, t: f64, y: &Y) -> DMatrix<f64>
+where
+    F: Fn(Dual, &Y::Dual) -> Y::Dual,
+    Y: DualType,
+{
+    let n = y.dof();
+    let t = Dual::constant(t);
+    let mut jac = DMatrix::zeros(n, n);
+    for col in 0..n {
+        let mut yd = y.to_dual();
+        yd.insert(col, Dual::variable(y.get(col)));
+        let fd = f(t, &yd);
+        for row in 0..n {
+            jac[(row, col)] = fd.get(row).derivative();
+        }
+    }
+    jac
+}
+
+/// The partial derivative `∂F/∂t` of the RHS `f` at `(t, y)` by forward mode automatic
+/// differentiation, c.f. [`jacobian_ad`].
+pub fn tgrad_ad<Y, F>(f: F, t: f64, y: &Y) -> Y
+where
+    F: Fn(Dual, &Y::Dual) -> Y::Dual,
+    Y: DualType,
+{
+    let fd = f(Dual::variable(t), &y.to_dual());
+    let mut dfdt = y.clone();
+    for d in 0..dfdt.dof() {
+        dfdt.insert(d, fd.get(d).derivative());
+    }
+    dfdt
+}
+
+/// The directional derivative `∂F/∂y (t, y) v` of the RHS `f` at `(t, y)` by forward
+/// mode automatic differentiation with a single evaluation of `f`, c.f. [`jacobian_ad`].
+pub fn jvp_ad<Y, F>(f: F, t: f64, y: &Y, v: &Y) -> Y
+where
+    F: Fn(Dual, &Y::Dual) -> Y::Dual,
+    Y: DualType,
+{
+    let mut yd = y.to_dual();
+    for d in 0..y.dof() {
+        yd.insert(d, Dual::new(y.get(d), v.get(d)));
+    }
+    let fd = f(Dual::constant(t), &yd);
+    let mut jv = y.clone();
+    for d in 0..jv.dof() {
+        jv.insert(d, fd.get(d).derivative());
+    }
+    jv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::DVector;
+
+    #[test]
+    fn elementary_functions() {
+        let x = Dual::variable(0.5);
+        let close = |dual: Dual, value: f64, deriv: f64| {
+            (dual.value() - value).abs() < 1e-14 && (dual.derivative() - deriv).abs() < 1e-14
+        };
+
+        assert!(close(x.exp(), 0.5f64.exp(), 0.5f64.exp()));
+        assert!(close(x.ln(), 0.5f64.ln(), 2.));
+        assert!(close(x.sqrt(), 0.5f64.sqrt(), 0.5 / 0.5f64.sqrt()));
+        assert!(close(
+            x.powf(Dual::constant(1.5)),
+            0.5f64.powf(1.5),
+            1.5 * 0.5f64.sqrt()
+        ));
+        assert!(close(x.powi(-3), 8., -48.));
+        assert!(close(
+            x.sin() * x.cos(),
+            0.5f64.sin() * 0.5f64.cos(),
+            1f64.cos()
+        ));
+        assert!(close((x * 2. + 1.) / (1. - x), 4., 12.));
+        assert!(close(x.atan2(Dual::constant(1.)), 0.5f64.atan(), 0.8));
+    }
+
+    /// the van der Pol oscillator, generic over the scalar
+    fn vdp<T: RealField + Mul<f64, Output = T> + Add<f64, Output = T>>(_t: T, y: &[T]) -> Vec<T> {
+        vec![y[1], (-(y[0] * y[0]) + 1.) * y[1] * 10. - y[0]]
+    }
+
+    #[test]
+    fn jacobian() {
+        let y = vec![2., -0.5];
+        let jac = jacobian_ad(|t, y: &Vec<Dual>| vdp(t, y), 0., &y);
+        let exact = DMatrix::from_row_slice(
+            2,
+            2,
+            &[
+                0.,
+                1.,
+                -2. * 10. * y[0] * y[1] - 1.,
+                10. * (1. - y[0] * y[0]),
+            ],
+        );
+        assert_eq!(exact, jac);
+        let v = vec![0.3, 1.5];
+        let jv = jvp_ad(|t, y: &Vec<Dual>| vdp(t, y), 0., &y, &v);
+        assert_eq!((&exact * DVector::from_vec(v)).as_slice(), &jv[..]);
+        let jac = jacobian_ad(
+            |t, y: &[Dual; 2]| {
+                let f = vdp(t, y);
+                [f[0], f[1]]
+            },
+            0.,
+            &[2., -0.5],
+        );
+        assert_eq!(exact, jac);
+
+        // `y' = t y`
+        let dfdt = tgrad_ad(|t, y: &Dual| t * *y, 2., &3.);
+        assert_eq!(3., dfdt);
+    }
+}
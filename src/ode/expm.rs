@@ -0,0 +1,104 @@
+use crate::error::OdeError;
+use na::DMatrix;
+
+/// Degree of the diagonal Padé approximant of the exponential.
+const PADE_DEGREE: usize = 6;
+
+/// The norm the matrix is scaled to before the Padé approximant is applied.
+const SCALED_NORM: f64 = 0.5;
+
+/// The matrix exponential `e^A` by scaling and squaring with a diagonal Padé
+/// approximant, c.f. Golub & Van Loan, "Matrix Computations", 1996, 11.3.
+pub fn expm(a: &DMatrix<f64>) -> Result<DMatrix<f64>, OdeError> {
+    let n = a.nrows();
+    let norm = one_norm(a);
+    let squarings = if norm > SCALED_NORM {
+        (norm / SCALED_NORM).log2().ceil() as i32
+    } else {
+        0
+    };
+    let b = a / 2f64.powi(squarings);
+
+    // numerator `N = Σ c_k B^k` and denominator `D = Σ (-1)^k c_k B^k`
+    let mut c = 1.;
+    let mut power = DMatrix::identity(n, n);
+    let mut num = DMatrix::identity(n, n);
+    let mut den = DMatrix::identity(n, n);
+    for k in 1..=PADE_DEGREE {
+        c *= (PADE_DEGREE + 1 - k) as f64 / (k * (2 * PADE_DEGREE + 1 - k)) as f64;
+        power = &power * &b;
+        num += &power * c;
+        if k % 2 == 0 {
+            den += &power * c;
+        } else {
+            den -= &power * c;
+        }
+    }
+
+    let mut x = den.lu().solve(&num).ok_or(OdeError::InvalidMatrix)?;
+    for _ in 0..squarings {
+        x = &x * &x;
+    }
+    Ok(x)
+}
+
+/// The matrix functions `[φ_0(A), φ_1(A), ..., φ_p(A)]` with `φ_0(z) = e^z` and
+/// `φ_{k+1}(z) = (φ_k(z) - 1/k!) / z`, from the exponential of the augmented matrix
+///
+/// ```text
+/// | A I 0 ... 0 |
+/// | 0 0 I ... 0 |
+/// |     ...     |
+/// | 0 0 0 ... 0 |
+/// ```
+///
+/// whose first block row is `[φ_0(A), ..., φ_p(A)]`, c.f. Sidje, "Expokit", 1998.
+pub fn phi_functions(a: &DMatrix<f64>, p: usize) -> Result<Vec<DMatrix<f64>>, OdeError> {
+    let n = a.nrows();
+    let m = n * (p + 1);
+    let mut w = DMatrix::zeros(m, m);
+    w.slice_mut((0, 0), (n, n)).copy_from(a);
+    for k in 0..p {
+        w.slice_mut((k * n, (k + 1) * n), (n, n))
+            .fill_with_identity();
+    }
+    let e = expm(&w)?;
+    Ok((0..=p)
+        .map(|k| e.slice((0, k * n), (n, n)).into_owned())
+        .collect())
+}
+
+/// The maximum absolute column sum.
+fn one_norm(a: &DMatrix<f64>) -> f64 {
+    a.column_iter()
+        .map(|col| col.iter().map(|x| x.abs()).sum::<f64>())
+        .fold(0., f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_exponential() {
+        // `exp([[0, θ], [-θ, 0]])` is the rotation by `θ`
+        let theta = 10.;
+        let a = DMatrix::from_row_slice(2, 2, &[0., theta, -theta, 0.]);
+        let e = expm(&a).unwrap();
+        let expected =
+            DMatrix::from_row_slice(2, 2, &[theta.cos(), theta.sin(), -theta.sin(), theta.cos()]);
+        assert!((e - expected).amax() < 1e-12);
+    }
+
+    #[test]
+    fn scalar_phi_functions() {
+        let z = -2.;
+        let phis = phi_functions(&DMatrix::from_element(1, 1, z), 3).unwrap();
+        let phi1 = (z.exp() - 1.) / z;
+        let phi2 = (phi1 - 1.) / z;
+        let phi3 = (phi2 - 0.5) / z;
+        for (phi, expected) in phis.iter().zip(&[z.exp(), phi1, phi2, phi3]) {
+            assert!((phi[(0, 0)] - expected).abs() < 1e-14);
+        }
+    }
+}
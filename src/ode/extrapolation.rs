@@ -0,0 +1,106 @@
+use alga::general::RealField;
+use na::DVector;
+use std::ops::{Add, Mul};
+
+/// Number of columns of the extrapolation table, the highest order is `2 * MAX_COLUMNS`.
+pub const MAX_COLUMNS: usize = 9;
+
+/// Safety factors of the step size selection `0.94 (0.65 / err)^{1/(2j+1)}`.
+pub const SAFETY: (f64, f64) = (0.94, 0.65);
+
+/// Bounds of the factor the step size is changed by, the lower bound is taken to the
+/// power `1/(2j+1)`.
+pub const FACTOR_BOUNDS: (f64, f64) = (0.25, 0.02);
+
+/// The coefficients of the Gragg–Bulirsch–Stoer extrapolation with the harmonic step
+/// number sequence `n_j = 2 (j + 1)`, c.f. Hairer, Nørsett & Wanner, "Solving Ordinary
+/// Differential Equations I", 1993, II.9.
+#[derive(Clone, Debug)]
+pub struct ExtrapolationCoeffs {
+    /// step numbers of the midpoint rule in each row of the table
+    pub n: Vec<usize>,
+    /// number of RHS evaluations to compute the rows `0..=j` of the table
+    pub work: Vec<f64>,
+}
+
+impl Default for ExtrapolationCoeffs {
+    fn default() -> Self {
+        let n: Vec<usize> = (1..=MAX_COLUMNS + 1).map(|j| 2 * j).collect();
+        // one evaluation at the start of the step, `n_j` more for each row
+        let work = n
+            .iter()
+            .scan(1., |work, nj| {
+                *work += *nj as f64;
+                Some(*work)
+            })
+            .collect();
+        Self { n, work }
+    }
+}
+
+impl ExtrapolationCoeffs {
+    /// Completes the new row `j = [T_j0]` of the table `t`, where `T_j0` is the result
+    /// of the midpoint rule with `n_j` steps, by the Aitken–Neville scheme.
+    pub fn extrapolate<T>(&self, t: &mut [Vec<DVector<T>>], j: usize)
+    where
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    {
+        for l in 1..=j {
+            let ratio = self.n[j] as f64 / self.n[j - l] as f64;
+            let tjl = &t[j][l - 1]
+                + (&t[j][l - 1] - &t[j - 1][l - 1]) * (T::one() * (1. / (ratio * ratio - 1.)));
+            t[j].push(tjl);
+        }
+    }
+}
+
+/// Gragg's modified midpoint rule with `n` steps over `[t, t + h]` from `y` with the
+/// derivative `dy = f(t, y)`, smoothed at the end point.
+pub fn midpoint<F, T>(
+    f: &F,
+    t: f64,
+    y: &DVector<T>,
+    dy: &DVector<T>,
+    h: f64,
+    n: usize,
+) -> DVector<T>
+where
+    F: Fn(f64, &DVector<T>) -> DVector<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+{
+    let hs = h / n as f64;
+    let mut z0 = y.clone();
+    let mut z1 = y + dy * (T::one() * hs);
+    for m in 1..n {
+        let z2 = &z0 + f(t + m as f64 * hs, &z1) * (T::one() * (2. * hs));
+        z0 = z1;
+        z1 = z2;
+    }
+    // smoothing step `(z_{n-1} + 2 z_n + z_{n+1}) / 4`
+    (z0 + &z1 + f(t + h, &z1) * (T::one() * hs)) * (T::one() * 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extrapolate_exponential() {
+        // `y' = y` on `[0, 1]`, the diagonal of the table converges to `e`
+        let coeffs = ExtrapolationCoeffs::default();
+        assert_eq!(coeffs.n[..4], [2, 4, 6, 8]);
+        assert_eq!(coeffs.work[..3], [3., 7., 13.]);
+
+        let f = |_t: f64, y: &DVector<f64>| y.clone();
+        let y = DVector::from_element(1, 1.);
+        let mut table: Vec<Vec<DVector<f64>>> = Vec::new();
+        let mut errors = Vec::new();
+        for j in 0..5 {
+            table.push(vec![midpoint(&f, 0., &y, &y, 1., coeffs.n[j])]);
+            coeffs.extrapolate(&mut table, j);
+            errors.push((table[j][j][0] - 1f64.exp()).abs());
+        }
+        assert!(errors.windows(2).all(|e| e[1] < e[0] * 0.1));
+        assert!(errors[4] < 1e-7);
+    }
+}
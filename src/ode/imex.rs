@@ -0,0 +1,506 @@
+use crate::error::OdeError;
+use crate::ode::implicit::{ImplicitCoeffs, ImplicitTrial};
+use crate::ode::newton::NewtonOptions;
+use crate::ode::options::{AdaptiveOptions, ImplicitOptions};
+use crate::ode::problem::{count, OdeProblem};
+use crate::ode::solution::{Diagnostics, OdeSolution};
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::linalg::LU;
+use na::{DMatrix, DVector, Dynamic};
+use std::cell::Cell;
+use std::ops::{Add, Mul};
+
+/// The paired tableaus of an additive Runge–Kutta method, an explicit method for the
+/// non-stiff part and a singly diagonally implicit method for the stiff part that share
+/// the nodes and weights.
+#[derive(Clone, Debug)]
+pub struct AdditiveTableau {
+    /// rk matrix of the explicit method
+    pub explicit: DMatrix<f64>,
+    /// the implicit method with the shared weights, embedded weights and nodes
+    pub implicit: ImplicitCoeffs,
+    /// (order, order of the embedded method)
+    pub order: (usize, usize),
+}
+
+impl AdditiveTableau {
+    /// The 4 stage ARK3(2)4L\[2\]SA pair of Kennedy & Carpenter, "Additive Runge–Kutta
+    /// schemes for convection–diffusion–reaction equations", 2003.
+    pub fn ark324() -> Self {
+        let gamma = 1767732205903. / 4055673282236.;
+        let b = vec![
+            1471266399579. / 7840856788654.,
+            -4482444167858. / 7529755066697.,
+            11266239266428. / 11593286722821.,
+            gamma,
+        ];
+        let bhat = vec![
+            2756255671327. / 12835298489170.,
+            -10771552573575. / 22201958757719.,
+            9247589265047. / 10645013368117.,
+            2193209047091. / 5459859503100.,
+        ];
+        #[rustfmt::skip]
+        let explicit = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            1767732205903. / 2027836641118., 0., 0., 0.,
+            5535828885825. / 10492691773637., 788022342437. / 10882634858940., 0., 0.,
+            6485989280629. / 16251701735622., -4246266847089. / 9704473918619.,
+            10755448449292. / 10357097424841., 0.,
+        ]);
+        #[rustfmt::skip]
+        let a = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            gamma, gamma, 0., 0.,
+            2746238789719. / 10658868560708., -640167445237. / 6845629431997., gamma, 0.,
+            b[0], b[1], b[2], gamma,
+        ]);
+
+        Self {
+            explicit,
+            implicit: ImplicitCoeffs {
+                a,
+                b,
+                bhat: Some(bhat),
+                c: vec![0., 2. * gamma, 0.6, 1.],
+            },
+            order: (3, 2),
+        }
+    }
+
+    /// The 6 stage ARK4(3)6L\[2\]SA pair of Kennedy & Carpenter, "Additive Runge–Kutta
+    /// schemes for convection–diffusion–reaction equations", 2003.
+    pub fn ark436() -> Self {
+        let gamma = 0.25;
+        let b = vec![
+            82889. / 524892.,
+            0.,
+            15625. / 83664.,
+            69875. / 102672.,
+            -2260. / 8211.,
+            gamma,
+        ];
+        let bhat = vec![
+            4586570599. / 29645900160.,
+            0.,
+            178811875. / 945068544.,
+            814220225. / 1159782912.,
+            -3700637. / 11593932.,
+            61727. / 225920.,
+        ];
+        #[rustfmt::skip]
+        let explicit = DMatrix::from_row_slice(6, 6, &[
+            0., 0., 0., 0., 0., 0.,
+            0.5, 0., 0., 0., 0., 0.,
+            13861. / 62500., 6889. / 62500., 0., 0., 0., 0.,
+            -116923316275. / 2393684061468., -2731218467317. / 15368042101831.,
+            9408046702089. / 11113171139209., 0., 0., 0.,
+            -451086348788. / 2902428689909., -2682348792572. / 7519795681897.,
+            12662868775082. / 11960479115383., 3355817975965. / 11060851509271., 0., 0.,
+            647845179188. / 3216320057751., 73281519250. / 8382639484533.,
+            552539513391. / 3454668386233., 3354512671639. / 8306763924573., 4040. / 17871., 0.,
+        ]);
+        #[rustfmt::skip]
+        let a = DMatrix::from_row_slice(6, 6, &[
+            0., 0., 0., 0., 0., 0.,
+            gamma, gamma, 0., 0., 0., 0.,
+            8611. / 62500., -1743. / 31250., gamma, 0., 0., 0.,
+            5012029. / 34652500., -654441. / 2922500., 174375. / 388108., gamma, 0., 0.,
+            15267082809. / 155376265600., -71443401. / 120774400., 730878875. / 902184768.,
+            2285395. / 8070912., gamma, 0.,
+            b[0], b[1], b[2], b[3], b[4], gamma,
+        ]);
+
+        Self {
+            explicit,
+            implicit: ImplicitCoeffs {
+                a,
+                b,
+                bhat: Some(bhat),
+                c: vec![0., 0.5, 83. / 250., 31. / 50., 17. / 20., 1.],
+            },
+            order: (4, 3),
+        }
+    }
+
+    /// the number of stages
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.implicit.nstages()
+    }
+}
+
+/// The problem `dy/dt = E(t, y) + I(t, y)` split into a non-stiff part `E` that is
+/// treated explicitly and a stiff part `I` that is treated implicitly.
+///
+/// E: the non-stiff part of the RHS, a function of t and y(t).
+/// I: the stiff part of the RHS, a function of t and y(t).
+#[derive(Debug, Clone)]
+pub struct ImexProblem<E, I, Y>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    /// The non-stiff part `E(t, y)`.
+    explicit: E,
+    /// The stiff part `I(t, y)`.
+    implicit: I,
+    /// Initial value.
+    y0: Y,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImexBuilder<E, I, Y>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    explicit: Option<E>,
+    implicit: Option<I>,
+    y0: Option<Y>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<E, I, Y> Default for ImexBuilder<E, I, Y>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    fn default() -> Self {
+        Self {
+            explicit: None,
+            implicit: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<E, I, Y> ImexBuilder<E, I, Y>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    /// set the non-stiff part of the problem function
+    pub fn explicit(mut self, explicit: E) -> Self {
+        self.explicit = Some(explicit);
+        self
+    }
+
+    /// set the stiff part of the problem function
+    pub fn implicit(mut self, implicit: I) -> Self {
+        self.implicit = Some(implicit);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<Y>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`ImexProblem`].
+    ///
+    /// Returns an error if a field is None.
+    pub fn build(self) -> Result<ImexProblem<E, I, Y>, OdeError> {
+        let explicit = self
+            .explicit
+            .ok_or_else(|| OdeError::uninitialized("Explicit part must be initialized"))?;
+        let implicit = self
+            .implicit
+            .ok_or_else(|| OdeError::uninitialized("Implicit part must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+
+        Ok(ImexProblem {
+            explicit,
+            implicit,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl<E, I, Y, T> ImexProblem<E, I, Y>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    /// convenience method to create a new builder
+    /// same as `ImexBuilder::default()`
+    pub fn builder() -> ImexBuilder<E, I, Y> {
+        ImexBuilder::default()
+    }
+
+    /// Solve the problem using the ARK3(2)4L\[2\]SA pair of Kennedy & Carpenter, order 3
+    /// with an embedded second order error estimate.
+    pub fn ark3(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.oderk_imex(&AdditiveTableau::ark324(), opts)
+    }
+
+    /// Solve the problem using the ARK4(3)6L\[2\]SA pair of Kennedy & Carpenter, order 4
+    /// with an embedded third order error estimate.
+    pub fn ark4(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.oderk_imex(&AdditiveTableau::ark436(), opts)
+    }
+
+    /// Solve the problem with an adaptive additive Runge–Kutta method.
+    ///
+    /// The stiff part is linearized once per step with a finite difference jacobian,
+    /// the non-stiff part is only ever evaluated explicitly. The statistics of the
+    /// solution count the evaluations of either part.
+    pub fn oderk_imex<Ops: Into<AdaptiveOptions>>(
+        &self,
+        tableau: &AdditiveTableau,
+        opts: Ops,
+    ) -> Result<OdeSolution<f64, Y>, OdeError> {
+        let evals = Cell::new(0);
+        let explicit = |t, y: &Y| {
+            evals.set(evals.get() + 1);
+            (self.explicit)(t, y)
+        };
+        let implicit = |t, y: &Y| {
+            evals.set(evals.get() + 1);
+            (self.implicit)(t, y)
+        };
+        let (explicit, implicit) = (&explicit, &implicit);
+        // the full RHS drives the step size selection and the hermite output
+        let problem = OdeProblem::builder()
+            .fun(|t, y: &Y| {
+                let mut dy = explicit(t, y);
+                let di = implicit(t, y);
+                for p in 0..dy.dof() {
+                    *dy.get_mut(p) += di.get(p);
+                }
+                dy
+            })
+            .init(self.y0.clone())
+            .tspan(self.tspan.clone())
+            .build()?;
+        let stiff = OdeProblem::builder()
+            .fun(implicit)
+            .init(self.y0.clone())
+            .tspan(self.tspan.clone())
+            .build()?;
+        let newton = NewtonOptions::default();
+
+        let mut solution = problem.oderk_adapt_implicit(tableau.order.1, opts, |t, dt, y| {
+            let jac = stiff.fdjacobian(t, y);
+            let stats = problem.counter();
+            let ks =
+                solve_additive_stages(explicit, implicit, tableau, t, dt, y, &jac, &newton, stats)?;
+            let (ytrial, yerr) = tableau.implicit.embedded_step(dt, y, &ks);
+            Ok(ImplicitTrial {
+                y: ytrial,
+                err: yerr,
+                interpolant: None,
+                f1: None,
+            })
+        })?;
+        // the jacobians are taken of the stiff part alone
+        solution.stats.num_eval = evals.get();
+        solution.stats.num_jac = stiff.stats().num_jac;
+        Ok(solution)
+    }
+}
+
+/// Solves the stage equations `Y_i = y + dt Σ_{j<i} (ae_ij E_j + ai_ij I_j) + dt ai_ii
+/// I(t + c_i dt, Y_i)` of an additive Runge–Kutta method with a simplified newton
+/// iteration, using `jac` as the jacobian of the stiff part.
+///
+/// Returns the combined stages `k_i = E(t + c_i dt, Y_i) + I(t + c_i dt, Y_i)`, the
+/// solved linear systems are counted in `stats`.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_additive_stages<E, I, Y, T>(
+    explicit: &E,
+    implicit: &I,
+    tableau: &AdditiveTableau,
+    t: f64,
+    dt: f64,
+    y: &Y,
+    jac: &DMatrix<T>,
+    opts: &NewtonOptions,
+    stats: &Cell<Diagnostics>,
+) -> Result<Vec<Y>, OdeError>
+where
+    E: Fn(f64, &Y) -> Y,
+    I: Fn(f64, &Y) -> Y,
+    Y: OdeType<Item = T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+{
+    let (ae, ai, c) = (&tableau.explicit, &tableau.implicit.a, &tableau.implicit.c);
+    let n = y.dof();
+    let s = tableau.nstages();
+
+    let scale = y.ode_iter().fold(1f64, |acc, yi| acc.max(yi.abs().into()));
+    let mut lu: Option<(f64, LU<T, Dynamic, Dynamic>)> = None;
+    let mut kes: Vec<Y> = Vec::with_capacity(s);
+    let mut kis: Vec<Y> = Vec::with_capacity(s);
+
+    for i in 0..s {
+        let ti = t + c[i] * dt;
+
+        // explicit part of the stage
+        let mut e = y.clone();
+        for (j, (ke, ki)) in kes.iter().zip(&kis).enumerate() {
+            let (aej, aij) = (ae[(i, j)] * dt, ai[(i, j)] * dt);
+            for p in 0..n {
+                *e.get_mut(p) += ke.get(p) * aej + ki.get(p) * aij;
+            }
+        }
+
+        let gamma = ai[(i, i)] * dt;
+        if gamma == 0. {
+            kes.push(explicit(ti, &e));
+            kis.push(implicit(ti, &e));
+            continue;
+        }
+
+        if lu.as_ref().is_none_or(|(g, _)| *g != gamma) {
+            let mut m = DMatrix::<T>::identity(n, n);
+            for p in 0..n {
+                for q in 0..n {
+                    m[(p, q)] -= jac[(p, q)] * gamma;
+                }
+            }
+            lu = Some((gamma, m.lu()));
+        }
+        let (_, lu) = lu.as_ref().unwrap();
+
+        let mut yi = e.clone();
+        let mut converged = false;
+        for _ in 0..opts.maxiter {
+            let fi = implicit(ti, &yi);
+            // residual `e + dt ai_ii I(Y_i) - Y_i`
+            let g =
+                DVector::from_iterator(n, (0..n).map(|p| e.get(p) + fi.get(p) * gamma - yi.get(p)));
+            let dy = lu.solve(&g).ok_or(OdeError::InvalidMatrix)?;
+            count(stats, |stats| stats.num_linsolve += 1);
+            for p in 0..n {
+                *yi.get_mut(p) += dy[p];
+            }
+
+            let norm: f64 = dy.amax().into();
+            if norm <= opts.tol * scale {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            return Err(OdeError::NewtonConvergence {
+                at: t,
+                iterations: opts.maxiter,
+            });
+        }
+
+        kes.push(explicit(ti, &yi));
+        // `I_i = (Y_i - e) / (dt ai_ii)`, avoids another evaluation of `I`
+        let mut ki = yi;
+        for p in 0..n {
+            *ki.get_mut(p) = (ki.get(p) - e.get(p)) * (1. / gamma);
+        }
+        kis.push(ki);
+    }
+
+    Ok(kes
+        .into_iter()
+        .zip(kis)
+        .map(|(mut ke, ki)| {
+            for p in 0..n {
+                *ke.get_mut(p) += ki.get(p);
+            }
+            ke
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Reltol};
+
+    #[test]
+    fn ark_order_conditions() {
+        for (tableau, order) in [
+            (AdditiveTableau::ark324(), 3),
+            (AdditiveTableau::ark436(), 4),
+        ] {
+            let (b, c) = (&tableau.implicit.b, &tableau.implicit.c);
+            let dot = |x: &[f64]| b.iter().zip(x).map(|(b, x)| b * x).sum::<f64>();
+            let ones = vec![1.; b.len()];
+            let c2: Vec<f64> = c.iter().map(|c| c * c).collect();
+            let c3: Vec<f64> = c.iter().map(|c| c * c * c).collect();
+            assert!((dot(&ones) - 1.).abs() < 1e-14);
+            assert!((dot(c) - 0.5).abs() < 1e-14);
+            assert!((dot(&c2) - 1. / 3.).abs() < 1e-14);
+            assert_eq!(order == 4, (dot(&c3) - 0.25).abs() < 1e-14);
+            // the coupling conditions of order 3 between both methods
+            for a in [&tableau.explicit, &tableau.implicit.a] {
+                assert!(
+                    (a * DVector::from_row_slice(&ones) - DVector::from_row_slice(c)).amax()
+                        < 1e-14
+                );
+                for a2 in [&tableau.explicit, &tableau.implicit.a] {
+                    let ac = (a * a2 * DVector::from_row_slice(&ones))
+                        .as_slice()
+                        .to_vec();
+                    assert!((dot(&ac) - 1. / 6.).abs() < 1e-14);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ark_stiff_relaxation() {
+        let ops = ImplicitOptions {
+            reltol: Reltol::from(1e-6),
+            abstol: Abstol::from(1e-6),
+            ..Default::default()
+        };
+        // `y' = -sin(t) - λ (y - cos(t))` with the exact solution `cos(t)`, the step size
+        // is independent of the stiffness of the relaxation
+        for lambda in [1e2, 1e6] {
+            let problem = ImexProblem::builder()
+                .explicit(|t, _y: &Vec<f64>| vec![-t.sin()])
+                .implicit(move |t, y: &Vec<f64>| vec![-lambda * (y[0] - t.cos())])
+                .init(vec![1.])
+                .tspan_linspace(0., 10., 2)
+                .build()
+                .unwrap();
+            for solution in [
+                problem.ark3(ops.clone()).unwrap(),
+                problem.ark4(ops.clone()).unwrap(),
+            ] {
+                assert!(solution.tout.len() < 2000);
+                for (t, y) in solution.zipped() {
+                    assert!((y[0] - t.cos()).abs() < 1e-4);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,562 @@
+use crate::error::OdeError;
+use crate::ode::jacobian::JacobianMatrix;
+use crate::ode::linsolve::{Factorization, LinearSolver, LinearSolverType};
+use crate::ode::newton::{NewtonCache, NewtonOptions, NewtonSolver};
+use crate::ode::runge_kutta::ButcherTableau;
+use crate::ode::solution::Diagnostics;
+use crate::ode::sparse::CscMatrix;
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::allocator::Allocator;
+use na::{DMatrix, DVector, DefaultAllocator, Dim, Dynamic, U1, U2};
+use std::cell::Cell;
+use std::ops::{Add, Mul};
+
+/// A borrowed mass matrix `M(t, y)`.
+pub type DynMass<'a, Y, T> = &'a dyn Fn(f64, &Y) -> DMatrix<T>;
+
+/// A jacobian `J` the iteration matrices of the implicit methods are formed and
+/// factorized from.
+pub trait IterationMatrix<T: RealField> {
+    /// The factorization, or any other [`LinearSolver`] of the iteration matrix.
+    type Solver: LinearSolver<T>;
+
+    /// The factorization of `M - gamma J` with `solver`, with the identity for `M` if
+    /// there is no mass matrix.
+    fn factor(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        gamma: f64,
+        solver: LinearSolverType,
+    ) -> Result<Self::Solver, OdeError> {
+        self.factor_stages(mass, &DMatrix::from_element(1, 1, 1.), gamma, solver)
+    }
+
+    /// The factorization of `I ⊗ M - dt (A ⊗ J)` with `solver` for the coupled stage
+    /// equations of an implicit Runge–Kutta method with the rk matrix `A`.
+    fn factor_stages(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        a: &DMatrix<f64>,
+        dt: f64,
+        solver: LinearSolverType,
+    ) -> Result<Self::Solver, OdeError>;
+}
+
+impl<T: RealField> IterationMatrix<T> for DMatrix<T> {
+    type Solver = Factorization<T>;
+
+    fn factor_stages(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        a: &DMatrix<f64>,
+        dt: f64,
+        solver: LinearSolverType,
+    ) -> Result<Factorization<T>, OdeError> {
+        let n = self.nrows();
+        let s = a.nrows();
+        let mut m = DMatrix::<T>::zeros(s * n, s * n);
+        for i in 0..s {
+            match mass {
+                Some(mass) => m.slice_mut((i * n, i * n), (n, n)).copy_from(mass),
+                None => m.slice_mut((i * n, i * n), (n, n)).fill_diagonal(T::one()),
+            }
+        }
+        for i in 0..s {
+            for j in 0..s {
+                let aij = a[(i, j)] * dt;
+                if aij == 0. {
+                    continue;
+                }
+                let aij = T::from_subset(&aij);
+                for p in 0..n {
+                    for q in 0..n {
+                        m[(i * n + p, j * n + q)] -= self[(p, q)] * aij;
+                    }
+                }
+            }
+        }
+        Factorization::dense(m, solver)
+    }
+}
+
+impl<T: RealField> IterationMatrix<T> for CscMatrix<T> {
+    type Solver = Factorization<T>;
+
+    /// Only sparse without a mass matrix.
+    fn factor_stages(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        a: &DMatrix<f64>,
+        dt: f64,
+        solver: LinearSolverType,
+    ) -> Result<Factorization<T>, OdeError> {
+        if mass.is_some() {
+            return self.to_dense().factor_stages(mass, a, dt, solver);
+        }
+        Factorization::sparse(&self.stage_matrix(a, dt), solver)
+    }
+}
+
+impl<T: RealField> IterationMatrix<T> for JacobianMatrix<T> {
+    type Solver = Factorization<T>;
+
+    fn factor_stages(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        a: &DMatrix<f64>,
+        dt: f64,
+        solver: LinearSolverType,
+    ) -> Result<Factorization<T>, OdeError> {
+        match self {
+            JacobianMatrix::Dense(jac) => jac.factor_stages(mass, a, dt, solver),
+            JacobianMatrix::Sparse(jac) => jac.factor_stages(mass, a, dt, solver),
+        }
+    }
+}
+
+/// The coefficients of an implicit Runge–Kutta method, independent of the number of stages.
+#[derive(Clone, Debug)]
+pub struct ImplicitCoeffs {
+    /// rk matrix
+    pub a: DMatrix<f64>,
+    /// weights used for stepping
+    pub b: Vec<f64>,
+    /// weights of the embedded method used for error-checking, if any
+    pub bhat: Option<Vec<f64>>,
+    /// nodes
+    pub c: Vec<f64>,
+}
+
+impl ImplicitCoeffs {
+    pub fn from_tableau<S: Dim>(btab: &ButcherTableau<S>) -> Self
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        let nstages = btab.nstages();
+        let b = btab.b.as_slice();
+        Self {
+            a: DMatrix::from_iterator(nstages, nstages, btab.a.iter().cloned()),
+            b: b[..nstages].to_vec(),
+            bhat: if btab.is_adaptive() {
+                Some(b[nstages..].to_vec())
+            } else {
+                None
+            },
+            c: btab.c.iter().cloned().collect(),
+        }
+    }
+
+    /// the number of stages
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.c.len()
+    }
+
+    /// whether the rk matrix is lower triangular, so that the stages can be solved for
+    /// one after another
+    pub fn is_diagonally_implicit(&self) -> bool {
+        let s = self.nstages();
+        (0..s).all(|i| (i + 1..s).all(|j| self.a[(i, j)] == 0.))
+    }
+
+    /// Computes the solution after a step of size `dt` from the stages `ks`
+    /// and its error estimate `dt Σ (b_i - bhat_i) k_i`.
+    ///
+    /// Without embedded weights the error estimate is zero.
+    pub fn embedded_step<Y: OdeType>(&self, dt: f64, y: &Y, ks: &[Y]) -> (Y, Y) {
+        let mut ytrial = y.clone();
+        let mut yerr = y.clone();
+        yerr.set_zero();
+        for (i, k) in ks.iter().enumerate() {
+            let b = self.b[i] * dt;
+            let e = self
+                .bhat
+                .as_ref()
+                .map_or(0., |bhat| (self.b[i] - bhat[i]) * dt);
+            for d in 0..y.dof() {
+                *ytrial.get_mut(d) += k.get(d) * b;
+                *yerr.get_mut(d) += k.get(d) * e;
+            }
+        }
+        (ytrial, yerr)
+    }
+}
+
+/// A trial step of an adaptive implicit method.
+#[derive(Clone, Debug)]
+pub struct ImplicitTrial<Y> {
+    /// solution at the end of the step
+    pub y: Y,
+    /// local error estimate of the step
+    pub err: Y,
+    /// continuous output of the method, hermite interpolation is used otherwise
+    pub interpolant: Option<Interpolant<Y>>,
+    /// RHS at the end of the step, if the method computed it as its last stage
+    pub f1: Option<Y>,
+}
+
+/// The continuous output of a step.
+#[derive(Clone, Debug)]
+pub enum Interpolant<Y> {
+    Collocation(Collocation<Y>),
+    Rosenbrock(RosenbrockDense<Y>),
+}
+
+impl<Y: OdeType> Interpolant<Y> {
+    /// Evaluates the interpolant of a step from `y` at `θ ∈ [0, 1]`.
+    pub fn interpolate(&self, theta: f64, y: &Y) -> Y {
+        match self {
+            Interpolant::Collocation(collocation) => collocation.interpolate(theta, y),
+            Interpolant::Rosenbrock(dense) => dense.interpolate(theta, y),
+        }
+    }
+}
+
+/// The collocation polynomial `u` of a step of size `dt` from `y`, with
+/// `u(0) = y` and `u(c_i dt) = y + z_i`.
+#[derive(Clone, Debug)]
+pub struct Collocation<Y> {
+    /// collocation nodes
+    pub c: Vec<f64>,
+    /// stage increments `z_i = Y_i - y`
+    pub z: Vec<Y>,
+}
+
+impl<Y: OdeType> Collocation<Y> {
+    /// Evaluates the collocation polynomial at `θ ∈ [0, 1]`.
+    pub fn interpolate(&self, theta: f64, y: &Y) -> Y {
+        let mut u = y.clone();
+        for (i, z) in self.z.iter().enumerate() {
+            // lagrange polynomial for the nodes `0, c_1, ..., c_s`
+            let ci = self.c[i];
+            let mut l = theta / ci;
+            for (j, cj) in self.c.iter().enumerate() {
+                if j != i {
+                    l *= (theta - cj) / (ci - cj);
+                }
+            }
+            for d in 0..u.dof() {
+                *u.get_mut(d) += z.get(d) * l;
+            }
+        }
+        u
+    }
+}
+
+/// The stiffly accurate continuous output of Rosenbrock methods of the Rodas family
+///
+/// ```latex
+/// u(θ) = (1 - θ) y_0 + θ (y_1 + (1 - θ) (k_1 + θ k_2))
+/// ```
+///
+/// where `k_1, k_2` are linear combinations of the stages.
+#[derive(Clone, Debug)]
+pub struct RosenbrockDense<Y> {
+    /// solution at the end of the step
+    pub y1: Y,
+    pub k1: Y,
+    pub k2: Y,
+}
+
+impl<Y: OdeType> RosenbrockDense<Y> {
+    /// Evaluates the interpolant at `θ ∈ [0, 1]`.
+    pub fn interpolate(&self, theta: f64, y: &Y) -> Y {
+        let mut u = y.clone();
+        for d in 0..u.dof() {
+            *u.get_mut(d) = y.get(d) * (1. - theta)
+                + (self.y1.get(d) + (self.k1.get(d) + self.k2.get(d) * theta) * (1. - theta))
+                    * theta;
+        }
+        u
+    }
+}
+
+/// Solves the coupled stage equations of an implicit Runge–Kutta step of size `dt`
+///
+/// ```latex
+/// z_i = dt \sum_j a_{ij} f(t + c_j dt, y + z_j)
+/// ```
+///
+/// with a simplified newton iteration, the jacobian `jac` of `f` at `(t, y)` is used
+/// for all iterations and its factorized iteration matrix is reused from `cache`.
+/// Returns the stages `k_j = f(t + c_j dt, y + z_j)`, the solved linear systems are
+/// counted in `stats`.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_stages<F, Y, T, J>(
+    f: &F,
+    coeffs: &ImplicitCoeffs,
+    t: f64,
+    dt: f64,
+    y: &Y,
+    jac: &J,
+    solver: LinearSolverType,
+    opts: &NewtonOptions,
+    cache: &mut NewtonCache<J::Solver>,
+    stats: &Cell<Diagnostics>,
+) -> Result<Vec<Y>, OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    Y: OdeType<Item = T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    J: IterationMatrix<T>,
+{
+    let z = solve_stage_increments(f, coeffs, t, dt, y, jac, solver, None, opts, cache, stats)?;
+    Ok(z.iter()
+        .zip(&coeffs.c)
+        .map(|(zj, cj)| f(t + cj * dt, &y.clone().sum(zj)))
+        .collect())
+}
+
+/// Solves the coupled stage equations of an implicit Runge–Kutta step of size `dt` for
+/// the problem `M(t, y) dy/dt = f(t, y)`
+///
+/// ```latex
+/// \sum_j a_{ij} M(t + c_j dt, Y_j) \sum_l (A^{-1})_{jl} z_l = dt \sum_j a_{ij} f(t + c_j dt, Y_j)
+/// ```
+///
+/// with `Y_j = y + z_j`, which reduces to `M z_i = dt Σ_j a_ij f(t + c_j dt, Y_j)` for a
+/// constant mass matrix and to the usual stage equations without one. A state dependent
+/// mass matrix is evaluated at the stages, which requires an invertible rk matrix.
+///
+/// The equations are solved with a simplified newton iteration, the jacobian `jac` of
+/// `f` and the mass matrix at `(t, y)` are used for all iterations, the factorized
+/// iteration matrix is reused from `cache`. Since the stages are not recovered from the
+/// right hand sides, the mass matrix may be singular.
+/// Returns the stage increments `z_i`, the solved linear systems are counted in `stats`.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_stage_increments<F, Y, T, J>(
+    f: &F,
+    coeffs: &ImplicitCoeffs,
+    t: f64,
+    dt: f64,
+    y: &Y,
+    jac: &J,
+    solver: LinearSolverType,
+    mass: Option<DynMass<'_, Y, T>>,
+    opts: &NewtonOptions,
+    cache: &mut NewtonCache<J::Solver>,
+    stats: &Cell<Diagnostics>,
+) -> Result<Vec<Y>, OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    Y: OdeType<Item = T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    J: IterationMatrix<T>,
+{
+    let (a, c) = (&coeffs.a, &coeffs.c);
+    let n = y.dof();
+    let s = coeffs.nstages();
+    // the derivatives `(A^-1 ⊗ I) z / dt` of the collocation polynomial at the nodes
+    let ainv = match mass {
+        Some(_) => Some(a.clone().try_inverse().ok_or(OdeError::InvalidMatrix)?),
+        None => None,
+    };
+
+    // iteration matrix `I ⊗ M - dt (A ⊗ J)`, possibly of an earlier step size
+    let m0 = mass.map(|mass| mass(t, y));
+    let dt_lu = cache.step(dt);
+    let lu = cache.get_or_factor(
+        1.,
+        || jac.factor_stages(m0.as_ref(), a, dt_lu, solver),
+        stats,
+    )?;
+
+    let increment = |z: &DVector<T>, j: usize| {
+        let mut zj = y.clone();
+        for p in 0..n {
+            zj.insert(p, z[j * n + p]);
+        }
+        zj
+    };
+
+    let scale = y.ode_iter().fold(1f64, |acc, yi| acc.max(yi.abs().into()));
+    let residual = |z: &DVector<T>| {
+        // `w_j = dt f(t_j, Y_j) - M(t_j, Y_j) Σ_l (A^-1)_jl z_l`, or `dt f(t_j, Y_j)`
+        let mut w = DVector::<T>::zeros(s * n);
+        for j in 0..s {
+            let tj = t + c[j] * dt;
+            let yj = y.clone().sum(&increment(z, j));
+            let kj = f(tj, &yj);
+            for p in 0..n {
+                w[j * n + p] = kj.get(p) * dt;
+            }
+            if let (Some(mass), Some(ainv)) = (mass, &ainv) {
+                let vj = (0..s).fold(DVector::<T>::zeros(n), |acc, l| {
+                    acc + z.rows(l * n, n) * (T::one() * ainv[(j, l)])
+                });
+                let mv = mass(tj, &yj) * vj;
+                for p in 0..n {
+                    w[j * n + p] -= mv[p];
+                }
+            }
+        }
+
+        // residual `Σ_j a_ij w_j`, less `z_i` without a mass matrix
+        let mut g = match mass {
+            Some(_) => DVector::<T>::zeros(s * n),
+            None => -z.clone(),
+        };
+        for i in 0..s {
+            for j in 0..s {
+                let aij = a[(i, j)];
+                for p in 0..n {
+                    g[i * n + p] += w[j * n + p] * aij;
+                }
+            }
+        }
+        Some(g)
+    };
+    let norm = |dz: &DVector<T>| dz.amax().into();
+
+    let newton = NewtonSolver::new(opts, lu).first_estimate(1.);
+    match newton.solve(
+        DVector::zeros(s * n),
+        opts.tol * scale,
+        residual,
+        norm,
+        stats,
+    )? {
+        Some(z) => {
+            cache.converged(z.rate);
+            Ok((0..s).map(|j| increment(&z.x, j)).collect())
+        }
+        None => Err(OdeError::NewtonConvergence {
+            at: t,
+            iterations: opts.maxiter,
+        }),
+    }
+}
+
+/// Solves the stage equations of an implicit Runge–Kutta step of size `dt` one stage
+/// after another with [`solve_diagonal_stages`] if the method is diagonally implicit, and
+/// all at once with [`solve_stages`] otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_implicit_stages<F, Y, T, J>(
+    f: &F,
+    coeffs: &ImplicitCoeffs,
+    t: f64,
+    dt: f64,
+    y: &Y,
+    jac: &J,
+    solver: LinearSolverType,
+    opts: &NewtonOptions,
+    cache: &mut NewtonCache<J::Solver>,
+    stats: &Cell<Diagnostics>,
+) -> Result<Vec<Y>, OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    Y: OdeType<Item = T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    J: IterationMatrix<T>,
+{
+    if coeffs.is_diagonally_implicit() {
+        solve_diagonal_stages(f, coeffs, t, dt, y, jac, solver, opts, cache, stats)
+    } else {
+        solve_stages(f, coeffs, t, dt, y, jac, solver, opts, cache, stats)
+    }
+}
+
+/// Solves the stage equations of a diagonally implicit Runge–Kutta step of size `dt`
+/// one stage after another
+///
+/// ```latex
+/// Y_i = y + dt \sum_{j<i} a_{ij} k_j + dt a_{ii} f(t + c_i dt, Y_i)
+/// ```
+///
+/// with a simplified newton iteration, the jacobian `jac` of `f` at `(t, y)` is used
+/// for all iterations. The iteration matrix `I - dt a_ii J` is only factorized once for
+/// each distinct diagonal coefficient and reused from `cache`, so singly diagonally
+/// implicit methods require at most a single LU factorization per step. Stages with
+/// `a_ii = 0` are computed explicitly.
+/// Returns the stages `k_i = f(t + c_i dt, Y_i)`, the solved linear systems are counted
+/// in `stats`.
+#[allow(clippy::too_many_arguments)]
+pub fn solve_diagonal_stages<F, Y, T, J>(
+    f: &F,
+    coeffs: &ImplicitCoeffs,
+    t: f64,
+    dt: f64,
+    y: &Y,
+    jac: &J,
+    solver: LinearSolverType,
+    opts: &NewtonOptions,
+    cache: &mut NewtonCache<J::Solver>,
+    stats: &Cell<Diagnostics>,
+) -> Result<Vec<Y>, OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    Y: OdeType<Item = T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    J: IterationMatrix<T>,
+{
+    let (a, c) = (&coeffs.a, &coeffs.c);
+    let n = y.dof();
+    let s = coeffs.nstages();
+
+    let scale = y.ode_iter().fold(1f64, |acc, yi| acc.max(yi.abs().into()));
+    let dt_lu = cache.step(dt);
+    let mut ks: Vec<Y> = Vec::with_capacity(s);
+
+    for i in 0..s {
+        let ti = t + c[i] * dt;
+
+        // explicit part of the stage
+        let mut e = y.clone();
+        for (j, k) in ks.iter().enumerate() {
+            let aij = a[(i, j)] * dt;
+            for p in 0..n {
+                *e.get_mut(p) += k.get(p) * aij;
+            }
+        }
+
+        let gamma = a[(i, i)] * dt;
+        if gamma == 0. {
+            ks.push(f(ti, &e));
+            continue;
+        }
+
+        let aii = a[(i, i)];
+        let lu = cache.get_or_factor(aii, || jac.factor(None, aii * dt_lu, solver), stats)?;
+
+        let residual = |x: &DVector<T>| {
+            let mut yi = e.clone();
+            for p in 0..n {
+                yi.insert(p, x[p]);
+            }
+            let fi = f(ti, &yi);
+            // residual `e + dt a_ii f(Y_i) - Y_i`
+            Some(DVector::from_iterator(
+                n,
+                (0..n).map(|p| e.get(p) + fi.get(p) * gamma - x[p]),
+            ))
+        };
+        let norm = |dy: &DVector<T>| dy.amax().into();
+        let newton = NewtonSolver::new(opts, lu).first_estimate(1.);
+        let x0 = DVector::from_iterator(n, e.ode_iter());
+        let yi = match newton.solve(x0, opts.tol * scale, residual, norm, stats)? {
+            Some(yi) => {
+                cache.converged(yi.rate);
+                yi.x
+            }
+            None => {
+                return Err(OdeError::NewtonConvergence {
+                    at: t,
+                    iterations: opts.maxiter,
+                })
+            }
+        };
+
+        // `k_i = (Y_i - e) / (dt a_ii)`, avoids another evaluation of `f`
+        let mut k = e.clone();
+        for p in 0..n {
+            *k.get_mut(p) = (yi[p] - e.get(p)) * (1. / gamma);
+        }
+        ks.push(k);
+    }
+
+    Ok(ks)
+}
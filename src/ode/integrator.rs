@@ -0,0 +1,1080 @@
+use crate::error::OdeError;
+use crate::ode::callback::{itp, CallbackIndex, Event};
+use crate::ode::coeff::{CoefficientMap, CoefficientPoint};
+use crate::ode::controller::StepController;
+use crate::ode::dense::{DenseCoeffs, DenseOutput, StepInterpolant};
+use crate::ode::dop853::Dop853Coeffs;
+use crate::ode::options::{AdaptiveOptions, StepTimeout};
+use crate::ode::problem::OdeProblem;
+use crate::ode::runge_kutta::{ButcherTableau, EmbeddedPair};
+use crate::ode::solution::{Diagnostics, ReturnCode};
+use crate::ode::stiffness::{StiffnessAction, StiffnessDetector};
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::{allocator::Allocator, DMatrix, DefaultAllocator, Dim, U1, U12, U2};
+use num_traits::{abs, signum};
+use std::ops::{Add, Mul};
+
+/// The number of points inside a step at which the event functions are sampled.
+const EVENT_SAMPLES: usize = 10;
+
+/// How an [`OdeIntegrator`] estimates the local error of a step.
+#[derive(Debug, Clone)]
+enum ErrorEstimate {
+    /// the difference of the solutions of the embedded pair of the tableau
+    Embedded,
+    /// the embedded 5th and 3rd order estimates of the Dormand–Prince 8(5,3) method,
+    /// combined as done by Hairer's `DOP853`
+    Dop853 { e5: Vec<f64>, e3: Vec<f64> },
+}
+
+impl ErrorEstimate {
+    /// The order of the estimate, which determines the initial step and the step size
+    /// control.
+    fn order<S: Dim>(&self, btab: &ButcherTableau<S>) -> usize
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        match self {
+            ErrorEstimate::Embedded => btab.symbol.order().min(),
+            ErrorEstimate::Dop853 { .. } => 7,
+        }
+    }
+
+    /// The number of steps after a rejected step that may not grow, Hairer's `DOP853`
+    /// only keeps the size of the step right after the rejection.
+    fn rejection_timeout(&self) -> usize {
+        match self {
+            ErrorEstimate::Embedded => *StepTimeout::default(),
+            ErrorEstimate::Dop853 { .. } => 1,
+        }
+    }
+}
+
+/// The continuous extension of the method of an [`OdeIntegrator`].
+#[derive(Debug, Clone)]
+enum Extension {
+    /// a continuous extension given by the polynomial weights of the stages
+    Tableau(DenseCoeffs),
+    /// the 7th order continuous extension of the Dormand–Prince 8(5,3) method
+    Dop853(Box<Dop853Coeffs>),
+}
+
+/// The last accepted step of an [`OdeIntegrator`].
+#[derive(Debug, Clone)]
+struct AcceptedStep<Y> {
+    /// start of the step
+    t: f64,
+    /// size of the step
+    dt: f64,
+    /// end of the integration over the step, before `t + dt` if it was interrupted by an
+    /// event
+    end: f64,
+    /// state at the start of the step
+    y: Y,
+    /// state at the end of the step
+    y1: Y,
+    /// stages of the step, the first one is the derivative at the start
+    ks: Vec<Y>,
+    /// derivative at the end of the step
+    f1: Y,
+    /// continuous output of the step, only computed if required
+    interpolant: Option<StepInterpolant<Y>>,
+}
+
+/// Integrates an [`OdeProblem`] step by step with an embedded explicit Runge–Kutta
+/// method.
+///
+/// The integrator owns the state of the solver and advances it by one accepted step
+/// with [`OdeIntegrator::step`], which is also how it iterates over the `(t, y)` pairs
+/// of the accepted steps. In between steps the state may be replaced with
+/// [`OdeIntegrator::set_state`] and the whole integration restarted with
+/// [`OdeIntegrator::reinit`], which is what co-simulation and control loops need. The
+/// step size control is the same as the one of [`OdeProblem::tsit5`] and friends, steps
+/// are truncated to end exactly at the [`Tstops`](crate::ode::options::Tstops) of the
+/// options.
+///
+/// [`OdeProblem::dop853_integrator`] drives the Dormand–Prince 8(5,3) method, whose
+/// error estimate and continuous extension are not given by its tableau.
+#[derive(Debug)]
+pub struct OdeIntegrator<'a, F, Y, S, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    problem: &'a OdeProblem<F, Y, G, M>,
+    btab: ButcherTableau<S>,
+    /// the weights of the solution and of its error estimate
+    pair: EmbeddedPair,
+    estimate: ErrorEstimate,
+    /// the order of the error estimate
+    order: usize,
+    /// whether the last stage of a step is the RHS at its end, which is reused as the
+    /// first stage of the next step
+    fsal: bool,
+    /// continuous extension of the method, hermite interpolation is used otherwise
+    dense: Option<Extension>,
+    opts: AdaptiveOptions,
+    /// current time
+    t: f64,
+    /// current state
+    y: Y,
+    /// derivative at the current state
+    f0: Y,
+    /// size of the next step
+    dt: f64,
+    /// end of the integration
+    tend: f64,
+    /// direction of the integration
+    tdir: f64,
+    minstep: f64,
+    maxstep: f64,
+    controller: Box<dyn StepController>,
+    timeout: usize,
+    /// whether the next step ends at `tend`
+    last_step: bool,
+    /// the remaining tstops, the next one last
+    tstops: Vec<f64>,
+    /// whether the integration reached `tend` or was aborted
+    finished: bool,
+    previous: Option<AcceptedStep<Y>>,
+    /// whether the next step is taken at the minimum step size without error control,
+    /// c.f. [`ForceDtmin`](crate::ode::options::ForceDtmin)
+    forced: bool,
+    /// buffers of the stages of a step, reused over steps
+    stages: CoefficientMap<Y>,
+    /// values of the event functions at the current state
+    gprev: Vec<f64>,
+    /// the time and continuous callbacks of the last event
+    last_event: Option<(f64, Vec<usize>)>,
+    /// the event that interrupted the last step
+    event: Option<Event<Y>>,
+    /// continuous output of all steps, if requested by the options
+    output: Option<DenseOutput<Y>>,
+    /// the stiffness detector, unless disabled or not available for the method
+    stiffness: Option<StiffnessDetector>,
+    /// the time at which the problem first appeared stiff
+    stiff_at: Option<f64>,
+    /// the accepted and rejected steps since the start of the integration
+    stats: Diagnostics,
+    /// the statistics of the problem at the start of the integration
+    start: Diagnostics,
+    retcode: ReturnCode,
+}
+
+impl<'a, F, Y, S, G, M, T> OdeIntegrator<'a, F, Y, S, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    /// Creates a new integrator for `problem` from the start to the end of its time
+    /// span.
+    ///
+    /// Output inside a step is computed with the continuous extension `dense` if
+    /// provided, otherwise with the one of the tableau or with hermite interpolation if
+    /// it has none. Fails for implicit tableaus, which are solved with
+    /// [`OdeProblem::oderk_sdirk`].
+    pub fn new<O: Into<AdaptiveOptions>>(
+        problem: &'a OdeProblem<F, Y, G, M>,
+        btab: ButcherTableau<S>,
+        dense: Option<DenseCoeffs>,
+        opts: O,
+    ) -> Result<Self, OdeError> {
+        let dense = dense.or_else(|| btab.dense.clone()).map(Extension::Tableau);
+        Self::with_estimate(problem, btab, ErrorEstimate::Embedded, dense, opts.into())
+    }
+
+    /// Creates a new integrator like [`OdeIntegrator::new`] with the error estimate
+    /// `estimate` and the continuous extension `dense`.
+    fn with_estimate(
+        problem: &'a OdeProblem<F, Y, G, M>,
+        btab: ButcherTableau<S>,
+        estimate: ErrorEstimate,
+        dense: Option<Extension>,
+        opts: AdaptiveOptions,
+    ) -> Result<Self, OdeError> {
+        problem.check_mass_of("OdeIntegrator")?;
+        let pair = btab.embedded_pair()?;
+        if btab.is_implicit() {
+            return Err(OdeError::not_explicit());
+        }
+        let tspan = problem.tspan();
+        if tspan.is_empty() {
+            return Err(OdeError::ZeroTimeSpan);
+        }
+        let (t0, tend) = (tspan[0], tspan[tspan.len() - 1]);
+        let y0 = problem.y0().clone();
+
+        let order = estimate.order(&btab);
+        let controller = opts.controller.0.controller(order, &opts);
+        let stiffness = match *opts.stiffness {
+            StiffnessAction::Ignore => None,
+            _ => StiffnessDetector::from_tableau(&btab),
+        };
+        let mut integrator = Self {
+            problem,
+            fsal: btab.is_first_same_as_last(),
+            btab,
+            pair,
+            estimate,
+            order,
+            dense,
+            opts,
+            t: t0,
+            f0: y0.clone(),
+            y: y0.clone(),
+            dt: 0.,
+            tend,
+            tdir: 1.,
+            minstep: 0.,
+            maxstep: 0.,
+            controller,
+            timeout: 0,
+            last_step: false,
+            tstops: Vec::new(),
+            finished: false,
+            previous: None,
+            forced: false,
+            stages: CoefficientMap::default(),
+            gprev: Vec::new(),
+            last_event: None,
+            event: None,
+            output: None,
+            stiffness,
+            stiff_at: None,
+            stats: Diagnostics::default(),
+            start: Diagnostics::default(),
+            retcode: ReturnCode::Success,
+        };
+        integrator.reinit(y0, t0, tend)?;
+        Ok(integrator)
+    }
+
+    /// Restarts the integration at `(t0, y0)` towards `tend`.
+    ///
+    /// The initial step size, the statistics and the stored continuous output are reset.
+    pub fn reinit(&mut self, y0: Y, t0: f64, tend: f64) -> Result<(), OdeError> {
+        let (reltol, abstol) = self.opts.tolerances(y0.dof())?;
+        self.start = self.problem.stats();
+        let init = self
+            .problem
+            .hinit(&y0, t0, tend, self.order, &reltol, &abstol)?;
+
+        self.dt = if self.opts.initstep.0 != 0. {
+            if (signum(self.opts.initstep.0) - init.tdir).abs() < f64::EPSILON {
+                self.opts.initstep.0
+            } else {
+                return Err(OdeError::InvalidInitstep);
+            }
+        } else {
+            init.h
+        };
+        self.minstep = self
+            .opts
+            .minstep
+            .as_ref()
+            .map_or_else(|| abs(tend - t0) / 1e18, |step| step.0);
+        self.maxstep = self
+            .opts
+            .maxstep
+            .as_ref()
+            .map_or_else(|| abs(tend - t0) / 2.5, |step| step.0);
+        self.t = t0;
+        self.y = y0;
+        self.f0 = init.f0;
+        self.tend = tend;
+        self.tdir = init.tdir;
+        self.controller.reset();
+        self.timeout = 0;
+        self.last_step = (t0 + self.dt - tend).abs() <= f64::EPSILON;
+        let tdir = init.tdir;
+        self.tstops = self
+            .opts
+            .tstops
+            .iter()
+            .copied()
+            .filter(|&ts| tdir * t0 < tdir * ts && tdir * ts < tdir * tend)
+            .collect();
+        self.tstops
+            .sort_by(|a, b| (tdir * b).total_cmp(&(tdir * a)));
+        self.finished = false;
+        self.previous = None;
+        self.gprev = self.event_values();
+        self.last_event = None;
+        self.event = None;
+        self.output = if self.opts.dense.0 {
+            Some(DenseOutput::default())
+        } else {
+            None
+        };
+        if let Some(stiffness) = self.stiffness.as_mut() {
+            stiffness.reset();
+        }
+        self.stiff_at = None;
+        self.stats = Diagnostics::default();
+        self.retcode = ReturnCode::Success;
+        Ok(())
+    }
+
+    /// The options of the integration.
+    #[inline]
+    pub fn options(&self) -> &AdaptiveOptions {
+        &self.opts
+    }
+
+    /// The current time.
+    #[inline]
+    pub fn t(&self) -> f64 {
+        self.t
+    }
+
+    /// The current state.
+    #[inline]
+    pub fn y(&self) -> &Y {
+        &self.y
+    }
+
+    /// The size of the next step.
+    #[inline]
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// The end of the integration.
+    #[inline]
+    pub fn tend(&self) -> f64 {
+        self.tend
+    }
+
+    /// Whether the integration reached its end or was aborted, c.f.
+    /// [`OdeIntegrator::retcode`].
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// How the integration ended so far.
+    #[inline]
+    pub fn retcode(&self) -> ReturnCode {
+        self.retcode
+    }
+
+    /// The time at which the problem first appeared stiff, c.f.
+    /// [`Stiffness`](crate::ode::options::Stiffness).
+    #[inline]
+    pub fn stiff_at(&self) -> Option<f64> {
+        self.stiff_at
+    }
+
+    /// Statistics of the integration so far.
+    #[inline]
+    pub fn stats(&self) -> Diagnostics {
+        Diagnostics {
+            accepted_steps: self.stats.accepted_steps,
+            rejected_steps: self.stats.rejected_steps,
+            ..self.problem.stats() - self.start
+        }
+    }
+
+    /// Replaces the current state.
+    ///
+    /// The integration continues from `y` at the current time with the current step
+    /// size, output inside the previous step is no longer available.
+    pub fn set_state(&mut self, y: Y) {
+        self.f0 = self.problem.rhs(self.t, &y);
+        self.y = y;
+        self.previous = None;
+        self.timeout = 0;
+        self.gprev = self.event_values();
+        self.event = None;
+    }
+
+    /// The event that interrupted the last step, if any.
+    #[inline]
+    pub fn event(&self) -> Option<&Event<Y>> {
+        self.event.as_ref()
+    }
+
+    /// Replaces the step size controller selected by the options.
+    pub fn set_controller<C: StepController + 'static>(&mut self, controller: C) {
+        self.controller = Box::new(controller);
+    }
+
+    /// Sets the size of the next step, limited by the end of the integration.
+    pub fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+        self.last_step = self.tdir * (self.t + dt) >= self.tdir * self.tend;
+        if self.last_step {
+            self.dt = self.tend - self.t;
+        }
+    }
+
+    /// Advances the integration by one accepted step.
+    ///
+    /// Returns `false` without stepping if the integration already finished, if the
+    /// step size falls below the minimum step size, which is reported as
+    /// [`ReturnCode::DtLessThanMin`], and if the maximum number of steps was taken, which
+    /// is reported as [`ReturnCode::MaxIters`]. With [`StiffnessAction::Abort`], the
+    /// integration finishes after the step at which the problem appears stiff, which is
+    /// reported as [`ReturnCode::Stiff`].
+    ///
+    /// If a callback of the problem triggers inside the step, the step ends at the event
+    /// and the state is modified by the callback, c.f. [`OdeIntegrator::event`].
+    pub fn step(&mut self) -> Result<bool, OdeError> {
+        if self.finished {
+            return Ok(false);
+        }
+        self.event = None;
+
+        loop {
+            if (self.stats.accepted_steps + self.stats.rejected_steps) as usize
+                >= *self.opts.maxiters
+            {
+                self.retcode = ReturnCode::MaxIters;
+                self.finished = true;
+                return Ok(false);
+            }
+            let (t, mut dt) = (self.t, self.dt);
+            // end the step at the next tstop, if it is within 1% of the step
+            let tstop = self
+                .tstops
+                .last()
+                .copied()
+                .filter(|&ts| self.tdir * (t + dt + dt / 100.) >= self.tdir * ts);
+            if let Some(ts) = tstop {
+                dt = ts - t;
+            }
+            let mut coeffs = std::mem::take(&mut self.stages);
+            match coeffs.first_mut() {
+                Some(init) => {
+                    init.k.clone_from(&self.f0);
+                    init.y.clone_from(&self.y);
+                }
+                None => coeffs.push(CoefficientPoint::new(self.f0.clone(), self.y.clone())),
+            }
+            self.problem
+                .calc_coefficients_into(&self.btab, t, dt, &mut coeffs);
+            let (ytrial, yerr) = self.pair.step(dt, &self.y, coeffs.ks());
+
+            // check error and find a new step size
+            let err = self.error(dt, &ytrial, &yerr, &coeffs);
+            let timeout = self.timeout;
+            self.timeout = timeout.saturating_sub(1);
+
+            if err < 1. || self.forced {
+                self.forced = false;
+                let mut dtnew = self.controller.accept(dt, err);
+                if dtnew.abs() > self.maxstep {
+                    dtnew = self.tdir * self.maxstep;
+                }
+                if timeout > 0 && dtnew.abs() > dt.abs() {
+                    // no increase for timeout controlled steps
+                    dtnew = dt;
+                }
+                // accept step
+                self.stats.accepted_steps += 1;
+                let f1 = if self.fsal {
+                    coeffs[self.btab.nstages() - 1].k.clone()
+                } else {
+                    self.problem.rhs(t + dt, &ytrial)
+                };
+                let stiff = self.stiffness.as_mut().is_some_and(|stiffness| {
+                    stiffness.update(dt, &ytrial, &f1, &coeffs[stiffness.stage()])
+                });
+                let tnew = match tstop {
+                    Some(ts) => ts,
+                    None if self.last_step => self.tend,
+                    None => t + dt,
+                };
+                let y = std::mem::replace(&mut self.y, ytrial);
+                // reuse the stages of the previous step as buffers
+                let mut ks = self
+                    .previous
+                    .take()
+                    .map_or_else(Vec::new, |previous| previous.ks);
+                ks.truncate(coeffs.len());
+                for (i, k) in coeffs.ks().enumerate() {
+                    match ks.get_mut(i) {
+                        Some(buffer) => buffer.clone_from(k),
+                        None => ks.push(k.clone()),
+                    }
+                }
+                self.stages = coeffs;
+                self.previous = Some(AcceptedStep {
+                    t,
+                    dt,
+                    end: tnew,
+                    y,
+                    y1: self.y.clone(),
+                    ks,
+                    f1: f1.clone(),
+                    interpolant: None,
+                });
+                self.f0 = f1;
+                if self.output.is_some() {
+                    let interpolant = self.step_interpolant().cloned();
+                    let previous = self.previous.as_ref().unwrap();
+                    if let (Some(output), Some(mut interpolant)) =
+                        (self.output.as_mut(), interpolant)
+                    {
+                        if let Some(order) = &self.opts.interp_order {
+                            interpolant = interpolant.with_max_degree(
+                                order.0,
+                                dt,
+                                &previous.y,
+                                &previous.y1,
+                                &previous.ks[0],
+                                &previous.f1,
+                            );
+                        }
+                        output.push(t, dt, previous.y.clone(), interpolant);
+                    }
+                }
+                if let Some((indices, tevent)) = self.locate_event(tnew) {
+                    self.apply_event(indices, tevent, tnew, dtnew);
+                } else {
+                    self.t = tnew;
+                    if self.last_step && tstop.is_none() {
+                        self.finished = true;
+                    } else {
+                        self.propose_dt(dtnew);
+                    }
+                }
+                while self
+                    .tstops
+                    .last()
+                    .is_some_and(|&ts| self.tdir * ts <= self.tdir * self.t)
+                {
+                    self.tstops.pop();
+                }
+                self.apply_discrete_callbacks();
+                if stiff && self.stiff_at.is_none() {
+                    self.stiff_at = Some(tnew);
+                    if *self.opts.stiffness == StiffnessAction::Abort && !self.finished {
+                        self.retcode = ReturnCode::Stiff;
+                        self.finished = true;
+                    }
+                }
+                return Ok(true);
+            }
+            self.stages = coeffs;
+            let mut dtnew = self.controller.reject(dt, err);
+            if dtnew.abs() < self.minstep {
+                if !*self.opts.force_dtmin {
+                    // minimum step size reached
+                    self.retcode = ReturnCode::DtLessThanMin;
+                    self.finished = true;
+                    return Ok(false);
+                }
+                // take the minimum step regardless of its error
+                dtnew = self.tdir * self.minstep;
+                self.forced = true;
+            }
+            // redo step with smaller dt
+            self.stats.rejected_steps += 1;
+            self.last_step = false;
+            self.dt = dtnew;
+            self.timeout = self.estimate.rejection_timeout();
+        }
+    }
+
+    /// The scaled local error of the step of size `dt` from the current state to
+    /// `ytrial`, `yerr` is the error estimate of the embedded pair and `coeffs` are the
+    /// stages of the step.
+    ///
+    /// A step is acceptable if the error does not exceed 1, a step that failed to
+    /// produce a finite solution has an infinite error.
+    fn error(&self, dt: f64, ytrial: &Y, yerr: &Y, coeffs: &CoefficientMap<Y>) -> f64 {
+        let (abstol, reltol) = (&self.opts.abstol, &self.opts.reltol);
+        let err = match &self.estimate {
+            ErrorEstimate::Embedded => self
+                .problem
+                .local_error(&self.y, ytrial, yerr, abstol, reltol),
+            ErrorEstimate::Dop853 { e5, e3 } => {
+                let estimate = |e: &[f64]| {
+                    let mut err = self.y.clone();
+                    err.set_zero();
+                    for (e, k) in e.iter().zip(coeffs.ks()) {
+                        err.axpy(e * dt, k);
+                    }
+                    self.problem
+                        .local_error(&self.y, ytrial, &err, abstol, reltol)
+                };
+                let (err5, err3) = (estimate(e5), estimate(e3));
+                // the 5th order estimate, damped where the 3rd order one is much larger
+                let deno = err5.powi(2) + 0.01 * err3.powi(2);
+                if deno > 0. {
+                    err5.powi(2) / deno.sqrt()
+                } else {
+                    0.
+                }
+            }
+        };
+        if err.is_nan() {
+            f64::INFINITY
+        } else {
+            err
+        }
+    }
+
+    /// Continues with the step size `dt`, hitting the end point exactly if the next step
+    /// is within 1% of it.
+    fn propose_dt(&mut self, dt: f64) {
+        self.dt = dt;
+        self.last_step = false;
+        if self.tdir * (self.t + self.dt + self.dt / 100.) >= self.tdir * self.tend {
+            self.dt = self.tend - self.t;
+            // next step is the last, if it succeeds
+            self.last_step = true;
+        }
+    }
+
+    /// The values of the event functions at the current state.
+    fn event_values(&self) -> Vec<f64> {
+        self.problem
+            .callbacks()
+            .continuous_callbacks()
+            .iter()
+            .map(|callback| callback.condition(self.t, &self.y))
+            .collect()
+    }
+
+    /// Finds the first event inside the previous accepted step, which ends at `tnew`,
+    /// and the continuous callbacks that trigger at it.
+    ///
+    /// The event functions are sampled at [`EVENT_SAMPLES`] points of the continuous
+    /// output, so that an even number of crossings inside the step is detected as well,
+    /// and the first crossing is located with [`itp`]. Crossings of several callbacks
+    /// that are indistinguishable in time are merged into one event.
+    fn locate_event(&mut self, tnew: f64) -> Option<(Vec<usize>, f64)> {
+        let callbacks = self.problem.callbacks().continuous_callbacks();
+        if callbacks.is_empty() {
+            return None;
+        }
+        let gend: Vec<f64> = callbacks
+            .iter()
+            .map(|callback| callback.condition(tnew, &self.y))
+            .collect();
+        self.step_interpolant()?;
+        let previous = self.previous.as_ref()?;
+        let interpolant = previous.interpolant.as_ref()?;
+        let state = |tq: f64| interpolant.interpolate((tq - previous.t) / previous.dt, &previous.y);
+
+        let t = previous.t;
+        let tol = 4. * f64::EPSILON * t.abs().max(tnew.abs());
+        let mut roots = Vec::new();
+        for (i, callback) in callbacks.iter().enumerate() {
+            let mut g0 = self.gprev[i];
+            let mut ta = t;
+            for k in 1..=EVENT_SAMPLES {
+                let tb = t + (tnew - t) * k as f64 / EVENT_SAMPLES as f64;
+                let g1 = if k == EVENT_SAMPLES {
+                    gend[i]
+                } else {
+                    callback.condition(tb, &state(tb))
+                };
+                if callback.crossing_direction().is_crossing(g0, g1) {
+                    let g = |tq: f64| callback.condition(tq, &state(tq));
+                    let troot = itp(g, ta, tb, g0, g1, tol);
+                    // a crossing right after an event of the same callback is caused by the
+                    // event itself
+                    let spurious = self.last_event.as_ref().is_some_and(|(tlast, last)| {
+                        *tlast == t && last.contains(&i) && (troot - t).abs() <= 100. * tol
+                    });
+                    if !spurious {
+                        roots.push((i, troot));
+                        break;
+                    }
+                }
+                g0 = g1;
+                ta = tb;
+            }
+        }
+        let tdir = self.tdir;
+        let tfirst = roots
+            .iter()
+            .map(|&(_, troot)| troot)
+            .min_by(|a, b| (tdir * a).total_cmp(&(tdir * b)));
+        let tfirst = match tfirst {
+            Some(tfirst) => tfirst,
+            None => {
+                self.gprev = gend;
+                return None;
+            }
+        };
+        let simultaneous: Vec<_> = roots
+            .into_iter()
+            .filter(|&(_, troot)| (troot - tfirst).abs() <= 100. * tol)
+            .collect();
+        // the event lies past all merged crossings
+        let tevent = simultaneous
+            .iter()
+            .map(|&(_, troot)| troot)
+            .max_by(|a, b| (tdir * a).total_cmp(&(tdir * b)))
+            .unwrap_or(tfirst);
+        Some((simultaneous.into_iter().map(|(i, _)| i).collect(), tevent))
+    }
+
+    /// Ends the previous accepted step at the event of the continuous callbacks `indices`
+    /// at `tevent` and applies them in order, the integration continues with the step
+    /// size `dt`.
+    fn apply_event(&mut self, indices: Vec<usize>, tevent: f64, tnew: f64, dt: f64) {
+        let callbacks = self.problem.callbacks().continuous_callbacks();
+        let y = if tevent == tnew {
+            self.y.clone()
+        } else {
+            self.interpolate(tevent).unwrap_or_else(|| self.y.clone())
+        };
+        let mut ynew = y.clone();
+        let mut terminate = false;
+        for &i in &indices {
+            callbacks[i].apply(tevent, &mut ynew);
+            terminate |= callbacks[i].is_terminal();
+        }
+        if let Some(previous) = self.previous.as_mut() {
+            previous.end = tevent;
+        }
+        if let Some(output) = self.output.as_mut() {
+            output.truncate(tevent);
+        }
+
+        self.t = tevent;
+        self.f0 = self.problem.rhs(tevent, &ynew);
+        self.y = ynew;
+        self.gprev = self.event_values();
+        self.event = Some(Event {
+            t: tevent,
+            callbacks: indices
+                .iter()
+                .map(|&i| CallbackIndex::Continuous(i))
+                .collect(),
+            y,
+        });
+        self.last_event = Some((tevent, indices));
+        if terminate {
+            self.retcode = ReturnCode::Terminated;
+            self.finished = true;
+        } else if tevent == self.tend {
+            self.finished = true;
+        } else {
+            self.propose_dt(dt);
+        }
+    }
+
+    /// Applies the discrete callbacks whose condition holds at the end of the accepted
+    /// step, in the order they were added to the problem.
+    fn apply_discrete_callbacks(&mut self) {
+        let callbacks = self.problem.callbacks().discrete_callbacks();
+        if callbacks.is_empty() {
+            return;
+        }
+        let y = self.y.clone();
+        let mut triggered = Vec::new();
+        let mut terminate = false;
+        for (i, callback) in callbacks.iter().enumerate() {
+            if callback.condition(self.t, &self.y) {
+                callback.apply(self.t, &mut self.y);
+                triggered.push(CallbackIndex::Discrete(i));
+                terminate |= callback.is_terminal();
+            }
+        }
+        if triggered.is_empty() {
+            return;
+        }
+
+        self.f0 = self.problem.rhs(self.t, &self.y);
+        self.gprev = self.event_values();
+        match self.event.as_mut() {
+            // the state before the continuous event is kept
+            Some(event) => event.callbacks.extend(triggered),
+            None => {
+                self.event = Some(Event {
+                    t: self.t,
+                    callbacks: triggered,
+                    y,
+                })
+            }
+        }
+        if terminate && !self.finished {
+            self.retcode = ReturnCode::Terminated;
+            self.finished = true;
+        }
+    }
+
+    /// The start of the previous accepted step, if any.
+    #[inline]
+    pub fn tprev(&self) -> Option<f64> {
+        self.previous.as_ref().map(|previous| previous.t)
+    }
+
+    /// The continuous output of the previous accepted step, if any.
+    fn step_interpolant(&mut self) -> Option<&StepInterpolant<Y>> {
+        let problem = self.problem;
+        let dense = self.dense.as_ref();
+        let previous = self.previous.as_mut()?;
+        if previous.interpolant.is_none() {
+            let interpolant = match dense {
+                Some(Extension::Tableau(dense)) => {
+                    let mut ks = previous.ks.clone();
+                    problem.dense_stages(dense, previous.t, previous.dt, &previous.y, &mut ks);
+                    StepInterpolant::from_dense(dense, previous.dt, &ks)
+                }
+                Some(Extension::Dop853(dense)) => {
+                    let rcont = problem.dop853_dense(
+                        dense,
+                        previous.t,
+                        previous.dt,
+                        &previous.y,
+                        &previous.y1,
+                        &previous.ks,
+                        &previous.f1,
+                    );
+                    StepInterpolant::dop853(&rcont)
+                }
+                None => StepInterpolant::hermite(
+                    previous.dt,
+                    &previous.y,
+                    &previous.y1,
+                    &previous.ks[0],
+                    &previous.f1,
+                ),
+            };
+            previous.interpolant = Some(interpolant);
+        }
+        previous.interpolant.as_ref()
+    }
+
+    /// The state at `t` inside the previous accepted step, interpolated with the
+    /// continuous output of the method.
+    ///
+    /// `None` if there is no previous step or `t` lies outside of it. A step interrupted
+    /// by an event only covers the time up to the event.
+    pub fn interpolate(&mut self, t: f64) -> Option<Y> {
+        let previous = self.previous.as_ref()?;
+        let theta = (t - previous.t) / previous.dt;
+        let theta_end = (previous.end - previous.t) / previous.dt;
+        if !(-1e-12..=theta_end + 1e-12).contains(&theta) {
+            return None;
+        }
+        let y = previous.y.clone();
+        let interpolant = self.step_interpolant()?;
+        Some(interpolant.interpolate(theta.clamp(0., theta_end), &y))
+    }
+
+    /// Takes the continuous output stored so far, if requested with the
+    /// [`Dense`](crate::ode::options::Dense) option.
+    pub fn take_output(&mut self) -> Option<DenseOutput<Y>> {
+        let output = self.output.take();
+        if output.is_some() {
+            self.output = Some(DenseOutput::default());
+        }
+        output
+    }
+}
+
+impl<'a, F, Y, G, M, T> OdeIntegrator<'a, F, Y, U12, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    /// Creates a new integrator for `problem` with the Dormand–Prince 8(5,3) method.
+    ///
+    /// The local error is estimated by combining the embedded 5th and 3rd order
+    /// solutions, output inside a step is computed with the 7th order continuous
+    /// extension of the method.
+    pub fn dop853<O: Into<AdaptiveOptions>>(
+        problem: &'a OdeProblem<F, Y, G, M>,
+        opts: O,
+    ) -> Result<Self, OdeError> {
+        let coeffs = Dop853Coeffs::new();
+        let estimate = ErrorEstimate::Dop853 {
+            e5: coeffs.e5.iter().copied().collect(),
+            e3: coeffs.e3.iter().copied().collect(),
+        };
+        Self::with_estimate(
+            problem,
+            ButcherTableau::dop853(),
+            estimate,
+            Some(Extension::Dop853(Box::new(coeffs))),
+            opts.into(),
+        )
+    }
+}
+
+impl<'a, F, Y, S, G, M, T> Iterator for OdeIntegrator<'a, F, Y, S, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+    S: Dim,
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    type Item = (f64, Y);
+
+    /// The time and state after the next accepted step.
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.step() {
+            Ok(true) => Some((self.t, self.y.clone())),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Dop853Options, ExplicitRkOptions, Reltol, Tstops};
+
+    /// `y' = -y`
+    fn decay() -> OdeProblem<impl Fn(f64, &f64) -> f64, f64> {
+        OdeProblem::builder()
+            .fun(|_t, y: &f64| -y)
+            .init(1.)
+            .interval(0., 2.)
+            .build()
+            .unwrap()
+    }
+
+    fn opts() -> ExplicitRkOptions {
+        ExplicitRkOptions {
+            reltol: Reltol::from(1e-10),
+            abstol: Abstol::from(1e-10),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn steps() {
+        let problem = decay();
+        let mut integrator = problem.tsit5_integrator(opts()).unwrap();
+        let steps: Vec<_> = integrator.by_ref().collect();
+        assert_eq!(2., steps.last().unwrap().0);
+        for (t, y) in &steps {
+            assert!((y - (-t).exp()).abs() < 1e-9);
+        }
+        assert!(integrator.is_finished());
+        assert_eq!(ReturnCode::Success, integrator.retcode());
+        assert_eq!(steps.len() as u32, integrator.stats().accepted_steps);
+        assert!(!integrator.step().unwrap());
+
+        // inside the last step
+        let t = (integrator.tprev().unwrap() + 2.) / 2.;
+        assert!((integrator.interpolate(t).unwrap() - (-t).exp()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tstops() {
+        // a forcing with a kink at `t = 1`, which is integrated exactly on either side
+        let problem = OdeProblem::builder()
+            .fun(|t: f64, _y: &f64| (1. - t).max(0.))
+            .init(0.)
+            .interval(0., 2.)
+            .build()
+            .unwrap();
+        let opts = ExplicitRkOptions {
+            tstops: Tstops(vec![1., 0.5, 3.]),
+            ..opts()
+        };
+        let mut integrator = problem.tsit5_integrator(opts.clone()).unwrap();
+        let steps: Vec<_> = integrator.by_ref().collect();
+        assert!(steps.iter().any(|&(t, _)| t == 0.5));
+        let (_, y) = steps.iter().find(|&&(t, _)| t == 1.).unwrap();
+        assert!((y - 0.5).abs() < 1e-14);
+        assert_eq!(2., integrator.t());
+        assert!((integrator.y() - 0.5).abs() < 1e-14);
+
+        let solution = problem
+            .dop853(Dop853Options {
+                reltol: opts.reltol,
+                abstol: opts.abstol,
+                tstops: opts.tstops,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(solution.tout.contains(&0.5));
+        let (_, y) = solution.iter().find(|&(&t, _)| t == 1.).unwrap();
+        assert!((y - 0.5).abs() < 1e-14);
+        assert_eq!(Some(&2.), solution.tout.last());
+        assert!((solution.yout.last().unwrap() - 0.5).abs() < 1e-14);
+    }
+
+    #[test]
+    fn modify_state() {
+        let problem = decay();
+        let mut integrator = problem.tsit5_integrator(opts()).unwrap();
+        while integrator.t() < 1. {
+            integrator.step().unwrap();
+        }
+        // kick the state, which restarts the decay
+        let t1 = integrator.t();
+        integrator.set_state(integrator.y() + 1.);
+        let y1 = *integrator.y();
+        assert!(integrator.interpolate(t1).is_none());
+        while integrator.step().unwrap() {}
+        let y = *integrator.y();
+        assert!((y - y1 * (t1 - 2.).exp()).abs() < 1e-9);
+
+        // and integrate back in time
+        integrator.reinit(y, 2., 0.).unwrap();
+        while integrator.step().unwrap() {}
+        assert_eq!(0., integrator.t());
+        assert!((integrator.y() - (1. + (-t1).exp()) * t1.exp()).abs() < 1e-8);
+    }
+
+    #[test]
+    fn componentwise_tolerance() {
+        // a tiny component that is below a shared absolute tolerance
+        let problem = OdeProblem::builder()
+            .fun(|_t, y: &(f64, f64)| (-y.0, -2. * y.1))
+            .init((1., 1e-9))
+            .interval(0., 1.)
+            .build()
+            .unwrap();
+        let solve = |abstol: Abstol| {
+            let opts = ExplicitRkOptions {
+                reltol: Reltol::from(1e-6),
+                abstol,
+                ..Default::default()
+            };
+            let mut integrator = problem.tsit5_integrator(opts).unwrap();
+            while integrator.step().unwrap() {}
+            (integrator.y().1 * 2f64.exp() / 1e-9 - 1.).abs()
+        };
+        let shared = solve(Abstol::from(1e-6));
+        let componentwise = solve(Abstol::from(vec![1e-6, 1e-18]));
+        assert!(componentwise < 1e-5);
+        assert!(componentwise < shared / 10.);
+
+        let opts = ExplicitRkOptions {
+            abstol: Abstol::from(vec![1e-6]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            problem.tsit5_integrator(opts),
+            Err(OdeError::ToleranceLength {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,500 @@
+use crate::error::OdeError;
+use crate::ode::newton::JacobianReuse;
+use crate::ode::sparse::{Coloring, CscMatrix, SparsityPattern};
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::DMatrix;
+use std::fmt;
+use std::ops::{Add, Mul};
+use std::rc::Rc;
+
+/// The jacobian `∂F/∂y (t, y)` of the RHS of an
+/// [`OdeProblem`](crate::ode::problem::OdeProblem), supplied by the user.
+///
+/// The entry `(i, j)` is the derivative of the `i`-th component of the RHS with respect
+/// to the `j`-th component of `y`.
+#[derive(Clone)]
+pub struct Jacobian<Y: OdeType>(JacobianFunction<Y>);
+
+type JacobianFunction<Y> = Rc<dyn Fn(f64, &Y) -> DMatrix<<Y as OdeType>::Item>>;
+
+impl<Y: OdeType> Jacobian<Y> {
+    pub fn new<J: Fn(f64, &Y) -> DMatrix<Y::Item> + 'static>(jac: J) -> Self {
+        Jacobian(Rc::new(jac))
+    }
+
+    /// The jacobian at `(t, y)`.
+    #[inline]
+    pub fn eval(&self, t: f64, y: &Y) -> DMatrix<Y::Item> {
+        (self.0)(t, y)
+    }
+}
+
+impl<Y: OdeType> fmt::Debug for Jacobian<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Jacobian")
+    }
+}
+
+/// The product `∂F/∂y (t, y) v` of the jacobian of the RHS with a vector `v`, supplied
+/// by the user for the matrix-free linear solves of
+/// [`LinearSolverType::Gmres`](crate::ode::linsolve::LinearSolverType::Gmres).
+#[derive(Clone)]
+pub struct JacobianVectorProduct<Y: OdeType>(ProductFunction<Y>);
+
+type ProductFunction<Y> = Rc<dyn Fn(f64, &Y, &Y) -> Y>;
+
+impl<Y: OdeType> JacobianVectorProduct<Y> {
+    pub fn new<J: Fn(f64, &Y, &Y) -> Y + 'static>(jvp: J) -> Self {
+        JacobianVectorProduct(Rc::new(jvp))
+    }
+
+    /// The product of the jacobian at `(t, y)` with `v`.
+    #[inline]
+    pub fn eval(&self, t: f64, y: &Y, v: &Y) -> Y {
+        (self.0)(t, y, v)
+    }
+}
+
+impl<Y: OdeType> fmt::Debug for JacobianVectorProduct<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("JacobianVectorProduct")
+    }
+}
+
+/// Newton iterations converge with an outdated jacobian as well, which
+/// [`JacobianCache::reuse`] hands out for at most this many steps by default.
+pub const MAX_REUSES: usize = 20;
+
+/// The difference quotient of a finite difference jacobian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DifferenceScheme {
+    /// `(F(y + h e_j) - F(y)) / h`, one evaluation of the RHS per component.
+    #[default]
+    Forward,
+    /// `(F(y + h e_j) - F(y - h e_j)) / 2h`, two evaluations of the RHS per component
+    /// and second order accurate.
+    Central,
+}
+
+impl fmt::Display for DifferenceScheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DifferenceScheme::Forward => write!(f, "Forward"),
+            DifferenceScheme::Central => write!(f, "Central"),
+        }
+    }
+}
+
+/// The finite difference approximation of the jacobian, which the implicit and
+/// Rosenbrock methods use unless a jacobian is provided.
+///
+/// The `j`-th column perturbs `y_j` by `h_j = ε^p max(|y_j|, typ_j)`, where `p = 1/2`
+/// for forward and `p = 1/3` for central differences balances the truncation and the
+/// rounding error. The perturbation is rounded so that `y_j ± h_j` is exact.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FiniteDifferences {
+    pub scheme: DifferenceScheme,
+    /// The typical magnitude `typ_j` of each component, `1` for components without one.
+    pub typical: Vec<f64>,
+}
+
+impl FiniteDifferences {
+    pub fn forward() -> Self {
+        Self::default()
+    }
+
+    pub fn central() -> Self {
+        Self {
+            scheme: DifferenceScheme::Central,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the typical magnitudes of the components.
+    pub fn typical(mut self, typical: Vec<f64>) -> Self {
+        self.typical = typical;
+        self
+    }
+
+    /// The perturbation of the `j`-th component with the value `yj`.
+    pub fn step(&self, j: usize, yj: f64) -> f64 {
+        let eps = match self.scheme {
+            DifferenceScheme::Forward => f64::EPSILON.sqrt(),
+            DifferenceScheme::Central => f64::EPSILON.cbrt(),
+        };
+        let typical = if j < self.typical.len() {
+            self.typical[j]
+        } else {
+            1.
+        };
+        let h = eps * yj.abs().max(typical.abs());
+        (yj + h) - yj
+    }
+
+    /// The jacobian of `f` at `(t, y)`, `fy` is `f(t, y)` if already known.
+    pub fn jacobian<Y, T, F>(&self, f: F, t: f64, y: &Y, fy: Option<&Y>) -> DMatrix<T>
+    where
+        F: Fn(f64, &Y) -> Y,
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+        Y: OdeType<Item = T>,
+    {
+        let n = y.dof();
+        let f0 = match (self.scheme, fy) {
+            (DifferenceScheme::Forward, None) => Some(f(t, y)),
+            _ => None,
+        };
+        let mut jac = DMatrix::zeros(n, n);
+        for col in 0..n {
+            let h = self.step(col, y.get(col).into());
+            let mut forward = y.clone();
+            *forward.get_mut(col) += T::one() * h;
+            let fp = f(t, &forward);
+            let (fm, width) = match self.scheme {
+                DifferenceScheme::Forward => (None, h),
+                DifferenceScheme::Central => {
+                    let mut backward = y.clone();
+                    *backward.get_mut(col) -= T::one() * h;
+                    (Some(f(t, &backward)), 2. * h)
+                }
+            };
+            let fm = fm.as_ref().or(f0.as_ref()).or(fy).unwrap_or(&fp);
+            for row in 0..n {
+                jac[(row, col)] = (fp.get(row) - fm.get(row)) * (1. / width);
+            }
+        }
+        jac
+    }
+
+    /// The directional derivative `∂F/∂y (t, y) v` of `f` at `(t, y)`, `fy` is `f(t, y)`
+    /// if already known.
+    ///
+    /// `y` is perturbed by `σ v` with `σ = ε^p max_j max(|y_j|, typ_j) / max_j |v_j|`,
+    /// which takes one or two evaluations of `f` regardless of the dimension.
+    pub fn directional<Y, T, F>(&self, f: F, t: f64, y: &Y, v: &Y, fy: Option<&Y>) -> Y
+    where
+        F: Fn(f64, &Y) -> Y,
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+        Y: OdeType<Item = T>,
+    {
+        let n = y.dof();
+        let vnorm = v.ode_iter().fold(0f64, |acc, vi| acc.max(vi.abs().into()));
+        let mut jv = v.clone();
+        if vnorm == 0. {
+            return jv;
+        }
+        let ynorm = (0..n).fold(0f64, |acc, j| acc.max(self.step(j, y.get(j).into())));
+        let sigma = ynorm / vnorm;
+        let perturbed = |sign: f64| {
+            let mut yp = y.clone();
+            for j in 0..n {
+                *yp.get_mut(j) += v.get(j) * (sign * sigma);
+            }
+            f(t, &yp)
+        };
+        let fp = perturbed(1.);
+        let (fm, width) = match (self.scheme, fy) {
+            (DifferenceScheme::Forward, Some(fy)) => (fy.clone(), sigma),
+            (DifferenceScheme::Forward, None) => (f(t, y), sigma),
+            (DifferenceScheme::Central, _) => (perturbed(-1.), 2. * sigma),
+        };
+        for i in 0..n {
+            jv.insert(i, (fp.get(i) - fm.get(i)) * (1. / width));
+        }
+        jv
+    }
+
+    /// The jacobian of `f` at `(t, y)` with the sparsity `pattern`, `fy` is `f(t, y)` if
+    /// already known.
+    ///
+    /// All columns of a group of the `coloring` of the pattern are perturbed at once, so
+    /// a forward difference jacobian takes one evaluation of the RHS per color instead
+    /// of per component.
+    pub fn sparse_jacobian<Y, T, F>(
+        &self,
+        f: F,
+        t: f64,
+        y: &Y,
+        pattern: &Rc<SparsityPattern>,
+        coloring: &Coloring,
+        fy: Option<&Y>,
+    ) -> CscMatrix<T>
+    where
+        F: Fn(f64, &Y) -> Y,
+        T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+        Y: OdeType<Item = T>,
+    {
+        let f0 = match (self.scheme, fy) {
+            (DifferenceScheme::Forward, None) => Some(f(t, y)),
+            _ => None,
+        };
+        let mut jac = CscMatrix::zeros(Rc::clone(pattern));
+        for group in coloring.groups() {
+            let steps: Vec<f64> = group
+                .iter()
+                .map(|&col| self.step(col, y.get(col).into()))
+                .collect();
+            let perturbed = |sign: f64| {
+                let mut x = y.clone();
+                for (&col, h) in group.iter().zip(&steps) {
+                    *x.get_mut(col) += T::one() * (sign * h);
+                }
+                f(t, &x)
+            };
+            let fp = perturbed(1.);
+            let (fm, width) = match self.scheme {
+                DifferenceScheme::Forward => (None, 1.),
+                DifferenceScheme::Central => (Some(perturbed(-1.)), 2.),
+            };
+            let fm = fm.as_ref().or(f0.as_ref()).or(fy).unwrap_or(&fp);
+            for (&col, h) in group.iter().zip(&steps) {
+                let (rows, values) = jac.column_mut(col);
+                for (&row, value) in rows.iter().zip(values) {
+                    *value = (fp.get(row) - fm.get(row)) * (1. / (width * h));
+                }
+            }
+        }
+        jac
+    }
+}
+
+/// A jacobian stored densely, or sparsely if the problem declares its sparsity.
+#[derive(Debug, Clone)]
+pub enum JacobianMatrix<T: RealField> {
+    Dense(DMatrix<T>),
+    Sparse(CscMatrix<T>),
+}
+
+impl<T: RealField> JacobianMatrix<T> {
+    pub fn shape(&self) -> (usize, usize) {
+        match self {
+            JacobianMatrix::Dense(jac) => jac.shape(),
+            JacobianMatrix::Sparse(jac) => jac.shape(),
+        }
+    }
+
+    pub fn to_dense(&self) -> DMatrix<T> {
+        match self {
+            JacobianMatrix::Dense(jac) => jac.clone(),
+            JacobianMatrix::Sparse(jac) => jac.to_dense(),
+        }
+    }
+}
+
+/// Compares the jacobian `jac` of `f` at `(t, y)` with central differences.
+///
+/// An entry is consistent if it differs from its approximation by at most `tol` times
+/// the larger of their magnitudes, or `tol` for entries smaller than one. Returns
+/// [`OdeError::JacobianMismatch`] for the first inconsistent entry.
+pub fn check<Y, T, F>(jac: &DMatrix<T>, f: F, t: f64, y: &Y, tol: f64) -> Result<(), OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    let n = y.dof();
+    if jac.shape() != (n, n) {
+        return Err(OdeError::DimensionMismatch {
+            expected: (n, n),
+            found: jac.shape(),
+        });
+    }
+    let approx = FiniteDifferences::central().jacobian(f, t, y, None);
+    for col in 0..n {
+        for row in 0..n {
+            let (analytic, approx): (f64, f64) =
+                (jac[(row, col)].into(), approx[(row, col)].into());
+            if (analytic - approx).abs() > tol * analytic.abs().max(approx.abs()).max(1.) {
+                return Err(OdeError::JacobianMismatch {
+                    at: t,
+                    entry: (row, col),
+                    analytic,
+                    approx,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The last jacobian of a solve and the point it was evaluated at.
+///
+/// A solver that returns to the same point, e.g. to retry a rejected step, finds the
+/// jacobian with [`JacobianCache::at`]. Solvers that only need an approximate jacobian,
+/// like the newton iterations of the implicit methods, reuse an outdated one with
+/// [`JacobianCache::reuse`], c.f. Hairer & Wanner, "Solving Ordinary Differential
+/// Equations II", 1996, Sec. IV.8.
+#[derive(Debug, Clone)]
+pub struct JacobianCache<Y: OdeType, M = DMatrix<<Y as OdeType>::Item>> {
+    /// the point of the jacobian, `None` before the first evaluation
+    point: Option<(f64, Y)>,
+    jac: Option<M>,
+    /// the number of steps the jacobian was reused for
+    reuses: usize,
+    max_reuses: usize,
+}
+
+impl<Y: OdeType, M> Default for JacobianCache<Y, M> {
+    fn default() -> Self {
+        Self::new(JacobianReuse::default())
+    }
+}
+
+impl<Y: OdeType, M> JacobianCache<Y, M> {
+    /// An empty cache whose jacobians are reused according to `reuse`.
+    pub fn new(reuse: JacobianReuse) -> Self {
+        Self {
+            point: None,
+            jac: None,
+            reuses: 0,
+            max_reuses: reuse.max_reuses(),
+        }
+    }
+
+    /// Whether the jacobian was evaluated at `(t, y)`.
+    pub fn is_current(&self, t: f64, y: &Y) -> bool {
+        self.point.as_ref().is_some_and(|(tc, yc)| {
+            *tc == t && yc.dof() == y.dof() && (0..y.dof()).all(|d| yc.get(d) == y.get(d))
+        })
+    }
+
+    /// The last evaluated jacobian.
+    ///
+    /// # Panics
+    ///
+    /// If the jacobian was never evaluated.
+    #[inline]
+    pub fn get(&self) -> &M {
+        self.jac.as_ref().expect("the jacobian was never evaluated")
+    }
+
+    /// Evaluates the jacobian at `(t, y)` with `jac`.
+    pub fn update<J>(&mut self, t: f64, y: &Y, jac: J) -> Result<&M, OdeError>
+    where
+        J: FnOnce(f64, &Y) -> Result<M, OdeError>,
+    {
+        let jac = jac(t, y)?;
+        self.point = Some((t, y.clone()));
+        self.reuses = 0;
+        Ok(self.jac.insert(jac))
+    }
+
+    /// The jacobian at `(t, y)`, only evaluated with `jac` if it is not cached.
+    pub fn at<J>(&mut self, t: f64, y: &Y, jac: J) -> Result<&M, OdeError>
+    where
+        J: FnOnce(f64, &Y) -> Result<M, OdeError>,
+    {
+        if !self.is_current(t, y) {
+            self.update(t, y, jac)?;
+        }
+        Ok(self.get())
+    }
+
+    /// Marks the jacobian as outdated, the next [`JacobianCache::reuse`] evaluates a new
+    /// one.
+    pub fn expire(&mut self) {
+        self.reuses = usize::MAX;
+    }
+
+    /// The last jacobian if it was reused for less steps than the [`JacobianReuse`] of
+    /// the cache allows, otherwise the one evaluated at `(t, y)` with `jac`.
+    ///
+    /// Also returns whether the jacobian is current, i.e. evaluated at `(t, y)`, a
+    /// solver retries a failed newton iteration with [`JacobianCache::update`] if not.
+    pub fn reuse<J>(&mut self, t: f64, y: &Y, jac: J) -> Result<(&M, bool), OdeError>
+    where
+        J: FnOnce(f64, &Y) -> Result<M, OdeError>,
+    {
+        if self.is_current(t, y) {
+            // e.g. a rejected step is retried
+        } else if self.point.is_none() || self.reuses >= self.max_reuses {
+            self.update(t, y, jac)?;
+        } else {
+            self.reuses += 1;
+        }
+        let current = self.is_current(t, y);
+        Ok((self.get(), current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consistency() {
+        // `f(y) = (y0 y1, sin y0)`
+        let f = |_t: f64, y: &Vec<f64>| vec![y[0] * y[1], y[0].sin()];
+        let y: Vec<f64> = vec![0.5, 2.];
+        let jac = DMatrix::from_row_slice(2, 2, &[y[1], y[0], y[0].cos(), 0.]);
+        assert!(check(&jac, f, 0., &y, 1e-6).is_ok());
+
+        let wrong = DMatrix::from_row_slice(2, 2, &[y[1], y[0], -y[0].cos(), 0.]);
+        match check(&wrong, f, 0., &y, 1e-6) {
+            Err(OdeError::JacobianMismatch { entry, .. }) => assert_eq!((1, 0), entry),
+            other => panic!("expected a mismatch, found {:?}", other),
+        }
+        assert!(check(&DMatrix::zeros(1, 1), f, 0., &y, 1e-6).is_err());
+    }
+
+    #[test]
+    fn finite_differences() {
+        let f = |_t: f64, y: &Vec<f64>| vec![y[0] * y[1], y[0].sin()];
+        let y: Vec<f64> = vec![0.5, 2e3];
+        let exact = [y[1], y[0], y[0].cos(), 0.];
+        for (fd, tol) in [
+            (FiniteDifferences::forward(), 1e-7),
+            (FiniteDifferences::central(), 1e-10),
+        ] {
+            let jac = fd.jacobian(f, 0., &y, None);
+            for (i, e) in exact.iter().enumerate() {
+                assert!((jac[(i / 2, i % 2)] - e).abs() <= tol * e.abs().max(1.));
+            }
+        }
+        // the step scales with the magnitude of the component or its typical one
+        let fd = FiniteDifferences::forward().typical(vec![1e-3, 1e-3]);
+        assert!(fd.step(0, 1e-3) < 1e-10);
+        assert!((fd.step(1, 2e3) - 2e3 * f64::EPSILON.sqrt()).abs() < 1e-9);
+
+        // directional derivatives take one or two evaluations regardless of the dimension
+        let v: Vec<f64> = vec![1e-2, -3.];
+        for (fd, tol) in [
+            (FiniteDifferences::forward(), 1e-6),
+            (FiniteDifferences::central(), 1e-9),
+        ] {
+            let jv = fd.directional(f, 0., &y, &v, None);
+            let exact = [y[1] * v[0] + y[0] * v[1], y[0].cos() * v[0]];
+            for (jvi, e) in jv.iter().zip(&exact) {
+                assert!((jvi - e).abs() <= tol * e.abs().max(1.), "{} {}", jvi, e);
+            }
+        }
+    }
+
+    #[test]
+    fn cache() {
+        let evals = std::cell::Cell::new(0);
+        let jac = |t: f64, y: &f64| {
+            evals.set(evals.get() + 1);
+            Ok(DMatrix::from_element(1, 1, t * y))
+        };
+        let mut cache = JacobianCache::default();
+        assert_eq!(2., cache.at(1., &2., jac).unwrap()[0]);
+        assert_eq!(2., cache.at(1., &2., jac).unwrap()[0]);
+        assert_eq!(1, evals.get());
+        // an outdated jacobian is reused, but not forever
+        for step in 0..MAX_REUSES {
+            let (reused, current) = cache.reuse(1. + step as f64, &3., jac).unwrap();
+            assert_eq!((2., false), (reused[0], current));
+        }
+        let (fresh, current) = cache.reuse(5., &3., jac).unwrap();
+        assert_eq!((15., true), (fresh[0], current));
+        assert_eq!(2, evals.get());
+
+        let mut never = JacobianCache::new(JacobianReuse::Never);
+        never.reuse(1., &2., jac).unwrap();
+        never.reuse(1., &2., jac).unwrap();
+        let (fresh, current) = never.reuse(2., &2., jac).unwrap();
+        assert_eq!((4., true), (fresh[0], current));
+        assert_eq!(4, evals.get());
+    }
+}
@@ -0,0 +1,342 @@
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+
+/// A truncated Taylor series `Σ_k c_k (t - t_0)^k` of a function of time, c.f. Jorba &
+/// Zou, "A software package for the numerical integration of ODEs by means of high-order
+/// Taylor methods", 2005.
+///
+/// Arithmetic operations and elementary functions propagate all known coefficients by
+/// the automatic differentiation recurrences, so any RHS written in terms of [`Jet`]s
+/// yields the Taylor coefficients of its value. Operations on jets of different length
+/// are truncated to the shorter jet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jet {
+    coeffs: Vec<f64>,
+}
+
+impl Jet {
+    /// A jet of the given coefficients.
+    pub fn new(coeffs: Vec<f64>) -> Self {
+        assert!(!coeffs.is_empty(), "a jet needs at least one coefficient");
+        Self { coeffs }
+    }
+
+    /// The constant `value` with `len` coefficients.
+    pub fn constant(value: f64, len: usize) -> Self {
+        let mut coeffs = vec![0.; len.max(1)];
+        coeffs[0] = value;
+        Self { coeffs }
+    }
+
+    /// The independent variable `t` at `t0` with `len` coefficients.
+    pub fn variable(t0: f64, len: usize) -> Self {
+        let mut jet = Self::constant(t0, len);
+        if len > 1 {
+            jet.coeffs[1] = 1.;
+        }
+        jet
+    }
+
+    /// The value `c_0` at the expansion point.
+    #[inline]
+    pub fn value(&self) -> f64 {
+        self.coeffs[0]
+    }
+
+    /// The number of coefficients.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.coeffs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    #[inline]
+    pub fn coeffs(&self) -> &[f64] {
+        &self.coeffs
+    }
+
+    /// Evaluates the series at `t0 + dt`.
+    pub fn eval(&self, dt: f64) -> f64 {
+        self.coeffs.iter().rev().fold(0., |acc, c| acc * dt + c)
+    }
+
+    /// `e^a` by `e_k = 1/k Σ_{j=1}^k j a_j e_{k-j}`
+    pub fn exp(&self) -> Self {
+        let a = &self.coeffs;
+        let mut e = vec![a[0].exp()];
+        for k in 1..a.len() {
+            let sum: f64 = (1..=k).map(|j| j as f64 * a[j] * e[k - j]).sum();
+            e.push(sum / k as f64);
+        }
+        Self { coeffs: e }
+    }
+
+    /// `ln a` by `l_k = (a_k - 1/k Σ_{j=1}^{k-1} j l_j a_{k-j}) / a_0`
+    pub fn ln(&self) -> Self {
+        let a = &self.coeffs;
+        let mut l = vec![a[0].ln()];
+        for k in 1..a.len() {
+            let sum: f64 = (1..k).map(|j| j as f64 * l[j] * a[k - j]).sum();
+            l.push((a[k] - sum / k as f64) / a[0]);
+        }
+        Self { coeffs: l }
+    }
+
+    /// `a^α` by `p_k = 1/(k a_0) Σ_{j=0}^{k-1} (α (k - j) - j) a_{k-j} p_j`, requires
+    /// `a_0 != 0`.
+    pub fn powf(&self, alpha: f64) -> Self {
+        let a = &self.coeffs;
+        let mut p = vec![a[0].powf(alpha)];
+        for k in 1..a.len() {
+            let sum: f64 = (0..k)
+                .map(|j| (alpha * (k - j) as f64 - j as f64) * a[k - j] * p[j])
+                .sum();
+            p.push(sum / (k as f64 * a[0]));
+        }
+        Self { coeffs: p }
+    }
+
+    /// `a^n` by repeated multiplication.
+    pub fn powi(&self, n: i32) -> Self {
+        let mut result = Self::constant(1., self.len());
+        let mut base = self.clone();
+        let mut m = n.unsigned_abs();
+        while m > 0 {
+            if m & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            m >>= 1;
+        }
+        if n < 0 {
+            1. / result
+        } else {
+            result
+        }
+    }
+
+    #[inline]
+    pub fn sqrt(&self) -> Self {
+        self.powf(0.5)
+    }
+
+    /// `(sin a, cos a)` by `s_k = 1/k Σ_{j=1}^k j a_j c_{k-j}` and
+    /// `c_k = -1/k Σ_{j=1}^k j a_j s_{k-j}`
+    pub fn sin_cos(&self) -> (Self, Self) {
+        let a = &self.coeffs;
+        let mut s = vec![a[0].sin()];
+        let mut c = vec![a[0].cos()];
+        for k in 1..a.len() {
+            let (mut sk, mut ck) = (0., 0.);
+            for j in 1..=k {
+                sk += j as f64 * a[j] * c[k - j];
+                ck -= j as f64 * a[j] * s[k - j];
+            }
+            s.push(sk / k as f64);
+            c.push(ck / k as f64);
+        }
+        (Self { coeffs: s }, Self { coeffs: c })
+    }
+
+    #[inline]
+    pub fn sin(&self) -> Self {
+        self.sin_cos().0
+    }
+
+    #[inline]
+    pub fn cos(&self) -> Self {
+        self.sin_cos().1
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self {
+            coeffs: self
+                .coeffs
+                .iter()
+                .zip(&other.coeffs)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        }
+    }
+
+    fn mul_jet(&self, other: &Self) -> Self {
+        let (a, b) = (&self.coeffs, &other.coeffs);
+        let len = a.len().min(b.len());
+        Self {
+            coeffs: (0..len)
+                .map(|k| (0..=k).map(|j| a[j] * b[k - j]).sum())
+                .collect(),
+        }
+    }
+
+    /// `a / b` by `c_k = (a_k - Σ_{j=0}^{k-1} c_j b_{k-j}) / b_0`
+    fn div_jet(&self, other: &Self) -> Self {
+        let (a, b) = (&self.coeffs, &other.coeffs);
+        let len = a.len().min(b.len());
+        let mut c: Vec<f64> = Vec::with_capacity(len);
+        for k in 0..len {
+            let sum: f64 = (0..k).map(|j| c[j] * b[k - j]).sum();
+            c.push((a[k] - sum) / b[0]);
+        }
+        Self { coeffs: c }
+    }
+}
+
+impl Index<usize> for Jet {
+    type Output = f64;
+
+    #[inline]
+    fn index(&self, k: usize) -> &f64 {
+        &self.coeffs[k]
+    }
+}
+
+impl Neg for Jet {
+    type Output = Jet;
+
+    fn neg(self) -> Jet {
+        -&self
+    }
+}
+
+impl Neg for &Jet {
+    type Output = Jet;
+
+    fn neg(self) -> Jet {
+        Jet {
+            coeffs: self.coeffs.iter().map(|c| -c).collect(),
+        }
+    }
+}
+
+/// implements a binary operation for all combinations of owned and borrowed jets and
+/// scalars in terms of the operation on borrowed jets
+macro_rules! jet_op {
+    ($op:ident, $fun:ident, |$a:ident, $b:ident| $jet:expr, |$c:ident, $s:ident| $scalar:expr, |$s2:ident, $c2:ident| $rscalar:expr) => {
+        impl<'a, 'b> $op<&'b Jet> for &'a Jet {
+            type Output = Jet;
+
+            fn $fun(self, other: &'b Jet) -> Jet {
+                let ($a, $b) = (self, other);
+                $jet
+            }
+        }
+
+        impl $op<Jet> for Jet {
+            type Output = Jet;
+
+            #[inline]
+            fn $fun(self, other: Jet) -> Jet {
+                $op::$fun(&self, &other)
+            }
+        }
+
+        impl<'a> $op<&'a Jet> for Jet {
+            type Output = Jet;
+
+            #[inline]
+            fn $fun(self, other: &'a Jet) -> Jet {
+                $op::$fun(&self, other)
+            }
+        }
+
+        impl<'a> $op<Jet> for &'a Jet {
+            type Output = Jet;
+
+            #[inline]
+            fn $fun(self, other: Jet) -> Jet {
+                $op::$fun(self, &other)
+            }
+        }
+
+        impl<'a> $op<f64> for &'a Jet {
+            type Output = Jet;
+
+            fn $fun(self, $s: f64) -> Jet {
+                let mut $c = self.clone();
+                $scalar;
+                $c
+            }
+        }
+
+        impl $op<f64> for Jet {
+            type Output = Jet;
+
+            #[inline]
+            fn $fun(self, other: f64) -> Jet {
+                $op::$fun(&self, other)
+            }
+        }
+
+        impl<'a> $op<&'a Jet> for f64 {
+            type Output = Jet;
+
+            fn $fun(self, other: &'a Jet) -> Jet {
+                let ($s2, $c2) = (self, other);
+                $rscalar
+            }
+        }
+
+        impl $op<Jet> for f64 {
+            type Output = Jet;
+
+            #[inline]
+            fn $fun(self, other: Jet) -> Jet {
+                $op::$fun(self, &other)
+            }
+        }
+    };
+}
+
+jet_op!(
+    Add,
+    add,
+    |a, b| a.zip_with(b, |x, y| x + y),
+    |c, s| c.coeffs[0] += s,
+    |s, c| c + s
+);
+jet_op!(
+    Sub,
+    sub,
+    |a, b| a.zip_with(b, |x, y| x - y),
+    |c, s| c.coeffs[0] -= s,
+    |s, c| -c + s
+);
+jet_op!(
+    Mul,
+    mul,
+    |a, b| a.mul_jet(b),
+    |c, s| c.coeffs.iter_mut().for_each(|x| *x *= s),
+    |s, c| c * s
+);
+jet_op!(
+    Div,
+    div,
+    |a, b| a.div_jet(b),
+    |c, s| c.coeffs.iter_mut().for_each(|x| *x /= s),
+    |s, c| Jet::constant(s, c.len()).div_jet(c)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elementary_functions() {
+        // the series of `t` at `t0 = 0.5`
+        let t = Jet::variable(0.5, 16);
+        let dt = 0.05;
+        let close = |jet: Jet, expected: f64| (jet.eval(dt) - expected).abs() < 1e-9;
+
+        assert!(close(t.exp(), 0.55f64.exp()));
+        assert!(close(t.ln(), 0.55f64.ln()));
+        assert!(close(t.sqrt(), 0.55f64.sqrt()));
+        assert!(close(t.powf(1.5), 0.55f64.powf(1.5)));
+        assert!(close(t.powi(-3), 0.55f64.powi(-3)));
+        assert!(close(t.sin() * t.cos(), 0.55f64.sin() * 0.55f64.cos()));
+        assert!(close((&t * 2. + 1.) / (1. - &t), 2.1 / 0.45));
+    }
+}
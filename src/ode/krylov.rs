@@ -0,0 +1,511 @@
+use crate::error::OdeError;
+use crate::ode::implicit::IterationMatrix;
+use crate::ode::linsolve::{LinearSolver, LinearSolverType};
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::{DMatrix, DVector};
+use std::fmt;
+use std::rc::Rc;
+
+/// Settings of the restarted GMRES iteration, c.f. [`gmres`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GmresOptions {
+    /// The dimension of the Krylov subspace after which the iteration is restarted.
+    pub restart: usize,
+    /// Maximum number of iterations, i.e. operator applications, per linear system.
+    pub maxiter: usize,
+    /// The iteration converged once the residual is reduced to this fraction of the
+    /// right hand side. A newton iteration only requires rough solutions, c.f. Brown &
+    /// Hindmarsh, "Matrix-free methods for stiff systems of ODE's", 1986.
+    pub tol: f64,
+}
+
+impl Default for GmresOptions {
+    fn default() -> Self {
+        Self {
+            restart: 30,
+            maxiter: 300,
+            tol: 1e-4,
+        }
+    }
+}
+
+impl fmt::Display for GmresOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "restart={}, maxiter={}, tol={}",
+            self.restart, self.maxiter, self.tol
+        )
+    }
+}
+
+/// The side of the iteration matrix `A` a preconditioner `P` is applied on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreconditionerSide {
+    /// Solve `P^-1 A x = P^-1 b`, which changes the residual the iteration converges in.
+    Left,
+    /// Solve `A P^-1 u = b` for `x = P^-1 u`, which keeps the residual of `A x = b`.
+    #[default]
+    Right,
+}
+
+/// A preconditioner `P ≈ M - γ J` of the matrix-free solves of
+/// [`LinearSolverType::Gmres`], with the mass matrix `M` and the jacobian `J` of the RHS.
+///
+/// Set with [`OdeBuilder::preconditioner`](crate::ode::problem::OdeBuilder::preconditioner),
+/// it is set up whenever the implicit methods start a newton iteration, once for each
+/// distinct `γ` of the stages.
+#[derive(Clone)]
+pub struct Preconditioner<Y: OdeType> {
+    side: PreconditionerSide,
+    kind: PreconditionerKind<Y>,
+}
+
+#[derive(Clone)]
+pub(crate) enum PreconditionerKind<Y: OdeType> {
+    Banded { lower: usize, upper: usize },
+    Ilu0,
+    Custom(PreconditionerFunction<Y>),
+}
+
+type PreconditionerFunction<Y> = Rc<dyn Fn(f64, &Y, f64) -> Rc<dyn Fn(&Y) -> Y>>;
+
+impl<Y: OdeType> Preconditioner<Y> {
+    /// A user supplied preconditioner, `setup(t, y, γ)` prepares the solves of
+    /// `P(t, y, γ) z = r`, e.g. by factorizing an approximation of `M - γ J(t, y)`, and
+    /// `apply(&state, r)` returns `z`.
+    pub fn new<S, Setup, Apply>(setup: Setup, apply: Apply) -> Self
+    where
+        S: 'static,
+        Setup: Fn(f64, &Y, f64) -> S + 'static,
+        Apply: Fn(&S, &Y) -> Y + 'static,
+    {
+        let apply = Rc::new(apply);
+        let setup = move |t, y: &Y, gamma| {
+            let state = setup(t, y, gamma);
+            let apply = Rc::clone(&apply);
+            Rc::new(move |r: &Y| apply(&state, r)) as Rc<dyn Fn(&Y) -> Y>
+        };
+        Self {
+            side: PreconditionerSide::default(),
+            kind: PreconditionerKind::Custom(Rc::new(setup)),
+        }
+    }
+
+    /// The LU factorization of the band of `M - γ J` with `lower` subdiagonals and `upper`
+    /// superdiagonals, c.f. [`BandedLu`](crate::ode::linsolve::BandedLu). Unless set with
+    /// [`OdeBuilder::jac`](crate::ode::problem::OdeBuilder::jac), the band of the jacobian
+    /// is approximated by `lower + upper + 1` evaluations of the RHS.
+    pub fn banded(lower: usize, upper: usize) -> Self {
+        Self {
+            side: PreconditionerSide::default(),
+            kind: PreconditionerKind::Banded { lower, upper },
+        }
+    }
+
+    /// The incomplete LU factorization [`CscMatrix::ilu0`](crate::ode::sparse::CscMatrix::ilu0)
+    /// of `M - γ J` on the pattern declared with
+    /// [`OdeBuilder::sparsity`](crate::ode::problem::OdeBuilder::sparsity), which it
+    /// requires.
+    pub fn ilu0() -> Self {
+        Self {
+            side: PreconditionerSide::default(),
+            kind: PreconditionerKind::Ilu0,
+        }
+    }
+
+    /// Applies the preconditioner on `side`, [`PreconditionerSide::Right`] by default.
+    pub fn with_side(mut self, side: PreconditionerSide) -> Self {
+        self.side = side;
+        self
+    }
+
+    #[inline]
+    pub fn side(&self) -> PreconditionerSide {
+        self.side
+    }
+
+    pub(crate) fn kind(&self) -> &PreconditionerKind<Y> {
+        &self.kind
+    }
+}
+
+impl<Y: OdeType> fmt::Debug for Preconditioner<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            PreconditionerKind::Banded { lower, upper } => format!("Banded({}, {})", lower, upper),
+            PreconditionerKind::Ilu0 => "Ilu0".to_string(),
+            PreconditionerKind::Custom(_) => "Custom".to_string(),
+        };
+        f.debug_struct("Preconditioner")
+            .field("side", &self.side)
+            .field("kind", &kind)
+            .finish()
+    }
+}
+
+/// The solve `z = P^-1 r` of a set up preconditioner.
+pub type PreconditionerSolve<'a, T> = Rc<dyn Fn(&DVector<T>) -> DVector<T> + 'a>;
+
+/// A [`Preconditioner`] on the components at the current point of the integration,
+/// `setup(mass, γ)` sets it up for `M - γ J`.
+pub struct PreconditionerSetup<'a, T: RealField> {
+    pub side: PreconditionerSide,
+    pub setup: SetupFunction<'a, T>,
+}
+
+/// Sets up a [`PreconditionerSolve`] for the mass matrix and `γ`.
+pub type SetupFunction<'a, T> =
+    Box<dyn Fn(Option<&DMatrix<T>>, f64) -> Result<PreconditionerSolve<'a, T>, OdeError> + 'a>;
+
+impl<'a, T: RealField> fmt::Debug for PreconditionerSetup<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PreconditionerSetup")
+            .field("side", &self.side)
+            .finish()
+    }
+}
+
+/// The approximate solution of a linear system by [`gmres`].
+#[derive(Debug, Clone)]
+pub struct KrylovSolution<T: RealField> {
+    pub x: DVector<T>,
+    /// the number of operator applications
+    pub iterations: usize,
+    /// whether the residual was reduced to the tolerance
+    pub converged: bool,
+}
+
+/// Solves `A x = b` with the restarted generalized minimal residual method of Saad &
+/// Schultz, 1986, which only requires the products `op(v) = A v`.
+///
+/// Starting from `x = 0`, each iteration extends an orthonormal basis of the Krylov
+/// subspace `span{b, A b, A^2 b, ...}` by modified Gram-Schmidt and minimizes the residual
+/// over it, the least squares problem is kept triangular with Givens rotations. After
+/// `restart` iterations the basis is discarded and the iteration restarts from the
+/// current solution.
+pub fn gmres<T, A>(op: A, b: &DVector<T>, opts: &GmresOptions) -> KrylovSolution<T>
+where
+    T: RealField,
+    A: Fn(&DVector<T>) -> DVector<T>,
+{
+    let n = b.len();
+    let m = opts.restart.max(1).min(n.max(1));
+    let target = b.norm() * na::convert::<f64, T>(opts.tol);
+    let mut x = DVector::<T>::zeros(n);
+    let mut iterations = 0;
+
+    loop {
+        let r = if iterations == 0 {
+            b.clone()
+        } else {
+            b - op(&x)
+        };
+        let beta = r.norm();
+        if beta <= target || !beta.is_finite() {
+            return KrylovSolution {
+                x,
+                iterations,
+                converged: beta <= target,
+            };
+        }
+        if iterations >= opts.maxiter {
+            return KrylovSolution {
+                x,
+                iterations,
+                converged: false,
+            };
+        }
+
+        let mut basis = vec![r / beta];
+        // the hessenberg matrix, triangular after the rotations
+        let mut h = DMatrix::<T>::zeros(m + 1, m);
+        let mut rotations: Vec<(T, T)> = Vec::with_capacity(m);
+        let mut g = DVector::<T>::zeros(m + 1);
+        g[0] = beta;
+
+        let mut k = 0;
+        while k < m && iterations < opts.maxiter {
+            let mut w = op(&basis[k]);
+            iterations += 1;
+            for (i, v) in basis.iter().enumerate() {
+                let hik = w.dot(v);
+                h[(i, k)] = hik;
+                w.axpy(-hik, v, T::one());
+            }
+            let norm = w.norm();
+            h[(k + 1, k)] = norm;
+
+            for (i, &(c, s)) in rotations.iter().enumerate() {
+                let (a, b) = (h[(i, k)], h[(i + 1, k)]);
+                h[(i, k)] = c * a + s * b;
+                h[(i + 1, k)] = c * b - s * a;
+            }
+            let (a, b) = (h[(k, k)], h[(k + 1, k)]);
+            let r = (a * a + b * b).sqrt();
+            let (c, s) = if r.is_zero() {
+                (T::one(), T::zero())
+            } else {
+                (a / r, b / r)
+            };
+            rotations.push((c, s));
+            h[(k, k)] = r;
+            h[(k + 1, k)] = T::zero();
+            g[k + 1] = -s * g[k];
+            g[k] *= c;
+            k += 1;
+
+            if norm.is_zero() || g[k].abs() <= target {
+                break;
+            }
+            basis.push(w / norm);
+        }
+
+        // back substitution of the triangular least squares problem
+        let mut y = DVector::<T>::zeros(k);
+        for i in (0..k).rev() {
+            let mut yi = g[i];
+            for j in i + 1..k {
+                yi -= h[(i, j)] * y[j];
+            }
+            if h[(i, i)].is_zero() {
+                return KrylovSolution {
+                    x,
+                    iterations,
+                    converged: false,
+                };
+            }
+            y[i] = yi / h[(i, i)];
+        }
+        for (i, v) in basis.iter().take(k).enumerate() {
+            x.axpy(y[i], v, T::one());
+        }
+    }
+}
+
+/// The jacobian `J` of the RHS as the operator `v ↦ J v`, from which the iteration
+/// matrices of the implicit methods are applied without forming them. The linear
+/// systems are solved with [`gmres`].
+pub struct JacobianOperator<'a, 'p, T: RealField> {
+    jvp: &'a dyn Fn(&DVector<T>) -> DVector<T>,
+    opts: GmresOptions,
+    preconditioner: Option<&'p PreconditionerSetup<'a, T>>,
+}
+
+impl<'a, 'p, T: RealField> JacobianOperator<'a, 'p, T> {
+    pub fn new(jvp: &'a dyn Fn(&DVector<T>) -> DVector<T>, opts: GmresOptions) -> Self {
+        Self {
+            jvp,
+            opts,
+            preconditioner: None,
+        }
+    }
+
+    /// Preconditions the solves of the iteration matrices block diagonally, which sets up
+    /// `preconditioner` for the diagonal blocks `M - dt a_ii J` of a lower triangular rk
+    /// matrix `A`. Since the diagonal of a full `A`, e.g. of Radau IIA, is no
+    /// approximation of it, all blocks are `M - dt γ J` with `γ = |det A|^(1/s)` instead,
+    /// which keeps the spectrum of the preconditioned matrix in the right half plane.
+    pub fn preconditioner(
+        mut self,
+        preconditioner: Option<&'p PreconditionerSetup<'a, T>>,
+    ) -> Self {
+        self.preconditioner = preconditioner;
+        self
+    }
+}
+
+impl<'a, 'p, T: RealField> fmt::Debug for JacobianOperator<'a, 'p, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JacobianOperator")
+            .field("opts", &self.opts)
+            .field("preconditioner", &self.preconditioner)
+            .finish()
+    }
+}
+
+impl<'a, 'p, T: RealField> IterationMatrix<T> for JacobianOperator<'a, 'p, T> {
+    type Solver = MatrixFree<'a, T>;
+
+    /// Ignores `solver`, the systems are always solved with [`gmres`].
+    fn factor_stages(
+        &self,
+        mass: Option<&DMatrix<T>>,
+        a: &DMatrix<f64>,
+        dt: f64,
+        _solver: LinearSolverType,
+    ) -> Result<MatrixFree<'a, T>, OdeError> {
+        let preconditioner = match self.preconditioner {
+            Some(preconditioner) => {
+                let s = a.nrows();
+                let lower_triangular = (0..s).all(|i| (i + 1..s).all(|j| a[(i, j)] == 0.));
+                // the geometric mean of the eigenvalues of a full `A`
+                let mean = a.determinant().abs().powf(1. / s as f64);
+                let mut blocks: Vec<(f64, PreconditionerSolve<'a, T>)> = Vec::new();
+                for i in 0..s {
+                    let gamma = dt * if lower_triangular { a[(i, i)] } else { mean };
+                    let solve = match blocks.iter().find(|(g, _)| *g == gamma) {
+                        Some((_, solve)) => Rc::clone(solve),
+                        None => (preconditioner.setup)(mass, gamma)?,
+                    };
+                    blocks.push((gamma, solve));
+                }
+                let blocks = blocks.into_iter().map(|(_, solve)| solve).collect();
+                Some((preconditioner.side, blocks))
+            }
+            None => None,
+        };
+        Ok(MatrixFree {
+            jvp: self.jvp,
+            mass: mass.cloned(),
+            a: a * dt,
+            opts: self.opts,
+            preconditioner,
+        })
+    }
+}
+
+/// The iteration matrix `I ⊗ M - (dt A ⊗ J)` of a [`JacobianOperator`], whose systems are
+/// solved with [`gmres`].
+pub struct MatrixFree<'a, T: RealField> {
+    jvp: &'a dyn Fn(&DVector<T>) -> DVector<T>,
+    mass: Option<DMatrix<T>>,
+    /// `dt A`
+    a: DMatrix<f64>,
+    opts: GmresOptions,
+    /// the block diagonal preconditioner, a solve for each stage
+    preconditioner: Option<(PreconditionerSide, Vec<PreconditionerSolve<'a, T>>)>,
+}
+
+impl<'a, T: RealField> MatrixFree<'a, T> {
+    /// The product of the iteration matrix with `z`.
+    pub fn apply(&self, z: &DVector<T>) -> DVector<T> {
+        let s = self.a.nrows();
+        let n = z.len() / s;
+        let jz: Vec<_> = (0..s)
+            .map(|j| {
+                if (0..s).all(|i| self.a[(i, j)] == 0.) {
+                    None
+                } else {
+                    Some((self.jvp)(&z.rows(j * n, n).into_owned()))
+                }
+            })
+            .collect();
+        let mut out = DVector::zeros(s * n);
+        for i in 0..s {
+            let zi = z.rows(i * n, n);
+            let mut outi = match &self.mass {
+                Some(mass) => mass * zi,
+                None => zi.into_owned(),
+            };
+            for (j, jzj) in jz.iter().enumerate() {
+                if let Some(jzj) = jzj {
+                    let aij = self.a[(i, j)];
+                    if aij != 0. {
+                        outi.axpy(na::convert(-aij), jzj, T::one());
+                    }
+                }
+            }
+            out.rows_mut(i * n, n).copy_from(&outi);
+        }
+        out
+    }
+
+    /// The solve with the block diagonal preconditioner, the identity without one.
+    fn precondition(&self, r: &DVector<T>) -> DVector<T> {
+        match &self.preconditioner {
+            Some((_, blocks)) => {
+                let n = r.len() / blocks.len();
+                let mut z = DVector::zeros(r.len());
+                for (i, solve) in blocks.iter().enumerate() {
+                    z.rows_mut(i * n, n)
+                        .copy_from(&solve(&r.rows(i * n, n).into_owned()));
+                }
+                z
+            }
+            None => r.clone(),
+        }
+    }
+}
+
+impl<'a, T: RealField> fmt::Debug for MatrixFree<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MatrixFree")
+            .field("a", &self.a)
+            .field("opts", &self.opts)
+            .field(
+                "preconditioner",
+                &self.preconditioner.as_ref().map(|(side, _)| side),
+            )
+            .finish()
+    }
+}
+
+impl<'a, T: RealField> LinearSolver<T> for MatrixFree<'a, T> {
+    /// `None` if the iteration broke down, an unconverged solution is still returned
+    /// since the newton iteration only needs an approximate one.
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        let x = match self.preconditioner {
+            None => gmres(|v| self.apply(v), b, &self.opts).x,
+            Some((PreconditionerSide::Left, _)) => {
+                let op = |v: &DVector<T>| self.precondition(&self.apply(v));
+                gmres(op, &self.precondition(b), &self.opts).x
+            }
+            Some((PreconditionerSide::Right, _)) => {
+                let op = |v: &DVector<T>| self.apply(&self.precondition(v));
+                self.precondition(&gmres(op, b, &self.opts).x)
+            }
+        };
+        if x.iter().all(|xi| xi.is_finite()) {
+            Some(x)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gmres_convergence() {
+        // the nonsymmetric tridiagonal matrix of a convection diffusion problem
+        let n = 50;
+        let m = DMatrix::from_fn(n, n, |i, j| match i as isize - j as isize {
+            0 => 3.,
+            1 => -1.5,
+            -1 => -0.5,
+            _ => 0.,
+        });
+        let b = DVector::from_fn(n, |i, _| (i as f64 * 0.3).cos());
+        let opts = GmresOptions {
+            tol: 1e-10,
+            ..Default::default()
+        };
+        let solution = gmres(|v| &m * v, &b, &opts);
+        assert!(solution.converged);
+        assert!((&m * &solution.x - &b).norm() <= 1e-10 * b.norm());
+
+        // restarts with a small subspace still converge, but take longer
+        let restarted = gmres(|v| &m * v, &b, &GmresOptions { restart: 3, ..opts });
+        assert!(restarted.converged);
+        assert!(restarted.iterations > solution.iterations);
+
+        // `I - dt (A ⊗ J)` of two stages, applied matrix-free
+        let jac = DMatrix::from_row_slice(2, 2, &[-2., 1., 0.5, -3.]);
+        let jvp = |v: &DVector<f64>| &jac * v;
+        let operator = JacobianOperator::new(&jvp, opts);
+        let a = DMatrix::from_row_slice(2, 2, &[0.5, 0., 0.25, 0.25]);
+        let free = operator
+            .factor_stages(None, &a, 0.1, LinearSolverType::Auto)
+            .unwrap();
+        let dense = jac
+            .factor_stages(None, &a, 0.1, LinearSolverType::Auto)
+            .unwrap();
+        let b = DVector::from_column_slice(&[1., 2., 3., 4.]);
+        let diff = free.solve(&b).unwrap() - dense.solve(&b).unwrap();
+        assert!(diff.amax() < 1e-9);
+    }
+}
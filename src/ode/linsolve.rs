@@ -0,0 +1,325 @@
+use crate::error::OdeError;
+use crate::ode::krylov::GmresOptions;
+use crate::ode::sparse::{CscMatrix, SparseLu, SparsityPattern};
+use alga::general::RealField;
+use na::linalg::{LU, QR};
+use na::{DMatrix, DVector, Dynamic};
+use std::fmt;
+use std::rc::Rc;
+
+/// The method the linear systems of the implicit methods are solved with.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LinearSolverType {
+    /// Sparse LU if the sparsity pattern of the jacobian was declared, dense LU otherwise.
+    #[default]
+    Auto,
+    /// LU factorization with partial pivoting of the dense matrix.
+    DenseLu,
+    /// QR factorization of the dense matrix, more robust for nearly singular matrices.
+    Qr,
+    /// LU factorization with partial pivoting restricted to the band of the matrix, with
+    /// the bandwidth of the declared sparsity pattern or of the nonzero entries.
+    Banded,
+    /// LU factorization of the sparse matrix, c.f. [`CscMatrix::lu`].
+    SparseLu,
+    /// Matrix-free [`gmres`](crate::ode::krylov::gmres) with jacobian-vector products,
+    /// which never forms the jacobian, preconditioned with
+    /// [`OdeBuilder::preconditioner`](crate::ode::problem::OdeBuilder::preconditioner).
+    /// Methods that need the iteration matrix anyway, i.e. the Rosenbrock methods,
+    /// factorize it with dense LU instead.
+    Gmres(GmresOptions),
+}
+
+impl fmt::Display for LinearSolverType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinearSolverType::Auto => write!(f, "Auto"),
+            LinearSolverType::DenseLu => write!(f, "DenseLu"),
+            LinearSolverType::Qr => write!(f, "Qr"),
+            LinearSolverType::Banded => write!(f, "Banded"),
+            LinearSolverType::SparseLu => write!(f, "SparseLu"),
+            LinearSolverType::Gmres(opts) => write!(f, "Gmres({})", opts),
+        }
+    }
+}
+
+/// A factorized matrix `A` that solves linear systems `A x = b`.
+pub trait LinearSolver<T: RealField> {
+    /// Solves the system, `None` if the matrix is singular.
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>>;
+}
+
+impl<T: RealField> LinearSolver<T> for LU<T, Dynamic, Dynamic> {
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        LU::solve(self, b)
+    }
+}
+
+impl<T: RealField> LinearSolver<T> for QR<T, Dynamic, Dynamic> {
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        QR::solve(self, b)
+    }
+}
+
+impl<T: RealField> LinearSolver<T> for SparseLu<T> {
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        Some(SparseLu::solve(self, b))
+    }
+}
+
+/// The factorization of the iteration matrix of a newton iteration.
+#[derive(Debug, Clone)]
+pub enum Factorization<T: RealField> {
+    Dense(LU<T, Dynamic, Dynamic>),
+    Qr(QR<T, Dynamic, Dynamic>),
+    Banded(BandedLu<T>),
+    Sparse(SparseLu<T>),
+}
+
+impl<T: RealField> Factorization<T> {
+    /// Factorizes the dense matrix `m` with `solver`, [`LinearSolverType::Auto`] and
+    /// [`LinearSolverType::Gmres`] being a dense LU factorization.
+    pub fn dense(m: DMatrix<T>, solver: LinearSolverType) -> Result<Self, OdeError> {
+        Ok(match solver {
+            LinearSolverType::Auto | LinearSolverType::DenseLu | LinearSolverType::Gmres(_) => {
+                Factorization::Dense(m.lu())
+            }
+            LinearSolverType::Qr => Factorization::Qr(m.qr()),
+            LinearSolverType::Banded => {
+                let (lower, upper) = bandwidth(&m);
+                Factorization::Banded(
+                    BandedLu::new(&m, lower, upper).ok_or(OdeError::InvalidMatrix)?,
+                )
+            }
+            LinearSolverType::SparseLu => {
+                let pattern = Rc::new(SparsityPattern::from_matrix(&m));
+                Factorization::Sparse(
+                    CscMatrix::from_dense(pattern, &m)
+                        .lu()
+                        .ok_or(OdeError::InvalidMatrix)?,
+                )
+            }
+        })
+    }
+
+    /// Factorizes the sparse matrix `m` with `solver`, [`LinearSolverType::Auto`] and
+    /// [`LinearSolverType::Gmres`] being a sparse LU factorization.
+    pub fn sparse(m: &CscMatrix<T>, solver: LinearSolverType) -> Result<Self, OdeError> {
+        match solver {
+            LinearSolverType::Auto | LinearSolverType::SparseLu | LinearSolverType::Gmres(_) => Ok(
+                Factorization::Sparse(m.lu().ok_or(OdeError::InvalidMatrix)?),
+            ),
+            LinearSolverType::Banded => {
+                let (lower, upper) = m.pattern().bandwidth();
+                Ok(Factorization::Banded(
+                    BandedLu::new(&m.to_dense(), lower, upper).ok_or(OdeError::InvalidMatrix)?,
+                ))
+            }
+            LinearSolverType::DenseLu | LinearSolverType::Qr => Self::dense(m.to_dense(), solver),
+        }
+    }
+}
+
+impl<T: RealField> LinearSolver<T> for Factorization<T> {
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        match self {
+            Factorization::Dense(lu) => LinearSolver::solve(lu, b),
+            Factorization::Qr(qr) => LinearSolver::solve(qr, b),
+            Factorization::Banded(lu) => LinearSolver::solve(lu, b),
+            Factorization::Sparse(lu) => LinearSolver::solve(lu, b),
+        }
+    }
+}
+
+/// The number of subdiagonals and superdiagonals with nonzero entries of `m`.
+pub fn bandwidth<T: RealField>(m: &DMatrix<T>) -> (usize, usize) {
+    let mut band = (0, 0);
+    for j in 0..m.ncols() {
+        for i in 0..m.nrows() {
+            if !m[(i, j)].is_zero() {
+                if i > j {
+                    band.0 = band.0.max(i - j);
+                } else {
+                    band.1 = band.1.max(j - i);
+                }
+            }
+        }
+    }
+    band
+}
+
+/// The LU factorization with partial pivoting of a square band matrix with `lower`
+/// subdiagonals and `upper` superdiagonals, which only takes `O(n lower (lower + upper))`
+/// operations.
+///
+/// Row interchanges widen the band of `U` to `lower + upper` superdiagonals, as in
+/// LAPACK's `xGBTRF`.
+#[derive(Debug, Clone)]
+pub struct BandedLu<T> {
+    n: usize,
+    lower: usize,
+    upper: usize,
+    /// the band of `U` by rows, entry `(i, j)` at `i * width + j + lower - i`
+    u: Vec<T>,
+    /// the multipliers of elimination step `k` at `k * lower + i - k - 1`
+    l: Vec<T>,
+    /// the row interchanged with row `k` in step `k`
+    pivots: Vec<usize>,
+}
+
+impl<T: RealField> BandedLu<T> {
+    /// Factorizes the band of `m`, entries outside of it are ignored. Returns `None` if
+    /// `m` is singular.
+    pub fn new(m: &DMatrix<T>, lower: usize, upper: usize) -> Option<Self> {
+        assert!(m.is_square(), "only square matrices can be factorized");
+        let n = m.nrows();
+        let width = 2 * lower + upper + 1;
+        let mut u = vec![T::zero(); n * width];
+        for i in 0..n {
+            for j in i.saturating_sub(lower)..n.min(i + upper + 1) {
+                u[i * width + j + lower - i] = m[(i, j)];
+            }
+        }
+        Self::factor(n, lower, upper, u)
+    }
+
+    /// Factorizes the band of the sparse matrix `m`, entries outside of it are ignored.
+    /// Returns `None` if `m` is singular.
+    pub fn from_sparse(m: &CscMatrix<T>, lower: usize, upper: usize) -> Option<Self> {
+        let (n, ncols) = m.shape();
+        assert_eq!(n, ncols, "only square matrices can be factorized");
+        let width = 2 * lower + upper + 1;
+        let mut u = vec![T::zero(); n * width];
+        for (i, j, value) in m.triplets() {
+            if i <= j + lower && j <= i + upper {
+                u[i * width + j + lower - i] = value;
+            }
+        }
+        Self::factor(n, lower, upper, u)
+    }
+
+    /// Factorizes the band `u` of the matrix, stored by rows like the band of `U`.
+    fn factor(n: usize, lower: usize, upper: usize, mut u: Vec<T>) -> Option<Self> {
+        let width = 2 * lower + upper + 1;
+        let mut l = vec![T::zero(); n * lower];
+        let mut pivots = Vec::with_capacity(n);
+        let at = |i: usize, j: usize| i * width + j + lower - i;
+
+        for k in 0..n {
+            let last_row = (n - 1).min(k + lower);
+            let last_col = (n - 1).min(k + lower + upper);
+            let p = (k..=last_row)
+                .max_by(|&a, &b| {
+                    u[at(a, k)]
+                        .abs()
+                        .partial_cmp(&u[at(b, k)].abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(k);
+            if u[at(p, k)].is_zero() {
+                return None;
+            }
+            if p != k {
+                for j in k..=last_col {
+                    u.swap(at(k, j), at(p, j));
+                }
+            }
+            pivots.push(p);
+
+            let pivot = u[at(k, k)];
+            for i in k + 1..=last_row {
+                let factor = u[at(i, k)] / pivot;
+                l[k * lower + i - k - 1] = factor;
+                u[at(i, k)] = T::zero();
+                if factor.is_zero() {
+                    continue;
+                }
+                for j in k + 1..=last_col {
+                    let ukj = u[at(k, j)];
+                    u[at(i, j)] -= factor * ukj;
+                }
+            }
+        }
+
+        Some(Self {
+            n,
+            lower,
+            upper,
+            u,
+            l,
+            pivots,
+        })
+    }
+
+    /// Solves `m x = b`.
+    pub fn solve(&self, b: &DVector<T>) -> DVector<T> {
+        let (n, lower) = (self.n, self.lower);
+        let width = 2 * lower + self.upper + 1;
+        let at = |i: usize, j: usize| i * width + j + lower - i;
+        let mut x = b.clone();
+        for k in 0..n {
+            x.swap_rows(k, self.pivots[k]);
+            let xk = x[k];
+            for i in k + 1..=(n - 1).min(k + lower) {
+                x[i] -= self.l[k * lower + i - k - 1] * xk;
+            }
+        }
+        for k in (0..n).rev() {
+            let mut xk = x[k];
+            for j in k + 1..=(n - 1).min(k + lower + self.upper) {
+                xk -= self.u[at(k, j)] * x[j];
+            }
+            x[k] = xk / self.u[at(k, k)];
+        }
+        x
+    }
+}
+
+impl<T: RealField> LinearSolver<T> for BandedLu<T> {
+    fn solve(&self, b: &DVector<T>) -> Option<DVector<T>> {
+        Some(BandedLu::solve(self, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solvers() {
+        // a tridiagonal matrix that requires pivoting
+        let n = 8;
+        let m = DMatrix::from_fn(n, n, |i, j| match (i as isize - j as isize, i % 3) {
+            (0, 0) => 1e-3,
+            (0, _) => 2. + i as f64,
+            (1, _) => 3.,
+            (-1, _) => -1.,
+            _ => 0.,
+        });
+        assert_eq!((1, 1), bandwidth(&m));
+        let b = DVector::from_fn(n, |i, _| (i as f64).sin());
+        for solver in [
+            LinearSolverType::Auto,
+            LinearSolverType::DenseLu,
+            LinearSolverType::Qr,
+            LinearSolverType::Banded,
+            LinearSolverType::SparseLu,
+        ] {
+            let x = Factorization::dense(m.clone(), solver)
+                .unwrap()
+                .solve(&b)
+                .unwrap();
+            assert!((&m * x - &b).amax() < 1e-12, "{}", solver);
+        }
+
+        // a wider band than nonzero entries
+        let x = BandedLu::new(&m, 2, 3).unwrap().solve(&b);
+        assert!((&m * x - &b).amax() < 1e-12);
+        let sparse = CscMatrix::from_dense(Rc::new(SparsityPattern::banded(n, 1, 1)), &m);
+        let x = BandedLu::from_sparse(&sparse, 1, 2).unwrap().solve(&b);
+        assert!((&m * x - &b).amax() < 1e-12);
+
+        let singular = DMatrix::from_row_slice(3, 3, &[1., 2., 0., 2., 4., 0., 0., 1., 1.]);
+        assert!(BandedLu::new(&singular, 1, 1).is_none());
+    }
+}
@@ -0,0 +1,75 @@
+use na::allocator::Allocator;
+use na::*;
+
+/// Coefficients of a low-storage Runge–Kutta method in Williamson's 2N form.
+///
+/// A step only needs the solution `u` and a single increment register `du`:
+///
+/// ```latex
+/// du = a_i du + h f(t + c_i h, u)
+/// u = u + b_i du
+/// ```
+///
+/// for each stage `i`, with `a_1 = 0`.
+#[derive(Clone, Debug)]
+pub struct LowStorageCoeffs<S: Dim>
+where
+    DefaultAllocator: Allocator<f64, S>,
+{
+    pub a: VectorN<f64, S>,
+    pub b: VectorN<f64, S>,
+    pub c: VectorN<f64, S>,
+}
+
+impl<S: Dim> LowStorageCoeffs<S>
+where
+    DefaultAllocator: Allocator<f64, S>,
+{
+    /// the number of stages `S`
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.c.nrows()
+    }
+}
+
+impl LowStorageCoeffs<U3> {
+    /// Three stage, third order method, c.f. J.H. Williamson, "Low-storage
+    /// Runge-Kutta schemes", 1980.
+    pub fn williamson3() -> Self {
+        let a = Vector3::new(0., -5. / 9., -153. / 128.);
+        let b = Vector3::new(1. / 3., 15. / 16., 8. / 15.);
+        let c = Vector3::new(0., 1. / 3., 0.75);
+
+        Self { a, b, c }
+    }
+}
+
+impl LowStorageCoeffs<U5> {
+    /// Five stage, fourth order method, c.f. M.H. Carpenter, C.A. Kennedy,
+    /// "Fourth-order 2N-storage Runge-Kutta schemes", NASA TM 109112, 1994.
+    pub fn ck54() -> Self {
+        let a = Vector5::new(
+            0.,
+            -567_301_805_773. / 1_357_537_059_087.,
+            -2_404_267_990_393. / 2_016_746_695_238.,
+            -3_550_918_686_646. / 2_091_501_179_385.,
+            -1_275_806_237_668. / 842_570_457_699.,
+        );
+        let b = Vector5::new(
+            1_432_997_174_477. / 9_575_080_441_755.,
+            5_161_836_677_717. / 13_612_068_292_357.,
+            1_720_146_321_549. / 2_090_206_949_498.,
+            3_134_564_353_537. / 4_481_467_310_338.,
+            2_277_821_191_437. / 14_882_151_754_819.,
+        );
+        let c = Vector5::new(
+            0.,
+            1_432_997_174_477. / 9_575_080_441_755.,
+            2_526_269_341_429. / 6_820_363_962_896.,
+            2_006_345_519_317. / 3_224_310_063_776.,
+            2_802_321_613_138. / 2_924_317_926_251.,
+        );
+
+        Self { a, b, c }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::OdeError;
+use crate::ode::expm;
+use crate::ode::solution::OdeSolution;
+use na::{DMatrix, DVector};
+
+/// The linear problem `du/dt = A(t) u` with a time dependent matrix `A`.
+///
+/// A: the matrix of the RHS as a function of t.
+#[derive(Debug, Clone)]
+pub struct LinearOdeProblem<A>
+where
+    A: Fn(f64) -> DMatrix<f64>,
+{
+    /// The matrix `A(t)`.
+    a: A,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinearOdeBuilder<A>
+where
+    A: Fn(f64) -> DMatrix<f64>,
+{
+    a: Option<A>,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<A> Default for LinearOdeBuilder<A>
+where
+    A: Fn(f64) -> DMatrix<f64>,
+{
+    fn default() -> Self {
+        Self {
+            a: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<A> LinearOdeBuilder<A>
+where
+    A: Fn(f64) -> DMatrix<f64>,
+{
+    /// set the matrix of the problem function
+    pub fn matrix(mut self, a: A) -> Self {
+        self.a = Some(a);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`LinearOdeProblem`].
+    ///
+    /// Returns an error if a field is None or the matrix does not match the initial
+    /// value.
+    pub fn build(self) -> Result<LinearOdeProblem<A>, OdeError> {
+        let a = self
+            .a
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        if let Some(t0) = tspan.first() {
+            let shape = a(*t0).shape();
+            if shape != (y0.len(), y0.len()) {
+                return Err(OdeError::DimensionMismatch {
+                    expected: (y0.len(), y0.len()),
+                    found: shape,
+                });
+            }
+        }
+
+        Ok(LinearOdeProblem { a, y0, tspan })
+    }
+}
+
+impl<A> LinearOdeProblem<A>
+where
+    A: Fn(f64) -> DMatrix<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `LinearOdeBuilder::default()`
+    pub fn builder() -> LinearOdeBuilder<A> {
+        LinearOdeBuilder::default()
+    }
+
+    /// Solve the problem using the Magnus integrator of order 2 at the fixed steps of
+    /// `tspan`, the exponential midpoint rule
+    ///
+    /// ```latex
+    /// u_{n+1} = e^{h A(t_n + h/2)} u_n
+    /// ```
+    pub fn magnus2(&self) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        self.ode_magnus(|t, h| (self.a)(t + h / 2.) * h)
+    }
+
+    /// Solve the problem using the Magnus integrator of order 4 at the fixed steps of
+    /// `tspan`, with the two point Gauss–Legendre quadrature
+    ///
+    /// ```latex
+    /// Ω = h/2 (A_1 + A_2) + √3/12 h^2 [A_2, A_1]
+    /// ```
+    ///
+    /// c.f. Blanes, Casas, Oteo & Ros, "The Magnus expansion and some of its
+    /// applications", 2009.
+    pub fn magnus4(&self) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        let c = 3f64.sqrt() / 6.;
+        self.ode_magnus(|t, h| {
+            let a1 = (self.a)(t + (0.5 - c) * h);
+            let a2 = (self.a)(t + (0.5 + c) * h);
+            let commutator = &a2 * &a1 - &a1 * &a2;
+            (a1 + a2) * (h / 2.) + commutator * (c / 2. * h * h)
+        })
+    }
+
+    /// Integrates at the fixed steps of `tspan` with `u_{n+1} = e^{Ω(t_n, h)} u_n`.
+    fn ode_magnus<M>(&self, omega: M) -> Result<OdeSolution<f64, DVector<f64>>, OdeError>
+    where
+        M: Fn(f64, f64) -> DMatrix<f64>,
+    {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        yout.push(self.y0.clone());
+
+        for w in self.tspan.windows(2) {
+            let (t, h) = (w[0], w[1] - w[0]);
+            let u = expm::expm(&omega(t, h))? * yout.last().unwrap();
+            yout.push(u);
+        }
+
+        Ok(OdeSolution::new(self.tspan.clone(), yout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magnus_convergence() {
+        // airy equation `y'' = -(1 + t) y`
+        let solve = |steps: usize, fourth_order: bool| {
+            let problem = LinearOdeProblem::builder()
+                .matrix(|t| DMatrix::from_row_slice(2, 2, &[0., 1., -(1. + t), 0.]))
+                .init(DVector::from_row_slice(&[1., 0.]))
+                .tspan_linspace(0., 5., steps + 1)
+                .build()
+                .unwrap();
+            let solution = if fourth_order {
+                problem.magnus4()
+            } else {
+                problem.magnus2()
+            }
+            .unwrap();
+            solution.yout.last().unwrap().clone()
+        };
+        let reference = solve(2000, true);
+        let error = |steps, fourth_order| (solve(steps, fourth_order) - &reference).amax();
+
+        assert!((error(50, false) / error(100, false) - 4.).abs() < 0.5);
+        assert!((error(50, true) / error(100, true) - 16.).abs() < 2.);
+    }
+}
@@ -1,9 +1,41 @@
+pub mod adams;
+pub mod bdf;
+pub mod callback;
 pub mod coeff;
+pub mod composite;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod controller;
+pub mod dense;
+pub mod dop853;
+pub mod dual;
+pub mod expm;
+pub mod exponential;
+pub mod extrapolation;
+pub mod imex;
+pub mod implicit;
+pub mod integrator;
+pub mod jacobian;
+pub mod jet;
+pub mod krylov;
+pub mod linsolve;
+pub mod low_storage;
+pub mod magnus;
+pub mod multirate;
+pub mod multistep;
+pub mod newton;
+pub mod nordsieck;
+pub mod nystrom;
 pub mod options;
 pub mod problem;
 pub mod rosenbrock;
 pub mod runge_kutta;
 pub mod solution;
+pub mod sparse;
+pub mod splitting;
+pub mod stiffness;
+pub mod symplectic;
+pub mod taylor;
 pub mod types;
 #[cfg(feature = "serde0")]
 use serde::{Deserialize, Serialize};
@@ -13,16 +45,48 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone)]
 pub enum Ode {
     Feuler,
+    Beuler,
+    Trapezoid,
+    TrapezoidAdapt,
+    Sdirk2,
+    Sdirk4,
+    Kvaerno4,
+    TrBdf2,
+    Radau5,
     Heun,
     Midpoint,
+    Ssprk22,
+    Ssprk33,
+    Ssprk104,
+    Gauss4,
+    Gauss6,
+    LobattoIIIA,
+    LobattoIIIB,
+    LobattoIIIC,
     Ode23,
     Ode23s,
+    Rosenbrock23,
+    Rodas4,
+    Rodas5,
+    AutoTsit5,
+    Bdf,
+    Abm,
+    Adams,
+    Gbs,
+    Bs3,
     Ode4,
     Ode45,
     Ode45fe,
+    Ode45ck,
+    Lsrk33,
+    Lsrk54,
     Ode4skr,
     Ode4ss,
     Ode78,
+    Dop853,
+    Tsit5,
+    Vern6,
+    Vern9,
 }
 
 impl std::str::FromStr for Ode {
@@ -31,15 +95,47 @@ impl std::str::FromStr for Ode {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "feuler" => Ok(Ode::Feuler),
+            "beuler" => Ok(Ode::Beuler),
+            "trapezoid" => Ok(Ode::Trapezoid),
+            "trapezoid_adapt" => Ok(Ode::TrapezoidAdapt),
+            "sdirk2" => Ok(Ode::Sdirk2),
+            "sdirk4" => Ok(Ode::Sdirk4),
+            "kvaerno4" => Ok(Ode::Kvaerno4),
+            "trbdf2" => Ok(Ode::TrBdf2),
+            "radau5" => Ok(Ode::Radau5),
             "heun" => Ok(Ode::Heun),
             "midpoint" => Ok(Ode::Midpoint),
+            "ssprk22" => Ok(Ode::Ssprk22),
+            "ssprk33" => Ok(Ode::Ssprk33),
+            "ssprk104" => Ok(Ode::Ssprk104),
+            "gauss4" => Ok(Ode::Gauss4),
+            "gauss6" => Ok(Ode::Gauss6),
+            "lobatto3a" => Ok(Ode::LobattoIIIA),
+            "lobatto3b" => Ok(Ode::LobattoIIIB),
+            "lobatto3c" => Ok(Ode::LobattoIIIC),
             "ode23" => Ok(Ode::Ode23),
             "ode23s" => Ok(Ode::Ode23s),
+            "rosenbrock23" => Ok(Ode::Rosenbrock23),
+            "rodas4" => Ok(Ode::Rodas4),
+            "rodas5" => Ok(Ode::Rodas5),
+            "auto_tsit5" => Ok(Ode::AutoTsit5),
+            "bdf" => Ok(Ode::Bdf),
+            "abm" => Ok(Ode::Abm),
+            "adams" => Ok(Ode::Adams),
+            "gbs" => Ok(Ode::Gbs),
+            "bs3" => Ok(Ode::Bs3),
             "ode4" => Ok(Ode::Ode4),
             "ode45" => Ok(Ode::Ode45),
+            "ode45ck" => Ok(Ode::Ode45ck),
+            "lsrk33" => Ok(Ode::Lsrk33),
+            "lsrk54" => Ok(Ode::Lsrk54),
             "ode4skr" => Ok(Ode::Ode4skr),
             "ode4s" => Ok(Ode::Ode4ss),
             "ode78" => Ok(Ode::Ode78),
+            "dop853" => Ok(Ode::Dop853),
+            "tsit5" => Ok(Ode::Tsit5),
+            "vern6" => Ok(Ode::Vern6),
+            "vern9" => Ok(Ode::Vern9),
             _ => Err(format!("{} is not a valid Ode identifier", s)),
         }
     }
@@ -0,0 +1,323 @@
+use crate::error::OdeError;
+use crate::ode::options::OdeOptionMap;
+use crate::ode::problem::OdeProblem;
+use crate::ode::solution::OdeSolution;
+use crate::ode::types::OdeType;
+use crate::ode::Ode;
+use alga::general::RealField;
+use na::DMatrix;
+use std::ops::{Add, Mul};
+
+/// The coefficients of an explicit multirate infinitesimal GARK method, c.f. Sandu, "A
+/// class of multirate infinitesimal GARK methods", 2019.
+///
+/// Stage `i` solves the modified fast problem
+///
+/// ```latex
+/// v' = F(t, v) + 1/Δc_i Σ_j γ_ij((t - T_{i-1}) / (Δc_i h)) S(T_j, Y_j)
+/// ```
+///
+/// from `Y_{i-1}` over `[T_{i-1}, T_i]` with `γ_ij(θ) = Σ_k Γ^k_ij θ^k`.
+#[derive(Clone, Debug)]
+pub struct MriCoeffs {
+    /// the coupling matrices `Γ^k` of the stages `2..=s` and the solution
+    pub gamma: Vec<DMatrix<f64>>,
+    /// slow nodes, the last node is 1
+    pub c: Vec<f64>,
+    /// order of the method
+    pub order: usize,
+}
+
+impl MriCoeffs {
+    /// MRI-GARK-ERK22a, the explicit midpoint rule as slow method.
+    pub fn erk22a() -> Self {
+        Self {
+            gamma: vec![DMatrix::from_row_slice(2, 2, &[0.5, 0., -0.5, 1.])],
+            c: vec![0., 0.5, 1.],
+            order: 2,
+        }
+    }
+
+    /// MRI-GARK-ERK33a with `δ = -1/2`, Heun's third order method as slow method.
+    pub fn erk33a() -> Self {
+        #[rustfmt::skip]
+        let gamma0 = DMatrix::from_row_slice(3, 3, &[
+            1. / 3., 0., 0.,
+            -1. / 3., 2. / 3., 0.,
+            0., -2. / 3., 1.,
+        ]);
+        #[rustfmt::skip]
+        let gamma1 = DMatrix::from_row_slice(3, 3, &[
+            0., 0., 0.,
+            0., 0., 0.,
+            0.5, 0., -0.5,
+        ]);
+        Self {
+            gamma: vec![gamma0, gamma1],
+            c: vec![0., 1. / 3., 2. / 3., 1.],
+            order: 3,
+        }
+    }
+
+    /// the number of slow stages
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.c.len() - 1
+    }
+
+    /// The coupling coefficient `γ_ij(θ)` of the slow stage `j` in the fast problem of
+    /// stage `i + 1`.
+    pub fn coupling(&self, i: usize, j: usize, theta: f64) -> f64 {
+        self.gamma
+            .iter()
+            .rev()
+            .fold(0., |acc, gamma| acc * theta + gamma[(i, j)])
+    }
+}
+
+/// The problem `dy/dt = F(t, y) + S(t, y)` with a fast part `F` that is integrated with
+/// smaller substeps than the slow part `S`.
+///
+/// F: the fast part of the RHS, a function of t and y(t).
+/// S: the slow part of the RHS, a function of t and y(t).
+#[derive(Debug, Clone)]
+pub struct MultirateProblem<F, S, Y>
+where
+    F: Fn(f64, &Y) -> Y,
+    S: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    /// The fast part `F(t, y)`.
+    fast: F,
+    /// The slow part `S(t, y)`.
+    slow: S,
+    /// Initial value.
+    y0: Y,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MultirateBuilder<F, S, Y>
+where
+    F: Fn(f64, &Y) -> Y,
+    S: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    fast: Option<F>,
+    slow: Option<S>,
+    y0: Option<Y>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F, S, Y> Default for MultirateBuilder<F, S, Y>
+where
+    F: Fn(f64, &Y) -> Y,
+    S: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    fn default() -> Self {
+        Self {
+            fast: None,
+            slow: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F, S, Y> MultirateBuilder<F, S, Y>
+where
+    F: Fn(f64, &Y) -> Y,
+    S: Fn(f64, &Y) -> Y,
+    Y: OdeType,
+{
+    /// set the fast part of the problem function
+    pub fn fast(mut self, fast: F) -> Self {
+        self.fast = Some(fast);
+        self
+    }
+
+    /// set the slow part of the problem function
+    pub fn slow(mut self, slow: S) -> Self {
+        self.slow = Some(slow);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<Y>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`MultirateProblem`].
+    ///
+    /// Returns an error if a field is None.
+    pub fn build(self) -> Result<MultirateProblem<F, S, Y>, OdeError> {
+        let fast = self
+            .fast
+            .ok_or_else(|| OdeError::uninitialized("Fast part must be initialized"))?;
+        let slow = self
+            .slow
+            .ok_or_else(|| OdeError::uninitialized("Slow part must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+
+        Ok(MultirateProblem {
+            fast,
+            slow,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl<F, S, Y, T> MultirateProblem<F, S, Y>
+where
+    F: Fn(f64, &Y) -> Y,
+    S: Fn(f64, &Y) -> Y,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    /// convenience method to create a new builder
+    /// same as `MultirateBuilder::default()`
+    pub fn builder() -> MultirateBuilder<F, S, Y> {
+        MultirateBuilder::default()
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` with the second order
+    /// MRI-GARK-ERK22a method, using `substeps` steps of the fast solver `ode` per step.
+    pub fn mri_gark2(&self, ode: Ode, substeps: usize) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.mri_gark(&MriCoeffs::erk22a(), ode, substeps)
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` with the third order
+    /// MRI-GARK-ERK33a method, using `substeps` steps of the fast solver `ode` per step.
+    pub fn mri_gark3(&self, ode: Ode, substeps: usize) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.mri_gark(&MriCoeffs::erk33a(), ode, substeps)
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` with an explicit MRI-GARK method.
+    ///
+    /// The fast problem of every stage is solved by `ode` on a uniform grid with a share
+    /// of the `substeps` proportional to the length of the stage, adaptive solvers only
+    /// use the grid for their output.
+    pub fn mri_gark(
+        &self,
+        coeffs: &MriCoeffs,
+        ode: Ode,
+        substeps: usize,
+    ) -> Result<OdeSolution<f64, Y>, OdeError> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let c = &coeffs.c;
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        yout.push(self.y0.clone());
+
+        for w in self.tspan.windows(2) {
+            let (t, h) = (w[0], w[1] - w[0]);
+            let mut y = yout.last().unwrap().clone();
+            let mut slow = Vec::with_capacity(coeffs.nstages());
+
+            for i in 0..coeffs.nstages() {
+                let t0 = t + c[i] * h;
+                let dc = c[i + 1] - c[i];
+                slow.push((self.slow)(t0, &y));
+
+                // the fast problem of the stage, forced by the slow stages
+                let problem = OdeProblem::builder()
+                    .fun(|tf, v: &Y| {
+                        let theta = (tf - t0) / (dc * h);
+                        let mut dv = (self.fast)(tf, v);
+                        for (j, s) in slow.iter().enumerate() {
+                            let gamma = coeffs.coupling(i, j, theta) / dc;
+                            for p in 0..dv.dof() {
+                                *dv.get_mut(p) += s.get(p) * gamma;
+                            }
+                        }
+                        dv
+                    })
+                    .init(y)
+                    .tspan_linspace(
+                        t0,
+                        t0 + dc * h,
+                        (dc * substeps as f64).ceil().max(1.) as usize + 1,
+                    )
+                    .build()?;
+                y = problem
+                    .solve(ode.clone(), OdeOptionMap::default())?
+                    .yout
+                    .pop()
+                    .unwrap();
+            }
+            yout.push(y);
+        }
+
+        Ok(OdeSolution::new(self.tspan.clone(), yout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::expm;
+    use na::DVector;
+
+    #[test]
+    fn mri_gark_convergence() {
+        // a fast rotation coupled to a slow decay
+        #[rustfmt::skip]
+        let fast = DMatrix::from_row_slice(3, 3, &[
+            0., 20., 0.,
+            -20., 0., 0.,
+            0., 0., 0.,
+        ]);
+        #[rustfmt::skip]
+        let slow = DMatrix::from_row_slice(3, 3, &[
+            -0.5, 0., 1.,
+            0., 0., 0.,
+            0.3, 0., -0.2,
+        ]);
+        let y0 = DVector::from_row_slice(&[1., 0., 1.]);
+        let exact = expm::expm(&(&fast + &slow)).unwrap() * &y0;
+
+        let error = |steps: usize, third_order: bool| {
+            let problem = MultirateProblem::builder()
+                .fast(|_t, y: &DVector<f64>| &fast * y)
+                .slow(|_t, y: &DVector<f64>| &slow * y)
+                .init(y0.clone())
+                .tspan_linspace(0., 1., steps + 1)
+                .build()
+                .unwrap();
+            let solution = if third_order {
+                problem.mri_gark3(Ode::Ode4, 60)
+            } else {
+                problem.mri_gark2(Ode::Ode4, 60)
+            }
+            .unwrap();
+            (solution.yout.last().unwrap() - &exact).amax()
+        };
+
+        assert!((error(40, false) / error(80, false) - 4.).abs() < 0.5);
+        assert!((error(40, true) / error(80, true) - 8.).abs() < 1.);
+    }
+}
@@ -0,0 +1,140 @@
+use alga::general::RealField;
+use na::{DMatrix, DVector};
+use std::ops::{Add, Mul};
+
+/// The history of a multistep integration as backward differences `D_j = ∇^j x_n`
+/// of a quantity `x` (the solution for BDF, the derivatives for Adams methods) at
+/// equally spaced points `t_n, t_n - h, ...`.
+///
+/// Changing the step size rescales the differences to the new spacing, c.f.
+/// Shampine & Reichelt, "The MATLAB ODE Suite", 1997. For a method of order `k`
+/// the differences `D_{k+1}` and `D_{k+2}` are available after an update and can
+/// be used to estimate the error at the neighbouring orders.
+#[derive(Clone, Debug)]
+pub struct DifferenceHistory<T: RealField> {
+    pub d: Vec<DVector<T>>,
+}
+
+impl<T> DifferenceHistory<T>
+where
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+{
+    /// Starts the history at `x0` with the first difference `dx0`, for methods up
+    /// to order `max_order`.
+    pub fn new(x0: DVector<T>, dx0: DVector<T>, max_order: usize) -> Self {
+        let mut d = vec![DVector::zeros(x0.len()); max_order + 3];
+        d[0] = x0;
+        d[1] = dx0;
+        Self { d }
+    }
+
+    /// Rescales the differences up to order `order` to the step size `factor * h`.
+    pub fn rescale(&mut self, order: usize, factor: f64) {
+        let ru = difference_transform(order, factor) * difference_transform(order, 1.);
+        let d: Vec<DVector<T>> = (0..=order)
+            .map(|i| {
+                self.d[..=order]
+                    .iter()
+                    .enumerate()
+                    .fold(DVector::zeros(self.d[0].len()), |acc, (j, dj)| {
+                        acc + dj * (T::one() * ru[(j, i)])
+                    })
+            })
+            .collect();
+        for (i, di) in d.into_iter().enumerate() {
+            self.d[i] = di;
+        }
+    }
+
+    /// The extrapolation `Σ_{j=0}^k D_j` of the polynomial of degree `order` to the
+    /// next point.
+    pub fn predict(&self, order: usize) -> DVector<T> {
+        self.d[1..=order]
+            .iter()
+            .fold(self.d[0].clone(), |acc, dj| acc + dj)
+    }
+
+    /// Updates the differences with the correction `d = x_{n+1} - Σ_{j=0}^k D_j`
+    /// of the new point from the prediction of order `order`.
+    pub fn update(&mut self, order: usize, d: &DVector<T>) {
+        self.d[order + 2] = d - &self.d[order + 1];
+        self.d[order + 1] = d.clone();
+        for i in (0..=order).rev() {
+            let di = &self.d[i] + &self.d[i + 1];
+            self.d[i] = di;
+        }
+    }
+
+    /// Appends the value `x_{n+1}` at the next point.
+    pub fn push(&mut self, order: usize, x: &DVector<T>) {
+        let d = x - self.predict(order);
+        self.update(order, &d);
+    }
+
+    /// Evaluates the interpolating polynomial of degree `order` of the history at `t`,
+    /// the differences belong to the points `tn, tn - h, ...`.
+    pub fn interpolate(&self, order: usize, tn: f64, h: f64, t: f64) -> DVector<T> {
+        let mut p = 1.;
+        let mut y = self.d[0].clone();
+        for j in 0..order {
+            p *= (t - (tn - h * j as f64)) / (h * (j + 1) as f64);
+            y += &self.d[j + 1] * (T::one() * p);
+        }
+        y
+    }
+}
+
+/// The matrix that transforms the differences up to order `order` to a step size
+/// changed by `factor`.
+fn difference_transform(order: usize, factor: f64) -> DMatrix<f64> {
+    let mut r = DMatrix::zeros(order + 1, order + 1);
+    for j in 0..=order {
+        r[(0, j)] = 1.;
+    }
+    for i in 1..=order {
+        for j in 1..=order {
+            r[(i, j)] = r[(i - 1, j)] * (i as f64 - 1. - factor * j as f64) / i as f64;
+        }
+    }
+    r
+}
+
+/// Root mean square norm of `v` weighted by `scale`.
+pub fn rms_norm<T: RealField + Into<f64>>(v: &DVector<T>, scale: &[f64]) -> f64 {
+    let sum = v.iter().zip(scale).fold(0., |acc, (vi, si)| {
+        let vi: f64 = (*vi).into();
+        acc + (vi / si).powi(2)
+    });
+    (sum / v.len() as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rescale_history() {
+        // the history of `y = t^2` at `t = 1` with step size `0.1`
+        let h = 0.1;
+        let y = |t: f64| DVector::from_element(1, t * t);
+        let mut history = DifferenceHistory::new(y(1.), DVector::zeros(1), 5);
+        history.d[1] = y(1.) - y(1. - h);
+        history.d[2] = y(1.) - y(1. - h) * 2. + y(1. - 2. * h);
+
+        assert!((history.interpolate(2, 1., h, 1.25)[0] - 1.5625).abs() < 1e-12);
+
+        history.rescale(2, 0.5);
+        assert!((history.d[1][0] - (1. - 0.95 * 0.95)).abs() < 1e-12);
+        assert!((history.interpolate(2, 1., h / 2., 1.25)[0] - 1.5625).abs() < 1e-12);
+
+        history.rescale(2, 2.);
+        assert!((history.d[1][0] - (1. - 0.9 * 0.9)).abs() < 1e-12);
+        assert!((history.predict(2)[0] - 1.21).abs() < 1e-12);
+
+        // the next point of the parabola leaves the higher differences unchanged
+        history.push(2, &y(1.1));
+        assert!((history.d[0][0] - 1.21).abs() < 1e-12);
+        assert!((history.d[2][0] - 2. * h * h).abs() < 1e-12);
+        assert!(history.d[3][0].abs() < 1e-12);
+    }
+}
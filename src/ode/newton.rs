@@ -0,0 +1,366 @@
+use crate::error::OdeError;
+use crate::ode::bdf;
+use crate::ode::jacobian::MAX_REUSES;
+use crate::ode::linsolve::LinearSolver;
+use crate::ode::problem::count;
+use crate::ode::solution::Diagnostics;
+use alga::general::RealField;
+use na::DVector;
+use std::cell::Cell;
+use std::fmt;
+
+/// When the implicit methods evaluate a new jacobian for their newton iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JacobianReuse {
+    /// Evaluate the jacobian at the start of every step.
+    Never,
+    /// Reuse the jacobian of an earlier step for at most this many steps.
+    Steps(usize),
+    /// Only evaluate a new jacobian if the newton iteration fails to converge.
+    UntilFailure,
+}
+
+impl JacobianReuse {
+    /// The number of steps a jacobian may be reused for.
+    pub fn max_reuses(&self) -> usize {
+        match self {
+            JacobianReuse::Never => 0,
+            JacobianReuse::Steps(steps) => *steps,
+            JacobianReuse::UntilFailure => usize::MAX,
+        }
+    }
+}
+
+impl Default for JacobianReuse {
+    fn default() -> Self {
+        JacobianReuse::Steps(MAX_REUSES)
+    }
+}
+
+impl fmt::Display for JacobianReuse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JacobianReuse::Never => write!(f, "Never"),
+            JacobianReuse::Steps(steps) => write!(f, "Steps({})", steps),
+            JacobianReuse::UntilFailure => write!(f, "UntilFailure"),
+        }
+    }
+}
+
+/// Settings of the newton iteration that solves the stage equations of implicit methods.
+///
+/// With the rate of convergence `θ = ‖Δ_k‖ / ‖Δ_{k-1}‖` of the updates `Δ_k`, the
+/// iteration converged once the estimated error `θ / (1 - θ) ‖Δ_k‖` is at most
+/// `kappa * tol`, and is given up as soon as it diverges, `θ >= 1`, or is too slow to
+/// converge within `maxiter` iterations, c.f. Hairer & Wanner, "Solving Ordinary
+/// Differential Equations II", 1996, Sec. IV.8.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewtonOptions {
+    /// Maximum number of iterations per step.
+    pub maxiter: usize,
+    /// Tolerance of the norm of the updates, relative to `max(1, |y|)` in the max norm
+    /// for the Runge–Kutta methods and in the norm of the integration tolerances for
+    /// [`OdeProblem::bdf`](crate::ode::problem::OdeProblem::bdf).
+    pub tol: f64,
+    /// Safety factor of the convergence test, values below one demand a more accurate
+    /// solution of the stage equations.
+    pub kappa: f64,
+    /// Fraction of the newton update taken in each iteration, one for a full step.
+    pub damping: f64,
+    /// When a new jacobian is evaluated.
+    pub reuse: JacobianReuse,
+    /// A new jacobian is also evaluated for the next step once the iteration of a step
+    /// converged with a rate `θ` above this, i.e. the reused jacobian slowed it down.
+    /// Hairer & Wanner suggest `0.001` for cheap and `0.1` for expensive jacobians.
+    pub max_rate: f64,
+    /// The factorized iteration matrices are reused over steps while the step size
+    /// differs by at most this fraction from the one they were factorized for, c.f.
+    /// Hindmarsh et al., "SUNDIALS: Suite of Nonlinear and Differential/Algebraic Equation
+    /// Solvers", 2005. Zero factorizes them for every new step size.
+    pub max_step_change: f64,
+}
+
+impl NewtonOptions {
+    /// The iteration of [`OdeProblem::bdf`](crate::ode::problem::OdeProblem::bdf) at the
+    /// relative tolerance `reltol` of the integration, following Shampine & Reichelt, "The
+    /// MATLAB ODE Suite", 1997.
+    pub fn bdf(reltol: f64) -> Self {
+        Self {
+            maxiter: bdf::NEWTON_MAXITER,
+            tol: (10. * f64::EPSILON / reltol).max(0.03f64.min(reltol.sqrt())),
+            reuse: JacobianReuse::UntilFailure,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for NewtonOptions {
+    fn default() -> Self {
+        Self {
+            maxiter: 20,
+            tol: 1e-10,
+            kappa: 1.,
+            damping: 1.,
+            reuse: JacobianReuse::default(),
+            max_rate: 0.1,
+            max_step_change: 0.3,
+        }
+    }
+}
+
+impl fmt::Display for NewtonOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "maxiter={}, tol={}, kappa={}, damping={}, reuse={}, max_rate={}, max_step_change={}",
+            self.maxiter,
+            self.tol,
+            self.kappa,
+            self.damping,
+            self.reuse,
+            self.max_rate,
+            self.max_step_change
+        )
+    }
+}
+
+/// The converged solution of a [`NewtonSolver`].
+#[derive(Debug, Clone)]
+pub struct NewtonResult<T: RealField> {
+    pub x: DVector<T>,
+    /// the number of iterations it took
+    pub iterations: usize,
+    /// the last estimate of the rate of convergence `θ`, zero if the first iteration
+    /// was accepted
+    pub rate: f64,
+}
+
+/// A simplified newton iteration `x_{k+1} = x_k + λ Δ_k` with the update `Δ_k` solved
+/// with the [`LinearSolver`] `lu` of the iteration matrix, which is kept for all
+/// iterations.
+#[derive(Debug, Clone)]
+pub struct NewtonSolver<'a, L> {
+    opts: &'a NewtonOptions,
+    lu: &'a L,
+    /// estimate of `θ / (1 - θ)` for the first iteration
+    eta: f64,
+}
+
+impl<'a, L> NewtonSolver<'a, L> {
+    pub fn new(opts: &'a NewtonOptions, lu: &'a L) -> Self {
+        Self {
+            opts,
+            lu,
+            eta: f64::INFINITY,
+        }
+    }
+
+    /// Accepts the first iteration if `eta ‖Δ_0‖ <= kappa * tol`. Since the rate of
+    /// convergence is not known yet, the first iteration is otherwise only accepted if
+    /// the update vanishes.
+    pub fn first_estimate(mut self, eta: f64) -> Self {
+        self.eta = eta;
+        self
+    }
+
+    /// Iterates from `x` until the norm of the updates converged to `tol`.
+    ///
+    /// `residual(x)` is the right hand side of the linear system of the update, i.e. the
+    /// negated residual of the equations, or `None` if it can't be evaluated at `x`.
+    /// Returns `None` if the iteration failed to converge, the solved linear systems are
+    /// counted in `stats`.
+    pub fn solve<T, R, N>(
+        &self,
+        mut x: DVector<T>,
+        tol: f64,
+        mut residual: R,
+        norm: N,
+        stats: &Cell<Diagnostics>,
+    ) -> Result<Option<NewtonResult<T>>, OdeError>
+    where
+        T: RealField,
+        L: LinearSolver<T>,
+        R: FnMut(&DVector<T>) -> Option<DVector<T>>,
+        N: Fn(&DVector<T>) -> f64,
+    {
+        let opts = self.opts;
+        let tol = opts.kappa * tol;
+        let mut eta = self.eta;
+        let mut rate = 0.;
+        let mut dx_norm_old: Option<f64> = None;
+
+        for k in 0..opts.maxiter {
+            let g = match residual(&x) {
+                Some(g) => g,
+                None => break,
+            };
+            let dx = self.lu.solve(&g).ok_or(OdeError::InvalidMatrix)?;
+            count(stats, |stats| stats.num_linsolve += 1);
+            let dx_norm = norm(&dx);
+
+            if let Some(old) = dx_norm_old {
+                rate = dx_norm / old;
+                // diverging or too slow to converge within the remaining iterations
+                if rate >= 1. || rate.powi((opts.maxiter - k) as i32) / (1. - rate) * dx_norm > tol
+                {
+                    break;
+                }
+                eta = rate / (1. - rate);
+            }
+
+            x += dx * na::convert::<f64, T>(opts.damping);
+
+            if dx_norm == 0. || eta * dx_norm <= tol {
+                return Ok(Some(NewtonResult {
+                    x,
+                    iterations: k + 1,
+                    rate,
+                }));
+            }
+            dx_norm_old = Some(dx_norm);
+        }
+        Ok(None)
+    }
+}
+
+/// The factorized iteration matrices the newton iterations of an implicit method reuse
+/// over steps, together with the rate of convergence of the iterations of a step.
+///
+/// A factorization is kept until [`NewtonCache::clear`], i.e. until the jacobian is
+/// re-evaluated, or until the step size changed by more than
+/// [`NewtonOptions::max_step_change`]. The iterations then solve with the iteration
+/// matrix of the old step size, which only slows down their convergence.
+#[derive(Debug, Clone)]
+pub struct NewtonCache<S> {
+    max_step_change: f64,
+    /// the step size of the factorizations
+    dt: Option<f64>,
+    /// the factorizations by their key, c.f. [`NewtonCache::get_or_factor`]
+    solvers: Vec<(f64, S)>,
+    /// the largest rate of convergence since the last [`NewtonCache::take_rate`]
+    rate: f64,
+}
+
+impl<S> NewtonCache<S> {
+    pub fn new(opts: &NewtonOptions) -> Self {
+        Self {
+            max_step_change: opts.max_step_change,
+            dt: None,
+            solvers: Vec::new(),
+            rate: 0.,
+        }
+    }
+
+    /// Forgets the factorizations, which are outdated once the jacobian changed.
+    pub fn clear(&mut self) {
+        self.dt = None;
+        self.solvers.clear();
+    }
+
+    /// The step size to factorize the iteration matrices of a step of size `dt` for,
+    /// that of the cached factorizations if `dt` is close enough to it. Otherwise the
+    /// cache is cleared and `dt` is returned.
+    pub fn step(&mut self, dt: f64) -> f64 {
+        match self.dt {
+            Some(cached) if (dt / cached - 1.).abs() <= self.max_step_change => cached,
+            _ => {
+                self.clear();
+                self.dt = Some(dt);
+                dt
+            }
+        }
+    }
+
+    /// Whether the factorizations are for another step size than `dt`.
+    pub fn is_stale(&self, dt: f64) -> bool {
+        self.dt.is_some_and(|cached| cached != dt)
+    }
+
+    /// The factorization with the `key`, e.g. the diagonal coefficient of the stage it
+    /// solves, which is only factorized with `factor` if it isn't cached.
+    pub fn get_or_factor<F>(
+        &mut self,
+        key: f64,
+        factor: F,
+        stats: &Cell<Diagnostics>,
+    ) -> Result<&S, OdeError>
+    where
+        F: FnOnce() -> Result<S, OdeError>,
+    {
+        let idx = match self.solvers.iter().position(|(k, _)| *k == key) {
+            Some(idx) => idx,
+            None => {
+                self.solvers.push((key, factor()?));
+                count(stats, |stats| stats.num_decomp += 1);
+                self.solvers.len() - 1
+            }
+        };
+        Ok(&self.solvers[idx].1)
+    }
+
+    /// Records the rate of convergence of a converged iteration.
+    pub fn converged(&mut self, rate: f64) {
+        self.rate = self.rate.max(rate);
+    }
+
+    /// The largest rate of convergence of the iterations since the last call.
+    pub fn take_rate(&mut self) -> f64 {
+        std::mem::take(&mut self.rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::linsolve::Factorization;
+    use na::DMatrix;
+
+    #[test]
+    fn newton() {
+        // `x^2 = 2` with the derivative at the initial guess
+        let lu = Factorization::Dense(DMatrix::from_element(1, 1, 2. * 1.5).lu());
+        let residual = |x: &DVector<f64>| Some(x.map(|xi| 2. - xi * xi));
+        let norm = |dx: &DVector<f64>| dx.amax();
+        let stats = Cell::new(Diagnostics::default());
+        let x0 = DVector::from_element(1, 1.5);
+
+        let opts = NewtonOptions::default();
+        let solver = NewtonSolver::new(&opts, &lu);
+        let res = solver
+            .solve(x0.clone(), 1e-12, residual, norm, &stats)
+            .unwrap()
+            .unwrap();
+        assert!((res.x[0] - 2f64.sqrt()).abs() < 1e-12);
+        assert_eq!(res.iterations as u32, stats.get().num_linsolve);
+
+        // a loose safety factor accepts the iterate earlier
+        let loose = NewtonOptions {
+            kappa: 1e3,
+            ..Default::default()
+        };
+        let early = NewtonSolver::new(&loose, &lu)
+            .solve(x0.clone(), 1e-12, residual, norm, &stats)
+            .unwrap()
+            .unwrap();
+        assert!(early.iterations < res.iterations);
+
+        // too few iterations
+        let short = NewtonOptions {
+            maxiter: 2,
+            ..Default::default()
+        };
+        let solver = NewtonSolver::new(&short, &lu);
+        assert!(solver
+            .solve(x0.clone(), 1e-12, residual, norm, &stats)
+            .unwrap()
+            .is_none());
+
+        // a jacobian of the wrong sign diverges
+        let wrong = Factorization::Dense(DMatrix::from_element(1, 1, -3.).lu());
+        let solver = NewtonSolver::new(&opts, &wrong);
+        assert!(solver
+            .solve(x0, 1e-12, residual, norm, &stats)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,181 @@
+use alga::general::RealField;
+use na::DVector;
+use std::ops::{Add, Mul};
+
+/// Highest order of the Adams–Moulton methods in Nordsieck form.
+pub const MAX_ORDER: usize = 12;
+
+/// Maximum number of corrector iterations per step.
+pub const MAX_CORRECTOR_ITER: usize = 3;
+
+/// Largest factor the step size is increased by.
+pub const MAX_FACTOR: f64 = 10.;
+
+/// Coefficients of the Adams–Moulton methods of order `1..=MAX_ORDER` in Nordsieck form,
+/// c.f. Hindmarsh, "ODEPACK, A Systematized Collection of ODE Solvers", 1983.
+///
+/// Both vectors are indexed by the order, index `0` is unused.
+#[derive(Clone, Debug)]
+pub struct NordsieckCoeffs {
+    /// the vectors `l` of length `q + 1` that distribute the correction of a step of
+    /// order `q` over the Nordsieck array, `l_1 = 1`
+    pub el: Vec<Vec<f64>>,
+    /// test coefficients of the local error at the orders `q - 1`, `q` and `q + 1`
+    pub tesco: Vec<[f64; 3]>,
+}
+
+impl Default for NordsieckCoeffs {
+    fn default() -> Self {
+        let mut el = vec![vec![], vec![1., 1.]];
+        let mut tesco = vec![[0.; 3]; MAX_ORDER + 1];
+        tesco[1] = [0., 2., 0.];
+        tesco[2][0] = 1.;
+
+        // coefficients of `p(x) = Π_{i=1}^{q-1} (x + i)`
+        let mut pc = [0.; MAX_ORDER];
+        pc[0] = 1.;
+        let mut rqfac = 1.;
+        for q in 2..=MAX_ORDER {
+            let rq1fac = rqfac;
+            rqfac /= q as f64;
+            let qm1 = (q - 1) as f64;
+            for i in (1..q).rev() {
+                pc[i] = pc[i - 1] + qm1 * pc[i];
+            }
+            pc[0] *= qm1;
+
+            // integrals of `p(x)` and `x p(x)` from -1 to 0
+            let mut pint = pc[0];
+            let mut xpin = pc[0] / 2.;
+            let mut sign = 1.;
+            for (i, p) in pc.iter().enumerate().take(q).skip(1) {
+                sign = -sign;
+                pint += sign * p / (i + 1) as f64;
+                xpin += sign * p / (i + 2) as f64;
+            }
+
+            let mut l = vec![pint * rq1fac, 1.];
+            l.extend((2..=q).map(|i| rq1fac * pc[i - 1] / i as f64));
+            el.push(l);
+
+            let ragq = 1. / (rqfac * xpin);
+            tesco[q][1] = ragq;
+            if q < MAX_ORDER {
+                tesco[q + 1][0] = ragq * rqfac / (q + 1) as f64;
+            }
+            tesco[q - 1][2] = ragq;
+        }
+
+        Self { el, tesco }
+    }
+}
+
+/// The Nordsieck array `z_j = h^j y^{(j)}(t_n) / j!` of the scaled derivatives of the
+/// solution at `t_n` for the step size `h`.
+///
+/// Changing the step size only rescales the columns, the prediction of the next step is
+/// the Taylor expansion of the array.
+#[derive(Clone, Debug)]
+pub struct NordsieckHistory<T: RealField> {
+    pub z: Vec<DVector<T>>,
+}
+
+impl<T> NordsieckHistory<T>
+where
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+{
+    /// Starts the history at `y0` with the scaled derivative `h y0'`.
+    pub fn new(y0: DVector<T>, hdy0: DVector<T>) -> Self {
+        let mut z = vec![DVector::zeros(y0.len()); MAX_ORDER + 1];
+        z[0] = y0;
+        z[1] = hdy0;
+        Self { z }
+    }
+
+    /// Rescales the array of order `order` to the step size `factor * h`.
+    pub fn rescale(&mut self, order: usize, factor: f64) {
+        let mut r = 1.;
+        for zj in self.z[1..=order].iter_mut() {
+            r *= factor;
+            *zj *= T::one() * r;
+        }
+    }
+
+    /// Advances the array of order `order` to the next point by the Taylor expansion
+    /// `z ← z P` with the pascal triangle matrix `P`.
+    pub fn predict(&mut self, order: usize) {
+        for k in 0..order {
+            for i in ((k + 1)..=order).rev() {
+                let zi = &self.z[i - 1] + &self.z[i];
+                self.z[i - 1] = zi;
+            }
+        }
+    }
+
+    /// Reverts a [`predict`](Self::predict) of a rejected step.
+    pub fn retract(&mut self, order: usize) {
+        for k in (0..order).rev() {
+            for i in (k + 1)..=order {
+                let zi = &self.z[i - 1] - &self.z[i];
+                self.z[i - 1] = zi;
+            }
+        }
+    }
+
+    /// Adds the correction `l_j e` of a step to the predicted array.
+    pub fn correct(&mut self, el: &[f64], e: &DVector<T>) {
+        for (zj, lj) in self.z.iter_mut().zip(el) {
+            *zj += e * (T::one() * *lj);
+        }
+    }
+
+    /// Evaluates the polynomial of degree `order` of the array at `t`, the array
+    /// belongs to the point `tn` and the step size `h`.
+    pub fn interpolate(&self, order: usize, tn: f64, h: f64, t: f64) -> DVector<T> {
+        let s = (t - tn) / h;
+        self.z[..order]
+            .iter()
+            .rev()
+            .fold(self.z[order].clone(), |acc, zj| acc * (T::one() * s) + zj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nordsieck_coeffs() {
+        let coeffs = NordsieckCoeffs::default();
+        // trapezoidal rule and the Adams–Moulton method of order 3
+        assert_eq!(coeffs.el[2], vec![0.5, 1., 0.5]);
+        let expected = [5. / 12., 1., 3. / 4., 1. / 6.];
+        for (l, e) in coeffs.el[3].iter().zip(expected.iter()) {
+            assert!((l - e).abs() < 1e-15);
+        }
+        assert!((coeffs.tesco[2][1] - 12.).abs() < 1e-12);
+        assert!(coeffs.el.iter().skip(1).all(|l| l[1] == 1.));
+    }
+
+    #[test]
+    fn predict_retract() {
+        // `y = t^3` at `t = 1` with step size `0.1`
+        let h = 0.1;
+        let mut history = NordsieckHistory::new(DVector::from_element(1, 1.), DVector::zeros(1));
+        history.z[1][0] = 3. * h;
+        history.z[2][0] = 3. * h * h;
+        history.z[3][0] = h * h * h;
+
+        history.predict(3);
+        assert!((history.z[0][0] - 1.331).abs() < 1e-12);
+        assert!((history.z[1][0] - 3. * 1.21 * h).abs() < 1e-12);
+        assert!((history.interpolate(3, 1.1, h, 1.05)[0] - 1.05f64.powi(3)).abs() < 1e-12);
+
+        history.retract(3);
+        assert!((history.z[0][0] - 1.).abs() < 1e-12);
+        assert!((history.z[2][0] - 3. * h * h).abs() < 1e-12);
+
+        history.rescale(3, 0.5);
+        assert!((history.interpolate(3, 1., h / 2., 0.9)[0] - 0.729).abs() < 1e-12);
+    }
+}
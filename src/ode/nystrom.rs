@@ -0,0 +1,273 @@
+use crate::error::OdeError;
+use crate::ode::implicit::ImplicitCoeffs;
+use crate::ode::solution::OdeSolution;
+use na::{DMatrix, DVector};
+
+/// The coefficients of an explicit Runge–Kutta–Nyström method for `y'' = f(t, y, y')`
+///
+/// ```latex
+/// k_i = f(t + c_i h, y + c_i h y' + h^2 Σ_j ā_ij k_j, y' + h Σ_j a_ij k_j)
+/// y_1 = y + h y' + h^2 Σ_i b̄_i k_i
+/// y'_1 = y' + h Σ_i b_i k_i
+/// ```
+#[derive(Clone, Debug)]
+pub struct NystromCoeffs {
+    /// rk matrix of the derivative
+    pub a: DMatrix<f64>,
+    /// rk matrix of the solution
+    pub abar: DMatrix<f64>,
+    /// weights of the derivative
+    pub b: Vec<f64>,
+    /// weights of the solution
+    pub bbar: Vec<f64>,
+    /// nodes
+    pub c: Vec<f64>,
+}
+
+impl NystromCoeffs {
+    /// The Nyström form of an explicit Runge–Kutta method applied to the first order
+    /// system `(y, y')' = (y', f)`, with `ā = A²` and `b̄ = b A`.
+    pub fn from_explicit(coeffs: &ImplicitCoeffs) -> Self {
+        let a = coeffs.a.clone();
+        let b = DVector::from_row_slice(&coeffs.b);
+        Self {
+            abar: &a * &a,
+            bbar: (a.transpose() * b).as_slice().to_vec(),
+            a,
+            b: coeffs.b.clone(),
+            c: coeffs.c.clone(),
+        }
+    }
+
+    /// The classical Runge–Kutta–Nyström method of order 4.
+    pub fn rkn4() -> Self {
+        #[rustfmt::skip]
+        let a = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            0.5, 0., 0., 0.,
+            0., 0.5, 0., 0.,
+            0., 0., 1., 0.,
+        ]);
+        #[rustfmt::skip]
+        let abar = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            0., 0., 0., 0.,
+            0.25, 0., 0., 0.,
+            0., 0.5, 0., 0.,
+        ]);
+        Self {
+            a,
+            abar,
+            b: vec![1. / 6., 1. / 3., 1. / 3., 1. / 6.],
+            bbar: vec![1. / 6., 1. / 6., 1. / 6., 0.],
+            c: vec![0., 0.5, 0.5, 1.],
+        }
+    }
+
+    /// the number of stages
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.c.len()
+    }
+}
+
+/// The second order problem `y'' = f(t, y, y')`, integrated directly by Runge–Kutta–Nyström
+/// methods without doubling the dimension of the state.
+///
+/// F: the RHS, a function of t, y(t) and y'(t).
+///
+/// Solutions hold the state `[y; y']`.
+#[derive(Debug, Clone)]
+pub struct NystromProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// The RHS of the ODE `y'' = f(t, y, y')`.
+    f: F,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Initial derivative.
+    dy0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NystromBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    f: Option<F>,
+    y0: Option<DVector<f64>>,
+    dy0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F> Default for NystromBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            f: None,
+            y0: None,
+            dy0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F> NystromBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// set the problem function
+    pub fn fun(mut self, f: F) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// set the initial value and derivative
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T, dy0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self.dy0 = Some(dy0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`NystromProblem`].
+    ///
+    /// Returns an error if a field is None or the initial value and derivative differ in
+    /// length.
+    pub fn build(self) -> Result<NystromProblem<F>, OdeError> {
+        let f = self
+            .f
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let dy0 = self
+            .dy0
+            .ok_or_else(|| OdeError::uninitialized("Initial derivative must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        if y0.len() != dy0.len() {
+            return Err(OdeError::DimensionMismatch {
+                expected: y0.shape(),
+                found: dy0.shape(),
+            });
+        }
+
+        Ok(NystromProblem { f, y0, dy0, tspan })
+    }
+}
+
+impl<F> NystromProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `NystromBuilder::default()`
+    pub fn builder() -> NystromBuilder<F> {
+        NystromBuilder::default()
+    }
+
+    /// Solve the problem using the classical Runge–Kutta–Nyström method of order 4 at the
+    /// fixed steps of `tspan`.
+    pub fn rkn4(&self) -> OdeSolution<f64, DVector<f64>> {
+        self.nystrom(&NystromCoeffs::rkn4())
+    }
+
+    /// Solve the problem using an explicit Runge–Kutta–Nyström method at the fixed steps
+    /// of `tspan`.
+    pub fn nystrom(&self, coeffs: &NystromCoeffs) -> OdeSolution<f64, DVector<f64>> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return OdeSolution::default();
+        }
+
+        let mut y = self.y0.clone();
+        let mut dy = self.dy0.clone();
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        yout.push(Self::state(&y, &dy));
+
+        let s = coeffs.nstages();
+        for w in self.tspan.windows(2) {
+            let (t, h) = (w[0], w[1] - w[0]);
+            let mut ks: Vec<DVector<f64>> = Vec::with_capacity(s);
+            for i in 0..s {
+                let mut yi = &y + &dy * (coeffs.c[i] * h);
+                let mut dyi = dy.clone();
+                for (j, k) in ks.iter().enumerate() {
+                    yi += k * (coeffs.abar[(i, j)] * h * h);
+                    dyi += k * (coeffs.a[(i, j)] * h);
+                }
+                ks.push((self.f)(t + coeffs.c[i] * h, &yi, &dyi));
+            }
+
+            y += &dy * h;
+            for (i, k) in ks.iter().enumerate() {
+                y += k * (coeffs.bbar[i] * h * h);
+                dy += k * (coeffs.b[i] * h);
+            }
+            yout.push(Self::state(&y, &dy));
+        }
+
+        OdeSolution::new(self.tspan.clone(), yout)
+    }
+
+    /// The state `[y; y']`.
+    fn state(y: &DVector<f64>, dy: &DVector<f64>) -> DVector<f64> {
+        DVector::from_iterator(y.len() + dy.len(), y.iter().chain(dy.iter()).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::runge_kutta::ButcherTableau;
+
+    #[test]
+    fn rkn4_from_rk4() {
+        let rkn4 = NystromCoeffs::rkn4();
+        let from_rk4 =
+            NystromCoeffs::from_explicit(&ImplicitCoeffs::from_tableau(&ButcherTableau::rk4()));
+        assert_eq!(rkn4.a, from_rk4.a);
+        assert_eq!(rkn4.abar, from_rk4.abar);
+        assert_eq!(rkn4.b, from_rk4.b);
+        assert_eq!(rkn4.bbar, from_rk4.bbar);
+        assert_eq!(rkn4.c, from_rk4.c);
+    }
+
+    #[test]
+    fn rkn4_damped_oscillator() {
+        // `y'' = -y - 2ζ y'` with `y(0) = 1, y'(0) = 0`
+        let zeta: f64 = 0.1;
+        let omega = (1. - zeta * zeta).sqrt();
+        let exact =
+            |t: f64| (-zeta * t).exp() * ((omega * t).cos() + zeta / omega * (omega * t).sin());
+        let error = |steps: usize| {
+            let problem = NystromProblem::builder()
+                .fun(|_t, y: &DVector<f64>, dy: &DVector<f64>| -y - dy * (2. * zeta))
+                .init(DVector::from_element(1, 1.), DVector::from_element(1, 0.))
+                .tspan_linspace(0., 10., steps + 1)
+                .build()
+                .unwrap();
+            (problem.rkn4().yout.last().unwrap()[0] - exact(10.)).abs()
+        };
+
+        assert!((error(100) / error(200) - 16.).abs() < 1.);
+    }
+}
@@ -1,8 +1,17 @@
-use crate::ode::types::PNorm;
+use crate::error::OdeError;
+use crate::ode::controller::ControllerType;
+use crate::ode::stiffness::StiffnessAction;
+use crate::ode::types::OdeType;
+#[cfg(feature = "serde0")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::str::FromStr;
 
+#[derive(Clone, Default)]
 pub struct OdeOptionMap {
     inner: HashMap<&'static str, OdeOption>,
 }
@@ -21,33 +30,121 @@ impl DerefMut for OdeOptionMap {
     }
 }
 
-impl Default for OdeOptionMap {
-    fn default() -> Self {
-        Self {
-            inner: HashMap::new(),
+impl OdeOptionMap {
+    /// Sets `op` under its name, replacing the previous value.
+    pub fn set<O: Into<OdeOption>>(&mut self, op: O) -> Option<OdeOption> {
+        let op = op.into();
+        self.inner.insert(op.name(), op)
+    }
+}
+
+/// Formats the options as `Name=value` separated by commas, ordered by their names.
+impl fmt::Display for OdeOptionMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut ops: Vec<_> = self.inner.values().collect();
+        ops.sort_by_key(|op| op.name());
+        fmt_comma_delimited(f, &ops)
+    }
+}
+
+/// Parses options formatted like `"Reltol=1e-6, Points=All, Tstops=1, 2"`, the values
+/// of list options and per component tolerances are separated by commas as well.
+impl FromStr for OdeOptionMap {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries: Vec<String> = Vec::new();
+        for part in s.split(',').filter(|part| !part.trim().is_empty()) {
+            match entries.last_mut() {
+                // another value of the previous option
+                Some(entry) if !part.contains('=') => {
+                    entry.push(',');
+                    entry.push_str(part);
+                }
+                _ => entries.push(part.to_string()),
+            }
         }
+        let mut ops = OdeOptionMap::default();
+        for entry in entries {
+            ops.set(entry.parse::<OdeOption>()?);
+        }
+        Ok(ops)
+    }
+}
+
+/// Serializes the options as a sequence ordered by their names.
+#[cfg(feature = "serde0")]
+impl Serialize for OdeOptionMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut ops: Vec<_> = self.inner.values().collect();
+        ops.sort_by_key(|op| op.name());
+        serializer.collect_seq(ops)
     }
 }
 
+#[cfg(feature = "serde0")]
+impl<'de> Deserialize<'de> for OdeOptionMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut ops = OdeOptionMap::default();
+        for op in Vec::<OdeOption>::deserialize(deserializer)? {
+            ops.set(op);
+        }
+        Ok(ops)
+    }
+}
+
+/// The option `$id` of the map, `Ok(None)` if it is not set and an error if the entry
+/// under its name holds another option.
 macro_rules! option_val {
     ($ops:ident rm $id:ident) => {
-        $ops.remove($id::option_name()).and_then(|op| {
-            if let OdeOption::$id(el) = op {
-                Some(el)
-            } else {
-                None
-            }
-        })
+        match $ops.remove($id::option_name()) {
+            None => Ok(None),
+            Some(OdeOption::$id(el)) => Ok(Some(el)),
+            Some(other) => Err(OdeError::OptionMismatch {
+                name: $id::option_name(),
+                found: other.name(),
+            }),
+        }
     };
 
     ($ops:ident get $id:ident) => {
-        $ops.get($id::option_name()).and_then(|op| {
-            if let OdeOption::$id(el) = op {
-                Some(el.clone())
-            } else {
-                None
-            }
-        })
+        match $ops.get($id::option_name()) {
+            None => Ok(None),
+            Some(OdeOption::$id(el)) => Ok(Some(el.clone())),
+            Some(other) => Err(OdeError::OptionMismatch {
+                name: $id::option_name(),
+                found: other.name(),
+            }),
+        }
+    };
+}
+
+/// The [`AdaptiveOptions`] of the map, with the defaults for the missing options.
+macro_rules! adaptive_options {
+    ($ops:ident $mode:ident) => {
+        AdaptiveOptions {
+            minstep: option_val!($ops $mode Minstep)?,
+            maxstep: option_val!($ops $mode Maxstep)?,
+            initstep: option_val!($ops $mode Initstep)?.unwrap_or_default(),
+            points: option_val!($ops $mode Points)?.unwrap_or_default(),
+            reltol: option_val!($ops $mode Reltol)?.unwrap_or_default(),
+            abstol: option_val!($ops $mode Abstol)?.unwrap_or_default(),
+            step_timeout: option_val!($ops $mode StepTimeout)?.unwrap_or_default(),
+            max_order: option_val!($ops $mode MaxOrder)?,
+            dense: option_val!($ops $mode Dense)?.unwrap_or_default(),
+            interp_order: option_val!($ops $mode InterpOrder)?,
+            tstops: option_val!($ops $mode Tstops)?.unwrap_or_default(),
+            saveat: option_val!($ops $mode Saveat)?.unwrap_or_default(),
+            beta1: option_val!($ops $mode Beta1)?,
+            beta2: option_val!($ops $mode Beta2)?,
+            safety: option_val!($ops $mode Safety)?.unwrap_or_default(),
+            qmin: option_val!($ops $mode Qmin)?.unwrap_or_default(),
+            qmax: option_val!($ops $mode Qmax)?.unwrap_or_default(),
+            controller: option_val!($ops $mode Controller)?.unwrap_or_default(),
+            maxiters: option_val!($ops $mode MaxIters)?.unwrap_or_default(),
+            stiffness: option_val!($ops $mode Stiffness)?.unwrap_or_default(),
+            force_dtmin: option_val!($ops $mode ForceDtmin)?.unwrap_or_default(),
+        }
     };
 }
 
@@ -62,18 +159,72 @@ pub struct AdaptiveOptions {
     pub initstep: Initstep,
     /// Defaults to [`Points::All`] output is given for each value in tspan
     /// as well as for each intermediate point the solver used.
+    ///
+    /// [`Points::Last`] and [`Points::Every`] are respected by the ODE and DAE solvers,
+    /// the stochastic and delay solvers treat them like [`Points::Specified`].
     pub points: Points,
-    /// An integration step is accepted if `E <= reltol*abs(y)`
-    /// defaults to `1e-5`
+    /// An integration step is accepted if `E <= reltol*abs(y)`, either for all
+    /// components or per component, defaults to `1e-5`
     pub reltol: Reltol,
-    /// An integration step is accepted if `E <= abstol`
-    /// defaults to `1e-8`
+    /// An integration step is accepted if `E <= abstol`, either for all components or
+    /// per component, defaults to `1e-8`
     pub abstol: Abstol,
-    /// User-supplied norm for determining the error.
-    pub norm: Norm,
     /// User defined timeout after which step reduction should not
     /// increase step for timeout controlled steps.
     pub step_timeout: StepTimeout,
+    /// Highest order of variable order methods, defaults to the highest order
+    /// the method supports.
+    pub max_order: Option<MaxOrder>,
+    /// Whether the continuous output of every step is stored in the solution,
+    /// defaults to `false`.
+    pub dense: Dense,
+    /// Highest degree of the stored continuous output, a continuous extension of a
+    /// higher degree is replaced by the cubic hermite interpolation, or the linear one
+    /// below degree 3, which needs less memory per step. Defaults to the continuous
+    /// extension of the method.
+    pub interp_order: Option<InterpOrder>,
+    /// Times the steps must end at exactly, like discontinuities of the problem,
+    /// defaults to none.
+    pub tstops: Tstops,
+    /// Times at which the solution is stored, interpolated with the continuous output
+    /// of the method without restricting the steps. If set, `tspan` only provides the
+    /// start and end of the integration and `points` is ignored, defaults to none.
+    ///
+    /// Only respected by the solvers driven by an
+    /// [`OdeIntegrator`](crate::ode::integrator::OdeIntegrator) and
+    /// [`OdeProblem::dop853`](crate::ode::problem::OdeProblem::dop853).
+    pub saveat: Saveat,
+    /// Exponent of the current error in the PI step size controller, defaults to
+    /// `0.7 / (order + 1)`.
+    pub beta1: Option<Beta1>,
+    /// Exponent of the previous error in the PI step size controller, defaults to
+    /// `0.4 / (order + 1)`.
+    pub beta2: Option<Beta2>,
+    /// Safety factor of the new step size, defaults to `0.9`.
+    pub safety: Safety,
+    /// Smallest factor by which the step size changes, defaults to `0.2`.
+    pub qmin: Qmin,
+    /// Largest factor by which the step size changes, defaults to `10`.
+    pub qmax: Qmax,
+    /// The step size controller, defaults to [`ControllerType::Pi`].
+    pub controller: Controller,
+    /// Maximum number of steps, accepted or rejected, after which the integration is
+    /// aborted with [`ReturnCode::MaxIters`](crate::ode::solution::ReturnCode::MaxIters),
+    /// defaults to `100_000`.
+    pub maxiters: MaxIters,
+    /// What the explicit Runge–Kutta solvers do once the problem appears stiff, defaults
+    /// to [`StiffnessAction::Flag`].
+    pub stiffness: Stiffness,
+    /// Whether a step the error control would shrink below `minstep` is taken with
+    /// `minstep` regardless of its error, instead of aborting the integration with
+    /// [`ReturnCode::DtLessThanMin`](crate::ode::solution::ReturnCode::DtLessThanMin),
+    /// defaults to `false`.
+    ///
+    /// Only respected by the explicit Runge–Kutta solvers driven by an
+    /// [`OdeIntegrator`](crate::ode::integrator::OdeIntegrator),
+    /// [`OdeProblem::dop853`](crate::ode::problem::OdeProblem::dop853) and the implicit
+    /// one step methods.
+    pub force_dtmin: ForceDtmin,
 }
 
 impl AdaptiveOptions {
@@ -82,50 +233,451 @@ impl AdaptiveOptions {
     pub fn builder() -> AdaptiveOptionsBuilder {
         AdaptiveOptionsBuilder::default()
     }
-}
 
-impl From<OdeOptionMap> for AdaptiveOptions {
-    fn from(mut ops: OdeOptionMap) -> Self {
-        Self {
-            minstep: option_val!(ops rm Minstep),
-            maxstep: option_val!(ops rm Maxstep),
-            initstep: option_val!(ops rm Initstep).unwrap_or_default(),
-            points: option_val!(ops rm Points).unwrap_or_default(),
-            reltol: option_val!(ops rm Reltol).unwrap_or_default(),
-            abstol: option_val!(ops rm Abstol).unwrap_or_default(),
-            norm: option_val!(ops rm Norm).unwrap_or_default(),
-            step_timeout: option_val!(ops rm StepTimeout).unwrap_or_default(),
+    /// The relative and absolute tolerance for a state with `dof` components.
+    ///
+    /// Returns an error if the options are invalid for the state, c.f.
+    /// [`AdaptiveOptions::validate`].
+    pub fn tolerances(&self, dof: usize) -> Result<(Tolerance, Tolerance), OdeError> {
+        self.validate(dof)?;
+        Ok((self.reltol.0.clone(), self.abstol.0.clone()))
+    }
+
+    /// Checks the options for a state with `dof` components, which every adaptive solver
+    /// does before the first step.
+    ///
+    /// Returns [`OdeError::ToleranceLength`] if a tolerance is given per component for a
+    /// different number of components and [`OdeError::OptionOutOfRange`] for the first
+    /// option with a value the solvers can't work with, like a non-positive absolute
+    /// tolerance, a minimum step larger than the maximum step or controller gains that
+    /// don't shrink the step after a large error.
+    pub fn validate(&self, dof: usize) -> Result<(), OdeError> {
+        fn check<T: fmt::Display>(
+            name: &'static str,
+            value: T,
+            valid: bool,
+            expected: &'static str,
+        ) -> Result<(), OdeError> {
+            if valid {
+                Ok(())
+            } else {
+                Err(OdeError::OptionOutOfRange {
+                    name,
+                    value: value.to_string(),
+                    expected,
+                })
+            }
         }
+        self.reltol.check(dof)?;
+        self.abstol.check(dof)?;
+        for i in 0..dof.max(1) {
+            let (reltol, abstol) = (self.reltol.at(i), self.abstol.at(i));
+            check(
+                Reltol::option_name(),
+                &self.reltol,
+                reltol >= 0. && reltol.is_finite(),
+                "non-negative and finite",
+            )?;
+            check(
+                Abstol::option_name(),
+                &self.abstol,
+                abstol > 0. && abstol.is_finite(),
+                "positive and finite",
+            )?;
+        }
+
+        let minstep = self.minstep.as_ref().map_or(0., |step| step.0);
+        let maxstep = self.maxstep.as_ref().map_or(f64::INFINITY, |step| step.0);
+        check(
+            Minstep::option_name(),
+            minstep,
+            minstep >= 0. && minstep.is_finite(),
+            "non-negative and finite",
+        )?;
+        check(Maxstep::option_name(), maxstep, maxstep > 0., "positive")?;
+        check(
+            Minstep::option_name(),
+            minstep,
+            minstep <= maxstep,
+            "at most the Maxstep",
+        )?;
+        let initstep = self.initstep.0.abs();
+        check(
+            Initstep::option_name(),
+            self.initstep.0,
+            initstep == 0. || (minstep..=maxstep).contains(&initstep),
+            "zero or between the Minstep and the Maxstep",
+        )?;
+        if let Points::Every(k) = self.points {
+            check(
+                Points::option_name(),
+                &self.points,
+                k > 0,
+                "every k > 0 steps",
+            )?;
+        }
+        if let Some(interp_order) = &self.interp_order {
+            check(
+                InterpOrder::option_name(),
+                interp_order,
+                interp_order.0 > 0,
+                "positive",
+            )?;
+        }
+        if let Some(max_order) = &self.max_order {
+            check(
+                MaxOrder::option_name(),
+                max_order,
+                max_order.0 > 0,
+                "positive",
+            )?;
+        }
+        check(
+            Tstops::option_name(),
+            &self.tstops,
+            self.tstops.iter().all(|t| t.is_finite()),
+            "finite",
+        )?;
+        check(
+            Saveat::option_name(),
+            &self.saveat,
+            self.saveat.iter().all(|t| t.is_finite()),
+            "finite",
+        )?;
+
+        // the step size controllers
+        if let Some(beta1) = &self.beta1 {
+            check(
+                Beta1::option_name(),
+                beta1,
+                beta1.0 > 0. && beta1.0 <= 1.,
+                "in (0, 1]",
+            )?;
+        }
+        if let Some(beta2) = &self.beta2 {
+            check(
+                Beta2::option_name(),
+                beta2,
+                beta2.0 >= 0. && beta2.0 < 1.,
+                "in [0, 1)",
+            )?;
+        }
+        check(
+            Safety::option_name(),
+            &self.safety,
+            self.safety.0 > 0. && self.safety.0 <= 1.,
+            "in (0, 1]",
+        )?;
+        check(
+            Qmin::option_name(),
+            &self.qmin,
+            self.qmin.0 > 0. && self.qmin.0 <= 1.,
+            "in (0, 1]",
+        )?;
+        check(
+            Qmax::option_name(),
+            &self.qmax,
+            self.qmax.0 >= 1. && self.qmax.0.is_finite(),
+            "at least 1 and finite",
+        )
+    }
+}
+
+impl AdaptiveOptions {
+    /// The options of the map, with the defaults for the missing ones.
+    ///
+    /// Returns [`OdeError::OptionMismatch`] if an entry holds another option than the
+    /// one its name refers to.
+    pub fn from_map(ops: &OdeOptionMap) -> Result<Self, OdeError> {
+        Ok(adaptive_options!(ops get))
+    }
+
+    /// Like [`AdaptiveOptions::from_map`], but moves the options out of the map.
+    pub fn from_map_owned(mut ops: OdeOptionMap) -> Result<Self, OdeError> {
+        Ok(adaptive_options!(ops rm))
     }
 }
 
-impl From<&OdeOptionMap> for AdaptiveOptions {
-    fn from(ops: &OdeOptionMap) -> Self {
-        Self {
-            minstep: option_val!(ops get Minstep),
-            maxstep: option_val!(ops get Maxstep),
-            initstep: option_val!(ops get Initstep).unwrap_or_default(),
-            points: option_val!(ops get Points).unwrap_or_default(),
-            reltol: option_val!(ops get Reltol).unwrap_or_default(),
-            abstol: option_val!(ops get Abstol).unwrap_or_default(),
-            norm: option_val!(ops get Norm).unwrap_or_default(),
-            step_timeout: option_val!(ops get StepTimeout).unwrap_or_default(),
+/// c.f. [`AdaptiveOptions::from_map_owned`]
+impl TryFrom<OdeOptionMap> for AdaptiveOptions {
+    type Error = OdeError;
+
+    fn try_from(ops: OdeOptionMap) -> Result<Self, Self::Error> {
+        Self::from_map_owned(ops)
+    }
+}
+
+/// c.f. [`AdaptiveOptions::from_map`]
+impl TryFrom<&OdeOptionMap> for AdaptiveOptions {
+    type Error = OdeError;
+
+    fn try_from(ops: &OdeOptionMap) -> Result<Self, Self::Error> {
+        Self::from_map(ops)
+    }
+}
+
+impl From<AdaptiveOptions> for OdeOptionMap {
+    fn from(opts: AdaptiveOptions) -> Self {
+        let mut ops = OdeOptionMap::default();
+        let options: Vec<OdeOption> = vec![
+            opts.initstep.into(),
+            opts.points.into(),
+            opts.reltol.into(),
+            opts.abstol.into(),
+            opts.step_timeout.into(),
+            opts.dense.into(),
+            opts.tstops.into(),
+            opts.saveat.into(),
+            opts.safety.into(),
+            opts.qmin.into(),
+            opts.qmax.into(),
+            opts.controller.into(),
+            opts.maxiters.into(),
+            opts.stiffness.into(),
+            opts.force_dtmin.into(),
+        ];
+        let optional: Vec<Option<OdeOption>> = vec![
+            opts.minstep.map(Into::into),
+            opts.maxstep.map(Into::into),
+            opts.max_order.map(Into::into),
+            opts.interp_order.map(Into::into),
+            opts.beta1.map(Into::into),
+            opts.beta2.map(Into::into),
+        ];
+        for op in options.into_iter().chain(optional.into_iter().flatten()) {
+            ops.set(op);
         }
+        ops
+    }
+}
+
+/// Options structs with the subset of the [`AdaptiveOptions`] a family of solvers
+/// honors, which convert into [`AdaptiveOptions`] with the defaults for the others.
+///
+/// The optional fields follow the `;`, their setters take the value itself. Maps of
+/// options convert with `from_map`, which rejects the options the solvers ignore.
+macro_rules! solver_options {
+    ($($(#[$a:meta])* $name:ident($builder:ident) {
+        $($field:ident: $ty:ident),*;
+        $($opt:ident: Option<$opt_ty:ident>),*
+    })*) => {
+        $(
+            $(#[$a])*
+            #[derive(Clone, Debug, Default, Builder)]
+            #[builder(setter(strip_option, into), default)]
+            pub struct $name {
+                $(
+                    #[doc = concat!("c.f. [`AdaptiveOptions::", stringify!($field), "`]")]
+                    pub $field: $ty,
+                )*
+                $(
+                    #[doc = concat!("c.f. [`AdaptiveOptions::", stringify!($opt), "`]")]
+                    pub $opt: Option<$opt_ty>,
+                )*
+            }
+
+            impl $name {
+                /// convenience method to create a new builder
+                #[inline]
+                pub fn builder() -> $builder {
+                    $builder::default()
+                }
+
+                /// The options of the map, with the defaults for the missing ones.
+                ///
+                /// Returns [`OdeError::UnhonoredOption`] if the map holds an option
+                /// these solvers ignore and [`OdeError::OptionMismatch`] if an entry
+                /// holds another option than the one its name refers to.
+                pub fn from_map(ops: &OdeOptionMap) -> Result<Self, OdeError> {
+                    let honored = [
+                        $($ty::option_name(),)*
+                        $($opt_ty::option_name(),)*
+                    ];
+                    if let Some(name) = ops.keys().find(|name| !honored.contains(name)) {
+                        return Err(OdeError::UnhonoredOption {
+                            name,
+                            options: stringify!($name),
+                        });
+                    }
+                    let opts = AdaptiveOptions::from_map(ops)?;
+                    Ok($name {
+                        $($field: opts.$field,)*
+                        $($opt: opts.$opt,)*
+                    })
+                }
+            }
+
+            impl TryFrom<&OdeOptionMap> for $name {
+                type Error = OdeError;
+
+                fn try_from(ops: &OdeOptionMap) -> Result<Self, Self::Error> {
+                    Self::from_map(ops)
+                }
+            }
+
+            impl TryFrom<OdeOptionMap> for $name {
+                type Error = OdeError;
+
+                fn try_from(ops: OdeOptionMap) -> Result<Self, Self::Error> {
+                    Self::from_map(&ops)
+                }
+            }
+
+            impl From<$name> for AdaptiveOptions {
+                fn from(opts: $name) -> Self {
+                    AdaptiveOptions {
+                        $($field: opts.$field,)*
+                        $($opt: opts.$opt,)*
+                        ..AdaptiveOptions::default()
+                    }
+                }
+            }
+
+            impl From<$name> for OdeOptionMap {
+                fn from(opts: $name) -> Self {
+                    let mut ops = OdeOptionMap::default();
+                    $(ops.set(opts.$field);)*
+                    $(if let Some(op) = opts.$opt {
+                        ops.set(op);
+                    })*
+                    ops
+                }
+            }
+        )*
+    };
+}
+
+solver_options! {
+    /// The options of the explicit Runge–Kutta methods driven by an
+    /// [`OdeIntegrator`](crate::ode::integrator::OdeIntegrator), like
+    /// [`OdeProblem::tsit5`](crate::ode::problem::OdeProblem::tsit5).
+    ExplicitRkOptions(ExplicitRkOptionsBuilder) {
+        reltol: Reltol,
+        abstol: Abstol,
+        initstep: Initstep,
+        points: Points,
+        saveat: Saveat,
+        dense: Dense,
+        tstops: Tstops,
+        maxiters: MaxIters,
+        stiffness: Stiffness,
+        controller: Controller,
+        safety: Safety,
+        qmin: Qmin,
+        qmax: Qmax,
+        force_dtmin: ForceDtmin;
+        minstep: Option<Minstep>,
+        maxstep: Option<Maxstep>,
+        interp_order: Option<InterpOrder>,
+        beta1: Option<Beta1>,
+        beta2: Option<Beta2>
+    }
+
+    /// The options of the implicit and Rosenbrock one step methods with continuous
+    /// output, like [`OdeProblem::radau5`](crate::ode::problem::OdeProblem::radau5), the
+    /// SDIRK methods, [`OdeProblem::rodas5`](crate::ode::problem::OdeProblem::rodas5),
+    /// [`OdeProblem::auto_tsit5`](crate::ode::problem::OdeProblem::auto_tsit5) and the
+    /// IMEX methods like [`ImexProblem::ark4`](crate::ode::imex::ImexProblem::ark4).
+    ImplicitOptions(ImplicitOptionsBuilder) {
+        reltol: Reltol,
+        abstol: Abstol,
+        initstep: Initstep,
+        points: Points,
+        dense: Dense,
+        maxiters: MaxIters,
+        force_dtmin: ForceDtmin;
+        minstep: Option<Minstep>,
+        maxstep: Option<Maxstep>,
+        interp_order: Option<InterpOrder>
+    }
+
+    /// The options of the variable order multistep methods
+    /// [`OdeProblem::bdf`](crate::ode::problem::OdeProblem::bdf),
+    /// [`OdeProblem::adams`](crate::ode::problem::OdeProblem::adams) and
+    /// [`DaeProblem::bdf`](crate::dae::problem::DaeProblem::bdf).
+    MultistepOptions(MultistepOptionsBuilder) {
+        reltol: Reltol,
+        abstol: Abstol,
+        initstep: Initstep,
+        points: Points,
+        maxiters: MaxIters;
+        minstep: Option<Minstep>,
+        maxstep: Option<Maxstep>,
+        max_order: Option<MaxOrder>
+    }
+
+    /// The options of [`TaylorProblem::taylor`](crate::ode::taylor::TaylorProblem::taylor).
+    TaylorOptions(TaylorOptionsBuilder) {
+        reltol: Reltol,
+        abstol: Abstol,
+        points: Points,
+        maxiters: MaxIters;
+        minstep: Option<Minstep>,
+        maxstep: Option<Maxstep>,
+        max_order: Option<MaxOrder>
+    }
+
+    /// The options of the solvers without continuous output,
+    /// [`OdeProblem::ode23s`](crate::ode::problem::OdeProblem::ode23s),
+    /// [`OdeProblem::odeabm`](crate::ode::problem::OdeProblem::odeabm),
+    /// [`OdeProblem::gbs`](crate::ode::problem::OdeProblem::gbs) and the adaptive solvers of
+    /// the SDE, RODE and DDE problems, like
+    /// [`SdeProblem::sri`](crate::sde::problem::SdeProblem::sri).
+    BasicOptions(BasicOptionsBuilder) {
+        reltol: Reltol,
+        abstol: Abstol,
+        initstep: Initstep,
+        points: Points,
+        maxiters: MaxIters;
+        minstep: Option<Minstep>,
+        maxstep: Option<Maxstep>
     }
 }
 
+/// The options of [`OdeProblem::dop853`](crate::ode::problem::OdeProblem::dop853) and
+/// [`JumpProblem::ode`](crate::jump::problem::JumpProblem::ode), DOP853 is driven by an
+/// [`OdeIntegrator`](crate::ode::integrator::OdeIntegrator) like the other explicit
+/// Runge–Kutta methods.
+pub type Dop853Options = ExplicitRkOptions;
+
 pub trait OdeOp {
     fn option_name() -> &'static str;
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum Points {
     /// output is given for each value in `tspan`,
     /// as well as for each intermediate point the solver used
+    #[default]
     All,
     /// output is given only for the supplied time stamps,
     /// without additional calculated time stamps
     Specified,
+    /// output is only given for the final state, which keeps the memory of long
+    /// integrations of large systems constant
+    Last,
+    /// output is given for the initial state, every `k`-th step the solver took and the
+    /// final state, ignoring the time stamps of `tspan`
+    Every(usize),
+}
+
+impl Points {
+    /// Whether the values at the time stamps of `tspan` are part of the output.
+    pub fn saves_tspan(&self) -> bool {
+        matches!(self, Points::All | Points::Specified)
+    }
+
+    /// Whether the `n`-th accepted step, counted from `1`, is part of the output.
+    ///
+    /// The final state is always stored, regardless of this.
+    pub fn saves_step(&self, n: usize) -> bool {
+        match self {
+            Points::All => true,
+            Points::Specified | Points::Last => false,
+            Points::Every(k) => *k > 0 && n.is_multiple_of(*k),
+        }
+    }
 }
 
 impl OdeOp for Points {
@@ -135,28 +687,164 @@ impl OdeOp for Points {
     }
 }
 
-impl Default for Points {
-    fn default() -> Self {
-        Points::All
-    }
-}
-
-impl Into<OdeOption> for Points {
-    fn into(self) -> OdeOption {
-        OdeOption::Points(self)
+impl From<Points> for OdeOption {
+    fn from(points: Points) -> Self {
+        OdeOption::Points(points)
     }
 }
 
 impl fmt::Display for Points {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Points: ")?;
         match self {
             Points::All => write!(f, "All"),
             Points::Specified => write!(f, "Specified"),
+            Points::Last => write!(f, "Last"),
+            Points::Every(k) => write!(f, "Every({})", k),
+        }
+    }
+}
+
+impl FromStr for Points {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "All" => Ok(Points::All),
+            "Specified" => Ok(Points::Specified),
+            "Last" => Ok(Points::Last),
+            s => s
+                .strip_prefix("Every(")
+                .and_then(|s| s.strip_suffix(')'))
+                .and_then(|k| k.trim().parse().ok())
+                .map(Points::Every)
+                .ok_or_else(|| {
+                    OdeError::invalid_option(s, "expected `All`, `Specified`, `Last` or `Every(k)`")
+                }),
+        }
+    }
+}
+
+/// A tolerance that is either shared by all components of the state or given for each
+/// component, for states that mix quantities of very different magnitudes.
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde0", serde(untagged))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tolerance {
+    /// The same tolerance for every component.
+    Scalar(f64),
+    /// The tolerance of each component.
+    Vector(Vec<f64>),
+}
+
+impl Tolerance {
+    /// The tolerance of component `i`.
+    #[inline]
+    pub fn at(&self, i: usize) -> f64 {
+        match self {
+            Tolerance::Scalar(tol) => *tol,
+            Tolerance::Vector(tols) => tols[i],
+        }
+    }
+
+    /// The smallest tolerance of all components.
+    pub fn min(&self) -> f64 {
+        match self {
+            Tolerance::Scalar(tol) => *tol,
+            Tolerance::Vector(tols) => tols.iter().cloned().fold(f64::INFINITY, f64::min),
+        }
+    }
+
+    /// Returns an error unless the tolerance applies to a state with `dof` components.
+    pub fn check(&self, dof: usize) -> Result<(), OdeError> {
+        match self {
+            Tolerance::Vector(tols) if tols.len() != dof => Err(OdeError::ToleranceLength {
+                expected: dof,
+                found: tols.len(),
+            }),
+            _ => Ok(()),
         }
     }
 }
 
+impl From<f64> for Tolerance {
+    fn from(tol: f64) -> Self {
+        Tolerance::Scalar(tol)
+    }
+}
+
+impl From<Vec<f64>> for Tolerance {
+    fn from(tols: Vec<f64>) -> Self {
+        Tolerance::Vector(tols)
+    }
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tolerance::Scalar(tol) => fmt::Display::fmt(tol, f),
+            Tolerance::Vector(tols) => fmt_comma_delimited(f, tols),
+        }
+    }
+}
+
+/// Parses a single tolerance or the comma separated tolerances of each component.
+impl FromStr for Tolerance {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tols = parse_comma_delimited::<f64>(s)?;
+        match tols.as_slice() {
+            [tol] => Ok(Tolerance::Scalar(*tol)),
+            _ => Ok(Tolerance::Vector(tols)),
+        }
+    }
+}
+
+/// The relative and absolute tolerance the local error of a step is measured against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tolerances {
+    pub reltol: Tolerance,
+    pub abstol: Tolerance,
+}
+
+impl Tolerances {
+    /// The allowed error of component `i` with the magnitude `y`, `abstol + reltol * |y|`.
+    #[inline]
+    pub fn scale(&self, i: usize, y: f64) -> f64 {
+        self.abstol.at(i) + self.reltol.at(i) * y.abs()
+    }
+}
+
+/// A user-supplied norm of the local error estimate of a step, to e.g. ignore algebraic
+/// components or weight the error by a mass matrix.
+///
+/// The norm is a function of the unscaled error estimate `err`, the magnitude
+/// `max(|y_n|, |y_n+1|)` of each component over the step, c.f. [`OdeType::modulus_at`],
+/// and the tolerances. A step is accepted if the norm does not exceed 1.
+#[derive(Clone)]
+pub struct ErrorNorm<Y>(NormFunction<Y>);
+
+type NormFunction<Y> = Rc<dyn Fn(&Y, &Y, &Tolerances) -> f64>;
+
+impl<Y: OdeType> ErrorNorm<Y> {
+    pub fn new<N: Fn(&Y, &Y, &Tolerances) -> f64 + 'static>(norm: N) -> Self {
+        ErrorNorm(Rc::new(norm))
+    }
+
+    /// The norm of the error estimate `err` of a step over which the state has the
+    /// magnitude `y`.
+    #[inline]
+    pub fn norm(&self, err: &Y, y: &Y, tolerances: &Tolerances) -> f64 {
+        (self.0)(err, y, tolerances)
+    }
+}
+
+impl<Y> fmt::Debug for ErrorNorm<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ErrorNorm")
+    }
+}
+
 /// either single or mult value
 macro_rules! options {
     ($($(#[$a:meta])*($id:ident, $n:expr) => $value:tt),*) => {
@@ -169,6 +857,7 @@ macro_rules! options {
         )*
 
         /// All available Ode options
+        #[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
         #[derive(Debug, Clone, PartialEq)]
         pub enum OdeOption {
             Points(Points),
@@ -176,6 +865,49 @@ macro_rules! options {
                 $id($id),
             )*
         }
+
+        /// Formats the option as `Name=value`.
+        impl fmt::Display for OdeOption {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}=", self.name())?;
+                match self {
+                    OdeOption::Points(op) => fmt::Display::fmt(op, f),
+                    $(
+                        OdeOption::$id(op) => fmt::Display::fmt(op, f),
+                    )*
+                }
+            }
+        }
+
+        /// Parses an option formatted as `Name=value`.
+        impl FromStr for OdeOption {
+            type Err = OdeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let (name, value) = s
+                    .split_once('=')
+                    .ok_or_else(|| OdeError::invalid_option(s, "expected `Name=value`"))?;
+                match name.trim() {
+                    "Points" => value.parse::<Points>().map(OdeOption::Points),
+                    $(
+                        $n => value.parse::<$id>().map(OdeOption::$id),
+                    )*
+                    _ => Err(OdeError::invalid_option(s, "unknown option")),
+                }
+            }
+        }
+
+        impl OdeOption {
+            /// The name the option is stored under in an [`OdeOptionMap`].
+            pub fn name(&self) -> &'static str {
+                match self {
+                    OdeOption::Points(_) => Points::option_name(),
+                    $(
+                        OdeOption::$id(_) => $id::option_name(),
+                    )*
+                }
+            }
+        }
     };
 }
 
@@ -183,6 +915,7 @@ macro_rules! option {
     // Single value option
     ($(#[$a:meta])*($id:ident, $n:expr) => [$value:ty]) => {
         $(#[$a])*
+        #[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
         #[derive(Clone, Debug, PartialEq)]
         pub struct $id(pub $value);
         __ode__deref!($id => $value);
@@ -206,10 +939,21 @@ macro_rules! option {
             }
         }
 
-        impl Into<OdeOption> for $id {
+        impl ::std::str::FromStr for $id {
+            type Err = OdeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.trim()
+                    .parse::<$value>()
+                    .map($id)
+                    .map_err(|_| OdeError::invalid_option(s, concat!("not a valid ", $n)))
+            }
+        }
+
+        impl From<$id> for OdeOption {
 
-            fn into(self) -> OdeOption {
-                OdeOption::$id(self)
+            fn from(op: $id) -> Self {
+                OdeOption::$id(op)
             }
         }
 
@@ -218,9 +962,17 @@ macro_rules! option {
     // List option, multiple items
     ($(#[$a:meta])*($id:ident, $n:expr) => ($item:ty)) => {
         $(#[$a])*
+        #[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
         #[derive(Clone, Debug, PartialEq)]
         pub struct $id(pub Vec<$item>);
         __ode__deref!($id => Vec<$item>);
+        impl $crate::ode::options::OdeOp for $id {
+            #[inline]
+            fn option_name() -> &'static str {
+                static NAME: &'static str = $n;
+                NAME
+            }
+        }
 
         impl ::std::fmt::Display for $id {
             #[inline]
@@ -229,10 +981,25 @@ macro_rules! option {
             }
         }
 
-        impl Into<OdeOption> for $id {
+        impl From<Vec<$item>> for $id {
 
-            fn into(self) -> OdeOption {
-                OdeOption::$id(self)
+            fn from(items: Vec<$item>) -> Self {
+                $id(items)
+            }
+        }
+
+        impl ::std::str::FromStr for $id {
+            type Err = OdeError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_comma_delimited(s).map($id)
+            }
+        }
+
+        impl From<$id> for OdeOption {
+
+            fn from(op: $id) -> Self {
+                OdeOption::$id(op)
             }
         }
     };
@@ -262,9 +1029,9 @@ macro_rules! __ode__deref {
 
 options! {
     /// An integration step is accepted if `E <= reltol*abs(y)`
-    (Reltol, "Reltol") => [f64],
+    (Reltol, "Reltol") => [Tolerance],
     /// An integration step is accepted if `E <= abstol`
-    (Abstol, "Abstol") => [f64],
+    (Abstol, "Abstol") => [Tolerance],
     /// Minimal integration step.
     (Minstep, "Minstep") => [f64],
     /// Maximal integration step.
@@ -277,23 +1044,102 @@ options! {
     /// retries sets a limit to the number of times the solver might try with a smaller step.
     #[derive(Default)]
     (Retries, "Retries") => [usize],
-    /// user defined norm for determining the error
-    #[derive(Default)]
-    (Norm, "Norm") => [PNorm],
     /// User defined timeout after which step reduction should not
     /// increase step for timeout controlled steps.
-    (StepTimeout, "StepTimeout") => [usize]
+    (StepTimeout, "StepTimeout") => [usize],
+    /// Highest order of variable order methods.
+    (MaxOrder, "MaxOrder") => [usize],
+    /// Store the continuous output of every step in the solution.
+    #[derive(Default)]
+    (Dense, "Dense") => [bool],
+    /// Highest degree of the stored continuous output.
+    (InterpOrder, "InterpOrder") => [usize],
+    /// Times the steps must end at exactly.
+    #[derive(Default)]
+    (Tstops, "Tstops") => (f64),
+    /// Times at which the solution is stored.
+    #[derive(Default)]
+    (Saveat, "Saveat") => (f64),
+    /// Exponent of the current error in the PI step size controller.
+    (Beta1, "Beta1") => [f64],
+    /// Exponent of the previous error in the PI step size controller.
+    (Beta2, "Beta2") => [f64],
+    /// Safety factor of the new step size.
+    (Safety, "Safety") => [f64],
+    /// Smallest factor by which the step size changes.
+    (Qmin, "Qmin") => [f64],
+    /// Largest factor by which the step size changes.
+    (Qmax, "Qmax") => [f64],
+    /// The step size controller.
+    #[derive(Default)]
+    (Controller, "Controller") => [ControllerType],
+    /// Maximum number of steps.
+    (MaxIters, "MaxIters") => [usize],
+    /// What to do once the problem appears stiff.
+    #[derive(Default)]
+    (Stiffness, "Stiffness") => [StiffnessAction],
+    /// Take steps below the minimum step size at the minimum step size.
+    #[derive(Default)]
+    (ForceDtmin, "ForceDtmin") => [bool]
 }
 
 impl Default for Reltol {
     fn default() -> Self {
-        Reltol(1e-5)
+        Reltol(Tolerance::Scalar(1e-5))
+    }
+}
+
+impl From<f64> for Reltol {
+    fn from(tol: f64) -> Self {
+        Reltol(tol.into())
+    }
+}
+
+impl From<Vec<f64>> for Reltol {
+    fn from(tols: Vec<f64>) -> Self {
+        Reltol(tols.into())
     }
 }
 
 impl Default for Abstol {
     fn default() -> Self {
-        Abstol(1e-8)
+        Abstol(Tolerance::Scalar(1e-8))
+    }
+}
+
+impl From<f64> for Abstol {
+    fn from(tol: f64) -> Self {
+        Abstol(tol.into())
+    }
+}
+
+impl From<Vec<f64>> for Abstol {
+    fn from(tols: Vec<f64>) -> Self {
+        Abstol(tols.into())
+    }
+}
+
+impl Default for Safety {
+    fn default() -> Self {
+        Safety(0.9)
+    }
+}
+
+impl Default for Qmin {
+    fn default() -> Self {
+        Qmin(0.2)
+    }
+}
+
+impl Default for Qmax {
+    fn default() -> Self {
+        Qmax(10.)
+    }
+}
+
+impl Default for MaxIters {
+    fn default() -> Self {
+        MaxIters(100_000)
     }
 }
 
@@ -303,6 +1149,20 @@ impl Default for StepTimeout {
     }
 }
 
+/// parses a list type separated by commas
+fn parse_comma_delimited<T: FromStr>(s: &str) -> Result<Vec<T>, OdeError> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .map(|part| {
+            part.trim()
+                .parse()
+                .map_err(|_| OdeError::invalid_option(part, "not a valid value"))
+        })
+        .collect()
+}
+
 /// formats a list type separated by commas
 #[inline]
 fn fmt_comma_delimited<T: fmt::Display>(f: &mut ::std::fmt::Formatter, parts: &[T]) -> fmt::Result {
@@ -316,3 +1176,141 @@ fn fmt_comma_delimited<T: fmt::Display>(f: &mut ::std::fmt::Formatter, parts: &[
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option_map_conversion() {
+        let opts = AdaptiveOptions {
+            reltol: 1e-7.into(),
+            maxstep: Some(Maxstep(0.5)),
+            tstops: vec![1., 2.].into(),
+            ..AdaptiveOptions::default()
+        };
+        let ops = OdeOptionMap::from(opts);
+        assert!(!ops.contains_key(Minstep::option_name()));
+        let opts = AdaptiveOptions::try_from(&ops).unwrap();
+        assert_eq!(Reltol::from(1e-7), opts.reltol);
+        assert_eq!(Some(Maxstep(0.5)), opts.maxstep);
+        assert_eq!(vec![1., 2.], *opts.tstops);
+        // missing options take their defaults
+        assert_eq!(None, opts.minstep);
+        assert_eq!(Abstol::default(), opts.abstol);
+
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Abstol::from(1e-3).into());
+        assert!(matches!(
+            ExplicitRkOptions::try_from(&ops),
+            Err(OdeError::OptionMismatch { .. })
+        ));
+        match AdaptiveOptions::try_from(ops) {
+            Err(OdeError::OptionMismatch { name, found }) => {
+                assert_eq!(("Reltol", "Abstol"), (name, found))
+            }
+            other => panic!("expected a mismatch, found {:?}", other),
+        }
+
+        // the typed options only hold the options of their solvers
+        let opts = BasicOptions {
+            maxstep: Some(Maxstep(0.5)),
+            ..BasicOptions::default()
+        };
+        let ops = OdeOptionMap::from(opts);
+        assert!(!ops.contains_key(Tstops::option_name()));
+        let opts = BasicOptions::try_from(ops).unwrap();
+        assert_eq!(Some(Maxstep(0.5)), opts.maxstep);
+    }
+
+    #[test]
+    fn parse_options() {
+        let ops: OdeOptionMap = "Reltol=1e-6, Points=Specified, Maxstep=0.1, Tstops=1, 2.5"
+            .parse()
+            .unwrap();
+        let opts = AdaptiveOptions::from_map(&ops).unwrap();
+        assert_eq!(Reltol::from(1e-6), opts.reltol);
+        assert_eq!(Points::Specified, opts.points);
+        assert_eq!(Some(Maxstep(0.1)), opts.maxstep);
+        assert_eq!(vec![1., 2.5], *opts.tstops);
+
+        // formatting round-trips
+        let ops = OdeOptionMap::from(AdaptiveOptions {
+            abstol: vec![1e-8, 1e-3].into(),
+            controller: ControllerType::Pid.into(),
+            points: Points::Every(10),
+            ..AdaptiveOptions::default()
+        });
+        let parsed: OdeOptionMap = ops.to_string().parse().unwrap();
+        assert_eq!(ops.to_string(), parsed.to_string());
+        assert_eq!(ops.len(), parsed.len());
+        for (name, op) in ops.iter() {
+            assert_eq!(Some(op), parsed.get(name));
+        }
+
+        assert_eq!(Points::Last, "Last".parse().unwrap());
+        assert!("Every(x)".parse::<Points>().is_err());
+        assert!("Reltol=x".parse::<OdeOptionMap>().is_err());
+        assert!("Unknown=1".parse::<OdeOptionMap>().is_err());
+        // no solver measures the error with a chosen p-norm
+        assert!("Norm=2".parse::<OdeOptionMap>().is_err());
+        assert!("1e-6, Reltol=1".parse::<OdeOptionMap>().is_err());
+        assert_eq!(0, "".parse::<OdeOptionMap>().unwrap().len());
+    }
+
+    #[test]
+    fn validation() {
+        assert!(AdaptiveOptions::default().validate(2).is_ok());
+        let invalid = |opts: AdaptiveOptions| match opts.validate(2) {
+            Err(OdeError::OptionOutOfRange { name, .. }) => name,
+            other => panic!("expected an invalid option, found {:?}", other),
+        };
+        let abstol = AdaptiveOptions {
+            abstol: vec![1e-8, 0.].into(),
+            ..AdaptiveOptions::default()
+        };
+        assert_eq!("Abstol", invalid(abstol));
+        let steps = AdaptiveOptions {
+            minstep: Some(Minstep(0.1)),
+            maxstep: Some(Maxstep(0.01)),
+            ..AdaptiveOptions::default()
+        };
+        assert_eq!("Minstep", invalid(steps));
+        let initstep = AdaptiveOptions {
+            maxstep: Some(Maxstep(0.01)),
+            initstep: Initstep(-0.1),
+            ..AdaptiveOptions::default()
+        };
+        assert_eq!("Initstep", invalid(initstep));
+        let gains = AdaptiveOptions {
+            beta1: Some(Beta1(-0.1)),
+            ..AdaptiveOptions::default()
+        };
+        assert_eq!("Beta1", invalid(gains));
+        let qmax = AdaptiveOptions {
+            qmax: Qmax(0.5),
+            ..AdaptiveOptions::default()
+        };
+        assert_eq!("Qmax", invalid(qmax));
+        assert!(matches!(
+            AdaptiveOptions::default().tolerances(2),
+            Ok((Tolerance::Scalar(_), Tolerance::Scalar(_)))
+        ));
+    }
+
+    #[cfg(feature = "serde0")]
+    #[test]
+    fn serde_options() {
+        let ops: OdeOptionMap = "Reltol=1e-6, Abstol=1e-8, 1e-6, Points=Specified, Tstops=1, 2"
+            .parse()
+            .unwrap();
+        let json = serde_json::to_string(&ops).unwrap();
+        assert!(json.contains(r#"{"Abstol":[1e-8,1e-6]}"#), "{}", json);
+        assert!(json.contains(r#"{"Points":"Specified"}"#), "{}", json);
+        let reloaded: OdeOptionMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(ops.to_string(), reloaded.to_string());
+
+        let points: Points = serde_json::from_str(r#""All""#).unwrap();
+        assert_eq!(Points::All, points);
+    }
+}
@@ -1,18 +1,81 @@
 #![allow(clippy::many_single_char_names)]
 #![allow(clippy::too_many_arguments)]
 use crate::error::OdeError;
+use crate::ode::adams::{self, AdamsCoeffs};
+use crate::ode::bdf::{self, BdfCoeffs};
+use crate::ode::callback::{CallbackSet, ContinuousCallback, DiscreteCallback};
 use crate::ode::coeff::{CoefficientMap, CoefficientPoint};
-use crate::ode::options::{AdaptiveOptions, OdeOptionMap, Points, StepTimeout};
-use crate::ode::rosenbrock::RosenbrockCoeffs;
+use crate::ode::composite::{Composite, ExplicitMethod, FnMethod, RosenbrockMethod, StepMethod};
+use crate::ode::dense::{DenseCoeffs, DenseOutput, StepInterpolant};
+use crate::ode::dop853::{self, Dop853Coeffs};
+use crate::ode::dual::{self, Dual, DualType};
+use crate::ode::extrapolation::{self, ExtrapolationCoeffs};
+use crate::ode::implicit::{
+    self, Collocation, ImplicitCoeffs, ImplicitTrial, Interpolant, IterationMatrix, RosenbrockDense,
+};
+use crate::ode::integrator::OdeIntegrator;
+use crate::ode::jacobian::{
+    self, DifferenceScheme, FiniteDifferences, Jacobian, JacobianCache, JacobianMatrix,
+    JacobianVectorProduct,
+};
+use crate::ode::krylov::{
+    JacobianOperator, Preconditioner, PreconditionerKind, PreconditionerSetup, PreconditionerSolve,
+    SetupFunction,
+};
+use crate::ode::linsolve::{BandedLu, Factorization, LinearSolver, LinearSolverType};
+use crate::ode::low_storage::LowStorageCoeffs;
+use crate::ode::multistep::{self, DifferenceHistory};
+use crate::ode::newton::{NewtonCache, NewtonOptions};
+use crate::ode::nordsieck::{self, NordsieckCoeffs, NordsieckHistory};
+use crate::ode::options::{
+    AdaptiveOptions, BasicOptions, Dop853Options, ErrorNorm, ExplicitRkOptions, ImplicitOptions,
+    MultistepOptions, OdeOptionMap, Points, StepTimeout, Tolerance, Tolerances,
+};
+use crate::ode::rosenbrock::{RodasCoeffs, RosenbrockCoeffs};
 use crate::ode::runge_kutta::{ButcherTableau, WeightType, Weights};
-use crate::ode::solution::OdeSolution;
-use crate::ode::types::{OdeType, PNorm};
+use crate::ode::solution::{Diagnostics, OdeSolution, ReturnCode};
+use crate::ode::sparse::{Coloring, CscMatrix, SparsityPattern};
+use crate::ode::stiffness::{StiffnessAction, StiffnessDetector};
+use crate::ode::types::OdeType;
 use crate::ode::Ode;
 use alga::general::RealField;
-use na::{allocator::Allocator, DMatrix, DVector, DefaultAllocator, Dim, U1, U2};
+use na::{allocator::Allocator, DMatrix, DVector, DefaultAllocator, Dim, U1, U12, U2, U7};
 use num_traits::{abs, signum};
+use std::cell::Cell;
 use std::fmt;
 use std::ops::{Add, Mul};
+use std::rc::Rc;
+
+/// The type of an unset time derivative of the RHS of an [`OdeProblem`].
+pub type TimeDerivative<Y> = fn(f64, &Y) -> Y;
+
+/// The type of an unset mass matrix of an [`OdeProblem`].
+pub type MassMatrix<Y> = fn(f64, &Y) -> DMatrix<<Y as OdeType>::Item>;
+
+/// The RHS of an [`OdeProblem`] that writes the derivatives into a preallocated state
+/// instead of returning a fresh one, set with [`OdeBuilder::fun_inplace`].
+#[derive(Clone)]
+pub struct InplaceRhs<Y>(InplaceFunction<Y>);
+
+type InplaceFunction<Y> = Rc<dyn Fn(&mut Y, f64, &Y)>;
+
+impl<Y: OdeType> InplaceRhs<Y> {
+    pub fn new<F: Fn(&mut Y, f64, &Y) + 'static>(f: F) -> Self {
+        InplaceRhs(Rc::new(f))
+    }
+
+    /// Writes the derivatives at `(t, y)` into `dy`.
+    #[inline]
+    pub fn eval(&self, dy: &mut Y, t: f64, y: &Y) {
+        (self.0)(dy, t, y)
+    }
+}
+
+impl<Y> fmt::Debug for InplaceRhs<Y> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("InplaceRhs")
+    }
+}
 
 /// F: the RHS of the ODE `dy/dt = F(t,y)`, which is a function of t and y(t)
 /// and returns `dy/dt`.
@@ -22,38 +85,96 @@ use std::ops::{Add, Mul};
 /// Most solvers will only consider tspan\[0\] and tspan\[end\], and intermediary points will be
 /// interpolated. If tspan\[0\] > tspan\[end\] the integration is performed backwards. The times are
 /// promoted as necessary to a common floating-point type.
+/// G: optional partial derivative `∂F/∂t` of the RHS, used by Rosenbrock methods.
+/// M: optional mass matrix `M(t, y)` of the problem `M dy/dt = F(t,y)`.
 #[derive(Debug, Clone)]
-pub struct OdeProblem<F, Y>
+pub struct OdeProblem<F, Y, G = TimeDerivative<Y>, M = MassMatrix<Y>>
 where
     F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
     Y: OdeType,
 {
     /// The RHS of the ODE `dy/dt = F(t,y)`.
     ///
     /// Is a function of t and y(t) and returns the derivatives of y
     f: F,
+    /// The RHS writing into a preallocated state, which the steppers with stage buffers
+    /// prefer over `f`.
+    f_inplace: Option<InplaceRhs<Y>>,
+    /// The partial derivative `∂F/∂t` of the RHS.
+    ///
+    /// Approximated by finite differences if not set.
+    tgrad: Option<G>,
+    /// The mass matrix `M(t, y)` of the problem `M dy/dt = F(t,y)`, may be singular.
+    ///
+    /// The identity if not set.
+    mass: Option<M>,
+    /// The jacobian `∂F/∂y` of the RHS.
+    ///
+    /// Approximated by finite differences if not set.
+    jac: Option<Jacobian<Y>>,
+    /// The product of the jacobian with a vector, finite differences if not set.
+    jvp: Option<JacobianVectorProduct<Y>>,
+    /// The tolerance the jacobian is checked against finite differences with.
+    jac_check: Option<f64>,
+    /// The finite differences the jacobian is approximated with if not set.
+    fd: FiniteDifferences,
+    /// The sparsity pattern of the jacobian and the coloring of its columns.
+    sparsity: Option<(Rc<SparsityPattern>, Rc<Coloring>)>,
+    /// The newton iteration of the implicit methods, the default of each method if not set.
+    newton: Option<NewtonOptions>,
+    /// How the linear systems of the implicit methods are solved.
+    linear_solver: LinearSolverType,
+    /// The preconditioner of the matrix-free linear solves, none if not set.
+    preconditioner: Option<Preconditioner<Y>>,
     /// Initial value for `Rhs` input.
     ///
     /// determines the element type of the `yout` vector of the solutions
     y0: Y,
     /// Sorted t values at which the solution (y) is requested
     tspan: Vec<f64>,
+    /// Events at which the integration is interrupted.
+    callbacks: CallbackSet<Y>,
+    /// The norm of the local error estimate.
+    ///
+    /// The 2-norm of the error scaled by the tolerances if not set.
+    error_norm: Option<ErrorNorm<Y>>,
+    /// The statistics of all solves of the problem.
+    stats: Cell<Diagnostics>,
 }
 
 #[derive(Debug, Clone)]
-pub struct OdeBuilder<F, Y>
+pub struct OdeBuilder<F, Y, G = TimeDerivative<Y>, M = MassMatrix<Y>>
 where
     F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
     Y: OdeType,
 {
     f: Option<F>,
+    f_inplace: Option<InplaceRhs<Y>>,
+    tgrad: Option<G>,
+    mass: Option<M>,
+    jac: Option<Jacobian<Y>>,
+    jvp: Option<JacobianVectorProduct<Y>>,
+    jac_check: Option<f64>,
+    fd: FiniteDifferences,
+    sparsity: Option<(Rc<SparsityPattern>, Rc<Coloring>)>,
+    newton: Option<NewtonOptions>,
+    linear_solver: LinearSolverType,
+    preconditioner: Option<Preconditioner<Y>>,
     y0: Option<Y>,
     tspan: Option<Vec<f64>>,
+    callbacks: CallbackSet<Y>,
+    error_norm: Option<ErrorNorm<Y>>,
 }
 
-impl<F, Y> OdeBuilder<F, Y>
+impl<F, Y, G, M> OdeBuilder<F, Y, G, M>
 where
     F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
     Y: OdeType,
 {
     /// set the problem function
@@ -62,6 +183,129 @@ where
         self
     }
 
+    /// set the problem function, same as [`OdeBuilder::fun`]
+    pub fn rhs(self, f: F) -> Self {
+        self.fun(f)
+    }
+
+    /// set the partial derivative `∂F/∂t` of the problem function
+    pub fn tgrad<G2: Fn(f64, &Y) -> Y>(self, tgrad: G2) -> OdeBuilder<F, Y, G2, M> {
+        OdeBuilder {
+            f: self.f,
+            f_inplace: self.f_inplace,
+            tgrad: Some(tgrad),
+            mass: self.mass,
+            jac: self.jac,
+            jvp: self.jvp,
+            jac_check: self.jac_check,
+            fd: self.fd,
+            sparsity: self.sparsity,
+            newton: self.newton,
+            linear_solver: self.linear_solver,
+            preconditioner: self.preconditioner,
+            y0: self.y0,
+            tspan: self.tspan,
+            callbacks: self.callbacks,
+            error_norm: self.error_norm,
+        }
+    }
+
+    /// set the mass matrix `M(t, y)` of the problem `M dy/dt = F(t,y)`
+    ///
+    /// A singular mass matrix turns the problem into a differential algebraic equation
+    /// of index 1. The mass matrix is only respected by [`OdeProblem::rodas4`],
+    /// [`OdeProblem::rodas5`], [`OdeProblem::radau5`] and [`OdeProblem::bdf`], the other
+    /// solvers return [`OdeError::UnsupportedSolver`] for problems with a mass matrix.
+    pub fn mass<M2: Fn(f64, &Y) -> DMatrix<Y::Item>>(self, mass: M2) -> OdeBuilder<F, Y, G, M2> {
+        OdeBuilder {
+            f: self.f,
+            f_inplace: self.f_inplace,
+            tgrad: self.tgrad,
+            mass: Some(mass),
+            jac: self.jac,
+            jvp: self.jvp,
+            jac_check: self.jac_check,
+            fd: self.fd,
+            sparsity: self.sparsity,
+            newton: self.newton,
+            linear_solver: self.linear_solver,
+            preconditioner: self.preconditioner,
+            y0: self.y0,
+            tspan: self.tspan,
+            callbacks: self.callbacks,
+            error_norm: self.error_norm,
+        }
+    }
+
+    /// set the jacobian `∂F/∂y` of the problem function, used by the implicit and
+    /// Rosenbrock methods instead of finite differences
+    pub fn jac<J: Fn(f64, &Y) -> DMatrix<Y::Item> + 'static>(mut self, jac: J) -> Self {
+        self.jac = Some(Jacobian::new(jac));
+        self
+    }
+
+    /// set the product `∂F/∂y v` of the jacobian with a vector `v`, which the
+    /// matrix-free solves of [`LinearSolverType::Gmres`] use instead of finite
+    /// differences
+    pub fn jvp<J: Fn(f64, &Y, &Y) -> Y + 'static>(mut self, jvp: J) -> Self {
+        self.jvp = Some(JacobianVectorProduct::new(jvp));
+        self
+    }
+
+    /// compare the jacobian set with [`OdeBuilder::jac`] against finite differences
+    /// whenever it is evaluated, see [`jacobian::check`]
+    ///
+    /// The solvers return [`OdeError::JacobianMismatch`] at the first entry that differs
+    /// by more than `tol`. Meant for debugging, as every check costs `2N` evaluations of
+    /// the RHS.
+    pub fn check_jacobian(mut self, tol: f64) -> Self {
+        self.jac_check = Some(tol);
+        self
+    }
+
+    /// set the finite differences the jacobian is approximated with unless set with
+    /// [`OdeBuilder::jac`], forward differences by default
+    pub fn finite_differences(mut self, fd: FiniteDifferences) -> Self {
+        self.fd = fd;
+        self
+    }
+
+    /// declare the sparsity pattern of the jacobian `∂F/∂y`
+    ///
+    /// Finite difference jacobians take one evaluation of the RHS per color of the
+    /// [`SparsityPattern::coloring`], which is computed once. Without a mass matrix, the implicit Runge–Kutta
+    /// methods, i.e. the SDIRK methods, [`OdeProblem::radau5`] and the fully implicit
+    /// methods, store and factorize their iteration matrices sparsely unless another
+    /// [`OdeBuilder::linear_solver`] is set. Entries of a
+    /// jacobian set with [`OdeBuilder::jac`] outside of the pattern are dropped.
+    pub fn sparsity(mut self, pattern: SparsityPattern) -> Self {
+        let coloring = pattern.coloring();
+        self.sparsity = Some((Rc::new(pattern), Rc::new(coloring)));
+        self
+    }
+
+    /// set the newton iteration of the implicit Runge–Kutta methods and
+    /// [`OdeProblem::bdf`], which default to [`NewtonOptions::default`] and
+    /// [`NewtonOptions::bdf`] respectively
+    pub fn newton(mut self, newton: NewtonOptions) -> Self {
+        self.newton = Some(newton);
+        self
+    }
+
+    /// set how the linear systems of the implicit and Rosenbrock methods are solved,
+    /// [`LinearSolverType::Auto`] by default
+    pub fn linear_solver(mut self, solver: LinearSolverType) -> Self {
+        self.linear_solver = solver;
+        self
+    }
+
+    /// set the preconditioner of the matrix-free solves of [`LinearSolverType::Gmres`],
+    /// which are unpreconditioned by default
+    pub fn preconditioner(mut self, preconditioner: Preconditioner<Y>) -> Self {
+        self.preconditioner = Some(preconditioner);
+        self
+    }
+
     /// set the initial starting point
     pub fn init<T: Into<Y>>(mut self, y0: T) -> Self {
         self.y0 = Some(y0.into());
@@ -80,10 +324,55 @@ where
         self
     }
 
+    /// Set the time span to the interval from `t0` to `tf`, the solution is requested at
+    /// the end points only.
+    pub fn interval(mut self, t0: f64, tf: f64) -> Self {
+        self.tspan = Some(vec![t0, tf]);
+        self
+    }
+
+    /// Add an event at which the integration is interrupted.
+    ///
+    /// Callbacks are only respected by the solvers driven by an [`OdeIntegrator`], i.e.
+    /// the embedded explicit Runge–Kutta methods like [`OdeProblem::tsit5`] and
+    /// [`OdeProblem::dop853`], the other solvers return [`OdeError::UnsupportedSolver`]
+    /// for problems with callbacks.
+    pub fn callback(mut self, callback: ContinuousCallback<Y>) -> Self {
+        self.callbacks = self.callbacks.continuous(callback);
+        self
+    }
+
+    /// Add an event that is checked after every accepted step, after the events added
+    /// with [`OdeBuilder::callback`].
+    ///
+    /// Like those, discrete callbacks are only respected by the solvers driven by an
+    /// [`OdeIntegrator`].
+    pub fn discrete_callback(mut self, callback: DiscreteCallback<Y>) -> Self {
+        self.callbacks = self.callbacks.discrete(callback);
+        self
+    }
+
+    /// Add all callbacks of `callbacks`, after the ones already added.
+    pub fn callbacks<C: Into<CallbackSet<Y>>>(mut self, callbacks: C) -> Self {
+        self.callbacks = self.callbacks.merge(callbacks.into());
+        self
+    }
+
+    /// set the norm the local error estimate of a step is measured with, see
+    /// [`ErrorNorm`]
+    ///
+    /// The norm is respected by the error tests and order selections of all adaptive
+    /// solvers, which otherwise measure the error with the weighted root mean square norm
+    /// [`OdeType::wrms_norm`]. The norm can't be chosen with the [`AdaptiveOptions`].
+    pub fn error_norm<N: Fn(&Y, &Y, &Tolerances) -> f64 + 'static>(mut self, norm: N) -> Self {
+        self.error_norm = Some(ErrorNorm::new(norm));
+        self
+    }
+
     /// Creates a new [`OdeProblem`].
     ///
     /// Returns an error if a field is None.
-    pub fn build(self) -> Result<OdeProblem<F, Y>, OdeError> {
+    pub fn build(self) -> Result<OdeProblem<F, Y, G, M>, OdeError> {
         let f = self
             .f
             .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
@@ -94,7 +383,51 @@ where
             .tspan
             .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
 
-        Ok(OdeProblem { f, y0, tspan })
+        Ok(OdeProblem {
+            f,
+            f_inplace: self.f_inplace,
+            tgrad: self.tgrad,
+            mass: self.mass,
+            jac: self.jac,
+            jvp: self.jvp,
+            jac_check: self.jac_check,
+            fd: self.fd,
+            sparsity: self.sparsity,
+            newton: self.newton,
+            linear_solver: self.linear_solver,
+            preconditioner: self.preconditioner,
+            y0,
+            tspan,
+            callbacks: self.callbacks,
+            error_norm: self.error_norm,
+            stats: Cell::default(),
+        })
+    }
+}
+
+impl<F, Y, G, M> OdeBuilder<F, Y, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<f64>,
+    Y: DualType,
+{
+    /// set the jacobian `∂F/∂y` and the partial derivative `∂F/∂t` of the problem
+    /// function to the exact ones of `f` by automatic differentiation, see
+    /// [`dual::jacobian_ad`]
+    ///
+    /// `f` is the problem function evaluated in [`Dual`] numbers, usually the same
+    /// function as the one set with [`OdeBuilder::fun`] if that is generic over its
+    /// scalar.
+    pub fn autodiff<FD>(self, f: FD) -> OdeBuilder<F, Y, impl Fn(f64, &Y) -> Y, M>
+    where
+        FD: Fn(Dual, &Y::Dual) -> Y::Dual + 'static,
+    {
+        let f = Rc::new(f);
+        let (jac, jvp) = (Rc::clone(&f), Rc::clone(&f));
+        self.jac(move |t, y: &Y| dual::jacobian_ad(&*jac, t, y))
+            .jvp(move |t, y: &Y, v: &Y| dual::jvp_ad(&*jvp, t, y, v))
+            .tgrad(move |t, y: &Y| dual::tgrad_ad(&*f, t, y))
     }
 }
 
@@ -106,85 +439,430 @@ where
     fn default() -> Self {
         Self {
             f: None,
+            f_inplace: None,
+            tgrad: None,
+            mass: None,
+            jac: None,
+            jvp: None,
+            jac_check: None,
+            fd: FiniteDifferences::default(),
+            sparsity: None,
+            newton: None,
+            linear_solver: LinearSolverType::default(),
+            preconditioner: None,
             y0: None,
             tspan: None,
+            callbacks: CallbackSet::default(),
+            error_norm: None,
         }
     }
 }
 
-impl<F, Y, T> OdeProblem<F, Y>
+impl<Y, G, M> OdeBuilder<TimeDerivative<Y>, Y, G, M>
+where
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<Y::Item>,
+    Y: OdeType,
+{
+    /// set the problem function `F(t, y, p)` that depends on the parameters `p`, which
+    /// are moved into the problem
+    pub fn params<P, F2>(self, f: F2, params: P) -> OdeBuilder<impl Fn(f64, &Y) -> Y, Y, G, M>
+    where
+        F2: Fn(f64, &Y, &P) -> Y,
+    {
+        OdeBuilder {
+            f: Some(move |t, y: &Y| f(t, y, &params)),
+            f_inplace: self.f_inplace,
+            tgrad: self.tgrad,
+            mass: self.mass,
+            jac: self.jac,
+            jvp: self.jvp,
+            jac_check: self.jac_check,
+            fd: self.fd,
+            sparsity: self.sparsity,
+            newton: self.newton,
+            linear_solver: self.linear_solver,
+            preconditioner: self.preconditioner,
+            y0: self.y0,
+            tspan: self.tspan,
+            callbacks: self.callbacks,
+            error_norm: self.error_norm,
+        }
+    }
+
+    /// set the problem function as `F(dy, t, y)`, which writes the derivatives into `dy`
+    ///
+    /// The explicit Runge–Kutta methods, the ones driven by an [`OdeIntegrator`] as well as
+    /// the fixed step, SSP and low-storage ones, evaluate their stages into preallocated
+    /// buffers then, which avoids allocating a state for every stage if `Y` is heap
+    /// allocated. The other solvers, which solve for the components as a vector, evaluate
+    /// into a copy of `y`.
+    pub fn fun_inplace<F2>(self, f: F2) -> OdeBuilder<impl Fn(f64, &Y) -> Y + Clone, Y, G, M>
+    where
+        F2: Fn(&mut Y, f64, &Y) + 'static,
+    {
+        let inplace = InplaceRhs::new(f);
+        let rhs = inplace.clone();
+        OdeBuilder {
+            f: Some(move |t, y: &Y| {
+                let mut dy = y.clone();
+                rhs.eval(&mut dy, t, y);
+                dy
+            }),
+            f_inplace: Some(inplace),
+            tgrad: self.tgrad,
+            mass: self.mass,
+            jac: self.jac,
+            jvp: self.jvp,
+            jac_check: self.jac_check,
+            fd: self.fd,
+            sparsity: self.sparsity,
+            newton: self.newton,
+            linear_solver: self.linear_solver,
+            preconditioner: self.preconditioner,
+            y0: self.y0,
+            tspan: self.tspan,
+            callbacks: self.callbacks,
+            error_norm: self.error_norm,
+        }
+    }
+}
+
+impl<F, Y> OdeProblem<F, Y>
 where
     F: Fn(f64, &Y) -> Y,
-    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
-    Y: OdeType<Item = T>,
+    Y: OdeType,
 {
     /// convenience method to create a new builder
     /// same as `OdeBuilder::default()`
     pub fn builder() -> OdeBuilder<F, Y> {
         OdeBuilder::default()
     }
+}
 
+impl<F, G, M, Y, T> OdeProblem<F, Y, G, M>
+where
+    F: Fn(f64, &Y) -> Y,
+    G: Fn(f64, &Y) -> Y,
+    M: Fn(f64, &Y) -> DMatrix<T>,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    /// Solve the problem with the method `ode`.
+    ///
+    /// The map is converted with the `from_map` of the options of the method, e.g.
+    /// [`ExplicitRkOptions::from_map`], so options the method ignores are an error.
     pub fn solve(self, ode: Ode, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
         match ode {
-            Ode::Feuler => Ok(self.feuler()),
-            Ode::Heun => Ok(self.heun()),
-            Ode::Midpoint => Ok(self.midpoint()),
-            Ode::Ode23 => self.ode23(opts),
-            Ode::Ode23s => self.ode23s(opts),
-            Ode::Ode4 => Ok(self.ode4()),
-            Ode::Ode45 => self.ode45(opts),
-            Ode::Ode45fe => self.ode45_fe(opts),
+            Ode::Feuler => self.feuler(),
+            Ode::Beuler => self.beuler(),
+            Ode::Trapezoid => self.trapezoid(),
+            Ode::TrapezoidAdapt => self.trapezoid_adapt(ImplicitOptions::from_map(&opts)?),
+            Ode::Sdirk2 => self.sdirk2(ImplicitOptions::from_map(&opts)?),
+            Ode::Sdirk4 => self.sdirk4(ImplicitOptions::from_map(&opts)?),
+            Ode::Kvaerno4 => self.kvaerno4(ImplicitOptions::from_map(&opts)?),
+            Ode::TrBdf2 => self.trbdf2(ImplicitOptions::from_map(&opts)?),
+            Ode::Radau5 => self.radau5(ImplicitOptions::from_map(&opts)?),
+            Ode::Heun => self.heun(),
+            Ode::Midpoint => self.midpoint(),
+            Ode::Ssprk22 => self.ssprk22(),
+            Ode::Ssprk33 => self.ssprk33(),
+            Ode::Ssprk104 => self.ssprk104(),
+            Ode::Gauss4 => self.gauss4(),
+            Ode::Gauss6 => self.gauss6(),
+            Ode::LobattoIIIA => self.lobatto_iiia(),
+            Ode::LobattoIIIB => self.lobatto_iiib(),
+            Ode::LobattoIIIC => self.lobatto_iiic(),
+            Ode::Ode23 => self.ode23(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Ode23s => self.ode23s(BasicOptions::from_map(&opts)?),
+            Ode::Rosenbrock23 => self.rosenbrock23(BasicOptions::from_map(&opts)?),
+            Ode::Rodas4 => self.rodas4(ImplicitOptions::from_map(&opts)?),
+            Ode::Rodas5 => self.rodas5(ImplicitOptions::from_map(&opts)?),
+            Ode::AutoTsit5 => self.auto_tsit5(ImplicitOptions::from_map(&opts)?),
+            Ode::Bdf => self.bdf(MultistepOptions::from_map(&opts)?),
+            Ode::Abm => self.abm(BasicOptions::from_map(&opts)?),
+            Ode::Adams => self.adams(MultistepOptions::from_map(&opts)?),
+            Ode::Gbs => self.gbs(BasicOptions::from_map(&opts)?),
+            Ode::Bs3 => self.bs3(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Ode4 => self.ode4(),
+            Ode::Ode45 => self.ode45(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Ode45fe => self.ode45_fe(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Ode45ck => self.ode45_ck(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Lsrk33 => self.lsrk33(),
+            Ode::Lsrk54 => self.lsrk54(),
             Ode::Ode4skr => self.ode4s_kr(),
             Ode::Ode4ss => self.ode4s_s(),
-            Ode::Ode78 => self.ode78(opts),
+            Ode::Ode78 => self.ode78(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Dop853 => self.dop853(Dop853Options::from_map(&opts)?),
+            Ode::Tsit5 => self.tsit5(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Vern6 => self.vern6(ExplicitRkOptions::from_map(&opts)?),
+            Ode::Vern9 => self.vern9(ExplicitRkOptions::from_map(&opts)?),
         }
     }
 
     /// Solve the problem using the Feuler Butchertableau.
-    pub fn feuler(self) -> OdeSolution<f64, Y> {
+    pub fn feuler(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Feuler)?;
+        self.check_callbacks(Ode::Feuler)?;
         self.oderk_fixed(&ButcherTableau::feuler())
     }
 
     /// Solve the problem using the Heun Butchertableau.
-    pub fn heun(self) -> OdeSolution<f64, Y> {
+    pub fn heun(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Heun)?;
+        self.check_callbacks(Ode::Heun)?;
         self.oderk_fixed(&ButcherTableau::heun())
     }
 
     /// Solve the problem using the Mindpoint method.
-    pub fn midpoint(self) -> OdeSolution<f64, Y> {
+    pub fn midpoint(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Midpoint)?;
+        self.check_callbacks(Ode::Midpoint)?;
         self.oderk_fixed(&ButcherTableau::midpoint())
     }
 
-    pub fn ode21(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    /// Solve the problem using the optimal second order SSP method.
+    pub fn ssprk22(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ssprk22)?;
+        self.check_callbacks(Ode::Ssprk22)?;
+        self.oderk_fixed(&ButcherTableau::ssprk22())
+    }
+
+    /// Solve the problem using the optimal third order SSP method.
+    pub fn ssprk33(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ssprk33)?;
+        self.check_callbacks(Ode::Ssprk33)?;
+        self.oderk_fixed(&ButcherTableau::ssprk33())
+    }
+
+    /// Solve the problem using the ten stage, fourth order SSP method.
+    ///
+    /// Uses the low-storage implementation of [`ButcherTableau::ssprk104`], which
+    /// only keeps two registers besides the derivative instead of all ten stages.
+    pub fn ssprk104(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ssprk104)?;
+        self.check_callbacks(Ode::Ssprk104)?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let start = self.stats();
+        // store for the computed values
+        let mut ys = Vec::with_capacity(self.tspan.len());
+        ys.push(self.y0.clone());
+
+        let dof = self.y0.dof();
+        let mut k = self.y0.clone();
+
+        for i in 0..self.tspan.len() - 1 {
+            let t = self.tspan[i];
+            let dt = self.tspan[i + 1] - t;
+
+            // forward euler step of size dt/6 for the stage at `t + c*dt`
+            let mut euler = |q: &mut Y, c: f64| {
+                self.rhs_into(&mut k, t + c * dt, q);
+                for d in 0..dof {
+                    *q.get_mut(d) += k.get(d) * (dt / 6.);
+                }
+            };
+
+            let mut q1 = ys[i].clone();
+            let mut q2 = ys[i].clone();
+            for s in 0..5 {
+                euler(&mut q1, s as f64 / 6.);
+            }
+            for d in 0..dof {
+                *q2.get_mut(d) = q2.get(d) * (1. / 25.) + q1.get(d) * (9. / 25.);
+                *q1.get_mut(d) = q2.get(d) * 15. + q1.get(d) * -5.;
+            }
+            for s in 2..6 {
+                euler(&mut q1, s as f64 / 6.);
+            }
+            self.rhs_into(&mut k, t + dt, &q1);
+            for d in 0..dof {
+                *q1.get_mut(d) = q2.get(d) + q1.get(d) * 0.6 + k.get(d) * (dt / 10.);
+            }
+            ys.push(q1);
+            self.count(|stats| stats.accepted_steps += 1);
+        }
+
+        let stats = self.stats() - start;
+        Ok(OdeSolution::new(self.tspan, ys).with_stats(stats))
+    }
+
+    pub fn ode21(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
         self.oderk_adapt(&ButcherTableau::rk21(), opts)
     }
 
-    pub fn ode23(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    pub fn ode23(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode23)?;
         self.oderk_adapt(&ButcherTableau::rk23(), opts)
     }
 
-    pub fn ode4(self) -> OdeSolution<f64, Y> {
+    /// Solve the problem using the Bogacki–Shampine 3(2) pair.
+    ///
+    /// Requires fewer function evaluations per step than the higher order pairs,
+    /// which pays off for loose tolerances.
+    pub fn bs3(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Bs3)?;
+        self.oderk_adapt(&ButcherTableau::bs3(), opts)
+    }
+
+    pub fn ode4(self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode4)?;
+        self.check_callbacks(Ode::Ode4)?;
         self.oderk_fixed(&ButcherTableau::rk4())
     }
 
-    pub fn ode45(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    pub fn ode45(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
         self.ode45_dp(opts)
     }
 
-    pub fn ode45_dp(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    /// Solve the problem using the Dormand–Prince 5(4) pair.
+    ///
+    /// Output at the requested times is computed with a 4th order continuous extension.
+    pub fn ode45_dp(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode45)?;
         self.oderk_adapt(&ButcherTableau::dopri5(), opts)
     }
 
-    pub fn ode45_fe(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    pub fn ode45_fe(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode45fe)?;
         self.oderk_adapt(&ButcherTableau::rk45(), opts)
     }
 
-    pub fn ode78(&self, opts: OdeOptionMap) -> Result<OdeSolution<f64, Y>, OdeError> {
+    /// Solve the problem using the Cash–Karp 4(5) pair.
+    pub fn ode45_ck(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode45ck)?;
+        self.oderk_adapt(&ButcherTableau::cash_karp(), opts)
+    }
+
+    /// Solve the problem using the Tsitouras 5(4) pair.
+    ///
+    /// Generally more efficient than the classic 5(4) pairs for non-stiff problems.
+    /// Output at the requested times is computed with a 4th order continuous extension.
+    pub fn tsit5(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Tsit5)?;
+        self.oderk_adapt(&ButcherTableau::tsit5(), opts)
+    }
+
+    /// Solve the problem with one of the embedded explicit Runge–Kutta methods, storing
+    /// only `save(t, y)` instead of the full states, e.g. a few components of a large
+    /// system selected with [`OdeProblem::save_idxs`].
+    ///
+    /// The full system is integrated as by the method itself, but the solution holds no
+    /// continuous output. Returns an error for the other methods.
+    pub fn solve_saving<Z, P>(
+        &self,
+        ode: Ode,
+        opts: ExplicitRkOptions,
+        save: P,
+    ) -> Result<OdeSolution<f64, Z>, OdeError>
+    where
+        Z: OdeType,
+        P: Fn(f64, &Y) -> Z,
+    {
+        let (solution, _) = match ode {
+            Ode::Ode23 => self.oderk_adapt_saving(&ButcherTableau::rk23(), opts, save),
+            Ode::Bs3 => self.oderk_adapt_saving(&ButcherTableau::bs3(), opts, save),
+            Ode::Ode45 => self.oderk_adapt_saving(&ButcherTableau::dopri5(), opts, save),
+            Ode::Ode45fe => self.oderk_adapt_saving(&ButcherTableau::rk45(), opts, save),
+            Ode::Ode45ck => self.oderk_adapt_saving(&ButcherTableau::cash_karp(), opts, save),
+            Ode::Ode78 => self.oderk_adapt_saving(&ButcherTableau::feh78(), opts, save),
+            Ode::Tsit5 => self.oderk_adapt_saving(&ButcherTableau::tsit5(), opts, save),
+            Ode::Vern6 => self.oderk_adapt_saving(&ButcherTableau::vern6(), opts, save),
+            Ode::Vern9 => self.oderk_adapt_saving(&ButcherTableau::vern9(), opts, save),
+            method => {
+                return Err(OdeError::UnsupportedSolver {
+                    method,
+                    feature: "saving projected states",
+                })
+            }
+        }?;
+        Ok(solution)
+    }
+
+    /// A projection for [`OdeProblem::solve_saving`] that keeps the components `idxs` of
+    /// the state.
+    ///
+    /// Returns [`OdeError::InvalidOption`] if an index is out of range for the initial
+    /// value.
+    pub fn save_idxs(
+        &self,
+        idxs: Vec<usize>,
+    ) -> Result<impl Fn(f64, &Y) -> DVector<Y::Item>, OdeError> {
+        let dof = self.y0.dof();
+        if let Some(i) = idxs.iter().find(|&&i| i >= dof) {
+            return Err(OdeError::invalid_option(
+                &format!("save_idxs={:?}", idxs),
+                format!("index {} is out of range for {} components", i, dof),
+            ));
+        }
+        Ok(move |_t, y: &Y| DVector::from_iterator(idxs.len(), idxs.iter().map(|&i| y.get(i))))
+    }
+
+    /// Creates an [`OdeIntegrator`] that advances the problem step by step with the
+    /// embedded method `btab`, output inside a step is computed with the continuous
+    /// extension `dense` if provided, otherwise with the one of the tableau or with
+    /// hermite interpolation if it has none.
+    pub fn integrator<S: Dim, Ops: Into<AdaptiveOptions>>(
+        &self,
+        btab: ButcherTableau<S>,
+        dense: Option<DenseCoeffs>,
+        opts: Ops,
+    ) -> Result<OdeIntegrator<'_, F, Y, S, G, M>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        OdeIntegrator::new(self, btab, dense, opts)
+    }
+
+    /// Creates an [`OdeIntegrator`] with the Tsitouras 5(4) pair and its continuous
+    /// extension.
+    pub fn tsit5_integrator(
+        &self,
+        opts: ExplicitRkOptions,
+    ) -> Result<OdeIntegrator<'_, F, Y, U7, G, M>, OdeError> {
+        self.integrator(ButcherTableau::tsit5(), None, opts)
+    }
+
+    /// Solve the problem using Verner's 6(5) pair.
+    ///
+    /// Output at the requested times is computed with a 5th order continuous extension.
+    pub fn vern6(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Vern6)?;
+        self.oderk_adapt(&ButcherTableau::vern6(), opts)
+    }
+
+    /// Solve the problem using Verner's 9(8) pair, for very tight tolerances.
+    ///
+    /// Output at the requested times is computed with a 7th order continuous extension.
+    pub fn vern9(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Vern9)?;
+        self.oderk_adapt(&ButcherTableau::vern9(), opts)
+    }
+
+    /// Solve the problem using the Runge–Kutta–Fehlberg 7(8) pair.
+    ///
+    /// Suited for long integrations at tight tolerances that don't need accurate output
+    /// between steps.
+    pub fn ode78(&self, opts: ExplicitRkOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode78)?;
         self.oderk_adapt(&ButcherTableau::feh78(), opts)
     }
 
-    /// Solve with adaptive Runge-Kutta methods.
-    fn oderk_adapt<S: Dim, Ops: Into<AdaptiveOptions>>(
+    /// Solve with the embedded Runge–Kutta method `btab`, which may also be a user
+    /// defined tableau, c.f. [`ButcherTableau::new`].
+    ///
+    /// Output inside a step is computed with the continuous extension of the tableau if
+    /// it has one, otherwise with hermite interpolation. The same interpolant of every
+    /// step is stored in the solution if the [`Dense`](crate::ode::options::Dense) option
+    /// is set. Implicit tableaus are solved with [`OdeProblem::oderk_sdirk`].
+    pub fn oderk_adapt<S: Dim, Ops: Into<AdaptiveOptions>>(
         &self,
         btab: &ButcherTableau<S>,
         opts: Ops,
@@ -194,171 +872,264 @@ where
             + Allocator<f64, S, U2>
             + Allocator<f64, S, S>
             + Allocator<f64, S>,
+    {
+        if btab.is_implicit() {
+            return self.oderk_sdirk(btab, opts);
+        }
+        let (solution, output) = self.oderk_adapt_saving(btab, opts, |_t, y| y.clone())?;
+        Ok(solution.with_interpolant(output))
+    }
+
+    /// Solve with adaptive Runge-Kutta methods like [`OdeProblem::oderk_adapt`],
+    /// storing `save(t, y)` instead of the states.
+    ///
+    /// The continuous output of the steps is returned separately as it interpolates the
+    /// states.
+    #[allow(clippy::type_complexity)]
+    fn oderk_adapt_saving<S, Ops, Z, P>(
+        &self,
+        btab: &ButcherTableau<S>,
+        opts: Ops,
+        save: P,
+    ) -> Result<(OdeSolution<f64, Z>, Option<DenseOutput<Y>>), OdeError>
+    where
+        S: Dim,
+        Ops: Into<AdaptiveOptions>,
+        Z: OdeType,
+        P: Fn(f64, &Y) -> Z,
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
     {
         if !btab.is_adaptive() {
-            return Err(OdeError::InvalidButcherTableauWeightType {
-                expected: WeightType::Adaptive,
-                found: WeightType::Explicit,
-            });
+            return Err(OdeError::not_embedded());
         }
 
         if self.tspan.is_empty() {
             // nothing to solve
-            return Ok(OdeSolution::default());
+            return Ok((OdeSolution::default(), None));
         }
 
-        let mut t = self.tspan[0];
-        let tend = self.tspan[self.tspan.len() - 1];
-        let opts = opts.into();
-        let minstep = opts
-            .minstep
-            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
-
-        let maxstep = opts
-            .maxstep
-            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
-
-        let reltol = opts.reltol.0;
-        let abstol = opts.abstol.0;
-
-        let init = self.hinit(&self.y0, t, tend, btab.symbol.order().min(), reltol, abstol)?;
+        let integrator = self.integrator(btab.clone(), None, opts)?;
+        self.integrate_saving(integrator, save)
+    }
 
-        let mut dt = if opts.initstep.0 != 0. {
-            if (signum(opts.initstep.0) - init.tdir).abs() < std::f64::EPSILON {
-                opts.initstep.0
-            } else {
-                return Err(OdeError::InvalidInitstep);
-            }
+    /// Integrates the problem with `integrator` from its start to its end, storing
+    /// `save(t, y)` at the output points of its options.
+    #[allow(clippy::type_complexity)]
+    fn integrate_saving<S, Z, P>(
+        &self,
+        mut integrator: OdeIntegrator<'_, F, Y, S, G, M>,
+        save: P,
+    ) -> Result<(OdeSolution<f64, Z>, Option<DenseOutput<Y>>), OdeError>
+    where
+        S: Dim,
+        Z: OdeType,
+        P: Fn(f64, &Y) -> Z,
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        let opts = integrator.options();
+        let saveat = !opts.saveat.is_empty();
+        let specified = saveat || opts.points == Points::Specified;
+        let points = opts.points.clone();
+        let mut outputs = if saveat {
+            opts.saveat.0.clone()
         } else {
-            init.h
+            self.tspan.clone()
         };
+        let (t0, tend) = (integrator.t(), integrator.tend());
+        let tdir = signum(tend - t0);
+        if saveat {
+            outputs.retain(|&ts| tdir * t0 <= tdir * ts && tdir * ts <= tdir * tend);
+            outputs.sort_by(|a, b| (tdir * a).total_cmp(&(tdir * b)));
+            outputs.dedup();
+        }
 
-        let mut timeout = 0usize;
-        let order = btab.symbol.order().min();
-        let mut diagnostics = Diagnostics::default();
-        let norm = opts.norm.0;
-
-        let mut last_step = (t + dt - tend).abs() <= std::f64::EPSILON;
-
-        let mut tspan: Vec<f64> = Vec::with_capacity(self.tspan.len());
-        tspan.push(t);
-
+        // the initial value is only stored if requested by saveat
+        let mut iter_fixed = if saveat {
+            outputs.iter().take_while(|&&ts| ts == t0).count()
+        } else {
+            1
+        };
+        let mut tspan: Vec<f64> = Vec::with_capacity(outputs.len());
         // store for the computed values
-        let mut ys = Vec::with_capacity(self.tspan.len());
-        ys.push(self.y0.clone());
-
-        let mut coeff = CoefficientPoint::new(init.f0.clone(), self.y0.clone());
+        let mut ys = Vec::with_capacity(outputs.len());
+        if iter_fixed > 0 && points != Points::Last {
+            tspan.push(t0);
+            ys.push(save(t0, &self.y0));
+        }
 
-        let mut iter_fixed = 1usize;
         // integration loop
-        loop {
-            let coeffs = self.calc_coefficients(btab, t, coeff.clone(), dt);
-            let y = ys[ys.len() - 1].clone();
-
-            let (ytrial, yerr) = self.embedded_step(&y, &coeffs, t, dt, btab)?;
-
-            // check error and find a new step size
-            let step = self.stepsize_hw92(
-                dt, init.tdir, &y, &ytrial, yerr, order, timeout, abstol, reltol, maxstep,
-            );
-            timeout = step.timeout_ctn;
-
-            if step.err < 1. {
-                // accept step
-                diagnostics.accepted_steps += 1;
-
-                let f0 = &coeffs[0].k;
-                let f1 = if btab.is_first_same_as_last() {
-                    coeffs[btab.nstages() - 1].k.clone()
-                } else {
-                    (self.f)(t + dt, &ytrial)
-                };
+        let mut steps = 0;
+        while integrator.step()? {
+            steps += 1;
+            let t = integrator.tprev().unwrap_or_default();
+            let tnew = integrator.t();
+            // a terminated integration ends before the remaining output points
+            let last_step = integrator.is_finished() && tnew == integrator.tend();
+            let event = integrator.event().cloned();
+            let mut interp = |tquery: f64| {
+                let y = integrator
+                    .interpolate(tquery)
+                    .unwrap_or_else(|| integrator.y().clone());
+                save(tquery, &y)
+            };
 
-                // interpolate onto given output points
-                if Points::Specified == opts.points {
-                    while iter_fixed < self.tspan.len()
-                        && (init.tdir * self.tspan[iter_fixed] < init.tdir * (t + dt) || last_step)
-                    {
-                        let yout = self.hermite_interp(
-                            self.tspan[iter_fixed],
-                            t,
-                            dt,
-                            &y,
-                            &ytrial,
-                            f0,
-                            &f1,
-                        );
-                        ys.push(yout);
-                        tspan.push(self.tspan[iter_fixed]);
-                        iter_fixed += 1;
-                    }
-                } else {
-                    // store at all new times which are < t+dt
-                    while iter_fixed < self.tspan.len()
-                        && init.tdir * t < init.tdir * self.tspan[iter_fixed]
-                        && init.tdir * self.tspan[iter_fixed] < init.tdir * (t + dt)
-                    {
-                        let yout = self.hermite_interp(
-                            self.tspan[iter_fixed],
-                            t,
-                            dt,
-                            &y,
-                            &ytrial,
-                            f0,
-                            &f1,
-                        );
-                        ys.push(yout);
-                        tspan.push(self.tspan[iter_fixed]);
-                        iter_fixed += 1;
+            // interpolate onto given output points
+            if specified {
+                while iter_fixed < outputs.len()
+                    && (tdir * outputs[iter_fixed] < tdir * tnew || last_step)
+                {
+                    ys.push(interp(outputs[iter_fixed]));
+                    tspan.push(outputs[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                if integrator.retcode() == ReturnCode::Terminated {
+                    ys.push(save(tnew, integrator.y()));
+                    tspan.push(tnew);
+                }
+            } else {
+                // store at all new times which are < tnew
+                while points.saves_tspan()
+                    && iter_fixed < outputs.len()
+                    && tdir * t < tdir * outputs[iter_fixed]
+                    && tdir * outputs[iter_fixed] < tdir * tnew
+                {
+                    ys.push(interp(outputs[iter_fixed]));
+                    tspan.push(outputs[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, and the state before an event
+                if points.saves_step(steps) {
+                    if let Some(event) = event {
+                        ys.push(save(event.t, &event.y));
+                        tspan.push(event.t);
                     }
-                    // also store every step taken
-                    ys.push(ytrial.clone());
-                    tspan.push(t + dt);
+                    ys.push(save(tnew, integrator.y()));
+                    tspan.push(tnew);
                 }
+            }
+        }
+        if !specified && tspan.last() != Some(&integrator.t()) {
+            // the final state of the sparse output modes
+            tspan.push(integrator.t());
+            ys.push(save(integrator.t(), integrator.y()));
+        }
 
-                coeff = CoefficientPoint::new(f1, ytrial);
+        let solution = OdeSolution::new(tspan, ys)
+            .with_retcode(integrator.retcode())
+            .with_stats(integrator.stats())
+            .with_stiff_at(integrator.stiff_at());
+        Ok((solution, integrator.take_output()))
+    }
 
-                // break if this was the last step
-                if last_step {
-                    break;
-                }
+    /// Creates an [`OdeIntegrator`] with the Dormand–Prince 8(5,3) method, c.f.
+    /// [`OdeProblem::dop853`].
+    pub fn dop853_integrator(
+        &self,
+        opts: Dop853Options,
+    ) -> Result<OdeIntegrator<'_, F, Y, U12, G, M>, OdeError> {
+        OdeIntegrator::dop853(self, opts)
+    }
 
-                // update t to the time at the end of current step:
-                t += dt;
-                dt = step.dt;
+    /// Solve with the Dormand–Prince 8(5,3) method.
+    ///
+    /// The local error is estimated by combining the embedded 5th and 3rd order
+    /// solutions as done by Hairer's `DOP853`, output at the requested times of
+    /// `tspan` is computed with the 7th order continuous extension of the method. The
+    /// problem is integrated by an [`OdeIntegrator`] like [`OdeProblem::tsit5`], which
+    /// also locates the events of the callbacks.
+    pub fn dop853(&self, opts: Dop853Options) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Dop853)?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+        let integrator = self.dop853_integrator(opts)?;
+        let (solution, output) = self.integrate_saving(integrator, |_t, y| y.clone())?;
+        Ok(solution.with_interpolant(output))
+    }
 
-                // Hit end point exactly if next step within 1% of end
-                if init.tdir * (t + dt + dt / 100.) >= init.tdir * tend {
-                    dt = tend - t;
-                    // next step is the last, if it succeeds
-                    last_step = true;
-                }
-            } else if step.dt.abs() < minstep {
-                // minimum step size reached
-                break;
-            } else {
-                // redo step with smaller dt
-                diagnostics.rejected_steps += 1;
-                last_step = false;
-                dt = step.dt;
-                timeout = *StepTimeout::default();
+    /// Computes the coefficients of the 7th order continuous extension
+    /// of an accepted step of the Dormand–Prince 8(5,3) method.
+    ///
+    /// `ks` are the 12 stages of the step, the evaluation of three additional stages is
+    /// required.
+    pub(crate) fn dop853_dense(
+        &self,
+        dense: &Dop853Coeffs,
+        t: f64,
+        dt: f64,
+        y: &Y,
+        ytrial: &Y,
+        ks: &[Y],
+        f1: &Y,
+    ) -> Vec<Y> {
+        // all 16 stages, the 13th stage is the derivative at the end of the step
+        let mut ks = ks.to_vec();
+        ks.push(f1.clone());
+        for s in 0..dense.c_dense.nrows() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate() {
+                yi.axpy(dense.a_dense[(s, j)] * dt, k);
             }
+            let tn = t + dense.c_dense[s] * dt;
+            ks.push(self.rhs(tn, &yi));
         }
 
-        Ok(OdeSolution {
-            yout: ys,
-            tout: tspan,
-        })
+        let mut zero = y.clone();
+        zero.set_zero();
+        let mut rcont = vec![zero; 7];
+        let f0 = &ks[0];
+        for d in 0..y.dof() {
+            let dy = ytrial.get(d) - y.get(d);
+            rcont[0].insert(d, dy);
+            rcont[1].insert(d, f0.get(d) * dt - dy);
+            rcont[2].insert(d, dy * 2. - (f1.get(d) + f0.get(d)) * dt);
+            for row in 0..dense.d.nrows() {
+                let mut r = T::zero();
+                for (j, k) in ks.iter().enumerate() {
+                    r += k.get(d) * dense.d[(row, j)];
+                }
+                rcont[3 + row].insert(d, r * dt);
+            }
+        }
+        rcont
     }
 
-    /// Solve with fixed step Runge-Kutta methods.
-    fn oderk_fixed<S: Dim>(self, btab: &ButcherTableau<S>) -> OdeSolution<f64, Y>
+    /// Solve with the fixed step Runge–Kutta method `btab`, stepping from one time
+    /// stamp of `tspan` to the next, c.f. [`ButcherTableau::new`] for user defined
+    /// tableaus.
+    ///
+    /// Implicit tableaus are solved like with [`OdeProblem::oderk_implicit`], a step that
+    /// fails ends the solution with [`ReturnCode::Failure`].
+    pub fn oderk_fixed<S: Dim>(
+        self,
+        btab: &ButcherTableau<S>,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
     where
         DefaultAllocator: Allocator<f64, U1, S>
             + Allocator<f64, S, U2>
             + Allocator<f64, S, S>
             + Allocator<f64, S>,
     {
+        self.check_mass_of("oderk_fixed")?;
+        self.check_callbacks_of("oderk_fixed")?;
+        if btab.is_implicit() {
+            return Ok(match self.implicit_fixed(btab) {
+                (solution, Some(_)) => solution.with_retcode(ReturnCode::Failure),
+                (solution, None) => solution,
+            });
+        }
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+        let start = self.stats();
         // store for the computed values
         let mut ys = Vec::with_capacity(self.tspan.len());
 
@@ -368,51 +1139,66 @@ where
         // the dimension of the solution type, eg. Vec3
         let dof = self.y0.dof();
 
+        // the stages are evaluated into the ones of the previous step, the last stage of
+        // a first same as last tableau is the first of the next step
+        let fsal = btab.is_first_same_as_last();
+        let mut coeffs = CoefficientMap::with_capacity(btab.nstages());
+        coeffs.push(CoefficientPoint::new(self.y0.clone(), self.y0.clone()));
         for i in 0..self.tspan.len() - 1 {
             let dt = self.tspan[i + 1] - self.tspan[i];
             let mut yi = ys[i].clone();
+            if fsal && i > 0 {
+                let last = coeffs.len() - 1;
+                coeffs.swap(0, last);
+            } else {
+                self.rhs_into(&mut coeffs[0].k, self.tspan[i], &yi);
+            }
+            coeffs[0].y.clone_from(&yi);
+            self.calc_coefficients_into(btab, self.tspan[i], dt, &mut coeffs);
 
             // all weights
             let b = btab.b.as_slice();
             // loop over all stages and k values of the butcher tableau
-            for (s, k) in self
-                .calc_coefficients(
-                    btab,
-                    self.tspan[i],
-                    CoefficientPoint::new((self.f)(self.tspan[i], &yi), yi.clone()),
-                    dt,
-                )
-                .ks()
-                .enumerate()
-            {
+            for (s, k) in coeffs.ks().enumerate() {
                 // adapt in all dimensions
                 for d in 0..dof {
                     *yi.get_mut(d) += k.get(d) * b[s] * dt;
                 }
             }
             ys.push(yi);
+            self.count(|stats| stats.accepted_steps += 1);
         }
 
-        OdeSolution {
-            tout: self.tspan,
-            yout: ys,
-        }
+        let stats = self.stats() - start;
+        Ok(OdeSolution::new(self.tspan, ys).with_stats(stats))
     }
 
     /// Solve stiff systems based on a modified Rosenbrock triple
-    pub fn ode23s<Ops: Into<AdaptiveOptions>>(
-        &self,
-        opts: Ops,
-    ) -> Result<OdeSolution<f64, Y>, OdeError> {
+    ///
+    /// Same as [`OdeProblem::rosenbrock23`].
+    pub fn ode23s(&self, opts: BasicOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode23s)?;
+        self.check_callbacks(Ode::Ode23s)?;
+        self.rosenbrock23(opts)
+    }
+
+    /// Solve stiff systems with the modified Rosenbrock triple of order 2(3) of
+    /// Shampine & Reichelt, "The MATLAB ODE Suite", 1997.
+    ///
+    /// Each step requires a single jacobian, one LU factorization and three linear
+    /// solves, but no newton iteration.
+    pub fn rosenbrock23(&self, opts: BasicOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Rosenbrock23)?;
+        self.check_callbacks(Ode::Rosenbrock23)?;
         if self.tspan.is_empty() {
             // nothing to solve
             return Ok(OdeSolution::default());
         }
+        let start = self.stats();
         let mut t = self.tspan[0];
         let tfinal = self.tspan[self.tspan.len() - 1];
-        let opts = opts.into();
-        let reltol = opts.reltol.0;
-        let abstol = opts.abstol.0;
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
         let minstep = opts
             .minstep
             .map_or_else(|| (tfinal - t).abs() / 1e18, |step| step.0);
@@ -426,26 +1212,26 @@ where
 
         let init = if opts.initstep.0 == 0. {
             // initial guess at a step size
-            self.hinit(&self.y0, t, tfinal, 3, reltol, abstol)?
+            self.hinit(&self.y0, t, tfinal, 3, &reltol, &abstol)?
         } else {
             InitialHint {
                 h: opts.initstep.0,
                 tdir: (tfinal - t).signum(),
-                f0: (self.f)(t, &self.y0),
+                f0: self.rhs(t, &self.y0),
             }
         };
         let mut h = init.tdir * init.h.abs().min(maxstep);
 
         let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
-        // first output time
-        tout.push(t);
-
         let mut yout = Vec::with_capacity(self.tspan.len());
-        // first output solution
-        yout.push(self.y0.clone());
+        if opts.points != Points::Last {
+            // first output time and solution
+            tout.push(t);
+            yout.push(self.y0.clone());
+        }
 
         // get Jacobian of F wrt y0
-        let mut jac = self.fdjacobian(t, &self.y0);
+        let mut jac = self.jacobian(t, &self.y0)?;
 
         let (m, n) = jac.shape();
         let identity = DMatrix::<T>::identity(m, n);
@@ -453,65 +1239,64 @@ where
         let mut y = self.y0.clone();
         let mut f0 = DVector::from_iterator(y.dof(), init.f0.ode_iter());
 
-        while (t - tfinal).abs() > 0. && minstep < h.abs() {
+        let mut iters = 0;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+        while (t - tfinal).abs() > 0. && minstep < h.abs() && iters < *opts.maxiters {
+            iters += 1;
             if (t - tfinal).abs() < h.abs() {
                 h = tfinal - t;
             }
-            let mut w = &identity - &jac * (T::one() * (h * d));
-            if jac.len() != 1 {
-                //  W = lu( I - h*d*J )
-                w = w.lu().lu_internal().clone();
+            //  W = lu( I - h*d*J )
+            let w =
+                Factorization::dense(&identity - &jac * (T::one() * (h * d)), self.linear_solver)?;
+            self.count(|stats| stats.num_decomp += 1);
+            let solve = |b: DVector<T>| {
+                self.count(|stats| stats.num_linsolve += 1);
+                w.solve(&b).ok_or(OdeError::InvalidMatrix)
             };
 
-            // approximate time-derivative of f
-            let mut fdt = DVector::from_iterator(y.dof(), (self.f)(t + h / 100., &y).ode_iter());
-
-            for i in 0..fdt.dof() {
-                let fdti = fdt[i] - f0[i];
-                fdt[i] = fdti * ((h * d) / (h / 100.));
-            }
+            // time-derivative of f
+            let fdt = DVector::from_iterator(y.dof(), self.tgrad(t, &y).ode_iter())
+                * (T::one() * (h * d));
 
             // modified Rosenbrock formula: inv(W) * (F0 + T)
-            let w_inv = w.try_inverse().ok_or(OdeError::InvalidMatrix)?;
-
-            let k1 = &w_inv * (&f0 + &fdt);
+            let k1 = solve(&f0 + &fdt)?;
 
             let mut f1y = y.clone();
             for i in 0..y.dof() {
                 *f1y.get_mut(i) += k1[i] * 0.5 * h;
             }
 
-            let f1 = DVector::from_iterator(y.dof(), (self.f)(t + 0.5 * h, &f1y).ode_iter());
-            let k2 = &w_inv * (&f1 - &k1) + &k1;
+            let f1 = DVector::from_iterator(y.dof(), self.rhs(t + 0.5 * h, &f1y).ode_iter());
+            let k2 = solve(&f1 - &k1)? + &k1;
 
             let mut ynew = y.clone();
             for i in 0..ynew.dof() {
                 *ynew.get_mut(i) += k2[i] * h;
             }
 
-            let f2 = DVector::from_iterator(y.dof(), (self.f)(t + h, &ynew).ode_iter());
+            let f2 = DVector::from_iterator(y.dof(), self.rhs(t + h, &ynew).ode_iter());
 
-            let k3 = &w_inv
-                * (&f2 - ((&k2 - &f1) * (T::one() * e32)) - ((&k1 - &f0) * (T::one() * 2.)) + &fdt);
+            let k3 = solve(
+                &f2 - ((&k2 - &f1) * (T::one() * e32)) - ((&k1 - &f0) * (T::one() * 2.)) + &fdt,
+            )?;
 
-            // error estimate
+            // error estimate, scaled by the allowable error of each component
             let kerr = &k1 - (&k2 * (T::one() * 2.)) + &k3;
-            // TODO impl Pnorm for Iterator type
             let mut etmp = y.clone();
             for i in 0..etmp.dof() {
-                etmp.insert(i, kerr[i]);
+                etmp.insert(i, kerr[i] * (h.abs() / 6.));
             }
-            let err = etmp.pnorm(PNorm::default()) * (h.abs() / 6.);
+            let err = self.local_error(&y, &ynew, &etmp, &abstol, &reltol);
 
-            // allowable error
-            let delta = (y.pnorm(PNorm::default()).max(ynew.pnorm(PNorm::default())) * reltol)
-                .max(T::one() * abstol);
-
-            if err <= delta {
+            if err <= 1. {
                 // only points in tspan are requested
                 // -> find relevant points in (t,t+h]
-                for toi in &self.tspan {
-                    if *toi > t && *toi <= t + h {
+                let saves_tspan = opts.points.saves_tspan();
+                for toi in self.tspan.iter().filter(|_| saves_tspan) {
+                    if init.tdir * *toi > init.tdir * t && init.tdir * *toi <= init.tdir * (t + h) {
                         // rescale to (0,1]
                         let s = (*toi - t) / h;
                         // use interpolation formula to get solutions at t=toi
@@ -527,10 +1312,20 @@ where
                     }
                 }
 
-                if Points::All == opts.points
-                    && (tout[tout.len() - 1] - (t + h)).abs() > std::f64::EPSILON
+                if provisional {
+                    // superseded by this step
+                    tout.pop();
+                    yout.pop();
+                }
+                steps += 1;
+                if Points::Specified != opts.points
+                    && tout
+                        .last()
+                        .is_none_or(|&tlast| (tlast - (t + h)).abs() > f64::EPSILON)
                 {
-                    // add the intermediate points
+                    // add the intermediate points, the sparse output modes only keep the
+                    // latest one until it is superseded
+                    provisional = !opts.points.saves_step(steps);
                     tout.push(t + h);
                     yout.push(ynew.clone());
                 }
@@ -540,14 +1335,26 @@ where
                 // use FSAL property
                 f0 = f2;
                 // get Jacobian of F wrt y for new solution
-                jac = self.fdjacobian(t, &y);
+                jac = self.jacobian(t, &y)?;
+                self.count(|stats| stats.accepted_steps += 1);
+            } else {
+                self.count(|stats| stats.rejected_steps += 1);
             }
 
-            let r: f64 = (delta / err).into();
-            h = maxstep.min(r.powf(1. / 3.) * h.abs() * 0.8) * init.tdir;
+            h = maxstep.min((1. / err).powf(1. / 3.) * h.abs() * 0.8) * init.tdir;
         }
 
-        Ok(OdeSolution { yout, tout })
+        let retcode = if t == tfinal {
+            ReturnCode::Success
+        } else if iters >= *opts.maxiters {
+            ReturnCode::MaxIters
+        } else {
+            // minimum step size reached
+            ReturnCode::DtLessThanMin
+        };
+        Ok(OdeSolution::new(tout, yout)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start))
     }
 
     /// Solve stiff differential equations, Rosenbrock method with provided coefficients.
@@ -558,11 +1365,14 @@ where
     where
         DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S>,
     {
+        self.check_mass_of("oderosenbrock")?;
+        self.check_callbacks_of("oderosenbrock")?;
         if self.tspan.is_empty() {
             // nothing to solve
             return Ok(OdeSolution::default());
         }
 
+        let start = self.stats();
         let h = diff(&self.tspan);
 
         let mut x = Vec::with_capacity(self.tspan.len());
@@ -572,7 +1382,7 @@ where
         for (solstep, hs) in h.iter().enumerate() {
             let ts = self.tspan[solstep];
             let xs = x[solstep].clone();
-            let dfdx = self.fdjacobian(ts, &xs);
+            let dfdx = self.jacobian(ts, &xs)?;
 
             let (m, n) = dfdx.shape();
             let v = DMatrix::from_diagonal_element(m, n, T::one() * (1. / (coeffs.gamma * hs)));
@@ -584,9 +1394,10 @@ where
             let mut g = Vec::with_capacity(coeffs.a.nrows());
 
             let yg =
-                DVector::from_iterator(xs.dof(), (self.f)(ts + coeffs.b[0] * hs, &xs).ode_iter());
+                DVector::from_iterator(xs.dof(), self.rhs(ts + coeffs.b[0] * hs, &xs).ode_iter());
 
             let jac_yg = &jac_inv * &yg;
+            self.count(|stats| stats.num_linsolve += 1);
 
             // convert back to odetype
             let mut g1 = xs.clone();
@@ -605,18 +1416,19 @@ where
                 let mut dx = next_x.clone();
                 dx.set_zero();
                 let mut df = dx.clone();
-                for (j, gj) in g.iter().enumerate().take(i - 1) {
+                for (j, gj) in g.iter().enumerate().take(i) {
                     for d in 0..dx.dof() {
                         *dx.get_mut(d) += gj.get(d) * coeffs.a[(i, j)];
                         *df.get_mut(d) += gj.get(d) * coeffs.c[(i, j)];
                     }
                 }
                 let next_gvec = &jac_inv
-                    * DVector::from_iterator(
+                    * (DVector::from_iterator(
                         xs.dof(),
-                        (self.f)(ts + coeffs.b[i] * hs, &xs.clone().sum(&dx)).ode_iter(),
-                    )
-                    + DVector::from_iterator(xs.dof(), df.ode_iter().map(|x| x * (1. / hs)));
+                        self.rhs(ts + coeffs.b[i] * hs, &xs.clone().sum(&dx))
+                            .ode_iter(),
+                    ) + DVector::from_iterator(xs.dof(), df.ode_iter().map(|x| x * (1. / hs))));
+                self.count(|stats| stats.num_linsolve += 1);
 
                 // convert back
                 let mut next_g = xs.clone();
@@ -628,296 +1440,2357 @@ where
             }
 
             x.push(next_x);
+            self.count(|stats| stats.accepted_steps += 1);
         }
 
-        Ok(OdeSolution { yout: x, tout: h })
+        Ok(OdeSolution::new(self.tspan.clone(), x).with_stats(self.stats() - start))
     }
 
     /// Solve the problem using the Kaps-Rentrop coefficients.
     pub fn ode4s_kr(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode4skr)?;
+        self.check_callbacks(Ode::Ode4skr)?;
         self.oderosenbrock(RosenbrockCoeffs::kr4())
     }
 
     /// Solve the problem using the Shampine coefficients.
     pub fn ode4s_s(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Ode4ss)?;
+        self.check_callbacks(Ode::Ode4ss)?;
         self.oderosenbrock(RosenbrockCoeffs::s4())
     }
 
-    /// ```latex
-    /// e_{n+1}=h\sum _{i=1}^{s}(b_{i}-b_{i}^{*})k_{i}
-    /// ```
-    /// Panics if the number of stages of the butcher tableau is not equal
-    /// to the length of the coefficients.
-    fn calc_error<S: Dim>(
+    /// Solve stiff problems with a stiffly accurate Rosenbrock method of the Rodas family.
+    ///
+    /// Each step requires the jacobian and the time derivative of the RHS, a single LU
+    /// factorization and one linear solve per stage. Output inside a step is given by
+    /// the stiffly accurate continuous output of the method.
+    ///
+    /// A mass matrix is evaluated once at the start of each step, so the order is only
+    /// retained for constant mass matrices. Singular mass matrices are
+    /// supported since the methods are stiffly accurate.
+    pub fn oderodas<S: Dim, Ops: Into<AdaptiveOptions>>(
         &self,
-        coeffs: &CoefficientMap<Y>,
-        btab: &ButcherTableau<S>,
-        dt: f64,
-    ) -> Result<Y, OdeError>
+        coeffs: &RodasCoeffs<S>,
+        order: usize,
+        opts: Ops,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
     where
-        DefaultAllocator: Allocator<f64, U1, S>
-            + Allocator<f64, S, U2>
-            + Allocator<f64, S, S>
-            + Allocator<f64, S>,
+        DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
     {
-        assert_eq!(btab.nstages(), coeffs.len());
-
-        // get copy of the Odetype and ensure default values
-        let mut err = coeffs[0].k.clone();
-        err.set_zero();
+        let mut method = RosenbrockMethod::new(self, coeffs.clone(), order);
+        self.oderk_adapt_method(opts, &mut method)
+    }
 
-        if let Weights::Adaptive(b) = &btab.b {
-            for (s, k) in coeffs.ks().enumerate() {
-                // adapt in every dimension
-                for d in 0..err.dof() {
-                    // subtract b_1s from b_0s
-                    let weight_err = b[(s, 0)] - b[(s, 1)];
-                    *err.get_mut(d) += k.get(d) * weight_err;
-                }
-            }
-            // multiply with stepsize
-            for d in 0..err.dof() {
-                err.insert(d, err.get(d) * dt);
-            }
+    /// Solve stiff problems using the Rodas4 Rosenbrock method of order 4.
+    pub fn rodas4(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_callbacks(Ode::Rodas4)?;
+        self.oderodas(&RodasCoeffs::rodas4(), 3, opts)
+    }
 
-            Ok(err)
-        } else {
-            Err(OdeError::InvalidButcherTableauWeightType {
-                expected: WeightType::Adaptive,
-                found: WeightType::Explicit,
-            })
-        }
+    /// Solve stiff problems using the Rodas5 Rosenbrock method of order 5.
+    pub fn rodas5(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_callbacks(Ode::Rodas5)?;
+        self.oderodas(&RodasCoeffs::rodas5(), 4, opts)
     }
 
-    /// Calculates all coefficients values for a given value `yn` at a specific time `t`.
+    /// Solve problems that may be stiff on parts of the interval, starting with
+    /// [`OdeProblem::tsit5`] and switching to [`OdeProblem::rodas5`] while the problem
+    /// appears stiff, c.f. [`Composite`].
     ///
-    /// Creates an `CoefficientMap` with the calculated coefficient `k` and their
-    /// approximations `y` of size `S`, the number of stages of the butcher tableau
-    pub fn calc_coefficients<S: Dim>(
-        &self,
-        btab: &ButcherTableau<S>,
-        t: f64,
-        init: CoefficientPoint<Y>,
-        dt: f64,
-    ) -> CoefficientMap<Y>
-    where
-        DefaultAllocator: Allocator<f64, U1, S>
-            + Allocator<f64, S, U2>
-            + Allocator<f64, S, S>
-            + Allocator<f64, S>,
-    {
-        let mut coeffs = CoefficientMap::with_capacity(btab.nstages());
-
-        coeffs.push(init);
-
-        // a coeffs in first row are zero
-        for row in 1..btab.nstages() {
-            // need a fresh y clone
-            let mut yi = coeffs[0].y.clone();
-
-            for (col, k) in coeffs.ks().enumerate() {
-                // adapt in all dimensions
-                for d in 0..yi.dof() {
-                    *yi.get_mut(d) += k.get(d) * (btab.a[(row, col)] * dt);
-                }
-            }
-
-            let tn = t + btab.c[row] * dt;
-            // compute the next k value
-            coeffs.push(CoefficientPoint::new((self.f)(tn, &yi), yi));
-        }
-
-        coeffs
+    /// The solution records the time of the first switch to the stiff method.
+    pub fn auto_tsit5(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::AutoTsit5)?;
+        self.check_callbacks(Ode::AutoTsit5)?;
+        let mut method = Composite::new(
+            ExplicitMethod::new(self, ButcherTableau::tsit5()),
+            RosenbrockMethod::new(self, RodasCoeffs::rodas5(), 4),
+        );
+        let solution = self.oderk_adapt_method(opts, &mut method)?;
+        Ok(solution.with_stiff_at(method.switches().first().copied()))
     }
 
-    /// Does one embedded R-K step updating ytrial, yerr and ks.
-    fn embedded_step<S: Dim>(
-        &self,
-        yn: &Y,
-        coeffs: &CoefficientMap<Y>,
-        _t: f64,
-        dt: f64,
-        btab: &ButcherTableau<S>,
-    ) -> Result<(Y, Y), OdeError>
-    where
-        DefaultAllocator: Allocator<f64, U1, S>
-            + Allocator<f64, S, U2>
-            + Allocator<f64, S, S>
-            + Allocator<f64, S>,
-    {
-        // trial solution at time t+dt
-        let mut ytrial = yn.clone();
-        ytrial.set_zero();
-
-        // error of trial solution
-        let mut yerr = ytrial.clone();
+    /// Solve stiff problems with the variable order, variable step size backward
+    /// differentiation formulas of order 1 to 5, or up to
+    /// [`MaxOrder`](crate::ode::options::MaxOrder) if given.
+    ///
+    /// The solution history is kept as modified divided differences that are rescaled
+    /// whenever the step size changes, c.f. Shampine & Reichelt, "The MATLAB ODE Suite",
+    /// 1997. The formula of each step is solved with a simplified newton iteration, the
+    /// jacobian is only reevaluated if the iteration fails to converge. The order is
+    /// adapted after `order + 1` steps of equal size. Output inside a step is given by
+    /// the interpolating polynomial of the history.
+    ///
+    /// A mass matrix, which may be singular, is evaluated at the prediction of each step.
+    pub fn bdf(&self, opts: MultistepOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_callbacks(Ode::Bdf)?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
 
-        if let Weights::Adaptive(b) = &btab.b {
-            for (s, k) in coeffs.ks().enumerate() {
-                for d in 0..yn.dof() {
-                    *ytrial.get_mut(d) += k.get(d) * b[(s, 0)];
-                    *yerr.get_mut(d) += k.get(d) * b[(s, 1)];
-                }
-            }
-            for d in 0..yn.dof() {
-                yerr.insert(d, (ytrial.get(d) - yerr.get(d)) * dt);
-                ytrial.insert(d, yn.get(d) + (ytrial.get(d) * dt));
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let start = self.stats();
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
+        let max_order = opts
+            .max_order
+            .map_or(bdf::MAX_ORDER, |order| order.0.clamp(1, bdf::MAX_ORDER));
+
+        let init = self.hinit(&self.y0, t, tend, 1, &reltol, &abstol)?;
+        let tdir = init.tdir;
+        let mut h_abs = if opts.initstep.0 != 0. {
+            if (signum(opts.initstep.0) - tdir).abs() < f64::EPSILON {
+                opts.initstep.0.abs()
+            } else {
+                return Err(OdeError::InvalidInitstep);
             }
-
-            Ok((ytrial, yerr))
         } else {
-            Err(OdeError::InvalidButcherTableauWeightType {
-                expected: WeightType::Adaptive,
-                found: WeightType::Explicit,
-            })
+            init.h.abs()
         }
-    }
+        .min(maxstep);
 
-    /// For dense output see Hairer & Wanner p.190 using Hermite interpolation.
-    fn hermite_interp(&self, tquery: f64, t: f64, dt: f64, y0: &Y, y1: &Y, f0: &Y, f1: &Y) -> Y {
-        let mut y = y0.clone();
-        let theta = (tquery - t) / dt;
+        let n = self.y0.dof();
+        let to_y = |v: &DVector<T>| {
+            let mut y = self.y0.clone();
+            for d in 0..n {
+                y.insert(d, v[d]);
+            }
+            y
+        };
+        let from_y = |y: &Y| DVector::from_iterator(n, y.ode_iter());
+        let f = |t: f64, v: &DVector<T>| from_y(&self.rhs(t, &to_y(v)));
+        let scale = |y: &DVector<T>| -> Vec<f64> {
+            let y = to_y(y);
+            (0..n)
+                .map(|d| abstol.at(d) + reltol.at(d) * y.modulus_at(d).into())
+                .collect()
+        };
+        // the error tests measure with the norm of the problem, if set
+        let local_error = |err: &DVector<T>, y: &DVector<T>| {
+            let y = to_y(y);
+            self.local_error(&y, &y, &to_y(err), &abstol, &reltol)
+        };
 
-        for i in 0..y0.dof() {
-            let val = (y0.get(i) * (1. - theta) + y1.get(i) * theta)
-                + ((y1.get(i) - y0.get(i)) * (1. - 2. * theta)
-                    + f0.get(i) * (theta - 1.) * dt
-                    + f1.get(i) * theta * dt)
-                    * theta
-                    * (theta - 1.);
+        let coeffs = BdfCoeffs::default();
+        let newton = self
+            .newton
+            .clone()
+            .unwrap_or_else(|| NewtonOptions::bdf(reltol.min()));
+
+        // the initial derivative `M^+ f`, the components of the algebraic equations of a
+        // singular mass matrix are left at zero
+        let mut dy0 = from_y(&init.f0);
+        if self.mass.is_some() {
+            let pinv = self
+                .mass_matrix(t, &self.y0)
+                .pseudo_inverse(T::one() * 1e-12)
+                .map_err(|_| OdeError::InvalidMatrix)?;
+            dy0 = pinv * dy0;
+        }
+        let mut history = DifferenceHistory::new(
+            from_y(&self.y0),
+            dy0 * (T::one() * (h_abs * tdir)),
+            bdf::MAX_ORDER,
+        );
+        let mut order = 1;
+        let mut n_equal_steps = 0;
+        let jacobian = |t, y: &Y| self.jacobian(t, y);
+        let mut jac = JacobianCache::new(newton.reuse);
+        let mut cache = NewtonCache::new(&newton);
 
-            y.insert(i, val);
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(self.y0.clone());
         }
-        y
-    }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        'integration: while tdir * (tend - t) > 0. {
+            if h_abs > maxstep {
+                history.rescale(order, maxstep / h_abs);
+                h_abs = maxstep;
+                n_equal_steps = 0;
+            }
+            let matrix_free = matches!(self.linear_solver, LinearSolverType::Gmres(_));
+            if !matrix_free && jac.reuse(t, &to_y(&history.d[0]), jacobian)?.1 {
+                cache.clear();
+            }
 
-    /// Estimates the error and a new step size following Hairer & Wanner 1992, p167.
-    fn stepsize_hw92(
-        &self,
-        dt: f64,
-        tdir: f64,
-        x0: &Y,
-        xtrial: &Y,
-        mut xerr: Y,
-        order: usize,
-        mut timeout: usize,
-        abstol: f64,
-        reltol: f64,
-        maxstep: f64,
-    ) -> StepHW92 {
-        let fac = 0.8;
-        let _facmax = 5.;
-        let facmin = 0.2;
+            let mut fresh_jac = false;
+            let (t_new, d, safety, error_norm) = loop {
+                if iters >= *opts.maxiters {
+                    retcode = ReturnCode::MaxIters;
+                    break 'integration;
+                }
+                iters += 1;
+                if h_abs < minstep {
+                    // minimum step size reached
+                    retcode = ReturnCode::DtLessThanMin;
+                    break 'integration;
+                }
+                let mut t_new = t + h_abs * tdir;
+                if tdir * (t_new - tend) > 0. {
+                    // hit the end point exactly
+                    t_new = tend;
+                    history.rescale(order, (t_new - t).abs() / h_abs);
+                    h_abs = (t_new - t).abs();
+                    n_equal_steps = 0;
+                }
 
-        for d in 0..x0.dof() {
-            if xtrial.get(d).into().is_nan() {
-                return StepHW92 {
-                    err: 10.,
-                    dt: facmin * dt,
-                    timeout_ctn: *StepTimeout::default(),
+                let ypred = history.predict(order);
+                let psi = coeffs.psi(&history, order);
+                let c = (t_new - t) / coeffs.alpha[order];
+                let mass = self.mass_matrix(t_new, &to_y(&ypred));
+                let solved = match self.linear_solver {
+                    LinearSolverType::Gmres(gmres) => {
+                        let yp = to_y(&ypred);
+                        let jvp = self.jacobian_operator(t_new, &yp);
+                        let preconditioner = self.preconditioner_setup(t_new, &yp)?;
+                        let iteration = JacobianOperator::new(&jvp, gmres)
+                            .preconditioner(preconditioner.as_ref())
+                            .factor(Some(&mass), c, self.linear_solver)?;
+                        bdf::solve_bdf_system(
+                            &f,
+                            t_new,
+                            &ypred,
+                            c,
+                            &psi,
+                            self.mass.as_ref().map(|_| &mass),
+                            &iteration,
+                            &scale(&ypred),
+                            &newton,
+                            &self.stats,
+                        )?
+                    }
+                    _ => loop {
+                        let c_lu = cache.step(c);
+                        let iteration = cache.get_or_factor(
+                            1.,
+                            || jac.get().factor(Some(&mass), c_lu, self.linear_solver),
+                            &self.stats,
+                        )?;
+                        match bdf::solve_bdf_system(
+                            &f,
+                            t_new,
+                            &ypred,
+                            c,
+                            &psi,
+                            self.mass.as_ref().map(|_| &mass),
+                            iteration,
+                            &scale(&ypred),
+                            &newton,
+                            &self.stats,
+                        )? {
+                            None if !fresh_jac => {
+                                // retry with an up to date jacobian
+                                jac.update(t_new, &to_y(&ypred), jacobian)?;
+                                cache.clear();
+                                fresh_jac = true;
+                            }
+                            None if c_lu != c => {
+                                // retry with the iteration matrix of the step size
+                                cache.clear();
+                            }
+                            solved => break solved,
+                        }
+                    },
+                };
+                let (iterations, ynew, d) = match solved {
+                    Some(solved) => {
+                        cache.converged(solved.rate);
+                        (solved.iterations, &ypred + &solved.x, solved.x)
+                    }
+                    None => {
+                        self.count(|stats| stats.rejected_steps += 1);
+                        h_abs *= 0.5;
+                        history.rescale(order, 0.5);
+                        n_equal_steps = 0;
+                        continue;
+                    }
                 };
-            }
-
-            *xerr.get_mut(d) /= x0.get(d).norm1().max(xtrial.get(d).norm1()) * reltol + abstol;
-        }
 
-        let err = xerr.pnorm(PNorm::default()).into();
+                let safety = 0.9 * (2 * newton.maxiter + 1) as f64
+                    / (2 * newton.maxiter + iterations) as f64;
+                let error_norm = local_error(&(&d * (T::one() * coeffs.error_const[order])), &ynew);
+                if error_norm > 1. {
+                    let factor =
+                        bdf::MIN_FACTOR.max(safety * error_norm.powf(-1. / (order + 1) as f64));
+                    self.count(|stats| stats.rejected_steps += 1);
+                    h_abs *= factor;
+                    history.rescale(order, factor);
+                    n_equal_steps = 0;
+                    continue;
+                }
+                break (t_new, d, safety, error_norm);
+            };
 
-        let pow = 1. / (order + 1) as f64;
-        let mut new_dt = maxstep.min(facmin.max(err.powi(-1).powf(pow) * fac) * tdir * dt);
+            self.count(|stats| stats.accepted_steps += 1);
+            if cache.take_rate() > newton.max_rate {
+                // slow convergence, re-evaluate the jacobian for the next step
+                jac.expire();
+            }
+            let t_old = t;
+            t = t_new;
+            n_equal_steps += 1;
+            history.update(order, &d);
+            let y = history.d[0].clone();
+
+            if n_equal_steps > order {
+                // estimate the error at the neighbouring orders and pick the order
+                // that allows the largest step
+                let error_at = |k: usize, dk: &DVector<T>| {
+                    local_error(&(dk * (T::one() * coeffs.error_const[k])), &y)
+                };
+                let error_m = if order > 1 {
+                    error_at(order - 1, &history.d[order])
+                } else {
+                    f64::INFINITY
+                };
+                let error_p = if order < max_order {
+                    error_at(order + 1, &history.d[order + 2])
+                } else {
+                    f64::INFINITY
+                };
+                let factors = [
+                    error_m.powf(-1. / order as f64),
+                    error_norm.powf(-1. / (order + 1) as f64),
+                    error_p.powf(-1. / (order + 2) as f64),
+                ];
+                let (idx, max) =
+                    factors
+                        .iter()
+                        .enumerate()
+                        .fold((0, f64::NEG_INFINITY), |(i, max), (j, f)| {
+                            if *f > max {
+                                (j, *f)
+                            } else {
+                                (i, max)
+                            }
+                        });
+                order = order + idx - 1;
+
+                let factor = bdf::MAX_FACTOR.min(safety * max);
+                h_abs *= factor;
+                history.rescale(order, factor);
+                n_equal_steps = 0;
+            }
 
-        if timeout > 0 {
-            new_dt = new_dt.min(dt);
-            timeout -= 1;
+            let interp = |tquery: f64| to_y(&history.interpolate(order, t, h_abs * tdir, tquery));
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && tdir * self.tspan[iter_fixed] <= tdir * t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && tdir * t_old < tdir * self.tspan[iter_fixed]
+                    && tdir * self.tspan[iter_fixed] < tdir * t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(to_y(&y));
+                tout.push(t);
+            }
         }
 
-        StepHW92 {
-            err,
-            dt: tdir * new_dt,
-            timeout_ctn: timeout,
-        }
+        Ok(OdeSolution::new(tout, yout)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start))
     }
 
-    /// estimator for initial step based on book
-    /// "Solving Ordinary Differential Equations I" by Hairer et al., p.169
-    /// Returns first step, direction of integration and F evaluated at t0
-    fn hinit(
+    /// Solve non-stiff problems with the variable step Adams–Bashforth–Moulton
+    /// predictor–corrector pair of orders `order` and `order + 1`.
+    ///
+    /// Each step predicts with the explicit Adams–Bashforth method, evaluates the RHS,
+    /// corrects with the implicit Adams–Moulton method and evaluates again (PECE), so a
+    /// step costs two evaluations of the RHS regardless of the order. The difference of
+    /// prediction and correction estimates the local error. The derivatives are kept as
+    /// backward differences that are rescaled whenever the step size changes. The
+    /// integration starts at order 1 and raises the order by one each step until `order`
+    /// (at most [`adams::MAX_ORDER`]) is reached. Output inside a step is given by
+    /// hermite interpolation.
+    pub fn odeabm(
         &self,
-        x0: &Y,
-        t0: f64,
-        tend: f64,
         order: usize,
-        reltol: f64,
-        abstol: f64,
-    ) -> Result<InitialHint<Y>, OdeError> {
-        let tdir = signum(tend - t0);
-        if tdir == 0. {
-            return Err(OdeError::ZeroTimeSpan);
+        opts: BasicOptions,
+    ) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass_of("odeabm")?;
+        self.check_callbacks_of("odeabm")?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
         }
 
-        let norm = x0.pnorm(PNorm::InfPos);
-        let one = Y::Item::one();
-        let tau = (norm * reltol).max(one * abstol);
-        let d0 = norm / tau;
-        let f0 = (self.f)(t0, x0);
-        let d1 = f0.pnorm(PNorm::InfPos) / tau;
+        let order = order.clamp(1, adams::MAX_ORDER);
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let start = self.stats();
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
 
-        let h0: f64 = if d0 < one * 1e-5 || d1 < one * 1e-5 {
-            1.0e-6
+        let init = self.hinit(&self.y0, t, tend, 1, &reltol, &abstol)?;
+        let tdir = init.tdir;
+        let mut h_abs = if opts.initstep.0 != 0. {
+            if (signum(opts.initstep.0) - tdir).abs() < f64::EPSILON {
+                opts.initstep.0.abs()
+            } else {
+                return Err(OdeError::InvalidInitstep);
+            }
         } else {
-            0.01 * (d0 / d1).into()
-        };
-
-        // perform Euler step, in every dimension
-        let mut x1 = x0.clone();
-        for d in 0..x1.dof() {
-            *x1.get_mut(d) += f0.get(d) * h0 * tdir;
-        }
-        // estimate second derivative
-        let mut f1_0 = (self.f)(t0 + tdir * h0, &x1);
-        for d in 0..f1_0.dof() {
-            *f1_0.get_mut(d) -= f0.get(d);
+            init.h.abs()
         }
-        let d2 = f1_0.pnorm(PNorm::InfPos) / (tau * h0);
+        .min(maxstep);
 
-        let h1: f64 = if d1.max(d2) < one * 1e-15f64 {
-            1.0e-6f64.max(1.0e-3f64 * h0)
-        } else {
-            let pow = -(2. + d1.max(d2).log10().into()) / ((order + 1) as f64);
-            10f64.powf(pow)
+        let n = self.y0.dof();
+        let to_y = |v: &DVector<T>| {
+            let mut y = self.y0.clone();
+            for d in 0..n {
+                y.insert(d, v[d]);
+            }
+            y
+        };
+        let from_y = |y: &Y| DVector::from_iterator(n, y.ode_iter());
+        // the error tests measure with the norm of the problem, if set
+        let local_error = |err: &DVector<T>, y0: &DVector<T>, y1: &DVector<T>| {
+            self.local_error(&to_y(y0), &to_y(y1), &to_y(err), &abstol, &reltol)
         };
 
-        let h = tdir * h1.min(100. * h0).min(tdir * (tend - t0));
+        let coeffs = AdamsCoeffs::default();
+        let mut y = from_y(&self.y0);
+        let mut history =
+            DifferenceHistory::new(from_y(&init.f0), DVector::zeros(n), adams::MAX_ORDER);
+        let mut k = 1;
+        let mut n_equal_steps = 0;
 
-        Ok(InitialHint { h, tdir, f0 })
-    }
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(self.y0.clone());
+        }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        'integration: while tdir * (tend - t) > 0. {
+            if h_abs > maxstep {
+                history.rescale(k - 1, maxstep / h_abs);
+                h_abs = maxstep;
+                n_equal_steps = 0;
+            }
 
-    /// Crude forward finite differences estimator of Jacobian as fallback
-    /// returns a NxN Matrix where N is the degree of freedom of the `OdeType` `y`
-    pub fn fdjacobian(&self, t: f64, x: &Y) -> DMatrix<T> {
-        let ftx = (self.f)(t, x);
-        let lx = ftx.dof();
+            let (t_new, ynew, fnew, factor) = loop {
+                if iters >= *opts.maxiters {
+                    retcode = ReturnCode::MaxIters;
+                    break 'integration;
+                }
+                iters += 1;
+                if h_abs < minstep {
+                    // minimum step size reached
+                    retcode = ReturnCode::DtLessThanMin;
+                    break 'integration;
+                }
+                let mut t_new = t + h_abs * tdir;
+                if tdir * (t_new - tend) > 0. {
+                    // hit the end point exactly
+                    t_new = tend;
+                    history.rescale(k - 1, (t_new - t).abs() / h_abs);
+                    h_abs = (t_new - t).abs();
+                    n_equal_steps = 0;
+                }
+                let h = t_new - t;
+
+                // predict and evaluate
+                let ypred = &y + coeffs.predictor(&history, k) * (T::one() * h);
+                let fpred = from_y(&self.rhs(t_new, &to_y(&ypred)));
+
+                // correct with the k-th difference of the predicted derivatives
+                let corr = (fpred - history.predict(k - 1)) * (T::one() * (h * coeffs.gamma[k]));
+                let ynew = &ypred + &corr;
+
+                let error_norm = local_error(&corr, &y, &ynew);
+                let factor = 0.9 * error_norm.powf(-1. / (k + 1) as f64);
+                if error_norm > 1. {
+                    let factor = adams::MIN_FACTOR.max(factor);
+                    self.count(|stats| stats.rejected_steps += 1);
+                    h_abs *= factor;
+                    history.rescale(k - 1, factor);
+                    n_equal_steps = 0;
+                    continue;
+                }
 
-        let mut dfdx = DMatrix::<T>::zeros(lx, lx);
-        for n in 0..lx {
-            let mut xj = x.get(n);
-            if xj == T::zero() {
-                xj += T::one();
-            }
-            // The / 100. is heuristic
-            let dxj = xj * 0.01;
-            let mut tmp = x.clone();
-            *tmp.get_mut(n) += dxj;
-            let yj = (self.f)(t, &tmp);
-            for m in 0..lx {
-                let mut yi = yj.get(m);
-                yi -= ftx.get(m);
-                yi /= dxj;
-                dfdx[(m, n)] = yi;
+                // evaluate the corrected solution
+                let fnew = from_y(&self.rhs(t_new, &to_y(&ynew)));
+                break (t_new, ynew, fnew, factor);
+            };
+
+            self.count(|stats| stats.accepted_steps += 1);
+            let (t_old, y_old, f_old) = (t, to_y(&y), to_y(&history.d[0]));
+            history.push(k - 1, &fnew);
+            t = t_new;
+            y = ynew;
+
+            n_equal_steps += 1;
+
+            // the next difference is available after each step, the step size is only
+            // increased after `k + 1` equal steps at the final order, frequent changes
+            // excite the parasitic solutions of the higher orders
+            if k < order {
+                k += 1;
+            } else if n_equal_steps > k && factor > 1.2 {
+                let factor = adams::MAX_FACTOR.min(factor);
+                h_abs *= factor;
+                history.rescale(k - 1, factor);
+                n_equal_steps = 0;
             }
+
+            let ynew = to_y(&y);
+            let fnew = to_y(&fnew);
+            let interp = |tquery: f64| {
+                self.hermite_interp(tquery, t_old, t - t_old, &y_old, &ynew, &f_old, &fnew)
+            };
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && tdir * self.tspan[iter_fixed] <= tdir * t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && tdir * t_old < tdir * self.tspan[iter_fixed]
+                    && tdir * self.tspan[iter_fixed] < tdir * t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(ynew);
+                tout.push(t);
+            }
+        }
+
+        Ok(OdeSolution::new(tout, yout)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start))
+    }
+
+    /// Solve the problem using the Adams–Bashforth–Moulton pair of orders 4 and 5.
+    pub fn abm(&self, opts: BasicOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Abm)?;
+        self.check_callbacks(Ode::Abm)?;
+        self.odeabm(4, opts)
+    }
+
+    /// Solve non-stiff problems with the variable order, variable step size Adams–Moulton
+    /// methods of order 1 to 12 in Nordsieck form.
+    ///
+    /// The solution is kept as the array of its scaled derivatives, so a change of the
+    /// step size only rescales the array, c.f. Hindmarsh, "ODEPACK, A Systematized
+    /// Collection of ODE Solvers", 1983. The corrector is solved with a functional
+    /// iteration. The order is adapted after `order + 1` steps of equal size, up to
+    /// [`MaxOrder`](crate::ode::options::MaxOrder) if given. Output inside a step is given
+    /// by the interpolating polynomial of the array.
+    pub fn adams(&self, opts: MultistepOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Adams)?;
+        self.check_callbacks(Ode::Adams)?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let start = self.stats();
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
+        let max_order = opts.max_order.map_or(nordsieck::MAX_ORDER, |order| {
+            order.0.clamp(1, nordsieck::MAX_ORDER)
+        });
+
+        let init = self.hinit(&self.y0, t, tend, 1, &reltol, &abstol)?;
+        let tdir = init.tdir;
+        let mut h_abs = if opts.initstep.0 != 0. {
+            if (signum(opts.initstep.0) - tdir).abs() < f64::EPSILON {
+                opts.initstep.0.abs()
+            } else {
+                return Err(OdeError::InvalidInitstep);
+            }
+        } else {
+            init.h.abs()
+        }
+        .min(maxstep);
+
+        let n = self.y0.dof();
+        let to_y = |v: &DVector<T>| {
+            let mut y = self.y0.clone();
+            for d in 0..n {
+                y.insert(d, v[d]);
+            }
+            y
+        };
+        let from_y = |y: &Y| DVector::from_iterator(n, y.ode_iter());
+        let f = |t: f64, v: &DVector<T>| from_y(&self.rhs(t, &to_y(v)));
+        let scale = |y: &DVector<T>| -> Vec<f64> {
+            let y = to_y(y);
+            (0..n)
+                .map(|d| abstol.at(d) + reltol.at(d) * y.modulus_at(d).into())
+                .collect()
+        };
+        // the error tests measure with the norm of the problem, if set
+        let local_error =
+            |err: &DVector<T>, y: &Y| self.local_error(y, y, &to_y(err), &abstol, &reltol);
+
+        let coeffs = NordsieckCoeffs::default();
+        let mut history = NordsieckHistory::new(
+            from_y(&self.y0),
+            from_y(&init.f0) * (T::one() * (h_abs * tdir)),
+        );
+        let mut order = 1;
+        // steps until the order may be changed
+        let mut n_wait = 2;
+        let mut rate = 0.7;
+        let mut error_prev = DVector::zeros(n);
+
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(self.y0.clone());
+        }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        'integration: while tdir * (tend - t) > 0. {
+            if h_abs > maxstep {
+                history.rescale(order, maxstep / h_abs);
+                h_abs = maxstep;
+                n_wait = n_wait.max(2);
+            }
+
+            let mut failures = 0;
+            let (t_new, error, error_norm) = loop {
+                if iters >= *opts.maxiters {
+                    retcode = ReturnCode::MaxIters;
+                    break 'integration;
+                }
+                iters += 1;
+                if h_abs < minstep {
+                    // minimum step size reached
+                    retcode = ReturnCode::DtLessThanMin;
+                    break 'integration;
+                }
+                let mut t_new = t + h_abs * tdir;
+                if tdir * (t_new - tend) > 0. {
+                    // hit the end point exactly
+                    t_new = tend;
+                    history.rescale(order, (t_new - t).abs() / h_abs);
+                    h_abs = (t_new - t).abs();
+                    n_wait = n_wait.max(2);
+                }
+                let h = t_new - t;
+                let (weights, yold) = (scale(&history.z[0]), to_y(&history.z[0]));
+                let el = &coeffs.el[order];
+                let tesco = coeffs.tesco[order];
+
+                history.predict(order);
+                let (ypred, hdy) = (history.z[0].clone(), history.z[1].clone());
+
+                // functional iteration of the corrector `y = ypred + l_0 e` with
+                // `e = h f(t, y) - h y'_pred`
+                let mut error = DVector::zeros(n);
+                let mut norm_prev = 0.;
+                let mut fy = f(t_new, &ypred);
+                let mut converged = false;
+                for m in 0..nordsieck::MAX_CORRECTOR_ITER {
+                    let e = fy * (T::one() * h) - &hdy;
+                    let norm = multistep::rms_norm(&(&e - &error), &weights);
+                    let y = &ypred + &e * (T::one() * el[0]);
+                    error = e;
+                    if m > 0 {
+                        rate = (0.2 * rate).max(norm / norm_prev);
+                    }
+                    if norm * (1.5 * rate).min(1.) / tesco[1] <= 0.5 / (order + 2) as f64 {
+                        converged = true;
+                        break;
+                    }
+                    if m > 0 && norm > 2. * norm_prev {
+                        break;
+                    }
+                    norm_prev = norm;
+                    fy = f(t_new, &y);
+                }
+
+                if !converged {
+                    self.count(|stats| stats.rejected_steps += 1);
+                    history.retract(order);
+                    h_abs *= 0.25;
+                    history.rescale(order, 0.25);
+                    n_wait = order + 1;
+                    continue;
+                }
+
+                let error_norm = local_error(&error, &yold) / tesco[1];
+                if error_norm > 1. {
+                    self.count(|stats| stats.rejected_steps += 1);
+                    history.retract(order);
+                    failures += 1;
+                    let factor = if failures >= 3 {
+                        // restart at order 1 from the last accepted point
+                        order = 1;
+                        history.z[1] = f(t, &history.z[0]) * (T::one() * (0.1 * h));
+                        h_abs *= 0.1;
+                        n_wait = 2;
+                        continue;
+                    } else {
+                        1. / (1.2 * error_norm.powf(1. / (order + 1) as f64) + 1.2e-6)
+                    };
+                    let factor = factor.clamp(0.1, 0.9);
+                    h_abs *= factor;
+                    history.rescale(order, factor);
+                    n_wait = order + 1;
+                    continue;
+                }
+                break (t_new, error, error_norm);
+            };
+
+            self.count(|stats| stats.accepted_steps += 1);
+            let t_old = t;
+            t = t_new;
+            history.correct(&coeffs.el[order], &error);
+            n_wait -= 1;
+
+            if n_wait == 0 {
+                // estimate the error at the neighbouring orders and pick the order
+                // that allows the largest step
+                let tesco = coeffs.tesco[order];
+                let y = to_y(&history.z[0]);
+                let factor_m = if order > 1 {
+                    let error_m = local_error(&history.z[order], &y) / tesco[0];
+                    1. / (1.3 * error_m.powf(1. / order as f64) + 1.3e-6)
+                } else {
+                    0.
+                };
+                let factor = 1. / (1.2 * error_norm.powf(1. / (order + 1) as f64) + 1.2e-6);
+                let factor_p = if order < max_order {
+                    let error_p = local_error(&(&error - &error_prev), &y) / tesco[2];
+                    1. / (1.4 * error_p.powf(1. / (order + 2) as f64) + 1.4e-6)
+                } else {
+                    0.
+                };
+
+                let (new_order, factor) = if factor_p > factor && factor_p >= factor_m {
+                    (order + 1, factor_p)
+                } else if factor_m > factor {
+                    (order - 1, factor_m)
+                } else {
+                    (order, factor)
+                };
+                if factor < 1.1 {
+                    n_wait = 3;
+                } else {
+                    if new_order > order {
+                        history.z[new_order] =
+                            &error * (T::one() * (coeffs.el[order][order] / new_order as f64));
+                    }
+                    order = new_order;
+                    let factor = factor.min(nordsieck::MAX_FACTOR);
+                    h_abs *= factor;
+                    history.rescale(order, factor);
+                    n_wait = order + 1;
+                }
+            } else if n_wait == 1 {
+                // the correction is needed to estimate the error at the next order
+                error_prev = error;
+            }
+
+            let interp = |tquery: f64| to_y(&history.interpolate(order, t, h_abs * tdir, tquery));
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && tdir * self.tspan[iter_fixed] <= tdir * t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && tdir * t_old < tdir * self.tspan[iter_fixed]
+                    && tdir * self.tspan[iter_fixed] < tdir * t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(to_y(&history.z[0]));
+                tout.push(t);
+            }
+        }
+
+        Ok(OdeSolution::new(tout, yout)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start))
+    }
+
+    /// Solve smooth problems to high accuracy with the Gragg–Bulirsch–Stoer
+    /// extrapolation method of adaptive order, following Hairer's `ODEX`.
+    ///
+    /// Each step computes Gragg's modified midpoint rule with the harmonic step numbers
+    /// `2, 4, 6, ...` and extrapolates the results to step size zero. The column of the
+    /// extrapolation table, and with it the order, is chosen to minimize the work per
+    /// unit step, c.f. Hairer, Nørsett & Wanner, "Solving Ordinary Differential
+    /// Equations I", 1993, II.9. Output inside a step is given by hermite interpolation.
+    pub fn gbs(&self, opts: BasicOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Gbs)?;
+        self.check_callbacks(Ode::Gbs)?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let start = self.stats();
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
+
+        let init = self.hinit(&self.y0, t, tend, 4, &reltol, &abstol)?;
+        let tdir = init.tdir;
+        let mut h_abs = if opts.initstep.0 != 0. {
+            if (signum(opts.initstep.0) - tdir).abs() < f64::EPSILON {
+                opts.initstep.0.abs()
+            } else {
+                return Err(OdeError::InvalidInitstep);
+            }
+        } else {
+            init.h.abs()
+        }
+        .min(maxstep);
+
+        let n = self.y0.dof();
+        let to_y = |v: &DVector<T>| {
+            let mut y = self.y0.clone();
+            for d in 0..n {
+                y.insert(d, v[d]);
+            }
+            y
+        };
+        let from_y = |y: &Y| DVector::from_iterator(n, y.ode_iter());
+        let f = |t: f64, v: &DVector<T>| from_y(&self.rhs(t, &to_y(v)));
+        // the error tests measure with the norm of the problem, if set
+        let local_error = |err: &DVector<T>, y0: &DVector<T>, y1: &DVector<T>| {
+            self.local_error(&to_y(y0), &to_y(y1), &to_y(err), &abstol, &reltol)
+        };
+
+        let coeffs = ExtrapolationCoeffs::default();
+        let max_column = extrapolation::MAX_COLUMNS - 2;
+        // initial column from the tolerance
+        let mut column =
+            ((-reltol.min().log10() * 0.6 + 0.5).max(0.) as usize).clamp(1, max_column);
+        let mut y = from_y(&self.y0);
+        let mut dy = from_y(&init.f0);
+        let mut factors = [1.; extrapolation::MAX_COLUMNS];
+        let mut work = [0.; extrapolation::MAX_COLUMNS];
+        let mut table: Vec<Vec<DVector<T>>> = Vec::with_capacity(extrapolation::MAX_COLUMNS);
+
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(self.y0.clone());
+        }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        'integration: while tdir * (tend - t) > 0. {
+            h_abs = h_abs.min(maxstep);
+
+            let mut rejected = false;
+            let (t_new, accepted) = loop {
+                if iters >= *opts.maxiters {
+                    retcode = ReturnCode::MaxIters;
+                    break 'integration;
+                }
+                iters += 1;
+                if h_abs < minstep {
+                    // minimum step size reached
+                    retcode = ReturnCode::DtLessThanMin;
+                    break 'integration;
+                }
+                let mut t_new = t + h_abs * tdir;
+                if tdir * (t_new - tend) > 0. {
+                    // hit the end point exactly
+                    t_new = tend;
+                    h_abs = (t_new - t).abs();
+                }
+                let h = t_new - t;
+
+                table.clear();
+                let mut accepted = None;
+                let mut failed = column;
+                for j in 0..=column + 1 {
+                    table.push(vec![extrapolation::midpoint(
+                        &f,
+                        t,
+                        &y,
+                        &dy,
+                        h,
+                        coeffs.n[j],
+                    )]);
+                    coeffs.extrapolate(&mut table, j);
+                    if j == 0 {
+                        continue;
+                    }
+
+                    let err = local_error(&(&table[j][j] - &table[j][j - 1]), &y, &table[j][j]);
+                    let expo = 1. / (2 * j + 1) as f64;
+                    let (safe1, safe2) = extrapolation::SAFETY;
+                    let (fmin, fmax) = extrapolation::FACTOR_BOUNDS;
+                    factors[j] = (safe1 * (safe2 / err).powf(expo)).clamp(fmin, fmax.powf(-expo));
+                    work[j] = coeffs.work[j] / factors[j];
+
+                    if j + 1 >= column {
+                        if err <= 1. {
+                            accepted = Some(j);
+                            break;
+                        }
+                        // reject early if convergence within the remaining columns is unlikely
+                        let n0 = coeffs.n[0] as f64;
+                        let (nk, nk1) = (coeffs.n[column] as f64, coeffs.n[column + 1] as f64);
+                        if (j + 1 == column && err > (nk * nk1 / (n0 * n0)).powi(2))
+                            || (j == column && err > (nk1 / n0).powi(2))
+                            || j == column + 1
+                        {
+                            failed = j;
+                            break;
+                        }
+                    }
+                }
+
+                match accepted {
+                    Some(j) => break (t_new, j),
+                    None => {
+                        self.count(|stats| stats.rejected_steps += 1);
+                        rejected = true;
+                        column = failed.min(column);
+                        if column >= 2 && work[column - 1] < 0.8 * work[column] {
+                            column -= 1;
+                        }
+                        h_abs *= factors[column].min(1.);
+                    }
+                }
+            };
+
+            self.count(|stats| stats.accepted_steps += 1);
+            let (t_old, y_old, f_old) = (t, to_y(&y), to_y(&dy));
+            y = table[accepted][accepted].clone();
+            dy = f(t_new, &y);
+            t = t_new;
+
+            // order and step size for the next step
+            column = if accepted >= 2 && work[accepted - 1] < 0.8 * work[accepted] {
+                accepted - 1
+            } else if !rejected && (accepted == 1 || work[accepted] < 0.9 * work[accepted - 1]) {
+                (accepted + 1).min(max_column)
+            } else {
+                accepted.min(max_column)
+            };
+            let factor = if column > accepted {
+                factors[accepted] * coeffs.work[column] / coeffs.work[accepted]
+            } else {
+                factors[column]
+            };
+            h_abs *= if rejected { factor.min(1.) } else { factor };
+
+            let ynew = to_y(&y);
+            let fnew = to_y(&dy);
+            let interp = |tquery: f64| {
+                self.hermite_interp(tquery, t_old, t - t_old, &y_old, &ynew, &f_old, &fnew)
+            };
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && tdir * self.tspan[iter_fixed] <= tdir * t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && tdir * t_old < tdir * self.tspan[iter_fixed]
+                    && tdir * self.tspan[iter_fixed] < tdir * t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(ynew);
+                tout.push(t);
+            }
+        }
+
+        Ok(OdeSolution::new(tout, yout)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start))
+    }
+
+    /// Solve with an implicit Runge-Kutta method at the fixed steps of `tspan`.
+    ///
+    /// The stage equations are solved with a simplified newton iteration, the jacobian
+    /// is reused over steps as set with [`OdeBuilder::newton`].
+    pub fn oderk_implicit<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        self.check_mass_of("oderk_implicit")?;
+        self.check_callbacks_of("oderk_implicit")?;
+        match self.implicit_fixed(btab) {
+            (_, Some(err)) => Err(err),
+            (solution, None) => Ok(solution),
+        }
+    }
+
+    /// Steps with an implicit Runge-Kutta method from one time stamp of `tspan` to the
+    /// next, like [`OdeProblem::oderk_implicit`]. A failed step ends the solution and is
+    /// returned alongside.
+    fn implicit_fixed<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+    ) -> (OdeSolution<f64, Y>, Option<OdeError>)
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return (OdeSolution::default(), None);
+        }
+
+        let start = self.stats();
+        let coeffs = ImplicitCoeffs::from_tableau(btab);
+        let newton = self.newton.clone().unwrap_or_default();
+
+        let mut ys = Vec::with_capacity(self.tspan.len());
+        ys.push(self.y0.clone());
+
+        let mut jac = JacobianCache::new(newton.reuse);
+        let mut cache = NewtonCache::new(&newton);
+        let mut failure = None;
+        for (i, dt) in diff(&self.tspan).into_iter().enumerate() {
+            let t = self.tspan[i];
+            match self.implicit_step(&coeffs, t, dt, &ys[i], &newton, &mut jac, &mut cache) {
+                Ok(y) => ys.push(y),
+                Err(err) => {
+                    failure = Some(err);
+                    break;
+                }
+            }
+            self.count(|stats| stats.accepted_steps += 1);
+        }
+
+        let ts = self.tspan[..ys.len()].to_vec();
+        let solution = OdeSolution::new(ts, ys).with_stats(self.stats() - start);
+        (solution, failure)
+    }
+
+    /// Computes a single step of size `dt` of an implicit Runge-Kutta method.
+    ///
+    /// The jacobian and the factorized iteration matrix are reused from `jac` and
+    /// `cache`, c.f. [`OdeProblem::solve_reusing`].
+    #[allow(clippy::too_many_arguments)]
+    fn implicit_step(
+        &self,
+        coeffs: &ImplicitCoeffs,
+        t: f64,
+        dt: f64,
+        y: &Y,
+        newton: &NewtonOptions,
+        jac: &mut JacobianCache<Y, JacobianMatrix<T>>,
+        cache: &mut NewtonCache<Factorization<T>>,
+    ) -> Result<Y, OdeError> {
+        let f = |t, y: &Y| self.rhs(t, y);
+        let solve = |jac: &JacobianMatrix<T>, cache: &mut NewtonCache<_>| {
+            implicit::solve_stages(
+                &f,
+                coeffs,
+                t,
+                dt,
+                y,
+                jac,
+                self.linear_solver,
+                newton,
+                cache,
+                &self.stats,
+            )
+        };
+        let ks = match self.linear_solver {
+            LinearSolverType::Gmres(gmres) => {
+                let jvp = self.jacobian_operator(t, y);
+                let mut cache = NewtonCache::new(newton);
+                let preconditioner = self.preconditioner_setup(t, y)?;
+                let jac =
+                    JacobianOperator::new(&jvp, gmres).preconditioner(preconditioner.as_ref());
+                implicit::solve_stages(
+                    &f,
+                    coeffs,
+                    t,
+                    dt,
+                    y,
+                    &jac,
+                    self.linear_solver,
+                    newton,
+                    &mut cache,
+                    &self.stats,
+                )?
+            }
+            _ => self.solve_reusing(t, dt, y, newton, jac, cache, solve)?,
+        };
+        let mut y = y.clone();
+        for (k, b) in ks.iter().zip(&coeffs.b) {
+            y.axpy(b * dt, k);
+        }
+        Ok(y)
+    }
+
+    /// Solves the newton iterations of a step of size `dt` from `(t, y)` with `solve`,
+    /// given the jacobian and the cached factorizations.
+    ///
+    /// The jacobian is reused from `jac` as allowed by [`NewtonOptions::reuse`], the
+    /// factorizations are reused from `cache` while the jacobian is unchanged. If the
+    /// iteration fails to converge with either of them outdated, it is retried with a
+    /// jacobian at `(t, y)` and an iteration matrix of the step size. A jacobian that
+    /// slowed the iteration down to a rate above [`NewtonOptions::max_rate`] is
+    /// re-evaluated for the next step.
+    #[allow(clippy::too_many_arguments)]
+    fn solve_reusing<R, S>(
+        &self,
+        t: f64,
+        dt: f64,
+        y: &Y,
+        newton: &NewtonOptions,
+        jac: &mut JacobianCache<Y, JacobianMatrix<T>>,
+        cache: &mut NewtonCache<Factorization<T>>,
+        solve: S,
+    ) -> Result<R, OdeError>
+    where
+        S: Fn(&JacobianMatrix<T>, &mut NewtonCache<Factorization<T>>) -> Result<R, OdeError>,
+    {
+        let jacobian = |t, y: &Y| self.iteration_jacobian(t, y);
+        let (reused, current) = jac.reuse(t, y, jacobian)?;
+        if current {
+            cache.clear();
+        }
+        let solved = match solve(reused, cache) {
+            Err(OdeError::NewtonConvergence { .. }) if !current => {
+                // retry with an up to date jacobian
+                let jac = jac.update(t, y, jacobian)?;
+                cache.clear();
+                solve(jac, cache)
+            }
+            Err(OdeError::NewtonConvergence { .. }) if cache.is_stale(dt) => {
+                // retry with the iteration matrix of the step size
+                cache.clear();
+                solve(jac.get(), cache)
+            }
+            solved => solved,
+        }?;
+        if cache.take_rate() > newton.max_rate {
+            jac.expire();
+        }
+        Ok(solved)
+    }
+
+    /// Solve the problem using the backward (implicit) Euler method.
+    ///
+    /// Each step solves `y1 = y0 + dt f(t + dt, y1)` with a newton iteration.
+    /// The method is only first order accurate but L-stable, which makes it the
+    /// most robust choice for very stiff problems.
+    pub fn beuler(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Beuler)?;
+        self.check_callbacks(Ode::Beuler)?;
+        self.oderk_implicit(&ButcherTableau::beuler())
+    }
+
+    /// Solve the problem using the trapezoidal rule (Crank–Nicolson) at the fixed
+    /// steps of `tspan`.
+    ///
+    /// The method is A-stable and second order accurate.
+    pub fn trapezoid(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Trapezoid)?;
+        self.check_callbacks(Ode::Trapezoid)?;
+        self.oderk_implicit(&ButcherTableau::lobatto_iiia2())
+    }
+
+    /// Solve the problem using the trapezoidal rule (Crank–Nicolson) with adaptive
+    /// step size.
+    ///
+    /// The local error is estimated by step doubling: every step is also computed
+    /// as two steps of half the size, the more accurate two half steps are kept.
+    pub fn trapezoid_adapt(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::TrapezoidAdapt)?;
+        self.check_callbacks(Ode::TrapezoidAdapt)?;
+        let coeffs = ImplicitCoeffs::from_tableau(&ButcherTableau::lobatto_iiia2());
+        let newton = self.newton.clone().unwrap_or_default();
+
+        let mut jac = JacobianCache::new(newton.reuse);
+        let mut cache = NewtonCache::new(&newton);
+        self.oderk_adapt_implicit(2, opts, |t, dt, y| {
+            let mut step =
+                |t, dt, y: &Y| self.implicit_step(&coeffs, t, dt, y, &newton, &mut jac, &mut cache);
+            let yfull = step(t, dt, y)?;
+            let yhalf = step(t, dt / 2., y)?;
+            let ytrial = step(t + dt / 2., dt / 2., &yhalf)?;
+
+            // richardson error estimate of the two half steps
+            let mut yerr = ytrial.clone();
+            for d in 0..yerr.dof() {
+                *yerr.get_mut(d) = (ytrial.get(d) - yfull.get(d)) * (1. / 3.);
+            }
+            Ok(ImplicitTrial {
+                y: ytrial,
+                err: yerr,
+                interpolant: None,
+                f1: None,
+            })
+        })
+    }
+
+    /// Solve the problem using a singly diagonally implicit Runge-Kutta method
+    /// with an embedded error estimate.
+    ///
+    /// All implicit stages share the same diagonal coefficient, hence the newton
+    /// iterations of all stages of a step use the same LU factorization. The jacobian is
+    /// reused over steps like for [`OdeProblem::radau5`].
+    ///
+    /// The stages of a fully implicit tableau are solved for together, like for
+    /// [`OdeProblem::oderk_implicit`].
+    pub fn oderk_sdirk<S: Dim, Ops: Into<AdaptiveOptions>>(
+        &self,
+        btab: &ButcherTableau<S>,
+        opts: Ops,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        self.check_mass_of("oderk_sdirk")?;
+        self.check_callbacks_of("oderk_sdirk")?;
+        if !btab.is_adaptive() {
+            return Err(OdeError::not_embedded());
+        }
+
+        let coeffs = ImplicitCoeffs::from_tableau(btab);
+        let newton = self.newton.clone().unwrap_or_default();
+
+        let mut jac = JacobianCache::new(newton.reuse);
+        let mut cache = NewtonCache::new(&newton);
+        self.oderk_adapt_implicit(btab.order().min(), opts, |t, dt, y| {
+            let f = |t, y: &Y| self.rhs(t, y);
+            let solve = |jac: &JacobianMatrix<T>, cache: &mut NewtonCache<_>| {
+                implicit::solve_implicit_stages(
+                    &f,
+                    &coeffs,
+                    t,
+                    dt,
+                    y,
+                    jac,
+                    self.linear_solver,
+                    &newton,
+                    cache,
+                    &self.stats,
+                )
+            };
+            let ks = match self.linear_solver {
+                LinearSolverType::Gmres(gmres) => {
+                    let jvp = self.jacobian_operator(t, y);
+                    let mut cache = NewtonCache::new(&newton);
+                    let preconditioner = self.preconditioner_setup(t, y)?;
+                    let jac =
+                        JacobianOperator::new(&jvp, gmres).preconditioner(preconditioner.as_ref());
+                    implicit::solve_implicit_stages(
+                        &f,
+                        &coeffs,
+                        t,
+                        dt,
+                        y,
+                        &jac,
+                        self.linear_solver,
+                        &newton,
+                        &mut cache,
+                        &self.stats,
+                    )?
+                }
+                _ => self.solve_reusing(t, dt, y, &newton, &mut jac, &mut cache, solve)?,
+            };
+            let (ytrial, yerr) = coeffs.embedded_step(dt, y, &ks);
+            Ok(ImplicitTrial {
+                y: ytrial,
+                err: yerr,
+                interpolant: None,
+                f1: None,
+            })
+        })
+    }
+
+    /// Solve the problem using the 2 stage, L-stable SDIRK method of order 2
+    /// with an embedded first order error estimate.
+    pub fn sdirk2(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Sdirk2)?;
+        self.check_callbacks(Ode::Sdirk2)?;
+        self.oderk_sdirk(&ButcherTableau::sdirk2(), opts)
+    }
+
+    /// Solve the problem using the 5 stage, L-stable SDIRK method of order 4 of
+    /// Hairer & Wanner with an embedded third order error estimate.
+    pub fn sdirk4(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Sdirk4)?;
+        self.check_callbacks(Ode::Sdirk4)?;
+        self.oderk_sdirk(&ButcherTableau::sdirk4(), opts)
+    }
+
+    /// Solve the problem using Kværnø's 4(3) ESDIRK pair.
+    pub fn kvaerno4(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Kvaerno4)?;
+        self.check_callbacks(Ode::Kvaerno4)?;
+        self.oderk_sdirk(&ButcherTableau::kvaerno4(), opts)
+    }
+
+    /// Solve the problem using the TR-BDF2 method, a trapezoidal step followed by a
+    /// BDF2 step, with the third order error estimate of Hosea & Shampine.
+    pub fn trbdf2(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::TrBdf2)?;
+        self.check_callbacks(Ode::TrBdf2)?;
+        self.oderk_sdirk(&ButcherTableau::trbdf2(), opts)
+    }
+
+    /// Solve stiff problems with the 3 stage Radau IIA method of order 5, following
+    /// Hairer's `RADAU5`.
+    ///
+    /// The stage equations are solved with a simplified newton iteration. The jacobian
+    /// is reused over steps and only reevaluated if the iteration fails to converge or
+    /// after the steps allowed by [`NewtonOptions::reuse`].
+    /// The local error is estimated with the embedded formula of `RADAU5`, output
+    /// inside a step is given by the collocation polynomial. A mass matrix, which may be
+    /// singular, is evaluated at the stages, c.f. [`implicit::solve_stage_increments`].
+    pub fn radau5(&self, opts: ImplicitOptions) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_callbacks(Ode::Radau5)?;
+        let coeffs = ImplicitCoeffs::from_tableau(&ButcherTableau::radau_iia5());
+        let newton = self.newton.clone().unwrap_or_default();
+
+        let s6 = 6f64.sqrt();
+        // inverse of the real eigenvalue of the rk matrix
+        let u1 = 30. / (6. + 81f64.cbrt() - 9f64.cbrt());
+        let dd = [-(13. + 7. * s6) / 3., (-13. + 7. * s6) / 3., -1. / 3.];
+
+        let mut jac = JacobianCache::new(newton.reuse);
+        let mut cache = NewtonCache::new(&newton);
+        self.oderk_adapt_implicit(3, opts, |t, dt, y| {
+            let n = y.dof();
+            let mass = self
+                .mass
+                .as_ref()
+                .map(|mass| mass as implicit::DynMass<'_, Y, T>);
+            let f = |t, y: &Y| self.rhs(t, y);
+            let solve = |jac: &JacobianMatrix<T>, cache: &mut NewtonCache<_>| {
+                implicit::solve_stage_increments(
+                    &f,
+                    &coeffs,
+                    t,
+                    dt,
+                    y,
+                    jac,
+                    self.linear_solver,
+                    mass,
+                    &newton,
+                    cache,
+                    &self.stats,
+                )
+            };
+            // error estimate `(M - dt/u1 J)^-1 (dt/u1 f(t, y) + M Σ_i dd_i/u1 z_i)`
+            let m0 = self.mass_matrix(t, y);
+            let estimate = |z: &[Y], m: &dyn LinearSolver<T>| {
+                let f0 = self.rhs(t, y);
+                let zsum = DVector::from_iterator(
+                    n,
+                    (0..n).map(|d| {
+                        z.iter()
+                            .zip(dd.iter())
+                            .fold(T::zero(), |acc, (zi, ddi)| acc + zi.get(d) * (ddi / u1))
+                    }),
+                );
+                let rhs =
+                    DVector::from_iterator(n, f0.ode_iter().map(|fi| fi * (dt / u1))) + &m0 * zsum;
+                self.count(|stats| stats.num_linsolve += 1);
+                m.solve(&rhs).ok_or(OdeError::InvalidMatrix)
+            };
+            let mass0 = self.mass.as_ref().map(|_| &m0);
+
+            // stage increments `z_i = Y_i - y`
+            let (z, err) = match self.linear_solver {
+                LinearSolverType::Gmres(gmres) => {
+                    let jvp = self.jacobian_operator(t, y);
+                    let mut cache = NewtonCache::new(&newton);
+                    let preconditioner = self.preconditioner_setup(t, y)?;
+                    let jac =
+                        JacobianOperator::new(&jvp, gmres).preconditioner(preconditioner.as_ref());
+                    let z = implicit::solve_stage_increments(
+                        &f,
+                        &coeffs,
+                        t,
+                        dt,
+                        y,
+                        &jac,
+                        self.linear_solver,
+                        mass,
+                        &newton,
+                        &mut cache,
+                        &self.stats,
+                    )?;
+                    let err = estimate(&z, &jac.factor(mass0, dt / u1, self.linear_solver)?)?;
+                    (z, err)
+                }
+                _ => {
+                    let z = self.solve_reusing(t, dt, y, &newton, &mut jac, &mut cache, solve)?;
+                    // the estimate needs the exact step size
+                    let m = jac.get().factor(mass0, dt / u1, self.linear_solver)?;
+                    self.count(|stats| stats.num_decomp += 1);
+                    let err = estimate(&z, &m)?;
+                    (z, err)
+                }
+            };
+
+            // stiffly accurate, the last stage is the new solution
+            let ytrial = y.clone().sum(&z[z.len() - 1]);
+            let mut yerr = y.clone();
+            for d in 0..n {
+                yerr.insert(d, err[d]);
+            }
+
+            Ok(ImplicitTrial {
+                y: ytrial,
+                err: yerr,
+                interpolant: Some(Interpolant::Collocation(Collocation {
+                    c: coeffs.c.clone(),
+                    z,
+                })),
+                f1: None,
+            })
+        })
+    }
+
+    /// Adaptive integration loop of the implicit methods.
+    ///
+    /// `step` computes a trial step of size `dt` from `(t, y)`, returning the new
+    /// solution and its local error estimate, c.f. [`OdeProblem::oderk_adapt_method`].
+    pub(crate) fn oderk_adapt_implicit<Ops, St>(
+        &self,
+        order: usize,
+        opts: Ops,
+        step: St,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
+    where
+        Ops: Into<AdaptiveOptions>,
+        St: FnMut(f64, f64, &Y) -> Result<ImplicitTrial<Y>, OdeError>,
+    {
+        self.oderk_adapt_method(opts, &mut FnMethod { order, step })
+    }
+
+    /// Adaptive integration loop of a one step `method`, which computes the trial steps
+    /// and their local error estimates.
+    ///
+    /// Steps whose newton iteration fails to converge are retried with half the step
+    /// size. The method is informed of every accepted step and may change its order in
+    /// between, e.g. a [`Composite`] switching between a non-stiff and a stiff method.
+    /// Output inside a step is given by the interpolant of the trial if provided,
+    /// otherwise by hermite interpolation.
+    pub fn oderk_adapt_method<Ops, Me>(
+        &self,
+        opts: Ops,
+        method: &mut Me,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
+    where
+        Ops: Into<AdaptiveOptions>,
+        Me: StepMethod<Y>,
+    {
+        self.check_callbacks_of("oderk_adapt_method")?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let start = self.stats();
+        let opts = opts.into();
+        let (reltol, abstol) = opts.tolerances(self.y0.dof())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| abs(tend - t) / 2.5, |step| step.0);
+
+        let init = self.hinit(&self.y0, t, tend, method.order(), &reltol, &abstol)?;
+
+        let mut dt = if opts.initstep.0 != 0. {
+            if (signum(opts.initstep.0) - init.tdir).abs() < f64::EPSILON {
+                opts.initstep.0
+            } else {
+                return Err(OdeError::InvalidInitstep);
+            }
+        } else {
+            init.h
+        };
+
+        let mut timeout = 0usize;
+        // whether the step at the minimum step size is taken without error control
+        let mut forced = false;
+        let mut output = if opts.dense.0 {
+            Some(DenseOutput::default())
+        } else {
+            None
+        };
+        let mut last_step = (t + dt - tend).abs() <= f64::EPSILON;
+
+        let mut tspan: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        // store for the computed values
+        let mut ys = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tspan.push(t);
+            ys.push(self.y0.clone());
+        }
+
+        let mut y = self.y0.clone();
+        let mut f0 = init.f0;
+
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+        // integration loop
+        let mut iters = 0;
+        loop {
+            if iters >= *opts.maxiters {
+                retcode = ReturnCode::MaxIters;
+                break;
+            }
+            iters += 1;
+            let trial = match method.trial(t, dt, &y, &f0) {
+                Ok(trial) => {
+                    let step = self.stepsize_hw92(
+                        dt,
+                        init.tdir,
+                        &y,
+                        &trial.y,
+                        &trial.err,
+                        method.order(),
+                        timeout,
+                        &abstol,
+                        &reltol,
+                        maxstep,
+                    );
+                    Some((step, trial.y, trial.interpolant, trial.f1))
+                }
+                Err(OdeError::NewtonConvergence { .. }) => None,
+                Err(err) => return Err(err),
+            };
+
+            match trial {
+                Some((step, ytrial, interpolant, f1)) if step.err < 1. || forced => {
+                    self.count(|stats| stats.accepted_steps += 1);
+                    forced = false;
+                    timeout = step.timeout_ctn;
+                    let f1 = f1.unwrap_or_else(|| self.rhs(t + dt, &ytrial));
+                    method.accept(t + dt, dt, &ytrial, &f1);
+                    if let Some(output) = output.as_mut() {
+                        let mut interpolant = match &interpolant {
+                            Some(interpolant) => StepInterpolant::Implicit(interpolant.clone()),
+                            None => StepInterpolant::hermite(dt, &y, &ytrial, &f0, &f1),
+                        };
+                        if let Some(order) = &opts.interp_order {
+                            interpolant =
+                                interpolant.with_max_degree(order.0, dt, &y, &ytrial, &f0, &f1);
+                        }
+                        output.push(t, dt, y.clone(), interpolant);
+                    }
+                    let interp = |tquery: f64| match &interpolant {
+                        Some(interpolant) => interpolant.interpolate((tquery - t) / dt, &y),
+                        None => self.hermite_interp(tquery, t, dt, &y, &ytrial, &f0, &f1),
+                    };
+
+                    // interpolate onto given output points
+                    if Points::Specified == opts.points {
+                        while iter_fixed < self.tspan.len()
+                            && (init.tdir * self.tspan[iter_fixed] < init.tdir * (t + dt)
+                                || last_step)
+                        {
+                            let yout = interp(self.tspan[iter_fixed]);
+                            ys.push(yout);
+                            tspan.push(self.tspan[iter_fixed]);
+                            iter_fixed += 1;
+                        }
+                    } else {
+                        // store at all new times which are < t+dt
+                        while opts.points.saves_tspan()
+                            && iter_fixed < self.tspan.len()
+                            && init.tdir * t < init.tdir * self.tspan[iter_fixed]
+                            && init.tdir * self.tspan[iter_fixed] < init.tdir * (t + dt)
+                        {
+                            let yout = interp(self.tspan[iter_fixed]);
+                            ys.push(yout);
+                            tspan.push(self.tspan[iter_fixed]);
+                            iter_fixed += 1;
+                        }
+                        // also store the steps taken, the sparse output modes only keep
+                        // the latest one until it is superseded
+                        if provisional {
+                            ys.pop();
+                            tspan.pop();
+                        }
+                        steps += 1;
+                        provisional = !opts.points.saves_step(steps);
+                        ys.push(ytrial.clone());
+                        tspan.push(t + dt);
+                    }
+
+                    y = ytrial;
+                    f0 = f1;
+
+                    // break if this was the last step
+                    if last_step {
+                        break;
+                    }
+
+                    // update t to the time at the end of current step:
+                    t += dt;
+                    dt = step.dt;
+
+                    // Hit end point exactly if next step within 1% of end
+                    if init.tdir * (t + dt + dt / 100.) >= init.tdir * tend {
+                        dt = tend - t;
+                        // next step is the last, if it succeeds
+                        last_step = true;
+                    }
+                }
+                trial => {
+                    self.count(|stats| stats.rejected_steps += 1);
+                    // redo step with smaller dt, halve it if the newton iteration failed
+                    let mut new_dt = trial.map_or(dt / 2., |(step, _, _, _)| step.dt);
+                    if new_dt.abs() < minstep {
+                        if *opts.force_dtmin && !forced {
+                            // take the minimum step regardless of its error, unless the
+                            // newton iteration failed for it
+                            new_dt = init.tdir * minstep;
+                            forced = true;
+                        } else {
+                            // minimum step size reached
+                            retcode = ReturnCode::DtLessThanMin;
+                            break;
+                        }
+                    }
+                    last_step = false;
+                    dt = new_dt;
+                    timeout = *StepTimeout::default();
+                }
+            }
+        }
+
+        Ok(OdeSolution::new(tspan, ys)
+            .with_retcode(retcode)
+            .with_stats(self.stats() - start)
+            .with_interpolant(output))
+    }
+
+    /// Solve the problem using the two stage Gauss–Legendre method of order 4.
+    pub fn gauss4(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Gauss4)?;
+        self.check_callbacks(Ode::Gauss4)?;
+        self.oderk_implicit(&ButcherTableau::gauss4())
+    }
+
+    /// Solve the problem using the three stage Gauss–Legendre method of order 6.
+    pub fn gauss6(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Gauss6)?;
+        self.check_callbacks(Ode::Gauss6)?;
+        self.oderk_implicit(&ButcherTableau::gauss6())
+    }
+
+    /// Solve the problem using the three stage Lobatto IIIA method of order 4.
+    pub fn lobatto_iiia(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::LobattoIIIA)?;
+        self.check_callbacks(Ode::LobattoIIIA)?;
+        self.oderk_implicit(&ButcherTableau::lobatto_iiia4())
+    }
+
+    /// Solve the problem using the three stage Lobatto IIIB method of order 4.
+    pub fn lobatto_iiib(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::LobattoIIIB)?;
+        self.check_callbacks(Ode::LobattoIIIB)?;
+        self.oderk_implicit(&ButcherTableau::lobatto_iiib4())
+    }
+
+    /// Solve the problem using the three stage Lobatto IIIC method of order 4.
+    ///
+    /// Being L-stable, it damps stiff components, e.g. of boundary layers.
+    pub fn lobatto_iiic(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::LobattoIIIC)?;
+        self.check_callbacks(Ode::LobattoIIIC)?;
+        self.oderk_implicit(&ButcherTableau::lobatto_iiic4())
+    }
+
+    /// Solve with a low-storage Runge-Kutta method in 2N form.
+    ///
+    /// Besides the solution only a single increment of the size of `Y` is kept
+    /// during a step, regardless of the number of stages.
+    pub fn oderk_low_storage<S: Dim>(
+        &self,
+        coeffs: LowStorageCoeffs<S>,
+    ) -> Result<OdeSolution<f64, Y>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, S>,
+    {
+        self.check_mass_of("oderk_low_storage")?;
+        self.check_callbacks_of("oderk_low_storage")?;
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut ys = Vec::with_capacity(self.tspan.len());
+        ys.push(self.y0.clone());
+
+        let start = self.stats();
+        let dof = self.y0.dof();
+        let mut du = self.y0.clone();
+        let mut k = self.y0.clone();
+
+        for (i, dt) in diff(&self.tspan).into_iter().enumerate() {
+            let t = self.tspan[i];
+            let mut u = ys[i].clone();
+            for s in 0..coeffs.nstages() {
+                self.rhs_into(&mut k, t + coeffs.c[s] * dt, &u);
+                for d in 0..dof {
+                    *du.get_mut(d) = du.get(d) * coeffs.a[s] + k.get(d) * dt;
+                    *u.get_mut(d) += du.get(d) * coeffs.b[s];
+                }
+            }
+            ys.push(u);
+            self.count(|stats| stats.accepted_steps += 1);
+        }
+
+        Ok(OdeSolution::new(self.tspan.clone(), ys).with_stats(self.stats() - start))
+    }
+
+    /// Solve the problem using Williamson's three stage, third order low-storage method.
+    pub fn lsrk33(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Lsrk33)?;
+        self.check_callbacks(Ode::Lsrk33)?;
+        self.oderk_low_storage(LowStorageCoeffs::williamson3())
+    }
+
+    /// Solve the problem using the five stage, fourth order low-storage method of
+    /// Carpenter and Kennedy.
+    pub fn lsrk54(&self) -> Result<OdeSolution<f64, Y>, OdeError> {
+        self.check_mass(Ode::Lsrk54)?;
+        self.check_callbacks(Ode::Lsrk54)?;
+        self.oderk_low_storage(LowStorageCoeffs::ck54())
+    }
+
+    /// Calculates all coefficients values for a given value `yn` at a specific time `t`.
+    ///
+    /// Creates an `CoefficientMap` with the calculated coefficient `k` and their
+    /// approximations `y` of size `S`, the number of stages of the butcher tableau
+    pub fn calc_coefficients<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+        t: f64,
+        init: CoefficientPoint<Y>,
+        dt: f64,
+    ) -> CoefficientMap<Y>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        let mut coeffs = CoefficientMap::with_capacity(btab.nstages());
+        coeffs.push(init);
+        self.calc_coefficients_into(btab, t, dt, &mut coeffs);
+        coeffs
+    }
+
+    /// Calculates the coefficients like [`OdeProblem::calc_coefficients`] into `coeffs`,
+    /// whose first point is the one at `t`.
+    ///
+    /// The points of the other stages are reused as buffers if present, so that a
+    /// problem with an [`InplaceRhs`] evaluates its stages without allocating.
+    pub fn calc_coefficients_into<S: Dim>(
+        &self,
+        btab: &ButcherTableau<S>,
+        t: f64,
+        dt: f64,
+        coeffs: &mut CoefficientMap<Y>,
+    ) where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        coeffs.truncate(btab.nstages());
+        // a coeffs in first row are zero
+        for row in 1..btab.nstages() {
+            if coeffs.len() == row {
+                let buffer = coeffs[0].clone();
+                coeffs.push(buffer);
+            }
+            let (done, rest) = coeffs.split_at_mut(row);
+            let stage = &mut rest[0];
+            stage.y.clone_from(&done[0].y);
+            for (col, point) in done.iter().enumerate() {
+                stage.y.axpy(btab.a[(row, col)] * dt, &point.k);
+            }
+
+            let tn = t + btab.c[row] * dt;
+            // compute the next k value
+            self.rhs_into(&mut stage.k, tn, &stage.y);
+        }
+    }
+
+    /// Evaluates the additional stages of the continuous extension `dense`
+    /// for a step of size `dt` starting at `y`.
+    ///
+    /// `ks` are the stages of the step, the additional stages are appended.
+    pub(crate) fn dense_stages(
+        &self,
+        dense: &DenseCoeffs,
+        t: f64,
+        dt: f64,
+        y: &Y,
+        ks: &mut Vec<Y>,
+    ) {
+        for s in 0..dense.nstages_extra() {
+            let mut yi = y.clone();
+            for (j, k) in ks.iter().enumerate().take(dense.a.ncols()) {
+                yi.axpy(dense.a[(s, j)] * dt, k);
+            }
+            ks.push(self.rhs(t + dense.c[s] * dt, &yi));
+        }
+    }
+
+    /// For dense output see Hairer & Wanner p.190 using Hermite interpolation.
+    fn hermite_interp(&self, tquery: f64, t: f64, dt: f64, y0: &Y, y1: &Y, f0: &Y, f1: &Y) -> Y {
+        let mut y = y0.clone();
+        let theta = (tquery - t) / dt;
+
+        for i in 0..y0.dof() {
+            let val = (y0.get(i) * (1. - theta) + y1.get(i) * theta)
+                + ((y1.get(i) - y0.get(i)) * (1. - 2. * theta)
+                    + f0.get(i) * (theta - 1.) * dt
+                    + f1.get(i) * theta * dt)
+                    * theta
+                    * (theta - 1.);
+
+            y.insert(i, val);
+        }
+        y
+    }
+
+    /// The scaled norm of the error estimate `xerr` of a step from `x0` to `xtrial`,
+    /// with the [`ErrorNorm`] of the problem if set and the weighted root-mean-square
+    /// norm otherwise.
+    pub(crate) fn local_error(
+        &self,
+        x0: &Y,
+        xtrial: &Y,
+        xerr: &Y,
+        abstol: &Tolerance,
+        reltol: &Tolerance,
+    ) -> f64 {
+        if let Some(norm) = &self.error_norm {
+            let mut magnitude = x0.clone();
+            for d in 0..x0.dof() {
+                magnitude.insert(d, x0.modulus_at(d).max(xtrial.modulus_at(d)));
+            }
+            let tolerances = Tolerances {
+                reltol: reltol.clone(),
+                abstol: abstol.clone(),
+            };
+            norm.norm(xerr, &magnitude, &tolerances)
+        } else {
+            xerr.wrms_norm(x0, xtrial, abstol, reltol).into()
+        }
+    }
+
+    /// Estimates the error and a new step size following Hairer & Wanner 1992, p167.
+    pub(crate) fn stepsize_hw92(
+        &self,
+        dt: f64,
+        tdir: f64,
+        x0: &Y,
+        xtrial: &Y,
+        xerr: &Y,
+        order: usize,
+        mut timeout: usize,
+        abstol: &Tolerance,
+        reltol: &Tolerance,
+        maxstep: f64,
+    ) -> StepHW92 {
+        let fac = 0.8;
+        let _facmax = 5.;
+        let facmin = 0.2;
+
+        if xtrial.ode_iter().any(|x| x.into().is_nan()) {
+            return StepHW92 {
+                err: 10.,
+                dt: facmin * dt,
+                timeout_ctn: *StepTimeout::default(),
+            };
+        }
+
+        let err = self.local_error(x0, xtrial, xerr, abstol, reltol);
+
+        let pow = 1. / (order + 1) as f64;
+        let mut new_dt = maxstep.min(facmin.max(err.powi(-1).powf(pow) * fac) * tdir * dt);
+
+        if timeout > 0 {
+            new_dt = new_dt.min(tdir * dt);
+            timeout -= 1;
+        }
+
+        StepHW92 {
+            err,
+            dt: tdir * new_dt,
+            timeout_ctn: timeout,
+        }
+    }
+
+    /// estimator for initial step based on book
+    /// "Solving Ordinary Differential Equations I" by Hairer et al., p.169
+    /// Returns first step, direction of integration and F evaluated at t0
+    pub(crate) fn hinit(
+        &self,
+        x0: &Y,
+        t0: f64,
+        tend: f64,
+        order: usize,
+        reltol: &Tolerance,
+        abstol: &Tolerance,
+    ) -> Result<InitialHint<Y>, OdeError> {
+        hinit(
+            |t, x: &Y| self.rhs(t, x),
+            x0,
+            t0,
+            tend,
+            order,
+            reltol,
+            abstol,
+        )
+    }
+
+    /// Finite differences estimator of the jacobian as fallback, see
+    /// [`FiniteDifferences`]
+    ///
+    /// returns a NxN Matrix where N is the degree of freedom of the `OdeType` `y`
+    pub fn fdjacobian(&self, t: f64, x: &Y) -> DMatrix<T> {
+        self.count(|stats| stats.num_jac += 1);
+        match &self.sparsity {
+            Some((pattern, coloring)) if pattern.shape() == (x.dof(), x.dof()) => self
+                .fd
+                .sparse_jacobian(|t, x: &Y| self.rhs(t, x), t, x, pattern, coloring, None)
+                .to_dense(),
+            _ => self.fd.jacobian(|t, x: &Y| self.rhs(t, x), t, x, None),
+        }
+    }
+
+    /// The jacobian `∂F/∂y` of the RHS at `(t, x)`.
+    ///
+    /// Falls back to [`OdeProblem::fdjacobian`] if no jacobian was provided. A provided
+    /// jacobian is compared against finite differences if enabled with
+    /// [`OdeBuilder::check_jacobian`].
+    pub fn jacobian(&self, t: f64, x: &Y) -> Result<DMatrix<T>, OdeError> {
+        let jac = match &self.jac {
+            Some(jac) => jac,
+            None => return Ok(self.fdjacobian(t, x)),
+        };
+        self.count(|stats| stats.num_jac += 1);
+        let dfdx = jac.eval(t, x);
+        let n = x.dof();
+        if dfdx.shape() != (n, n) {
+            return Err(OdeError::DimensionMismatch {
+                expected: (n, n),
+                found: dfdx.shape(),
+            });
+        }
+        if let Some(tol) = self.jac_check {
+            jacobian::check(&dfdx, |t, y: &Y| self.rhs(t, y), t, x, tol)?;
+        }
+        Ok(dfdx)
+    }
+
+    /// The product `∂F/∂y (t, x) v` of the jacobian of the RHS with `v`, `fx` is
+    /// `F(t, x)` if already known.
+    ///
+    /// Falls back to [`FiniteDifferences::directional`] if no product was provided with
+    /// [`OdeBuilder::jvp`].
+    pub fn jvp(&self, t: f64, x: &Y, v: &Y, fx: Option<&Y>) -> Y {
+        match &self.jvp {
+            Some(jvp) => jvp.eval(t, x, v),
+            None => self.fd.directional(|t, x: &Y| self.rhs(t, x), t, x, v, fx),
+        }
+    }
+
+    /// The jacobian at `(t, x)` as the operator `v ↦ ∂F/∂y v` on the components, see
+    /// [`OdeProblem::jvp`].
+    fn jacobian_operator<'a>(
+        &'a self,
+        t: f64,
+        x: &'a Y,
+    ) -> impl Fn(&DVector<T>) -> DVector<T> + 'a {
+        let fx = match (&self.jvp, self.fd.scheme) {
+            (None, DifferenceScheme::Forward) => Some(self.rhs(t, x)),
+            _ => None,
+        };
+        move |v: &DVector<T>| {
+            let mut vy = x.clone();
+            for d in 0..v.len() {
+                vy.insert(d, v[d]);
+            }
+            DVector::from_iterator(v.len(), self.jvp(t, x, &vy, fx.as_ref()).ode_iter())
+        }
+    }
+
+    /// The preconditioner of the matrix-free solves at `(t, x)` if one was set with
+    /// [`OdeBuilder::preconditioner`].
+    ///
+    /// The built-in preconditioners evaluate the jacobian on their pattern once, which is
+    /// shifted to `M - γ J` and factorized for each `γ`.
+    fn preconditioner_setup<'a>(
+        &'a self,
+        t: f64,
+        x: &'a Y,
+    ) -> Result<Option<PreconditionerSetup<'a, T>>, OdeError> {
+        let preconditioner = match &self.preconditioner {
+            Some(preconditioner) => preconditioner,
+            None => return Ok(None),
+        };
+        let n = x.dof();
+        let setup: SetupFunction<'a, T> = match preconditioner.kind() {
+            PreconditionerKind::Custom(setup) => Box::new(move |_mass, gamma| {
+                let apply = setup(t, x, gamma);
+                let solve = move |r: &DVector<T>| {
+                    let mut ry = x.clone();
+                    for d in 0..n {
+                        ry.insert(d, r[d]);
+                    }
+                    DVector::from_iterator(n, apply(&ry).ode_iter())
+                };
+                Ok(Rc::new(solve) as PreconditionerSolve<T>)
+            }),
+            PreconditionerKind::Banded { lower, upper } => {
+                let (lower, upper) = (*lower, *upper);
+                let pattern = Rc::new(SparsityPattern::banded(n, lower, upper));
+                let jac = self.pattern_jacobian(t, x, &pattern, &pattern.coloring())?;
+                Box::new(move |mass, gamma| {
+                    let lu = BandedLu::from_sparse(&jac.shifted(mass, gamma), lower, upper)
+                        .ok_or(OdeError::InvalidMatrix)?;
+                    Ok(Rc::new(move |r: &DVector<T>| lu.solve(r)) as PreconditionerSolve<T>)
+                })
+            }
+            PreconditionerKind::Ilu0 => {
+                let (pattern, coloring) = self.sparsity.as_ref().ok_or_else(|| {
+                    OdeError::uninitialized("The ILU(0) preconditioner requires a sparsity pattern")
+                })?;
+                let jac = self.pattern_jacobian(t, x, pattern, coloring)?;
+                Box::new(move |mass, gamma| {
+                    let ilu = jac
+                        .shifted(mass, gamma)
+                        .ilu0()
+                        .ok_or(OdeError::InvalidMatrix)?;
+                    Ok(Rc::new(move |r: &DVector<T>| ilu.solve(r)) as PreconditionerSolve<T>)
+                })
+            }
+        };
+        Ok(Some(PreconditionerSetup {
+            side: preconditioner.side(),
+            setup,
+        }))
+    }
+
+    /// The entries of the jacobian `∂F/∂y` at `(t, x)` in `pattern`, by finite
+    /// differences with the `coloring` of the pattern unless it was set with
+    /// [`OdeBuilder::jac`].
+    fn pattern_jacobian(
+        &self,
+        t: f64,
+        x: &Y,
+        pattern: &Rc<SparsityPattern>,
+        coloring: &Coloring,
+    ) -> Result<CscMatrix<T>, OdeError> {
+        let n = x.dof();
+        if pattern.shape() != (n, n) {
+            return Err(OdeError::DimensionMismatch {
+                expected: (n, n),
+                found: pattern.shape(),
+            });
+        }
+        if self.jac.is_some() {
+            return Ok(CscMatrix::from_dense(
+                Rc::clone(pattern),
+                &self.jacobian(t, x)?,
+            ));
+        }
+        self.count(|stats| stats.num_jac += 1);
+        Ok(self
+            .fd
+            .sparse_jacobian(|t, x: &Y| self.rhs(t, x), t, x, pattern, coloring, None))
+    }
+
+    /// The jacobian `∂F/∂y` of the RHS at `(t, x)` the iteration matrices of the
+    /// implicit Runge–Kutta methods are formed from, stored sparsely if the problem
+    /// declares a [`SparsityPattern`] and has no mass matrix.
+    pub fn iteration_jacobian(&self, t: f64, x: &Y) -> Result<JacobianMatrix<T>, OdeError> {
+        match &self.sparsity {
+            Some((pattern, coloring)) if self.mass.is_none() => self
+                .pattern_jacobian(t, x, pattern, coloring)
+                .map(JacobianMatrix::Sparse),
+            _ => self.jacobian(t, x).map(JacobianMatrix::Dense),
+        }
+    }
+
+    /// The partial derivative `∂F/∂t` of the RHS at `(t, x)`.
+    ///
+    /// Falls back to forward finite differences if no time derivative was provided.
+    pub fn tgrad(&self, t: f64, x: &Y) -> Y {
+        if let Some(tgrad) = &self.tgrad {
+            return tgrad(t, x);
+        }
+        let dt = f64::EPSILON.sqrt() * t.abs().max(1.);
+        let f0 = self.rhs(t, x);
+        let mut dfdt = self.rhs(t + dt, x);
+        for i in 0..dfdt.dof() {
+            *dfdt.get_mut(i) = (dfdt.get(i) - f0.get(i)) * (1. / dt);
+        }
+        dfdt
+    }
+
+    /// The RHS `F(t, x)`.
+    #[inline]
+    pub fn rhs(&self, t: f64, x: &Y) -> Y {
+        self.count(|stats| stats.num_eval += 1);
+        (self.f)(t, x)
+    }
+
+    /// Evaluates the RHS at `(t, x)` into `dx`, in place if the problem was built with
+    /// [`OdeBuilder::fun_inplace`].
+    pub fn rhs_into(&self, dx: &mut Y, t: f64, x: &Y) {
+        self.count(|stats| stats.num_eval += 1);
+        match &self.f_inplace {
+            Some(f) => f.eval(dx, t, x),
+            None => *dx = (self.f)(t, x),
+        }
+    }
+
+    /// The statistics of all solves of the problem so far, the solutions hold the
+    /// statistics of their own solve.
+    #[inline]
+    pub fn stats(&self) -> Diagnostics {
+        self.stats.get()
+    }
+
+    /// The counter of the statistics.
+    #[inline]
+    pub(crate) fn counter(&self) -> &Cell<Diagnostics> {
+        &self.stats
+    }
+
+    /// How the linear systems of the implicit methods are solved.
+    #[inline]
+    pub(crate) fn linear_solver(&self) -> LinearSolverType {
+        self.linear_solver
+    }
+
+    /// Updates the statistics.
+    #[inline]
+    pub(crate) fn count<U: FnOnce(&mut Diagnostics)>(&self, update: U) {
+        count(&self.stats, update)
+    }
+
+    /// The initial value.
+    #[inline]
+    pub fn y0(&self) -> &Y {
+        &self.y0
+    }
+
+    /// The t values at which the solution is requested.
+    #[inline]
+    pub fn tspan(&self) -> &[f64] {
+        &self.tspan
+    }
+
+    /// The events at which the integration is interrupted.
+    #[inline]
+    pub fn callbacks(&self) -> &CallbackSet<Y> {
+        &self.callbacks
+    }
+
+    /// Fails with [`OdeError::UnsupportedSolver`] if the problem has callbacks, which
+    /// `method` doesn't respect.
+    fn check_callbacks(&self, method: Ode) -> Result<(), OdeError> {
+        if self.callbacks.is_empty() {
+            Ok(())
+        } else {
+            Err(OdeError::UnsupportedSolver {
+                method,
+                feature: "callbacks",
+            })
+        }
+    }
+
+    /// Fails with [`OdeError::UnsupportedMethod`] if the problem has callbacks, which the
+    /// generic `method` doesn't respect.
+    fn check_callbacks_of(&self, method: &'static str) -> Result<(), OdeError> {
+        if self.callbacks.is_empty() {
+            Ok(())
+        } else {
+            Err(OdeError::UnsupportedMethod {
+                method,
+                feature: "callbacks",
+            })
+        }
+    }
+
+    /// Fails with [`OdeError::UnsupportedSolver`] if the problem has a mass matrix, which
+    /// `method` doesn't respect.
+    fn check_mass(&self, method: Ode) -> Result<(), OdeError> {
+        match self.mass {
+            Some(_) => Err(OdeError::UnsupportedSolver {
+                method,
+                feature: "mass matrices",
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails with [`OdeError::UnsupportedMethod`] if the problem has a mass matrix, which
+    /// the generic `method` doesn't respect.
+    pub(crate) fn check_mass_of(&self, method: &'static str) -> Result<(), OdeError> {
+        match self.mass {
+            Some(_) => Err(OdeError::UnsupportedMethod {
+                method,
+                feature: "mass matrices",
+            }),
+            None => Ok(()),
         }
-        dfdx
     }
+
+    /// The mass matrix at `(t, x)`, the identity if no mass matrix was provided.
+    pub fn mass_matrix(&self, t: f64, x: &Y) -> DMatrix<T> {
+        match &self.mass {
+            Some(mass) => mass(t, x),
+            None => DMatrix::identity(x.dof(), x.dof()),
+        }
+    }
+}
+
+/// Updates the statistics in `stats`.
+#[inline]
+pub(crate) fn count<U: FnOnce(&mut Diagnostics)>(stats: &Cell<Diagnostics>, update: U) {
+    let mut current = stats.get();
+    update(&mut current);
+    stats.set(current);
 }
 
 /// Finite difference operator on a vector
@@ -930,93 +3803,1335 @@ pub fn diff<R: RealField>(a: &[R]) -> Vec<R> {
         .collect()
 }
 
-#[derive(Debug)]
-pub struct InitialHint<Y> {
-    /// step size hint
-    h: f64,
-    /// signum(tend - t0)
-    tdir: f64,
-    /// initial evaluation of the problem function
-    f0: Y,
-}
+/// Estimates the size of the first step of a method of `order` for the RHS `f`, based
+/// on ‖f(t0, x0)‖ and an explicit Euler trial step, c.f. Hairer, Nørsett & Wanner,
+/// "Solving Ordinary Differential Equations I", p.169.
+///
+/// Returns first step, direction of integration and F evaluated at t0
+pub(crate) fn hinit<F, Y, T>(
+    f: F,
+    x0: &Y,
+    t0: f64,
+    tend: f64,
+    order: usize,
+    reltol: &Tolerance,
+    abstol: &Tolerance,
+) -> Result<InitialHint<Y>, OdeError>
+where
+    F: Fn(f64, &Y) -> Y,
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    let tdir = signum(tend - t0);
+    if tdir == 0. {
+        return Err(OdeError::ZeroTimeSpan);
+    }
+
+    let one = Y::Item::one();
+    // the norm weighted by the tolerance of each component
+    let tau = |d: usize| (x0.modulus_at(d) * reltol.at(d)).max(one * abstol.at(d));
+    let scaled = |x: &Y| {
+        (0..x.dof())
+            .map(|d| x.get(d).abs() / tau(d))
+            .fold(T::zero(), |acc, v| acc.max(v))
+    };
+    let d0 = scaled(x0);
+    let f0 = f(t0, x0);
+    let d1 = scaled(&f0);
+
+    let h0: f64 = if d0 < one * 1e-5 || d1 < one * 1e-5 {
+        1.0e-6
+    } else {
+        0.01 * (d0 / d1).into()
+    };
+
+    // perform Euler step, in every dimension
+    let mut x1 = x0.clone();
+    for d in 0..x1.dof() {
+        *x1.get_mut(d) += f0.get(d) * h0 * tdir;
+    }
+    // estimate second derivative
+    let mut f1_0 = f(t0 + tdir * h0, &x1);
+    for d in 0..f1_0.dof() {
+        *f1_0.get_mut(d) -= f0.get(d);
+    }
+    let d2 = scaled(&f1_0) / (one * h0);
+
+    let h1: f64 = if d1.max(d2) < one * 1e-15f64 {
+        1.0e-6f64.max(1.0e-3f64 * h0)
+    } else {
+        let pow = -(2. + d1.max(d2).log10().into()) / ((order + 1) as f64);
+        10f64.powf(pow)
+    };
+
+    let h = tdir * h1.min(100. * h0).min(tdir * (tend - t0));
+
+    Ok(InitialHint { h, tdir, f0 })
+}
+
+#[derive(Debug)]
+pub struct InitialHint<Y> {
+    /// step size hint
+    pub(crate) h: f64,
+    /// signum(tend - t0)
+    pub(crate) tdir: f64,
+    /// initial evaluation of the problem function
+    pub(crate) f0: Y,
+}
+
+#[derive(Debug)]
+pub struct StepHW92 {
+    pub(crate) err: f64,
+    pub(crate) dt: f64,
+    pub(crate) timeout_ctn: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::controller::ControllerType;
+    use crate::ode::krylov::{GmresOptions, Preconditioner, PreconditionerSide};
+    use crate::ode::newton::JacobianReuse;
+    use crate::ode::options::{
+        Abstol, Beta2, Controller, Dense, Dop853Options, ForceDtmin, Initstep, InterpOrder,
+        MaxIters, MaxOrder, Maxstep, Minstep, OdeOp, Qmax, Reltol, Saveat, Stiffness,
+    };
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    const DT: f64 = 0.001;
+    const TF: f64 = 100.0;
+
+    // Initial position in space
+    const Y0: [f64; 3] = [0.1, 0.0, 0.0];
+
+    // Constants SIGMA, RHO and beta
+    const SIGMA: f64 = 10.0;
+    const RHO: f64 = 28.0;
+    const BET: f64 = 8.0 / 3.0;
+
+    #[allow(clippy::ptr_arg)]
+    fn lorenz_attractor(_t: f64, v: &Vec<f64>) -> Vec<f64> {
+        // extract coordinates from the vec
+        let (x, y, z) = (v[0], v[1], v[2]);
+
+        // Lorenz equations
+        let dx_dt = SIGMA * (y - x);
+        let dy_dt = x * (RHO - z) - y;
+        let dz_dt = x * y - BET * z;
+
+        // derivatives as vec
+        vec![dx_dt, dy_dt, dz_dt]
+    }
+
+    fn lorenz_problem() -> OdeProblem<impl Fn(f64, &Vec<f64>) -> Vec<f64>, Vec<f64>> {
+        OdeProblem::builder()
+            .tspan_linspace(0., TF, 100_001)
+            .fun(lorenz_attractor)
+            .init(vec![0.1, 0., 0.])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn diff_test() {
+        let a = vec![2., 6., 4., 16.];
+        assert_eq!(vec![4.0, -2.0, 12.0], diff(&a));
+
+        let v: Vec<f64> = Vec::new();
+        assert_eq!(v, diff(&v));
+    }
+
+    #[test]
+    fn dopri5_nonautonomous() {
+        // `y' = cos t` depends on the nodes, which don't matter for autonomous problems
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|t, _y: &f64| t.cos())
+            .init(0.)
+            .build()
+            .unwrap();
+        let solution = problem.oderk_fixed(&ButcherTableau::dopri5()).unwrap();
+        for (t, y) in solution.zipped() {
+            assert!((y - t.sin()).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn ode45_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        let _solution = lorenz_problem().ode45(Default::default()).unwrap();
+    }
+
+    #[test]
+    fn dop853_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-10).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-10).into());
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let solution = problem
+            .dop853(Dop853Options::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(101, solution.tout.len());
+        for (t, y) in solution.zipped() {
+            assert!((y.0 - t.cos()).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn dop853_controller() {
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let steps = |opts: Dop853Options| {
+            let solution = problem.dop853(opts).unwrap();
+            assert!((solution.last().unwrap().y.0 - 10f64.cos()).abs() < 1e-6);
+            solution.tout
+        };
+        let opts = || Dop853Options {
+            reltol: Reltol::from(1e-8),
+            abstol: Abstol::from(1e-8),
+            ..Default::default()
+        };
+        let pi = steps(opts());
+        // without the memory of the previous error
+        let integral = steps(Dop853Options {
+            beta2: Some(Beta2(0.)),
+            ..opts()
+        });
+        assert_ne!(pi, integral);
+        let damped = steps(Dop853Options {
+            qmax: Qmax(1.5),
+            ..opts()
+        });
+        assert!(damped.len() > pi.len());
+        let pid = steps(Dop853Options {
+            controller: Controller(ControllerType::Pid),
+            ..opts()
+        });
+        assert_ne!(pi, pid);
+    }
+
+    #[test]
+    fn tsit5_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &f64| -*y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let solution = problem.tsit5(Default::default()).unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(2., t);
+        assert!((y - (-t).exp()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn array_test() {
+        // harmonic oscillator `y'' = -y` with a fixed-size state
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &[f64; 2]| [y[1], -y[0]])
+            .init([1., 0.])
+            .build()
+            .unwrap();
+        let check = |solution: OdeSolution<f64, [f64; 2]>| {
+            let (t, y) = solution.zipped().pop().unwrap();
+            assert!((y[0] - t.cos()).abs() < 1e-4);
+        };
+        check(problem.tsit5(Default::default()).unwrap());
+        check(problem.radau5(Default::default()).unwrap());
+    }
+
+    #[test]
+    fn force_dtmin_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 3)
+            .fun(|_t, y: &Vec<f64>| vec![y[1], -y[0]])
+            .init(vec![1., 0.])
+            .build()
+            .unwrap();
+        // the tolerance asks for much smaller steps than the minimum step size, so the
+        // large initial step is rejected down to it
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-12).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-12).into());
+        ops.insert(Minstep::option_name(), Minstep(0.25).into());
+        ops.insert(Initstep::option_name(), Initstep(0.5).into());
+        let check = |solution: OdeSolution<f64, Vec<f64>>, retcode| {
+            assert_eq!(retcode, solution.retcode);
+            let (t, y) = solution.zipped().pop().unwrap();
+            if retcode == ReturnCode::Success {
+                assert_eq!(2., t);
+                assert!((y[0] - t.cos()).abs() < 1e-4);
+            } else {
+                assert!(t < 2.);
+            }
+        };
+        check(
+            problem
+                .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::DtLessThanMin,
+        );
+        check(
+            problem
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::DtLessThanMin,
+        );
+        check(
+            problem
+                .dop853(Dop853Options::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::DtLessThanMin,
+        );
+        ops.insert(ForceDtmin::option_name(), ForceDtmin(true).into());
+        check(
+            problem
+                .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::Success,
+        );
+        check(
+            problem
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::Success,
+        );
+        check(
+            problem
+                .dop853(Dop853Options::from_map(&ops).unwrap())
+                .unwrap(),
+            ReturnCode::Success,
+        );
+    }
+
+    #[test]
+    fn user_defined_tableau() {
+        use crate::ode::runge_kutta::{RKOrder, RKSymbol};
+        // Bogacki–Shampine 3(2) from borrowed coefficients
+        #[rustfmt::skip]
+        static A: [f64; 16] = [
+            0., 0., 0., 0.,
+            0.5, 0., 0., 0.,
+            0., 0.75, 0., 0.,
+            2. / 9., 1. / 3., 4. / 9., 0.,
+        ];
+        static B: [f64; 4] = [2. / 9., 1. / 3., 4. / 9., 0.];
+        static B_ERR: [f64; 4] = [7. / 24., 0.25, 1. / 3., 0.125];
+        static C: [f64; 4] = [0., 0.5, 0.75, 1.];
+        let symbol = RKSymbol::Other(("bs3".to_string(), RKOrder::Adaptive((3, 2))));
+        let btab = ButcherTableau::from_slices(symbol.clone(), &A, &B, Some(&B_ERR), &C).unwrap();
+
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 11)
+            .fun(|_t, y: &Vec<f64>| vec![y[1], -y[0]])
+            .init(vec![1., 0.])
+            .build()
+            .unwrap();
+        let expected = problem.bs3(Default::default()).unwrap();
+        let solution = problem
+            .oderk_adapt(&btab, AdaptiveOptions::default())
+            .unwrap();
+        assert_eq!(expected.tout, solution.tout);
+        assert_eq!(expected.yout, solution.yout);
+        let mut integrator = problem
+            .integrator(btab, None, AdaptiveOptions::default())
+            .unwrap();
+        while integrator.step().unwrap() {}
+        assert_eq!(expected.yout.last(), Some(integrator.y()));
+
+        // the explicit weights step with a fixed step size
+        let euler = ButcherTableau::from_slices(symbol.clone(), &[0.], &[1.], None, &[0.]).unwrap();
+        let solution = problem.clone().oderk_fixed(&euler).unwrap();
+        assert_eq!(11, solution.yout.len());
+
+        let inconsistent = [0., 0., 0.5, 0.];
+        assert!(ButcherTableau::from_slices(
+            symbol.clone(),
+            &inconsistent,
+            &[0.5, 0.5],
+            None,
+            &[0., 1.]
+        )
+        .is_err());
+        assert!(ButcherTableau::from_slices(symbol, &A, &B[..3], None, &C).is_err());
+    }
+
+    #[test]
+    fn implicit_tableaus() {
+        use crate::ode::runge_kutta::{RKOrder, RKSymbol};
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|t: f64, y: &Vec<f64>| vec![-50. * (y[0] - t.cos())])
+            .init(vec![0.])
+            .build()
+            .unwrap();
+
+        // the coupled stages are solved instead of being computed explicitly
+        let expected = problem.gauss4().unwrap();
+        let solution = problem
+            .clone()
+            .oderk_fixed(&ButcherTableau::gauss4())
+            .unwrap();
+        assert_eq!(ReturnCode::Success, solution.retcode);
+        assert_eq!(expected.yout, solution.yout);
+
+        // Lobatto IIIC with an explicit euler step for error-checking
+        let symbol = RKSymbol::Other(("lobatto3c".to_string(), RKOrder::Adaptive((2, 1))));
+        let btab = ButcherTableau::from_slices(
+            symbol,
+            &[0.5, -0.5, 0.5, 0.5],
+            &[0.5, 0.5],
+            Some(&[1., 0.]),
+            &[0., 1.],
+        )
+        .unwrap();
+        let opts = ImplicitOptions {
+            reltol: Reltol::from(1e-6),
+            abstol: Abstol::from(1e-8),
+            ..ImplicitOptions::default()
+        };
+        let solution = problem.oderk_adapt(&btab, opts.clone()).unwrap();
+        let radau5 = problem.radau5(opts.clone()).unwrap();
+        let (y, expected) = (solution.yout.last().unwrap(), radau5.yout.last().unwrap());
+        assert!((y[0] - expected[0]).abs() < 1e-4);
+        assert!(problem.integrator(btab, None, opts).is_err());
+    }
+
+    #[test]
+    fn fsal_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 1., 11)
+            .fun(|_t, y: &Vec<f64>| vec![y[1], -y[0]])
+            .init(vec![1., 0.])
+            .build()
+            .unwrap();
+        // the first stage is only evaluated for the first step
+        let solution = problem.clone().oderk_fixed(&ButcherTableau::bs3()).unwrap();
+        assert_eq!(1 + 3 * 10, solution.stats.num_eval);
+        let y = solution.yout.last().unwrap();
+        assert!((y[0] - 1f64.cos()).abs() < 1e-4);
+        let solution = problem.clone().oderk_fixed(&ButcherTableau::rk4()).unwrap();
+        assert_eq!(4 * 10, solution.stats.num_eval);
+
+        // six new stages per step of the auto-switching method, after the initial ones
+        let solution = problem.auto_tsit5(Default::default()).unwrap();
+        let steps = solution.stats.accepted_steps + solution.stats.rejected_steps;
+        assert!(
+            solution.stats.num_eval <= 6 * steps + 3,
+            "{}",
+            solution.stats
+        );
+    }
+
+    #[test]
+    fn sparse_points_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &Vec<f64>| vec![y[1], -y[0]])
+            .init(vec![1., 0.])
+            .build()
+            .unwrap();
+        let solve = |points: Points| {
+            vec![
+                problem
+                    .tsit5(ExplicitRkOptions {
+                        points: points.clone(),
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                problem
+                    .dop853(Dop853Options {
+                        points: points.clone(),
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                problem
+                    .ode23s(BasicOptions {
+                        points: points.clone(),
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                problem
+                    .bdf(MultistepOptions {
+                        points: points.clone(),
+                        ..Default::default()
+                    })
+                    .unwrap(),
+                problem
+                    .radau5(ImplicitOptions {
+                        points,
+                        ..Default::default()
+                    })
+                    .unwrap(),
+            ]
+        };
+        let all = solve(Points::All);
+        for (solution, all) in solve(Points::Last).into_iter().zip(&all) {
+            assert_eq!(vec![10.], solution.tout);
+            assert_eq!(all.yout.last(), solution.yout.last());
+        }
+        for (solution, all) in solve(Points::Every(3)).into_iter().zip(&all) {
+            // the initial state, every third step and the final state
+            let steps = all.stats.accepted_steps as usize;
+            assert_eq!(1 + steps.div_ceil(3), solution.tout.len());
+            assert_eq!(Some(&10.), solution.tout.last());
+            assert_eq!(all.yout.last(), solution.yout.last());
+        }
+    }
+
+    #[test]
+    fn solver_options_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 11)
+            .fun(|_t, y: &Vec<f64>| vec![y[1], -y[0]])
+            .init(vec![1., 0.])
+            .build()
+            .unwrap();
+        let opts = ImplicitOptions::builder()
+            .reltol(1e-8)
+            .abstol(1e-8)
+            .maxstep(0.1)
+            .build()
+            .unwrap();
+        let typed = problem.radau5(opts).unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.set(Reltol::from(1e-8));
+        ops.set(Abstol::from(1e-8));
+        ops.set(Maxstep(0.1));
+        let mapped = problem
+            .radau5(ImplicitOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(mapped.tout, typed.tout);
+        assert_eq!(mapped.yout, typed.yout);
+
+        // options the solvers ignore are rejected
+        ops.set(Controller(ControllerType::Pid));
+        assert!(matches!(
+            ImplicitOptions::from_map(&ops),
+            Err(OdeError::UnhonoredOption {
+                name: "Controller",
+                options: "ImplicitOptions"
+            })
+        ));
+        assert!(matches!(
+            problem.clone().solve(Ode::Radau5, ops),
+            Err(OdeError::UnhonoredOption { .. })
+        ));
+
+        let opts = ExplicitRkOptions::builder()
+            .points(Points::Specified)
+            .build()
+            .unwrap();
+        let solution = problem.tsit5(opts).unwrap();
+        assert_eq!(11, solution.len());
+    }
+
+    #[test]
+    fn inplace_test() {
+        // the same Lotka-Volterra problem with an allocating and an in-place RHS
+        let allocating = OdeProblem::builder()
+            .tspan_linspace(0., 5., 11)
+            .init(vec![1., 1.])
+            .fun(|_t, y: &Vec<f64>| vec![1.5 * y[0] - y[0] * y[1], y[0] * y[1] - 3. * y[1]])
+            .build()
+            .unwrap();
+        let inplace = OdeProblem::builder()
+            .tspan_linspace(0., 5., 11)
+            .init(vec![1., 1.])
+            .fun_inplace(|dy: &mut Vec<f64>, _t, y: &Vec<f64>| {
+                dy[0] = 1.5 * y[0] - y[0] * y[1];
+                dy[1] = y[0] * y[1] - 3. * y[1];
+            })
+            .build()
+            .unwrap();
+        let expected = allocating.clone().tsit5(Default::default()).unwrap();
+        let solution = inplace.clone().tsit5(Default::default()).unwrap();
+        assert_eq!(expected.yout, solution.yout);
+        assert_eq!(expected.stats.num_eval, solution.stats.num_eval);
+
+        let expected = allocating.radau5(Default::default()).unwrap();
+        let solution = inplace.radau5(Default::default()).unwrap();
+        assert_eq!(expected.yout, solution.yout);
+        assert_eq!(expected.stats.num_eval, solution.stats.num_eval);
+
+        // the fixed step methods
+        for ode in [Ode::Ode4, Ode::Ssprk33, Ode::Ssprk104, Ode::Lsrk54] {
+            let expected = allocating
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            let solution = inplace.clone().solve(ode, Default::default()).unwrap();
+            assert_eq!(expected.yout, solution.yout);
+            assert_eq!(expected.stats.num_eval, solution.stats.num_eval);
+        }
+    }
+
+    #[test]
+    fn complex_test() {
+        use na::Complex;
+        // two-level Schroedinger equation `i psi' = H psi` with `H = [0 w/2; w/2 0]`,
+        // Rabi oscillations `psi = (cos(w t / 2), -i sin(w t / 2))`
+        let w = 2.;
+        let h = na::Matrix2::new(0., w / 2., w / 2., 0.).map(|x| Complex::new(x, 0.));
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 3., 31)
+            .fun(move |_t, psi: &na::Vector2<Complex<f64>>| (h * psi) * -Complex::i())
+            .init(na::Vector2::new(Complex::new(1., 0.), Complex::new(0., 0.)))
+            .build()
+            .unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        let check = |solution: OdeSolution<f64, na::Vector2<Complex<f64>>>, tol: f64| {
+            let (t, psi) = solution.zipped().pop().unwrap();
+            let exact = na::Vector2::new(
+                Complex::new((w * t / 2.).cos(), 0.),
+                Complex::new(0., -(w * t / 2.).sin()),
+            );
+            assert!((psi - exact).norm() < tol);
+        };
+        check(
+            problem
+                .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-6,
+        );
+        check(
+            problem
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-6,
+        );
+        check(
+            problem
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-4,
+        );
+    }
+
+    #[test]
+    fn derive_test() {
+        use crate::ode::types::OdeType;
+
+        #[derive(Debug, Clone, OdeType)]
+        struct State {
+            x: f64,
+            v: f64,
+        }
+
+        // harmonic oscillator `x'' = -x` with named components
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &State| State { x: y.v, v: -y.x })
+            .init(State { x: 1., v: 0. })
+            .build()
+            .unwrap();
+        let check = |solution: OdeSolution<f64, State>| {
+            let (t, y) = solution.zipped().pop().unwrap();
+            assert!((y.x - t.cos()).abs() < 1e-4);
+            assert!((y.v + t.sin()).abs() < 1e-4);
+        };
+        check(problem.tsit5(Default::default()).unwrap());
+        check(problem.radau5(Default::default()).unwrap());
+    }
+
+    #[test]
+    fn matrix_test() {
+        // Lyapunov equation `X' = A X + X A^T` with solution `exp(A t) exp(A^T t)`
+        let a = na::Matrix2::new(-1., 1., 0., -2.);
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 1., 11)
+            .fun(move |_t, x: &na::Matrix2<f64>| a * x + x * a.transpose())
+            .init(na::Matrix2::identity())
+            .build()
+            .unwrap();
+        let exact = |t: f64| {
+            let (e1, e2) = ((-t).exp(), (-2. * t).exp());
+            let e = na::Matrix2::new(e1, e1 - e2, 0., e2);
+            e * e.transpose()
+        };
+        let check = |solution: OdeSolution<f64, na::Matrix2<f64>>| {
+            let (t, x) = solution.zipped().pop().unwrap();
+            assert!((x - exact(t)).amax() < 1e-4);
+        };
+        check(problem.tsit5(Default::default()).unwrap());
+        check(problem.radau5(Default::default()).unwrap());
+
+        let dynamic = OdeProblem::builder()
+            .tspan_linspace(0., 1., 11)
+            .fun(move |_t, x: &DMatrix<f64>| {
+                let a = DMatrix::from_column_slice(2, 2, a.as_slice());
+                &a * x + x * a.transpose()
+            })
+            .init(DMatrix::identity(2, 2))
+            .build()
+            .unwrap();
+        let (t, x) = dynamic
+            .bdf(Default::default())
+            .unwrap()
+            .zipped()
+            .pop()
+            .unwrap();
+        assert!((x - DMatrix::from_column_slice(2, 2, exact(t).as_slice())).amax() < 1e-3);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_test() {
+        use ndarray::{arr1, Array1};
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &Array1<f64>| arr1(&[y[1], -y[0]]))
+            .init(arr1(&[1., 0.]))
+            .build()
+            .unwrap();
+        let check = |solution: OdeSolution<f64, Array1<f64>>| {
+            let (t, y) = solution.zipped().pop().unwrap();
+            assert!((y[0] - t.cos()).abs() < 1e-4);
+        };
+        check(problem.tsit5(Default::default()).unwrap());
+        check(problem.radau5(Default::default()).unwrap());
+    }
+
+    #[test]
+    fn ode45_ck_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &f64| -*y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let solution = problem.solve(Ode::Ode45ck, Default::default()).unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(2., t);
+        assert!((y - (-t).exp()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn ode78_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-12).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-12).into());
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &f64| -*y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let solution = problem
+            .ode78(ExplicitRkOptions::from_map(&ops).unwrap())
+            .unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(2., t);
+        assert!((y - (-t).exp()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn ssprk104_low_storage() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|t, y: &f64| t.sin() - *y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let low_storage = problem.clone().ssprk104().unwrap();
+        let tableau = problem.oderk_fixed(&ButcherTableau::ssprk104()).unwrap();
+        for (y1, y2) in low_storage.yout.iter().zip(tableau.yout.iter()) {
+            assert!((y1 - y2).abs() < 1e-14);
+        }
+
+        let empty = OdeProblem::builder()
+            .tspan(Vec::new())
+            .fun(|t, y: &f64| t.sin() - *y)
+            .init(1.)
+            .build()
+            .unwrap();
+        assert!(empty.ssprk104().unwrap().tout.is_empty());
+    }
+
+    #[test]
+    fn lsrk54_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &f64| -*y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let solution = problem.lsrk54().unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(2., t);
+        assert!((y - (-t).exp()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn beuler_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|t, y: &f64| -1000. * (*y - t.cos()))
+            .init(0.)
+            .build()
+            .unwrap();
+        let solution = problem.beuler().unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert!((y - t.cos()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn trapezoid_test() {
+        // heat equation `u_t = u_xx` on three interior grid points
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 1., 11)
+            .fun(|_t, u: &Vec<f64>| {
+                let dx2 = 0.25f64.powi(2);
+                (0..u.len())
+                    .map(|i| {
+                        let left = if i > 0 { u[i - 1] } else { 0. };
+                        let right = if i + 1 < u.len() { u[i + 1] } else { 0. };
+                        (left - 2. * u[i] + right) / dx2
+                    })
+                    .collect()
+            })
+            .init(vec![1., 1., 1.])
+            .build()
+            .unwrap();
+        let fixed = problem.trapezoid().unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        let adapt = problem
+            .trapezoid_adapt(ImplicitOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(11, adapt.tout.len());
+        let (_, fixed) = fixed.zipped().pop().unwrap();
+        let (_, adapt) = adapt.zipped().pop().unwrap();
+        for (yf, ya) in fixed.iter().zip(adapt.iter()) {
+            assert!(yf.abs() < 1e-2);
+            assert!(ya.abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn sdirk_test() {
+        // robertson chemical kinetics
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 40., 2)
+            .fun(|_t, y: &Vec<f64>| {
+                vec![
+                    -0.04 * y[0] + 1e4 * y[1] * y[2],
+                    0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+                    3e7 * y[1] * y[1],
+                ]
+            })
+            .init(vec![1., 0., 0.])
+            .build()
+            .unwrap();
+        for ode in [
+            Ode::Sdirk2,
+            Ode::Sdirk4,
+            Ode::Kvaerno4,
+            Ode::TrBdf2,
+            Ode::Radau5,
+            Ode::Rodas4,
+            Ode::Rodas5,
+            Ode::AutoTsit5,
+            Ode::Bdf,
+        ] {
+            let solution = problem.clone().solve(ode, Default::default()).unwrap();
+            let (t, y) = solution.zipped().pop().unwrap();
+            assert_eq!(40., t);
+            // reference value of y1(40)
+            assert!((y[0] - 0.7158270687).abs() < 1e-3);
+            assert!((y[0] + y[1] + y[2] - 1.).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn radau5_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // harmonic oscillator `y'' = -y`, output by the collocation polynomial
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let solution = problem
+            .radau5(ImplicitOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(101, solution.tout.len());
+        for (t, y) in solution.zipped() {
+            assert!((y.0 - t.cos()).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn newton_options() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-6).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        let builder = OdeProblem::builder()
+            .tspan(vec![0., 2.])
+            .fun(|t, y: &f64| -1000. * (y - t.cos()) - t.sin())
+            .init(1.);
+        let reused = builder.clone().build().unwrap();
+        let fresh = builder
+            .newton(NewtonOptions {
+                reuse: JacobianReuse::Never,
+                kappa: 0.1,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let check = |a: OdeSolution<f64, f64>, b: OdeSolution<f64, f64>, tol: f64| {
+            assert!(a.stats.num_jac < b.stats.num_jac);
+            for y in [a.yout[a.yout.len() - 1], b.yout[b.yout.len() - 1]] {
+                assert!((y - 2f64.cos()).abs() < tol);
+            }
+        };
+        check(
+            reused
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-5,
+        );
+        check(
+            reused
+                .sdirk4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .sdirk4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-5,
+        );
+        check(
+            reused
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            1e-3,
+        );
+    }
 
-#[derive(Debug)]
-pub struct StepHW92 {
-    err: f64,
-    dt: f64,
-    timeout_ctn: usize,
-}
+    #[test]
+    fn jacobian_reuse() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-6).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // van der Pol oscillator with `mu = 100`
+        let builder = OdeProblem::builder()
+            .tspan(vec![0., 2.])
+            .fun(|_t, y: &(f64, f64)| (y.1, 100. * ((1. - y.0 * y.0) * y.1 - y.0)))
+            .init((2., 0.));
+        let reused = builder.clone().build().unwrap();
+        let fresh = builder
+            .newton(NewtonOptions {
+                max_rate: 0.,
+                max_step_change: 0.,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let check = |a: OdeSolution<f64, (f64, f64)>, b: OdeSolution<f64, (f64, f64)>| {
+            assert!(a.stats.num_decomp < b.stats.num_decomp);
+            assert!(a.stats.num_jac <= b.stats.num_jac);
+            let (ya, yb) = (a.yout[a.yout.len() - 1], b.yout[b.yout.len() - 1]);
+            assert!((ya.0 - yb.0).abs() < 1e-3);
+        };
+        check(
+            reused
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        );
+        check(
+            reused
+                .sdirk4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .sdirk4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        );
+        check(
+            reused
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            fresh
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        );
+    }
 
-/// Contains some diagnostics of the integration.
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Diagnostics {
-    pub num_eval: u32,
-    pub accepted_steps: u32,
-    pub rejected_steps: u32,
-}
+    #[test]
+    fn rodas_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // stiff, non autonomous `y' = -1000 (y - cos(t)) - sin(t)` with solution `cos(t)`,
+        // output by the continuous extension
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 41)
+            .fun(|t, y: &f64| -1000. * (y - t.cos()) - t.sin())
+            .tgrad(|t, _y: &f64| -1000. * t.sin() - t.cos())
+            .init(1.)
+            .build()
+            .unwrap();
+        for solution in [
+            problem
+                .rodas4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            problem
+                .rodas5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        ] {
+            assert_eq!(41, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y - t.cos()).abs() < 1e-6);
+            }
+        }
+    }
 
-impl fmt::Display for Diagnostics {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Number of function evaluations: {}", self.num_eval)?;
-        writeln!(f, "Number of accepted steps: {}", self.accepted_steps)?;
-        write!(f, "Number of rejected steps: {}", self.rejected_steps)
+    #[test]
+    fn bdf_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // harmonic oscillator `y'' = -y`, output by the interpolating polynomial
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let solution = problem
+            .bdf(MultistepOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(101, solution.tout.len());
+        for (t, y) in solution.zipped() {
+            assert!((y.0 - t.cos()).abs() < 1e-5);
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ode::options::OdeOp;
-    use std::fs::OpenOptions;
-    use std::io::Write;
+    #[test]
+    fn mass_matrix_test() {
+        // semi-explicit DAE `y_1' = y_2, 0 = y_1 + y_2` with solution `y_1 = e^{-t}`
+        let singular = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &(f64, f64)| (y.1, y.0 + y.1))
+            .mass(|_t, _y: &(f64, f64)| DMatrix::from_diagonal(&DVector::from_row_slice(&[1., 0.])))
+            .init((1., -1.))
+            .build()
+            .unwrap();
+        // state dependent `(1 + y_1^2) y_1' = -(1 + y_1^2) y_1, y_2' = -y_2`
+        let dependent = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|_t, y: &(f64, f64)| (-(1. + y.0 * y.0) * y.0, -y.1))
+            .mass(|_t, y: &(f64, f64)| {
+                DMatrix::from_diagonal(&DVector::from_row_slice(&[1. + y.0 * y.0, 1.]))
+            })
+            .init((1., -1.))
+            .build()
+            .unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // rodas only keeps its order for constant mass matrices
+        for solution in [
+            singular
+                .rodas4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            singular
+                .rodas5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            singular
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            singular
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            dependent
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            dependent
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        ] {
+            assert_eq!(21, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y.0 - (-t).exp()).abs() < 1e-6);
+                assert!((y.1 + (-t).exp()).abs() < 1e-6);
+            }
+        }
 
-    const DT: f64 = 0.001;
-    const TF: f64 = 100.0;
+        // the other solvers would ignore the mass matrix
+        for method in [
+            Ode::Feuler,
+            Ode::Ssprk104,
+            Ode::Lsrk54,
+            Ode::Gauss4,
+            Ode::Tsit5,
+            Ode::Dop853,
+            Ode::Rosenbrock23,
+            Ode::Ode4skr,
+            Ode::Sdirk4,
+            Ode::TrapezoidAdapt,
+            Ode::AutoTsit5,
+            Ode::Adams,
+            Ode::Abm,
+            Ode::Gbs,
+        ] {
+            let err = singular
+                .clone()
+                .solve(method.clone(), OdeOptionMap::default())
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                OdeError::UnsupportedSolver {
+                    feature: "mass matrices",
+                    ..
+                }
+            ));
+        }
+        assert!(matches!(
+            singular.integrator(ButcherTableau::rk21(), None, ExplicitRkOptions::default()),
+            Err(OdeError::UnsupportedMethod { .. })
+        ));
+    }
 
-    // Initial position in space
-    const Y0: [f64; 3] = [0.1, 0.0, 0.0];
+    #[test]
+    fn abm_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-8).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-8).into());
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        for order in 1..=adams::MAX_ORDER {
+            let solution = problem
+                .odeabm(order, BasicOptions::from_map(&ops).unwrap())
+                .unwrap();
+            assert_eq!(101, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y.0 - t.cos()).abs() < 1e-5);
+            }
+        }
+    }
 
-    // Constants SIGMA, RHO and beta
-    const SIGMA: f64 = 10.0;
-    const RHO: f64 = 28.0;
-    const BET: f64 = 8.0 / 3.0;
+    #[test]
+    fn adams_test() {
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        for max_order in &[2, 5, nordsieck::MAX_ORDER] {
+            let mut ops = OdeOptionMap::default();
+            ops.insert(Points::option_name(), Points::Specified.into());
+            ops.insert(Reltol::option_name(), Reltol::from(1e-10).into());
+            ops.insert(Abstol::option_name(), Abstol::from(1e-10).into());
+            ops.insert(MaxOrder::option_name(), MaxOrder(*max_order).into());
+            let solution = problem
+                .adams(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap();
+            assert_eq!(101, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y.0 - t.cos()).abs() < 1e-6);
+            }
+        }
+    }
 
-    fn lorenz_attractor(_t: f64, v: &Vec<f64>) -> Vec<f64> {
-        // extract coordinates from the vec
-        let (x, y, z) = (v[0], v[1], v[2]);
+    #[test]
+    fn gbs_test() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        ops.insert(Reltol::option_name(), Reltol::from(1e-12).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-12).into());
+        // arenstorf orbit, periodic with the period `tend`
+        let mu = 0.012277471;
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 17.065_216_560_157_96, 2)
+            .fun(|_t, y: &Vec<f64>| {
+                let d1 = ((y[0] + mu).powi(2) + y[1].powi(2)).powf(1.5);
+                let d2 = ((y[0] - 1. + mu).powi(2) + y[1].powi(2)).powf(1.5);
+                vec![
+                    y[2],
+                    y[3],
+                    y[0] + 2. * y[3] - (1. - mu) * (y[0] + mu) / d1 - mu * (y[0] - 1. + mu) / d2,
+                    y[1] - 2. * y[2] - (1. - mu) * y[1] / d1 - mu * y[1] / d2,
+                ]
+            })
+            .init(vec![0.994, 0., 0., -2.001_585_106_379_082_5])
+            .build()
+            .unwrap();
+        let solution = problem.gbs(BasicOptions::from_map(&ops).unwrap()).unwrap();
+        let y = solution.yout.last().unwrap();
+        assert!((y[0] - 0.994).abs() < 1e-8);
+        assert!(y[1].abs() < 1e-8);
+    }
 
-        // Lorenz equations
-        let dx_dt = SIGMA * (y - x);
-        let dy_dt = x * (RHO - z) - y;
-        let dz_dt = x * y - BET * z;
+    #[test]
+    fn rosenbrock23_test() {
+        // robertson chemical kinetics
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 40., 2)
+            .fun(|_t, y: &Vec<f64>| {
+                vec![
+                    -0.04 * y[0] + 1e4 * y[1] * y[2],
+                    0.04 * y[0] - 1e4 * y[1] * y[2] - 3e7 * y[1] * y[1],
+                    3e7 * y[1] * y[1],
+                ]
+            })
+            .init(vec![1., 0., 0.])
+            .build()
+            .unwrap();
+        let solution = problem.rosenbrock23(Default::default()).unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert_eq!(40., t);
+        // reference value of y1(40)
+        assert!((y[0] - 0.7158270687).abs() < 1e-3);
+    }
 
-        // derivatives as vec
-        vec![dx_dt, dy_dt, dz_dt]
+    #[test]
+    fn gauss_test() {
+        // harmonic oscillator `y'' = -y`, the energy is conserved by symplectic methods
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 100., 1001)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let gauss4 = problem.gauss4().unwrap();
+        let gauss6 = problem.gauss6().unwrap();
+        for (t, y) in gauss4.zipped() {
+            assert!((y.0 * y.0 + y.1 * y.1 - 1.).abs() < 1e-8);
+            assert!((y.0 - t.cos()).abs() < 1e-3);
+        }
+        for (t, y) in gauss6.zipped() {
+            assert!((y.0 - t.cos()).abs() < 1e-6);
+        }
     }
 
-    fn lorenz_problem() -> OdeProblem<impl Fn(f64, &Vec<f64>) -> Vec<f64>, Vec<f64>> {
-        OdeProblem::builder()
-            .tspan_linspace(0., TF, 100_001)
-            .fun(lorenz_attractor)
-            .init(vec![0.1, 0., 0.])
+    #[test]
+    fn gauss_stiff_test() {
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|t, y: &f64| -1000. * (*y - t.cos()))
+            .init(0.)
             .build()
-            .unwrap()
+            .unwrap();
+        let solution = problem.gauss6().unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert!((y - t.cos()).abs() < 1e-2);
     }
 
     #[test]
-    fn diff_test() {
-        let a = vec![2., 6., 4., 16.];
-        assert_eq!(vec![4.0, -2.0, 12.0], diff(&a));
+    fn lobatto_test() {
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        for ode in [Ode::LobattoIIIA, Ode::LobattoIIIB, Ode::LobattoIIIC] {
+            let solution = problem.clone().solve(ode, Default::default()).unwrap();
+            for (t, y) in solution.zipped() {
+                assert!((y.0 - t.cos()).abs() < 1e-4);
+            }
+        }
 
-        let v: Vec<f64> = Vec::new();
-        assert_eq!(v, diff(&v));
+        // the initial transient is damped by the L-stable IIIC method only
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 2., 21)
+            .fun(|t, y: &f64| -1000. * (*y - t.cos()))
+            .init(0.)
+            .build()
+            .unwrap();
+        let solution = problem.lobatto_iiic().unwrap();
+        let (t, y) = solution.zipped().pop().unwrap();
+        assert!((y - t.cos()).abs() < 1e-2);
     }
 
     #[test]
-    fn ode45_test() {
+    fn bs3_test() {
         let mut ops = OdeOptionMap::default();
-        ops.insert(Points::option_name(), Points::Specified.into());
-        let _solution = lorenz_problem().ode45(Default::default()).unwrap();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-3).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-3).into());
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let solution = problem.solve(Ode::Bs3, ops).unwrap();
+        assert_eq!(Some(&10.), solution.tout.last());
+        let y = solution.yout.last().unwrap();
+        assert!((y.0 - 10f64.cos()).abs() < 2e-2);
+
+        // three evaluations of the RHS per step, as the last stage is reused
+        let Diagnostics {
+            num_eval,
+            accepted_steps,
+            rejected_steps,
+            ..
+        } = solution.stats;
+        assert!(f64::from(num_eval) < 3.1 * f64::from(accepted_steps + rejected_steps));
+    }
+
+    #[test]
+    fn vern6_test() {
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .tspan_linspace(0., 10., 101)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        for (ode, tol, err) in [(Ode::Vern6, 1e-9, 1e-7), (Ode::Vern9, 1e-13, 1e-11)] {
+            let mut ops = OdeOptionMap::default();
+            ops.insert(Points::option_name(), Points::Specified.into());
+            ops.insert(Reltol::option_name(), Reltol::from(tol).into());
+            ops.insert(Abstol::option_name(), Abstol::from(tol).into());
+            let solution = problem.clone().solve(ode, ops).unwrap();
+            assert_eq!(101, solution.tout.len());
+            for (t, y) in solution.zipped() {
+                assert!((y.0 - t.cos()).abs() < err);
+            }
+        }
     }
 
     #[test]
@@ -1026,4 +5141,790 @@ mod tests {
         let jac = problem.fdjacobian(0.0, &x);
         assert_eq!((3, 3), jac.shape());
     }
+
+    #[test]
+    fn max_iters() {
+        let problem = lorenz_problem();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(MaxIters::option_name(), MaxIters(50).into());
+        for solution in [
+            problem
+                .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            problem
+                .dop853(Dop853Options::from_map(&ops).unwrap())
+                .unwrap(),
+            problem
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap(),
+            problem.gbs(BasicOptions::from_map(&ops).unwrap()).unwrap(),
+        ] {
+            assert_eq!(ReturnCode::MaxIters, solution.retcode);
+            assert!(!solution.retcode.is_success());
+            assert!(*solution.tout.last().unwrap() < TF);
+        }
+    }
+
+    #[test]
+    fn trbdf2_test() {
+        // stiff relaxation onto the solution `cos t`
+        let problem = OdeProblem::builder()
+            .fun(|t, y: &f64| -1000. * (y - t.cos()) - t.sin())
+            .init(1.)
+            .interval(0., 2.)
+            .build()
+            .unwrap();
+        let mut steps = 0;
+        for &tol in &[1e-3, 1e-6] {
+            let mut ops = OdeOptionMap::default();
+            ops.insert(Reltol::option_name(), Reltol::from(tol).into());
+            ops.insert(Abstol::option_name(), Abstol::from(tol).into());
+            let solution = problem
+                .trbdf2(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap();
+            assert_eq!(ReturnCode::Success, solution.retcode);
+            let y = solution.yout.last().unwrap();
+            assert!((y - 2f64.cos()).abs() < tol);
+            // the error estimate refines the steps with the tolerance, which still
+            // exceed the stability limit `dt < 2e-3` of explicit methods
+            assert!(solution.stats.accepted_steps > steps);
+            assert!(solution.stats.accepted_steps < 100);
+            steps = solution.stats.accepted_steps;
+            assert!(solution.stats.num_decomp > 0);
+        }
+    }
+
+    #[test]
+    fn stiffness_detection() {
+        let problem = OdeProblem::builder()
+            .fun(|t, y: &f64| -1000. * (y - t.cos()) - t.sin())
+            .init(1.)
+            .interval(0., 10.)
+            .build()
+            .unwrap();
+        let flagged = problem.tsit5(Default::default()).unwrap();
+        let stiff_at = flagged.stiff_at.unwrap();
+        assert!(stiff_at < 1.);
+        assert_eq!(ReturnCode::Success, flagged.retcode);
+        assert!(
+            problem
+                .dop853(Default::default())
+                .unwrap()
+                .stiff_at
+                .unwrap()
+                < 1.
+        );
+
+        let mut ops = OdeOptionMap::default();
+        ops.insert(
+            Stiffness::option_name(),
+            Stiffness(StiffnessAction::Abort).into(),
+        );
+        let aborted = problem
+            .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert_eq!(ReturnCode::Stiff, aborted.retcode);
+        assert_eq!(stiff_at, *aborted.tout.last().unwrap());
+        let opts = Dop853Options {
+            stiffness: Stiffness(StiffnessAction::Abort),
+            ..Default::default()
+        };
+        assert_eq!(ReturnCode::Stiff, problem.dop853(opts).unwrap().retcode);
+
+        // the lorenz system is not stiff
+        let solution = lorenz_problem().tsit5(Default::default()).unwrap();
+        assert_eq!(None, solution.stiff_at);
+    }
+
+    #[test]
+    fn solver_statistics() {
+        let problem = lorenz_problem();
+        let mut ops = OdeOptionMap::default();
+        ops.set(Reltol::from(1e-8));
+        ops.set(Abstol::from(1e-8));
+        let opts = || Dop853Options::from_map(&ops).unwrap();
+
+        let explicit = problem.dop853(opts()).unwrap().stats;
+        let steps = explicit.accepted_steps + explicit.rejected_steps;
+        assert!(explicit.accepted_steps > 0);
+        // eleven new stages per step, the first is reused
+        assert!(explicit.num_eval >= 11 * steps);
+        assert_eq!(0, explicit.num_jac);
+        assert_eq!(0, explicit.num_linsolve);
+        // the statistics are those of a single solve, the problem keeps the total
+        assert_eq!(explicit, problem.dop853(opts()).unwrap().stats);
+        assert_eq!(explicit.num_eval * 2, problem.stats().num_eval);
+
+        for stats in [
+            problem
+                .bdf(MultistepOptions::from_map(&ops).unwrap())
+                .unwrap()
+                .stats,
+            problem
+                .rodas4(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap()
+                .stats,
+            problem
+                .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                .unwrap()
+                .stats,
+        ] {
+            assert!(stats.accepted_steps > 0);
+            assert!(stats.num_jac > 0);
+            assert!(stats.num_linsolve >= stats.accepted_steps);
+            // the finite difference jacobians evaluate the RHS once per component
+            assert!(stats.num_eval > 3 * stats.num_jac);
+        }
+    }
+
+    #[test]
+    fn error_norm() {
+        // the second component oscillates fast, but is of no interest
+        let builder = || {
+            OdeProblem::builder()
+                .fun(|t, y: &(f64, f64)| (-y.0, 100. * (100. * t).cos()))
+                .init((1., 0.))
+                .interval(0., 2.)
+        };
+        let opts = || ExplicitRkOptions {
+            reltol: Reltol::from(1e-8),
+            abstol: Abstol::from(1e-8),
+            ..Default::default()
+        };
+        let full = builder().build().unwrap();
+        let first = builder()
+            .error_norm(|err: &(f64, f64), y, tols| err.0.abs() / tols.scale(0, y.0))
+            .build()
+            .unwrap();
+
+        let steps = |problem: &OdeProblem<_, (f64, f64)>| {
+            let mut integrator = problem.tsit5_integrator(opts()).unwrap();
+            while integrator.step().unwrap() {}
+            assert!((integrator.y().0 - (-2f64).exp()).abs() < 1e-8);
+            integrator.stats().accepted_steps
+        };
+        assert!(steps(&first) * 5 < steps(&full));
+
+        // the solvers that don't step with an integrator, on a slower oscillation
+        let builder = || {
+            OdeProblem::builder()
+                .fun(|t, y: &(f64, f64)| (-y.0, 10. * (10. * t).cos()))
+                .init((1., 0.))
+                .interval(0., 2.)
+        };
+        let full = builder().build().unwrap();
+        let first = builder()
+            .error_norm(|err: &(f64, f64), y, tols| err.0.abs() / tols.scale(0, y.0))
+            .build()
+            .unwrap();
+        for ode in [Ode::Rosenbrock23, Ode::Bdf, Ode::Abm, Ode::Adams, Ode::Gbs] {
+            let name = format!("{:?}", ode);
+            let full = full.clone().solve(ode.clone(), Default::default()).unwrap();
+            let first = first.clone().solve(ode, Default::default()).unwrap();
+            for solution in [&full, &first] {
+                let y = solution.yout.last().unwrap();
+                assert!((y.0 - (-2f64).exp()).abs() < 1e-4, "{}", name);
+            }
+            assert!(first.stats.num_eval < full.stats.num_eval, "{}", name);
+        }
+    }
+
+    #[test]
+    fn initial_step() {
+        let decay = |k: f64| move |_t: f64, y: &f64| -k * y;
+        let tol = Tolerance::Scalar(1e-6);
+        let slow = hinit(decay(1.), &1., 0., 10., 5, &tol, &tol).unwrap();
+        let fast = hinit(decay(1e4), &1., 0., 10., 5, &tol, &tol).unwrap();
+        assert_eq!(-1., slow.f0);
+        // the step shrinks with the scale of the problem
+        assert!(slow.h < 0.1);
+        assert!(fast.h < slow.h / 100.);
+
+        let backward = hinit(decay(1.), &1., 10., 0., 5, &tol, &tol).unwrap();
+        assert_eq!(-1., backward.tdir);
+        assert!(backward.h < 0.);
+    }
+
+    #[test]
+    fn parameterized_problem() {
+        let problem = OdeProblem::builder()
+            .params(|_t, y: &f64, k: &f64| -k * y, 2.)
+            .init(1.)
+            .interval(0., 1.)
+            .build()
+            .unwrap();
+        assert_eq!(&[0., 1.], problem.tspan());
+        assert_eq!(1., *problem.y0());
+        let solution = problem.dop853(Default::default()).unwrap();
+        assert!((solution.yout.last().unwrap() - (-2f64).exp()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn dense_output() {
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-10).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-10).into());
+        ops.insert(Dense::option_name(), Dense(true).into());
+        // harmonic oscillator `y'' = -y`
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let ts: Vec<f64> = itertools_num::linspace(0., 10., 37).collect();
+        for (solution, tol) in [
+            (
+                problem
+                    .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-8,
+            ),
+            (
+                problem
+                    .vern6(ExplicitRkOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-8,
+            ),
+            (
+                problem
+                    .vern9(ExplicitRkOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-8,
+            ),
+            // hermite interpolation
+            (
+                problem
+                    .ode78(ExplicitRkOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-4,
+            ),
+            (
+                problem
+                    .dop853(Dop853Options::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-8,
+            ),
+            (
+                problem
+                    .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                1e-7,
+            ),
+            // the continuous extension of a user defined tableau
+            (
+                problem
+                    .oderk_adapt(
+                        &ButcherTableau {
+                            dense: None,
+                            ..ButcherTableau::dopri5()
+                        }
+                        .with_dense(DenseCoeffs::dopri5()),
+                        AdaptiveOptions::from_map(&ops).unwrap(),
+                    )
+                    .unwrap(),
+                1e-8,
+            ),
+        ] {
+            for (t, y) in ts.iter().zip(solution.at_many(&ts).unwrap()) {
+                assert!((y.0 - t.cos()).abs() < tol);
+            }
+            assert!(solution.at(10.5).is_none());
+        }
+        assert!(problem.tsit5(Default::default()).unwrap().at(1.).is_none());
+    }
+
+    #[test]
+    fn interp_order() {
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let ts: Vec<f64> = itertools_num::linspace(0., 10., 37).collect();
+        // hermite and linear interpolation instead of the continuous extensions
+        for (order, tol) in [(3, 1e-4), (1, 1e-1)] {
+            let mut ops = OdeOptionMap::default();
+            ops.insert(Reltol::option_name(), Reltol::from(1e-10).into());
+            ops.insert(Abstol::option_name(), Abstol::from(1e-10).into());
+            ops.insert(Dense::option_name(), Dense(true).into());
+            ops.insert(InterpOrder::option_name(), InterpOrder(order).into());
+            for solution in [
+                problem
+                    .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+                problem
+                    .dop853(Dop853Options::from_map(&ops).unwrap())
+                    .unwrap(),
+                problem
+                    .radau5(ImplicitOptions::from_map(&ops).unwrap())
+                    .unwrap(),
+            ] {
+                for (t, y) in ts.iter().zip(solution.at_many(&ts).unwrap()) {
+                    assert!((y.0 - t.cos()).abs() < tol);
+                }
+                for (t, y) in solution.tout.iter().zip(&solution.yout) {
+                    assert!((solution.at(*t).unwrap().0 - y.0).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn saveat() {
+        let problem = OdeProblem::builder()
+            .interval(0., 10.)
+            .fun(|_t, y: &(f64, f64)| (y.1, -y.0))
+            .init((1., 0.))
+            .build()
+            .unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Reltol::option_name(), Reltol::from(1e-10).into());
+        ops.insert(Abstol::option_name(), Abstol::from(1e-10).into());
+        ops.insert(
+            Saveat::option_name(),
+            Saveat(vec![10., 2.5, 0.1, 5., 20., 2.5]).into(),
+        );
+        let check = |solution: OdeSolution<f64, (f64, f64)>| {
+            assert_eq!(vec![0.1, 2.5, 5., 10.], solution.tout);
+            for (t, y) in solution.iter() {
+                assert!((y.0 - t.cos()).abs() < 1e-8);
+            }
+        };
+        check(
+            problem
+                .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+                .unwrap(),
+        );
+        check(
+            problem
+                .dop853(Dop853Options::from_map(&ops).unwrap())
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn saving_projections() {
+        // decoupled decays `y_i' = -y_i / (i + 1)`
+        let n = 1000;
+        let problem = OdeProblem::builder()
+            .interval(0., 1.)
+            .fun(move |_t, y: &DVector<f64>| DVector::from_fn(n, |i, _| -y[i] / (i + 1) as f64))
+            .init(DVector::from_element(n, 1.))
+            .build()
+            .unwrap();
+        let solution = problem
+            .solve_saving(
+                Ode::Tsit5,
+                Default::default(),
+                problem.save_idxs(vec![0, n - 1]).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(1., *solution.tout.last().unwrap());
+        for (t, y) in solution.iter() {
+            assert_eq!(2, y.len());
+            assert!((y[0] - (-t).exp()).abs() < 1e-6);
+            assert!((y[1] - (-t / n as f64).exp()).abs() < 1e-6);
+        }
+        assert!(problem
+            .solve_saving(
+                Ode::Radau5,
+                Default::default(),
+                problem.save_idxs(vec![0]).unwrap()
+            )
+            .is_err());
+        assert!(matches!(
+            problem.save_idxs(vec![0, n]),
+            Err(OdeError::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn reverse_time() {
+        // `y' = cos t - y` from `y(1) = 1` backwards, through `t = 0`
+        let c = (1. - (1f64.cos() + 1f64.sin()) / 2.) * 1f64.exp();
+        let exact = |t: f64| (t.cos() + t.sin()) / 2. + c * (-t).exp();
+        let problem = OdeProblem::builder()
+            .tspan_linspace(1., -1., 9)
+            .fun(|t, y: &f64| t.cos() - y)
+            .init(1.)
+            .build()
+            .unwrap();
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Points::option_name(), Points::Specified.into());
+        for ode in [
+            Ode::TrapezoidAdapt,
+            Ode::Sdirk2,
+            Ode::Sdirk4,
+            Ode::Kvaerno4,
+            Ode::TrBdf2,
+            Ode::Radau5,
+            Ode::Ode23s,
+            Ode::Rodas4,
+            Ode::Rodas5,
+            Ode::AutoTsit5,
+            Ode::Bdf,
+            Ode::Adams,
+            Ode::Gbs,
+            Ode::Dop853,
+            Ode::Tsit5,
+        ] {
+            let solution = problem.clone().solve(ode.clone(), ops.clone()).unwrap();
+            assert!(solution.is_success(), "{:?}", ode);
+            assert_eq!(problem.tspan, solution.tout, "{:?}", ode);
+            for (t, y) in solution.iter() {
+                assert!((y - exact(*t)).abs() < 5e-3, "{:?} at {}", ode, t);
+            }
+        }
+
+        // the fixed step Rosenbrock methods, on an autonomous problem
+        let decay = OdeProblem::builder()
+            .tspan_linspace(1., -1., 9)
+            .fun(|_t, y: &f64| -y)
+            .init(1.)
+            .build()
+            .unwrap();
+        for solution in [decay.ode4s_kr().unwrap(), decay.ode4s_s().unwrap()] {
+            assert_eq!(decay.tspan, solution.tout);
+            for (t, y) in solution.iter() {
+                assert!((y / (1. - t).exp() - 1.).abs() < 1e-3);
+            }
+        }
+
+        // the continuous output covers the decreasing interval as well
+        let mut ops = OdeOptionMap::default();
+        ops.insert(Dense::option_name(), Dense(true).into());
+        let solution = problem
+            .tsit5(ExplicitRkOptions::from_map(&ops).unwrap())
+            .unwrap();
+        assert!((solution.at(-0.3).unwrap() - exact(-0.3)).abs() < 1e-5);
+        assert!(solution.at(1.5).is_none());
+    }
+
+    #[test]
+    fn analytic_jacobian() {
+        // `y' = -1000 (y - cos t) - sin t` with the jacobian `-1000`
+        let builder = || {
+            OdeProblem::builder()
+                .interval(0., 1.)
+                .fun(|t, y: &Vec<f64>| vec![-1000. * (y[0] - t.cos()) - t.sin()])
+                .init(vec![1.])
+        };
+        let fd = builder().build().unwrap();
+        let analytic = builder()
+            .jac(|_t, _y| DMatrix::from_element(1, 1, -1000.))
+            .check_jacobian(1e-6)
+            .build()
+            .unwrap();
+        for ode in [Ode::Rodas5, Ode::Radau5, Ode::Bdf, Ode::Ode23s] {
+            let expected = fd.clone().solve(ode.clone(), Default::default()).unwrap();
+            let solution = analytic
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            let (y, yfd) = (solution.yout.last().unwrap(), expected.yout.last().unwrap());
+            assert!((y[0] - 1f64.cos()).abs() < 1e-4, "{:?}", ode);
+            assert!((y[0] - yfd[0]).abs() < 1e-6, "{:?}", ode);
+            assert!(solution.stats.num_jac > 0);
+        }
+
+        // the check catches a wrong sign
+        let wrong = builder()
+            .jac(|_t, _y| DMatrix::from_element(1, 1, 1000.))
+            .check_jacobian(1e-6)
+            .build()
+            .unwrap();
+        match wrong.rodas5(Default::default()) {
+            Err(OdeError::JacobianMismatch { entry, .. }) => assert_eq!((0, 0), entry),
+            other => panic!("expected a mismatch, found {:?}", other.map(|s| s.retcode)),
+        }
+    }
+
+    #[test]
+    fn autodiff() {
+        // `y' = -1000 (y - cos t) - sin t`, generic over the scalar
+        fn rhs<T>(t: T, y: &[T]) -> Vec<T>
+        where
+            T: RealField + Add<f64, Output = T> + Mul<f64, Output = T>,
+        {
+            vec![(y[0] - t.cos()) * -1000. - t.sin()]
+        }
+        let problem = OdeProblem::builder()
+            .interval(0., 1.)
+            .fun(|t, y: &Vec<f64>| rhs(t, y))
+            .autodiff(|t, y: &Vec<Dual>| rhs(t, y))
+            .check_jacobian(1e-6)
+            .init(vec![1.])
+            .build()
+            .unwrap();
+        let (t, y) = (0.5, vec![0.3]);
+        assert_eq!(-1000., problem.jacobian(t, &y).unwrap()[(0, 0)]);
+        let dfdt = -1000. * t.sin() - t.cos();
+        assert!((problem.tgrad(t, &y)[0] - dfdt).abs() < 1e-12);
+
+        let solution = problem.rodas5(Default::default()).unwrap();
+        assert!((solution.yout.last().unwrap()[0] - 1f64.cos()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sparse_jacobian() {
+        // the heat equation `u_t = u_xx` on `(0, 1)` with `u = 0` at the boundary,
+        // discretized by second order central differences
+        let n = 40;
+        let dx2 = 1. / ((n + 1) as f64).powi(2);
+        let builder = || {
+            OdeProblem::builder()
+                .interval(0., 0.05)
+                .fun(move |_t, u: &Vec<f64>| {
+                    (0..n)
+                        .map(|i| {
+                            let left = if i > 0 { u[i - 1] } else { 0. };
+                            let right = if i + 1 < n { u[i + 1] } else { 0. };
+                            (left - 2. * u[i] + right) / dx2
+                        })
+                        .collect()
+                })
+                .init(
+                    (1..=n)
+                        .map(|i| (std::f64::consts::PI * i as f64 / (n + 1) as f64).sin())
+                        .collect::<Vec<_>>(),
+                )
+        };
+        let dense = builder().build().unwrap();
+        let sparse = builder()
+            .sparsity(SparsityPattern::banded(n, 1, 1))
+            .build()
+            .unwrap();
+
+        // one evaluation of the RHS at the point and one for each of the three groups
+        let evals = sparse.stats().num_eval;
+        let jac = sparse.iteration_jacobian(0., &sparse.y0).unwrap();
+        assert!(matches!(jac, JacobianMatrix::Sparse(_)));
+        assert_eq!(4, sparse.stats().num_eval - evals);
+        let diff = jac.to_dense() - dense.fdjacobian(0., &dense.y0);
+        assert!(diff.amax() < 1e-4 / dx2);
+
+        for ode in [Ode::Radau5, Ode::TrBdf2, Ode::Sdirk4, Ode::TrapezoidAdapt] {
+            let expected = dense
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            let solution = sparse
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            let (u, uexp) = (solution.yout.last().unwrap(), expected.yout.last().unwrap());
+            let err = u
+                .iter()
+                .zip(uexp)
+                .fold(0f64, |acc, (a, b)| acc.max((a - b).abs()));
+            assert!(err < 1e-5, "{:?} {}", ode, err);
+            assert!(
+                solution.stats.num_eval < expected.stats.num_eval,
+                "{:?}",
+                ode
+            );
+        }
+    }
+
+    #[test]
+    fn linear_solvers() {
+        // the discretized heat equation of `sparse_jacobian`
+        let n = 20;
+        let dx2 = 1. / ((n + 1) as f64).powi(2);
+        let builder = OdeProblem::builder()
+            .interval(0., 0.05)
+            .fun(move |_t, u: &Vec<f64>| {
+                (0..n)
+                    .map(|i| {
+                        let left = if i > 0 { u[i - 1] } else { 0. };
+                        let right = if i + 1 < n { u[i + 1] } else { 0. };
+                        (left - 2. * u[i] + right) / dx2
+                    })
+                    .collect()
+            })
+            .init(
+                (1..=n)
+                    .map(|i| (std::f64::consts::PI * i as f64 / (n + 1) as f64).sin())
+                    .collect::<Vec<_>>(),
+            );
+        let reference = builder.clone().build().unwrap();
+        for solver in [
+            LinearSolverType::Qr,
+            LinearSolverType::Banded,
+            LinearSolverType::SparseLu,
+        ] {
+            let problem = builder
+                .clone()
+                .sparsity(SparsityPattern::banded(n, 1, 1))
+                .linear_solver(solver)
+                .build()
+                .unwrap();
+            for ode in [Ode::Radau5, Ode::Sdirk4, Ode::Bdf, Ode::Rodas4] {
+                let expected = reference.clone().solve(ode.clone(), Default::default());
+                let solution = problem.clone().solve(ode.clone(), Default::default());
+                let (u, uexp) = (solution.unwrap().yout, expected.unwrap().yout);
+                let err = u
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .zip(uexp.last().unwrap())
+                    .fold(0f64, |acc, (a, b)| acc.max((a - b).abs()));
+                assert!(err < 1e-5, "{} {:?} {}", solver, ode, err);
+            }
+        }
+    }
+
+    #[test]
+    fn gmres() {
+        // the discretized heat equation of `sparse_jacobian`
+        let n = 20;
+        let dx2 = 1. / ((n + 1) as f64).powi(2);
+        let heat = move |_t: f64, u: &Vec<f64>| -> Vec<f64> {
+            (0..n)
+                .map(|i| {
+                    let left = if i > 0 { u[i - 1] } else { 0. };
+                    let right = if i + 1 < n { u[i + 1] } else { 0. };
+                    (left - 2. * u[i] + right) / dx2
+                })
+                .collect()
+        };
+        let builder = OdeProblem::builder().interval(0., 0.05).fun(heat).init(
+            (1..=n)
+                .map(|i| (std::f64::consts::PI * i as f64 / (n + 1) as f64).sin())
+                .collect::<Vec<_>>(),
+        );
+        let reference = builder.clone().build().unwrap();
+        let gmres = LinearSolverType::Gmres(GmresOptions {
+            tol: 1e-8,
+            ..Default::default()
+        });
+        let problems = [
+            // finite difference products
+            builder.clone().linear_solver(gmres).build().unwrap(),
+            // the exact products of the linear RHS
+            builder
+                .clone()
+                .linear_solver(gmres)
+                .jvp(move |t, _u, v| heat(t, v))
+                .build()
+                .unwrap(),
+        ];
+        for problem in problems.iter() {
+            for ode in [Ode::Radau5, Ode::Sdirk4, Ode::Bdf, Ode::TrapezoidAdapt] {
+                let expected = reference.clone().solve(ode.clone(), Default::default());
+                let solution = problem
+                    .clone()
+                    .solve(ode.clone(), Default::default())
+                    .unwrap();
+                // the jacobian is never formed
+                assert_eq!(0, solution.stats.num_jac, "{:?}", ode);
+                let (u, uexp) = (solution.yout, expected.unwrap().yout);
+                let err = u
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .zip(uexp.last().unwrap())
+                    .fold(0f64, |acc, (a, b)| acc.max((a - b).abs()));
+                assert!(err < 1e-5, "{:?} {}", ode, err);
+            }
+        }
+    }
+
+    #[test]
+    fn preconditioners() {
+        // the discretized heat equation of `sparse_jacobian`, on a finer grid
+        let n = 50;
+        let dx2 = 1. / ((n + 1) as f64).powi(2);
+        let heat = move |_t: f64, u: &Vec<f64>| -> Vec<f64> {
+            (0..n)
+                .map(|i| {
+                    let left = if i > 0 { u[i - 1] } else { 0. };
+                    let right = if i + 1 < n { u[i + 1] } else { 0. };
+                    (left - 2. * u[i] + right) / dx2
+                })
+                .collect()
+        };
+        let builder = OdeProblem::builder()
+            .interval(0., 0.05)
+            .fun(heat)
+            .init(
+                (1..=n)
+                    .map(|i| (std::f64::consts::PI * i as f64 / (n + 1) as f64).sin())
+                    .collect::<Vec<_>>(),
+            )
+            .linear_solver(LinearSolverType::Gmres(GmresOptions::default()));
+        let reference = builder
+            .clone()
+            .linear_solver(LinearSolverType::default())
+            .build()
+            .unwrap();
+        let unpreconditioned = builder.clone().build().unwrap();
+        let laplacian = DMatrix::from_fn(n, n, |i, j| match i as isize - j as isize {
+            0 => -2. / dx2,
+            1 | -1 => 1. / dx2,
+            _ => 0.,
+        });
+        let exact = Preconditioner::new(
+            move |_t, _u: &Vec<f64>, gamma| (DMatrix::identity(n, n) - &laplacian * gamma).lu(),
+            |lu, r: &Vec<f64>| {
+                let z = lu.solve(&DVector::from_column_slice(r)).unwrap();
+                z.iter().copied().collect()
+            },
+        );
+        let problems = [
+            builder
+                .clone()
+                .preconditioner(Preconditioner::banded(1, 1))
+                .build()
+                .unwrap(),
+            builder
+                .clone()
+                .sparsity(SparsityPattern::banded(n, 1, 1))
+                .preconditioner(Preconditioner::ilu0().with_side(PreconditionerSide::Left))
+                .build()
+                .unwrap(),
+            builder.clone().preconditioner(exact).build().unwrap(),
+        ];
+        for ode in [Ode::Radau5, Ode::Sdirk4, Ode::Bdf] {
+            let expected = reference
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            let plain = unpreconditioned
+                .clone()
+                .solve(ode.clone(), Default::default())
+                .unwrap();
+            for problem in problems.iter() {
+                let solution = problem
+                    .clone()
+                    .solve(ode.clone(), Default::default())
+                    .unwrap();
+                let err = solution
+                    .yout
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .zip(expected.yout.last().unwrap())
+                    .fold(0f64, |acc, (a, b)| acc.max((a - b).abs()));
+                assert!(err < 1e-5, "{:?} {}", ode, err);
+                // the finite difference products of the krylov iterations dominate
+                assert!(
+                    solution.stats.num_eval < plain.stats.num_eval,
+                    "{:?} {} {}",
+                    ode,
+                    solution.stats.num_eval,
+                    plain.stats.num_eval
+                );
+            }
+        }
+
+        let missing = builder
+            .clone()
+            .preconditioner(Preconditioner::ilu0())
+            .build()
+            .unwrap();
+        assert!(missing.solve(Ode::Bdf, Default::default()).is_err());
+    }
 }
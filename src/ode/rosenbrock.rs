@@ -24,11 +24,11 @@ impl RosenbrockCoeffs<U4> {
             0.,
             0.,
             0.,
-            4.452_470_820_736,
+            4.524_708_207_36,
             4.163_528_788_60,
             0.,
             0.,
-            4.452_470_820_736,
+            4.524_708_207_36,
             4.163_528_788_60,
             0.,
             0.,
@@ -80,11 +80,11 @@ impl RosenbrockCoeffs<U4> {
             0.,
             0.,
             48. / 25.,
-            6. / 250.,
+            6. / 25.,
             0.,
             0.,
             48. / 25.,
-            6. / 250.,
+            6. / 25.,
             0.,
             0.,
         );
@@ -117,3 +117,369 @@ impl RosenbrockCoeffs<U4> {
         }
     }
 }
+
+/// Coefficients of a stiffly accurate Rosenbrock method of the Rodas family in
+/// Hairer & Wanner's form
+///
+/// ```latex
+/// (I / (h γ) - J) U_i = f(t + α_i h, y + Σ_j a_ij U_j) + Σ_j c_ij / h U_j + γ_i h ∂f/∂t
+/// ```
+///
+/// The argument of the last stage is the embedded solution, the new solution
+/// `y + Σ_j a_sj U_j + U_s` adds the last stage, which is also the error estimate.
+#[derive(Clone, Debug)]
+pub struct RodasCoeffs<S: Dim>
+where
+    DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
+{
+    pub gamma: f64,
+    pub a: MatrixN<f64, S>,
+    pub c: MatrixN<f64, S>,
+    /// nodes `α_i`
+    pub alpha: VectorN<f64, S>,
+    /// time derivative coefficients `γ_i`
+    pub gammas: VectorN<f64, S>,
+    /// coefficients of the stages for `k_1, k_2` of the continuous output
+    pub dense: MatrixMN<f64, S, U2>,
+}
+
+impl<S: Dim> RodasCoeffs<S>
+where
+    DefaultAllocator: Allocator<f64, S, S> + Allocator<f64, S> + Allocator<f64, S, U2>,
+{
+    /// the number of stages `S`
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.alpha.nrows()
+    }
+}
+
+impl RodasCoeffs<U6> {
+    /// Rodas4 of Hairer & Wanner, "Solving Ordinary Differential Equations II", 1996,
+    /// a stiffly accurate method of order 4 with an embedded third order error estimate.
+    pub fn rodas4() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U6,
+            U6,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                1.544,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.9466785280815826,
+                0.2557011698983284,
+                0.,
+                0.,
+                0.,
+                0.,
+                3.314825187068521,
+                2.896124015972201,
+                0.9986419139977817,
+                0.,
+                0.,
+                0.,
+                1.221224509226641,
+                6.019134481288629,
+                12.53708332932087,
+                -0.687886036105895,
+                0.,
+                0.,
+                1.221224509226641,
+                6.019134481288629,
+                12.53708332932087,
+                -0.687886036105895,
+                1.0,
+                0.,
+            ],
+        );
+        let c = MatrixMN::from_row_slice_generic(
+            U6,
+            U6,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -5.6688,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -2.430093356833875,
+                -0.2063599157091915,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.1073529058151375,
+                -9.594562251023355,
+                -20.47028614809616,
+                0.,
+                0.,
+                0.,
+                7.496443313967647,
+                -10.24680431464352,
+                -33.99990352819905,
+                11.7089089320616,
+                0.,
+                0.,
+                8.083246795921522,
+                -7.981132988064893,
+                -31.52159432874371,
+                16.31930543123136,
+                -6.058818238834054,
+                0.,
+            ],
+        );
+        let alpha = VectorN::from_row_slice_generic(U6, U1, &[0., 0.386, 0.21, 0.63, 1.0, 1.0]);
+        let gammas =
+            VectorN::from_row_slice_generic(U6, U1, &[0.25, -0.1043, 0.1035, -0.0362, 0., 0.]);
+        let dense = MatrixMN::from_row_slice_generic(
+            U6,
+            U2,
+            &[
+                10.12623508344586,
+                -0.6762803392801253,
+                -7.487995877610167,
+                6.087714651680015,
+                -34.80091861555747,
+                16.43084320892478,
+                -7.992771707568823,
+                24.76722511418386,
+                1.025137723295662,
+                -6.594389125716872,
+                0.,
+                0.,
+            ],
+        );
+
+        Self {
+            gamma: 0.25,
+            a,
+            c,
+            alpha,
+            gammas,
+            dense,
+        }
+    }
+}
+
+impl RodasCoeffs<U8> {
+    /// Rodas5 of Di Marzo, 1993, a stiffly accurate method of order 5 with an embedded
+    /// fourth order error estimate.
+    ///
+    /// The second row of the dense output coefficients minimizes the defect of the fourth
+    /// order conditions of the third order continuous output.
+    pub fn rodas5() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U8,
+            U8,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                2.0,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                3.040894194418781,
+                1.041747909077569,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                2.576417536461461,
+                1.62208306077664,
+                -0.9089668560264532,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                2.760842080225597,
+                1.446624659844071,
+                -0.3036980084553738,
+                0.2877498600325443,
+                0.,
+                0.,
+                0.,
+                0.,
+                -14.09640773051259,
+                6.925207756232704,
+                -41.47510893210728,
+                2.343771018586405,
+                24.13215229196062,
+                0.,
+                0.,
+                0.,
+                -14.09640773051259,
+                6.925207756232704,
+                -41.47510893210728,
+                2.343771018586405,
+                24.13215229196062,
+                1.0,
+                0.,
+                0.,
+                -14.09640773051259,
+                6.925207756232704,
+                -41.47510893210728,
+                2.343771018586405,
+                24.13215229196062,
+                1.0,
+                1.0,
+                0.,
+            ],
+        );
+        let c = MatrixMN::from_row_slice_generic(
+            U8,
+            U8,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -10.31323885133993,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -21.04823117650003,
+                -7.234992135176716,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                32.22751541853323,
+                -4.943732386540191,
+                19.44922031041879,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -20.69865579590063,
+                -8.816374604402768,
+                1.260436877740897,
+                -0.7495647613787146,
+                0.,
+                0.,
+                0.,
+                0.,
+                -46.22004352711257,
+                -17.49534862857472,
+                -289.6389582892057,
+                93.60855400400906,
+                318.3822534212147,
+                0.,
+                0.,
+                0.,
+                34.20013733472935,
+                -14.1553540271769,
+                57.823356409884,
+                25.83362985412365,
+                1.408950972071624,
+                -6.551835421242162,
+                0.,
+                0.,
+                42.57076742291101,
+                -13.80770672017997,
+                93.98938432427124,
+                18.77919633714503,
+                -31.5835918722337,
+                -6.685968952921985,
+                -5.810979938412932,
+                0.,
+            ],
+        );
+        let alpha = VectorN::from_row_slice_generic(
+            U8,
+            U1,
+            &[
+                0.,
+                0.38,
+                0.3878509998321533,
+                0.483971893787384,
+                0.457047700881958,
+                1.0,
+                1.0,
+                1.0,
+            ],
+        );
+        let gammas = VectorN::from_row_slice_generic(
+            U8,
+            U1,
+            &[
+                0.19,
+                -0.18230792253337147,
+                -0.3192318321868749,
+                0.3449828624725343,
+                -0.37741756439208984,
+                0.,
+                0.,
+                0.,
+            ],
+        );
+        let dense = MatrixMN::from_row_slice_generic(
+            U8,
+            U2,
+            &[
+                27.354592673333357,
+                -2.014934342532239,
+                -6.925207756232704,
+                -0.7742990243295782,
+                26.40037733258859,
+                9.093512557908271,
+                0.5635230501052979,
+                15.765586369890014,
+                -4.699151156849391,
+                6.078789058815008,
+                -1.6008677469422725,
+                -0.9647585578766318,
+                -1.5306074446748028,
+                0.1792427156057869,
+                0.,
+                0.,
+            ],
+        );
+
+        Self {
+            gamma: 0.19,
+            a,
+            c,
+            alpha,
+            gammas,
+            dense,
+        }
+    }
+}
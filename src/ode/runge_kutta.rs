@@ -1,18 +1,45 @@
+use crate::error::OdeError;
+use crate::ode::dense::DenseCoeffs;
+use crate::ode::types::OdeType;
 use na::allocator::Allocator;
 use na::*;
 use num_traits::identities::{One, Zero};
+use std::collections::BTreeSet;
 use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum RKSymbol {
     Feuler,
+    Beuler,
     Midpoint,
     Heun,
+    SSPRK22,
+    SSPRK33,
+    SSPRK104,
+    Gauss4,
+    Gauss6,
+    LobattoIIIA2,
+    LobattoIIIA4,
+    LobattoIIIB2,
+    LobattoIIIB4,
+    LobattoIIIC2,
+    LobattoIIIC4,
+    SDIRK2,
+    SDIRK4,
+    Kvaerno4,
+    TRBDF2,
+    RadauIIA5,
     RK4,
     RK21,
     RK23,
+    BS3,
     RK45,
+    CashKarp,
     Dopri5,
+    Tsit5,
+    Vern6,
+    Vern9,
+    Dop853,
     Feh78,
     /// (Name, Order)
     Other((String, RKOrder)),
@@ -22,13 +49,36 @@ impl RKSymbol {
     pub fn order(&self) -> RKOrder {
         match self {
             RKSymbol::Feuler => RKOrder::Explicit(1),
+            RKSymbol::Beuler => RKOrder::Explicit(1),
             RKSymbol::Midpoint => RKOrder::Explicit(2),
             RKSymbol::Heun => RKOrder::Explicit(2),
+            RKSymbol::SSPRK22 => RKOrder::Explicit(2),
+            RKSymbol::SSPRK33 => RKOrder::Explicit(3),
+            RKSymbol::SSPRK104 => RKOrder::Explicit(4),
+            RKSymbol::Gauss4 => RKOrder::Explicit(4),
+            RKSymbol::Gauss6 => RKOrder::Explicit(6),
+            RKSymbol::LobattoIIIA2 => RKOrder::Explicit(2),
+            RKSymbol::LobattoIIIA4 => RKOrder::Explicit(4),
+            RKSymbol::LobattoIIIB2 => RKOrder::Explicit(2),
+            RKSymbol::LobattoIIIB4 => RKOrder::Explicit(4),
+            RKSymbol::LobattoIIIC2 => RKOrder::Explicit(2),
+            RKSymbol::LobattoIIIC4 => RKOrder::Explicit(4),
+            RKSymbol::SDIRK2 => RKOrder::Adaptive((2, 1)),
+            RKSymbol::SDIRK4 => RKOrder::Adaptive((4, 3)),
+            RKSymbol::Kvaerno4 => RKOrder::Adaptive((4, 3)),
+            RKSymbol::TRBDF2 => RKOrder::Adaptive((2, 3)),
+            RKSymbol::RadauIIA5 => RKOrder::Explicit(5),
             RKSymbol::RK4 => RKOrder::Explicit(4),
             RKSymbol::RK21 => RKOrder::Adaptive((2, 1)),
             RKSymbol::RK23 => RKOrder::Adaptive((2, 3)),
+            RKSymbol::BS3 => RKOrder::Adaptive((3, 2)),
             RKSymbol::RK45 => RKOrder::Adaptive((4, 5)),
+            RKSymbol::CashKarp => RKOrder::Adaptive((4, 5)),
             RKSymbol::Dopri5 => RKOrder::Adaptive((5, 4)),
+            RKSymbol::Tsit5 => RKOrder::Adaptive((5, 4)),
+            RKSymbol::Vern6 => RKOrder::Adaptive((6, 5)),
+            RKSymbol::Vern9 => RKOrder::Adaptive((9, 8)),
+            RKSymbol::Dop853 => RKOrder::Adaptive((8, 5)),
             RKSymbol::Feh78 => RKOrder::Adaptive((7, 8)),
             RKSymbol::Other((_, order)) => order.clone(),
         }
@@ -69,6 +119,29 @@ impl RKOrder {
     }
 }
 
+/// The ratio `num / den` in the precision of `T`, rounded once by the division.
+///
+/// The integers are converted without rounding as long as they are representable in
+/// `T`, e.g. below `2^24` for `f32` like all numerators and denominators of the
+/// built-in tableaus.
+fn ratio<T: RealField>(num: i32, den: i32) -> T {
+    convert::<f64, T>(f64::from(num)) / convert(f64::from(den))
+}
+
+/// An array of rational coefficients, e.g. `rationals![0, 1 / 4, -8, 3680 / 513]`, in
+/// the precision of `T`.
+macro_rules! rationals {
+    (@den) => {
+        1
+    };
+    (@den $den:literal) => {
+        $den
+    };
+    ($($num:literal $(/ $den:literal)?),* $(,)?) => {
+        [$(ratio($num, rationals!(@den $($den)?))),*]
+    };
+}
+
 /// Tableua of the form
 ///
 /// ```text
@@ -84,6 +157,12 @@ impl RKOrder {
 ///
 /// where `T` is the type of the coefficients
 /// and `S` is the number of stages (an int)
+///
+/// The built-in tableaus are generic over `T`. Their rational coefficients are divided in
+/// the precision of `T` from exact integers and closed forms like the `sqrt(15)` of
+/// [`ButcherTableau::gauss6`] are evaluated in it. The coefficients of the Kværnø,
+/// Tsitouras, Verner and Dormand–Prince 8(5,3) tableaus are only given in double
+/// precision, they are converted from `f64` and keep its rounding in any precision.
 #[derive(Debug, Clone)]
 pub struct ButcherTableau<S: Dim, T: RealField = f64>
 where
@@ -98,6 +177,9 @@ where
     pub b: Weights<S, T>,
     /// nodes
     pub c: VectorN<T, S>,
+    /// continuous extension of an explicit method, used for output inside the steps,
+    /// hermite interpolation is used otherwise
+    pub dense: Option<DenseCoeffs>,
 }
 
 #[derive(Debug, Clone)]
@@ -165,10 +247,15 @@ where
 
     #[inline]
     pub fn is_explicit(&self) -> bool {
-        match &self.b {
-            Weights::Explicit(_) => true,
-            _ => false,
-        }
+        matches!(&self.b, Weights::Explicit(_))
+    }
+
+    /// whether the stages depend on themselves or on later stages, i.e. the rk matrix
+    /// `a` has nonzero entries on or above the diagonal
+    #[inline]
+    pub fn is_implicit(&self) -> bool {
+        let s = self.nstages();
+        (0..s).any(|i| (i..s).any(|j| self.a[(i, j)] != T::zero()))
     }
 
     #[inline]
@@ -176,6 +263,13 @@ where
         !self.is_explicit()
     }
 
+    /// Sets the continuous extension `dense` of the method, e.g. of a user defined
+    /// tableau.
+    pub fn with_dense(mut self, dense: DenseCoeffs) -> Self {
+        self.dense = Some(dense);
+        self
+    }
+
     #[inline]
     pub fn weight_type(&self) -> WeightType {
         match &self.b {
@@ -195,6 +289,299 @@ where
         }
         self.c[row_idx] == T::one()
     }
+
+    /// The tableau with a dynamic number of stages, e.g. to select a method at runtime.
+    pub fn into_dynamic(self) -> ButcherTableau<Dynamic, T> {
+        let s = self.nstages();
+        let b = match self.b {
+            Weights::Explicit(b) => {
+                Weights::<Dynamic, T>::Explicit(DVector::from_iterator(s, b.iter().cloned()))
+            }
+            Weights::Adaptive(b) => Weights::<Dynamic, T>::Adaptive(
+                MatrixMN::<T, Dynamic, U2>::from_iterator(s, b.iter().cloned()),
+            ),
+        };
+        ButcherTableau::<Dynamic, T> {
+            symbol: self.symbol,
+            a: DMatrix::from_iterator(s, s, self.a.iter().cloned()),
+            b,
+            c: DVector::from_iterator(s, self.c.iter().cloned()),
+            dense: self.dense,
+        }
+    }
+}
+
+impl<S: Dim> ButcherTableau<S>
+where
+    DefaultAllocator:
+        Allocator<f64, U1, S> + Allocator<f64, S, U2> + Allocator<f64, S, S> + Allocator<f64, S>,
+{
+    /// The weights of the embedded pair of an adaptive tableau, which yield the solution
+    /// of a step together with its error estimate.
+    pub fn embedded_pair(&self) -> Result<EmbeddedPair, OdeError> {
+        match &self.b {
+            Weights::Adaptive(b) => Ok(EmbeddedPair {
+                b: b.column(0).iter().cloned().collect(),
+                e: b.column(0)
+                    .iter()
+                    .zip(b.column(1).iter())
+                    .map(|(b, bhat)| b - bhat)
+                    .collect(),
+            }),
+            Weights::Explicit(_) => Err(OdeError::not_embedded()),
+        }
+    }
+
+    /// Constructs a user defined tableau with the rk matrix `a`, the weights `b` used for
+    /// stepping, the weights `b_err` of the embedded solution used for error-checking, if
+    /// any, and the nodes `c`.
+    ///
+    /// The order of the method and of its embedded solution are taken from `symbol`,
+    /// usually an [`RKSymbol::Other`]. Fails unless the dimensions agree, every set of
+    /// weights sums to one and every row of `a` sums to its node.
+    pub fn new(
+        symbol: RKSymbol,
+        a: MatrixN<f64, S>,
+        b: VectorN<f64, S>,
+        b_err: Option<VectorN<f64, S>>,
+        c: VectorN<f64, S>,
+    ) -> Result<Self, OdeError> {
+        let s = c.nrows();
+        let shapes = [
+            (a.shape(), (s, s)),
+            (b.shape(), (s, 1)),
+            (b_err.as_ref().map_or((s, 1), |b| b.shape()), (s, 1)),
+        ];
+        if let Some((found, expected)) = shapes.iter().find(|(found, expected)| found != expected) {
+            return Err(OdeError::DimensionMismatch {
+                expected: *expected,
+                found: *found,
+            });
+        }
+        let invalid = |reason: String| Err(OdeError::InvalidButcherTableau { reason });
+        if s == 0 {
+            return invalid("no stages".to_string());
+        }
+        // relative to the magnitude of the coefficients
+        let tol = |x: f64| 1e-12 * x.abs().max(1.);
+        for i in 0..s {
+            let sum: f64 = a.row(i).iter().sum();
+            if (sum - c[i]).abs() > tol(c[i]) {
+                return invalid(format!(
+                    "row {} of `a` sums to {} instead of its node {}",
+                    i, sum, c[i]
+                ));
+            }
+        }
+        for (name, weights) in std::iter::once(("b", &b)).chain(b_err.iter().map(|b| ("b_err", b)))
+        {
+            let sum: f64 = weights.iter().sum();
+            if (sum - 1.).abs() > tol(1.) {
+                return invalid(format!(
+                    "the weights `{}` sum to {} instead of 1",
+                    name, sum
+                ));
+            }
+        }
+        let b = match b_err {
+            Some(b_err) => Weights::Adaptive(MatrixMN::<f64, S, U2>::from_columns(&[b, b_err])),
+            None => Weights::Explicit(b),
+        };
+        Ok(Self {
+            symbol,
+            a,
+            b,
+            c,
+            dense: None,
+        })
+    }
+
+    /// The coefficients `γ_k = bᵀ A^(k-1) 1` of the stability polynomial
+    /// `R(z) = Σ_k γ_k z^k` of an explicit method, the factor a step multiplies the
+    /// solution of `y' = λ y` by for `z = h λ`. The lowest degree comes first.
+    pub fn stability_polynomial(&self) -> Vec<f64> {
+        let s = self.nstages();
+        let b = &self.b.as_slice()[..s];
+        // `A^(k-1) 1`
+        let mut v = vec![1.; s];
+        let mut gamma = Vec::with_capacity(s + 1);
+        gamma.push(1.);
+        for _ in 0..s {
+            gamma.push(b.iter().zip(&v).map(|(b, v)| b * v).sum());
+            v = (0..s)
+                .map(|i| (0..i).map(|j| self.a[(i, j)] * v[j]).sum())
+                .collect();
+        }
+        gamma
+    }
+
+    /// The length `x` of the interval `[-x, 0]` of the negative real axis inside the
+    /// stability region `|R(z)| <= 1` of an explicit method.
+    ///
+    /// The axis is scanned in steps of `0.1` and the end of the interval is refined by
+    /// bisection.
+    pub fn stability_boundary(&self) -> f64 {
+        let gamma = self.stability_polynomial();
+        let stable = |x: f64| gamma.iter().rev().fold(0., |acc, g| acc * -x + g).abs() <= 1.;
+        let dx = 0.1;
+        let mut lo = 0.;
+        while lo < 1e3 && stable(lo + dx) {
+            lo += dx;
+        }
+        let mut hi = lo + dx;
+        while hi - lo > 1e-9 {
+            let mid = 0.5 * (lo + hi);
+            if stable(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// The stability function `R(z) = 1 + z bᵀ (I - z A)^(-1) 1` of the method, the
+    /// factor a step multiplies the solution of `y' = λ y` by for `z = h λ`, for explicit
+    /// and implicit methods alike. Infinite at the poles of `R`.
+    pub fn stability_function(&self, z: Complex<f64>) -> Complex<f64> {
+        let s = self.nstages();
+        let one = Complex::new(1., 0.);
+        let m = DMatrix::from_fn(s, s, |i, j| {
+            let diagonal = if i == j { one } else { Complex::new(0., 0.) };
+            diagonal - z * self.a[(i, j)]
+        });
+        match m.lu().solve(&DVector::repeat(s, one)) {
+            Some(x) => {
+                let b = &self.b.as_slice()[..s];
+                one + z * b
+                    .iter()
+                    .zip(x.iter())
+                    .map(|(b, x)| x * *b)
+                    .sum::<Complex<f64>>()
+            }
+            None => Complex::new(f64::INFINITY, 0.),
+        }
+    }
+
+    /// Samples the boundary of the stability region `|R(z)| <= 1` on `n` rays
+    /// `z = r e^(iφ)` from the origin with evenly spaced angles `φ`, starting on the
+    /// negative real axis.
+    ///
+    /// Each point is where its ray first leaves the region, the rays are scanned in steps
+    /// of `0.1` like in [`ButcherTableau::stability_boundary`]. Rays that stay inside up to
+    /// `|z| = rmax`, e.g. of A-stable methods, end at `rmax`.
+    pub fn stability_region(&self, n: usize, rmax: f64) -> Vec<Complex<f64>> {
+        (0..n)
+            .map(|k| {
+                let phi = std::f64::consts::PI * (1. + 2. * k as f64 / n as f64);
+                let ray = Complex::from_polar(&1., &phi);
+                let stable = |r: f64| self.stability_function(ray * r).norm() <= 1.;
+                let dr = 0.1;
+                let mut lo = 0.;
+                while lo < rmax && stable((lo + dr).min(rmax)) {
+                    lo += dr;
+                }
+                if lo >= rmax {
+                    return ray * rmax;
+                }
+                let mut hi = lo + dr;
+                while hi - lo > 1e-9 {
+                    let mid = 0.5 * (lo + hi);
+                    if stable(mid) {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                ray * lo
+            })
+            .collect()
+    }
+
+    /// Whether the weights used for stepping satisfy the order conditions up to order `p`,
+    /// checked numerically for every rooted tree with at most `p` nodes.
+    pub fn verify_order(&self, p: usize) -> bool {
+        self.satisfies_order_conditions(0, p)
+    }
+
+    /// Whether the weights of the embedded solution used for error-checking satisfy the
+    /// order conditions up to order `p`, always `false` for explicit weights.
+    pub fn verify_embedded_order(&self, p: usize) -> bool {
+        self.satisfies_order_conditions(1, p)
+    }
+
+    /// Checks `bᵀ Φ(t) = 1 / γ(t)` for all trees `t` with at most `p` nodes, where `b` is
+    /// the column `col` of the weights.
+    fn satisfies_order_conditions(&self, col: usize, p: usize) -> bool {
+        let s = self.nstages();
+        let b = match (&self.b, col) {
+            (Weights::Explicit(b), 0) => DVector::from_iterator(s, b.iter().copied()),
+            (Weights::Adaptive(b), col) => DVector::from_iterator(s, b.column(col).iter().copied()),
+            _ => return false,
+        };
+        let a = DMatrix::from_iterator(s, s, self.a.iter().copied());
+        (1..=p).flat_map(RootedTree::with_order).all(|tree| {
+            let phi = b.dot(&tree.stage_weights(&a));
+            // the coefficients are only given to double precision
+            (phi - 1. / tree.density()).abs() <= 1e-10
+        })
+    }
+}
+
+/// A rooted tree, standing for an elementary differential in the Taylor expansion of a
+/// Runge–Kutta step, c.f. Hairer, Nørsett, Wanner, "Solving Ordinary Differential
+/// Equations I", Section II.2.
+///
+/// The children are kept sorted, so that equal trees compare equal.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct RootedTree(Vec<RootedTree>);
+
+impl RootedTree {
+    /// All trees with `order` nodes.
+    fn with_order(order: usize) -> Vec<RootedTree> {
+        let mut trees: BTreeSet<RootedTree> = std::iter::once(RootedTree(Vec::new())).collect();
+        for _ in 1..order {
+            trees = trees.iter().flat_map(RootedTree::grown).collect();
+        }
+        trees.into_iter().collect()
+    }
+
+    /// The trees with one more leaf, attached to any of the nodes.
+    fn grown(&self) -> Vec<RootedTree> {
+        let with_child = |i: Option<usize>, child: RootedTree| {
+            let mut children = self.0.clone();
+            match i {
+                Some(i) => children[i] = child,
+                None => children.push(child),
+            }
+            children.sort();
+            RootedTree(children)
+        };
+        let mut grown = vec![with_child(None, RootedTree(Vec::new()))];
+        for (i, child) in self.0.iter().enumerate() {
+            grown.extend(child.grown().into_iter().map(|g| with_child(Some(i), g)));
+        }
+        grown
+    }
+
+    /// The number of nodes `|t|`.
+    fn order(&self) -> usize {
+        1 + self.0.iter().map(RootedTree::order).sum::<usize>()
+    }
+
+    /// The density `γ(t) = |t| γ(t_1) ⋯ γ(t_m)` of the tree with the subtrees `t_i`.
+    fn density(&self) -> f64 {
+        self.order() as f64 * self.0.iter().map(RootedTree::density).product::<f64>()
+    }
+
+    /// The stage weights `Φ_i(t) = ∏_k Σ_j a_ij Φ_j(t_k)` of the rk matrix `a`.
+    fn stage_weights(&self, a: &DMatrix<f64>) -> DVector<f64> {
+        self.0
+            .iter()
+            .fold(DVector::from_element(a.nrows(), 1.), |phi, child| {
+                phi.component_mul(&(a * child.stage_weights(a)))
+            })
+    }
 }
 
 impl<S: Dim, T: RealField> fmt::Display for ButcherTableau<S, T>
@@ -232,7 +619,321 @@ where
     }
 }
 
-impl ButcherTableau<U1> {
+/// Lays out a user defined tableau like [`ButcherTableau::from_slices`], with the
+/// number of stages taken from the nodes `c`, so that rows and weights of another length
+/// fail to compile. Evaluates to a `Result<ButcherTableau<Dynamic>, OdeError>`.
+///
+/// With a leading `const` the coefficients are evaluated at compile time, which also
+/// checks that every row of `a` sums to its node and every set of weights to one.
+///
+/// ```
+/// use diffeq::butcher_tableau;
+/// use diffeq::ode::runge_kutta::{RKOrder, RKSymbol};
+///
+/// let ralston = butcher_tableau! {
+///     const
+///     symbol: RKSymbol::Other(("Ralston".to_string(), RKOrder::Adaptive((2, 1)))),
+///     c: [0., 2. / 3.],
+///     a: [
+///         [0., 0.],
+///         [2. / 3., 0.],
+///     ],
+///     b: [0.25, 0.75],
+///     b_err: [1., 0.],
+/// }
+/// .unwrap();
+/// assert!(ralston.verify_order(2));
+/// ```
+///
+/// ```compile_fail
+/// # use diffeq::butcher_tableau;
+/// # use diffeq::ode::runge_kutta::{RKOrder, RKSymbol};
+/// let heun = butcher_tableau! {
+///     const
+///     symbol: RKSymbol::Other(("Heun".to_string(), RKOrder::Explicit(2))),
+///     c: [0., 1.],
+///     a: [
+///         [0., 0.],
+///         [0.5, 0.],
+///     ],
+///     b: [0.5, 0.5],
+/// };
+/// ```
+#[macro_export]
+macro_rules! butcher_tableau {
+    (@unit $x:expr) => {
+        ()
+    };
+    (@weights) => {
+        None
+    };
+    (@weights $($e:expr),*) => {
+        Some([$($e),*])
+    };
+    (@build $symbol:expr, $a:expr, $b:expr, $b_err:expr, $c:expr) => {
+        $crate::ode::runge_kutta::ButcherTableau::from_slices(
+            $symbol,
+            &$a.concat(),
+            &$b,
+            $b_err.as_ref().map(|b| &b[..]),
+            &$c,
+        )
+    };
+    (
+        const
+        symbol: $symbol:expr,
+        c: [$($c:expr),* $(,)?],
+        a: [$([$($a:expr),* $(,)?]),* $(,)?],
+        b: [$($b:expr),* $(,)?]
+        $(, b_err: [$($e:expr),* $(,)?])? $(,)?
+    ) => {{
+        const N: usize = [$($crate::butcher_tableau!(@unit $c)),*].len();
+        const C: [f64; N] = [$($c),*];
+        const A: [[f64; N]; N] = [$([$($a),*]),*];
+        const B: [f64; N] = [$($b),*];
+        const B_ERR: Option<[f64; N]> = $crate::butcher_tableau!(@weights $($($e),*)?);
+        const _: () = assert!(
+            $crate::ode::runge_kutta::rows_sum_to_nodes(&A, &C),
+            "every row of `a` must sum to its node in `c`"
+        );
+        const _: () = assert!(
+            $crate::ode::runge_kutta::sums_to_one(&B),
+            "the weights `b` must sum to one"
+        );
+        const _: () = assert!(
+            match &B_ERR {
+                Some(b_err) => $crate::ode::runge_kutta::sums_to_one(b_err),
+                None => true,
+            },
+            "the weights `b_err` must sum to one"
+        );
+        $crate::butcher_tableau!(@build $symbol, A, B, B_ERR, C)
+    }};
+    (
+        symbol: $symbol:expr,
+        c: [$($c:expr),* $(,)?],
+        a: [$([$($a:expr),* $(,)?]),* $(,)?],
+        b: [$($b:expr),* $(,)?]
+        $(, b_err: [$($e:expr),* $(,)?])? $(,)?
+    ) => {{
+        const N: usize = [$($crate::butcher_tableau!(@unit $c)),*].len();
+        let c: [f64; N] = [$($c),*];
+        let a: [[f64; N]; N] = [$([$($a),*]),*];
+        let b: [f64; N] = [$($b),*];
+        let b_err: Option<[f64; N]> = $crate::butcher_tableau!(@weights $($($e),*)?);
+        $crate::butcher_tableau!(@build $symbol, a, b, b_err, c)
+    }};
+}
+
+/// Whether every row of `a` sums to its node, c.f. [`butcher_tableau!`].
+#[doc(hidden)]
+pub const fn rows_sum_to_nodes<const N: usize>(a: &[[f64; N]; N], c: &[f64; N]) -> bool {
+    let mut i = 0;
+    while i < N {
+        let mut sum = 0.;
+        let mut j = 0;
+        while j < N {
+            sum += a[i][j];
+            j += 1;
+        }
+        if !is_close(sum, c[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Whether the weights `b` sum to one, c.f. [`butcher_tableau!`].
+#[doc(hidden)]
+pub const fn sums_to_one(b: &[f64]) -> bool {
+    let mut sum = 0.;
+    let mut i = 0;
+    while i < b.len() {
+        sum += b[i];
+        i += 1;
+    }
+    is_close(sum, 1.)
+}
+
+/// The tolerance of [`ButcherTableau::new`].
+const fn is_close(x: f64, y: f64) -> bool {
+    (x - y).abs() <= 1e-12 * if y.abs() > 1. { y.abs() } else { 1. }
+}
+
+impl ButcherTableau<Dynamic> {
+    /// Constructs a user defined tableau from borrowed coefficients, e.g. `static` arrays,
+    /// with the rk matrix `a` given row by row, c.f. [`ButcherTableau::new`].
+    pub fn from_slices(
+        symbol: RKSymbol,
+        a: &[f64],
+        b: &[f64],
+        b_err: Option<&[f64]>,
+        c: &[f64],
+    ) -> Result<Self, OdeError> {
+        let s = c.len();
+        if a.len() != s * s {
+            return Err(OdeError::DimensionMismatch {
+                expected: (s, s),
+                found: (a.len() / s.max(1), s),
+            });
+        }
+        Self::new(
+            symbol,
+            DMatrix::from_row_slice(s, s, a),
+            DVector::from_column_slice(b),
+            b_err.map(DVector::from_column_slice),
+            DVector::from_column_slice(c),
+        )
+    }
+
+    /// The built-in tableau named like its constructor, e.g. `"dopri5"`, c.f.
+    /// [`TABLEAU_NAMES`].
+    pub fn by_name(name: &str) -> Option<Self> {
+        let btab = match name {
+            "feuler" => ButcherTableau::feuler().into_dynamic(),
+            "beuler" => ButcherTableau::beuler().into_dynamic(),
+            "midpoint" => ButcherTableau::midpoint().into_dynamic(),
+            "heun" => ButcherTableau::heun().into_dynamic(),
+            "ssprk22" => ButcherTableau::ssprk22().into_dynamic(),
+            "gauss4" => ButcherTableau::gauss4().into_dynamic(),
+            "lobatto_iiia2" => ButcherTableau::lobatto_iiia2().into_dynamic(),
+            "lobatto_iiib2" => ButcherTableau::lobatto_iiib2().into_dynamic(),
+            "lobatto_iiic2" => ButcherTableau::lobatto_iiic2().into_dynamic(),
+            "sdirk2" => ButcherTableau::sdirk2().into_dynamic(),
+            "rk21" => ButcherTableau::rk21().into_dynamic(),
+            "ssprk33" => ButcherTableau::ssprk33().into_dynamic(),
+            "gauss6" => ButcherTableau::gauss6().into_dynamic(),
+            "lobatto_iiia4" => ButcherTableau::lobatto_iiia4().into_dynamic(),
+            "lobatto_iiib4" => ButcherTableau::lobatto_iiib4().into_dynamic(),
+            "lobatto_iiic4" => ButcherTableau::lobatto_iiic4().into_dynamic(),
+            "radau_iia5" => ButcherTableau::radau_iia5().into_dynamic(),
+            "trbdf2" => ButcherTableau::trbdf2().into_dynamic(),
+            "rk23" => ButcherTableau::rk23().into_dynamic(),
+            "bs3" => ButcherTableau::bs3().into_dynamic(),
+            "rk4" => ButcherTableau::rk4().into_dynamic(),
+            "sdirk4" => ButcherTableau::sdirk4().into_dynamic(),
+            "kvaerno4" => ButcherTableau::kvaerno4().into_dynamic(),
+            "rk45" => ButcherTableau::rk45().into_dynamic(),
+            "cash_karp" => ButcherTableau::cash_karp().into_dynamic(),
+            "dopri5" => ButcherTableau::dopri5().into_dynamic(),
+            "tsit5" => ButcherTableau::tsit5().into_dynamic(),
+            "vern6" => ButcherTableau::vern6().into_dynamic(),
+            "vern9" => ButcherTableau::vern9().into_dynamic(),
+            "ssprk104" => ButcherTableau::ssprk104().into_dynamic(),
+            "dop853" => ButcherTableau::dop853().into_dynamic(),
+            "feh78" => ButcherTableau::feh78().into_dynamic(),
+            _ => return None,
+        };
+        Some(btab)
+    }
+
+    /// The properties of all built-in tableaus, e.g. to offer a choice of methods.
+    pub fn methods() -> Vec<TableauInfo> {
+        TABLEAU_NAMES
+            .iter()
+            .filter_map(|name| {
+                let btab = Self::by_name(name)?;
+                Some(TableauInfo {
+                    name,
+                    order: btab.order(),
+                    nstages: btab.nstages(),
+                    adaptive: btab.is_adaptive(),
+                    fsal: btab.is_first_same_as_last(),
+                    stiff: btab.is_implicit(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The weights of an embedded pair, c.f. [`ButcherTableau::embedded_pair`].
+///
+/// ```latex
+/// y_{n+1} = y_n + h \sum_{i=1}^{s} b_i k_i, \quad
+/// e_{n+1} = h \sum_{i=1}^{s} (b_i - \hat{b}_i) k_i
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedPair {
+    /// the weights used for stepping
+    pub b: Vec<f64>,
+    /// the differences of the weights used for stepping and of the embedded weights
+    pub e: Vec<f64>,
+}
+
+impl EmbeddedPair {
+    /// The solution after a step of size `dt` from `y` with the stages `ks` and its
+    /// error estimate. Stages beyond the ones of the tableau are ignored.
+    pub fn step<'a, Y: OdeType + 'a, K: IntoIterator<Item = &'a Y>>(
+        &self,
+        dt: f64,
+        y: &Y,
+        ks: K,
+    ) -> (Y, Y) {
+        let mut ytrial = y.clone();
+        ytrial.set_zero();
+        let mut yerr = ytrial.clone();
+        for ((b, e), k) in self.b.iter().zip(&self.e).zip(ks) {
+            ytrial.axpy(b * dt, k);
+            yerr.axpy(e * dt, k);
+        }
+        ytrial.sum_mut(y);
+        (ytrial, yerr)
+    }
+}
+
+/// The names of the built-in tableaus, c.f. [`ButcherTableau::by_name`].
+pub const TABLEAU_NAMES: &[&str] = &[
+    "feuler",
+    "beuler",
+    "midpoint",
+    "heun",
+    "ssprk22",
+    "gauss4",
+    "lobatto_iiia2",
+    "lobatto_iiib2",
+    "lobatto_iiic2",
+    "sdirk2",
+    "rk21",
+    "ssprk33",
+    "gauss6",
+    "lobatto_iiia4",
+    "lobatto_iiib4",
+    "lobatto_iiic4",
+    "radau_iia5",
+    "trbdf2",
+    "rk23",
+    "bs3",
+    "rk4",
+    "sdirk4",
+    "kvaerno4",
+    "rk45",
+    "cash_karp",
+    "dopri5",
+    "tsit5",
+    "vern6",
+    "vern9",
+    "ssprk104",
+    "dop853",
+    "feh78",
+];
+
+/// The properties of a built-in tableau, c.f. [`ButcherTableau::methods`].
+#[derive(Debug, Clone)]
+pub struct TableauInfo {
+    /// the name of the tableau, c.f. [`ButcherTableau::by_name`]
+    pub name: &'static str,
+    pub order: RKOrder,
+    pub nstages: usize,
+    /// whether the method has an embedded solution for error-checking
+    pub adaptive: bool,
+    /// whether the last stage is the first stage of the next step
+    pub fsal: bool,
+    /// whether the method is implicit, i.e. meant for stiff problems
+    pub stiff: bool,
+}
+
+impl<T: RealField> ButcherTableau<U1, T> {
     /// constructs the Butcher Tableau for the (forward) Euler method
     /// ```text
     ///   0.000 | 0.000
@@ -249,11 +950,32 @@ impl ButcherTableau<U1> {
             a,
             b,
             c,
+            dense: None,
+        }
+    }
+
+    /// constructs the Butcher Tableau for the backward (implicit) Euler method
+    /// ```text
+    ///  1.000 | 1.000
+    /// -------+------
+    ///        | 1.000
+    /// ```
+    pub fn beuler() -> Self {
+        let a = Matrix1::one();
+        let b = Weights::Explicit(Vector1::one());
+        let c = Vector1::one();
+
+        Self {
+            symbol: RKSymbol::Beuler,
+            a,
+            b,
+            c,
+            dense: None,
         }
     }
 }
 
-impl ButcherTableau<U2> {
+impl<T: RealField> ButcherTableau<U2, T> {
     /// the midpoint method https://en.wikipedia.org/wiki/Midpoint_method
     ///
     /// ```text
@@ -263,15 +985,16 @@ impl ButcherTableau<U2> {
     ///        | 0.000 1.000
     /// ```
     pub fn midpoint() -> Self {
-        let a = Matrix2::new(0., 0., 0.5, 0.0);
-        let b = Weights::Explicit(Vector2::new(0., 1.0));
-        let c = Vector2::new(0., 0.5);
+        let a = Matrix2::from_row_slice(&rationals![0, 0, 1 / 2, 0]);
+        let b = Weights::Explicit(Vector2::from_row_slice(&rationals![0, 1]));
+        let c = Vector2::from_row_slice(&rationals![0, 1 / 2]);
 
         Self {
             symbol: RKSymbol::Midpoint,
             a,
             b,
             c,
+            dense: None,
         }
     }
 
@@ -283,296 +1006,1577 @@ impl ButcherTableau<U2> {
     ///        | 0.500 0.500
     /// ```
     pub fn heun() -> Self {
-        let a = Matrix2::new(0., 0., 1., 0.);
-        let b = Weights::Explicit(Vector2::new(0.5, 0.5));
-        let c = Vector2::new(0., 1.);
+        let a = Matrix2::from_row_slice(&rationals![0, 0, 1, 0]);
+        let b = Weights::Explicit(Vector2::from_row_slice(&rationals![1 / 2, 1 / 2]));
+        let c = Vector2::from_row_slice(&rationals![0, 1]);
 
         Self {
             symbol: RKSymbol::Heun,
             a,
             b,
             c,
+            dense: None,
         }
     }
 
-    pub fn rk21() -> Self {
-        let a = Matrix2::new(0., 0., 1., 0.);
-        let b = Weights::Adaptive(Matrix2::new(0.5, 0.5, 1., 0.));
-        let c = Vector2::new(0., 1.);
-
+    /// Optimal two stage, second order strong stability preserving method,
+    /// c.f. C.-W. Shu, S. Osher, "Efficient implementation of essentially
+    /// non-oscillatory shock-capturing schemes", 1988.
+    /// SSP coefficient of 1, same coefficients as [`ButcherTableau::heun`].
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000
+    ///  1.000 | 1.000 0.000
+    /// -------+------------
+    ///        | 0.500 0.500
+    /// ```
+    pub fn ssprk22() -> Self {
         Self {
-            symbol: RKSymbol::RK21,
-            a,
-            b,
-            c,
+            symbol: RKSymbol::SSPRK22,
+            ..Self::heun()
         }
     }
-}
 
-impl ButcherTableau<U4> {
-    /// Bogacki–Shampine coefficients
+    /// Two stage Gauss–Legendre collocation method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.72.
+    /// Implicit, A-stable and symplectic with order 4.
+    ///
     /// ```text
-    ///  0.000 | 0.000 0.000 0.000 0.000
-    ///  0.500 | 0.500 0.000 0.000 0.000
-    ///  0.750 | 0.000 0.750 0.000 0.000
-    ///  1.000 | 0.222 0.333 0.444 0.000
-    /// -------+------------------------
-    ///        | 0.292 0.250 0.333 0.125
-    ///        | 0.111 0.333 0.444 0.000
+    ///  0.211 | 0.250 -0.039
+    ///  0.789 | 0.539 0.250
+    /// -------+------------
+    ///        | 0.500 0.500
     /// ```
-    pub fn rk23() -> Self {
-        let a = Matrix4::new(
-            0.,
-            0.,
-            0.,
-            0.,
-            0.5,
-            0.,
-            0.,
-            0.,
-            0.,
-            0.75,
-            0.,
-            0.,
-            2. / 9.,
-            1. / 3.,
-            4. / 9.,
-            0.,
-        );
-        let b = Weights::Adaptive(Matrix4x2::new(
-            7. / 24.,
-            1. / 9.,
-            0.25,
-            1. / 3.,
-            1. / 3.,
-            4. / 9.,
-            0.125,
-            0.,
-        ));
-
-        let c = Vector4::new(0., 0.5, 0.75, 1.);
+    pub fn gauss4() -> Self {
+        let s3 = ratio::<T>(3, 1).sqrt() / ratio(6, 1);
+        let (quarter, half) = (ratio(1, 4), ratio(1, 2));
+        let a = Matrix2::new(quarter, quarter - s3, quarter + s3, quarter);
+        let b = Weights::Explicit(Vector2::new(half, half));
+        let c = Vector2::new(half - s3, half + s3);
 
         Self {
-            symbol: RKSymbol::RK23,
+            symbol: RKSymbol::Gauss4,
             a,
             b,
             c,
+            dense: None,
         }
     }
-}
 
-impl ButcherTableau<U4> {
-    /// constructs the Butcher Tableau for the Runge Kutta 4 method
+    /// Two stage Lobatto IIIA method, the trapezoidal rule, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and A-stable with order 2.
+    ///
     /// ```text
-    ///    0.000 | 0.000 0.000 0.000 0.000
-    ///    0.500 | 0.500 0.000 0.000 0.000
-    ///    0.500 | 0.000 0.500 0.000 0.000
-    ///    1.000 | 0.000 0.000 1.000 0.000
-    ///    -------+------------------------
-    ///    | 0.167 0.333 0.333 0.167
+    ///  0.000 | 0.000 0.000
+    ///  1.000 | 0.500 0.500
+    /// -------+------------
+    ///        | 0.500 0.500
     /// ```
-    pub fn rk4() -> Self {
-        let c = Vector4::new(0., 0.5, 0.5, 1.);
-        let b = Weights::Explicit(Vector4::new(1. / 6., 1. / 3., 1. / 3., 1. / 6.));
-        let a = Matrix4::new(
-            0., 0., 0., 0., 0.5, 0., 0., 0., 0., 0.5, 0., 0., 0., 0., 1., 0.,
-        );
+    pub fn lobatto_iiia2() -> Self {
+        let a = Matrix2::from_row_slice(&rationals![0, 0, 1 / 2, 1 / 2]);
+        let b = Weights::Explicit(Vector2::from_row_slice(&rationals![1 / 2, 1 / 2]));
+        let c = Vector2::from_row_slice(&rationals![0, 1]);
 
         Self {
-            symbol: RKSymbol::RK4,
+            symbol: RKSymbol::LobattoIIIA2,
             a,
             b,
             c,
+            dense: None,
         }
     }
-}
 
-impl ButcherTableau<U6> {
-    /// Fehlberg https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta%E2%80%93Fehlberg_method
-    /// Order of 4 with an error estimator of order 5
+    /// Two stage Lobatto IIIB method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and A-stable with order 2.
     ///
     /// ```text
-    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000
-    ///  0.250 | 0.250 0.000 0.000 0.000 0.000 0.000
-    ///  0.375 | 0.094 0.281 0.000 0.000 0.000 0.000
-    ///  0.923 | 0.879 -3.277 3.321 0.000 0.000 0.000
-    ///  1.000 | 2.032 -8.000 7.173 -0.206 0.000 0.000
-    ///  0.500 | -0.296 2.000 -1.382 0.453 -0.275 0.000
-    /// -------+------------------------------------
-    ///        | 0.116 0.000 0.549 0.535 -0.200 0.000
-    ///        | 0.119 0.000 0.519 0.506 -0.180 0.036
+    ///  0.000 | 0.500 0.000
+    ///  1.000 | 0.500 0.000
+    /// -------+------------
+    ///        | 0.500 0.500
     /// ```
-    pub fn rk45() -> Self {
-        let a = Matrix6::new(
-            0.,
-            0.,
-            0.,
-            0.,
-            0.,
-            0.,
-            0.25,
-            0.,
-            0.,
-            0.,
-            0.,
-            0.,
-            0.09375,
-            0.28125,
-            0.,
-            0.,
-            0.,
-            0.,
-            1932. / 2197.,
-            -7200. / 2197.,
-            7296. / 2197.,
-            0.,
-            0.,
-            0.,
-            439. / 216.,
-            -8.,
-            3680. / 513.,
-            -845. / 4104.,
-            0.,
-            0.,
-            -8. / 27.,
-            2.,
-            -3544. / 2565.,
-            1859. / 4104.,
-            -0.275,
-            0.,
-        );
-        let b = Weights::Adaptive(Matrix6x2::new(
-            25. / 216.,
-            16. / 135.,
-            0.,
-            0.,
-            1408. / 2565.,
-            6656. / 12825.,
-            2197. / 4104.,
-            28561. / 56430.,
-            -0.2,
-            -0.18,
-            0.,
-            2. / 55.,
-        ));
-
-        let c = Vector6::new(0., 0.25, 0.375, 12. / 13., 1., 0.5);
+    pub fn lobatto_iiib2() -> Self {
+        let a = Matrix2::from_row_slice(&rationals![1 / 2, 0, 1 / 2, 0]);
+        let b = Weights::Explicit(Vector2::from_row_slice(&rationals![1 / 2, 1 / 2]));
+        let c = Vector2::from_row_slice(&rationals![0, 1]);
 
         Self {
-            symbol: RKSymbol::RK45,
+            symbol: RKSymbol::LobattoIIIB2,
             a,
             b,
             c,
+            dense: None,
         }
     }
-}
-impl ButcherTableau<U7> {
+
+    /// Two stage Lobatto IIIC method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and L-stable with order 2.
+    ///
     /// ```text
-    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000
-    ///  0.200 | 0.200 0.000 0.000 0.000 0.000 0.000 0.000
-    ///  0.300 | 0.075 0.225 0.000 0.000 0.000 0.000 0.000
-    ///  0.750 | 0.978 -3.733 3.556 0.000 0.000 0.000 0.000
-    ///  0.889 | 2.953 -11.596 9.823 -0.291 0.000 0.000 0.000
-    ///  1.000 | 2.846 -10.758 8.906 0.278 -0.274 0.000 0.000
-    ///  1.000 | 0.091 0.000 0.449 0.651 -0.322 0.131 0.000
-    /// -------+------------------------------------------
-    ///        | 0.091 0.000 0.449 0.651 -0.322 0.131 0.000
-    ///        | 0.090 0.000 0.453 0.614 -0.272 0.089 0.025
+    ///  0.000 | 0.500 -0.500
+    ///  1.000 | 0.500 0.500
+    /// -------+------------
+    ///        | 0.500 0.500
     /// ```
-    pub fn dopri5() -> Self {
-        let a = MatrixMN::from_row_slice_generic(
-            U7,
-            U7,
-            &[
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.2,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.075,
-                0.225,
-                0.,
-                0.,
-                0.,
-                0.,
-                0.,
-                44. / 45.,
-                -56. / 15.,
-                32. / 9.,
-                0.,
-                0.,
-                0.,
-                0.,
-                19372. / 6561.,
-                -25360. / 2187.,
-                64448. / 6561.,
-                -212. / 729.,
-                0.,
-                0.,
-                0.,
-                9017. / 3168.,
-                -355. / 33.,
-                46732. / 5247.,
-                49. / 176.,
-                -5103. / 18656.,
-                0.,
-                0.,
-                35. / 384.,
-                0.,
-                500. / 1113.,
-                125. / 192.,
-                -2187. / 6784.,
-                11. / 84.,
-                0.,
-            ],
-        );
-        let b = Weights::Adaptive(MatrixMN::from_row_slice_generic(
-            U7,
-            U2,
-            &[
-                35. / 384.,
-                5179. / 57600.,
-                0.,
-                0.,
-                500. / 1113.,
-                7571. / 16695.,
-                125. / 192.,
-                393. / 640.,
-                -2187. / 6784.,
-                -92097. / 339_200.,
-                11. / 84.,
-                187. / 2100.,
-                0.,
-                0.025,
-            ],
-        ));
-        let c = VectorN::from_row_slice_generic(U7, U1, &[0., 0.2, 0.3, 0.75, 8. / 9., 1., 1.]);
+    pub fn lobatto_iiic2() -> Self {
+        let a = Matrix2::from_row_slice(&rationals![1 / 2, -1 / 2, 1 / 2, 1 / 2]);
+        let b = Weights::Explicit(Vector2::from_row_slice(&rationals![1 / 2, 1 / 2]));
+        let c = Vector2::from_row_slice(&rationals![0, 1]);
 
         Self {
-            symbol: RKSymbol::Dopri5,
+            symbol: RKSymbol::LobattoIIIC2,
             a,
             b,
             c,
+            dense: None,
+        }
+    }
+
+    /// Two stage, L-stable singly diagonally implicit method of order 2 with
+    /// `γ = 1 - 1/√2`, c.f. R. Alexander, "Diagonally implicit Runge–Kutta methods
+    /// for stiff O.D.E.'s", 1977.
+    /// The embedded first order solution is `y + dt k_1`.
+    ///
+    /// ```text
+    ///  0.293 | 0.293 0.000
+    ///  1.000 | 0.707 0.293
+    /// -------+------------
+    ///        | 0.707 0.293
+    ///        | 1.000 0.000
+    /// ```
+    pub fn sdirk2() -> Self {
+        let gamma = T::one() - ratio::<T>(1, 2).sqrt();
+        let a = Matrix2::new(gamma, T::zero(), T::one() - gamma, gamma);
+        let b = Weights::Adaptive(Matrix2::new(T::one() - gamma, T::one(), gamma, T::zero()));
+        let c = Vector2::new(gamma, T::one());
+
+        Self {
+            symbol: RKSymbol::SDIRK2,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    pub fn rk21() -> Self {
+        let a = Matrix2::from_row_slice(&rationals![0, 0, 1, 0]);
+        let b = Weights::Adaptive(Matrix2::from_row_slice(&rationals![1 / 2, 1, 1 / 2, 0]));
+        let c = Vector2::from_row_slice(&rationals![0, 1]);
+
+        Self {
+            symbol: RKSymbol::RK21,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U3, T> {
+    /// Optimal three stage, third order strong stability preserving method,
+    /// c.f. C.-W. Shu, S. Osher, "Efficient implementation of essentially
+    /// non-oscillatory shock-capturing schemes", 1988.
+    /// SSP coefficient of 1.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000
+    ///  1.000 | 1.000 0.000 0.000
+    ///  0.500 | 0.250 0.250 0.000
+    /// -------+------------------
+    ///        | 0.167 0.167 0.667
+    /// ```
+    pub fn ssprk33() -> Self {
+        let a = Matrix3::from_row_slice(&rationals![0, 0, 0, 1, 0, 0, 1 / 4, 1 / 4, 0]);
+        let b = Weights::Explicit(Vector3::from_row_slice(&rationals![1 / 6, 1 / 6, 2 / 3]));
+        let c = Vector3::from_row_slice(&rationals![0, 1, 1 / 2]);
+
+        Self {
+            symbol: RKSymbol::SSPRK33,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Three stage Gauss–Legendre collocation method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.72.
+    /// Implicit, A-stable and symplectic with order 6.
+    ///
+    /// ```text
+    ///  0.113 | 0.139 -0.036 0.010
+    ///  0.500 | 0.300 0.222 -0.022
+    ///  0.887 | 0.268 0.480 0.139
+    /// -------+------------------
+    ///        | 0.278 0.444 0.278
+    /// ```
+    pub fn gauss6() -> Self {
+        let r = ratio::<T>;
+        let s15 = r(15, 1).sqrt();
+        let a = Matrix3::new(
+            r(5, 36),
+            r(2, 9) - s15 / r(15, 1),
+            r(5, 36) - s15 / r(30, 1),
+            r(5, 36) + s15 / r(24, 1),
+            r(2, 9),
+            r(5, 36) - s15 / r(24, 1),
+            r(5, 36) + s15 / r(30, 1),
+            r(2, 9) + s15 / r(15, 1),
+            r(5, 36),
+        );
+        let b = Weights::Explicit(Vector3::new(r(5, 18), r(4, 9), r(5, 18)));
+        let c = Vector3::new(r(1, 2) - s15 / r(10, 1), r(1, 2), r(1, 2) + s15 / r(10, 1));
+
+        Self {
+            symbol: RKSymbol::Gauss6,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Three stage Lobatto IIIA method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and A-stable with order 4.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000
+    ///  0.500 | 0.208 0.333 -0.042
+    ///  1.000 | 0.167 0.667 0.167
+    /// -------+------------------
+    ///        | 0.167 0.667 0.167
+    /// ```
+    pub fn lobatto_iiia4() -> Self {
+        let a = Matrix3::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            5 / 24,
+            1 / 3,
+            -1 / 24,
+            1 / 6,
+            2 / 3,
+            1 / 6
+        ]);
+        let b = Weights::Explicit(Vector3::from_row_slice(&rationals![1 / 6, 2 / 3, 1 / 6]));
+        let c = Vector3::from_row_slice(&rationals![0, 1 / 2, 1]);
+
+        Self {
+            symbol: RKSymbol::LobattoIIIA4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Three stage Lobatto IIIB method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and A-stable with order 4.
+    ///
+    /// ```text
+    ///  0.000 | 0.167 -0.167 0.000
+    ///  0.500 | 0.167 0.333 0.000
+    ///  1.000 | 0.167 0.833 0.000
+    /// -------+------------------
+    ///        | 0.167 0.667 0.167
+    /// ```
+    pub fn lobatto_iiib4() -> Self {
+        let a = Matrix3::from_row_slice(&rationals![
+            1 / 6,
+            -1 / 6,
+            0,
+            1 / 6,
+            1 / 3,
+            0,
+            1 / 6,
+            5 / 6,
+            0
+        ]);
+        let b = Weights::Explicit(Vector3::from_row_slice(&rationals![1 / 6, 2 / 3, 1 / 6]));
+        let c = Vector3::from_row_slice(&rationals![0, 1 / 2, 1]);
+
+        Self {
+            symbol: RKSymbol::LobattoIIIB4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Three stage Lobatto IIIC method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.75.
+    /// Implicit and L-stable with order 4.
+    ///
+    /// ```text
+    ///  0.000 | 0.167 -0.333 0.167
+    ///  0.500 | 0.167 0.417 -0.083
+    ///  1.000 | 0.167 0.667 0.167
+    /// -------+------------------
+    ///        | 0.167 0.667 0.167
+    /// ```
+    pub fn lobatto_iiic4() -> Self {
+        let a = Matrix3::from_row_slice(&rationals![
+            1 / 6,
+            -1 / 3,
+            1 / 6,
+            1 / 6,
+            5 / 12,
+            -1 / 12,
+            1 / 6,
+            2 / 3,
+            1 / 6
+        ]);
+        let b = Weights::Explicit(Vector3::from_row_slice(&rationals![1 / 6, 2 / 3, 1 / 6]));
+        let c = Vector3::from_row_slice(&rationals![0, 1 / 2, 1]);
+
+        Self {
+            symbol: RKSymbol::LobattoIIIC4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Three stage Radau IIA collocation method, c.f. Hairer & Wanner
+    /// "Solving Ordinary Differential Equations II", p.74.
+    /// Implicit, L-stable and stiffly accurate with order 5.
+    ///
+    /// ```text
+    ///  0.155 | 0.197 -0.066 0.024
+    ///  0.645 | 0.394 0.292 -0.042
+    ///  1.000 | 0.376 0.512 0.111
+    /// -------+------------------
+    ///        | 0.376 0.512 0.111
+    /// ```
+    pub fn radau_iia5() -> Self {
+        let r = ratio::<T>;
+        let s6 = r(6, 1).sqrt();
+        let a = Matrix3::new(
+            (r(88, 1) - r(7, 1) * s6) / r(360, 1),
+            (r(296, 1) - r(169, 1) * s6) / r(1800, 1),
+            (r(-2, 1) + r(3, 1) * s6) / r(225, 1),
+            (r(296, 1) + r(169, 1) * s6) / r(1800, 1),
+            (r(88, 1) + r(7, 1) * s6) / r(360, 1),
+            (r(-2, 1) - r(3, 1) * s6) / r(225, 1),
+            (r(16, 1) - s6) / r(36, 1),
+            (r(16, 1) + s6) / r(36, 1),
+            r(1, 9),
+        );
+        let b = Weights::Explicit(Vector3::new(
+            (r(16, 1) - s6) / r(36, 1),
+            (r(16, 1) + s6) / r(36, 1),
+            r(1, 9),
+        ));
+        let c = Vector3::new(
+            (r(4, 1) - s6) / r(10, 1),
+            (r(4, 1) + s6) / r(10, 1),
+            T::one(),
+        );
+
+        Self {
+            symbol: RKSymbol::RadauIIA5,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// The TR-BDF2 method written as an ESDIRK with `γ = 2 - √2`, c.f. Hosea & Shampine,
+    /// "Analysis and implementation of TR-BDF2", 1996. The first stage takes a
+    /// trapezoidal step to `t + γ dt`, the last stage a BDF2 step from `t` and `t + γ dt`
+    /// to `t + dt`. L-stable and stiffly accurate with order 2, the embedded solution is
+    /// of order 3.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000
+    ///  0.586 | 0.293 0.293 0.000
+    ///  1.000 | 0.354 0.354 0.293
+    /// -------+------------------
+    ///        | 0.354 0.354 0.293
+    ///        | 0.215 0.687 0.098
+    /// ```
+    pub fn trbdf2() -> Self {
+        let r = ratio::<T>;
+        let gamma = r(2, 1) - r(2, 1).sqrt();
+        let d = gamma / r(2, 1);
+        let w = r(2, 1).sqrt() / r(4, 1);
+        let zero = T::zero();
+        let a = Matrix3::new(zero, zero, zero, d, d, zero, w, w, d);
+        let b = Weights::Adaptive(Matrix3x2::new(
+            w,
+            (T::one() - w) / r(3, 1),
+            w,
+            (r(3, 1) * w + T::one()) / r(3, 1),
+            d,
+            d / r(3, 1),
+        ));
+        let c = Vector3::new(zero, gamma, T::one());
+
+        Self {
+            symbol: RKSymbol::TRBDF2,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U4, T> {
+    /// Bogacki–Shampine coefficients
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000
+    ///  0.500 | 0.500 0.000 0.000 0.000
+    ///  0.750 | 0.000 0.750 0.000 0.000
+    ///  1.000 | 0.222 0.333 0.444 0.000
+    /// -------+------------------------
+    ///        | 0.292 0.250 0.333 0.125
+    ///        | 0.222 0.333 0.444 0.000
+    /// ```
+    pub fn rk23() -> Self {
+        let a = Matrix4::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            0,
+            1 / 2,
+            0,
+            0,
+            0,
+            0,
+            3 / 4,
+            0,
+            0,
+            2 / 9,
+            1 / 3,
+            4 / 9,
+            0
+        ]);
+        let b = Weights::Adaptive(Matrix4x2::from_row_slice(&rationals![
+            7 / 24,
+            2 / 9,
+            1 / 4,
+            1 / 3,
+            1 / 3,
+            4 / 9,
+            1 / 8,
+            0
+        ]));
+
+        let c = Vector4::from_row_slice(&rationals![0, 1 / 2, 3 / 4, 1]);
+
+        Self {
+            symbol: RKSymbol::RK23,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Bogacki–Shampine 3(2), steps with the 3rd order solution and uses
+    /// the embedded 2nd order solution for error-checking.
+    ///
+    /// Cheap per step, suited for loose tolerances.
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000
+    ///  0.500 | 0.500 0.000 0.000 0.000
+    ///  0.750 | 0.000 0.750 0.000 0.000
+    ///  1.000 | 0.222 0.333 0.444 0.000
+    /// -------+------------------------
+    ///        | 0.222 0.333 0.444 0.000
+    ///        | 0.292 0.250 0.333 0.125
+    /// ```
+    pub fn bs3() -> Self {
+        let a = Matrix4::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            0,
+            1 / 2,
+            0,
+            0,
+            0,
+            0,
+            3 / 4,
+            0,
+            0,
+            2 / 9,
+            1 / 3,
+            4 / 9,
+            0
+        ]);
+        let b = Weights::Adaptive(Matrix4x2::from_row_slice(&rationals![
+            2 / 9,
+            7 / 24,
+            1 / 3,
+            1 / 4,
+            4 / 9,
+            1 / 3,
+            0,
+            1 / 8
+        ]));
+
+        let c = Vector4::from_row_slice(&rationals![0, 1 / 2, 3 / 4, 1]);
+
+        Self {
+            symbol: RKSymbol::BS3,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U4, T> {
+    /// constructs the Butcher Tableau for the Runge Kutta 4 method
+    /// ```text
+    ///    0.000 | 0.000 0.000 0.000 0.000
+    ///    0.500 | 0.500 0.000 0.000 0.000
+    ///    0.500 | 0.000 0.500 0.000 0.000
+    ///    1.000 | 0.000 0.000 1.000 0.000
+    ///    -------+------------------------
+    ///    | 0.167 0.333 0.333 0.167
+    /// ```
+    pub fn rk4() -> Self {
+        let c = Vector4::from_row_slice(&rationals![0, 1 / 2, 1 / 2, 1]);
+        let b = Weights::Explicit(Vector4::from_row_slice(&rationals![
+            1 / 6,
+            1 / 3,
+            1 / 3,
+            1 / 6
+        ]));
+        let a = Matrix4::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            0,
+            1 / 2,
+            0,
+            0,
+            0,
+            0,
+            1 / 2,
+            0,
+            0,
+            0,
+            0,
+            1,
+            0
+        ]);
+
+        Self {
+            symbol: RKSymbol::RK4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U5, T> {
+    /// Five stage, L-stable singly diagonally implicit method of order 4 with `γ = 1/4`,
+    /// c.f. Hairer & Wanner "Solving Ordinary Differential Equations II", p.100.
+    /// Order of 4 with an error estimator of order 3
+    ///
+    /// ```text
+    ///  0.250 | 0.250 0.000 0.000 0.000 0.000
+    ///  0.750 | 0.500 0.250 0.000 0.000 0.000
+    ///  0.550 | 0.340 -0.040 0.250 0.000 0.000
+    ///  0.500 | 0.273 -0.050 0.028 0.250 0.000
+    ///  1.000 | 1.042 -1.021 7.812 -7.083 0.250
+    /// -------+------------------------------
+    ///        | 1.042 -1.021 7.812 -7.083 0.250
+    ///        | 1.229 -0.177 7.031 -7.083 0.000
+    /// ```
+    pub fn sdirk4() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U5,
+            U5,
+            &rationals![
+                1 / 4,
+                0,
+                0,
+                0,
+                0,
+                1 / 2,
+                1 / 4,
+                0,
+                0,
+                0,
+                17 / 50,
+                -1 / 25,
+                1 / 4,
+                0,
+                0,
+                371 / 1360,
+                -137 / 2720,
+                15 / 544,
+                1 / 4,
+                0,
+                25 / 24,
+                -49 / 48,
+                125 / 16,
+                -85 / 12,
+                1 / 4
+            ],
+        );
+        let b = Weights::Adaptive(MatrixMN::from_row_slice_generic(
+            U5,
+            U2,
+            &rationals![
+                25 / 24,
+                59 / 48,
+                -49 / 48,
+                -17 / 96,
+                125 / 16,
+                225 / 32,
+                -85 / 12,
+                -85 / 12,
+                1 / 4,
+                0
+            ],
+        ));
+        let c = Vector5::from_row_slice(&rationals![1 / 4, 3 / 4, 11 / 20, 1 / 2, 1]);
+
+        Self {
+            symbol: RKSymbol::SDIRK4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Kværnø's five stage, stiffly accurate ESDIRK pair with `γ = 0.5728160625`,
+    /// c.f. A. Kværnø, "Singly diagonally implicit Runge–Kutta methods with an
+    /// explicit first stage", 2004.
+    /// Order of 4 with an error estimator of order 3, given by the fourth stage.
+    /// The coefficients are given in double precision only.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000
+    ///  1.146 | 0.573 0.573 0.000 0.000 0.000
+    ///  0.597 | 0.167 -0.143 0.573 0.000 0.000
+    ///  1.000 | 0.263 -0.312 0.476 0.573 0.000
+    ///  1.000 | 0.197 0.177 0.815 -0.762 0.573
+    /// -------+------------------------------
+    ///        | 0.197 0.177 0.815 -0.762 0.573
+    ///        | 0.263 -0.312 0.476 0.573 0.000
+    /// ```
+    pub fn kvaerno4() -> Self {
+        let gamma = 0.5728160625;
+        let a = MatrixMN::from_row_slice_generic(
+            U5,
+            U5,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                gamma,
+                gamma,
+                0.,
+                0.,
+                0.,
+                0.16723546204189926,
+                -0.1429465368612872,
+                gamma,
+                0.,
+                0.,
+                0.26260329027397755,
+                -0.31190432741478535,
+                0.4764849746408078,
+                gamma,
+                0.,
+                0.19721654832102847,
+                0.1768437839066134,
+                0.8154421814035515,
+                -0.7623185761311934,
+                gamma,
+            ],
+        )
+        .map(convert);
+        let b = Weights::Adaptive(
+            MatrixMN::from_row_slice_generic(
+                U5,
+                U2,
+                &[
+                    0.19721654832102847,
+                    0.26260329027397755,
+                    0.1768437839066134,
+                    -0.31190432741478535,
+                    0.8154421814035515,
+                    0.4764849746408078,
+                    -0.7623185761311934,
+                    gamma,
+                    gamma,
+                    0.,
+                ],
+            )
+            .map(convert),
+        );
+        let c = Vector5::new(0., 2. * gamma, 0.597104987680612, 1., 1.).map(convert);
+
+        Self {
+            symbol: RKSymbol::Kvaerno4,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U6, T> {
+    /// Fehlberg https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta%E2%80%93Fehlberg_method
+    /// Order of 4 with an error estimator of order 5
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.250 | 0.250 0.000 0.000 0.000 0.000 0.000
+    ///  0.375 | 0.094 0.281 0.000 0.000 0.000 0.000
+    ///  0.923 | 0.879 -3.277 3.321 0.000 0.000 0.000
+    ///  1.000 | 2.032 -8.000 7.173 -0.206 0.000 0.000
+    ///  0.500 | -0.296 2.000 -1.382 0.453 -0.275 0.000
+    /// -------+------------------------------------
+    ///        | 0.116 0.000 0.549 0.535 -0.200 0.000
+    ///        | 0.119 0.000 0.519 0.506 -0.180 0.036
+    /// ```
+    pub fn rk45() -> Self {
+        let a = Matrix6::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            1 / 4,
+            0,
+            0,
+            0,
+            0,
+            0,
+            3 / 32,
+            9 / 32,
+            0,
+            0,
+            0,
+            0,
+            1932 / 2197,
+            -7200 / 2197,
+            7296 / 2197,
+            0,
+            0,
+            0,
+            439 / 216,
+            -8,
+            3680 / 513,
+            -845 / 4104,
+            0,
+            0,
+            -8 / 27,
+            2,
+            -3544 / 2565,
+            1859 / 4104,
+            -11 / 40,
+            0
+        ]);
+        let b = Weights::Adaptive(Matrix6x2::from_row_slice(&rationals![
+            25 / 216,
+            16 / 135,
+            0,
+            0,
+            1408 / 2565,
+            6656 / 12825,
+            2197 / 4104,
+            28561 / 56430,
+            -1 / 5,
+            -9 / 50,
+            0,
+            2 / 55
+        ]));
+
+        let c = Vector6::from_row_slice(&rationals![0, 1 / 4, 3 / 8, 12 / 13, 1, 1 / 2]);
+
+        Self {
+            symbol: RKSymbol::RK45,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+
+    /// Cash–Karp, c.f. J.R. Cash, A.H. Karp, "A variable order Runge-Kutta method for
+    /// initial value problems with rapidly varying right-hand sides", 1990.
+    /// Order of 4 with an error estimator of order 5
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.200 | 0.200 0.000 0.000 0.000 0.000 0.000
+    ///  0.300 | 0.075 0.225 0.000 0.000 0.000 0.000
+    ///  0.600 | 0.300 -0.900 1.200 0.000 0.000 0.000
+    ///  1.000 | -0.204 2.500 -2.593 1.296 0.000 0.000
+    ///  0.875 | 0.029 0.342 0.042 0.400 0.062 0.000
+    /// -------+------------------------------------
+    ///        | 0.102 0.000 0.384 0.245 0.019 0.250
+    ///        | 0.098 0.000 0.403 0.210 0.000 0.289
+    /// ```
+    pub fn cash_karp() -> Self {
+        let a = Matrix6::from_row_slice(&rationals![
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            1 / 5,
+            0,
+            0,
+            0,
+            0,
+            0,
+            3 / 40,
+            9 / 40,
+            0,
+            0,
+            0,
+            0,
+            3 / 10,
+            -9 / 10,
+            6 / 5,
+            0,
+            0,
+            0,
+            -11 / 54,
+            5 / 2,
+            -70 / 27,
+            35 / 27,
+            0,
+            0,
+            1631 / 55296,
+            175 / 512,
+            575 / 13824,
+            44275 / 110592,
+            253 / 4096,
+            0
+        ]);
+        let b = Weights::Adaptive(Matrix6x2::from_row_slice(&rationals![
+            2825 / 27648,
+            37 / 378,
+            0,
+            0,
+            18575 / 48384,
+            250 / 621,
+            13525 / 55296,
+            125 / 594,
+            277 / 14336,
+            0,
+            1 / 4,
+            512 / 1771
+        ]));
+
+        let c = Vector6::from_row_slice(&rationals![0, 1 / 5, 3 / 10, 3 / 5, 1, 7 / 8]);
+
+        Self {
+            symbol: RKSymbol::CashKarp,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+impl<T: RealField> ButcherTableau<U7, T> {
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.200 | 0.200 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.300 | 0.075 0.225 0.000 0.000 0.000 0.000 0.000
+    ///  0.800 | 0.978 -3.733 3.556 0.000 0.000 0.000 0.000
+    ///  0.889 | 2.953 -11.596 9.823 -0.291 0.000 0.000 0.000
+    ///  1.000 | 2.846 -10.758 8.906 0.278 -0.274 0.000 0.000
+    ///  1.000 | 0.091 0.000 0.449 0.651 -0.322 0.131 0.000
+    /// -------+------------------------------------------
+    ///        | 0.091 0.000 0.449 0.651 -0.322 0.131 0.000
+    ///        | 0.090 0.000 0.453 0.614 -0.272 0.089 0.025
+    /// ```
+    pub fn dopri5() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U7,
+            U7,
+            &rationals![
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1 / 5,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                3 / 40,
+                9 / 40,
+                0,
+                0,
+                0,
+                0,
+                0,
+                44 / 45,
+                -56 / 15,
+                32 / 9,
+                0,
+                0,
+                0,
+                0,
+                19372 / 6561,
+                -25360 / 2187,
+                64448 / 6561,
+                -212 / 729,
+                0,
+                0,
+                0,
+                9017 / 3168,
+                -355 / 33,
+                46732 / 5247,
+                49 / 176,
+                -5103 / 18656,
+                0,
+                0,
+                35 / 384,
+                0,
+                500 / 1113,
+                125 / 192,
+                -2187 / 6784,
+                11 / 84,
+                0
+            ],
+        );
+        let b = Weights::Adaptive(MatrixMN::from_row_slice_generic(
+            U7,
+            U2,
+            &rationals![
+                35 / 384,
+                5179 / 57600,
+                0,
+                0,
+                500 / 1113,
+                7571 / 16695,
+                125 / 192,
+                393 / 640,
+                -2187 / 6784,
+                -92097 / 339200,
+                11 / 84,
+                187 / 2100,
+                0,
+                1 / 40
+            ],
+        ));
+        let c = VectorN::from_row_slice_generic(
+            U7,
+            U1,
+            &rationals![0, 1 / 5, 3 / 10, 4 / 5, 8 / 9, 1, 1],
+        );
+
+        Self {
+            symbol: RKSymbol::Dopri5,
+            a,
+            b,
+            c,
+            dense: Some(DenseCoeffs::dopri5()),
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U7, T> {
+    /// Tsitouras 5(4), c.f. Ch. Tsitouras, "Runge–Kutta pairs of order 5(4) satisfying
+    /// only the first column simplifying assumption", 2011.
+    /// Order of 5 with an error estimator of order 4.
+    /// The coefficients are given in double precision only.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.161 | 0.161 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.327 | -0.008 0.335 0.000 0.000 0.000 0.000 0.000
+    ///  0.900 | 2.897 -6.359 4.362 0.000 0.000 0.000 0.000
+    ///  0.980 | 5.326 -11.749 7.496 -0.092 0.000 0.000 0.000
+    ///  1.000 | 5.861 -12.921 8.159 -0.072 -0.028 0.000 0.000
+    ///  1.000 | 0.096 0.010 0.480 1.379 -3.290 2.325 0.000
+    /// -------+------------------------------------------
+    ///        | 0.096 0.010 0.480 1.379 -3.290 2.325 0.000
+    ///        | 0.098 0.011 0.472 1.524 -3.872 2.783 -0.015
+    /// ```
+    pub fn tsit5() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U7,
+            U7,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.161,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.008480655492356989,
+                0.335480655492357,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                2.897153057105493,
+                -6.359448489975075,
+                4.3622954328695815,
+                0.,
+                0.,
+                0.,
+                0.,
+                5.325864828439257,
+                -11.748883564062828,
+                7.4955393428898365,
+                -0.09249506636175525,
+                0.,
+                0.,
+                0.,
+                5.86145544294642,
+                -12.92096931784711,
+                8.159367898576159,
+                -0.071584973281401,
+                -0.028269050394068383,
+                0.,
+                0.,
+                0.09646076681806523,
+                0.01,
+                0.4798896504144996,
+                1.379008574103742,
+                -3.290069515436081,
+                2.324710524099774,
+                0.,
+            ],
+        )
+        .map(convert);
+        let b = Weights::Adaptive(
+            MatrixMN::from_row_slice_generic(
+                U7,
+                U2,
+                &[
+                    0.09646076681806523,
+                    0.09824077787029101,
+                    0.01,
+                    0.010816434459656746,
+                    0.4798896504144996,
+                    0.4720087724042376,
+                    1.379008574103742,
+                    1.5237195812770048,
+                    -3.290069515436081,
+                    -3.872426680888636,
+                    2.324710524099774,
+                    2.7827926300289607,
+                    0.,
+                    -1. / 66.,
+                ],
+            )
+            .map(convert),
+        );
+        let c = VectorN::from_row_slice_generic(
+            U7,
+            U1,
+            &[0., 0.161, 0.327, 0.9, 0.980_025_540_904_509_7, 1., 1.],
+        )
+        .map(convert);
+
+        Self {
+            symbol: RKSymbol::Tsit5,
+            a,
+            b,
+            c,
+            dense: Some(DenseCoeffs::tsit5()),
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U9, T> {
+    /// Verner's "most efficient" 6(5) pair, c.f. J.H. Verner, "Numerically optimal
+    /// Runge–Kutta pairs with interpolants", 2010.
+    /// Order of 6 with an error estimator of order 5.
+    /// The coefficients are given in double precision only.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.060 | 0.060 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.096 | 0.019 0.077 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.144 | 0.036 0.000 0.108 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.497 | 1.319 0.000 -5.042 4.221 0.000 0.000 0.000 0.000 0.000
+    ///  0.973 | -41.873 0.000 159.433 -122.119 5.532 0.000 0.000 0.000 0.000
+    ///  1.000 | -54.430 0.000 207.067 -158.611 6.992 -0.019 0.000 0.000 0.000
+    ///  1.000 | -54.664 0.000 207.953 -159.289 7.019 -0.018 -0.001 0.000 0.000
+    ///  1.000 | 0.034 0.000 0.000 0.258 0.421 4.405 -176.483 172.364 0.000
+    /// -------+------------------------------------------------------
+    ///        | 0.034 0.000 0.000 0.258 0.421 4.405 -176.483 172.364 0.000
+    ///        | 0.049 0.000 0.000 0.225 0.469 0.807 0.000 -0.607 0.057
+    /// ```
+    pub fn vern6() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U9,
+            U9,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.06,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.019239962962962962,
+                0.07669337037037037,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.035975,
+                0.,
+                0.107925,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                1.3186834152331484,
+                0.,
+                -5.042058063628562,
+                4.220674648395414,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -41.872591664327516,
+                0.,
+                159.4325621631375,
+                -122.11921356501003,
+                5.531743066200054,
+                0.,
+                0.,
+                0.,
+                0.,
+                -54.430156935316504,
+                0.,
+                207.06725136501848,
+                -158.61081378459,
+                6.991816585950242,
+                -0.018597231062203234,
+                0.,
+                0.,
+                0.,
+                -54.66374178728198,
+                0.,
+                207.95280625538936,
+                -159.2889574744995,
+                7.018743740796944,
+                -0.018338785905045722,
+                -0.0005119484997882099,
+                0.,
+                0.,
+                0.03438957868357036,
+                0.,
+                0.,
+                0.2582624555633503,
+                0.4209371189673537,
+                4.40539646966931,
+                -176.48311902429865,
+                172.36413340141507,
+                0.,
+            ],
+        )
+        .map(convert);
+        let b = Weights::Adaptive(
+            MatrixMN::from_row_slice_generic(
+                U9,
+                U2,
+                &[
+                    0.03438957868357036,
+                    0.0490996764838249,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.2582624555633503,
+                    0.2251112229516524,
+                    0.4209371189673537,
+                    0.4694682253029562,
+                    4.40539646966931,
+                    0.8065792249988868,
+                    -176.48311902429865,
+                    0.,
+                    172.36413340141507,
+                    -0.6071194891777959,
+                    0.,
+                    0.05686113944047569,
+                ],
+            )
+            .map(convert),
+        );
+        let c = VectorN::from_row_slice_generic(
+            U9,
+            U1,
+            &[
+                0.,
+                0.06,
+                0.095_933_333_333_333_33,
+                0.1439,
+                0.4973,
+                0.9725,
+                0.9995,
+                1.,
+                1.,
+            ],
+        )
+        .map(convert);
+
+        Self {
+            symbol: RKSymbol::Vern6,
+            a,
+            b,
+            c,
+            dense: Some(DenseCoeffs::vern6()),
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U10, T> {
+    /// Ten stage, fourth order strong stability preserving method, c.f. D.I. Ketcheson,
+    /// "Highly efficient strong stability-preserving Runge–Kutta methods with
+    /// low-storage implementations", 2008.
+    /// SSP coefficient of 6, allows a low-storage implementation with two registers.
+    ///
+    /// ```text
+    ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.167 | 0.167 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.333 | 0.167 0.167 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.500 | 0.167 0.167 0.167 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.667 | 0.167 0.167 0.167 0.167 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.333 | 0.067 0.067 0.067 0.067 0.067 0.000 0.000 0.000 0.000 0.000
+    ///  0.500 | 0.067 0.067 0.067 0.067 0.067 0.167 0.000 0.000 0.000 0.000
+    ///  0.667 | 0.067 0.067 0.067 0.067 0.067 0.167 0.167 0.000 0.000 0.000
+    ///  0.833 | 0.067 0.067 0.067 0.067 0.067 0.167 0.167 0.167 0.000 0.000
+    ///  1.000 | 0.067 0.067 0.067 0.067 0.067 0.167 0.167 0.167 0.167 0.000
+    /// -------+------------------------------------------------------------
+    ///        | 0.100 0.100 0.100 0.100 0.100 0.100 0.100 0.100 0.100 0.100
+    /// ```
+    pub fn ssprk104() -> Self {
+        // stages 1-5 and 6-10 are forward euler steps of size dt/6, the sixth stage
+        // starts from a combination of the initial value and the fifth stage
+        let mut a = MatrixN::<T, U10>::zeros();
+        for i in 1..10 {
+            for j in 0..i {
+                a[(i, j)] = if j < 5 && i > 4 {
+                    ratio(1, 15)
+                } else {
+                    ratio(1, 6)
+                };
+            }
+        }
+        let b = Weights::Explicit(VectorN::<T, U10>::repeat(ratio(1, 10)));
+        let c = VectorN::<T, U10>::from_row_slice(&rationals![
+            0,
+            1 / 6,
+            1 / 3,
+            1 / 2,
+            2 / 3,
+            1 / 3,
+            1 / 2,
+            2 / 3,
+            5 / 6,
+            1
+        ]);
+
+        Self {
+            symbol: RKSymbol::SSPRK104,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U12, T> {
+    /// Dormand–Prince 8(5,3), c.f. Hairer, Nørsett & Wanner p.181
+    /// Order of 8 with an embedded error estimator of order 5.
+    /// The coefficients are given in double precision only.
+    ///
+    /// The 13th stage of the original method coincides with the first stage
+    /// of the next step and is therefore not part of the tableau.
+    /// See [`crate::ode::dop853::Dop853Coeffs`] for the additional 3rd order
+    /// error estimator and the coefficients of the 7th order dense output.
+    pub fn dop853() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U12,
+            U12,
+            &[
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.05260015195876773,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.0197250569845379,
+                0.0591751709536137,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.02958758547680685,
+                0.,
+                0.08876275643042054,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.2413651341592667,
+                0.,
+                -0.8845494793282861,
+                0.924834003261792,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.037037037037037035,
+                0.,
+                0.,
+                0.17082860872947386,
+                0.12546768756682242,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.037109375,
+                0.,
+                0.,
+                0.17025221101954405,
+                0.06021653898045596,
+                -0.017578125,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.03709200011850479,
+                0.,
+                0.,
+                0.17038392571223998,
+                0.10726203044637328,
+                -0.015319437748624402,
+                0.008273789163814023,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.6241109587160757,
+                0.,
+                0.,
+                -3.3608926294469414,
+                -0.868219346841726,
+                27.59209969944671,
+                20.154067550477894,
+                -43.48988418106996,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.47766253643826434,
+                0.,
+                0.,
+                -2.4881146199716677,
+                -0.590290826836843,
+                21.230051448181193,
+                15.279233632882423,
+                -33.28821096898486,
+                -0.020331201708508627,
+                0.,
+                0.,
+                0.,
+                -0.9371424300859873,
+                0.,
+                0.,
+                5.186372428844064,
+                1.0914373489967295,
+                -8.149787010746927,
+                -18.52006565999696,
+                22.739487099350505,
+                2.4936055526796523,
+                -3.0467644718982196,
+                0.,
+                0.,
+                2.273310147516538,
+                0.,
+                0.,
+                -10.53449546673725,
+                -2.0008720582248625,
+                -17.9589318631188,
+                27.94888452941996,
+                -2.8589982771350235,
+                -8.87285693353063,
+                12.360567175794303,
+                0.6433927460157636,
+                0.,
+            ],
+        )
+        .map(convert);
+        let b = Weights::Adaptive(
+            MatrixMN::from_row_slice_generic(
+                U12,
+                U2,
+                &[
+                    0.054293734116568765,
+                    0.04117368912237388,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    4.450312892752409,
+                    5.675469339128614,
+                    1.8915178993145003,
+                    2.3872768489717506,
+                    -5.801203960010585,
+                    -7.465581142465571,
+                    0.3111643669578199,
+                    0.6614932157077936,
+                    -0.1521609496625161,
+                    -0.48634006837553356,
+                    0.20136540080403034,
+                    0.11944219431891463,
+                    0.04471061572777259,
+                    0.06706592359165889,
+                ],
+            )
+            .map(convert),
+        );
+        let c = VectorN::from_row_slice_generic(
+            U12,
+            U1,
+            &[
+                0.,
+                0.05260015195876773,
+                0.0789002279381516,
+                0.1183503419072274,
+                0.2816496580927726,
+                1. / 3.,
+                0.25,
+                4. / 13.,
+                127. / 195.,
+                0.6,
+                6. / 7.,
+                1.0,
+            ],
+        )
+        .map(convert);
+
+        Self {
+            symbol: RKSymbol::Dop853,
+            a,
+            b,
+            c,
+            dense: None,
         }
     }
 }
 
-impl ButcherTableau<U13> {
+impl<T: RealField> ButcherTableau<U13, T> {
+    /// Fehlberg 7(8), c.f. E. Fehlberg, "Classical fifth-, sixth-, seventh-, and
+    /// eighth-order Runge-Kutta formulas with stepsize control", NASA TR R-287, 1968.
+    /// Order of 7 with an error estimator of order 8
+    ///
+    /// ```text
     ///  0.000 | 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.074 | 0.074 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.111 | 0.028 0.083 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.167 | 0.042 0.000 0.125 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.417 | 0.417 0.000 -1.562 1.562 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
-    ///  0.500 | 0.500 0.000 0.000 0.750 0.200 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
+    ///  0.500 | 0.050 0.000 0.000 0.250 0.200 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.833 | -0.231 0.000 0.000 1.157 -2.407 2.315 0.000 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.167 | 0.103 0.000 0.000 0.000 0.271 -0.222 0.014 0.000 0.000 0.000 0.000 0.000 0.000
     ///  0.667 | 2.000 0.000 0.000 -8.833 15.644 -11.889 0.744 3.000 0.000 0.000 0.000 0.000 0.000
@@ -581,12 +2585,256 @@ impl ButcherTableau<U13> {
     ///  0.000 | 0.015 0.000 0.000 0.000 0.000 -0.146 -0.015 -0.073 0.073 0.146 0.000 0.000 0.000
     ///  1.000 | -0.433 0.000 0.000 -2.079 4.386 -3.524 0.535 0.622 0.201 0.293 0.000 1.000 0.000
     /// -------+------------------------------------------------------------------------------
-    ///        | 0.049 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.000 0.324 0.324 0.257
-    ///        | 0.257 0.257 0.257 0.032 0.032 0.032 0.032 0.049 0.000 0.000 0.049 0.000 0.049
+    ///        | 0.049 0.000 0.000 0.000 0.000 0.324 0.257 0.257 0.032 0.032 0.049 0.000 0.000
+    ///        | 0.000 0.000 0.000 0.000 0.000 0.324 0.257 0.257 0.032 0.032 0.000 0.049 0.049
+    /// ```
     pub fn feh78() -> Self {
         let a = MatrixMN::from_row_slice_generic(
             U13,
             U13,
+            &rationals![
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                2 / 27,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1 / 36,
+                1 / 12,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1 / 24,
+                0,
+                1 / 8,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                5 / 12,
+                0,
+                -25 / 16,
+                25 / 16,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1 / 20,
+                0,
+                0,
+                1 / 4,
+                1 / 5,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                -25 / 108,
+                0,
+                0,
+                125 / 108,
+                -65 / 27,
+                125 / 54,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                31 / 300,
+                0,
+                0,
+                0,
+                61 / 225,
+                -2 / 9,
+                13 / 900,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                2,
+                0,
+                0,
+                -53 / 6,
+                704 / 45,
+                -107 / 9,
+                67 / 90,
+                3,
+                0,
+                0,
+                0,
+                0,
+                0,
+                -91 / 108,
+                0,
+                0,
+                23 / 108,
+                -976 / 135,
+                311 / 54,
+                -19 / 60,
+                17 / 6,
+                -1 / 12,
+                0,
+                0,
+                0,
+                0,
+                2383 / 4100,
+                0,
+                0,
+                -341 / 164,
+                4496 / 1025,
+                -301 / 82,
+                2133 / 4100,
+                45 / 82,
+                45 / 164,
+                18 / 41,
+                0,
+                0,
+                0,
+                3 / 205,
+                0,
+                0,
+                0,
+                0,
+                -6 / 41,
+                -3 / 205,
+                -3 / 41,
+                3 / 41,
+                6 / 41,
+                0,
+                0,
+                0,
+                -1777 / 4100,
+                0,
+                0,
+                -341 / 164,
+                4496 / 1025,
+                -289 / 82,
+                2193 / 4100,
+                51 / 82,
+                33 / 164,
+                12 / 41,
+                0,
+                1,
+                0
+            ],
+        );
+        let b = Weights::Adaptive(MatrixMN::from_row_slice_generic(
+            U13,
+            U2,
+            &rationals![
+                41 / 840,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                34 / 105,
+                34 / 105,
+                9 / 35,
+                9 / 35,
+                9 / 35,
+                9 / 35,
+                9 / 280,
+                9 / 280,
+                9 / 280,
+                9 / 280,
+                41 / 840,
+                0,
+                0,
+                41 / 840,
+                0,
+                41 / 840
+            ],
+        ));
+        let c = VectorN::from_row_slice_generic(
+            U13,
+            U1,
+            &rationals![
+                0,
+                2 / 27,
+                1 / 9,
+                1 / 6,
+                5 / 12,
+                1 / 2,
+                5 / 6,
+                1 / 6,
+                2 / 3,
+                1 / 3,
+                1,
+                0,
+                1
+            ],
+        );
+
+        Self {
+            symbol: RKSymbol::Feh78,
+            a,
+            b,
+            c,
+            dense: None,
+        }
+    }
+}
+
+impl<T: RealField> ButcherTableau<U16, T> {
+    /// Verner's "most efficient" 9(8) pair, c.f. J.H. Verner, "Numerically optimal
+    /// Runge–Kutta pairs with interpolants", 2010.
+    /// Order of 9 with an error estimator of order 8.
+    /// The coefficients are given in double precision only.
+    pub fn vern9() -> Self {
+        let a = MatrixMN::from_row_slice_generic(
+            U16,
+            U16,
             &[
                 0.,
                 0.,
@@ -601,10 +2849,10 @@ impl ButcherTableau<U13> {
                 0.,
                 0.,
                 0.,
-                2. / 27.,
                 0.,
                 0.,
                 0.,
+                0.03462,
                 0.,
                 0.,
                 0.,
@@ -614,22 +2862,20 @@ impl ButcherTableau<U13> {
                 0.,
                 0.,
                 0.,
-                1. / 36.,
-                1. / 12.,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
+                -0.03893354388572875,
+                0.13595789452450918,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
-                1. / 24.,
                 0.,
-                0.125,
                 0.,
                 0.,
                 0.,
@@ -638,12 +2884,11 @@ impl ButcherTableau<U13> {
                 0.,
                 0.,
                 0.,
+                0.03638413148954267,
                 0.,
+                0.10915239446862801,
                 0.,
-                5. / 12.,
                 0.,
-                -25. / 16.,
-                25. / 16.,
                 0.,
                 0.,
                 0.,
@@ -653,12 +2898,12 @@ impl ButcherTableau<U13> {
                 0.,
                 0.,
                 0.,
-                0.5,
                 0.,
                 0.,
-                0.75,
-                0.2,
+                2.0257639143939694,
                 0.,
+                -7.638023836496291,
+                6.173259922102322,
                 0.,
                 0.,
                 0.,
@@ -666,156 +2911,261 @@ impl ButcherTableau<U13> {
                 0.,
                 0.,
                 0.,
-                -25. / 108.,
                 0.,
                 0.,
-                125. / 108.,
-                -65. / 27.,
-                125. / 54.,
                 0.,
                 0.,
                 0.,
+                0.05112275589406061,
                 0.,
                 0.,
+                0.17708237945550218,
+                0.0008027762409222536,
                 0.,
                 0.,
-                31. / 300.,
                 0.,
                 0.,
                 0.,
-                61. / 225.,
-                -2. / 9.,
-                13. / 900.,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
-                2.,
+                0.13160063579752163,
                 0.,
                 0.,
-                -53. / 6.,
-                704. / 45.,
-                -107. / 9.,
-                67. / 90.,
-                3.,
+                -0.2957276252669636,
+                0.08781378035642955,
+                0.6213052975225274,
                 0.,
                 0.,
                 0.,
                 0.,
                 0.,
-                -91. / 108.,
                 0.,
                 0.,
-                23. / 108.,
-                -976. / 135.,
-                311. / 54.,
-                -19. / 60.,
-                17. / 6.,
-                -1. / 12.,
                 0.,
                 0.,
                 0.,
+                0.07166666666666667,
                 0.,
-                2383. / 4100.,
                 0.,
                 0.,
-                -341. / 164.,
-                4496. / 1025.,
-                -301. / 82.,
-                2133. / 4100.,
-                45. / 82.,
-                45. / 164.,
-                18. / 41.,
                 0.,
+                0.33055335789153195,
+                0.2427799754418014,
                 0.,
                 0.,
-                3. / 205.,
                 0.,
                 0.,
                 0.,
                 0.,
-                -6. / 41.,
-                -3. / 205.,
-                -3. / 41.,
-                3. / 41.,
-                6. / 41.,
                 0.,
                 0.,
                 0.,
-                -1777. / 4100.,
+                0.071806640625,
                 0.,
                 0.,
-                -341. / 164.,
-                4496. / 1025.,
-                -289. / 82.,
-                2193. / 4100.,
-                51. / 82.,
-                33. / 164.,
-                12. / 41.,
                 0.,
-                1.,
                 0.,
-            ],
-        );
-        let b = Weights::Adaptive(MatrixMN::from_row_slice_generic(
-            U13,
-            U2,
-            &[
-                41. / 840.,
-                9. / 35.,
+                0.3294380283228177,
+                0.1165190029271823,
+                -0.034013671875,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.04836757646340646,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.03928989925676164,
+                0.10547409458903446,
+                -0.021438652846483126,
+                -0.10412291746271944,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.026645614872014785,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.03333333333333333,
+                -0.1631072244872467,
+                0.03396081684127761,
+                0.1572319413814626,
+                0.21522674780318796,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.03689009248708622,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.1465181576725543,
+                0.2242577768172024,
+                0.02294405717066072,
+                -0.0035850052905728597,
+                0.08669223316444385,
+                0.43838406519683376,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.,
+                -0.4866012215113341,
+                0.,
+                0.,
+                0.,
+                0.,
+                -6.304602650282853,
+                -0.2812456182894729,
+                -2.679019236219849,
+                0.5188156639241577,
+                1.3653531876033418,
+                5.885091088503946,
+                2.8028087862720628,
+                0.,
+                0.,
+                0.,
+                0.,
+                0.4185367457753472,
+                0.,
+                0.,
+                0.,
+                0.,
+                6.724547581906459,
+                -0.42544428016461133,
+                3.3432791530012653,
+                0.6170816631175374,
+                -0.9299661239399329,
+                -6.099948804751011,
+                -3.002206187889399,
+                0.2553202529443446,
                 0.,
-                9. / 35.,
                 0.,
-                9. / 35.,
                 0.,
-                9. / 280.,
+                -0.7793740861228848,
                 0.,
-                9. / 280.,
                 0.,
-                9. / 280.,
                 0.,
-                9. / 280.,
                 0.,
-                41. / 840.,
+                -13.937342538107776,
+                1.2520488533793563,
+                -14.691500408016868,
+                -0.4947050585328968,
+                2.2429749091462368,
+                13.367893803828643,
+                14.396650486650687,
+                -0.79758133317768,
+                0.4409353709534278,
                 0.,
                 0.,
+                2.0580513374668867,
+                0.,
+                0.,
+                0.,
+                0.,
+                22.357937727968032,
+                0.9094981099755646,
+                35.89110098240264,
+                -3.442515027624454,
+                -4.865481358036369,
+                -18.909803813543427,
+                -34.26354448030452,
+                1.2647565216956427,
                 0.,
                 0.,
-                34. / 105.,
-                41. / 840.,
-                34. / 105.,
                 0.,
-                9. / 35.,
-                41. / 840.,
             ],
-        ));
+        )
+        .map(convert);
+        let b = Weights::Adaptive(
+            MatrixMN::from_row_slice_generic(
+                U16,
+                U2,
+                &[
+                    0.014611976858423152,
+                    0.01996996514886773,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    0.,
+                    -0.3915211862331339,
+                    2.19149930494933,
+                    0.23109325002895065,
+                    0.08857071848208439,
+                    0.12747667699928525,
+                    0.11405602348659657,
+                    0.2246434176204158,
+                    0.2533163805345107,
+                    0.5684352689748513,
+                    -2.056564386240941,
+                    0.058258715572158275,
+                    0.340809679901312,
+                    0.13643174034822156,
+                    0.,
+                    0.03057013983082797,
+                    0.,
+                    0.,
+                    0.04834231373823958,
+                ],
+            )
+            .map(convert),
+        );
         let c = VectorN::from_row_slice_generic(
-            U13,
+            U16,
             U1,
             &[
                 0.,
-                2. / 27.,
-                1. / 9.,
-                1. / 6.,
-                5. / 12.,
-                0.5,
-                5. / 6.,
-                1. / 6.,
-                2. / 3.,
-                1. / 3.,
-                1.,
-                0.,
-                1.,
+                0.03462,
+                0.09702435063878045,
+                0.14553652595817068,
+                0.561,
+                0.22900791159048503,
+                0.544992088409515,
+                0.645,
+                0.48375,
+                0.06757,
+                0.25,
+                0.6590650618730999,
+                0.8206,
+                0.9012,
+                1.0,
+                1.0,
             ],
-        );
+        )
+        .map(convert);
 
         Self {
-            symbol: RKSymbol::Feh78,
+            symbol: RKSymbol::Vern9,
             a,
             b,
             c,
+            dense: Some(DenseCoeffs::vern9()),
         }
     }
 }
@@ -824,6 +3174,160 @@ impl ButcherTableau<U13> {
 mod tests {
     use super::*;
 
+    /// the tableaus in double precision, the precision isn't inferred for the checks below
+    type ButcherTableau<S> = super::ButcherTableau<S, f64>;
+
+    #[test]
+    fn rooted_trees() {
+        // OEIS A000081
+        let counts: Vec<_> = (1..=8).map(|p| RootedTree::with_order(p).len()).collect();
+        assert_eq!(vec![1, 1, 2, 4, 9, 20, 48, 115], counts);
+    }
+
+    #[test]
+    fn order_conditions() {
+        fn check<S: Dim>(btab: ButcherTableau<S>)
+        where
+            DefaultAllocator: Allocator<f64, U1, S>
+                + Allocator<f64, S, U2>
+                + Allocator<f64, S, S>
+                + Allocator<f64, S>,
+        {
+            let (order, embedded) = match btab.order() {
+                RKOrder::Explicit(order) => (order, None),
+                RKOrder::Adaptive((order, embedded)) => (order, Some(embedded)),
+            };
+            assert!(btab.verify_order(order), "{:?}", btab.symbol);
+            assert!(!btab.verify_order(order + 1), "{:?}", btab.symbol);
+            if let Some(embedded) = embedded {
+                assert!(btab.verify_embedded_order(embedded), "{:?}", btab.symbol);
+                assert!(
+                    !btab.verify_embedded_order(embedded + 1),
+                    "{:?}",
+                    btab.symbol
+                );
+            }
+        }
+        check(ButcherTableau::feuler());
+        check(ButcherTableau::beuler());
+        check(ButcherTableau::midpoint());
+        check(ButcherTableau::heun());
+        check(ButcherTableau::ssprk22());
+        check(ButcherTableau::gauss4());
+        check(ButcherTableau::lobatto_iiia2());
+        check(ButcherTableau::lobatto_iiib2());
+        check(ButcherTableau::lobatto_iiic2());
+        check(ButcherTableau::sdirk2());
+        check(ButcherTableau::rk21());
+        check(ButcherTableau::ssprk33());
+        check(ButcherTableau::gauss6());
+        check(ButcherTableau::lobatto_iiia4());
+        check(ButcherTableau::lobatto_iiib4());
+        check(ButcherTableau::lobatto_iiic4());
+        check(ButcherTableau::radau_iia5());
+        check(ButcherTableau::trbdf2());
+        check(ButcherTableau::rk23());
+        check(ButcherTableau::bs3());
+        check(ButcherTableau::rk4());
+        check(ButcherTableau::sdirk4());
+        check(ButcherTableau::kvaerno4());
+        check(ButcherTableau::rk45());
+        check(ButcherTableau::cash_karp());
+        check(ButcherTableau::dopri5());
+        check(ButcherTableau::tsit5());
+        check(ButcherTableau::vern6());
+        check(ButcherTableau::ssprk104());
+        check(ButcherTableau::dop853());
+        check(ButcherTableau::feh78());
+        check(ButcherTableau::vern9());
+    }
+
+    #[test]
+    fn single_precision() {
+        let single = super::ButcherTableau::<U7, f32>::dopri5();
+        let double = ButcherTableau::dopri5();
+        for (x, y) in single.a.iter().zip(double.a.iter()) {
+            assert!((f64::from(*x) - y).abs() <= f64::from(f32::EPSILON) * y.abs());
+        }
+        assert_eq!(-92097. / 339_200f32, single.b.as_slice()[11]);
+
+        let gauss = super::ButcherTableau::<U3, f32>::gauss6();
+        assert_eq!(0.5 - 15f32.sqrt() / 10., gauss.c[0]);
+        assert_eq!(
+            ButcherTableau::tsit5().c.map(|c| c as f32),
+            super::ButcherTableau::<U7, f32>::tsit5().c
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let methods = ButcherTableau::methods();
+        assert_eq!(TABLEAU_NAMES.len(), methods.len());
+        let dopri5 = methods.iter().find(|m| m.name == "dopri5").unwrap();
+        assert_eq!(7, dopri5.nstages);
+        assert!(dopri5.adaptive && dopri5.fsal && !dopri5.stiff);
+        assert!(
+            methods
+                .iter()
+                .find(|m| m.name == "radau_iia5")
+                .unwrap()
+                .stiff
+        );
+
+        let btab = ButcherTableau::by_name("dopri5").unwrap();
+        assert_eq!(ButcherTableau::dopri5().a.as_slice(), btab.a.as_slice());
+        assert_eq!(ButcherTableau::dopri5().b.as_slice(), btab.b.as_slice());
+        assert!(btab.dense.is_some());
+        assert!(ButcherTableau::by_name("dopri6").is_none());
+    }
+
+    #[test]
+    fn embedded_pair() {
+        assert!(ButcherTableau::rk4().embedded_pair().is_err());
+        let pair = ButcherTableau::bs3().embedded_pair().unwrap();
+        assert_eq!(vec![2. / 9., 1. / 3., 4. / 9., 0.], pair.b);
+        assert!(pair.e.iter().sum::<f64>().abs() < 1e-15);
+
+        // constant stages are integrated exactly by both solutions
+        let (y, err) = pair.step(0.5, &vec![1.], &vec![vec![2.]; 4]);
+        assert!((y[0] - 2.).abs() < 1e-15);
+        assert!(err[0].abs() < 1e-15);
+    }
+
+    #[test]
+    fn tableau_macro() {
+        let symbol = || RKSymbol::Other(("Heun".to_string(), RKOrder::Explicit(2)));
+        let heun = ButcherTableau::heun();
+        let checked = butcher_tableau! {
+            const
+            symbol: symbol(),
+            c: [0., 1.],
+            a: [[0., 0.], [1., 0.]],
+            b: [0.5, 0.5],
+        }
+        .unwrap();
+        assert_eq!(heun.a.as_slice(), checked.a.as_slice());
+        assert_eq!(heun.b.as_slice(), checked.b.as_slice());
+
+        // coefficients computed at runtime are validated when the tableau is constructed
+        let third = 1. / 3f64.sqrt();
+        let unchecked = butcher_tableau! {
+            symbol: symbol(),
+            c: [0., third],
+            a: [[0., 0.], [third, 0.]],
+            b: [0.5, 0.5],
+            b_err: [1., 0.],
+        };
+        assert!(unchecked.unwrap().is_adaptive());
+        assert!(butcher_tableau! {
+            symbol: symbol(),
+            c: [0., third],
+            a: [[0., 0.], [0.5, 0.]],
+            b: [0.5, 0.5],
+        }
+        .is_err());
+    }
+
     #[test]
     fn is_consistent() {
         assert!(ButcherTableau::feuler().is_consistent_rk());
@@ -833,9 +3337,87 @@ mod tests {
         assert!(ButcherTableau::rk4().is_consistent_rk());
     }
 
+    #[test]
+    fn is_implicit() {
+        assert!(!ButcherTableau::rk4().is_implicit());
+        assert!(ButcherTableau::beuler().is_implicit());
+        assert!(ButcherTableau::sdirk4().is_implicit());
+        assert!(ButcherTableau::kvaerno4().is_implicit());
+        assert!(ButcherTableau::trbdf2().is_implicit());
+        assert!(ButcherTableau::radau_iia5().is_implicit());
+        assert!(!ButcherTableau::dopri5().is_implicit());
+        assert!(ButcherTableau::gauss4().is_implicit());
+        assert!(ButcherTableau::gauss6().is_implicit());
+        assert!(ButcherTableau::lobatto_iiia4().is_implicit());
+        assert!(ButcherTableau::lobatto_iiib4().is_implicit());
+        assert!(ButcherTableau::lobatto_iiic4().is_implicit());
+    }
+
+    #[test]
+    fn continuous_extensions() {
+        assert!(ButcherTableau::rk45().dense.is_none());
+        for (btab, order) in [
+            (ButcherTableau::dopri5().dense, 4),
+            (ButcherTableau::tsit5().dense, 4),
+        ] {
+            assert_eq!(Some(order), btab.map(|dense| dense.order));
+        }
+        assert_eq!(
+            Some(5),
+            ButcherTableau::vern6().dense.map(|dense| dense.order)
+        );
+        assert_eq!(
+            Some(7),
+            ButcherTableau::vern9().dense.map(|dense| dense.order)
+        );
+    }
+
     #[test]
     fn is_fsal() {
         assert!(!ButcherTableau::midpoint().is_first_same_as_last());
         assert!(ButcherTableau::dopri5().is_first_same_as_last());
+        assert!(ButcherTableau::tsit5().is_first_same_as_last());
+        assert!(ButcherTableau::bs3().is_first_same_as_last());
+        assert!(ButcherTableau::vern6().is_first_same_as_last());
+    }
+
+    #[test]
+    fn stability_function() {
+        let z = Complex::new(-0.5, 0.25);
+        let feuler = ButcherTableau::feuler().stability_function(z);
+        assert!((feuler - (1. + z)).norm() < 1e-15);
+        let beuler = ButcherTableau::beuler().stability_function(z);
+        assert!((beuler - 1. / (1. - z)).norm() < 1e-15);
+
+        let rk4 = ButcherTableau::rk4();
+        let gamma = rk4.stability_polynomial();
+        let polynomial = gamma
+            .iter()
+            .rev()
+            .fold(Complex::new(0., 0.), |acc, g| acc * z + g);
+        assert!((rk4.stability_function(z) - polynomial).norm() < 1e-14);
+
+        // the gauss methods are A-stable with `|R(iy)| = 1`
+        let gauss = ButcherTableau::gauss6();
+        assert!((gauss.stability_function(Complex::new(0., 3.)).norm() - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stability_region() {
+        let boundary = ButcherTableau::rk4().stability_region(8, 10.);
+        assert_eq!(8, boundary.len());
+        assert!((boundary[0] + 2.785).norm() < 1e-3);
+        // the right half plane is unstable
+        assert!(boundary[4].norm() < 1e-6);
+
+        let boundary = ButcherTableau::radau_iia5().stability_region(4, 10.);
+        assert!((boundary[0].norm() - 10.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn stability_boundary() {
+        assert!((ButcherTableau::feuler().stability_boundary() - 2.).abs() < 1e-3);
+        assert!((ButcherTableau::rk4().stability_boundary() - 2.785).abs() < 1e-3);
+        assert!((ButcherTableau::dopri5().stability_boundary() - 3.307).abs() < 1e-3);
     }
 }
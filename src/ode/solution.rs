@@ -1,8 +1,12 @@
+use crate::ode::dense::DenseOutput;
 use crate::ode::types::OdeType;
 use alga::general::RealField;
+#[cfg(feature = "ndarray")]
+use ndarray::{Array2, ArrayView1};
 #[cfg(feature = "serde0")]
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::{Index, Sub};
 
 /// pairs the timestamp with the corresponding calculated value`
 #[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
@@ -19,6 +23,82 @@ impl<Y: OdeType, T: RealField> SolutionPoint<Y, T> {
     }
 }
 
+/// How an integration ended.
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReturnCode {
+    /// The integration reached the end of the time span.
+    #[default]
+    Success,
+    /// The integration was aborted because the step size fell below the minimum step
+    /// size, the solution ends at the last accepted step.
+    DtLessThanMin,
+    /// The integration was ended by a callback before the end of the time span.
+    Terminated,
+    /// The integration was aborted after the maximum number of steps, the solution ends
+    /// at the last accepted step.
+    MaxIters,
+    /// The integration was aborted because the problem appeared stiff to an explicit
+    /// solver, the solution ends at the last accepted step.
+    Stiff,
+    /// A step of a fixed step implicit method failed, e.g. as its newton iteration didn't
+    /// converge, the solution ends at the last step.
+    Failure,
+}
+
+impl ReturnCode {
+    /// Whether the integration reached the end of the time span or was ended by a
+    /// callback.
+    #[inline]
+    pub fn is_success(self) -> bool {
+        self == ReturnCode::Success || self == ReturnCode::Terminated
+    }
+}
+
+/// Contains some diagnostics of the integration.
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// Number of evaluations of the RHS, including those of finite difference
+    /// jacobians.
+    pub num_eval: u32,
+    /// Number of evaluations of the jacobian of the RHS.
+    pub num_jac: u32,
+    /// Number of solved linear systems.
+    pub num_linsolve: u32,
+    /// Number of factorizations of iteration matrices.
+    pub num_decomp: u32,
+    pub accepted_steps: u32,
+    pub rejected_steps: u32,
+}
+
+impl Sub for Diagnostics {
+    type Output = Diagnostics;
+
+    /// The statistics collected between the snapshots `rhs` and `self`.
+    fn sub(self, rhs: Diagnostics) -> Diagnostics {
+        Diagnostics {
+            num_eval: self.num_eval - rhs.num_eval,
+            num_jac: self.num_jac - rhs.num_jac,
+            num_linsolve: self.num_linsolve - rhs.num_linsolve,
+            num_decomp: self.num_decomp - rhs.num_decomp,
+            accepted_steps: self.accepted_steps - rhs.accepted_steps,
+            rejected_steps: self.rejected_steps - rhs.rejected_steps,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Number of function evaluations: {}", self.num_eval)?;
+        writeln!(f, "Number of jacobian evaluations: {}", self.num_jac)?;
+        writeln!(f, "Number of linear solves: {}", self.num_linsolve)?;
+        writeln!(f, "Number of factorizations: {}", self.num_decomp)?;
+        writeln!(f, "Number of accepted steps: {}", self.accepted_steps)?;
+        write!(f, "Number of rejected steps: {}", self.rejected_steps)
+    }
+}
+
 #[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
 pub struct OdeSolution<T: RealField, Y: OdeType> {
@@ -26,22 +106,181 @@ pub struct OdeSolution<T: RealField, Y: OdeType> {
     pub tout: Vec<T>,
     /// solutions at times `tout`, stored as a vector `yout`
     pub yout: Vec<Y>,
+    /// How the integration ended
+    pub retcode: ReturnCode,
+    /// Statistics of the integration, collected by the solvers of an
+    /// [`OdeProblem`](crate::ode::problem::OdeProblem)
+    pub stats: Diagnostics,
+    /// The time at which the problem first appeared stiff to an explicit Runge–Kutta
+    /// solver, c.f. [`Stiffness`](crate::ode::options::Stiffness)
+    pub stiff_at: Option<T>,
+    /// The continuous output of the integration, only stored if requested with the
+    /// [`Dense`](crate::ode::options::Dense) option by the adaptive one step methods
+    #[cfg_attr(feature = "serde0", serde(skip))]
+    pub interpolant: Option<DenseOutput<Y>>,
 }
 
 impl<T: RealField, Y: OdeType> OdeSolution<T, Y> {
+    /// A successful solution with the states `yout` at the times `tout`.
+    #[inline]
+    pub fn new(tout: Vec<T>, yout: Vec<Y>) -> Self {
+        Self {
+            tout,
+            yout,
+            retcode: ReturnCode::Success,
+            stats: Diagnostics::default(),
+            stiff_at: None,
+            interpolant: None,
+        }
+    }
+
+    /// Sets the return code.
+    #[inline]
+    pub fn with_retcode(mut self, retcode: ReturnCode) -> Self {
+        self.retcode = retcode;
+        self
+    }
+
+    /// Sets the statistics.
+    #[inline]
+    pub fn with_stats(mut self, stats: Diagnostics) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Sets the time at which the problem appeared stiff.
+    #[inline]
+    pub fn with_stiff_at(mut self, stiff_at: Option<T>) -> Self {
+        self.stiff_at = stiff_at;
+        self
+    }
+
+    /// Sets the continuous output.
+    #[inline]
+    pub fn with_interpolant(mut self, interpolant: Option<DenseOutput<Y>>) -> Self {
+        self.interpolant = interpolant;
+        self
+    }
+
     /// pair each timestep with the corresponding output
     #[inline]
     pub fn zipped(self) -> Vec<(T, Y)> {
         self.tout.into_iter().zip(self.yout).collect()
     }
+
+    /// The number of stored points.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tout.len()
+    }
+
+    /// Whether no points are stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tout.is_empty()
+    }
+
+    /// The times of the stored points.
+    #[inline]
+    pub fn times(&self) -> &[T] {
+        &self.tout
+    }
+
+    /// The states of the stored points.
+    #[inline]
+    pub fn states(&self) -> &[Y] {
+        &self.yout
+    }
+
+    /// The stored point at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<SolutionPoint<Y, T>> {
+        Some(SolutionPoint::new(
+            *self.tout.get(index)?,
+            self.yout.get(index)?.clone(),
+        ))
+    }
+
+    /// The last stored point, the end of the integration.
+    #[inline]
+    pub fn last(&self) -> Option<SolutionPoint<Y, T>> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Whether the integration reached the end of the time span or was ended by a
+    /// callback.
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.retcode.is_success()
+    }
+
+    /// Iterates over the stored times and states.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &Y)> {
+        self.tout.iter().zip(self.yout.iter())
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: RealField, Y: OdeType> OdeSolution<T, Y> {
+    /// The times of the stored points as an array view, without copying.
+    #[inline]
+    pub fn times_view(&self) -> ArrayView1<'_, T> {
+        ArrayView1::from(&self.tout[..])
+    }
+
+    /// The states of the stored points as the rows of a `len x dof` array.
+    pub fn states_array(&self) -> Array2<Y::Item> {
+        let dof = self.yout.first().map_or(0, OdeType::dof);
+        Array2::from_shape_fn((self.len(), dof), |(i, j)| self.yout[i].get(j))
+    }
+}
+
+impl<Y: OdeType> OdeSolution<f64, Y> {
+    /// The state at `t` from the continuous output.
+    ///
+    /// `None` if the continuous output was not stored or `t` lies outside of the
+    /// integrated time span.
+    pub fn at(&self, t: f64) -> Option<Y> {
+        self.interpolant.as_ref()?.at(t)
+    }
+
+    /// The states at all times `ts` from the continuous output, c.f.
+    /// [`OdeSolution::at`].
+    pub fn at_many(&self, ts: &[f64]) -> Option<Vec<Y>> {
+        ts.iter().map(|t| self.at(*t)).collect()
+    }
 }
 
 impl<T: RealField, Y: OdeType> Default for OdeSolution<T, Y> {
     fn default() -> Self {
-        OdeSolution {
-            tout: Vec::new(),
-            yout: Vec::new(),
-        }
+        OdeSolution::new(Vec::new(), Vec::new())
+    }
+}
+
+/// The state at the stored point `index`.
+impl<T: RealField, Y: OdeType> Index<usize> for OdeSolution<T, Y> {
+    type Output = Y;
+
+    fn index(&self, index: usize) -> &Y {
+        &self.yout[index]
+    }
+}
+
+impl<T: RealField, Y: OdeType> IntoIterator for OdeSolution<T, Y> {
+    type Item = (T, Y);
+    type IntoIter = std::iter::Zip<std::vec::IntoIter<T>, std::vec::IntoIter<Y>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tout.into_iter().zip(self.yout)
+    }
+}
+
+impl<'a, T: RealField, Y: OdeType> IntoIterator for &'a OdeSolution<T, Y> {
+    type Item = (&'a T, &'a Y);
+    type IntoIter = std::iter::Zip<std::slice::Iter<'a, T>, std::slice::Iter<'a, Y>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tout.iter().zip(self.yout.iter())
     }
 }
 
@@ -74,3 +313,33 @@ impl<T: RealField, Y: OdeType> fmt::Display for OdeSolution<T, Y> {
         write!(f, ")")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solution_access() {
+        let solution = OdeSolution::new(vec![0., 0.5, 1.], vec![1., 2., 3.]);
+        assert!(solution.is_success());
+        assert_eq!(3, solution.len());
+        assert_eq!(2., solution[1]);
+        assert_eq!(1., solution.last().unwrap().t);
+        assert_eq!(
+            vec![(&0., &1.), (&0.5, &2.), (&1., &3.)],
+            (&solution).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(6., solution.into_iter().map(|(_, y)| y).sum::<f64>());
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn solution_ndarray() {
+        let solution = OdeSolution::new(vec![0., 1.], vec![(1., 2.), (3., 4.)]);
+        assert_eq!(ndarray::arr1(&[0., 1.]), solution.times_view());
+        assert_eq!(
+            ndarray::arr2(&[[1., 2.], [3., 4.]]),
+            solution.states_array()
+        );
+    }
+}
@@ -0,0 +1,819 @@
+use alga::general::RealField;
+use na::{DMatrix, DVector};
+use std::rc::Rc;
+
+/// A column is only pivoted on its diagonal entry if that is at least this fraction of
+/// the largest candidate, which preserves the sparsity of diagonally dominant matrices.
+pub const PIVOT_TOL: f64 = 0.1;
+
+/// The positions of the structurally nonzero entries of a sparse matrix, stored by
+/// columns with sorted row indices.
+///
+/// Declaring the pattern of the jacobian with
+/// [`OdeBuilder::sparsity`](crate::ode::problem::OdeBuilder::sparsity) lets the implicit
+/// solvers approximate it with one evaluation of the RHS per color of
+/// [`SparsityPattern::coloring`] and store and factorize it as a [`CscMatrix`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparsityPattern {
+    nrows: usize,
+    ncols: usize,
+    /// the entries of column `j` are `row_idx[col_ptr[j]..col_ptr[j + 1]]`
+    col_ptr: Vec<usize>,
+    row_idx: Vec<usize>,
+}
+
+impl SparsityPattern {
+    /// The pattern of the `(row, col)` entries, duplicates are merged.
+    pub fn from_entries<I>(nrows: usize, ncols: usize, entries: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .map(|(row, col)| {
+                assert!(
+                    row < nrows && col < ncols,
+                    "entry ({}, {}) out of bounds of a {}x{} matrix",
+                    row,
+                    col,
+                    nrows,
+                    ncols
+                );
+                (col, row)
+            })
+            .collect();
+        entries.sort_unstable();
+        entries.dedup();
+
+        let mut col_ptr = vec![0; ncols + 1];
+        for (col, _) in &entries {
+            col_ptr[col + 1] += 1;
+        }
+        for col in 0..ncols {
+            col_ptr[col + 1] += col_ptr[col];
+        }
+        Self {
+            nrows,
+            ncols,
+            col_ptr,
+            row_idx: entries.into_iter().map(|(_, row)| row).collect(),
+        }
+    }
+
+    /// The pattern of the nonzero entries of `prototype`.
+    pub fn from_matrix<T: RealField>(prototype: &DMatrix<T>) -> Self {
+        let (nrows, ncols) = prototype.shape();
+        Self::from_entries(
+            nrows,
+            ncols,
+            (0..ncols)
+                .flat_map(|col| (0..nrows).map(move |row| (row, col)))
+                .filter(|&entry| !prototype[entry].is_zero()),
+        )
+    }
+
+    /// The pattern of a square `n x n` band matrix with `lower` subdiagonals and `upper`
+    /// superdiagonals, e.g. of a second order finite difference discretization in one
+    /// dimension for `lower = upper = 1`.
+    pub fn banded(n: usize, lower: usize, upper: usize) -> Self {
+        Self::from_entries(
+            n,
+            n,
+            (0..n).flat_map(|col| {
+                (col.saturating_sub(upper)..n.min(col + lower + 1)).map(move |row| (row, col))
+            }),
+        )
+    }
+
+    #[inline]
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    #[inline]
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    #[inline]
+    pub fn shape(&self) -> (usize, usize) {
+        (self.nrows, self.ncols)
+    }
+
+    /// The number of structurally nonzero entries.
+    #[inline]
+    pub fn nnz(&self) -> usize {
+        self.row_idx.len()
+    }
+
+    /// The sorted rows of the entries of column `col`.
+    #[inline]
+    pub fn column(&self, col: usize) -> &[usize] {
+        &self.row_idx[self.col_ptr[col]..self.col_ptr[col + 1]]
+    }
+
+    /// The index of the entry `(row, col)` into the values of a [`CscMatrix`].
+    pub fn find(&self, row: usize, col: usize) -> Option<usize> {
+        self.column(col)
+            .binary_search(&row)
+            .ok()
+            .map(|idx| self.col_ptr[col] + idx)
+    }
+
+    /// The number of subdiagonals and superdiagonals with entries.
+    pub fn bandwidth(&self) -> (usize, usize) {
+        let mut band = (0, 0);
+        for col in 0..self.ncols {
+            for &row in self.column(col) {
+                if row > col {
+                    band.0 = band.0.max(row - col);
+                } else {
+                    band.1 = band.1.max(col - row);
+                }
+            }
+        }
+        band
+    }
+
+    /// The columns with an entry in each row.
+    fn rows(&self) -> Vec<Vec<usize>> {
+        let mut rows = vec![Vec::new(); self.nrows];
+        for col in 0..self.ncols {
+            for &row in self.column(col) {
+                rows[row].push(col);
+            }
+        }
+        rows
+    }
+
+    /// Partitions the columns into groups of structurally orthogonal columns, i.e.
+    /// columns without an entry in a common row, see [`Coloring`].
+    ///
+    /// The columns are colored greedily in the order of decreasing number of columns
+    /// they share a row with, c.f. Curtis, Powell & Reid, "On the estimation of sparse
+    /// Jacobian matrices", 1974, and Coleman & Moré, "Estimation of sparse Jacobian
+    /// matrices and graph coloring problems", 1983.
+    pub fn coloring(&self) -> Coloring {
+        let rows = self.rows();
+        // the last column that counted a column as its neighbour
+        let mut stamp = vec![usize::MAX; self.ncols];
+
+        // largest first ordering by the degree in the column intersection graph
+        let mut degrees = vec![0; self.ncols];
+        for (col, degree) in degrees.iter_mut().enumerate() {
+            for &row in self.column(col) {
+                for &other in &rows[row] {
+                    if other != col && stamp[other] != col {
+                        stamp[other] = col;
+                        *degree += 1;
+                    }
+                }
+            }
+        }
+        let mut order: Vec<usize> = (0..self.ncols).collect();
+        order.sort_by_key(|&col| std::cmp::Reverse(degrees[col]));
+        self.greedy_coloring(&rows, order)
+    }
+
+    /// Colors the columns in the given `order`, each with the first color none of the
+    /// columns it shares a row with has, `rows` are the columns with an entry in each row.
+    fn greedy_coloring<I>(&self, rows: &[Vec<usize>], order: I) -> Coloring
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut colors: Vec<Option<usize>> = vec![None; self.ncols];
+        // the last column a color was forbidden for
+        let mut forbidden: Vec<usize> = Vec::new();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for col in order {
+            for &row in self.column(col) {
+                for &other in &rows[row] {
+                    if let Some(color) = colors[other] {
+                        forbidden[color] = col;
+                    }
+                }
+            }
+            let color = (0..groups.len())
+                .find(|&color| forbidden[color] != col)
+                .unwrap_or_else(|| {
+                    groups.push(Vec::new());
+                    forbidden.push(usize::MAX);
+                    groups.len() - 1
+                });
+            colors[col] = Some(color);
+            groups[color].push(col);
+        }
+        for group in &mut groups {
+            group.sort_unstable();
+        }
+        Coloring { groups }
+    }
+}
+
+/// A partition of the columns of a [`SparsityPattern`] into groups of structurally
+/// orthogonal columns, see [`SparsityPattern::coloring`].
+///
+/// The columns of a group have no entry in a common row, so the sum of their columns of
+/// the matrix determines each of them. A finite difference jacobian perturbs all
+/// components of a group at once and takes one evaluation of the RHS per color instead
+/// of per component, e.g. three for a tridiagonal jacobian of any dimension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coloring {
+    groups: Vec<Vec<usize>>,
+}
+
+impl Coloring {
+    /// The number of groups.
+    #[inline]
+    pub fn ncolors(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// The sorted columns of each group.
+    #[inline]
+    pub fn groups(&self) -> &[Vec<usize>] {
+        &self.groups
+    }
+
+    /// Whether the groups partition the columns of `pattern` into structurally orthogonal
+    /// columns.
+    pub fn is_valid(&self, pattern: &SparsityPattern) -> bool {
+        let mut colored = vec![false; pattern.ncols()];
+        let mut stamp = vec![usize::MAX; pattern.nrows()];
+        for (color, group) in self.groups.iter().enumerate() {
+            for &col in group {
+                if col >= pattern.ncols() || colored[col] {
+                    return false;
+                }
+                colored[col] = true;
+                for &row in pattern.column(col) {
+                    if stamp[row] == color {
+                        return false;
+                    }
+                    stamp[row] = color;
+                }
+            }
+        }
+        colored.into_iter().all(|colored| colored)
+    }
+}
+
+/// A sparse matrix in compressed sparse column format.
+#[derive(Debug, Clone)]
+pub struct CscMatrix<T> {
+    pattern: Rc<SparsityPattern>,
+    /// the values of the entries of the pattern, by columns
+    values: Vec<T>,
+}
+
+impl<T: RealField> CscMatrix<T> {
+    /// The matrix with the `pattern` and all entries zero.
+    pub fn zeros(pattern: Rc<SparsityPattern>) -> Self {
+        let values = vec![T::zero(); pattern.nnz()];
+        Self { pattern, values }
+    }
+
+    /// The entries of `dense` in the `pattern`, all other entries are dropped.
+    pub fn from_dense(pattern: Rc<SparsityPattern>, dense: &DMatrix<T>) -> Self {
+        assert_eq!(
+            pattern.shape(),
+            dense.shape(),
+            "the shape differs from the pattern"
+        );
+        let values = (0..pattern.ncols())
+            .flat_map(|col| {
+                pattern
+                    .column(col)
+                    .iter()
+                    .map(move |&row| dense[(row, col)])
+            })
+            .collect();
+        Self { pattern, values }
+    }
+
+    /// The `nrows x ncols` matrix of the sum of the values of the `(row, col, value)`
+    /// triplets at each position.
+    pub fn from_triplets(nrows: usize, ncols: usize, triplets: &[(usize, usize, T)]) -> Self {
+        let pattern = SparsityPattern::from_entries(
+            nrows,
+            ncols,
+            triplets.iter().map(|&(row, col, _)| (row, col)),
+        );
+        let mut matrix = Self::zeros(Rc::new(pattern));
+        for &(row, col, value) in triplets {
+            let idx = matrix.pattern.find(row, col).unwrap();
+            matrix.values[idx] += value;
+        }
+        matrix
+    }
+
+    #[inline]
+    pub fn pattern(&self) -> &Rc<SparsityPattern> {
+        &self.pattern
+    }
+
+    #[inline]
+    pub fn shape(&self) -> (usize, usize) {
+        self.pattern.shape()
+    }
+
+    /// The values of the entries, by columns.
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// The rows and the values of the entries of column `col`.
+    pub fn column_mut(&mut self, col: usize) -> (&[usize], &mut [T]) {
+        let range = self.pattern.col_ptr[col]..self.pattern.col_ptr[col + 1];
+        (
+            &self.pattern.row_idx[range.clone()],
+            &mut self.values[range],
+        )
+    }
+
+    /// The entry `(row, col)`, zero outside of the pattern.
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.pattern
+            .find(row, col)
+            .map_or_else(T::zero, |idx| self.values[idx])
+    }
+
+    /// The iterator over the `(row, col, value)` of the entries, by columns.
+    pub fn triplets(&self) -> impl Iterator<Item = (usize, usize, T)> + '_ {
+        (0..self.pattern.ncols()).flat_map(move |col| {
+            let start = self.pattern.col_ptr[col];
+            self.pattern
+                .column(col)
+                .iter()
+                .enumerate()
+                .map(move |(idx, &row)| (row, col, self.values[start + idx]))
+        })
+    }
+
+    pub fn to_dense(&self) -> DMatrix<T> {
+        let mut dense = DMatrix::zeros(self.pattern.nrows(), self.pattern.ncols());
+        for (row, col, value) in self.triplets() {
+            dense[(row, col)] = value;
+        }
+        dense
+    }
+
+    /// The iteration matrix `I - dt (A ⊗ J)` of the stage equations of an implicit
+    /// Runge–Kutta method with the rk matrix `A` for this jacobian `J`, c.f.
+    /// [`implicit::solve_stage_increments`](crate::ode::implicit::solve_stage_increments).
+    pub fn stage_matrix(&self, a: &DMatrix<f64>, dt: f64) -> CscMatrix<T> {
+        let n = self.pattern.ncols();
+        let s = a.nrows();
+        let mut triplets: Vec<_> = (0..s * n).map(|d| (d, d, T::one())).collect();
+        for i in 0..s {
+            for j in 0..s {
+                let aij = a[(i, j)] * dt;
+                if aij == 0. {
+                    continue;
+                }
+                triplets
+                    .extend(self.triplets().map(|(p, q, value)| {
+                        (i * n + p, j * n + q, -value * T::from_subset(&aij))
+                    }));
+            }
+        }
+        Self::from_triplets(s * n, s * n, &triplets)
+    }
+
+    /// The matrix `M - γ J` of this jacobian `J` with the mass matrix `M`, the identity if
+    /// `None`. Its pattern adds the diagonal and the nonzero entries of `M` to the pattern
+    /// of `J`.
+    pub fn shifted(&self, mass: Option<&DMatrix<T>>, gamma: f64) -> CscMatrix<T> {
+        let n = self.pattern.ncols();
+        let gamma = T::from_subset(&gamma);
+        let mut triplets: Vec<_> = self
+            .triplets()
+            .map(|(row, col, value)| (row, col, -value * gamma))
+            .collect();
+        match mass {
+            Some(mass) => triplets.extend(
+                mass.iter()
+                    .enumerate()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(idx, &value)| (idx % n, idx / n, value)),
+            ),
+            None => triplets.extend((0..n).map(|d| (d, d, T::one()))),
+        }
+        Self::from_triplets(n, n, &triplets)
+    }
+
+    /// The incomplete LU factorization without fill-in of the square matrix, i.e. `L` and
+    /// `U` are restricted to the pattern, `None` if a diagonal entry is missing or
+    /// vanishes during the elimination.
+    ///
+    /// A preconditioner for the matrix-free linear solves, c.f. Saad, "Iterative Methods
+    /// for Sparse Linear Systems", 2003, Sec. 10.3.
+    pub fn ilu0(&self) -> Option<Ilu0<T>> {
+        let n = self.pattern.ncols();
+        assert_eq!(
+            n,
+            self.pattern.nrows(),
+            "only square matrices have an LU factorization"
+        );
+        let mut factors = self.clone();
+        let mut diag = Vec::with_capacity(n);
+        // the index of each row of the current column into the values
+        let mut position: Vec<Option<usize>> = vec![None; n];
+
+        for j in 0..n {
+            let range = self.pattern.col_ptr[j]..self.pattern.col_ptr[j + 1];
+            for idx in range.clone() {
+                position[self.pattern.row_idx[idx]] = Some(idx);
+            }
+            // eliminate with the columns of L left of the diagonal, top to bottom
+            for idx in range.clone() {
+                let k = self.pattern.row_idx[idx];
+                if k >= j {
+                    break;
+                }
+                let ukj = factors.values[idx];
+                for p in diag[k] + 1..self.pattern.col_ptr[k + 1] {
+                    if let Some(target) = position[self.pattern.row_idx[p]] {
+                        let lik = factors.values[p];
+                        factors.values[target] -= lik * ukj;
+                    }
+                }
+            }
+            let d = position[j]?;
+            let pivot = factors.values[d];
+            if pivot.is_zero() {
+                return None;
+            }
+            for idx in d + 1..range.end {
+                factors.values[idx] /= pivot;
+            }
+            diag.push(d);
+            for idx in range {
+                position[self.pattern.row_idx[idx]] = None;
+            }
+        }
+        Some(Ilu0 { factors, diag })
+    }
+
+    /// The LU factorization with threshold partial pivoting of the square matrix,
+    /// `None` if it is singular.
+    ///
+    /// Computes the columns of `L` and `U` one after another by a sparse triangular
+    /// solve, c.f. Gilbert & Peierls, "Sparse partial pivoting in time proportional to
+    /// arithmetic operations", 1988.
+    pub fn lu(&self) -> Option<SparseLu<T>> {
+        let n = self.pattern.ncols();
+        assert_eq!(
+            n,
+            self.pattern.nrows(),
+            "only square matrices have an LU factorization"
+        );
+        let (ap, ai, ax) = (&self.pattern.col_ptr, &self.pattern.row_idx, &self.values);
+
+        // the columns of L with the unit diagonal first, by original rows until the end
+        let (mut l_ptr, mut l_idx, mut l_val) = (vec![0], Vec::new(), Vec::new());
+        // the columns of U with the diagonal last, by pivot rows
+        let (mut u_ptr, mut u_idx, mut u_val) = (vec![0], Vec::new(), Vec::new());
+        // the pivot position of each row
+        let mut pinv: Vec<Option<usize>> = vec![None; n];
+
+        let mut x = vec![T::zero(); n];
+        let mut marked = vec![false; n];
+        let mut reach: Vec<usize> = Vec::new();
+        let mut stack: Vec<(usize, usize, usize)> = Vec::new();
+
+        for k in 0..n {
+            // the rows reachable from column k in the graph of L, in postorder
+            reach.clear();
+            for &start in &ai[ap[k]..ap[k + 1]] {
+                if marked[start] {
+                    continue;
+                }
+                let children = |j: usize| match pinv[j] {
+                    Some(col) => (l_ptr[col] + 1, l_ptr[col + 1]),
+                    None => (0, 0),
+                };
+                marked[start] = true;
+                let (p, end) = children(start);
+                stack.push((start, p, end));
+                while let Some((j, mut p, end)) = stack.pop() {
+                    while p < end && marked[l_idx[p]] {
+                        p += 1;
+                    }
+                    if p < end {
+                        let child = l_idx[p];
+                        stack.push((j, p + 1, end));
+                        marked[child] = true;
+                        let (p, end) = children(child);
+                        stack.push((child, p, end));
+                    } else {
+                        reach.push(j);
+                    }
+                }
+            }
+
+            // solve `L x = A[:, k]` in topological order
+            for p in ap[k]..ap[k + 1] {
+                x[ai[p]] = ax[p];
+            }
+            for &j in reach.iter().rev() {
+                if let Some(col) = pinv[j] {
+                    let xj = x[j];
+                    for p in l_ptr[col] + 1..l_ptr[col + 1] {
+                        x[l_idx[p]] -= l_val[p] * xj;
+                    }
+                }
+            }
+
+            // the entries of U and the pivot among the rows without one
+            let mut ipiv = None;
+            let mut amax = T::zero();
+            for &i in &reach {
+                match pinv[i] {
+                    Some(row) => {
+                        u_idx.push(row);
+                        u_val.push(x[i]);
+                    }
+                    None if ipiv.is_none() || x[i].abs() > amax => {
+                        ipiv = Some(i);
+                        amax = x[i].abs();
+                    }
+                    None => {}
+                }
+            }
+            let mut ipiv = ipiv?;
+            if amax.is_zero() || !amax.is_finite() {
+                return None;
+            }
+            if marked[k] && pinv[k].is_none() && x[k].abs() >= amax * T::from_subset(&PIVOT_TOL) {
+                ipiv = k;
+            }
+
+            let pivot = x[ipiv];
+            u_idx.push(k);
+            u_val.push(pivot);
+            u_ptr.push(u_idx.len());
+            pinv[ipiv] = Some(k);
+            l_idx.push(ipiv);
+            l_val.push(T::one());
+            for &i in &reach {
+                if pinv[i].is_none() {
+                    l_idx.push(i);
+                    l_val.push(x[i] / pivot);
+                }
+                x[i] = T::zero();
+                marked[i] = false;
+            }
+            l_ptr.push(l_idx.len());
+        }
+
+        let pinv: Vec<usize> = pinv.into_iter().map(Option::unwrap).collect();
+        for row in l_idx.iter_mut() {
+            *row = pinv[*row];
+        }
+        Some(SparseLu {
+            pinv,
+            l_ptr,
+            l_idx,
+            l_val,
+            u_ptr,
+            u_idx,
+            u_val,
+        })
+    }
+}
+
+/// The factorization `P A = L U` of a sparse matrix, see [`CscMatrix::lu`].
+#[derive(Debug, Clone)]
+pub struct SparseLu<T> {
+    /// the row of `P A` of each row of `A`
+    pinv: Vec<usize>,
+    l_ptr: Vec<usize>,
+    l_idx: Vec<usize>,
+    l_val: Vec<T>,
+    u_ptr: Vec<usize>,
+    u_idx: Vec<usize>,
+    u_val: Vec<T>,
+}
+
+impl<T: RealField> SparseLu<T> {
+    /// The number of nonzero entries of `L` and `U`, including their diagonals.
+    pub fn nnz(&self) -> usize {
+        self.l_idx.len() + self.u_idx.len()
+    }
+
+    /// Solves `A x = b`.
+    pub fn solve(&self, b: &DVector<T>) -> DVector<T> {
+        let n = self.pinv.len();
+        assert_eq!(n, b.len(), "the dimension differs from the matrix");
+        let mut x = DVector::zeros(n);
+        for (i, &row) in self.pinv.iter().enumerate() {
+            x[row] = b[i];
+        }
+        for j in 0..n {
+            let xj = x[j];
+            for p in self.l_ptr[j] + 1..self.l_ptr[j + 1] {
+                x[self.l_idx[p]] -= self.l_val[p] * xj;
+            }
+        }
+        for j in (0..n).rev() {
+            let diag = self.u_ptr[j + 1] - 1;
+            x[j] /= self.u_val[diag];
+            let xj = x[j];
+            for p in self.u_ptr[j]..diag {
+                x[self.u_idx[p]] -= self.u_val[p] * xj;
+            }
+        }
+        x
+    }
+}
+
+/// The incomplete factorization `A ≈ L U` of a sparse matrix, see [`CscMatrix::ilu0`].
+#[derive(Debug, Clone)]
+pub struct Ilu0<T> {
+    /// `L` below and `U` on and above the diagonal, `L` with an implied unit diagonal
+    factors: CscMatrix<T>,
+    /// the index of the diagonal entry of each column
+    diag: Vec<usize>,
+}
+
+impl<T: RealField> Ilu0<T> {
+    /// Solves `L U x = b`.
+    pub fn solve(&self, b: &DVector<T>) -> DVector<T> {
+        let pattern = &self.factors.pattern;
+        let values = &self.factors.values;
+        let n = self.diag.len();
+        assert_eq!(n, b.len(), "the dimension differs from the matrix");
+        let mut x = b.clone();
+        for j in 0..n {
+            let xj = x[j];
+            for p in self.diag[j] + 1..pattern.col_ptr[j + 1] {
+                x[pattern.row_idx[p]] -= values[p] * xj;
+            }
+        }
+        for j in (0..n).rev() {
+            x[j] /= values[self.diag[j]];
+            let xj = x[j];
+            for p in pattern.col_ptr[j]..self.diag[j] {
+                x[pattern.row_idx[p]] -= values[p] * xj;
+            }
+        }
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coloring() {
+        let tridiagonal = SparsityPattern::banded(10, 1, 1);
+        assert_eq!(28, tridiagonal.nnz());
+        assert_eq!(&[3, 4, 5], tridiagonal.column(4));
+        let coloring = tridiagonal.coloring();
+        assert_eq!(3, coloring.ncolors());
+        assert!(coloring.is_valid(&tridiagonal));
+
+        // the five point stencil of the laplacian on a 6x6 grid
+        let m = 6;
+        let laplacian = SparsityPattern::from_entries(
+            m * m,
+            m * m,
+            (0..m * m).flat_map(|i| {
+                let (x, y) = (i % m, i / m);
+                let mut entries = vec![(i, i)];
+                if x > 0 {
+                    entries.push((i, i - 1));
+                }
+                if x + 1 < m {
+                    entries.push((i, i + 1));
+                }
+                if y > 0 {
+                    entries.push((i, i - m));
+                }
+                if y + 1 < m {
+                    entries.push((i, i + m));
+                }
+                entries
+            }),
+        );
+        let coloring = laplacian.coloring();
+        assert!(coloring.is_valid(&laplacian));
+        assert!(coloring.ncolors() <= 7, "{}", coloring.ncolors());
+
+        let pattern = SparsityPattern::from_entries(
+            4,
+            4,
+            vec![(0, 0), (0, 3), (1, 1), (2, 2), (3, 0), (3, 1)],
+        );
+        assert!(pattern.coloring().is_valid(&pattern));
+        let invalid = Coloring {
+            groups: vec![vec![0, 1], vec![2, 3]],
+        };
+        assert!(!invalid.is_valid(&pattern));
+    }
+
+    #[test]
+    fn largest_first() {
+        // a tridiagonal pattern with a dense last column, i.e. an arrow pattern
+        let n = 20;
+        let arrow = SparsityPattern::from_entries(
+            n,
+            n,
+            (0..n)
+                .flat_map(|i| vec![(i, i.saturating_sub(1)), (i, i), (i, (i + 1).min(n - 1))])
+                .chain((0..n).map(|i| (i, n - 1))),
+        );
+        for pattern in [SparsityPattern::banded(n, 2, 1), arrow] {
+            let natural = pattern.greedy_coloring(&pattern.rows(), 0..n);
+            let coloring = pattern.coloring();
+            assert!(natural.is_valid(&pattern));
+            assert!(coloring.is_valid(&pattern));
+            assert!(coloring.ncolors() <= natural.ncolors());
+        }
+    }
+
+    #[test]
+    fn lu() {
+        // a sparse matrix that requires pivoting off the diagonal
+        let dense = DMatrix::from_row_slice(
+            5,
+            5,
+            &[
+                0., 2., 0., 0., 1., //
+                3., 0., 0., 1., 0., //
+                0., 0., 4., 0., 0., //
+                0., 1., 0., 1e-3, 2., //
+                1., 0., 5., 0., 0.,
+            ],
+        );
+        let sparse = CscMatrix::from_dense(Rc::new(SparsityPattern::from_matrix(&dense)), &dense);
+        assert_eq!(dense, sparse.to_dense());
+        let b = DVector::from_column_slice(&[1., 2., 3., 4., 5.]);
+        let x = sparse.lu().unwrap().solve(&b);
+        assert!((&dense * x - b).amax() < 1e-12);
+
+        let singular = DMatrix::from_row_slice(2, 2, &[1., 2., 2., 4.]);
+        let sparse =
+            CscMatrix::from_dense(Rc::new(SparsityPattern::from_matrix(&singular)), &singular);
+        assert!(sparse.lu().is_none());
+
+        // `I - dt (A ⊗ J)` of two stages
+        let jac = CscMatrix::from_dense(
+            Rc::new(SparsityPattern::banded(3, 1, 1)),
+            &DMatrix::from_element(3, 3, 2.),
+        );
+        let a = DMatrix::from_row_slice(2, 2, &[0.5, 0., 0.25, 0.25]);
+        let m = jac.stage_matrix(&a, 0.1).to_dense();
+        assert_eq!(1. - 0.1, m[(0, 0)]);
+        assert_eq!(-0.05, m[(3, 1)]);
+        assert_eq!(0., m[(0, 3)]);
+        assert_eq!(0., m[(2, 0)]);
+    }
+
+    #[test]
+    fn ilu0() {
+        // without fill-in on a tridiagonal matrix, the factorization is exact
+        let n = 6;
+        let tridiagonal = DMatrix::from_fn(n, n, |i, j| match i as isize - j as isize {
+            0 => 4. + i as f64,
+            1 => -1.,
+            -1 => 2.,
+            _ => 0.,
+        });
+        let sparse = CscMatrix::from_dense(Rc::new(SparsityPattern::banded(n, 1, 1)), &tridiagonal);
+        let b = DVector::from_fn(n, |i, _| (i as f64).cos());
+        let x = sparse.ilu0().unwrap().solve(&b);
+        assert!((&tridiagonal * x - &b).amax() < 1e-12);
+
+        // `I - 0.5 J` with the fill-in dropped only approximates the inverse
+        let jac = CscMatrix::from_dense(
+            Rc::new(SparsityPattern::from_entries(
+                3,
+                3,
+                vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (0, 2), (2, 2)],
+            )),
+            &DMatrix::from_element(3, 3, -2.),
+        );
+        let m = jac.shifted(None, 0.5);
+        assert_eq!(2., m.get(1, 1));
+        assert_eq!(1., m.get(1, 0));
+        let dense = m.to_dense();
+        let x = m.ilu0().unwrap().solve(&b.rows(0, 3).into_owned());
+        let residual = &dense * x - b.rows(0, 3);
+        assert!(residual.amax() > 1e-6);
+        assert!(residual.amax() < b.rows(0, 3).amax());
+
+        // a missing diagonal entry
+        let pattern = SparsityPattern::from_entries(2, 2, vec![(0, 0), (1, 0), (0, 1)]);
+        let m = CscMatrix::from_dense(Rc::new(pattern), &DMatrix::from_element(2, 2, 1.));
+        assert!(m.ilu0().is_none());
+    }
+}
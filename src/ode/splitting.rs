@@ -0,0 +1,269 @@
+use crate::error::OdeError;
+use crate::ode::options::OdeOptionMap;
+use crate::ode::problem::OdeProblem;
+use crate::ode::solution::OdeSolution;
+use crate::ode::types::OdeType;
+use crate::ode::Ode;
+use alga::general::RealField;
+use std::ops::{Add, Mul};
+
+/// The composition of the flows of the operators within one step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Splitting {
+    /// `φ_N^h ∘ ... ∘ φ_1^h`, of order 1
+    Lie,
+    /// `φ_1^{h/2} ∘ ... ∘ φ_{N-1}^{h/2} ∘ φ_N^h ∘ φ_{N-1}^{h/2} ∘ ... ∘ φ_1^{h/2}`, of
+    /// order 2
+    Strang,
+}
+
+impl Splitting {
+    /// the order of the splitting for exact sub-integrators
+    pub fn order(&self) -> usize {
+        match self {
+            Splitting::Lie => 1,
+            Splitting::Strang => 2,
+        }
+    }
+
+    /// The substeps `(operator, fraction of the step)` of a step with `n` operators in the
+    /// order they are applied.
+    pub fn substeps(&self, n: usize) -> Vec<(usize, f64)> {
+        match self {
+            Splitting::Lie => (0..n).map(|k| (k, 1.)).collect(),
+            Splitting::Strang if n < 2 => (0..n).map(|k| (k, 1.)).collect(),
+            Splitting::Strang => (0..n - 1)
+                .map(|k| (k, 0.5))
+                .chain(std::iter::once((n - 1, 1.)))
+                .chain((0..n - 1).rev().map(|k| (k, 0.5)))
+                .collect(),
+        }
+    }
+}
+
+impl std::str::FromStr for Splitting {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lie" => Ok(Splitting::Lie),
+            "strang" => Ok(Splitting::Strang),
+            _ => Err(format!("{} is not a valid Splitting identifier", s)),
+        }
+    }
+}
+
+/// The type of the RHS of an [`Operator`].
+pub type OperatorFn<Y> = Box<dyn Fn(f64, &Y) -> Y>;
+
+/// An operator `dy/dt = F_k(t, y)` of a split problem together with the solver that
+/// integrates its substeps.
+pub struct Operator<Y> {
+    /// The RHS of the operator.
+    f: OperatorFn<Y>,
+    /// The solver of the substeps.
+    ode: Ode,
+    /// The options of the solver.
+    opts: OdeOptionMap,
+}
+
+/// The problem `dy/dt = F_1(t, y) + ... + F_N(t, y)` whose operators are integrated
+/// separately and composed by a [`Splitting`].
+pub struct SplittingProblem<Y>
+where
+    Y: OdeType,
+{
+    /// The operators `F_k` in the order of the splitting.
+    operators: Vec<Operator<Y>>,
+    /// Initial value.
+    y0: Y,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+pub struct SplittingBuilder<Y>
+where
+    Y: OdeType,
+{
+    operators: Vec<Operator<Y>>,
+    y0: Option<Y>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<Y> Default for SplittingBuilder<Y>
+where
+    Y: OdeType,
+{
+    fn default() -> Self {
+        Self {
+            operators: Vec::new(),
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<Y> SplittingBuilder<Y>
+where
+    Y: OdeType,
+{
+    /// add an operator that is integrated by `ode` with default options
+    pub fn operator<F>(self, f: F, ode: Ode) -> Self
+    where
+        F: Fn(f64, &Y) -> Y + 'static,
+    {
+        self.operator_with_options(f, ode, OdeOptionMap::default())
+    }
+
+    /// add an operator that is integrated by `ode` with the options `opts`
+    pub fn operator_with_options<F>(mut self, f: F, ode: Ode, opts: OdeOptionMap) -> Self
+    where
+        F: Fn(f64, &Y) -> Y + 'static,
+    {
+        self.operators.push(Operator {
+            f: Box::new(f),
+            ode,
+            opts,
+        });
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<Y>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`SplittingProblem`].
+    ///
+    /// Returns an error if a field is None or no operator was added.
+    pub fn build(self) -> Result<SplittingProblem<Y>, OdeError> {
+        if self.operators.is_empty() {
+            return Err(OdeError::uninitialized(
+                "At least one operator must be added",
+            ));
+        }
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+
+        Ok(SplittingProblem {
+            operators: self.operators,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl<Y, T> SplittingProblem<Y>
+where
+    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T> + Into<f64>,
+    Y: OdeType<Item = T>,
+{
+    /// convenience method to create a new builder
+    /// same as `SplittingBuilder::default()`
+    pub fn builder() -> SplittingBuilder<Y> {
+        SplittingBuilder::default()
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` with the given splitting.
+    ///
+    /// Each substep is solved from scratch by the solver of its operator over the
+    /// substep, so the order of the splitting is only attained if the sub-integrators are
+    /// at least as accurate.
+    pub fn solve(&self, splitting: Splitting) -> Result<OdeSolution<f64, Y>, OdeError> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let substeps = splitting.substeps(self.operators.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        yout.push(self.y0.clone());
+
+        for w in self.tspan.windows(2) {
+            let (t, h) = (w[0], w[1] - w[0]);
+            let mut y = yout.last().unwrap().clone();
+            // each operator advances its own time through the step
+            let mut times = vec![t; self.operators.len()];
+            for (k, fraction) in &substeps {
+                let op = &self.operators[*k];
+                let t0 = times[*k];
+                times[*k] += fraction * h;
+                let problem = OdeProblem::builder()
+                    .fun(|t, y: &Y| (op.f)(t, y))
+                    .init(y)
+                    .tspan(vec![t0, times[*k]])
+                    .build()?;
+                y = problem
+                    .solve(op.ode.clone(), op.opts.clone())?
+                    .yout
+                    .pop()
+                    .unwrap();
+            }
+            yout.push(y);
+        }
+
+        Ok(OdeSolution::new(self.tspan.clone(), yout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::expm;
+    use crate::ode::options::{Abstol, OdeOp, Reltol};
+    use na::{DMatrix, DVector};
+
+    #[test]
+    fn strang_substeps() {
+        assert_eq!(vec![(0, 1.), (1, 1.)], Splitting::Lie.substeps(2));
+        assert_eq!(
+            vec![(0, 0.5), (1, 0.5), (2, 1.), (1, 0.5), (0, 0.5)],
+            Splitting::Strang.substeps(3)
+        );
+    }
+
+    #[test]
+    fn splitting_convergence() {
+        // `y' = (A + B) y` with non-commuting `A` and `B`
+        let a = DMatrix::from_row_slice(2, 2, &[0., 1., -1., 0.]);
+        let b = DMatrix::from_row_slice(2, 2, &[-1., 0., 0.5, -0.2]);
+        let y0 = DVector::from_row_slice(&[1., 0.]);
+        let exact = expm::expm(&(&a + &b)).unwrap() * &y0;
+
+        let error = |steps: usize, splitting: Splitting| {
+            let mut ops = OdeOptionMap::default();
+            ops.insert(Reltol::option_name(), Reltol::from(1e-12).into());
+            ops.insert(Abstol::option_name(), Abstol::from(1e-12).into());
+            let (a, b) = (a.clone(), b.clone());
+            let problem = SplittingProblem::builder()
+                .operator_with_options(move |_t, y: &DVector<f64>| &a * y, Ode::Dop853, ops)
+                .operator(move |_t, y: &DVector<f64>| &b * y, Ode::Gauss6)
+                .init(y0.clone())
+                .tspan_linspace(0., 1., steps + 1)
+                .build()
+                .unwrap();
+            let solution = problem.solve(splitting).unwrap();
+            (solution.yout.last().unwrap() - &exact).amax()
+        };
+
+        assert!((error(20, Splitting::Lie) / error(40, Splitting::Lie) - 2.).abs() < 0.2);
+        assert!((error(20, Splitting::Strang) / error(40, Splitting::Strang) - 4.).abs() < 0.2);
+    }
+}
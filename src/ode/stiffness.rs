@@ -0,0 +1,194 @@
+use crate::error::OdeError;
+use crate::ode::coeff::CoefficientPoint;
+use crate::ode::runge_kutta::ButcherTableau;
+use crate::ode::types::OdeType;
+use alga::general::RealField;
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, U1, U2};
+#[cfg(feature = "serde0")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of stiff steps after which the problem appears stiff.
+pub const STIFF_STEPS: usize = 15;
+
+/// Number of consecutive non-stiff steps that reset the count of stiff steps.
+pub const NONSTIFF_STEPS: usize = 6;
+
+/// The fraction of the stability boundary above which a step is stiff, the error control
+/// keeps the steps of a stiff problem just below the boundary.
+pub const BOUNDARY_FRACTION: f64 = 0.95;
+
+/// What the explicit Runge–Kutta solvers do once the problem appears stiff, selectable
+/// with the [`Stiffness`](crate::ode::options::Stiffness) option.
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StiffnessAction {
+    /// Records the time in the solution and continues.
+    #[default]
+    Flag,
+    /// Aborts the integration with
+    /// [`ReturnCode::Stiff`](crate::ode::solution::ReturnCode::Stiff).
+    Abort,
+    /// Skips the detection.
+    Ignore,
+}
+
+impl fmt::Display for StiffnessAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StiffnessAction::Flag => write!(f, "Flag"),
+            StiffnessAction::Abort => write!(f, "Abort"),
+            StiffnessAction::Ignore => write!(f, "Ignore"),
+        }
+    }
+}
+
+impl FromStr for StiffnessAction {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "Flag" => Ok(StiffnessAction::Flag),
+            "Abort" => Ok(StiffnessAction::Abort),
+            "Ignore" => Ok(StiffnessAction::Ignore),
+            _ => Err(OdeError::invalid_option(s, "unknown stiffness action")),
+        }
+    }
+}
+
+/// Detects stiffness from the accepted steps of an explicit Runge–Kutta method as done
+/// by DOPRI5 and DOP853, c.f. Hairer & Wanner, "Solving Ordinary Differential Equations
+/// II", 1996, Sec. IV.2.
+///
+/// The dominant eigenvalue `λ` of the jacobian is estimated from the RHS at the end of a
+/// step and a stage that is evaluated at the same time, `|h λ| ≈ |h| |f(y_1) - k_s| /
+/// |y_1 - Y_s|`. A step counts as stiff if the estimate is close to the boundary of the
+/// stability region of the method on the negative real axis, the problem appears stiff
+/// after [`STIFF_STEPS`] stiff steps that are not interrupted by [`NONSTIFF_STEPS`]
+/// non-stiff steps.
+#[derive(Debug, Clone)]
+pub struct StiffnessDetector {
+    /// the stage that is evaluated at the end of the step
+    stage: usize,
+    /// `|h λ|` above which a step is stiff
+    boundary: f64,
+    /// the number of stiff steps
+    stiff: usize,
+    /// the number of consecutive non-stiff steps
+    nonstiff: usize,
+}
+
+impl StiffnessDetector {
+    /// The detector of an explicit method, `None` if the method is implicit or if no
+    /// stage, besides a last stage that is the same as the next first, is evaluated at
+    /// the end of the step.
+    pub fn from_tableau<S: Dim>(btab: &ButcherTableau<S>) -> Option<Self>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>,
+    {
+        if btab.is_implicit() {
+            return None;
+        }
+        let last = if btab.is_first_same_as_last() {
+            btab.nstages() - 1
+        } else {
+            btab.nstages()
+        };
+        let stage = (0..last).rev().find(|&s| btab.c[s] == 1.)?;
+        Some(Self {
+            stage,
+            boundary: BOUNDARY_FRACTION * btab.stability_boundary(),
+            stiff: 0,
+            nonstiff: 0,
+        })
+    }
+
+    /// The stage that is compared with the RHS at the end of the step.
+    #[inline]
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    /// The estimate of `|h λ|` above which a step is stiff.
+    #[inline]
+    pub fn boundary(&self) -> f64 {
+        self.boundary
+    }
+
+    /// Updates the detector with an accepted step of size `dt` that ends at `y1` with the
+    /// RHS `f1`, `stage` is the stage of the step given by
+    /// [`StiffnessDetector::stage`].
+    ///
+    /// Returns whether the problem appears stiff.
+    pub fn update<Y, T>(&mut self, dt: f64, y1: &Y, f1: &Y, stage: &CoefficientPoint<Y>) -> bool
+    where
+        Y: OdeType<Item = T>,
+        T: RealField + Into<f64>,
+    {
+        let (mut num, mut den) = (0f64, 0f64);
+        for d in 0..y1.dof() {
+            let df: f64 = (f1.get(d) - stage.k.get(d)).into();
+            let dy: f64 = (y1.get(d) - stage.y.get(d)).into();
+            num += df * df;
+            den += dy * dy;
+        }
+        if den > 0. {
+            if dt.abs() * (num / den).sqrt() > self.boundary {
+                self.nonstiff = 0;
+                self.stiff += 1;
+            } else {
+                self.nonstiff += 1;
+                if self.nonstiff == NONSTIFF_STEPS {
+                    self.stiff = 0;
+                }
+            }
+        }
+        self.stiff >= STIFF_STEPS
+    }
+
+    /// Forgets the previous steps.
+    pub fn reset(&mut self) {
+        self.stiff = 0;
+        self.nonstiff = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_tableau() {
+        // the stage before the last
+        let detector = StiffnessDetector::from_tableau(&ButcherTableau::dopri5()).unwrap();
+        assert_eq!(5, detector.stage());
+        assert!((detector.boundary() - 0.95 * 3.307).abs() < 1e-2);
+        let detector = StiffnessDetector::from_tableau(&ButcherTableau::dop853()).unwrap();
+        assert_eq!(11, detector.stage());
+        // no other stage at the end of the step
+        assert!(StiffnessDetector::from_tableau(&ButcherTableau::bs3()).is_none());
+        assert!(StiffnessDetector::from_tableau(&ButcherTableau::sdirk2()).is_none());
+    }
+
+    #[test]
+    fn stiff_steps() {
+        let mut detector = StiffnessDetector::from_tableau(&ButcherTableau::dopri5()).unwrap();
+        // `y' = -1000 y` with steps beyond the boundary of stability
+        let stage = CoefficientPoint::new(-1000. * 0.9, 0.9);
+        let (y1, f1) = (1., -1000.);
+        for _ in 1..STIFF_STEPS {
+            assert!(!detector.update(3.5e-3, &y1, &f1, &stage));
+        }
+        assert!(detector.update(3.5e-3, &y1, &f1, &stage));
+
+        detector.reset();
+        for _ in 0..2 * STIFF_STEPS {
+            assert!(!detector.update(1e-3, &y1, &f1, &stage));
+        }
+    }
+}
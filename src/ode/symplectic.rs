@@ -0,0 +1,384 @@
+use crate::error::OdeError;
+use crate::ode::solution::OdeSolution;
+use na::DVector;
+
+/// The available symplectic methods of [`SecondOrderOdeProblem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symplectic {
+    Verlet,
+    Ruth3,
+    Yoshida4,
+    McLachlan4,
+    Yoshida6,
+}
+
+impl std::str::FromStr for Symplectic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "verlet" => Ok(Symplectic::Verlet),
+            "ruth3" => Ok(Symplectic::Ruth3),
+            "yoshida4" => Ok(Symplectic::Yoshida4),
+            "mclachlan4" => Ok(Symplectic::McLachlan4),
+            "yoshida6" => Ok(Symplectic::Yoshida6),
+            _ => Err(format!("{} is not a valid Symplectic identifier", s)),
+        }
+    }
+}
+
+/// The coefficients of a symplectic splitting method that alternates kicks
+/// `v += b_i h a(q)` and drifts `q += a_i h v`, starting and ending with a kick
+///
+/// ```text
+/// kick b_0, drift a_0, kick b_1, ..., drift a_{s-1}, kick b_s
+/// ```
+#[derive(Clone, Debug)]
+pub struct SymplecticCoeffs {
+    /// the weights `b_i` of the kicks, one more than the drifts
+    pub kick: Vec<f64>,
+    /// the weights `a_i` of the drifts
+    pub drift: Vec<f64>,
+    /// order of the method
+    pub order: usize,
+}
+
+impl SymplecticCoeffs {
+    /// The symmetric composition of velocity Verlet steps of the relative sizes `weights`,
+    /// adjacent kicks are merged.
+    pub fn composition(weights: &[f64], order: usize) -> Self {
+        let mut kick = vec![0.; weights.len() + 1];
+        for (i, w) in weights.iter().enumerate() {
+            kick[i] += w / 2.;
+            kick[i + 1] += w / 2.;
+        }
+        Self {
+            kick,
+            drift: weights.to_vec(),
+            order,
+        }
+    }
+
+    /// The velocity Verlet method of order 2.
+    pub fn verlet() -> Self {
+        Self::composition(&[1.], 2)
+    }
+
+    /// The method of order 3 of Ruth, "A canonical integration technique", 1983.
+    pub fn ruth3() -> Self {
+        Self {
+            kick: vec![0., -1. / 24., 3. / 4., 7. / 24.],
+            drift: vec![1., -2. / 3., 2. / 3.],
+            order: 3,
+        }
+    }
+
+    /// The triple jump of order 4 of Forest & Ruth and Yoshida, "Construction of higher
+    /// order symplectic integrators", 1990.
+    pub fn yoshida4() -> Self {
+        let cbrt2 = 2f64.cbrt();
+        let w1 = 1. / (2. - cbrt2);
+        Self::composition(&[w1, -cbrt2 * w1, w1], 4)
+    }
+
+    /// The five stage composition of order 4 of McLachlan, "On the numerical integration
+    /// of ordinary differential equations by symmetric composition methods", 1995, with
+    /// much smaller error constants than the triple jump.
+    pub fn mclachlan4() -> Self {
+        let (w1, w2) = (0.28, 0.625_466_428_467_67);
+        let w3 = 1. - 2. * (w1 + w2);
+        Self::composition(&[w1, w2, w3, w2, w1], 4)
+    }
+
+    /// The seven stage composition of order 6 of Yoshida, "Construction of higher order
+    /// symplectic integrators", 1990, solution A.
+    pub fn yoshida6() -> Self {
+        let (w1, w2, w3) = (
+            -1.177_679_984_178_87,
+            0.235_573_213_359_357,
+            0.784_513_610_477_560,
+        );
+        let w0 = 1. - 2. * (w1 + w2 + w3);
+        Self::composition(&[w3, w2, w1, w0, w1, w2, w3], 6)
+    }
+
+    /// the number of evaluations of the acceleration per step
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.drift.len()
+    }
+}
+
+impl From<Symplectic> for SymplecticCoeffs {
+    fn from(method: Symplectic) -> Self {
+        match method {
+            Symplectic::Verlet => SymplecticCoeffs::verlet(),
+            Symplectic::Ruth3 => SymplecticCoeffs::ruth3(),
+            Symplectic::Yoshida4 => SymplecticCoeffs::yoshida4(),
+            Symplectic::McLachlan4 => SymplecticCoeffs::mclachlan4(),
+            Symplectic::Yoshida6 => SymplecticCoeffs::yoshida6(),
+        }
+    }
+}
+
+/// The second order problem `d²q/dt² = a(t, q)` of a separable Hamiltonian
+/// `H = |v|²/2 + V(q)` with the acceleration `a = -∇V`.
+///
+/// A: the acceleration, a function of t and the position q(t).
+///
+/// Solutions hold the state `[q; v]` of positions and velocities.
+#[derive(Debug, Clone)]
+pub struct SecondOrderOdeProblem<A>
+where
+    A: Fn(f64, &DVector<f64>) -> DVector<f64>,
+{
+    /// The acceleration `a(t, q)`.
+    acceleration: A,
+    /// Initial position.
+    q0: DVector<f64>,
+    /// Initial velocity.
+    v0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecondOrderOdeBuilder<A>
+where
+    A: Fn(f64, &DVector<f64>) -> DVector<f64>,
+{
+    acceleration: Option<A>,
+    q0: Option<DVector<f64>>,
+    v0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<A> Default for SecondOrderOdeBuilder<A>
+where
+    A: Fn(f64, &DVector<f64>) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            acceleration: None,
+            q0: None,
+            v0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<A> SecondOrderOdeBuilder<A>
+where
+    A: Fn(f64, &DVector<f64>) -> DVector<f64>,
+{
+    /// set the acceleration of the problem
+    pub fn acceleration(mut self, acceleration: A) -> Self {
+        self.acceleration = Some(acceleration);
+        self
+    }
+
+    /// set the initial position and velocity
+    pub fn init<T: Into<DVector<f64>>>(mut self, q0: T, v0: T) -> Self {
+        self.q0 = Some(q0.into());
+        self.v0 = Some(v0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`SecondOrderOdeProblem`].
+    ///
+    /// Returns an error if a field is None or the initial position and velocity differ
+    /// in length.
+    pub fn build(self) -> Result<SecondOrderOdeProblem<A>, OdeError> {
+        let acceleration = self
+            .acceleration
+            .ok_or_else(|| OdeError::uninitialized("Acceleration must be initialized"))?;
+        let q0 = self
+            .q0
+            .ok_or_else(|| OdeError::uninitialized("Initial position must be initialized"))?;
+        let v0 = self
+            .v0
+            .ok_or_else(|| OdeError::uninitialized("Initial velocity must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        if q0.len() != v0.len() {
+            return Err(OdeError::DimensionMismatch {
+                expected: q0.shape(),
+                found: v0.shape(),
+            });
+        }
+
+        Ok(SecondOrderOdeProblem {
+            acceleration,
+            q0,
+            v0,
+            tspan,
+        })
+    }
+}
+
+impl<A> SecondOrderOdeProblem<A>
+where
+    A: Fn(f64, &DVector<f64>) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `SecondOrderOdeBuilder::default()`
+    pub fn builder() -> SecondOrderOdeBuilder<A> {
+        SecondOrderOdeBuilder::default()
+    }
+
+    /// Solve the problem using the velocity Verlet (leapfrog) method at the fixed steps of
+    /// `tspan`
+    ///
+    /// ```latex
+    /// v_{n+1/2} = v_n + h/2 a(t_n, q_n)
+    /// q_{n+1} = q_n + h v_{n+1/2}
+    /// v_{n+1} = v_{n+1/2} + h/2 a(t_{n+1}, q_{n+1})
+    /// ```
+    ///
+    /// The method is symplectic and time reversible of order 2, so the energy error stays
+    /// bounded over long integrations instead of drifting.
+    pub fn velocity_verlet(&self) -> OdeSolution<f64, DVector<f64>> {
+        self.symplectic(&SymplecticCoeffs::verlet())
+    }
+
+    /// Solve the problem using a symplectic splitting method at the fixed steps of
+    /// `tspan`, e.g. `problem.symplectic(&Symplectic::Yoshida6.into())`.
+    pub fn symplectic(&self, coeffs: &SymplecticCoeffs) -> OdeSolution<f64, DVector<f64>> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return OdeSolution::default();
+        }
+
+        let mut q = self.q0.clone();
+        let mut v = self.v0.clone();
+        let mut a = (self.acceleration)(self.tspan[0], &q);
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        yout.push(Self::state(&q, &v));
+
+        for w in self.tspan.windows(2) {
+            let h = w[1] - w[0];
+            let mut tq = w[0];
+            for (kick, drift) in coeffs.kick.iter().zip(&coeffs.drift) {
+                if *kick != 0. {
+                    v += &a * (kick * h);
+                }
+                q += &v * (drift * h);
+                tq += drift * h;
+                // the acceleration after the last drift starts the next step
+                a = (self.acceleration)(tq, &q);
+            }
+            v += &a * (coeffs.kick[coeffs.nstages()] * h);
+            yout.push(Self::state(&q, &v));
+        }
+
+        OdeSolution::new(self.tspan.clone(), yout)
+    }
+
+    /// The state `[q; v]`.
+    fn state(q: &DVector<f64>, v: &DVector<f64>) -> DVector<f64> {
+        DVector::from_iterator(q.len() + v.len(), q.iter().chain(v.iter()).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verlet_pendulum() {
+        // `q'' = -sin(q)` with the energy `v²/2 - cos(q)`
+        let energy = |y: &DVector<f64>| 0.5 * y[1] * y[1] - y[0].cos();
+        let solve = |steps: usize, tend: f64| {
+            SecondOrderOdeProblem::builder()
+                .acceleration(|_t, q: &DVector<f64>| q.map(|q| -q.sin()))
+                .init(DVector::from_element(1, 1.), DVector::from_element(1, 0.))
+                .tspan_linspace(0., tend, steps + 1)
+                .build()
+                .unwrap()
+                .velocity_verlet()
+        };
+
+        // second order at the end point
+        let reference = solve(20000, 10.).yout.pop().unwrap();
+        let error = |steps| (solve(steps, 10.).yout.pop().unwrap() - &reference).amax();
+        assert!((error(100) / error(200) - 4.).abs() < 0.2);
+
+        // the energy error oscillates with `O(h²)` but does not drift over 10^5 steps
+        let solution = solve(100_000, 10_000.);
+        let e0 = energy(&solution.yout[0]);
+        let energy_error =
+            |ys: &[DVector<f64>]| ys.iter().map(|y| (energy(y) - e0).abs()).fold(0., f64::max);
+        let first_periods = energy_error(&solution.yout[..1000]);
+        assert!(first_periods < 2e-3);
+        assert!(energy_error(&solution.yout) < 1.01 * first_periods);
+    }
+
+    #[test]
+    fn symplectic_convergence() {
+        let solve = |coeffs: &SymplecticCoeffs, steps: usize| {
+            SecondOrderOdeProblem::builder()
+                .acceleration(|_t, q: &DVector<f64>| q.map(|q| -q.sin()))
+                .init(DVector::from_element(1, 1.), DVector::from_element(1, 0.))
+                .tspan_linspace(0., 10., steps + 1)
+                .build()
+                .unwrap()
+                .symplectic(coeffs)
+                .yout
+                .pop()
+                .unwrap()
+        };
+        let reference = solve(&SymplecticCoeffs::yoshida6(), 5000);
+        for method in ["ruth3", "yoshida4", "mclachlan4", "yoshida6"] {
+            let coeffs: SymplecticCoeffs = method.parse::<Symplectic>().unwrap().into();
+            let error = |steps| (solve(&coeffs, steps) - &reference).amax();
+            assert!(error(50) / error(100) > 0.9 * 2f64.powi(coeffs.order as i32));
+        }
+    }
+
+    #[test]
+    fn kepler_energy_drift() {
+        // kepler problem with eccentricity 0.5 over 1000 periods of length `2π`
+        let energy = |y: &DVector<f64>| 0.5 * (y[2] * y[2] + y[3] * y[3]) - 1. / y[0].hypot(y[1]);
+        let problem = SecondOrderOdeProblem::builder()
+            .acceleration(|_t, q: &DVector<f64>| -q / q.norm().powi(3))
+            .init(
+                DVector::from_row_slice(&[0.5, 0.]),
+                DVector::from_row_slice(&[0., 3f64.sqrt()]),
+            )
+            .tspan_linspace(0., 2000. * std::f64::consts::PI, 200_001)
+            .build()
+            .unwrap();
+        for method in [
+            Symplectic::Verlet,
+            Symplectic::Ruth3,
+            Symplectic::McLachlan4,
+            Symplectic::Yoshida6,
+        ] {
+            let solution = problem.symplectic(&method.into());
+            let e0 = energy(&solution.yout[0]);
+            let energy_error =
+                |ys: &[DVector<f64>]| ys.iter().map(|y| (energy(y) - e0).abs()).fold(0., f64::max);
+            // the energy error of the first 10 periods doesn't grow over all periods
+            let first_periods = energy_error(&solution.yout[..2000]);
+            assert!(first_periods < 1e-2, "{:?}", method);
+            assert!(
+                energy_error(&solution.yout) <= 10. * first_periods,
+                "{:?}",
+                method
+            );
+        }
+    }
+}
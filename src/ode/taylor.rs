@@ -0,0 +1,273 @@
+use crate::error::OdeError;
+use crate::ode::jet::Jet;
+use crate::ode::options::{AdaptiveOptions, Points, TaylorOptions};
+use crate::ode::solution::{OdeSolution, ReturnCode};
+use num_traits::{abs, signum};
+
+/// Lowest order of the Taylor method.
+pub const MIN_ORDER: usize = 2;
+
+/// Highest order of the Taylor method.
+pub const MAX_ORDER: usize = 40;
+
+/// The problem `dy/dt = F(t, y)` with the RHS written in terms of [`Jet`]s, so the
+/// Taylor coefficients of the solution can be computed by jet transport.
+///
+/// F: the RHS, a function of the jets of `t` and `y` that returns the jets of `dy/dt`.
+/// Constants in `F` are plain `f64`, e.g. `|t, y| vec![y[1].clone(), -&y[0] * t]`.
+#[derive(Debug, Clone)]
+pub struct TaylorProblem<F>
+where
+    F: Fn(&Jet, &[Jet]) -> Vec<Jet>,
+{
+    /// The RHS of the ODE `dy/dt = F(t,y)`.
+    f: F,
+    /// Initial value.
+    y0: Vec<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaylorBuilder<F>
+where
+    F: Fn(&Jet, &[Jet]) -> Vec<Jet>,
+{
+    f: Option<F>,
+    y0: Option<Vec<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F> Default for TaylorBuilder<F>
+where
+    F: Fn(&Jet, &[Jet]) -> Vec<Jet>,
+{
+    fn default() -> Self {
+        Self {
+            f: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F> TaylorBuilder<F>
+where
+    F: Fn(&Jet, &[Jet]) -> Vec<Jet>,
+{
+    /// set the problem function
+    pub fn fun(mut self, f: F) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<Vec<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`TaylorProblem`].
+    ///
+    /// Returns an error if a field is None.
+    pub fn build(self) -> Result<TaylorProblem<F>, OdeError> {
+        let f = self
+            .f
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+
+        Ok(TaylorProblem { f, y0, tspan })
+    }
+}
+
+impl<F> TaylorProblem<F>
+where
+    F: Fn(&Jet, &[Jet]) -> Vec<Jet>,
+{
+    /// convenience method to create a new builder
+    /// same as `TaylorBuilder::default()`
+    pub fn builder() -> TaylorBuilder<F> {
+        TaylorBuilder::default()
+    }
+
+    /// The Taylor coefficients of order `0..=order` of the solution through `(t, y)`.
+    ///
+    /// The coefficient `k + 1` follows from the coefficient `k` of the RHS evaluated on
+    /// the jets of the coefficients `0..=k`, `y_{k+1} = F_k / (k + 1)`.
+    pub fn coefficients(&self, t: f64, y: &[f64], order: usize) -> Vec<Jet> {
+        let mut coeffs: Vec<Vec<f64>> = y.iter().map(|yi| vec![*yi]).collect();
+        for k in 0..order {
+            let tj = Jet::variable(t, k + 1);
+            let yj: Vec<Jet> = coeffs.iter().map(|c| Jet::new(c.clone())).collect();
+            let dy = (self.f)(&tj, &yj);
+            for (c, d) in coeffs.iter_mut().zip(&dy) {
+                c.push(d[k] / (k + 1) as f64);
+            }
+        }
+        coeffs.into_iter().map(Jet::new).collect()
+    }
+
+    /// Solve the problem with the Taylor method of adaptive step size.
+    ///
+    /// The order is chosen from the tolerance as `-ln(tol) / 2 + 1`, capped by
+    /// [`MaxOrder`](crate::ode::options::MaxOrder) if given, and the step size from the
+    /// last two coefficients, c.f. Jorba & Zou, "A software package for the numerical
+    /// integration of ODEs by means of high-order Taylor methods", 2005. Output inside
+    /// a step is given by the Taylor polynomial of the step.
+    pub fn taylor(&self, opts: TaylorOptions) -> Result<OdeSolution<f64, Vec<f64>>, OdeError> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let mut t = self.tspan[0];
+        let tend = self.tspan[self.tspan.len() - 1];
+        let tdir = signum(tend - t);
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.len())?;
+        let minstep = opts
+            .minstep
+            .map_or_else(|| abs(tend - t) / 1e18, |step| step.0);
+        let mut retcode = ReturnCode::Success;
+        let maxstep = opts.maxstep.map_or(f64::INFINITY, |step| step.0);
+
+        let tol = reltol.min().min(abstol.min());
+        let order = ((-tol.ln() / 2.).ceil() as usize + 1)
+            .min(opts.max_order.map_or(MAX_ORDER, |order| order.0))
+            .clamp(MIN_ORDER, MAX_ORDER);
+        let safety = (-0.7 / (order - 1) as f64).exp();
+
+        let mut y = self.y0.clone();
+        let mut tout: Vec<f64> = Vec::with_capacity(self.tspan.len());
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        if opts.points != Points::Last {
+            tout.push(t);
+            yout.push(y.clone());
+        }
+        let mut iter_fixed = 1usize;
+        let mut steps = 0;
+        // whether the latest stored step is only kept as the final state
+        let mut provisional = false;
+
+        let mut iters = 0;
+        while tdir * (tend - t) > 0. {
+            if iters >= *opts.maxiters {
+                retcode = ReturnCode::MaxIters;
+                break;
+            }
+            iters += 1;
+            let series = self.coefficients(t, &y, order);
+
+            // the step size that makes the last two terms of the series negligible
+            let ynorm = y.iter().fold(0f64, |acc, yi| acc.max(yi.abs()));
+            let eps = |d: usize| abstol.at(d).max(reltol.at(d) * ynorm);
+            let h_abs = [order - 1, order]
+                .iter()
+                .map(|k| {
+                    let norm = series
+                        .iter()
+                        .enumerate()
+                        .fold(0f64, |acc, (d, s)| acc.max(s[*k].abs() / eps(d)));
+                    (1. / norm).powf(1. / *k as f64)
+                })
+                .fold(f64::INFINITY, f64::min)
+                * safety;
+            let h_abs = h_abs.min(maxstep).min(abs(tend - t));
+            if h_abs < minstep {
+                // minimum step size reached
+                retcode = ReturnCode::DtLessThanMin;
+                break;
+            }
+
+            let t_old = t;
+            t = if h_abs == abs(tend - t) {
+                tend
+            } else {
+                t + h_abs * tdir
+            };
+            y = series.iter().map(|s| s.eval(t - t_old)).collect();
+
+            let interp = |tquery: f64| -> Vec<f64> {
+                series.iter().map(|s| s.eval(tquery - t_old)).collect()
+            };
+            if Points::Specified == opts.points {
+                while iter_fixed < self.tspan.len() && tdir * self.tspan[iter_fixed] <= tdir * t {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+            } else {
+                // store at all new times which are < t
+                while opts.points.saves_tspan()
+                    && iter_fixed < self.tspan.len()
+                    && tdir * t_old < tdir * self.tspan[iter_fixed]
+                    && tdir * self.tspan[iter_fixed] < tdir * t
+                {
+                    yout.push(interp(self.tspan[iter_fixed]));
+                    tout.push(self.tspan[iter_fixed]);
+                    iter_fixed += 1;
+                }
+                // also store the steps taken, the sparse output modes only keep the latest
+                // one until it is superseded
+                if provisional {
+                    yout.pop();
+                    tout.pop();
+                }
+                steps += 1;
+                provisional = !opts.points.saves_step(steps);
+                yout.push(y.clone());
+                tout.push(t);
+            }
+        }
+
+        Ok(OdeSolution::new(tout, yout).with_retcode(retcode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Reltol};
+
+    #[test]
+    fn kepler_energy() {
+        let ops = TaylorOptions {
+            reltol: Reltol::from(1e-15),
+            abstol: Abstol::from(1e-15),
+            ..Default::default()
+        };
+        // kepler problem with eccentricity 0.5 over 100 periods of length `2π`
+        let problem = TaylorProblem::builder()
+            .tspan_linspace(0., 200. * std::f64::consts::PI, 2)
+            .fun(|_t, y: &[Jet]| {
+                let r3 = (&y[0] * &y[0] + &y[1] * &y[1]).powf(1.5);
+                vec![y[2].clone(), y[3].clone(), -&y[0] / &r3, -&y[1] / &r3]
+            })
+            .init(vec![0.5, 0., 0., 3f64.sqrt()])
+            .build()
+            .unwrap();
+        let energy = |y: &[f64]| 0.5 * (y[2] * y[2] + y[3] * y[3]) - 1. / y[0].hypot(y[1]);
+
+        let solution = problem.taylor(ops).unwrap();
+        let y = solution.yout.last().unwrap();
+        assert!((energy(y) - energy(&problem.y0)).abs() < 1e-12);
+        assert!((y[0] - 0.5).abs() < 1e-8 && y[1].abs() < 1e-8);
+    }
+}
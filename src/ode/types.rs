@@ -1,10 +1,23 @@
+use crate::error::OdeError;
+use crate::ode::dual::Dual;
+use crate::ode::options::Tolerance;
 use alga::general::RealField;
-use na::{allocator::Allocator, ComplexField, DefaultAllocator, Dim, VectorN};
+use na::{allocator::Allocator, Complex, ComplexField, DefaultAllocator, Dim, MatrixMN};
+#[cfg(feature = "ndarray")]
+use ndarray::{Array, Dimension};
 use num_traits::identities::{One, Zero};
+#[cfg(feature = "serde0")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
 
-use std::ops::{Add, Mul};
+/// Derives [`OdeType`] for structs of scalar fields, re-exported from `diffeq-derive`.
+pub use diffeq_derive::OdeType;
 
+use std::ops::{Add, AddAssign, Mul};
+
+#[cfg_attr(feature = "serde0", derive(Serialize, Deserialize))]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PNorm {
     P(usize),
@@ -18,13 +31,28 @@ impl Default for PNorm {
     }
 }
 
+/// Formats the `p` of the norm, `Inf` and `-Inf` for the infinity norms.
 impl fmt::Display for PNorm {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "norm(A, p=")?;
         match self {
-            PNorm::P(p) => write!(f, "{})", p),
-            PNorm::InfPos => write!(f, "Inf)"),
-            PNorm::InfNeg => write!(f, "-Inf)"),
+            PNorm::P(p) => write!(f, "{}", p),
+            PNorm::InfPos => write!(f, "Inf"),
+            PNorm::InfNeg => write!(f, "-Inf"),
+        }
+    }
+}
+
+impl FromStr for PNorm {
+    type Err = OdeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "Inf" => Ok(PNorm::InfPos),
+            "-Inf" => Ok(PNorm::InfNeg),
+            p => p
+                .parse()
+                .map(PNorm::P)
+                .map_err(|_| OdeError::invalid_option(s, "expected `p`, `Inf` or `-Inf`")),
         }
     }
 }
@@ -40,9 +68,7 @@ pub trait OdeType: Clone + std::fmt::Debug {
 
     #[inline]
     fn fill(&mut self, item: Self::Item) {
-        for i in 0..self.dof() {
-            self.insert(i, item.clone());
-        }
+        self.map_inplace(|_| item);
     }
 
     /// degree of freedom
@@ -50,34 +76,117 @@ pub trait OdeType: Clone + std::fmt::Debug {
 
     fn get(&self, index: usize) -> Self::Item;
 
+    /// The component at `index`, distinct indices must refer to distinct components,
+    /// which [`OdeType::ode_iter_mut`] relies on.
     fn get_mut(&mut self, index: usize) -> &mut Self::Item;
 
     fn insert(&mut self, index: usize, item: Self::Item);
 
     #[inline]
     fn sum(mut self, other: &Self) -> Self {
-        for i in 0..self.dof() {
-            *self.get_mut(i) += other.get(i);
-        }
+        self.sum_mut(other);
         self
     }
 
     #[inline]
     fn sum_mut(&mut self, other: &Self) -> &mut Self {
-        for i in 0..self.dof() {
-            *self.get_mut(i) += other.get(i);
-        }
+        self.zip_mut_with(other, |item, rhs| *item += rhs);
         self
     }
 
     #[inline]
-    fn ode_iter(&self) -> OdeTypeIterator<Self> {
+    fn ode_iter(&self) -> OdeTypeIterator<'_, Self> {
         OdeTypeIterator {
             index: 0,
             ode_ty: self,
         }
     }
 
+    /// Iterates over mutable references to the components.
+    #[inline]
+    fn ode_iter_mut(&mut self) -> OdeTypeIterMut<'_, Self> {
+        OdeTypeIterMut {
+            index: 0,
+            dof: self.dof(),
+            ode_ty: self,
+            marker: PhantomData,
+        }
+    }
+
+    /// Replaces every component by `f` of it.
+    #[inline]
+    fn map_inplace<F: FnMut(Self::Item) -> Self::Item>(&mut self, mut f: F) {
+        for item in self.ode_iter_mut() {
+            *item = f(*item);
+        }
+    }
+
+    /// Updates every component with `f` of it and the component of `other` at the same
+    /// index.
+    #[inline]
+    fn zip_mut_with<F: FnMut(&mut Self::Item, Self::Item)>(&mut self, other: &Self, mut f: F) {
+        for (item, rhs) in self.ode_iter_mut().zip(other.ode_iter()) {
+            f(item, rhs);
+        }
+    }
+
+    /// The magnitude the error of the component at `index` is measured relative to, the
+    /// modulus of the complex number it is a part of, e.g.
+    #[inline]
+    fn modulus_at(&self, index: usize) -> Self::Item {
+        self.get(index).abs()
+    }
+
+    /// Adds `a * x` to the state.
+    #[inline]
+    fn axpy(&mut self, a: f64, x: &Self) {
+        self.zip_mut_with(x, |item, x| *item += x * a);
+    }
+
+    /// Multiplies the state by `a`.
+    #[inline]
+    fn scale(&mut self, a: f64) {
+        self.map_inplace(|item| item * a);
+    }
+
+    /// Sets the state to the linear combination `c_1 x_1 + ... + c_n x_n` of the
+    /// `terms` `(c_i, x_i)`, zero if there are none.
+    #[inline]
+    fn linear_combination(&mut self, terms: &[(f64, &Self)]) {
+        self.set_zero();
+        for (c, x) in terms {
+            self.axpy(*c, x);
+        }
+    }
+
+    /// The weighted root-mean-square norm of the error estimate `self` of a step from
+    /// `y_old` to `y_new`, c.f. Hairer, Norsett & Wanner, "Solving Ordinary Differential
+    /// Equations I", 1993, eq. (4.11):
+    ///
+    /// ```latex
+    ///   err = sqrt(1/n ∑i (erri / sci)^2),  sci = atoli + max(|y_oldi|, |y_newi|) rtoli
+    /// ```
+    ///
+    /// A step is acceptable if the norm does not exceed 1.
+    fn wrms_norm(
+        &self,
+        y_old: &Self,
+        y_new: &Self,
+        abstol: &Tolerance,
+        reltol: &Tolerance,
+    ) -> Self::Item {
+        let n = self.dof();
+        if n == 0 {
+            return Self::Item::zero();
+        }
+        let sum = (0..n).fold(Self::Item::zero(), |sum, i| {
+            let magnitude = y_old.modulus_at(i).max(y_new.modulus_at(i));
+            let sc = magnitude * reltol.at(i) + abstol.at(i);
+            sum + (self.get(i) / sc).powi(2)
+        });
+        (sum * (1. / n as f64)).sqrt()
+    }
+
     /// Compute the p-norm of the OdeIterable.
     ///
     /// The p-norm is defined as:
@@ -135,58 +244,312 @@ impl<'a, T: OdeType> Iterator for OdeTypeIterator<'a, T> {
     }
 }
 
-impl<T, D: Dim> OdeType for VectorN<T, D>
+/// Iterator over mutable references to the components of an [`OdeType`], c.f.
+/// [`OdeType::ode_iter_mut`].
+pub struct OdeTypeIterMut<'a, T: OdeType> {
+    index: usize,
+    dof: usize,
+    ode_ty: *mut T,
+    marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: OdeType> Iterator for OdeTypeIterMut<'a, T> {
+    type Item = &'a mut T::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.dof {
+            // SAFETY: the iterator borrows the state mutably for `'a` and yields every
+            // index once, whose components are distinct by the contract of `get_mut`
+            let next = unsafe { (*self.ode_ty).get_mut(self.index) as *mut T::Item };
+            self.index += 1;
+            Some(unsafe { &mut *next })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.dof - self.index;
+        (len, Some(len))
+    }
+}
+
+#[inline]
+fn axpy_slice<T: Copy + AddAssign + Mul<f64, Output = T>>(y: &mut [T], a: f64, x: &[T]) {
+    for (y, x) in y.iter_mut().zip(x) {
+        *y += *x * a;
+    }
+}
+
+#[inline]
+fn scale_slice<T: Copy + Mul<f64, Output = T>>(y: &mut [T], a: f64) {
+    for y in y.iter_mut() {
+        *y = *y * a;
+    }
+}
+
+/// The bulk operations of an [`OdeType`] with contiguous components, on their slices.
+macro_rules! impl_slice_ops {
+    () => {
+        #[inline]
+        fn axpy(&mut self, a: f64, x: &Self) {
+            axpy_slice(self.as_mut_slice(), a, x.as_slice());
+        }
+
+        #[inline]
+        fn scale(&mut self, a: f64) {
+            scale_slice(self.as_mut_slice(), a);
+        }
+
+        fn linear_combination(&mut self, terms: &[(f64, &Self)]) {
+            match terms.split_first() {
+                Some(((c, x), rest)) => {
+                    // start from the first term instead of zeroing the state
+                    self.clone_from(x);
+                    self.scale(*c);
+                    for (c, x) in rest {
+                        self.axpy(*c, x);
+                    }
+                }
+                None => self.set_zero(),
+            }
+        }
+    };
+}
+
+/// The scalar components of the vectors, matrices and arrays an [`OdeType`] is made of.
+///
+/// A scalar consists of [`OdeScalar::PARTS`] real parts, which are the components of the
+/// state the solvers work with, e.g. the real and imaginary part of a complex number.
+pub trait OdeScalar: na::Scalar + Zero + AddAssign + Mul<f64, Output = Self> + fmt::Debug {
+    type Real: RealField + Add<f64, Output = Self::Real> + Mul<f64, Output = Self::Real>;
+
+    /// The number of real parts.
+    const PARTS: usize;
+
+    fn part(&self, index: usize) -> Self::Real;
+
+    fn part_mut(&mut self, index: usize) -> &mut Self::Real;
+
+    /// The magnitude of the scalar, e.g. the modulus of a complex number.
+    fn modulus(&self) -> Self::Real;
+}
+
+macro_rules! impl_ode_scalar {
+    ($($ty:ident),*) => {
+        $(impl OdeScalar for $ty {
+            type Real = $ty;
+
+            const PARTS: usize = 1;
+
+            #[inline]
+            fn part(&self, _index: usize) -> Self::Real {
+                *self
+            }
+
+            #[inline]
+            fn part_mut(&mut self, _index: usize) -> &mut Self::Real {
+                self
+            }
+
+            #[inline]
+            fn modulus(&self) -> Self::Real {
+                self.abs()
+            }
+        })*
+    };
+}
+
+impl_ode_scalar!(f64, Dual);
+
+/// Complex numbers are made of their real part followed by their imaginary part.
+impl OdeScalar for Complex<f64> {
+    type Real = f64;
+
+    const PARTS: usize = 2;
+
+    #[inline]
+    fn part(&self, index: usize) -> f64 {
+        if index == 0 {
+            self.re
+        } else {
+            self.im
+        }
+    }
+
+    #[inline]
+    fn part_mut(&mut self, index: usize) -> &mut f64 {
+        if index == 0 {
+            &mut self.re
+        } else {
+            &mut self.im
+        }
+    }
+
+    #[inline]
+    fn modulus(&self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+/// The accessors of an [`OdeType`] of [`OdeScalar`]s indexed by `usize`, whose
+/// components are the parts of the scalars in order.
+macro_rules! impl_scalar_access {
+    () => {
+        #[inline]
+        fn get(&self, index: usize) -> Self::Item {
+            self[index / T::PARTS].part(index % T::PARTS)
+        }
+
+        #[inline]
+        fn get_mut(&mut self, index: usize) -> &mut Self::Item {
+            self[index / T::PARTS].part_mut(index % T::PARTS)
+        }
+
+        #[inline]
+        fn insert(&mut self, index: usize, item: Self::Item) {
+            *self[index / T::PARTS].part_mut(index % T::PARTS) = item;
+        }
+
+        #[inline]
+        fn modulus_at(&self, index: usize) -> Self::Item {
+            self[index / T::PARTS].modulus()
+        }
+    };
+}
+
+/// Vectors and matrices of any static or dynamic size, matrices are indexed in their
+/// column major order.
+impl<T: OdeScalar, R: Dim, C: Dim> OdeType for MatrixMN<T, R, C>
 where
-    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T>,
-    DefaultAllocator: Allocator<T, D>,
+    DefaultAllocator: Allocator<T, R, C>,
 {
-    type Item = T;
+    type Item = T::Real;
 
     #[inline]
     fn dof(&self) -> usize {
-        self.nrows()
+        self.len() * T::PARTS
+    }
+
+    impl_scalar_access!();
+    impl_slice_ops!();
+}
+
+impl<T: OdeScalar> OdeType for Vec<T> {
+    type Item = T::Real;
+
+    #[inline]
+    fn dof(&self) -> usize {
+        self.len() * T::PARTS
+    }
+
+    impl_scalar_access!();
+    impl_slice_ops!();
+}
+
+/// Fixed-size states of `N` components, which integrate without heap allocations of
+/// the state itself.
+impl<T: OdeScalar, const N: usize> OdeType for [T; N] {
+    type Item = T::Real;
+
+    #[inline]
+    fn dof(&self) -> usize {
+        N * T::PARTS
+    }
+
+    #[inline]
+    fn set_zero(&mut self) {
+        *self = [T::zero(); N];
+    }
+
+    impl_scalar_access!();
+    impl_slice_ops!();
+}
+
+/// `ndarray` arrays of any dimension, indexed in their logical (row major) order.
+#[cfg(feature = "ndarray")]
+impl<T: OdeScalar, D: Dimension> OdeType for Array<T, D> {
+    type Item = T::Real;
+
+    #[inline]
+    fn dof(&self) -> usize {
+        self.len() * T::PARTS
     }
 
     #[inline]
     fn get(&self, index: usize) -> Self::Item {
-        self[index]
+        let scalar = match self.as_slice() {
+            Some(slice) => &slice[index / T::PARTS],
+            None => self
+                .iter()
+                .nth(index / T::PARTS)
+                .expect("index out of bounds"),
+        };
+        scalar.part(index % T::PARTS)
     }
 
     #[inline]
     fn get_mut(&mut self, index: usize) -> &mut Self::Item {
-        &mut self[index]
+        let scalar = if self.is_standard_layout() {
+            &mut self.as_slice_mut().unwrap()[index / T::PARTS]
+        } else {
+            self.iter_mut()
+                .nth(index / T::PARTS)
+                .expect("index out of bounds")
+        };
+        scalar.part_mut(index % T::PARTS)
     }
 
     #[inline]
     fn insert(&mut self, index: usize, item: Self::Item) {
-        self[index] = item;
+        *OdeType::get_mut(self, index) = item;
+    }
+
+    #[inline]
+    fn modulus_at(&self, index: usize) -> Self::Item {
+        let scalar = match self.as_slice() {
+            Some(slice) => &slice[index / T::PARTS],
+            None => self
+                .iter()
+                .nth(index / T::PARTS)
+                .expect("index out of bounds"),
+        };
+        scalar.modulus()
+    }
+
+    #[inline]
+    fn set_zero(&mut self) {
+        self.fill(T::zero());
     }
 }
 
-impl<T> OdeType for Vec<T>
-where
-    T: RealField + Add<f64, Output = T> + Mul<f64, Output = T>,
-{
-    type Item = T;
+/// A single complex number, c.f. [`OdeScalar`].
+impl OdeType for Complex<f64> {
+    type Item = f64;
 
     #[inline]
     fn dof(&self) -> usize {
-        self.len()
+        2
     }
 
     #[inline]
     fn get(&self, index: usize) -> Self::Item {
-        self[index]
+        self.part(index)
     }
 
     #[inline]
     fn get_mut(&mut self, index: usize) -> &mut Self::Item {
-        &mut self[index]
+        self.part_mut(index)
     }
 
     #[inline]
     fn insert(&mut self, index: usize, item: Self::Item) {
-        self[index] = item;
+        *self.part_mut(index) = item;
+    }
+
+    #[inline]
+    fn modulus_at(&self, _index: usize) -> Self::Item {
+        self.modulus()
     }
 }
 
@@ -258,7 +621,7 @@ macro_rules! impl_ode_tuple {
     };
 }
 
-impl_ode_ty!(f64);
+impl_ode_ty!(f64, Dual);
 //impl_ode_ty!(f64, f32);
 impl_ode_tuple!([(f64, f64) => 2;f64;0,1]);
 //impl_ode_tuple!([(f32, f32) => 2;f32;0,1]);
@@ -298,4 +661,158 @@ mod tests {
             t6.ode_iter().collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn ode_array() {
+        let mut a = [0., 1., 2., 3.];
+        assert_eq!(4, a.dof());
+        a.insert(0, 2.);
+        *a.get_mut(3) += 1.;
+        assert_eq!(vec![2., 1., 2., 4.], a.ode_iter().collect::<Vec<_>>());
+        assert_eq!([4., 2., 4., 8.], a.sum(&a));
+        assert_eq!(4., a.pnorm(PNorm::InfPos));
+        a.set_zero();
+        assert_eq!([0.; 4], a);
+    }
+
+    #[derive(Debug, Clone, PartialEq, OdeType)]
+    struct State {
+        x: f64,
+        v: f64,
+        m: f64,
+    }
+
+    #[derive(Debug, Clone, OdeType)]
+    struct Pair(Dual, Dual);
+
+    #[test]
+    fn ode_derive() {
+        let mut state = State {
+            x: 1.,
+            v: 2.,
+            m: 3.,
+        };
+        assert_eq!(3, state.dof());
+        assert_eq!(2., state.get(1));
+        state.insert(2, 4.);
+        *state.get_mut(0) += 1.;
+        assert_eq!(vec![2., 2., 4.], state.ode_iter().collect::<Vec<_>>());
+        state.set_zero();
+        assert_eq!(
+            State {
+                x: 0.,
+                v: 0.,
+                m: 0.
+            },
+            state
+        );
+
+        let pair = Pair(Dual::constant(1.), Dual::variable(2.));
+        assert_eq!(2, pair.dof());
+        assert_eq!(1., pair.get(1).derivative());
+    }
+
+    #[test]
+    fn ode_iter_mut() {
+        let mut t3 = (1., 2., 3.);
+        for (i, item) in t3.ode_iter_mut().enumerate() {
+            *item += i as f64;
+        }
+        assert_eq!((1., 3., 5.), t3);
+        t3.map_inplace(|x| x * 2.);
+        assert_eq!((2., 6., 10.), t3);
+        t3.zip_mut_with(&(1., 1., 2.), |x, y| *x /= y);
+        assert_eq!((2., 6., 5.), t3);
+
+        let mut v = vec![1., 2.];
+        let refs: Vec<_> = v.ode_iter_mut().collect();
+        assert_eq!(2, refs.len());
+        for item in refs {
+            *item = -*item;
+        }
+        assert_eq!(vec![-1., -2.], v);
+    }
+
+    #[test]
+    fn ode_complex() {
+        let mut v = vec![Complex::new(1., 2.), Complex::new(-3., 4.)];
+        assert_eq!(4, v.dof());
+        assert_eq!(vec![1., 2., -3., 4.], v.ode_iter().collect::<Vec<_>>());
+        OdeType::insert(&mut v, 3, 0.);
+        *v.get_mut(0) = 3.;
+        assert_eq!(vec![Complex::new(3., 2.), Complex::new(-3., 0.)], v);
+        // both parts are measured relative to the modulus
+        let m = na::DVector::from_vec(vec![Complex::new(3., 4.)]);
+        assert_eq!(5., m.modulus_at(0));
+        assert_eq!(5., m.modulus_at(1));
+        assert_eq!(5., Complex::new(0., 5.).modulus_at(0));
+        let mut a = [Complex::new(1., 1.); 2];
+        a.axpy(2., &[Complex::new(0., 1.); 2]);
+        assert_eq!([Complex::new(1., 3.); 2], a);
+    }
+
+    #[test]
+    fn wrms_norm() {
+        use crate::ode::options::Tolerance;
+        let (abstol, reltol) = (Tolerance::Scalar(1.), Tolerance::Vector(vec![0., 1.]));
+        // the scales are `1` and `1 + max(|-1|, 3)`, so the scaled errors are `3` and `2`
+        let err = vec![3., 8.];
+        let norm = err.wrms_norm(&vec![2., -1.], &vec![0., 3.], &abstol, &reltol);
+        assert!((norm - 6.5f64.sqrt()).abs() < 1e-14);
+        assert_eq!(
+            0.,
+            Vec::<f64>::new().wrms_norm(&vec![], &vec![], &abstol, &reltol)
+        );
+    }
+
+    #[test]
+    fn linear_algebra() {
+        let x = (1., 2.);
+        let mut y = (3., 4.);
+        y.axpy(2., &x);
+        assert_eq!((5., 8.), y);
+        y.scale(0.5);
+        assert_eq!((2.5, 4.), y);
+        y.linear_combination(&[(1., &x), (-1., &(0., 1.))]);
+        assert_eq!((1., 1.), y);
+
+        // the slice specializations agree with the defaults
+        let mut v = vec![3., 4.];
+        v.axpy(2., &vec![1., 2.]);
+        v.scale(0.5);
+        assert_eq!(vec![2.5, 4.], v);
+        v.linear_combination(&[(1., &vec![1., 2.]), (-1., &vec![0., 1.])]);
+        assert_eq!(vec![1., 1.], v);
+        let mut m = na::DVector::from_vec(vec![1., 2.]);
+        m.linear_combination(&[(2., &m.clone()), (1., &na::DVector::from_vec(vec![1., 0.]))]);
+        assert_eq!(na::DVector::from_vec(vec![3., 4.]), m);
+        let mut a = [1., 2.];
+        a.linear_combination(&[]);
+        assert_eq!([0., 0.], a);
+    }
+
+    #[test]
+    fn ode_matrix() {
+        let mut m = na::Matrix2::new(1., 2., 3., 4.);
+        assert_eq!(4, m.dof());
+        m.insert(1, 5.);
+        assert_eq!(vec![1., 5., 2., 4.], m.ode_iter().collect::<Vec<_>>());
+        assert_eq!(5., m[(1, 0)]);
+        let d = na::DMatrix::from_element(2, 3, 1.);
+        assert_eq!(6., d.pnorm(PNorm::P(1)));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ode_ndarray() {
+        let mut a = ndarray::arr2(&[[1., 2.], [3., 4.]]);
+        assert_eq!(4, a.dof());
+        a.insert(1, 5.);
+        assert_eq!(vec![1., 5., 3., 4.], a.ode_iter().collect::<Vec<_>>());
+        // transposed arrays are still indexed in their logical order
+        let mut t = a.reversed_axes();
+        assert_eq!(vec![1., 3., 5., 4.], t.ode_iter().collect::<Vec<_>>());
+        *OdeType::get_mut(&mut t, 1) = 6.;
+        assert_eq!(6., t[[0, 1]]);
+    }
 }
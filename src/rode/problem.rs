@@ -0,0 +1,296 @@
+use crate::error::OdeError;
+use crate::ode::options::{AdaptiveOptions, BasicOptions, Points};
+use crate::ode::problem::hinit;
+use crate::ode::runge_kutta::ButcherTableau;
+use crate::ode::solution::{OdeSolution, ReturnCode};
+use crate::sde::noise::NoisePath;
+use na::allocator::Allocator;
+use na::{DVector, DefaultAllocator, Dim, Dynamic, U1, U2};
+use rand::Rng;
+use std::cell::RefCell;
+
+/// The random differential equation `dy/dt = f(t, y, W(t))`, an ODE driven by a path
+/// `W` of a stochastic process.
+///
+/// F: the RHS, a function of t, y(t) and the noise W(t).
+#[derive(Debug, Clone)]
+pub struct RodeProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// The RHS `f(t, y, W(t))`.
+    f: F,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RodeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    f: Option<F>,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F> Default for RodeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    fn default() -> Self {
+        Self {
+            f: None,
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F> RodeBuilder<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// set the problem function
+    pub fn fun(mut self, f: F) -> Self {
+        self.f = Some(f);
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`RodeProblem`].
+    ///
+    /// Returns an error if a field is None.
+    pub fn build(self) -> Result<RodeProblem<F>, OdeError> {
+        let f = self
+            .f
+            .ok_or_else(|| OdeError::uninitialized("Required problem must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+
+        Ok(RodeProblem { f, y0, tspan })
+    }
+}
+
+impl<F> RodeProblem<F>
+where
+    F: Fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `RodeBuilder::default()`
+    pub fn builder() -> RodeBuilder<F> {
+        RodeBuilder::default()
+    }
+
+    /// Solve the problem with the explicit Euler method on the steps of `tspan`, the
+    /// noise is evaluated at the start of each step.
+    pub fn euler<R: Rng>(&self, path: &mut NoisePath<R>) -> OdeSolution<f64, DVector<f64>> {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return OdeSolution::default();
+        }
+
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        let mut y = self.y0.clone();
+        yout.push(y.clone());
+        for w in self.tspan.windows(2) {
+            let (t, h) = (w[0], w[1] - w[0]);
+            y += (self.f)(t, &y, &path.at(t)) * h;
+            yout.push(y.clone());
+        }
+
+        OdeSolution::new(self.tspan.clone(), yout)
+    }
+
+    /// Solve the problem with the Dormand–Prince method of order 5(4).
+    ///
+    /// Same as [`RodeProblem::rk_adapt`] with [`ButcherTableau::dopri5`].
+    pub fn dopri5<R: Rng>(
+        &self,
+        path: &mut NoisePath<R>,
+        opts: BasicOptions,
+    ) -> Result<OdeSolution<f64, DVector<f64>>, OdeError> {
+        self.rk_adapt(&ButcherTableau::dopri5(), path, opts)
+    }
+
+    /// Solve the problem with an embedded explicit Runge–Kutta method.
+    ///
+    /// The noise is evaluated at the stages of every step, including rejected ones, and
+    /// the values in between are drawn from the bridge of the process, so that every
+    /// step sees the same path. As the RHS is usually not smooth in t, the methods do not
+    /// attain their order and the step size is controlled by the roughness of the path.
+    /// The steps hit every value of `tspan`, which is assumed to increase.
+    pub fn rk_adapt<S: Dim, R: Rng, O: Into<AdaptiveOptions>>(
+        &self,
+        btab: &ButcherTableau<S>,
+        path: &mut NoisePath<R>,
+        opts: O,
+    ) -> Result<OdeSolution<f64, DVector<f64>>, OdeError>
+    where
+        DefaultAllocator: Allocator<f64, U1, S>
+            + Allocator<f64, S, U2>
+            + Allocator<f64, S, S>
+            + Allocator<f64, S>
+            + Allocator<f64, Dynamic>,
+    {
+        let pair = btab.embedded_pair()?;
+        if btab.is_implicit() {
+            return Err(OdeError::not_explicit());
+        }
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(OdeSolution::default());
+        }
+
+        let opts = opts.into();
+        let (reltol, abstol) = opts.tolerances(self.y0.len())?;
+        let (t0, tend) = (self.tspan[0], self.tspan[self.tspan.len() - 1]);
+        let minstep = opts
+            .minstep
+            .map_or_else(|| (tend - t0).abs() / 1e18, |step| step.0);
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| (tend - t0).abs() / 2.5, |step| step.0);
+        let mut dt = if opts.initstep.0 != 0. {
+            opts.initstep.0.abs()
+        } else {
+            // the trial step draws the noise at its end from the path
+            let path = RefCell::new(&mut *path);
+            let f = |t: f64, y: &DVector<f64>| (self.f)(t, y, &path.borrow_mut().at(t));
+            let order = btab.symbol.order().max();
+            hinit(f, &self.y0, t0, tend, order, &reltol, &abstol)?
+                .h
+                .abs()
+        }
+        .min(maxstep);
+
+        let order = btab.symbol.order().min() as f64;
+        let mut t = t0;
+        let mut y = self.y0.clone();
+        let mut solution = OdeSolution::new(vec![t], vec![y.clone()]);
+
+        let mut iters = 0;
+        for &stop in &self.tspan[1..] {
+            while t < stop {
+                if iters >= *opts.maxiters {
+                    return Ok(solution.with_retcode(ReturnCode::MaxIters));
+                }
+                iters += 1;
+                let h = dt.min(stop - t);
+                let mut ks: Vec<DVector<f64>> = Vec::with_capacity(btab.nstages());
+                for i in 0..btab.nstages() {
+                    let mut yi = y.clone();
+                    for (j, k) in ks.iter().enumerate() {
+                        yi += k * (btab.a[(i, j)] * h);
+                    }
+                    let ti = t + btab.c[i] * h;
+                    ks.push((self.f)(ti, &yi, &path.at(ti)));
+                }
+
+                let (ytrial, yerr) = pair.step(h, &y, &ks);
+                let err = (0..y.len())
+                    .map(|d| {
+                        let scale = abstol.at(d) + reltol.at(d) * y[d].abs().max(ytrial[d].abs());
+                        yerr[d].abs() / scale
+                    })
+                    .fold(0., f64::max);
+                let fac = (0.9 * err.powf(-1. / (order + 1.))).clamp(0.2, 5.);
+
+                if err <= 1. {
+                    t = if stop - (t + h) <= h * 1e-12 {
+                        stop
+                    } else {
+                        t + h
+                    };
+                    y = ytrial;
+                    if opts.points == Points::All || t == stop {
+                        solution.tout.push(t);
+                        solution.yout.push(y.clone());
+                    }
+                    dt = maxstep.min(h * fac);
+                } else if h * fac < minstep {
+                    // minimum step size reached
+                    return Ok(solution.with_retcode(ReturnCode::DtLessThanMin));
+                } else {
+                    // redo step with smaller dt
+                    dt = h * fac.min(1.);
+                }
+            }
+        }
+
+        Ok(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Reltol};
+    use crate::sde::noise::PathProcess;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    type Rhs = fn(f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>;
+
+    /// `y' = -y + X(t)`, a linear system forced by the noise
+    fn forced(tspan: Vec<f64>) -> RodeProblem<Rhs> {
+        RodeProblem::<Rhs>::builder()
+            .fun(|_t, y, x| x - y)
+            .init(DVector::from_row_slice(&[1.]))
+            .tspan(tspan)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn adaptive_matches_euler() {
+        let process = PathProcess::OrnsteinUhlenbeck {
+            theta: 1.,
+            mean: 0.,
+            sigma: 1.,
+        };
+        let path = || NoisePath::new(process, StdRng::seed_from_u64(5), 0., DVector::zeros(1));
+        let opts = BasicOptions {
+            reltol: Reltol::from(1e-6),
+            abstol: Abstol::from(1e-6),
+            ..Default::default()
+        };
+
+        // the same path is seen regardless of the steps taken
+        let mut shared = path();
+        let adaptive = forced(vec![0., 1.])
+            .dopri5(&mut shared, opts.clone())
+            .unwrap();
+        let euler = forced(itertools_num::linspace(0., 1., 100_001).collect()).euler(&mut shared);
+        let y = adaptive.yout.last().unwrap()[0];
+        assert_eq!(1., *adaptive.tout.last().unwrap());
+        assert!((y - euler.yout.last().unwrap()[0]).abs() < 1e-3);
+
+        // and reproduced by the seed
+        let again = forced(vec![0., 1.]).dopri5(&mut path(), opts).unwrap();
+        assert_eq!(y, again.yout.last().unwrap()[0]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod noise;
+pub mod problem;
+pub mod solution;
+pub mod sri;
@@ -0,0 +1,340 @@
+use na::{DMatrix, DVector};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// The interpretation of the stochastic integral `∫ g(t, y) dW`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpretation {
+    /// evaluates `g` at the left end of each interval
+    #[default]
+    Ito,
+    /// evaluates `g` at the midpoint of each interval, the chain rule of ordinary calculus
+    /// holds
+    Stratonovich,
+}
+
+impl std::str::FromStr for Interpretation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ito" => Ok(Interpretation::Ito),
+            "stratonovich" => Ok(Interpretation::Stratonovich),
+            _ => Err(format!("{} is not a valid Interpretation identifier", s)),
+        }
+    }
+}
+
+/// The structure of the `n × m` diffusion `g(t, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseType {
+    /// a single Wiener process drives all components, `m = 1`
+    Scalar,
+    /// every component is driven by its own Wiener process, `m = n` and `g` is diagonal
+    /// with `g_ii` only depending on `y_i`
+    Diagonal,
+    /// an arbitrary diffusion
+    NonDiagonal,
+    /// the diffusion `g(t)` does not depend on `y`
+    Additive,
+}
+
+impl NoiseType {
+    /// Whether the diffusion is applied componentwise, so that the noise reduces to the
+    /// vectors of [`NoiseType::diffusion_vector`] and [`NoiseType::increment_vector`].
+    pub fn is_componentwise(&self) -> bool {
+        matches!(self, NoiseType::Scalar | NoiseType::Diagonal)
+    }
+
+    /// The vector `v` of a componentwise diffusion with `g ΔW = v ∘ w`, the column of a
+    /// scalar and the diagonal of a diagonal diffusion.
+    pub fn diffusion_vector(&self, g: &DMatrix<f64>) -> DVector<f64> {
+        match self {
+            NoiseType::Scalar => g.column(0).into_owned(),
+            _ => g.diagonal(),
+        }
+    }
+
+    /// The vector `w` of length `n` of a componentwise diffusion with `g ΔW = v ∘ w`.
+    pub fn increment_vector(&self, dw: &DVector<f64>, n: usize) -> DVector<f64> {
+        match self {
+            NoiseType::Scalar => DVector::from_element(n, dw[0]),
+            _ => dw.clone(),
+        }
+    }
+}
+
+impl std::str::FromStr for NoiseType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scalar" => Ok(NoiseType::Scalar),
+            "diagonal" => Ok(NoiseType::Diagonal),
+            "non-diagonal" => Ok(NoiseType::NonDiagonal),
+            "additive" => Ok(NoiseType::Additive),
+            _ => Err(format!("{} is not a valid NoiseType identifier", s)),
+        }
+    }
+}
+
+/// The increments of a noise process over a step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Increment {
+    /// the size of the step
+    pub h: f64,
+    /// the increment `ΔW` of the Wiener process
+    pub dw: DVector<f64>,
+    /// the increment `ΔZ` of an independent Wiener process that determines the iterated
+    /// integral `I_{(1,0)} = h (ΔW + ΔZ / √3) / 2`
+    pub dz: DVector<f64>,
+}
+
+/// The source of the Wiener increments of the SDE steppers.
+pub trait NoiseProcess {
+    /// The increments of an `m` dimensional process over the next step of size at most
+    /// `h`, the step is shorter if the process already holds a shorter increment.
+    fn increment(&mut self, m: usize, h: f64) -> Increment;
+
+    /// Hand back the increments of a rejected step, the following increments are
+    /// conditioned on them so that the path does not depend on the rejections.
+    fn reject(&mut self, increment: Increment);
+}
+
+/// A Wiener process sampled from `rng`, a seeded `rng` reproduces the path.
+///
+/// Increments handed back by [`NoiseProcess::reject`] are split by the Brownian bridge.
+#[derive(Debug, Clone)]
+pub struct WienerProcess<R> {
+    rng: R,
+    /// the increments that are yet to be used, the last one is next
+    future: Vec<Increment>,
+}
+
+impl<R: Rng> WienerProcess<R> {
+    pub fn new(rng: R) -> Self {
+        Self {
+            rng,
+            future: Vec::new(),
+        }
+    }
+}
+
+impl<R: Rng> NoiseProcess for WienerProcess<R> {
+    fn increment(&mut self, m: usize, h: f64) -> Increment {
+        match self.future.pop() {
+            None => Increment {
+                h,
+                dw: wiener_increment(&mut self.rng, m, h),
+                dz: wiener_increment(&mut self.rng, m, h),
+            },
+            Some(next) if next.h <= h => next,
+            Some(next) => {
+                // sample the Brownian bridge at `h` and keep the remainder
+                let q = h / next.h;
+                let sd = ((next.h - h) * q).sqrt();
+                let dw = &next.dw * q + wiener_increment(&mut self.rng, m, 1.) * sd;
+                let dz = &next.dz * q + wiener_increment(&mut self.rng, m, 1.) * sd;
+                self.future.push(Increment {
+                    h: next.h - h,
+                    dw: next.dw - &dw,
+                    dz: next.dz - &dz,
+                });
+                Increment { h, dw, dz }
+            }
+        }
+    }
+
+    fn reject(&mut self, increment: Increment) {
+        self.future.push(increment);
+    }
+}
+
+/// The Gaussian Markov processes that can be sampled by a [`NoisePath`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathProcess {
+    /// the standard Wiener process
+    Wiener,
+    /// the Ornstein–Uhlenbeck process `dX = θ (μ - X) dt + σ dW`
+    OrnsteinUhlenbeck { theta: f64, mean: f64, sigma: f64 },
+}
+
+impl PathProcess {
+    /// The value the process reverts to.
+    fn mean(&self) -> f64 {
+        match self {
+            PathProcess::Wiener => 0.,
+            PathProcess::OrnsteinUhlenbeck { mean, .. } => *mean,
+        }
+    }
+
+    /// The decay `e` and the variance `v` of the transition of the deviation from the
+    /// mean, `x(t + dt) ~ N(e x(t), v)`.
+    fn transition(&self, dt: f64) -> (f64, f64) {
+        match self {
+            PathProcess::Wiener => (1., dt),
+            PathProcess::OrnsteinUhlenbeck { theta, sigma, .. } => {
+                let e = (-theta * dt).exp();
+                (e, sigma * sigma / (2. * theta) * (1. - e * e))
+            }
+        }
+    }
+}
+
+/// A time of a [`NoisePath`], ordered by [`f64::total_cmp`].
+#[derive(Debug, Clone, Copy)]
+struct PathTime(f64);
+
+impl PartialEq for PathTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PathTime {}
+
+impl PartialOrd for PathTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PathTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// A path of an `m` dimensional process with independent components that is sampled
+/// from `rng` wherever it is queried.
+///
+/// Values after the latest sampled time follow from the transition of the process,
+/// values between sampled times are drawn from the bridge of the process, so that the
+/// path is consistent with every value handed out before, including those at the stages
+/// of rejected steps. A seeded `rng` reproduces the path for the same queries.
+#[derive(Debug, Clone)]
+pub struct NoisePath<R> {
+    process: PathProcess,
+    rng: R,
+    /// the sampled values
+    values: BTreeMap<PathTime, DVector<f64>>,
+}
+
+impl<R: Rng> NoisePath<R> {
+    /// A path of `process` that starts with `x0` at `t0`.
+    pub fn new(process: PathProcess, rng: R, t0: f64, x0: DVector<f64>) -> Self {
+        let mut values = BTreeMap::new();
+        values.insert(PathTime(t0), x0);
+        Self {
+            process,
+            rng,
+            values,
+        }
+    }
+
+    /// An `m` dimensional Wiener process with `W(t0) = 0`.
+    pub fn wiener(rng: R, m: usize, t0: f64) -> Self {
+        Self::new(PathProcess::Wiener, rng, t0, DVector::zeros(m))
+    }
+
+    /// The dimension of the process.
+    pub fn dim(&self) -> usize {
+        self.values.values().next().map_or(0, |x| x.len())
+    }
+
+    /// The value of the path at `t`, which must not precede the start of the path.
+    pub fn at(&mut self, t: f64) -> DVector<f64> {
+        let key = PathTime(t);
+        if let Some(x) = self.values.get(&key) {
+            return x.clone();
+        }
+        let mu = self.process.mean();
+        let m = self.dim();
+        let (s, a) = self
+            .values
+            .range(..key)
+            .next_back()
+            .map(|(s, x)| (s.0, x.add_scalar(-mu)))
+            .expect("the path is queried before its start");
+        let (e1, v1) = self.process.transition(t - s);
+        let xi = wiener_increment(&mut self.rng, m, 1.);
+
+        let x = match self.values.range(key..).next() {
+            None => a * e1 + xi * v1.sqrt(),
+            Some((u, b)) => {
+                // condition the transition from `s` on the value at `u`
+                let (e2, v2) = self.process.transition(u.0 - t);
+                let precision = 1. / v1 + e2 * e2 / v2;
+                let mean = (a * (e1 / v1) + b.add_scalar(-mu) * (e2 / v2)) / precision;
+                mean + xi * precision.recip().sqrt()
+            }
+        }
+        .add_scalar(mu);
+        self.values.insert(key, x.clone());
+        x
+    }
+}
+
+/// The increment `ΔW ~ N(0, |h| I)` of an `m` dimensional Wiener process over a step of
+/// size `h`.
+pub fn wiener_increment<R: Rng>(rng: &mut R, m: usize, h: f64) -> DVector<f64> {
+    let sqrt_h = h.abs().sqrt();
+    DVector::from_iterator(
+        m,
+        (0..m).map(|_| rng.sample::<f64, _>(StandardNormal) * sqrt_h),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn bridge_keeps_path() {
+        let mut noise = WienerProcess::new(StdRng::seed_from_u64(3));
+        let whole = noise.increment(2, 1.);
+        noise.reject(whole.clone());
+        let first = noise.increment(2, 0.3);
+        let second = noise.increment(2, 1.);
+        assert_eq!(first.h, 0.3);
+        assert!((second.h - 0.7).abs() < 1e-15);
+        assert!((first.dw + second.dw - &whole.dw).amax() < 1e-14);
+        assert!((first.dz + second.dz - &whole.dz).amax() < 1e-14);
+    }
+
+    #[test]
+    fn path_marginals() {
+        // sampling out of order must not change the distribution of the path
+        let paths = 4000;
+        let (mut var_quarter, mut cov, mut var_ou) = (0., 0., 0.);
+        for seed in 0..paths {
+            let mut wiener = NoisePath::wiener(StdRng::seed_from_u64(seed), 1, 0.);
+            let w1 = wiener.at(1.)[0];
+            let w_half = wiener.at(0.5)[0];
+            let w_quarter = wiener.at(0.25)[0];
+            assert_eq!(w_half, wiener.at(0.5)[0]);
+            var_quarter += w_quarter * w_quarter;
+            cov += w_half * w1;
+
+            let process = PathProcess::OrnsteinUhlenbeck {
+                theta: 2.,
+                mean: 1.,
+                sigma: 0.5,
+            };
+            let mut ou =
+                NoisePath::new(process, StdRng::seed_from_u64(seed), 0., DVector::zeros(1));
+            ou.at(20.);
+            let x = ou.at(10.)[0] - 1.;
+            var_ou += x * x;
+        }
+        let paths = paths as f64;
+        assert!((var_quarter / paths - 0.25).abs() < 0.02);
+        assert!((cov / paths - 0.5).abs() < 0.04);
+        // stationary variance `σ² / 2θ`
+        assert!((var_ou / paths - 0.0625).abs() < 0.005);
+    }
+}
@@ -0,0 +1,729 @@
+use crate::error::OdeError;
+use crate::ode::options::{AdaptiveOptions, BasicOptions, Points, Tolerance};
+use crate::ode::problem::hinit;
+use crate::ode::solution::ReturnCode;
+use crate::sde::noise::{Increment, Interpretation, NoiseProcess, NoiseType};
+use crate::sde::solution::SdeSolution;
+use crate::sde::sri::SriCoeffs;
+use na::{DMatrix, DVector};
+
+/// The stochastic differential equation `dy = f(t, y) dt + g(t, y) dW` driven by an `m`
+/// dimensional Wiener process `W`, in the Itô or the Stratonovich interpretation.
+///
+/// F: the drift, a function of t and y(t) that returns a vector of length `n`.
+/// G: the diffusion, a function of t and y(t) that returns a `n × m` matrix.
+#[derive(Debug, Clone)]
+pub struct SdeProblem<F, G>
+where
+    F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+{
+    /// The drift `f(t, y)`.
+    drift: F,
+    /// The diffusion `g(t, y)`.
+    diffusion: G,
+    /// The dimension `m` of the Wiener process.
+    noise_dim: usize,
+    /// The structure of the diffusion.
+    noise: NoiseType,
+    /// The interpretation of the stochastic integral.
+    interpretation: Interpretation,
+    /// Initial value.
+    y0: DVector<f64>,
+    /// Sorted t values at which the solution (y) is requested
+    tspan: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SdeBuilder<F, G>
+where
+    F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+{
+    drift: Option<F>,
+    diffusion: Option<G>,
+    noise_dim: Option<usize>,
+    noise: Option<NoiseType>,
+    interpretation: Interpretation,
+    y0: Option<DVector<f64>>,
+    tspan: Option<Vec<f64>>,
+}
+
+impl<F, G> Default for SdeBuilder<F, G>
+where
+    F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+{
+    fn default() -> Self {
+        Self {
+            drift: None,
+            diffusion: None,
+            noise_dim: None,
+            noise: None,
+            interpretation: Interpretation::default(),
+            y0: None,
+            tspan: None,
+        }
+    }
+}
+
+impl<F, G> SdeBuilder<F, G>
+where
+    F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+{
+    /// set the drift of the problem
+    pub fn drift(mut self, drift: F) -> Self {
+        self.drift = Some(drift);
+        self
+    }
+
+    /// set the diffusion of the problem
+    pub fn diffusion(mut self, diffusion: G) -> Self {
+        self.diffusion = Some(diffusion);
+        self
+    }
+
+    /// set the dimension of the Wiener process, defaults to the number of columns of the
+    /// diffusion at the initial value
+    pub fn noise_dim(mut self, noise_dim: usize) -> Self {
+        self.noise_dim = Some(noise_dim);
+        self
+    }
+
+    /// set the structure of the diffusion, defaults to [`NoiseType::Scalar`] for a one
+    /// dimensional Wiener process and [`NoiseType::NonDiagonal`] otherwise
+    pub fn noise(mut self, noise: NoiseType) -> Self {
+        self.noise = Some(noise);
+        self
+    }
+
+    /// set the interpretation of the stochastic integral, defaults to
+    /// [`Interpretation::Ito`]
+    pub fn interpretation(mut self, interpretation: Interpretation) -> Self {
+        self.interpretation = interpretation;
+        self
+    }
+
+    /// set the initial starting point
+    pub fn init<T: Into<DVector<f64>>>(mut self, y0: T) -> Self {
+        self.y0 = Some(y0.into());
+        self
+    }
+
+    /// Set the time span for the problem.
+    pub fn tspan(mut self, tspan: Vec<f64>) -> Self {
+        self.tspan = Some(tspan);
+        self
+    }
+
+    /// Creates a new tspan with `n` items from `from` to `to`.
+    pub fn tspan_linspace(mut self, from: f64, to: f64, n: usize) -> Self {
+        self.tspan = Some(itertools_num::linspace(from, to, n).collect());
+        self
+    }
+
+    /// Creates a new [`SdeProblem`].
+    ///
+    /// Returns an error if a field is None or the diffusion does not match the initial
+    /// value, the noise dimension and the noise type.
+    pub fn build(self) -> Result<SdeProblem<F, G>, OdeError> {
+        let drift = self
+            .drift
+            .ok_or_else(|| OdeError::uninitialized("Drift must be initialized"))?;
+        let diffusion = self
+            .diffusion
+            .ok_or_else(|| OdeError::uninitialized("Diffusion must be initialized"))?;
+        let y0 = self
+            .y0
+            .ok_or_else(|| OdeError::uninitialized("Initial starting point must be initialized"))?;
+        let tspan = self
+            .tspan
+            .ok_or_else(|| OdeError::uninitialized("Time span must be initialized"))?;
+        let t0 = tspan.first().cloned().unwrap_or_default();
+        let shape = diffusion(t0, &y0).shape();
+        let noise_dim = self.noise_dim.unwrap_or(shape.1);
+        let noise = self.noise.unwrap_or(if noise_dim == 1 {
+            NoiseType::Scalar
+        } else {
+            NoiseType::NonDiagonal
+        });
+        let expected = match noise {
+            NoiseType::Scalar => (y0.len(), 1),
+            NoiseType::Diagonal => (y0.len(), y0.len()),
+            _ => (y0.len(), noise_dim),
+        };
+        if shape != expected || expected.1 != noise_dim {
+            return Err(OdeError::DimensionMismatch {
+                expected,
+                found: (shape.0, noise_dim),
+            });
+        }
+
+        Ok(SdeProblem {
+            drift,
+            diffusion,
+            noise_dim,
+            noise,
+            interpretation: self.interpretation,
+            y0,
+            tspan,
+        })
+    }
+}
+
+impl<F, G> SdeProblem<F, G>
+where
+    F: Fn(f64, &DVector<f64>) -> DVector<f64>,
+    G: Fn(f64, &DVector<f64>) -> DMatrix<f64>,
+{
+    /// convenience method to create a new builder
+    /// same as `SdeBuilder::default()`
+    pub fn builder() -> SdeBuilder<F, G> {
+        SdeBuilder::default()
+    }
+
+    /// The dimension of the Wiener process.
+    #[inline]
+    pub fn noise_dim(&self) -> usize {
+        self.noise_dim
+    }
+
+    /// The structure of the diffusion.
+    #[inline]
+    pub fn noise(&self) -> NoiseType {
+        self.noise
+    }
+
+    /// The interpretation of the stochastic integral.
+    #[inline]
+    pub fn interpretation(&self) -> Interpretation {
+        self.interpretation
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` using the Euler–Maruyama method
+    ///
+    /// ```latex
+    /// y_{n+1} = y_n + h f(t_n, y_n) + g(t_n, y_n) ΔW_n
+    /// ```
+    ///
+    /// for the Itô interpretation and the Euler–Heun method
+    ///
+    /// ```latex
+    /// ȳ = y_n + h f(t_n, y_n) + g(t_n, y_n) ΔW_n
+    /// y_{n+1} = y_n + h f(t_n, y_n) + (g(t_n, y_n) + g(t_n, ȳ)) ΔW_n / 2
+    /// ```
+    ///
+    /// for the Stratonovich interpretation, both of strong order 1/2 and weak order 1 for
+    /// any type of noise.
+    pub fn euler_maruyama<N: NoiseProcess>(&self, noise: &mut N) -> SdeSolution {
+        let heun = self.interpretation == Interpretation::Stratonovich
+            && self.noise != NoiseType::Additive;
+        self.fixed_steps(noise, |t, h, y, dw| {
+            let f = (self.drift)(t, y) * h;
+            let g = (self.diffusion)(t, y);
+            if heun {
+                let support = y + &f + &g * dw;
+                y + f + (g + (self.diffusion)(t, &support)) * dw * 0.5
+            } else {
+                y + f + g * dw
+            }
+        })
+    }
+
+    /// Solve the problem at the fixed steps of `tspan` using the derivative free Milstein
+    /// method
+    ///
+    /// ```latex
+    /// ȳ = y_n + h f(t_n, y_n) + √h g(t_n, y_n)
+    /// y_{n+1} = y_n + h f(t_n, y_n) + g(t_n, y_n) ΔW_n
+    ///     + (g(t_n, ȳ) - g(t_n, y_n)) (ΔW_n^2 - h) / (2 √h)
+    /// ```
+    ///
+    /// of strong order 1. The Stratonovich interpretation drops the `- h` and takes the
+    /// central difference `(g(t_n, y_n + √h g) - g(t_n, y_n - √h g)) ΔW_n^2 / (4 √h)` as
+    /// correction. Additive noise has no correction term.
+    ///
+    /// Returns an error for non-diagonal noise, which requires the Lévy areas.
+    pub fn milstein<N: NoiseProcess>(&self, noise: &mut N) -> Result<SdeSolution, OdeError> {
+        if self.noise == NoiseType::NonDiagonal {
+            return Err(OdeError::UnsupportedNoise {
+                method: "milstein",
+                noise: self.noise,
+            });
+        }
+        let ito = self.interpretation == Interpretation::Ito;
+        Ok(self.fixed_steps(noise, |t, h, y, dw| {
+            let f = (self.drift)(t, y) * h;
+            let g = (self.diffusion)(t, y);
+            if self.noise == NoiseType::Additive {
+                return y + f + g * dw;
+            }
+            let sqrt_h = h.abs().sqrt();
+            let g = self.noise.diffusion_vector(&g);
+            let dw = self.noise.increment_vector(dw, y.len());
+            let diffusion = |y: &DVector<f64>| self.noise.diffusion_vector(&(self.diffusion)(t, y));
+            let (dg, dw2) = if ito {
+                let dg = diffusion(&(y + &f + &g * sqrt_h)) - &g;
+                (dg, dw.map(|dw| (dw * dw - h) / (2. * sqrt_h)))
+            } else {
+                // `ΔW^2` does not vanish in the mean, which requires a central difference
+                let dg = diffusion(&(y + &g * sqrt_h)) - diffusion(&(y - &g * sqrt_h));
+                (dg, dw.map(|dw| dw * dw / (4. * sqrt_h)))
+            };
+            y + f + g.component_mul(&dw) + dg.component_mul(&dw2)
+        }))
+    }
+
+    /// Solve the problem adaptively using the stochastic Runge–Kutta method SRIW1 of
+    /// strong order 1.5 and weak order 2.
+    ///
+    /// Returns an error unless the noise is scalar or diagonal.
+    pub fn sriw1<N: NoiseProcess>(
+        &self,
+        noise: &mut N,
+        opts: BasicOptions,
+    ) -> Result<SdeSolution, OdeError> {
+        self.sri(&SriCoeffs::sriw1(), noise, opts)
+    }
+
+    /// Solve the problem adaptively using a stochastic Runge–Kutta method of the SRI class.
+    ///
+    /// The local error is estimated by the terms of order 1.5, `|h (Σ_i α_i f_i - f_1)| / 6
+    /// + |Σ_i (β^3_i I_{(1,0)}/h + β^4_i I_{(1,1,1)}/h) g_i|`. The Wiener increments of
+    /// rejected steps are handed back to `noise`, so the sampled path does not depend on
+    /// the rejections. Steps hit every value of `tspan`, which is assumed to increase.
+    ///
+    /// SRI methods are Itô methods, the Stratonovich interpretation is solved through the
+    /// equivalent Itô drift of [`SdeProblem::ito_drift`].
+    ///
+    /// Returns an error unless the noise is scalar or diagonal.
+    pub fn sri<N: NoiseProcess>(
+        &self,
+        coeffs: &SriCoeffs,
+        noise: &mut N,
+        opts: BasicOptions,
+    ) -> Result<SdeSolution, OdeError> {
+        if !self.noise.is_componentwise() {
+            return Err(OdeError::UnsupportedNoise {
+                method: "sri",
+                noise: self.noise,
+            });
+        }
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return Ok(SdeSolution::default());
+        }
+
+        let opts = AdaptiveOptions::from(opts);
+        let (reltol, abstol) = opts.tolerances(self.y0.len())?;
+        let (t0, tend) = (self.tspan[0], self.tspan[self.tspan.len() - 1]);
+        let minstep = opts
+            .minstep
+            .map_or_else(|| (tend - t0).abs() / 1e18, |step| step.0);
+        let maxstep = opts
+            .maxstep
+            .map_or_else(|| (tend - t0).abs() / 2.5, |step| step.0);
+        let mut dt = if opts.initstep.0 != 0. {
+            opts.initstep.0.abs()
+        } else {
+            // the error is controlled like that of a method of order 1
+            let f = |t: f64, y: &DVector<f64>| self.ito_drift(t, y);
+            hinit(f, &self.y0, t0, tend, 1, &reltol, &abstol)?.h.abs()
+        }
+        .min(maxstep);
+
+        let mut t = t0;
+        let mut y = self.y0.clone();
+        let mut w = DVector::zeros(self.noise_dim);
+        let mut solution = SdeSolution {
+            tout: vec![t],
+            yout: vec![y.clone()],
+            wout: vec![w.clone()],
+            retcode: ReturnCode::Success,
+        };
+
+        let mut iters = 0;
+        for &tnext in &self.tspan[1..] {
+            while t < tnext {
+                if iters >= *opts.maxiters {
+                    // maximum number of steps reached
+                    solution.retcode = ReturnCode::MaxIters;
+                    return Ok(solution);
+                }
+                iters += 1;
+                let increment = noise.increment(self.noise_dim, dt.min(tnext - t));
+                let h = increment.h;
+                let (ytrial, err) = self.sri_step(coeffs, t, &y, &increment, &abstol, &reltol);
+                let fac = (0.9 * err.powf(-0.5)).clamp(0.2, 2.);
+
+                if err <= 1. {
+                    t = if tnext - (t + h) < h * 1e-12 {
+                        tnext
+                    } else {
+                        t + h
+                    };
+                    y = ytrial;
+                    w += &increment.dw;
+                    if opts.points == Points::All || t == tnext {
+                        solution.tout.push(t);
+                        solution.yout.push(y.clone());
+                        solution.wout.push(w.clone());
+                    }
+                    dt = maxstep.min(h * fac);
+                } else if h * fac < minstep {
+                    // minimum step size reached
+                    solution.retcode = ReturnCode::DtLessThanMin;
+                    return Ok(solution);
+                } else {
+                    // redo step with smaller dt on the same path
+                    noise.reject(increment);
+                    dt = h * fac.min(1.);
+                }
+            }
+        }
+
+        Ok(solution)
+    }
+
+    /// The drift of the equivalent Itô equation, `f + 1/2 Σ_k (∂g_k/∂y) g_k` with the
+    /// columns `g_k` of the diffusion for the Stratonovich interpretation and `f`
+    /// otherwise.
+    ///
+    /// The directional derivatives are approximated by finite differences.
+    pub fn ito_drift(&self, t: f64, y: &DVector<f64>) -> DVector<f64> {
+        let mut f = (self.drift)(t, y);
+        if self.interpretation == Interpretation::Ito || self.noise == NoiseType::Additive {
+            return f;
+        }
+        let g = (self.diffusion)(t, y);
+        let scale = f64::EPSILON.sqrt() * (1. + y.amax());
+        for (k, gk) in g.column_iter().enumerate() {
+            let norm = gk.amax();
+            if norm == 0. {
+                continue;
+            }
+            let eps = scale / norm;
+            let dg = ((self.diffusion)(t, &(y + gk * eps)).column(k) - gk) / eps;
+            f += dg * 0.5;
+        }
+        f
+    }
+
+    /// A step of a SRI method, returns the new value and the scaled error estimate.
+    fn sri_step(
+        &self,
+        coeffs: &SriCoeffs,
+        t: f64,
+        y: &DVector<f64>,
+        increment: &Increment,
+        abstol: &Tolerance,
+        reltol: &Tolerance,
+    ) -> (DVector<f64>, f64) {
+        let h = increment.h;
+        let sqrt_h = h.sqrt();
+        let dw = self.noise.increment_vector(&increment.dw, y.len());
+        let dz = self.noise.increment_vector(&increment.dz, y.len());
+        // the iterated integrals scaled as in the weights
+        let i11 = dw.map(|dw| (dw * dw - h) / (2. * sqrt_h));
+        let i10 = (&dw + dz / 3f64.sqrt()) * 0.5;
+        let i111 = dw.map(|dw| (dw * dw * dw - 3. * h * dw) / (6. * h));
+
+        let s = coeffs.nstages();
+        let mut fs: Vec<DVector<f64>> = Vec::with_capacity(s);
+        let mut gs: Vec<DVector<f64>> = Vec::with_capacity(s);
+        for i in 0..s {
+            let mut h0 = y.clone();
+            let mut h1 = y.clone();
+            for j in 0..i {
+                h0 += &fs[j] * (coeffs.a0[(i, j)] * h)
+                    + gs[j].component_mul(&i10) * coeffs.b0[(i, j)];
+                h1 += &fs[j] * (coeffs.a1[(i, j)] * h) + &gs[j] * (coeffs.b1[(i, j)] * sqrt_h);
+            }
+            fs.push(self.ito_drift(t + coeffs.c0[i] * h, &h0));
+            let g = (self.diffusion)(t + coeffs.c1[i] * h, &h1);
+            gs.push(self.noise.diffusion_vector(&g));
+        }
+
+        let mut ynew = y.clone();
+        let mut deterministic = -&fs[0] * h;
+        let mut stochastic = DVector::zeros(y.len());
+        for i in 0..s {
+            ynew += &fs[i] * (coeffs.alpha[i] * h);
+            deterministic += &fs[i] * (coeffs.alpha[i] * h);
+            let high = (&i10 * coeffs.beta3[i] + &i111 * coeffs.beta4[i]).component_mul(&gs[i]);
+            ynew += (&dw * coeffs.beta1[i] + &i11 * coeffs.beta2[i]).component_mul(&gs[i]) + &high;
+            stochastic += high;
+        }
+
+        let err = (0..y.len())
+            .map(|k| {
+                let e = deterministic[k].abs() / 6. + stochastic[k].abs();
+                e / (abstol.at(k) + reltol.at(k) * y[k].abs().max(ynew[k].abs()))
+            })
+            .fold(0., f64::max);
+        (ynew, err)
+    }
+
+    /// Steps through `tspan` with the increments drawn from `noise`.
+    fn fixed_steps<N, S>(&self, noise: &mut N, step: S) -> SdeSolution
+    where
+        N: NoiseProcess,
+        S: Fn(f64, f64, &DVector<f64>, &DVector<f64>) -> DVector<f64>,
+    {
+        if self.tspan.is_empty() {
+            // nothing to solve
+            return SdeSolution::default();
+        }
+
+        let mut y = self.y0.clone();
+        let mut w = DVector::zeros(self.noise_dim);
+        let mut yout = Vec::with_capacity(self.tspan.len());
+        let mut wout = Vec::with_capacity(self.tspan.len());
+        yout.push(y.clone());
+        wout.push(w.clone());
+
+        for win in self.tspan.windows(2) {
+            let mut t = win[0];
+            // the noise may hold shorter increments than the step
+            while t < win[1] {
+                let remaining = win[1] - t;
+                let increment = noise.increment(self.noise_dim, remaining);
+                y = step(t, increment.h, &y, &increment.dw);
+                w += increment.dw;
+                t = if increment.h < remaining {
+                    t + increment.h
+                } else {
+                    win[1]
+                };
+            }
+            yout.push(y.clone());
+            wout.push(w.clone());
+        }
+
+        SdeSolution {
+            tout: self.tspan.clone(),
+            yout,
+            wout,
+            retcode: ReturnCode::Success,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::options::{Abstol, Initstep, Maxstep, Minstep, Reltol};
+    use crate::sde::noise::WienerProcess;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const MU: f64 = 0.5;
+    const SIGMA: f64 = 1.;
+
+    type Gbm =
+        SdeProblem<fn(f64, &DVector<f64>) -> DVector<f64>, fn(f64, &DVector<f64>) -> DMatrix<f64>>;
+
+    /// geometric brownian motion `dy = μ y dt + σ y dW`
+    fn gbm(steps: usize, interpretation: Interpretation) -> Gbm {
+        Gbm::builder()
+            .drift(|_t, y| y * MU)
+            .diffusion(|_t, y| DMatrix::from_element(1, 1, SIGMA * y[0]))
+            .interpretation(interpretation)
+            .init(DVector::from_element(1, 1.))
+            .tspan_linspace(0., 1., steps + 1)
+            .build()
+            .unwrap()
+    }
+
+    fn noise(seed: u64) -> WienerProcess<StdRng> {
+        WienerProcess::new(StdRng::seed_from_u64(seed))
+    }
+
+    /// The mean strong error `E|y(1) - X(1)|` over `paths` solutions, with the exact
+    /// solution `X(1) = exp(μ - σ^2/2 + σ W(1))` of the Itô and `X(1) = exp(μ + σ W(1))`
+    /// of the Stratonovich equation on the sampled path.
+    fn strong_error<S: FnMut() -> SdeSolution>(
+        paths: usize,
+        interpretation: Interpretation,
+        mut solve: S,
+    ) -> f64 {
+        let drift = match interpretation {
+            Interpretation::Ito => MU - SIGMA * SIGMA / 2.,
+            Interpretation::Stratonovich => MU,
+        };
+        (0..paths)
+            .map(|_| {
+                let solution = solve();
+                let (y, w) = (solution.yout.last().unwrap(), solution.wout.last().unwrap());
+                (y[0] - (drift + SIGMA * w[0]).exp()).abs()
+            })
+            .sum::<f64>()
+            / paths as f64
+    }
+
+    /// options that force fixed steps of size `h`
+    fn fixed(h: f64) -> BasicOptions {
+        BasicOptions {
+            initstep: Initstep(h),
+            maxstep: Some(Maxstep(h)),
+            reltol: Reltol::from(1e9),
+            abstol: Abstol::from(1e9),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn euler_maruyama_reproducible() {
+        let problem = gbm(100, Interpretation::Ito);
+        let first = problem.euler_maruyama(&mut noise(42));
+        let second = problem.euler_maruyama(&mut noise(42));
+        assert_eq!(first.yout, second.yout);
+    }
+
+    #[test]
+    fn euler_maruyama_weak() {
+        // `E[y(1)] = e^μ`, the weak error of the mean is `O(h)`
+        let problem = gbm(100, Interpretation::Ito);
+        let mut noise = noise(7);
+        let paths = 5_000;
+        let mean = (0..paths)
+            .map(|_| problem.euler_maruyama(&mut noise).yout.last().unwrap()[0])
+            .sum::<f64>()
+            / paths as f64;
+        assert!((mean - MU.exp()).abs() < 0.1);
+    }
+
+    #[test]
+    fn milstein_strong() {
+        let mut noise = noise(1);
+        let mut error = |steps| {
+            let problem = gbm(steps, Interpretation::Ito);
+            strong_error(1000, Interpretation::Ito, || {
+                problem.milstein(&mut noise).unwrap()
+            })
+        };
+        // strong order 1
+        assert!(error(16) / error(64) > 3.);
+    }
+
+    #[test]
+    fn sriw1_strong() {
+        let mut noise = noise(1);
+        let problem = gbm(1, Interpretation::Ito);
+        let mut error = |steps| {
+            let opts = fixed(1. / steps as f64);
+            strong_error(1000, Interpretation::Ito, || {
+                problem.sriw1(&mut noise, opts.clone()).unwrap()
+            })
+        };
+        // strong order 1.5
+        assert!(error(8) / error(32) > 6.);
+    }
+
+    #[test]
+    fn sriw1_adaptive() {
+        let mut noise = noise(2);
+        let problem = gbm(4, Interpretation::Ito);
+        let mut error = |tol| {
+            let opts = BasicOptions {
+                reltol: Reltol::from(tol),
+                abstol: Abstol::from(tol),
+                ..Default::default()
+            };
+            strong_error(100, Interpretation::Ito, || {
+                problem.sriw1(&mut noise, opts.clone()).unwrap()
+            })
+        };
+        let (coarse, fine) = (error(1e-2), error(1e-4));
+        assert!(fine < coarse / 10.);
+        assert!(fine < 1e-3);
+
+        // the tolerance can't be met with steps of at least `0.1`
+        let opts = BasicOptions {
+            reltol: Reltol::from(1e-8),
+            abstol: Abstol::from(1e-8),
+            minstep: Some(Minstep(0.1)),
+            ..Default::default()
+        };
+        let solution = problem.sriw1(&mut noise, opts).unwrap();
+        assert_eq!(ReturnCode::DtLessThanMin, solution.retcode);
+        assert!(*solution.tout.last().unwrap() < 1.);
+    }
+
+    #[test]
+    fn sriw1_initial_step() {
+        let problem = gbm(1, Interpretation::Ito);
+        let first_step = |tol| {
+            let opts = BasicOptions {
+                reltol: Reltol::from(tol),
+                abstol: Abstol::from(tol),
+                ..Default::default()
+            };
+            let solution = problem.sriw1(&mut noise(5), opts).unwrap();
+            solution.tout[1] - solution.tout[0]
+        };
+        // the initial step is estimated from the drift, `h ~ tol^(1/2)`
+        let (coarse, fine) = (first_step(1e-3), first_step(1e-7));
+        assert!(coarse > 10. * fine);
+        assert!(coarse < 1.);
+    }
+
+    #[test]
+    fn stratonovich_strong() {
+        let strat = Interpretation::Stratonovich;
+        let mut noise = noise(3);
+        let mut heun = |steps| {
+            let problem = gbm(steps, strat);
+            strong_error(1000, strat, || problem.euler_maruyama(&mut noise))
+        };
+        // strong order 1 for scalar noise
+        assert!(heun(16) / heun(64) > 3.);
+        let mut milstein = |steps| {
+            let problem = gbm(steps, strat);
+            strong_error(1000, strat, || problem.milstein(&mut noise).unwrap())
+        };
+        assert!(milstein(16) / milstein(64) > 3.);
+        let problem = gbm(1, strat);
+        let mut sriw1 = |steps| {
+            let opts = fixed(1. / steps as f64);
+            strong_error(1000, strat, || {
+                problem.sriw1(&mut noise, opts.clone()).unwrap()
+            })
+        };
+        assert!(sriw1(8) / sriw1(32) > 6.);
+    }
+
+    #[test]
+    fn noise_types() {
+        // two geometric brownian motions driven by the same scalar noise
+        let problem = SdeProblem::builder()
+            .drift(|_t, y: &DVector<f64>| y * MU)
+            .diffusion(|_t, y: &DVector<f64>| {
+                DMatrix::from_column_slice(2, 1, &[SIGMA * y[0], 2. * SIGMA * y[1]])
+            })
+            .init(DVector::from_element(2, 1.))
+            .tspan_linspace(0., 1., 11)
+            .build()
+            .unwrap();
+        assert_eq!(NoiseType::Scalar, problem.noise());
+        let solution = problem.sriw1(&mut noise(4), fixed(1. / 256.)).unwrap();
+        let w = solution.wout.last().unwrap()[0];
+        let y = solution.yout.last().unwrap();
+        assert!((y[0] - (MU - SIGMA * SIGMA / 2. + SIGMA * w).exp()).abs() < 1e-3);
+        assert!((y[1] - (MU - 2. * SIGMA * SIGMA + 2. * SIGMA * w).exp()).abs() < 1e-3);
+
+        // a rotation of the noise needs the Lévy areas
+        let problem = SdeProblem::builder()
+            .drift(|_t, y: &DVector<f64>| y * MU)
+            .diffusion(|_t, y: &DVector<f64>| DMatrix::from_row_slice(2, 2, &[0., -y[1], y[0], 0.]))
+            .init(DVector::from_element(2, 1.))
+            .tspan_linspace(0., 1., 11)
+            .build()
+            .unwrap();
+        assert_eq!(NoiseType::NonDiagonal, problem.noise());
+        assert!(problem.milstein(&mut noise(4)).is_err());
+        assert!(problem.sriw1(&mut noise(4), fixed(0.1)).is_err());
+        assert_eq!(11, problem.euler_maruyama(&mut noise(4)).yout.len());
+    }
+}
@@ -0,0 +1,24 @@
+use crate::ode::solution::ReturnCode;
+use na::DVector;
+
+/// The solution of a stochastic differential equation together with the sampled Wiener
+/// path.
+#[derive(Debug, Clone, Default)]
+pub struct SdeSolution {
+    /// Vector of points at which solutions were obtained
+    pub tout: Vec<f64>,
+    /// solutions at times `tout`, stored as a vector `yout`
+    pub yout: Vec<DVector<f64>>,
+    /// the Wiener process `W(t) - W(t_0)` at times `tout`
+    pub wout: Vec<DVector<f64>>,
+    /// How the integration ended
+    pub retcode: ReturnCode,
+}
+
+impl SdeSolution {
+    /// pair each timestep with the corresponding output
+    #[inline]
+    pub fn zipped(self) -> Vec<(f64, DVector<f64>)> {
+        self.tout.into_iter().zip(self.yout).collect()
+    }
+}
@@ -0,0 +1,91 @@
+use na::DMatrix;
+
+/// The coefficients of a stochastic Runge–Kutta method of Rößler's SRI class for Itô
+/// SDEs with diagonal noise, c.f. Rößler, "Runge–Kutta methods for the strong
+/// approximation of solutions of stochastic differential equations", 2010.
+///
+/// ```latex
+/// H^0_i = y + h Σ_j A^0_ij f(t + c^0_j h, H^0_j) + I_{(1,0)}/h Σ_j B^0_ij g(t + c^1_j h, H^1_j)
+/// H^1_i = y + h Σ_j A^1_ij f(t + c^0_j h, H^0_j) + √h Σ_j B^1_ij g(t + c^1_j h, H^1_j)
+/// y_1 = y + h Σ_i α_i f(t + c^0_i h, H^0_i)
+///     + Σ_i (β^1_i I_{(1)} + β^2_i I_{(1,1)}/√h + β^3_i I_{(1,0)}/h + β^4_i I_{(1,1,1)}/h)
+///       g(t + c^1_i h, H^1_i)
+/// ```
+#[derive(Clone, Debug)]
+pub struct SriCoeffs {
+    /// drift matrix of the drift stages
+    pub a0: DMatrix<f64>,
+    /// drift matrix of the diffusion stages
+    pub a1: DMatrix<f64>,
+    /// diffusion matrix of the drift stages
+    pub b0: DMatrix<f64>,
+    /// diffusion matrix of the diffusion stages
+    pub b1: DMatrix<f64>,
+    /// nodes of the drift stages
+    pub c0: Vec<f64>,
+    /// nodes of the diffusion stages
+    pub c1: Vec<f64>,
+    /// weights of the drift
+    pub alpha: Vec<f64>,
+    /// weights of `I_{(1)}`
+    pub beta1: Vec<f64>,
+    /// weights of `I_{(1,1)}/√h`
+    pub beta2: Vec<f64>,
+    /// weights of `I_{(1,0)}/h`
+    pub beta3: Vec<f64>,
+    /// weights of `I_{(1,1,1)}/h`
+    pub beta4: Vec<f64>,
+}
+
+impl SriCoeffs {
+    /// SRIW1, of strong order 1.5 and weak order 2.
+    pub fn sriw1() -> Self {
+        #[rustfmt::skip]
+        let a0 = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            0.75, 0., 0., 0.,
+            0., 0., 0., 0.,
+            0., 0., 0., 0.,
+        ]);
+        #[rustfmt::skip]
+        let a1 = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            0.25, 0., 0., 0.,
+            1., 0., 0., 0.,
+            0.25, 0., 0., 0.,
+        ]);
+        #[rustfmt::skip]
+        let b0 = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            1.5, 0., 0., 0.,
+            0., 0., 0., 0.,
+            0., 0., 0., 0.,
+        ]);
+        #[rustfmt::skip]
+        let b1 = DMatrix::from_row_slice(4, 4, &[
+            0., 0., 0., 0.,
+            0.5, 0., 0., 0.,
+            -1., 0., 0., 0.,
+            -5., 3., 0.5, 0.,
+        ]);
+        Self {
+            a0,
+            a1,
+            b0,
+            b1,
+            c0: vec![0., 0.75, 0., 0.],
+            c1: vec![0., 0.25, 1., 0.25],
+            alpha: vec![1. / 3., 2. / 3., 0., 0.],
+            beta1: vec![-1., 4. / 3., 2. / 3., 0.],
+            beta2: vec![-1., 4. / 3., -1. / 3., 0.],
+            beta3: vec![2., -4. / 3., -2. / 3., 0.],
+            beta4: vec![-2., 5. / 3., -2. / 3., 1.],
+        }
+    }
+
+    /// the number of stages
+    #[inline]
+    pub fn nstages(&self) -> usize {
+        self.alpha.len()
+    }
+}